@@ -0,0 +1,219 @@
+//! Crediting the Wikipedia pages the output's text was taken from, with the revision of each that
+//! was used, as CC BY-SA asks of anyone reusing the text. Written as `attribution.json` for
+//! reusers of the data, and as `attribution.html` for people reading the website.
+use std::{collections::BTreeMap, fmt::Write as _};
+
+use serde::Serialize;
+
+use crate::{export::escape_xml, types::PageName};
+
+/// The license Wikipedia's text is available under.
+const LICENSE: &str = "CC BY-SA 4.0";
+const LICENSE_URL: &str = "https://creativecommons.org/licenses/by-sa/4.0/";
+
+/// What the license asks of anyone reusing the text.
+const REQUIREMENTS: &str = "Genre and artist descriptions are taken from the Wikipedia pages \
+    listed here, at the revisions listed, and are shortened from the originals. To reuse them, \
+    credit their authors (a link to each page's history, or to this list, is enough), say that \
+    they were changed, link to the license, and share anything made from them under the same \
+    license.";
+
+/// The pages the output's text was taken from.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Attribution {
+    /// The Wikipedia the pages are from.
+    pub source: String,
+    /// The date of the dump the pages were read from.
+    pub dump_date: String,
+    /// The license of the pages' text.
+    pub license: String,
+    /// The URL of the license.
+    pub license_url: String,
+    /// What the license asks of anyone reusing the text.
+    pub requirements: String,
+    /// The genres' pages, by title.
+    pub genres: Vec<AttributedPage>,
+    /// The artists' pages, by title.
+    pub artists: Vec<AttributedPage>,
+}
+
+/// A page that text was taken from.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct AttributedPage {
+    /// The title of the page.
+    pub title: String,
+    /// The URL of the page.
+    pub url: String,
+    /// The ID of the revision the text was taken from, if it was recorded when the dump was
+    /// extracted.
+    pub revision_id: Option<u64>,
+    /// The URL of that revision.
+    pub permalink: Option<String>,
+    /// The URL of the page's history, which lists its authors.
+    pub history_url: String,
+    /// When that revision was made.
+    pub last_revision_date: jiff::Timestamp,
+}
+
+/// A page's name, the revision its text was taken from, and when that revision was made.
+pub type PageRevision<'a> = (&'a PageName, Option<u64>, jiff::Timestamp);
+
+impl Attribution {
+    /// Credit the pages of `genres` and `artists` on the Wikipedia at `wikipedia_domain`.
+    /// Sections of the same page are credited once, as the page.
+    pub fn new<'a>(
+        wikipedia_domain: &str,
+        dump_date: &str,
+        genres: impl IntoIterator<Item = PageRevision<'a>>,
+        artists: impl IntoIterator<Item = PageRevision<'a>>,
+    ) -> Self {
+        let pages = |pages: &mut dyn Iterator<Item = PageRevision<'a>>| {
+            let pages: BTreeMap<&str, AttributedPage> = pages
+                .map(|(page, revision_id, last_revision_date)| {
+                    let linksafe = page.name.replace(' ', "_");
+                    (
+                        page.name.as_str(),
+                        AttributedPage {
+                            title: page.name.clone(),
+                            url: format!("https://{wikipedia_domain}/wiki/{linksafe}"),
+                            revision_id,
+                            permalink: revision_id.map(|id| {
+                                format!("https://{wikipedia_domain}/w/index.php?oldid={id}")
+                            }),
+                            history_url: format!(
+                                "https://{wikipedia_domain}/wiki/Special:History/{linksafe}"
+                            ),
+                            last_revision_date,
+                        },
+                    )
+                })
+                .collect();
+            pages.into_values().collect()
+        };
+        Self {
+            source: format!("https://{wikipedia_domain}/"),
+            dump_date: dump_date.to_string(),
+            license: LICENSE.to_string(),
+            license_url: LICENSE_URL.to_string(),
+            requirements: REQUIREMENTS.to_string(),
+            genres: pages(&mut genres.into_iter()),
+            artists: pages(&mut artists.into_iter()),
+        }
+    }
+
+    /// The attribution as a page for the website.
+    pub fn html(&self) -> String {
+        let mut out = String::new();
+        writeln!(
+            out,
+            r#"<!doctype html>
+<html lang="en">
+  <head>
+    <meta charset="UTF-8" />
+    <meta name="viewport" content="width=device-width, initial-scale=1.0" />
+    <title>Attribution – genres in space</title>
+    <style>
+      body {{ background: #04060f; color: #e9e3d3; font-family: serif; max-width: 60rem; margin: 2rem auto; padding: 0 1rem; }}
+      a {{ color: #d9c08a; }}
+      li {{ color: #a6b2c9; }}
+    </style>
+  </head>
+  <body>
+    <h1>Attribution</h1>
+    <p>{requirements}</p>
+    <p>
+      The pages were read from the {dump_date} dump of <a href="{source}">{source}</a>. Their
+      text is available under the <a href="{license_url}">{license}</a> license.
+    </p>"#,
+            requirements = escape_xml(&self.requirements),
+            dump_date = escape_xml(&self.dump_date),
+            source = escape_xml(&self.source),
+            license_url = escape_xml(&self.license_url),
+            license = escape_xml(&self.license),
+        )
+        .unwrap();
+        for (heading, pages) in [("Genres", &self.genres), ("Artists", &self.artists)] {
+            if pages.is_empty() {
+                continue;
+            }
+            writeln!(out, "    <h2>{heading}</h2>\n    <ul>").unwrap();
+            for page in pages {
+                let revision = match (&page.permalink, page.revision_id) {
+                    (Some(permalink), Some(id)) => {
+                        format!(r#"<a href="{}">revision {id}</a>"#, escape_xml(permalink))
+                    }
+                    _ => "revision".to_string(),
+                };
+                writeln!(
+                    out,
+                    r#"      <li><a href="{}">{}</a>, {revision} of {} (<a href="{}">authors</a>)</li>"#,
+                    escape_xml(&page.url),
+                    escape_xml(&page.title),
+                    page.last_revision_date
+                        .to_zoned(jiff::tz::TimeZone::UTC)
+                        .date(),
+                    escape_xml(&page.history_url),
+                )
+                .unwrap();
+            }
+            writeln!(out, "    </ul>").unwrap();
+        }
+        out.push_str("  </body>\n</html>\n");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn credits_each_page_once_with_its_revision() {
+        let timestamp: jiff::Timestamp = "2026-01-02T03:04:05Z".parse().unwrap();
+        let house = PageName::new("House music", None);
+        let deep_house = PageName::new("House music", Some("Deep house".to_string()));
+        let acid = PageName::new("Acid & bass", None);
+        let artist = PageName::new("Frankie Knuckles", None);
+
+        let attribution = Attribution::new(
+            "en.wikipedia.org",
+            "2026-02-01",
+            [
+                (&house, Some(123), timestamp),
+                (&deep_house, Some(123), timestamp),
+                (&acid, None, timestamp),
+            ],
+            [(&artist, Some(456), timestamp)],
+        );
+
+        assert_eq!(
+            attribution
+                .genres
+                .iter()
+                .map(|page| page.title.as_str())
+                .collect::<Vec<_>>(),
+            ["Acid & bass", "House music"]
+        );
+        assert_eq!(
+            attribution.genres[1],
+            AttributedPage {
+                title: "House music".to_string(),
+                url: "https://en.wikipedia.org/wiki/House_music".to_string(),
+                revision_id: Some(123),
+                permalink: Some("https://en.wikipedia.org/w/index.php?oldid=123".to_string()),
+                history_url: "https://en.wikipedia.org/wiki/Special:History/House_music"
+                    .to_string(),
+                last_revision_date: timestamp,
+            }
+        );
+        assert_eq!(attribution.genres[0].permalink, None);
+
+        let html = attribution.html();
+        assert!(html.contains(
+            r#"<a href="https://en.wikipedia.org/wiki/Acid_&amp;_bass">Acid &amp; bass</a>, revision of 2026-01-02"#
+        ));
+        assert!(html.contains(
+            r#"<a href="https://en.wikipedia.org/w/index.php?oldid=456">revision 456</a> of 2026-01-02"#
+        ));
+    }
+}
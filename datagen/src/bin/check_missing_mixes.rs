@@ -11,13 +11,8 @@ fn main() -> anyhow::Result<()> {
     anyhow::ensure!(mixes_path.is_dir(), "{mixes_path:?} does not exist");
 
     // Genre JSON files use sanitized page names; unsanitize to get the real page name
-    let genres: BTreeSet<shared::PageName> = std::fs::read_dir(genres_path)?
-        .filter_map(Result::ok)
-        .filter_map(|e| {
-            let name = e.file_name().to_string_lossy().to_string();
-            name.strip_suffix(".json").map(shared::PageName::unsanitize)
-        })
-        .collect();
+    let mut genres = BTreeSet::new();
+    collect_genres(genres_path, &mut genres)?;
 
     // Mix files also use sanitized page names
     let mixes: BTreeSet<shared::PageName> = std::fs::read_dir(mixes_path)?
@@ -43,3 +38,15 @@ fn main() -> anyhow::Result<()> {
 
     std::process::exit(1);
 }
+
+/// Collect the genres with files in `path`, including those sharded into its subdirectories.
+fn collect_genres(path: &Path, genres: &mut BTreeSet<shared::PageName>) -> anyhow::Result<()> {
+    for entry in std::fs::read_dir(path)?.filter_map(Result::ok) {
+        if entry.file_type()?.is_dir() {
+            collect_genres(&entry.path(), genres)?;
+        } else if let Some(name) = entry.file_name().to_string_lossy().strip_suffix(".json") {
+            genres.insert(shared::PageName::unsanitize(name));
+        }
+    }
+    Ok(())
+}
@@ -1,4 +1,4 @@
-//! Check for suspicious genre edges in data.json using a degree-ratio heuristic.
+//! Check for suspicious genre edges in the graph data using a degree-ratio heuristic.
 //!
 //! Flags Derivative edges where a low-degree source supposedly influences a much
 //! higher-degree target, which often indicates an error in a Wikipedia infobox.
@@ -12,10 +12,7 @@ use datagen::{
 };
 
 fn main() -> anyhow::Result<()> {
-    let data_path = frontend_types::data_json_path();
-    anyhow::ensure!(data_path.exists(), "{data_path:?} does not exist");
-
-    let data: FrontendData = serde_json::from_str(&std::fs::read_to_string(data_path)?)?;
+    let (data, _) = FrontendData::read(std::path::Path::new(frontend_types::WEBSITE_PUBLIC_PATH))?;
 
     // Compute degree for each node
     let mut degree: BTreeMap<usize, usize> = BTreeMap::new();
@@ -1,27 +1,164 @@
-//! Re-run force layout on existing data.json without needing Wikipedia dumps.
+//! Re-run force layout on existing graph data without needing Wikipedia dumps.
 
-use datagen::frontend_types::{self, FrontendData};
+use std::path::Path;
+
+use clap::Parser;
+use datagen::{
+    data_patches,
+    frontend_types::{self, EdgeType, FrontendData, Neighborhood},
+};
+use layout::{Algorithm, Initialization, LayoutInput, Precision, Progress};
+use serde::de::IntoDeserializer as _;
+
+/// Where `--snapshots` writes the layout's intermediate positions.
+const SNAPSHOTS_DIRECTORY: &str = "layout_snapshots";
+
+/// Re-runs the layout on the website's graph data, without needing Wikipedia dumps.
+#[derive(Debug, Parser)]
+#[command(name = "relayout")]
+struct Args {
+    /// Write the positions every this many iterations to `layout_snapshots/<iteration>.json`, to
+    /// watch the layout settle.
+    #[arg(long = "snapshots", value_name = "EVERY")]
+    snapshot_every: Option<usize>,
+    /// Lay it out with another algorithm, named as in `config.toml`.
+    #[arg(long, value_parser = config_name::<Algorithm>)]
+    algorithm: Option<Algorithm>,
+    /// Place the nodes before the simulation another way, named as in `config.toml`.
+    #[arg(long, value_parser = config_name::<Initialization>)]
+    initialization: Option<Initialization>,
+    /// Compute the layout in another precision, named as in `config.toml`.
+    #[arg(long, value_parser = config_name::<Precision>)]
+    precision: Option<Precision>,
+    /// Start the layout from other random numbers; by default it's started from the same ones as
+    /// the data's, so that it's reproduced.
+    #[arg(long)]
+    seed: Option<u32>,
+    /// Lay it out in 3D, giving each node a `z`.
+    #[arg(long = "3d")]
+    three_d: bool,
+    /// Indent the website's JSON files.
+    #[arg(long)]
+    pretty: bool,
+}
+
+/// Parse one of the layout's options, named as in `config.toml`.
+fn config_name<T: serde::de::DeserializeOwned>(name: &str) -> Result<T, serde::de::value::Error> {
+    T::deserialize(name.into_deserializer())
+}
 
 fn main() -> anyhow::Result<()> {
-    let data_path = frontend_types::data_json_path();
-    let raw = std::fs::read_to_string(data_path)?;
-    let mut data: FrontendData = serde_json::from_str(&raw)?;
+    let Args {
+        snapshot_every,
+        algorithm,
+        initialization,
+        precision,
+        seed,
+        three_d,
+        pretty,
+    } = Args::parse();
+    let website_public_path = Path::new(frontend_types::WEBSITE_PUBLIC_PATH);
+    let (mut data, format) = FrontendData::read(website_public_path)?;
 
     let num_nodes = data.nodes.len();
     let adjacency: Vec<(usize, usize)> = data
         .edges
         .iter()
+        .filter(|e| e.ty != EdgeType::Related)
         .map(|e| (e.source.0, e.target.0))
         .collect();
+    let edge_types: Vec<EdgeType> = data
+        .edges
+        .iter()
+        .filter(|e| e.ty != EdgeType::Related)
+        .map(|e| e.ty)
+        .collect();
 
     println!("Nodes: {num_nodes}, Edges: {}", adjacency.len());
 
-    let positions = datagen::force_layout::compute(num_nodes, &adjacency);
+    let communities = datagen::communities::detect(num_nodes, &adjacency);
+    let patches = data_patches::DataPatches::load(
+        Path::new(data_patches::PATCHES_PATH),
+        data_patches::language(&data.wikipedia_db_name),
+    )?;
+    let pins: Vec<Option<[f64; 2]>> = data
+        .nodes
+        .iter()
+        .map(|node| patches.pins.get(&node.label).copied())
+        .collect();
+
+    let snapshots_path = Path::new(SNAPSHOTS_DIRECTORY);
+    if snapshot_every.is_some() {
+        std::fs::remove_dir_all(snapshots_path).ok();
+        std::fs::create_dir_all(snapshots_path)?;
+    }
+    let algorithm = algorithm.unwrap_or_default();
+    let initialization = initialization.unwrap_or_default();
+    let precision = precision.unwrap_or_default();
+    let seed = seed.unwrap_or(data.layout_seed.unwrap_or_default());
+    println!(
+        "Laying out with {algorithm:?}{} in {precision:?} from {initialization:?} placement with \
+         seed {seed}",
+        if three_d { " in 3D" } else { "" }
+    );
+
+    let mut snapshot_error = None;
+    let input = LayoutInput {
+        num_nodes,
+        adjacency: &adjacency,
+        edge_types: &edge_types,
+        communities: &communities,
+        pins: &pins,
+        initial_positions: &[],
+        seed,
+        initialization,
+        precision,
+    };
+    let layout_algorithm = algorithm.layout();
+    let positions: Vec<[f64; 3]> = if three_d {
+        layout_algorithm
+            .compute_3d(&input, &mut |progress| {
+                on_progress(
+                    progress,
+                    snapshot_every,
+                    snapshots_path,
+                    &mut snapshot_error,
+                )
+            })
+            .ok_or_else(|| anyhow::anyhow!("The {algorithm:?} layout can't lay out in 3D"))?
+    } else {
+        layout_algorithm
+            .compute(&input, &mut |progress| {
+                on_progress(
+                    progress,
+                    snapshot_every,
+                    snapshots_path,
+                    &mut snapshot_error,
+                )
+            })
+            .into_iter()
+            .map(|[x, y]| [x, y, 0.0])
+            .collect()
+    };
+    if let Some(e) = snapshot_error {
+        return Err(e.context("Failed to write a layout snapshot"));
+    }
 
+    data.layout_seed = Some(seed);
     for (node, pos) in data.nodes.iter_mut().zip(positions.iter()) {
         node.x = pos[0];
         node.y = pos[1];
+        node.z = three_d.then_some(pos[2]);
     }
+    datagen::layout_metrics::LayoutMetrics::measure(
+        &positions
+            .iter()
+            .map(|&[x, y, _]| [x, y])
+            .collect::<Vec<_>>(),
+        &adjacency,
+        &edge_types,
+    )
+    .print();
 
     let hues = datagen::color_propagation::compute_hues(num_nodes, &adjacency);
     for (node, &hue) in data.nodes.iter_mut().zip(hues.iter()) {
@@ -29,7 +166,47 @@ fn main() -> anyhow::Result<()> {
     }
     println!("Computed color propagation for {num_nodes} nodes");
 
-    std::fs::write(data_path, serde_json::to_string_pretty(&data)?)?;
+    let data_path = data.write(website_public_path, format, pretty)?;
     println!("Updated {data_path:?}");
+
+    // The neighbourhood files have copies of the nodes from before the layout
+    let neighborhood_path = website_public_path.join(frontend_types::NEIGHBORHOOD_DIRECTORY);
+    if neighborhood_path.is_dir() {
+        for entry in std::fs::read_dir(&neighborhood_path)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|extension| extension != "json") {
+                continue;
+            }
+            let mut neighborhood: Neighborhood = serde_json::from_slice(&std::fs::read(&path)?)?;
+            neighborhood.nodes = neighborhood
+                .ids
+                .iter()
+                .map(|id| data.nodes[id.0].clone())
+                .collect();
+            std::fs::write(&path, frontend_types::to_json(&neighborhood, pretty)?)?;
+        }
+        println!("Updated {neighborhood_path:?}");
+    }
     Ok(())
 }
+
+/// Print the layout's progress, and write a snapshot of it every `snapshot_every` iterations
+/// (keeping the first error).
+fn on_progress<const D: usize>(
+    progress: &Progress<D>,
+    snapshot_every: Option<usize>,
+    snapshots_path: &Path,
+    snapshot_error: &mut Option<anyhow::Error>,
+) {
+    layout::print_progress(progress);
+    if snapshot_every.is_some_and(|every| progress.iteration.is_multiple_of(every))
+        && snapshot_error.is_none()
+    {
+        let path = snapshots_path.join(format!("{:05}.json", progress.iteration));
+        let positions: Vec<&[f64]> = progress.positions.iter().map(|p| p.as_slice()).collect();
+        *snapshot_error = serde_json::to_vec(&positions)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| Ok(std::fs::write(path, json)?))
+            .err();
+    }
+}
@@ -0,0 +1,191 @@
+//! Bundling the machine-readable outputs into one versioned `.tar.zst` archive (`bundle = true`
+//! in `[output]`), for downloading the whole dataset without crawling the website's files.
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+
+use crate::extract::DumpMeta;
+
+/// The files at the top of the output that are bundled, where they were written.
+const BUNDLED_FILES: &[&str] = &[
+    "data_index.json",
+    "data.json",
+    "data.msgpack",
+    "chunks.json",
+    "meta.json",
+    "nodes.json",
+    "edges.bin",
+    "links_to_page_ids.json",
+    "aliases.json",
+    "decades.json",
+    "countries.json",
+    "families.json",
+    "stats.json",
+    "attribution.json",
+    "genres.graphml",
+    "genres.gexf",
+    "genres.jsonld",
+    "genres.sqlite",
+    "nodes.csv",
+    "edges.csv",
+    "nodes.parquet",
+    "edges.parquet",
+    "genre_artists.parquet",
+];
+
+/// The directories in the output that are bundled, with everything in them.
+const BUNDLED_DIRECTORIES: &[&str] = &["clusters", "genres", "artists", "schema"];
+
+/// The zstd compression level: high, as the bundle is written once and downloaded many times.
+const COMPRESSION_LEVEL: i32 = 19;
+
+/// Write `genresinspace-<dump date>.tar.zst` to `output_path`, with the bundled files in a
+/// `genresinspace-<dump date>/` directory alongside a `LICENSE.txt` for the data. Returns the
+/// path of the bundle.
+///
+/// The archive is the same for the same output: its entries are sorted, and they all have the
+/// dump date as their modification time.
+pub fn write(
+    start: std::time::Instant,
+    output_path: &Path,
+    dump_meta: &DumpMeta,
+) -> anyhow::Result<PathBuf> {
+    let dump_date = dump_meta.dump_date;
+    let name = format!("genresinspace-{dump_date}");
+    let mtime = dump_date
+        .to_zoned(jiff::tz::TimeZone::UTC)?
+        .timestamp()
+        .as_second()
+        .try_into()?;
+
+    let mut files = vec![];
+    for file in BUNDLED_FILES {
+        let path = output_path.join(file);
+        if path.is_file() {
+            files.push(path);
+        }
+    }
+    for directory in BUNDLED_DIRECTORIES {
+        let path = output_path.join(directory);
+        if path.is_dir() {
+            directory_files(&path, &mut files)?;
+        }
+    }
+    files.sort();
+
+    let bundle_path = output_path.join(format!("{name}.tar.zst"));
+    let file = std::fs::File::create(&bundle_path)
+        .with_context(|| format!("Failed to create {bundle_path:?}"))?;
+    let mut tar = tar::Builder::new(zstd::Encoder::new(file, COMPRESSION_LEVEL)?);
+    let mut append = |path: &str, content: &[u8]| -> anyhow::Result<()> {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(mtime);
+        tar.append_data(&mut header, format!("{name}/{path}"), content)
+            .with_context(|| format!("Failed to add {path} to the bundle"))
+    };
+
+    append("LICENSE.txt", license(dump_meta).as_bytes())?;
+    for path in &files {
+        let content = std::fs::read(path).with_context(|| format!("Failed to read {path:?}"))?;
+        let relative_path = path
+            .strip_prefix(output_path)?
+            .to_string_lossy()
+            .replace('\\', "/");
+        append(&relative_path, &content)?;
+    }
+    tar.into_inner()?.finish()?;
+
+    println!(
+        "{:.2}s: bundled {} files into {bundle_path:?}",
+        start.elapsed().as_secs_f32(),
+        files.len() + 1
+    );
+    Ok(bundle_path)
+}
+
+/// Add the files in `dir` and its subdirectories to `files`.
+fn directory_files(dir: &Path, files: &mut Vec<PathBuf>) -> anyhow::Result<()> {
+    for entry in std::fs::read_dir(dir).with_context(|| format!("Failed to read {dir:?}"))? {
+        let path = entry?.path();
+        if path.is_dir() {
+            directory_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// The license of the bundled data, which comes from Wikipedia.
+fn license(dump_meta: &DumpMeta) -> String {
+    let DumpMeta {
+        wikipedia_domain,
+        dump_date,
+        ..
+    } = dump_meta;
+    format!(
+        "The data in this bundle was derived from the {dump_date} dump of Wikipedia \
+         (https://{wikipedia_domain}/) by genresin.space (https://genresin.space/).\n\
+         \n\
+         Like Wikipedia's text, it is available under the Creative Commons\n\
+         Attribution-ShareAlike 4.0 International license (CC BY-SA 4.0):\n\
+         https://creativecommons.org/licenses/by-sa/4.0/\n\
+         \n\
+         Genre and artist descriptions are from their Wikipedia articles, whose authors are listed\n\
+         in each article's history. attribution.json lists each article, with the revision the\n\
+         description was taken from and a link to its history.\n"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundles_the_outputs_reproducibly() {
+        let dir = std::env::temp_dir().join(format!("datagen-bundle-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("genres/te")).unwrap();
+        std::fs::write(dir.join("data.json"), "{}").unwrap();
+        std::fs::write(dir.join("genres/te/Techno.json"), "{}").unwrap();
+        std::fs::write(dir.join("robots.txt"), "").unwrap();
+        let dump_meta = DumpMeta {
+            wikipedia_db_name: "enwiki".to_string(),
+            wikipedia_domain: "en.wikipedia.org".to_string(),
+            dump_date: jiff::civil::date(2026, 2, 1),
+        };
+
+        let start = std::time::Instant::now();
+        let path = write(start, &dir, &dump_meta).unwrap();
+        assert_eq!(path, dir.join("genresinspace-2026-02-01.tar.zst"));
+        let bundle = std::fs::read(&path).unwrap();
+
+        let mut archive = tar::Archive::new(zstd::Decoder::new(&bundle[..]).unwrap());
+        let entries: Vec<String> = archive
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                entry
+                    .unwrap()
+                    .path()
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect();
+        assert_eq!(
+            entries,
+            [
+                "genresinspace-2026-02-01/LICENSE.txt",
+                "genresinspace-2026-02-01/data.json",
+                "genresinspace-2026-02-01/genres/te/Techno.json",
+            ]
+        );
+
+        // The previous bundle isn't bundled into the next
+        write(start, &dir, &dump_meta).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), bundle);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,175 @@
+//! Centrality metrics for the genre graph, for sizing nodes and ranking genres by influence.
+
+use rayon::prelude::*;
+
+/// Compute the PageRank of each node in a directed graph, where `edges` are
+/// `(source, target)` pairs. The ranks sum to 1.
+///
+/// Rank flows from source to target with probability `damping`, and nodes
+/// without outgoing edges spread theirs evenly over every node. It runs for at
+/// most `max_iters` power iterations, stopping sooner once the ranks settle.
+pub fn pagerank(
+    num_nodes: usize,
+    edges: &[(usize, usize)],
+    damping: f64,
+    max_iters: usize,
+) -> Vec<f64> {
+    if num_nodes == 0 {
+        return vec![];
+    }
+    const TOLERANCE: f64 = 1e-10;
+
+    let mut out_degree = vec![0usize; num_nodes];
+    for &(source, _) in edges {
+        out_degree[source] += 1;
+    }
+
+    let n = num_nodes as f64;
+    let mut ranks = vec![1.0 / n; num_nodes];
+    for _ in 0..max_iters {
+        let dangling: f64 = (0..num_nodes)
+            .filter(|&i| out_degree[i] == 0)
+            .map(|i| ranks[i])
+            .sum();
+        let base = (1.0 - damping) / n + damping * dangling / n;
+        let mut next = vec![base; num_nodes];
+        for &(source, target) in edges {
+            next[target] += damping * ranks[source] / out_degree[source] as f64;
+        }
+        let change: f64 = next.iter().zip(&ranks).map(|(a, b)| (a - b).abs()).sum();
+        ranks = next;
+        if change < TOLERANCE {
+            break;
+        }
+    }
+    ranks
+}
+
+/// Compute the betweenness centrality of each node in an undirected graph,
+/// where `edges` are `(source, target)` pairs, with Brandes' algorithm.
+///
+/// Normalized by the number of pairs of other nodes, so it ranges from 0 (on
+/// no shortest paths) to 1 (on every shortest path between other nodes).
+pub fn betweenness(num_nodes: usize, edges: &[(usize, usize)]) -> Vec<f64> {
+    let mut neighbours: Vec<Vec<usize>> = vec![vec![]; num_nodes];
+    for &(a, b) in edges {
+        if a != b {
+            neighbours[a].push(b);
+            neighbours[b].push(a);
+        }
+    }
+    for list in &mut neighbours {
+        list.sort_unstable();
+        list.dedup();
+    }
+
+    // Sources are summed in fixed-size chunks, in order, rather than reduced in whatever
+    // grouping rayon's work-stealing gives: floating-point addition isn't associative, and the
+    // output should be the same from run to run
+    const CHUNK_SIZE: usize = 64;
+    let add = |mut a: Vec<f64>, b: Vec<f64>| {
+        for (a, b) in a.iter_mut().zip(b) {
+            *a += b;
+        }
+        a
+    };
+    let sources: Vec<usize> = (0..num_nodes).collect();
+    let totals = sources
+        .par_chunks(CHUNK_SIZE)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .map(|&source| single_source_dependencies(&neighbours, source))
+                .fold(vec![0.0; num_nodes], add)
+        })
+        .collect::<Vec<_>>()
+        .into_iter()
+        .fold(vec![0.0; num_nodes], add);
+
+    // Each pair is counted from both of its ends
+    let pairs = (num_nodes.saturating_sub(1) * num_nodes.saturating_sub(2)) as f64;
+    totals
+        .into_iter()
+        .map(|total| if pairs > 0.0 { total / pairs } else { 0.0 })
+        .collect()
+}
+
+/// The dependency of `source` on each node: how much of the shortest paths
+/// from `source` to every other node pass through it.
+fn single_source_dependencies(neighbours: &[Vec<usize>], source: usize) -> Vec<f64> {
+    let num_nodes = neighbours.len();
+    let mut order = Vec::with_capacity(num_nodes);
+    let mut path_counts = vec![0.0; num_nodes];
+    let mut distances = vec![usize::MAX; num_nodes];
+    path_counts[source] = 1.0;
+    distances[source] = 0;
+
+    let mut queue = std::collections::VecDeque::from([source]);
+    while let Some(node) = queue.pop_front() {
+        order.push(node);
+        for &neighbour in &neighbours[node] {
+            if distances[neighbour] == usize::MAX {
+                distances[neighbour] = distances[node] + 1;
+                queue.push_back(neighbour);
+            }
+            if distances[neighbour] == distances[node] + 1 {
+                path_counts[neighbour] += path_counts[node];
+            }
+        }
+    }
+
+    let mut dependencies = vec![0.0; num_nodes];
+    for &node in order.iter().rev() {
+        for &predecessor in &neighbours[node] {
+            if distances[predecessor] != usize::MAX && distances[predecessor] + 1 == distances[node]
+            {
+                dependencies[predecessor] +=
+                    path_counts[predecessor] / path_counts[node] * (1.0 + dependencies[node]);
+            }
+        }
+    }
+    dependencies[source] = 0.0;
+    dependencies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-6, "{actual:?} != {expected:?}");
+        }
+    }
+
+    #[test]
+    fn pagerank_favours_the_target_of_many_edges() {
+        let ranks = pagerank(4, &[(1, 0), (2, 0), (3, 0)], 0.85, 100);
+        assert!((ranks.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!(ranks[0] > ranks[1]);
+        assert_close(&ranks[1..], &[ranks[1]; 3]);
+    }
+
+    #[test]
+    fn pagerank_is_uniform_on_a_cycle() {
+        assert_close(
+            &pagerank(3, &[(0, 1), (1, 2), (2, 0)], 0.85, 100),
+            &[1.0 / 3.0; 3],
+        );
+    }
+
+    #[test]
+    fn betweenness_of_a_path_peaks_in_the_middle() {
+        // 0 - 1 - 2 - 3 - 4: the middle node is on 4 of the 6 paths between other nodes
+        let centrality = betweenness(5, &[(0, 1), (1, 2), (2, 3), (3, 4)]);
+        assert_close(&centrality, &[0.0, 0.5, 4.0 / 6.0, 0.5, 0.0]);
+    }
+
+    #[test]
+    fn betweenness_splits_between_equal_paths() {
+        // A square: each corner is on half of the paths between its two neighbours
+        let centrality = betweenness(4, &[(0, 1), (1, 2), (2, 3), (3, 0)]);
+        assert_close(&centrality, &[0.5 / 3.0; 4]);
+    }
+}
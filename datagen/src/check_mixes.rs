@@ -4,9 +4,16 @@ use std::{
     path::Path,
 };
 
+use anyhow::Context as _;
 use serde::{Deserialize, Serialize};
 
-use crate::types::{GenreMix, GenreMixes};
+use crate::{
+    mix_embeds,
+    types::{GenreMix, GenreMixes, MixesFile, PageName},
+};
+
+/// Where `datagen validate-mixes` writes its report.
+pub const REPORT_PATH: &str = "output/mix_validation.json";
 
 /// Check the status of all videos in the mixes.
 pub fn run(mixes_path: &Path, key: &str) -> anyhow::Result<()> {
@@ -17,7 +24,8 @@ pub fn run(mixes_path: &Path, key: &str) -> anyhow::Result<()> {
     let mut genre_mixes = BTreeMap::new();
     for mix in std::fs::read_dir(mixes_path)? {
         let mix_path = mix?.path();
-        let mixes = GenreMixes::parse(&std::fs::read_to_string(&mix_path)?);
+        let mixes = GenreMixes::parse(&std::fs::read_to_string(&mix_path)?)
+            .with_context(|| format!("Failed to parse {mix_path:?}"))?;
         genre_mixes.insert(
             mix_path.file_stem().unwrap().to_str().unwrap().to_string(),
             mixes,
@@ -34,7 +42,7 @@ pub fn run(mixes_path: &Path, key: &str) -> anyhow::Result<()> {
         };
         for mix in items {
             match mix {
-                GenreMix::Playlist { playlist, note: _ } => {
+                GenreMix::Playlist { playlist, .. } => {
                     if let Some(existing_genre) = playlist_to_genre.insert(playlist.as_str(), genre)
                     {
                         anyhow::bail!(
@@ -44,7 +52,7 @@ pub fn run(mixes_path: &Path, key: &str) -> anyhow::Result<()> {
 
                     playlists.push((genre, playlist));
                 }
-                GenreMix::Video { video, note: _ } => {
+                GenreMix::Video { video, .. } => {
                     if videos_to_ignore.contains(video.as_str()) {
                         continue;
                     }
@@ -56,6 +64,8 @@ pub fn run(mixes_path: &Path, key: &str) -> anyhow::Result<()> {
 
                     videos.push((genre, video));
                 }
+                // Only YouTube's mixes can be checked
+                GenreMix::External { .. } => {}
             }
         }
     }
@@ -186,9 +196,8 @@ struct VideoContentDetails {
     region_restriction: Option<VideoRegionRestriction>,
 }
 impl VideoContentDetails {
-    #[allow(dead_code)]
-    // I tried to use this, but it was a bit too depressing to see the number of region-locked videos,
-    // so I decided to simply ignore it. I'm sure people will let me know if videos don't load for them.
+    // I tried to mark these as dead, but it was a bit too depressing to see the number of region-locked videos,
+    // so `validate-mixes` only reports them. I'm sure people will let me know if videos don't load for them.
     fn is_blocked(&self) -> bool {
         self.region_restriction.is_some()
     }
@@ -257,3 +266,315 @@ fn list_playlists<'a>(
 
     Ok(response.items)
 }
+
+/// What's wrong with a mix, as found by [`validate`].
+#[derive(Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case", tag = "problem")]
+enum Problem {
+    /// YouTube doesn't know of it: it, or its uploader's account, was deleted.
+    Deleted,
+    /// Only its uploader can see it.
+    Private,
+    /// Its uploader doesn't let it be embedded, so it can't be played on the website.
+    NotEmbeddable,
+    /// It can't be played in some countries: those `blocked`, or those not `allowed`.
+    RegionLocked {
+        allowed: Option<Vec<String>>,
+        blocked: Option<Vec<String>>,
+    },
+    /// oEmbed can't see it, so it's deleted, private or not embeddable (which of these can only
+    /// be told with the API).
+    Unavailable,
+}
+
+impl Problem {
+    /// Whether the mix can't be played anywhere, and so is marked by `--mark`.
+    fn is_dead(&self) -> bool {
+        !matches!(self, Self::RegionLocked { .. })
+    }
+
+    fn describe(&self) -> &'static str {
+        match self {
+            Self::Deleted => "deleted",
+            Self::Private => "private",
+            Self::NotEmbeddable => "not embeddable",
+            Self::RegionLocked { .. } => "region-locked",
+            Self::Unavailable => "unavailable",
+        }
+    }
+}
+
+/// A mix with a [`Problem`], in the report.
+#[derive(Debug, Serialize)]
+struct MixReport<'a> {
+    genre: &'a PageName,
+    url: String,
+    #[serde(flatten)]
+    problem: Problem,
+}
+
+/// Check that every YouTube mix still exists and can be embedded, with the YouTube API if
+/// there's a `key`, or with oEmbed if not (which can't tell why a mix is unavailable, or see
+/// region locks). The mixes with problems are printed and written to [`REPORT_PATH`]. With `mark`, the
+/// dead ones (all but the region-locked) are marked as dead in their genres' files, with why
+/// (see [`mark_dead_mixes`]), so that the website stops showing them.
+pub fn validate(mixes_path: &Path, key: &str, mark: bool) -> anyhow::Result<()> {
+    let mut genre_mixes = BTreeMap::new();
+    for entry in std::fs::read_dir(mixes_path)? {
+        let path = entry?.path();
+        if !path.is_file() {
+            continue;
+        }
+        let genre = PageName::unsanitize(&path.file_name().unwrap().to_string_lossy());
+        let mixes = GenreMixes::parse(&std::fs::read_to_string(&path)?)
+            .with_context(|| format!("Failed to parse {path:?}"))?;
+        genre_mixes.insert(genre, (path, mixes));
+    }
+    let mixes: Vec<(&PageName, &GenreMix)> = genre_mixes
+        .iter()
+        .flat_map(|(genre, (_, mixes))| match mixes {
+            // Only YouTube's mixes can be checked
+            GenreMixes::Mixes(items) => items
+                .iter()
+                .filter(|mix| mix_embeds::mix_url(mix).is_some())
+                .map(|mix| (genre, mix))
+                .collect(),
+            GenreMixes::Help { .. } => vec![],
+        })
+        .collect();
+    println!(
+        "Validating {} mixes {}",
+        mixes.len(),
+        if key.is_empty() {
+            "with oEmbed (set youtube_api_key in config.toml to tell why mixes are unavailable)"
+        } else {
+            "with the YouTube API"
+        }
+    );
+
+    let mut reports = vec![];
+    if key.is_empty() {
+        let client = reqwest::blocking::Client::new();
+        for (index, &(genre, mix)) in mixes.iter().enumerate() {
+            if index > 0 {
+                std::thread::sleep(mix_embeds::REQUEST_INTERVAL);
+            }
+            let (_, url) = mix_embeds::mix_url(mix).unwrap();
+            if mix_embeds::look_up(&client, &url)?.is_none() {
+                reports.push(MixReport {
+                    genre,
+                    url,
+                    problem: Problem::Unavailable,
+                });
+            }
+        }
+    } else {
+        let videos: Vec<_> = mixes
+            .iter()
+            .filter(|(_, mix)| matches!(mix, GenreMix::Video { .. }))
+            .collect();
+        for slice in videos.chunks(50) {
+            let found: BTreeMap<String, Video> = list_videos(
+                key,
+                slice
+                    .iter()
+                    .map(|(_, mix)| mix_embeds::mix_url(mix).unwrap().0),
+            )?
+            .into_iter()
+            .map(|video| (video.id.clone(), video))
+            .collect();
+            for &&(genre, mix) in slice {
+                let (id, url) = mix_embeds::mix_url(mix).unwrap();
+                let problem = match found.get(id) {
+                    None => Problem::Deleted,
+                    Some(video) if video.status.privacy_status == VideoPrivacyStatus::Private => {
+                        Problem::Private
+                    }
+                    Some(video) if !video.status.embeddable => Problem::NotEmbeddable,
+                    Some(video) if video.content_details.is_blocked() => {
+                        let restriction = video.content_details.region_restriction.as_ref();
+                        Problem::RegionLocked {
+                            allowed: restriction.and_then(|r| r.allowed.clone()),
+                            blocked: restriction.and_then(|r| r.blocked.clone()),
+                        }
+                    }
+                    Some(_) => continue,
+                };
+                reports.push(MixReport {
+                    genre,
+                    url,
+                    problem,
+                });
+            }
+        }
+
+        let playlists: Vec<_> = mixes
+            .iter()
+            .filter(|(_, mix)| matches!(mix, GenreMix::Playlist { .. }))
+            .collect();
+        for slice in playlists.chunks(50) {
+            let found: BTreeMap<String, Playlist> = list_playlists(
+                key,
+                slice
+                    .iter()
+                    .map(|(_, mix)| mix_embeds::mix_url(mix).unwrap().0),
+            )?
+            .into_iter()
+            .map(|playlist| (playlist.id.clone(), playlist))
+            .collect();
+            for &&(genre, mix) in slice {
+                let (id, url) = mix_embeds::mix_url(mix).unwrap();
+                let problem = match found.get(id) {
+                    None => Problem::Deleted,
+                    Some(playlist)
+                        if playlist.status.privacy_status == PlaylistPrivacyStatus::Private =>
+                    {
+                        Problem::Private
+                    }
+                    Some(_) => continue,
+                };
+                reports.push(MixReport {
+                    genre,
+                    url,
+                    problem,
+                });
+            }
+        }
+    }
+    reports.sort_by(|a, b| a.genre.cmp(b.genre).then_with(|| a.url.cmp(&b.url)));
+
+    for report in &reports {
+        println!(
+            "- {}: {} ({})",
+            report.genre,
+            report.url,
+            report.problem.describe()
+        );
+    }
+    let report_path = Path::new(REPORT_PATH);
+    if let Some(parent) = report_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(report_path, serde_json::to_string_pretty(&reports)?)
+        .with_context(|| format!("Failed to write {report_path:?}"))?;
+    let dead = reports.iter().filter(|r| r.problem.is_dead()).count();
+    println!(
+        "{dead} dead and {} region-locked mixes, out of {}; written to {report_path:?}",
+        reports.len() - dead,
+        mixes.len()
+    );
+
+    if mark && dead > 0 {
+        let mut marked_genres = 0;
+        for (genre, (path, _)) in &genre_mixes {
+            let dead_here: BTreeMap<&str, &Problem> = reports
+                .iter()
+                .filter(|r| r.genre == genre && r.problem.is_dead())
+                .map(|r| (r.url.as_str(), &r.problem))
+                .collect();
+            if dead_here.is_empty() {
+                continue;
+            }
+            let contents = std::fs::read_to_string(path)?;
+            let marked = mark_dead_mixes(&contents, &dead_here)
+                .with_context(|| format!("Failed to mark the dead mixes in {path:?}"))?;
+            std::fs::write(path, marked)?;
+            marked_genres += 1;
+        }
+        println!("Marked the dead mixes in {marked_genres} genres' files");
+    }
+    Ok(())
+}
+
+/// Mark each mix in a genre's mixes file that has a problem in `dead` (by URL), so that it's
+/// skipped when the file's parsed. In a TOML file, the mix's `dead` is set to the problem; in a
+/// plain text file, its line is commented out, noting the problem, and can be restored by
+/// removing the prefix.
+fn mark_dead_mixes(contents: &str, dead: &BTreeMap<&str, &Problem>) -> anyhow::Result<String> {
+    let problem_with = |url: &str| {
+        GenreMix::from_url(url, None, Default::default())
+            .and_then(|mix| dead.get(mix.url().as_str()).copied())
+    };
+
+    if MixesFile::is_toml(contents) {
+        // Edited in place, to keep the file's comments and layout
+        let mut document: toml_edit::DocumentMut = contents.parse()?;
+        if let Some(mixes) = document
+            .get_mut("mix")
+            .and_then(|mixes| mixes.as_array_of_tables_mut())
+        {
+            for mix in mixes.iter_mut() {
+                let problem = mix
+                    .get("url")
+                    .and_then(|url| url.as_str())
+                    .and_then(problem_with);
+                if let Some(problem) = problem {
+                    mix["dead"] = toml_edit::value(problem.describe());
+                }
+            }
+        }
+        return Ok(document.to_string());
+    }
+
+    let mut marked = String::new();
+    for line in contents.lines() {
+        let url = line.split('#').next().unwrap_or_default().trim();
+        let problem = problem_with(url);
+        match problem {
+            Some(problem) => {
+                marked.push_str(&format!("# dead ({}): {line}", problem.describe()));
+            }
+            None => marked.push_str(line),
+        }
+        marked.push('\n');
+    }
+    Ok(marked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dead_mixes_are_commented_out() {
+        let contents = "https://www.youtube.com/watch?v=alive\n\
+            https://www.youtube.com/watch?v=gone&t=10 # the good one\n";
+        let dead = BTreeMap::from([("https://www.youtube.com/watch?v=gone", &Problem::Deleted)]);
+        let marked = mark_dead_mixes(contents, &dead).unwrap();
+        assert_eq!(
+            marked,
+            "https://www.youtube.com/watch?v=alive\n\
+             # dead (deleted): https://www.youtube.com/watch?v=gone&t=10 # the good one\n"
+        );
+        assert_eq!(
+            GenreMixes::parse(&marked).unwrap(),
+            GenreMixes::Mixes(vec![GenreMix::Video {
+                video: "alive".to_string(),
+                note: None,
+                details: Default::default(),
+            }])
+        );
+    }
+
+    #[test]
+    fn dead_mixes_are_marked_in_toml() {
+        let contents = "# Mixes for the genre\n\
+            [[mix]]\n\
+            url = \"https://www.youtube.com/watch?v=alive\"\n\
+            \n\
+            [[mix]]\n\
+            url = \"https://www.youtube.com/watch?v=gone&t=10\"\n\
+            note = \"the good one\"\n";
+        let dead = BTreeMap::from([("https://www.youtube.com/watch?v=gone", &Problem::Deleted)]);
+        let marked = mark_dead_mixes(contents, &dead).unwrap();
+        assert_eq!(marked, contents.to_string() + "dead = \"deleted\"\n",);
+        assert_eq!(
+            GenreMixes::parse(&marked).unwrap(),
+            GenreMixes::Mixes(vec![GenreMix::Video {
+                video: "alive".to_string(),
+                note: None,
+                details: Default::default(),
+            }])
+        );
+    }
+}
@@ -0,0 +1,286 @@
+//! The command line: a subcommand for each stage of the pipeline, which runs it and the stages
+//! before it, along with the tools that work on the mixes and patches.
+use std::path::{Path, PathBuf};
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+/// Processes Wikipedia dumps to extract music genres and produce data for a graph.
+#[derive(Debug, Parser)]
+#[command(name = "datagen")]
+pub struct Cli {
+    /// What to run; the whole pipeline (`all`) if not given.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// The flags for the pipeline.
+    #[command(flatten)]
+    pub pipeline: PipelineArgs,
+}
+
+/// The flags shared by everything that runs the pipeline.
+#[derive(Debug, Args)]
+pub struct PipelineArgs {
+    /// The previous output to reuse unchanged pages from, instead of the latest one before this
+    /// dump's.
+    #[arg(long, global = true)]
+    pub previous_output: Option<PathBuf>,
+    /// The `layout.json` to warm-start the layout from, instead of the previous output's.
+    #[arg(long, global = true)]
+    pub previous_layout: Option<PathBuf>,
+    /// Redo a stage (and those after it) instead of loading its results from
+    /// `output/<dump date>`. Can be given more than once.
+    #[arg(long, global = true, value_enum)]
+    pub force: Vec<Stage>,
+    /// Leave out a stage. Only the layout can be left out, in which case the genres keep their
+    /// places in the previous layout.
+    #[arg(long, global = true, value_enum)]
+    pub skip: Vec<Stage>,
+    /// Indent the website's JSON files.
+    #[arg(long, global = true)]
+    pub pretty: bool,
+    /// Connect genres whose articles mention each other with "related" edges.
+    #[arg(long, global = true)]
+    pub prose_mentions: bool,
+    /// Print the parsed wikitext of this page as it's processed (use with `--force process`, so
+    /// that it's processed).
+    #[arg(long, global = true)]
+    pub dump_page: Option<String>,
+}
+
+/// The stages of the pipeline, in the order they run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum Stage {
+    /// Extract the genre and artist pages, and redirects, from the dump.
+    Extract,
+    /// Process the genres and artists from their infoboxes, and apply the patches.
+    Process,
+    /// Resolve links, and count the links to (and views of) each page.
+    Links,
+    /// Rank each genre's top artists.
+    Artists,
+    /// Lay out the graph, writing `layout.json`.
+    Layout,
+    /// Write the website's data.
+    Output,
+}
+
+impl Stage {
+    /// The files and directories in `output/<dump date>` that the stage's results are cached in,
+    /// and loaded from instead of being redone (ending in `*` for any starting with the rest); the
+    /// layout and output are always redone.
+    fn cached(self) -> &'static [&'static str] {
+        match self {
+            Self::Extract => &[
+                "offsets.txt",
+                "meta.toml",
+                "genres",
+                "artists",
+                "all_redirects.json",
+                "alias_redirects.json",
+                "id_to_page_names.json",
+                "wikidata_ids.json",
+            ],
+            Self::Process => &["processed_genres", "processed_artists"],
+            Self::Links => &[
+                "links_to_articles.json",
+                "page_aliases.json",
+                "inbound_link_counts.bin",
+                "linktargets_tracked.json",
+                "pageviews.json",
+                "langlinks.json",
+                "prose_mentions.json",
+            ],
+            // Named for the ranking, e.g. `genre_top_artists_pageviews.json`
+            Self::Artists => &["genre_top_artists*", "artist_genres*", "coverage_stats*"],
+            Self::Layout | Self::Output => &[],
+        }
+    }
+}
+
+/// The subcommands.
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Extract the pages from the dump.
+    Extract,
+    /// Run the pipeline up to processing the genres and artists.
+    Process,
+    /// Run the pipeline up to resolving and counting links.
+    Links,
+    /// Run the pipeline up to ranking the genres' top artists.
+    Artists,
+    /// Run the pipeline up to laying out the graph, without writing the website's data.
+    Layout,
+    /// Run the pipeline and write the website's data.
+    Output,
+    /// Run the whole pipeline, as `output` does.
+    All,
+    /// Find mixes for the genres without one, then stop.
+    PopulateMixes {
+        /// The order the genres are asked about in.
+        #[arg(long, value_enum, default_value_t)]
+        order: MixOrder,
+    },
+    /// Check that the mixes' videos are still up with the YouTube API, then stop.
+    CheckMixes,
+    /// Check every mix, listing the unavailable ones in `output/mix_validation.json`.
+    ValidateMixes {
+        /// Mark the dead mixes in their files, so that they're left out.
+        #[arg(long)]
+        mark: bool,
+    },
+    /// Check the patches against the extracted data.
+    ValidatePatches,
+    /// Reprocess every page from scratch and compare the results against a baseline.
+    Snapshot {
+        /// Where the baseline and current results are kept.
+        path: PathBuf,
+    },
+    /// Compare two outputs, writing a changelog and feed.
+    Diff {
+        /// The older output.
+        old_output: PathBuf,
+        /// The newer output.
+        new_output: PathBuf,
+    },
+    /// Convert the plain text mix files to TOML.
+    MigrateMixes,
+    /// Export the mixes to a CSV, or import them from one.
+    Mixes {
+        /// Whether to export or import.
+        #[command(subcommand)]
+        command: MixesCommand,
+    },
+}
+
+impl Command {
+    /// The last stage of the pipeline that the command runs, if it runs the pipeline at all.
+    pub fn stage(&self) -> Option<Stage> {
+        match self {
+            Self::Extract | Self::ValidatePatches | Self::Snapshot { .. } => Some(Stage::Extract),
+            Self::Process
+            | Self::Mixes {
+                command: MixesCommand::Import { .. },
+            } => Some(Stage::Process),
+            Self::Links | Self::PopulateMixes { .. } | Self::CheckMixes => Some(Stage::Links),
+            Self::Artists => Some(Stage::Artists),
+            Self::Layout => Some(Stage::Layout),
+            Self::Output | Self::All => Some(Stage::Output),
+            Self::ValidateMixes { .. }
+            | Self::Diff { .. }
+            | Self::MigrateMixes
+            | Self::Mixes {
+                command: MixesCommand::Export { .. },
+            } => None,
+        }
+    }
+}
+
+/// The mixes subcommands.
+#[derive(Debug, Subcommand)]
+pub enum MixesCommand {
+    /// Write every mix to a CSV.
+    Export {
+        /// The CSV to write.
+        csv: PathBuf,
+    },
+    /// Add the mixes in a CSV to the genres' files.
+    Import {
+        /// The CSV to read.
+        csv: PathBuf,
+    },
+}
+
+/// The order `populate-mixes` asks about the genres in.
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub enum MixOrder {
+    /// The most popular first.
+    #[default]
+    Popularity,
+    /// The most connected first.
+    Edges,
+}
+
+impl PipelineArgs {
+    /// Check that only the stages that can be left out are skipped.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if let Some(stage) = self.skip.iter().find(|stage| **stage != Stage::Layout) {
+            anyhow::bail!(
+                "Can't skip the {stage:?} stage; only the layout can be skipped, as the other stages' results are needed by the ones after them"
+            );
+        }
+        Ok(())
+    }
+
+    /// Whether `stage` is skipped.
+    pub fn skips(&self, stage: Stage) -> bool {
+        self.skip.contains(&stage)
+    }
+
+    /// Remove the cached results in `output_path` of the earliest forced stage and the stages
+    /// after it, so that they're redone.
+    pub fn clear_forced(&self, output_path: &Path) -> anyhow::Result<()> {
+        let Some(earliest) = self.force.iter().min() else {
+            return Ok(());
+        };
+        if !output_path.is_dir() {
+            return Ok(());
+        }
+        let cached: Vec<&str> = Stage::value_variants()
+            .iter()
+            .filter(|stage| *stage >= earliest)
+            .flat_map(|stage| stage.cached())
+            .copied()
+            .collect();
+        for entry in std::fs::read_dir(output_path)? {
+            let path = entry?.path();
+            let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+            if !cached.iter().any(|cached| is_cached_as(&file_name, cached)) {
+                continue;
+            }
+            if path.is_dir() {
+                std::fs::remove_dir_all(&path)?;
+            } else {
+                std::fs::remove_file(&path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether `file_name` is the cache `cached` (see [`Stage::cached`]).
+fn is_cached_as(file_name: &str, cached: &str) -> bool {
+    match cached.strip_suffix('*') {
+        Some(prefix) => file_name.starts_with(prefix),
+        None => file_name == cached,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stage_subcommands_and_global_flags_parse() {
+        let cli =
+            Cli::try_parse_from(["datagen", "links", "--force", "process", "--pretty"]).unwrap();
+        assert_eq!(cli.command.unwrap().stage(), Some(Stage::Links));
+        assert_eq!(cli.pipeline.force, [Stage::Process]);
+        assert!(cli.pipeline.pretty);
+
+        let cli = Cli::try_parse_from(["datagen"]).unwrap();
+        assert!(cli.command.is_none());
+
+        let cli = Cli::try_parse_from(["datagen", "--skip", "artists"]).unwrap();
+        assert!(cli.pipeline.validate().is_err());
+    }
+
+    #[test]
+    fn ranking_caches_are_matched_by_prefix() {
+        assert!(is_cached_as("genre_top_artists.json", "genre_top_artists*"));
+        assert!(is_cached_as(
+            "genre_top_artists_pageviews.json",
+            "genre_top_artists*"
+        ));
+        assert!(is_cached_as("genres", "genres"));
+        assert!(!is_cached_as("genres.json", "genres"));
+    }
+}
@@ -149,6 +149,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[allow(clippy::manual_range_contains)]
     fn circular_mean_wraps_correctly() {
         // 350° and 10° should average to ≈0°, not 180°
         let mean = weighted_circular_mean_hue(&[(350.0, 1.0), (10.0, 1.0)]);
@@ -0,0 +1,216 @@
+//! Community detection for the genre graph.
+//!
+//! Groups genres into clusters of densely-connected genres with the Louvain
+//! method, so that the frontend can colour and filter by cluster without its
+//! own heuristics. The algorithm:
+//!
+//! 1. Start with every node in its own community.
+//! 2. Move each node (in index order) into the neighbouring community that
+//!    most increases modularity, until no move helps.
+//! 3. Merge each community into a single node, and repeat from 2 on the merged
+//!    graph until nothing moves.
+//!
+//! Nodes are visited in a fixed order and ties go to the lowest community, so
+//! the result is deterministic.
+
+/// Detect communities in an undirected graph of `num_nodes` nodes.
+///
+/// `edges` is a list of `(source, target)` pairs; direction and duplicates are
+/// ignored. Returns the community of each node, numbered from the largest
+/// community down (ties broken by their lowest-indexed member).
+pub fn detect(num_nodes: usize, edges: &[(usize, usize)]) -> Vec<usize> {
+    // Weighted adjacency lists for the graph being partitioned. A self-loop
+    // holds the weight of the edges inside a merged community (counted from
+    // both ends), so that each node's degree is the sum of its row.
+    let mut graph: Vec<Vec<(usize, f64)>> = vec![vec![]; num_nodes];
+    {
+        let mut unique: Vec<(usize, usize)> = edges
+            .iter()
+            .filter(|(a, b)| a != b)
+            .map(|&(a, b)| (a.min(b), a.max(b)))
+            .collect();
+        unique.sort_unstable();
+        unique.dedup();
+        for (a, b) in unique {
+            graph[a].push((b, 1.0));
+            graph[b].push((a, 1.0));
+        }
+    }
+
+    // The community of each original node
+    let mut membership: Vec<usize> = (0..num_nodes).collect();
+    loop {
+        let communities = move_nodes(&graph);
+        let (communities, count) = renumber(&communities);
+        if count == graph.len() {
+            break;
+        }
+        for community in &mut membership {
+            *community = communities[*community];
+        }
+        graph = aggregate(&graph, &communities, count);
+    }
+
+    // Number the final communities from largest to smallest
+    let mut sizes: Vec<(usize, usize, usize)> = vec![(0, usize::MAX, 0); num_nodes];
+    for (node, &community) in membership.iter().enumerate() {
+        let entry = &mut sizes[community];
+        entry.0 += 1;
+        entry.1 = entry.1.min(node);
+        entry.2 = community;
+    }
+    sizes.retain(|(size, _, _)| *size > 0);
+    sizes.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    let mut order = vec![0; num_nodes];
+    for (rank, (_, _, community)) in sizes.iter().enumerate() {
+        order[*community] = rank;
+    }
+    membership
+        .iter()
+        .map(|community| order[*community])
+        .collect()
+}
+
+/// The local moving phase: repeatedly move each node into the neighbouring
+/// community with the best modularity gain. Returns each node's community.
+fn move_nodes(graph: &[Vec<(usize, f64)>]) -> Vec<usize> {
+    // Guards against oscillation on pathological inputs
+    const MAX_PASSES: usize = 100;
+
+    let degrees: Vec<f64> = graph
+        .iter()
+        .map(|neighbours| neighbours.iter().map(|(_, w)| w).sum())
+        .collect();
+    let total_weight: f64 = degrees.iter().sum();
+    let mut community: Vec<usize> = (0..graph.len()).collect();
+    if total_weight == 0.0 {
+        return community;
+    }
+    // The sum of the degrees of each community's members
+    let mut community_degree = degrees.clone();
+    // Scratch space for the weight from the current node to each community
+    let mut weight_to = vec![0.0; graph.len()];
+
+    for _ in 0..MAX_PASSES {
+        let mut moved = false;
+        for node in 0..graph.len() {
+            let current = community[node];
+            let mut candidates = vec![current];
+            for &(neighbour, weight) in &graph[node] {
+                if neighbour == node {
+                    continue;
+                }
+                let c = community[neighbour];
+                if weight_to[c] == 0.0 && c != current {
+                    candidates.push(c);
+                }
+                weight_to[c] += weight;
+            }
+
+            community_degree[current] -= degrees[node];
+            let gain = |c: usize| weight_to[c] - community_degree[c] * degrees[node] / total_weight;
+            let mut best = current;
+            let mut best_gain = gain(current);
+            candidates.sort_unstable();
+            for &c in &candidates {
+                let g = gain(c);
+                if g > best_gain + 1e-12 {
+                    best = c;
+                    best_gain = g;
+                }
+            }
+            community_degree[best] += degrees[node];
+            if best != current {
+                community[node] = best;
+                moved = true;
+            }
+
+            for c in candidates {
+                weight_to[c] = 0.0;
+            }
+        }
+        if !moved {
+            break;
+        }
+    }
+    community
+}
+
+/// Renumber communities to be contiguous from 0, in order of first appearance.
+/// Returns the renumbered communities and how many there are.
+fn renumber(communities: &[usize]) -> (Vec<usize>, usize) {
+    let mut ids = vec![usize::MAX; communities.len()];
+    let mut count = 0;
+    let renumbered = communities
+        .iter()
+        .map(|&c| {
+            if ids[c] == usize::MAX {
+                ids[c] = count;
+                count += 1;
+            }
+            ids[c]
+        })
+        .collect();
+    (renumbered, count)
+}
+
+/// Merge each community of `graph` into a single node.
+fn aggregate(
+    graph: &[Vec<(usize, f64)>],
+    communities: &[usize],
+    count: usize,
+) -> Vec<Vec<(usize, f64)>> {
+    let mut weights: Vec<std::collections::BTreeMap<usize, f64>> = vec![Default::default(); count];
+    for (node, neighbours) in graph.iter().enumerate() {
+        for &(neighbour, weight) in neighbours {
+            *weights[communities[node]]
+                .entry(communities[neighbour])
+                .or_default() += weight;
+        }
+    }
+    weights
+        .into_iter()
+        .map(|neighbours| neighbours.into_iter().collect())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn separates_two_cliques_joined_by_a_bridge() {
+        let mut edges = vec![];
+        for clique in [0..4, 4..8] {
+            for a in clique.clone() {
+                for b in clique.clone().filter(|b| *b > a) {
+                    edges.push((a, b));
+                }
+            }
+        }
+        edges.push((3, 4));
+
+        let communities = detect(8, &edges);
+        assert_eq!(communities, [0, 0, 0, 0, 1, 1, 1, 1]);
+    }
+
+    #[test]
+    fn numbers_communities_by_size() {
+        // A triangle, an isolated node, and a larger clique
+        let mut edges = vec![(0, 1), (1, 2), (0, 2)];
+        for a in 4..9 {
+            for b in (a + 1)..9 {
+                edges.push((a, b));
+            }
+        }
+
+        let communities = detect(9, &edges);
+        assert_eq!(communities, [1, 1, 1, 2, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn handles_graphs_without_edges() {
+        assert_eq!(detect(3, &[]), [0, 1, 2]);
+        assert_eq!(detect(0, &[]), Vec::<usize>::new());
+    }
+}
@@ -0,0 +1,368 @@
+//! Finding the countries a genre originated in from the text of its `cultural_origins`
+//! (e.g. "Mid-1980s, Chicago, Illinois, U.S." is the United States).
+//!
+//! Places are matched as whole words against a list of countries, and of the subdivisions,
+//! cities and abbreviations that commonly stand in for them. Longer names win, so "New Mexico"
+//! is a US state rather than Mexico.
+
+/// Countries, by the name used in the output.
+const COUNTRIES: &[&str] = &[
+    "Afghanistan",
+    "Albania",
+    "Algeria",
+    "Angola",
+    "Argentina",
+    "Armenia",
+    "Australia",
+    "Austria",
+    "Azerbaijan",
+    "Bahamas",
+    "Bangladesh",
+    "Barbados",
+    "Belarus",
+    "Belgium",
+    "Belize",
+    "Benin",
+    "Bolivia",
+    "Bosnia and Herzegovina",
+    "Botswana",
+    "Brazil",
+    "Bulgaria",
+    "Burkina Faso",
+    "Burundi",
+    "Cambodia",
+    "Cameroon",
+    "Canada",
+    "Cape Verde",
+    "Chile",
+    "China",
+    "Colombia",
+    "Costa Rica",
+    "Croatia",
+    "Cuba",
+    "Cyprus",
+    "Czech Republic",
+    "Czechoslovakia",
+    "Democratic Republic of the Congo",
+    "Denmark",
+    "Dominica",
+    "Dominican Republic",
+    "Ecuador",
+    "Egypt",
+    "El Salvador",
+    "Estonia",
+    "Ethiopia",
+    "Finland",
+    "France",
+    "Gabon",
+    "Gambia",
+    "Germany",
+    "Ghana",
+    "Greece",
+    "Grenada",
+    "Guatemala",
+    "Guinea",
+    "Guinea-Bissau",
+    "Guyana",
+    "Haiti",
+    "Honduras",
+    "Hong Kong",
+    "Hungary",
+    "Iceland",
+    "India",
+    "Indonesia",
+    "Iran",
+    "Iraq",
+    "Ireland",
+    "Israel",
+    "Italy",
+    "Ivory Coast",
+    "Jamaica",
+    "Japan",
+    "Jordan",
+    "Kazakhstan",
+    "Kenya",
+    "Kuwait",
+    "Kyrgyzstan",
+    "Laos",
+    "Latvia",
+    "Lebanon",
+    "Liberia",
+    "Libya",
+    "Lithuania",
+    "Luxembourg",
+    "Madagascar",
+    "Malawi",
+    "Malaysia",
+    "Mali",
+    "Malta",
+    "Martinique",
+    "Mauritania",
+    "Mauritius",
+    "Mexico",
+    "Moldova",
+    "Mongolia",
+    "Montenegro",
+    "Morocco",
+    "Mozambique",
+    "Myanmar",
+    "Namibia",
+    "Nepal",
+    "Netherlands",
+    "New Zealand",
+    "Nicaragua",
+    "Niger",
+    "Nigeria",
+    "North Korea",
+    "North Macedonia",
+    "Norway",
+    "Oman",
+    "Pakistan",
+    "Panama",
+    "Papua New Guinea",
+    "Paraguay",
+    "Peru",
+    "Philippines",
+    "Poland",
+    "Portugal",
+    "Puerto Rico",
+    "Qatar",
+    "Republic of the Congo",
+    "Romania",
+    "Russia",
+    "Rwanda",
+    "Saudi Arabia",
+    "Senegal",
+    "Serbia",
+    "Sierra Leone",
+    "Singapore",
+    "Slovakia",
+    "Slovenia",
+    "Somalia",
+    "South Africa",
+    "South Korea",
+    "Soviet Union",
+    "Spain",
+    "Sri Lanka",
+    "Sudan",
+    "Suriname",
+    "Sweden",
+    "Switzerland",
+    "Syria",
+    "Taiwan",
+    "Tajikistan",
+    "Tanzania",
+    "Thailand",
+    "Togo",
+    "Trinidad and Tobago",
+    "Tunisia",
+    "Turkey",
+    "Turkmenistan",
+    "Uganda",
+    "Ukraine",
+    "United Arab Emirates",
+    "United Kingdom",
+    "United States",
+    "Uruguay",
+    "Uzbekistan",
+    "Venezuela",
+    "Vietnam",
+    "Yemen",
+    "Yugoslavia",
+    "Zambia",
+    "Zimbabwe",
+];
+
+/// Other names for places in a country: `(name, country)`. Names without lowercase letters
+/// (abbreviations like "US") are matched case-sensitively.
+const PLACES: &[(&str, &str)] = &[
+    // Abbreviations and alternative names
+    ("US", "United States"),
+    ("U.S.", "United States"),
+    ("USA", "United States"),
+    ("U.S.A.", "United States"),
+    ("United States of America", "United States"),
+    ("UK", "United Kingdom"),
+    ("U.K.", "United Kingdom"),
+    ("Britain", "United Kingdom"),
+    ("Great Britain", "United Kingdom"),
+    ("USSR", "Soviet Union"),
+    ("Côte d'Ivoire", "Ivory Coast"),
+    ("Burma", "Myanmar"),
+    ("Holland", "Netherlands"),
+    ("Czechia", "Czech Republic"),
+    ("Macedonia", "North Macedonia"),
+    ("DR Congo", "Democratic Republic of the Congo"),
+    ("Belgian Congo", "Democratic Republic of the Congo"),
+    ("Zaire", "Democratic Republic of the Congo"),
+    ("Persia", "Iran"),
+    ("Trinidad", "Trinidad and Tobago"),
+    ("Tobago", "Trinidad and Tobago"),
+    // Countries of the United Kingdom
+    ("England", "United Kingdom"),
+    ("Scotland", "United Kingdom"),
+    ("Wales", "United Kingdom"),
+    ("Northern Ireland", "United Kingdom"),
+    // Regions that would otherwise match a country
+    ("New South Wales", "Australia"),
+    ("New England", "United States"),
+    // States of the United States
+    ("Alabama", "United States"),
+    ("Alaska", "United States"),
+    ("Arizona", "United States"),
+    ("Arkansas", "United States"),
+    ("California", "United States"),
+    ("Colorado", "United States"),
+    ("Connecticut", "United States"),
+    ("Delaware", "United States"),
+    ("Florida", "United States"),
+    ("Georgia", "United States"),
+    ("Hawaii", "United States"),
+    ("Idaho", "United States"),
+    ("Illinois", "United States"),
+    ("Indiana", "United States"),
+    ("Iowa", "United States"),
+    ("Kansas", "United States"),
+    ("Kentucky", "United States"),
+    ("Louisiana", "United States"),
+    ("Maine", "United States"),
+    ("Maryland", "United States"),
+    ("Massachusetts", "United States"),
+    ("Michigan", "United States"),
+    ("Minnesota", "United States"),
+    ("Mississippi", "United States"),
+    ("Missouri", "United States"),
+    ("Montana", "United States"),
+    ("Nebraska", "United States"),
+    ("Nevada", "United States"),
+    ("New Hampshire", "United States"),
+    ("New Jersey", "United States"),
+    ("New Mexico", "United States"),
+    ("New York", "United States"),
+    ("North Carolina", "United States"),
+    ("North Dakota", "United States"),
+    ("Ohio", "United States"),
+    ("Oklahoma", "United States"),
+    ("Oregon", "United States"),
+    ("Pennsylvania", "United States"),
+    ("Rhode Island", "United States"),
+    ("South Carolina", "United States"),
+    ("South Dakota", "United States"),
+    ("Tennessee", "United States"),
+    ("Texas", "United States"),
+    ("Utah", "United States"),
+    ("Vermont", "United States"),
+    ("Virginia", "United States"),
+    ("Washington", "United States"),
+    ("West Virginia", "United States"),
+    ("Wisconsin", "United States"),
+    ("Wyoming", "United States"),
+    // Cities that are often named without their country
+    ("Atlanta", "United States"),
+    ("Chicago", "United States"),
+    ("Detroit", "United States"),
+    ("Los Angeles", "United States"),
+    ("Memphis", "United States"),
+    ("Nashville", "United States"),
+    ("New Orleans", "United States"),
+    ("Seattle", "United States"),
+    ("London", "United Kingdom"),
+    ("Liverpool", "United Kingdom"),
+    ("Manchester", "United Kingdom"),
+    ("Bristol", "United Kingdom"),
+    ("Berlin", "Germany"),
+    ("Paris", "France"),
+    ("Tokyo", "Japan"),
+    ("Kingston", "Jamaica"),
+    ("Rio de Janeiro", "Brazil"),
+    ("Buenos Aires", "Argentina"),
+    ("Havana", "Cuba"),
+    ("Lagos", "Nigeria"),
+    ("Seoul", "South Korea"),
+];
+
+/// Find the countries mentioned in the text of a genre's `cultural_origins`, in order of first
+/// mention.
+pub fn parse(cultural_origins: &str) -> Vec<String> {
+    let names: Vec<(Vec<&str>, bool, &str)> = COUNTRIES
+        .iter()
+        .map(|country| (*country, *country))
+        .chain(PLACES.iter().copied())
+        .map(|(name, country)| {
+            let case_sensitive = !name.chars().any(char::is_lowercase);
+            (words(name), case_sensitive, country)
+        })
+        .collect();
+    let text = words(cultural_origins);
+
+    let mut countries: Vec<String> = vec![];
+    let mut i = 0;
+    while i < text.len() {
+        let matched = names
+            .iter()
+            .filter(|(name, case_sensitive, _)| {
+                text.get(i..i + name.len()).is_some_and(|candidate| {
+                    candidate.iter().zip(name).all(|(a, b)| {
+                        if *case_sensitive {
+                            a == b
+                        } else {
+                            a.to_lowercase() == b.to_lowercase()
+                        }
+                    })
+                })
+            })
+            .max_by_key(|(name, _, _)| name.len());
+        let Some((name, _, country)) = matched else {
+            i += 1;
+            continue;
+        };
+        if !countries.iter().any(|c| c == country) {
+            countries.push(country.to_string());
+        }
+        i += name.len();
+    }
+    countries
+}
+
+fn words(text: &str) -> Vec<&str> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_countries_through_places_and_abbreviations() {
+        assert_eq!(
+            parse("Mid-1980s, Chicago, Illinois, U.S."),
+            ["United States"]
+        );
+        assert_eq!(
+            parse("Late 1970s, Kingston, Jamaica and London, England"),
+            ["Jamaica", "United Kingdom"]
+        );
+        assert_eq!(parse("1990s, Côte d'Ivoire"), ["Ivory Coast"]);
+    }
+
+    #[test]
+    fn prefers_the_longest_name() {
+        assert_eq!(parse("Santa Fe, New Mexico"), ["United States"]);
+        assert_eq!(parse("Sydney, New South Wales"), ["Australia"]);
+        assert_eq!(
+            parse("Port Moresby, Papua New Guinea"),
+            ["Papua New Guinea"]
+        );
+    }
+
+    #[test]
+    fn matches_abbreviations_case_sensitively() {
+        assert_eq!(
+            parse("Spread by word of mouth among us"),
+            Vec::<String>::new()
+        );
+        assert_eq!(parse("Detroit, Michigan, US"), ["United States"]);
+    }
+}
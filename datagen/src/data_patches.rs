@@ -3,126 +3,683 @@
 
 use std::{
     collections::{BTreeMap, BTreeSet},
+    path::Path,
     str::FromStr,
 };
 
-use jiff::Timestamp;
+use anyhow::Context as _;
+use jiff::{Timestamp, ToSpan as _};
+use serde::{Deserialize, Serialize};
 
 use crate::{
     frontend_types::EdgeType,
     types::{ArtistName, GenreName, PageName},
 };
 
-/// Pages to ignore when processing Wikipedia.
-pub fn pages_to_ignore() -> Vec<PageName> {
-    [
-        // Redefines jazz as a genre; redundant with the "Jazz" article
-        ("Outline of jazz", None),
-        // Redefines disco as a genre; redundant with the "Disco" article"
-        ("History of disco", None),
-        // The "Styles of pop music" page redefined these genres instead of linking to
-        // dedicated articles with subsections describing them (including their infoboxes).
-        // I've fixed this in <https://en.wikipedia.org/w/index.php?title=Styles_of_pop_music&oldid=1288729877>,
-        // but I'm explicitly ignoring them here so that we have a solution until the next dump (after 2025-05-04).
-        //
-        // Redefines pop soul, which already has a subarticle.
-        ("Styles of pop music", Some("Pop soul / Motown")),
-        // Redefines street pop, which already has a subarticle.
-        ("Styles of pop music", Some("Street pop")),
-        // Redefines post-industrial, which already has a subarticle.
-        ("List of industrial music genres", Some("Post-Industrial")),
-    ]
-    .into_iter()
-    .map(|(page, subheading)| PageName::new(page, subheading.map(String::from)))
-    .collect()
+/// The directory of the TOML files with the page patches: the pages to exclude or include, and
+/// the data to use for genres and artists instead of the data in the dump.
+///
+/// Each Wikipedia edition's patches are in a directory named for its language (e.g. `en`, see
+/// [`language`]), and the patches for every edition, keyed by Wikidata item, are in
+/// [`SHARED_PATCHES`].
+pub const PATCHES_PATH: &str = "datagen/patches";
+
+/// The directory in [`PATCHES_PATH`] with the patches shared by every edition.
+pub const SHARED_PATCHES: &str = "shared";
+
+/// The patch files in [`PATCHES_PATH`], built in so that the patches can be loaded when it isn't
+/// there (e.g. in tests, which run from the crate's directory).
+const BUILTIN_PATCHES: &[(&str, &str)] = &[
+    (
+        "en/exclude.toml",
+        include_str!("../patches/en/exclude.toml"),
+    ),
+    ("en/genres.toml", include_str!("../patches/en/genres.toml")),
+    (
+        "shared/genres.toml",
+        include_str!("../patches/shared/genres.toml"),
+    ),
+];
+
+/// The language of the Wikipedia edition with the database `wikipedia_db_name` (e.g. `en` for
+/// `enwiki`), which its patches are organized by.
+pub fn language(wikipedia_db_name: &str) -> &str {
+    wikipedia_db_name
+        .strip_suffix("wiki")
+        .unwrap_or(wikipedia_db_name)
+}
+
+/// A patch, and when the fix was applied to Wikipedia, if it has been.
+pub type Patch<T> = (Option<Timestamp>, T);
+
+/// One of the relationship lists in a genre's infobox.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Relationship {
+    /// `stylistic_origins`
+    StylisticOrigin,
+    /// `derivatives`
+    Derivative,
+    /// `subgenres`
+    Subgenre,
+    /// `fusiongenres`
+    FusionGenre,
+}
+
+/// A patch to a processed genre, other than its name.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GenrePatch {
+    /// Use this description (as wikitext) instead of the one from the page.
+    Description(String),
+    /// Use this origin decade instead of the one parsed from the cultural origins.
+    OriginDecade(i32),
+    /// Add a link missing from a relationship list.
+    AddRelationship(Relationship, String),
+    /// Remove a wrong link from a relationship list.
+    RemoveRelationship(Relationship, String),
+}
+
+/// The type of an edge, as it's written in the patch files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum PatchEdgeType {
+    Derivative,
+    Subgenre,
+    FusionGenre,
+    Related,
+}
+impl From<PatchEdgeType> for EdgeType {
+    fn from(ty: PatchEdgeType) -> Self {
+        match ty {
+            PatchEdgeType::Derivative => EdgeType::Derivative,
+            PatchEdgeType::Subgenre => EdgeType::Subgenre,
+            PatchEdgeType::FusionGenre => EdgeType::FusionGenre,
+            PatchEdgeType::Related => EdgeType::Related,
+        }
+    }
+}
+
+/// A change to an edge between two genres whose type (or direction) is wrong, such as a
+/// subgenre listed as a derivative.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdgePatch {
+    /// The page (and heading) of the edge's source genre.
+    pub source: PageName,
+    /// The page (and heading) of the edge's target genre.
+    pub target: PageName,
+    /// The edge's type in the data.
+    pub ty: EdgeType,
+    /// The type to give it.
+    pub new_ty: EdgeType,
+    /// Whether to swap its source and target.
+    pub reverse: bool,
+    /// Why it's changed.
+    pub note: Option<String>,
+}
+
+/// Why a page is excluded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExclusionReason {
+    /// It redefines a genre (or artist) that has its own article or section.
+    Duplicate,
+    /// Its infobox isn't about a genre (or an artist).
+    NotAGenre,
+    /// Its infobox is wrong in a way that can't be patched.
+    Incorrect,
+    /// Some other reason, given in the note.
+    Other,
+}
+
+/// A page (or a section of one) to exclude when processing Wikipedia.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Exclusion {
+    /// Why it's excluded.
+    pub reason: ExclusionReason,
+    /// More about why it's excluded.
+    pub note: Option<String>,
+}
+
+/// A genre on a page (or a section of one) without an infobox, with its data given by hand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Inclusion {
+    /// The genre's name.
+    pub name: GenreName,
+    /// Why it's included.
+    pub reason: String,
+    /// When the data was taken from the page, which stands in for its last revision.
+    pub as_of: Timestamp,
+    /// The genre's description, as wikitext.
+    pub description: Option<String>,
+    /// The decade the genre originated in.
+    pub origin_decade: Option<i32>,
+    /// Links to the genre's stylistic origins.
+    pub stylistic_origins: Vec<String>,
+    /// Links to the genre's derivatives.
+    pub derivatives: Vec<String>,
+    /// Links to the genre's subgenres.
+    pub subgenres: Vec<String>,
+    /// Links to the genre's fusion genres.
+    pub fusion_genres: Vec<String>,
+}
+
+/// The page patches, from all of the patch files.
+#[derive(Debug, Default)]
+pub struct DataPatches {
+    /// Pages (or sections of pages) to exclude when processing Wikipedia.
+    pub exclusions: BTreeMap<PageName, Exclusion>,
+    /// Genres to include from pages (or sections of pages) without an infobox.
+    pub inclusions: BTreeMap<PageName, Inclusion>,
+    /// Genres that are the same as another genre, on another page (or section), to merge into
+    /// it.
+    pub merges: BTreeMap<PageName, PageName>,
+    /// The patches shared by every edition, until they're resolved to pages with
+    /// [`DataPatches::resolve_shared`].
+    pub shared: SharedPatches,
+    /// The names to give the genres on these pages.
+    pub genres: BTreeMap<PageName, Patch<GenreName>>,
+    /// The names to give the artists on these pages.
+    pub artists: BTreeMap<PageName, Patch<ArtistName>>,
+    /// The other patches to the genres with these pages (including their headings), in the
+    /// order they're listed in.
+    pub genre_patches: BTreeMap<PageName, Vec<Patch<GenrePatch>>>,
+    /// The edges to reclassify or reverse, applied once the edges are known.
+    pub edge_patches: Vec<EdgePatch>,
+    /// Genres to hold at fixed positions in the layout, e.g. to anchor the broadest genres at
+    /// compass points, as `[x, y]` in the layout's output coordinates (where the connected
+    /// genres are spread over an RMS radius of about 850, with +y pointing down).
+    ///
+    /// Pinned genres don't move, but still push and pull on the genres around them.
+    pub pins: BTreeMap<GenreName, [f64; 2]>,
+}
+
+/// A patch file; each can have any of the kinds of patches.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PatchFile {
+    #[serde(default)]
+    exclude: Vec<ExcludeEntry>,
+    #[serde(default)]
+    include: Vec<IncludeEntry>,
+    #[serde(default)]
+    merge: Vec<MergeEntry>,
+    #[serde(default)]
+    reclassify_edge: Vec<ReclassifyEdgeEntry>,
+    #[serde(default)]
+    genre: Vec<NameEntry>,
+    #[serde(default)]
+    artist: Vec<NameEntry>,
+    #[serde(default)]
+    description: Vec<DescriptionEntry>,
+    #[serde(default)]
+    origin: Vec<OriginEntry>,
+    #[serde(default)]
+    add_relationship: Vec<RelationshipEntry>,
+    #[serde(default)]
+    remove_relationship: Vec<RelationshipEntry>,
+    /// Genre names to their pinned positions.
+    #[serde(default)]
+    pins: BTreeMap<String, [f64; 2]>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ExcludeEntry {
+    page: String,
+    heading: Option<String>,
+    reason: ExclusionReason,
+    note: Option<String>,
 }
 
-/// All artist data patches.
-pub fn artist_all() -> BTreeMap<PageName, (Option<Timestamp>, ArtistName)> {
-    BTreeMap::new()
-}
-
-/// All genre data patches.
-pub fn genre_all() -> BTreeMap<PageName, (Option<Timestamp>, GenreName)> {
-    genre_fixed_already()
-        .into_iter()
-        .chain(genre_unclear_fixes())
-        .collect()
-}
-
-/// Patches that have already been applied to Wikipedia, but may not be
-/// in the dump being processed.
-fn genre_fixed_already() -> BTreeMap<PageName, (Option<Timestamp>, GenreName)> {
-    /// Represents a fix that has already been applied to Wikipedia
-    /// but may not be in the dump being processed.
-    struct WikipediaFix {
-        /// Timestamp when the fix was applied
-        timestamp: &'static str,
-        /// Page name and optional heading
-        page: (&'static str, Option<String>),
-        /// The correct genre name
-        name: &'static str,
-        /// Link to the Wikipedia edit or discussion
-        _link: &'static str,
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct IncludeEntry {
+    page: String,
+    heading: Option<String>,
+    name: String,
+    reason: String,
+    as_of: toml::value::Datetime,
+    description: Option<String>,
+    origin_decade: Option<i32>,
+    #[serde(default)]
+    stylistic_origins: Vec<String>,
+    #[serde(default)]
+    derivatives: Vec<String>,
+    #[serde(default)]
+    subgenres: Vec<String>,
+    #[serde(default)]
+    fusion_genres: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NameEntry {
+    page: String,
+    heading: Option<String>,
+    name: String,
+    /// When the fix was applied to Wikipedia; the patch isn't applied to later revisions.
+    fixed_at: Option<toml::value::Datetime>,
+    /// The Wikipedia edit or discussion for the fix.
+    #[allow(dead_code)]
+    link: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DescriptionEntry {
+    page: String,
+    heading: Option<String>,
+    description: String,
+    fixed_at: Option<toml::value::Datetime>,
+    #[allow(dead_code)]
+    link: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct OriginEntry {
+    page: String,
+    heading: Option<String>,
+    decade: i32,
+    fixed_at: Option<toml::value::Datetime>,
+    #[allow(dead_code)]
+    link: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RelationshipEntry {
+    page: String,
+    heading: Option<String>,
+    relationship: Relationship,
+    /// The link in the relationship list, as it would be written in the infobox.
+    target: String,
+    fixed_at: Option<toml::value::Datetime>,
+    #[allow(dead_code)]
+    link: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ReclassifyEdgeEntry {
+    source: String,
+    source_heading: Option<String>,
+    target: String,
+    target_heading: Option<String>,
+    #[serde(rename = "type")]
+    ty: PatchEdgeType,
+    /// Defaults to `type`, for edges that are only reversed.
+    new_type: Option<PatchEdgeType>,
+    #[serde(default)]
+    reverse: bool,
+    note: Option<String>,
+}
+
+/// The patches shared by every edition, keyed by the Wikidata items of their pages.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SharedPatches {
+    /// The Wikidata items to exclude.
+    #[serde(default)]
+    pub exclude: Vec<SharedExclusion>,
+    /// The origin decades of Wikidata items' genres.
+    #[serde(default)]
+    pub origin: Vec<SharedOrigin>,
+    /// The Wikidata items whose genres are merged into others.
+    #[serde(default)]
+    pub merge: Vec<SharedMerge>,
+}
+
+/// An [`Exclusion`] of a Wikidata item's page.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SharedExclusion {
+    /// The Wikidata item.
+    pub qid: String,
+    /// Why it's excluded.
+    pub reason: ExclusionReason,
+    /// More about why it's excluded.
+    pub note: Option<String>,
+}
+
+/// The origin decade of a Wikidata item's genre.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SharedOrigin {
+    /// The Wikidata item.
+    pub qid: String,
+    /// The decade the genre originated in.
+    pub decade: i32,
+}
+
+/// A merge of a Wikidata item's genre into another's.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SharedMerge {
+    /// The Wikidata item of the genre that's merged.
+    pub qid: String,
+    /// The Wikidata item of the genre it's merged into.
+    pub into: String,
+    /// Why they're the same genre.
+    pub note: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MergeEntry {
+    page: String,
+    heading: Option<String>,
+    into: String,
+    into_heading: Option<String>,
+    /// Why they're the same genre.
+    #[allow(dead_code)]
+    note: Option<String>,
+}
+
+impl DataPatches {
+    /// Load the patches for the edition in `language` and the shared patches from the `*.toml`
+    /// files in their directories in `path`, or the built-in patches if it doesn't exist.
+    pub fn load(path: &Path, language: &str) -> anyhow::Result<Self> {
+        if !path.is_dir() {
+            return Self::builtin(language);
+        }
+        let mut patches = Self::default();
+        for (directory, shared) in [(language, false), (SHARED_PATCHES, true)] {
+            let directory = path.join(directory);
+            if !directory.is_dir() {
+                continue;
+            }
+            let mut file_paths = vec![];
+            for entry in std::fs::read_dir(&directory)
+                .with_context(|| format!("Failed to read patches from {directory:?}"))?
+            {
+                let file_path = entry?.path();
+                if file_path.extension().is_some_and(|ext| ext == "toml") {
+                    file_paths.push(file_path);
+                }
+            }
+            file_paths.sort();
+
+            for file_path in file_paths {
+                let contents = std::fs::read_to_string(&file_path)
+                    .with_context(|| format!("Failed to read patches from {file_path:?}"))?;
+                patches
+                    .add_file(&contents, shared)
+                    .with_context(|| format!("Failed to parse patches from {file_path:?}"))?;
+            }
+        }
+        Ok(patches)
+    }
+
+    /// The patches for the edition in `language` and the shared patches built into the binary
+    /// from [`PATCHES_PATH`].
+    pub fn builtin(language: &str) -> anyhow::Result<Self> {
+        let mut patches = Self::default();
+        for (name, contents) in BUILTIN_PATCHES {
+            let Some((directory, _)) = name.split_once('/') else {
+                continue;
+            };
+            if directory != language && directory != SHARED_PATCHES {
+                continue;
+            }
+            patches
+                .add_file(contents, directory == SHARED_PATCHES)
+                .with_context(|| format!("Failed to parse built-in patches from {name}"))?;
+        }
+        Ok(patches)
+    }
+
+    /// Add the patches in a patch file's `contents`, which is a shared one if `shared`.
+    fn add_file(&mut self, contents: &str, shared: bool) -> anyhow::Result<()> {
+        if !shared {
+            return self.add(contents);
+        }
+        let file: SharedPatches = toml::from_str(contents)?;
+        self.shared.exclude.extend(file.exclude);
+        self.shared.origin.extend(file.origin);
+        self.shared.merge.extend(file.merge);
+        Ok(())
+    }
+
+    /// Resolve the shared patches to the pages of their Wikidata items (`wikidata_ids`, by page)
+    /// and add them to the edition's, returning the items that aren't on any page. The edition's
+    /// patches for a page take precedence over shared ones.
+    pub fn resolve_shared(
+        &mut self,
+        wikidata_ids: &BTreeMap<PageName, String>,
+    ) -> anyhow::Result<Vec<String>> {
+        let pages: BTreeMap<&str, &PageName> = wikidata_ids
+            .iter()
+            .map(|(page, qid)| (qid.as_str(), page))
+            .collect();
+        let shared = std::mem::take(&mut self.shared);
+        let mut unresolved = vec![];
+        let mut page = |qid: &str| {
+            let page = pages.get(qid).map(|page| (*page).clone());
+            if page.is_none() {
+                unresolved.push(qid.to_string());
+            }
+            page
+        };
+
+        for exclusion in shared.exclude {
+            if let Some(page) = page(&exclusion.qid) {
+                self.exclusions.entry(page).or_insert(Exclusion {
+                    reason: exclusion.reason,
+                    note: exclusion.note,
+                });
+            }
+        }
+        for origin in shared.origin {
+            // First, so that the edition's patches for the page are applied after it
+            if let Some(page) = page(&origin.qid) {
+                self.genre_patches
+                    .entry(page)
+                    .or_default()
+                    .insert(0, (None, GenrePatch::OriginDecade(origin.decade)));
+            }
+        }
+        for merge in shared.merge {
+            if let (Some(page), Some(into)) = (page(&merge.qid), page(&merge.into)) {
+                if self.merges.contains_key(&page) {
+                    continue;
+                }
+                self.add_merge(page, into).with_context(|| {
+                    format!("Failed to merge {} into {}", merge.qid, merge.into)
+                })?;
+            }
+        }
+        Ok(unresolved)
     }
-    const FIXES: &[WikipediaFix] = &[WikipediaFix {
-        timestamp: "2025-04-26T20:32:00Z",
-        page: ("Popcorn (Romanian music style)", None),
-        name: "Romanian popcorn",
-        _link: "https://en.wikipedia.org/w/index.php?title=Popcorn_(Romanian_music_style)&oldid=1287525657",
-    }];
-
-    FIXES
-        .iter()
-        .map(|fix| {
-            (
-                PageName::new(fix.page.0, fix.page.1.clone()),
+
+    /// Add the patches in a patch file's `contents`.
+    fn add(&mut self, contents: &str) -> anyhow::Result<()> {
+        let file: PatchFile = toml::from_str(contents)?;
+        for entry in file.exclude {
+            let page = PageName::new(entry.page, entry.heading);
+            anyhow::ensure!(
+                !self.exclusions.contains_key(&page),
+                "{page} is excluded more than once"
+            );
+            self.exclusions.insert(
+                page,
+                Exclusion {
+                    reason: entry.reason,
+                    note: entry.note,
+                },
+            );
+        }
+        for entry in file.merge {
+            self.add_merge(
+                PageName::new(entry.page, entry.heading),
+                PageName::new(entry.into, entry.into_heading),
+            )?;
+        }
+        for (name, position) in file.pins {
+            let name = GenreName(name);
+            anyhow::ensure!(
+                !self.pins.contains_key(&name),
+                "{} is pinned more than once",
+                name.0
+            );
+            self.pins.insert(name, position);
+        }
+        for entry in file.reclassify_edge {
+            let patch = EdgePatch {
+                source: PageName::new(entry.source, entry.source_heading),
+                target: PageName::new(entry.target, entry.target_heading),
+                ty: entry.ty.into(),
+                new_ty: entry.new_type.unwrap_or(entry.ty).into(),
+                reverse: entry.reverse,
+                note: entry.note,
+            };
+            anyhow::ensure!(
+                patch.new_ty != patch.ty || patch.reverse,
+                "The {} edge from {} to {} is reclassified as itself",
+                patch.ty.name(),
+                patch.source,
+                patch.target
+            );
+            anyhow::ensure!(
+                !self.edge_patches.iter().any(|other| {
+                    (&other.source, &other.target, other.ty)
+                        == (&patch.source, &patch.target, patch.ty)
+                }),
+                "The {} edge from {} to {} is reclassified more than once",
+                patch.ty.name(),
+                patch.source,
+                patch.target
+            );
+            self.edge_patches.push(patch);
+        }
+        for entry in file.include {
+            let page = PageName::new(entry.page, entry.heading);
+            let as_of = parse_fixed_at(&page, Some(entry.as_of))?.unwrap();
+            anyhow::ensure!(
+                !self.inclusions.contains_key(&page),
+                "{page} is included more than once"
+            );
+            self.inclusions.insert(
+                page,
+                Inclusion {
+                    name: GenreName(entry.name),
+                    reason: entry.reason,
+                    as_of,
+                    description: entry.description,
+                    origin_decade: entry.origin_decade,
+                    stylistic_origins: entry.stylistic_origins,
+                    derivatives: entry.derivatives,
+                    subgenres: entry.subgenres,
+                    fusion_genres: entry.fusion_genres,
+                },
+            );
+        }
+        add_name_patches(&mut self.genres, file.genre, GenreName)?;
+        add_name_patches(&mut self.artists, file.artist, ArtistName)?;
+
+        let genre_patches = file
+            .description
+            .into_iter()
+            .map(|entry| {
                 (
-                    Some(Timestamp::from_str(fix.timestamp).unwrap()),
-                    GenreName(fix.name.to_string()),
-                ),
-            )
-        })
-        .collect()
-}
-
-/// Patches to resolve ambiguity in the source data. I don't feel confident in making
-/// these changes myself, so I'm disambiguating them here.
-fn genre_unclear_fixes() -> BTreeMap<PageName, (Option<Timestamp>, GenreName)> {
-    /// Represents a fix to resolve ambiguity in the source data
-    struct UnclearFix {
-        /// Page name and optional heading
-        page: (&'static str, Option<&'static str>),
-        /// The disambiguated genre name
-        name: &'static str,
+                    (entry.page, entry.heading, entry.fixed_at),
+                    GenrePatch::Description(entry.description),
+                )
+            })
+            .chain(file.origin.into_iter().map(|entry| {
+                (
+                    (entry.page, entry.heading, entry.fixed_at),
+                    GenrePatch::OriginDecade(entry.decade),
+                )
+            }))
+            .chain(file.add_relationship.into_iter().map(|entry| {
+                (
+                    (entry.page, entry.heading, entry.fixed_at),
+                    GenrePatch::AddRelationship(entry.relationship, entry.target),
+                )
+            }))
+            .chain(file.remove_relationship.into_iter().map(|entry| {
+                (
+                    (entry.page, entry.heading, entry.fixed_at),
+                    GenrePatch::RemoveRelationship(entry.relationship, entry.target),
+                )
+            }));
+        for ((page, heading, fixed_at), patch) in genre_patches {
+            let page = PageName::new(page, heading);
+            let fixed_at = parse_fixed_at(&page, fixed_at)?;
+            self.genre_patches
+                .entry(page)
+                .or_default()
+                .push((fixed_at, patch));
+        }
+        Ok(())
     }
+}
 
-    const FIXES: &[UnclearFix] = &[
-        // HACK: "Calypso music" describes a genre, "Calypso", that originated in Trinidad and Tobago during the early to mid-19th century.
-        // "Brega pop" describes a genre, "Calypso", also known as "Brega Calypso" or "Brega-pop", that originated in Brazil in the 1990s.
-        // To work around this conflict, I'm renaming the latter to "Brega-pop".
-        UnclearFix {
-            page: ("Brega pop", None),
-            name: "Brega-pop",
-        },
-        // HACK: There are two genres referred to as "Popcorn" on Wikipedia. I've updated the Romanian version to "Romanian popcorn" and
-        // the Belgian version to "Belgian popcorn", but my change to the Belgian version was reverted. As a result, I've had to move it
-        // to an ambiguity fix.
-        UnclearFix {
-            page: ("Popcorn (Belgian music style)", None),
-            name: "Belgian popcorn",
-        },
-    ];
-
-    FIXES
-        .iter()
-        .map(|fix| {
-            (
-                PageName::new(fix.page.0, fix.page.1.map(String::from)),
-                (None, GenreName(fix.name.to_string())),
-            )
+impl DataPatches {
+    /// Merge the genre on `page` into the one on `into`.
+    fn add_merge(&mut self, page: PageName, into: PageName) -> anyhow::Result<()> {
+        anyhow::ensure!(page != into, "{page} is merged into itself");
+        anyhow::ensure!(
+            !self.merges.contains_key(&page),
+            "{page} is merged more than once"
+        );
+        // Merging into a genre that's merged itself would need the merges to be ordered
+        anyhow::ensure!(
+            !self.merges.contains_key(&into) && !self.merges.values().any(|i| *i == page),
+            "{page} is merged into {into}, but one of them is merged into another genre too"
+        );
+        self.merges.insert(page, into);
+        Ok(())
+    }
+}
+
+/// A time (e.g. `fixed_at`) from a patch to `page` as a [`Timestamp`].
+fn parse_fixed_at(
+    page: &PageName,
+    fixed_at: Option<toml::value::Datetime>,
+) -> anyhow::Result<Option<Timestamp>> {
+    fixed_at
+        .map(|fixed_at| {
+            Timestamp::from_str(&fixed_at.to_string())
+                .with_context(|| format!("Invalid time for {page}: {fixed_at}"))
         })
-        .collect()
+        .transpose()
+}
+
+fn add_name_patches<N>(
+    patches: &mut BTreeMap<PageName, Patch<N>>,
+    entries: Vec<NameEntry>,
+    to_name: impl Fn(String) -> N,
+) -> anyhow::Result<()> {
+    for entry in entries {
+        let page = PageName::new(entry.page, entry.heading);
+        let fixed_at = parse_fixed_at(&page, entry.fixed_at)?;
+        anyhow::ensure!(
+            !patches.contains_key(&page),
+            "{page} is patched more than once"
+        );
+        patches.insert(page, (fixed_at, to_name(entry.name)));
+    }
+    Ok(())
+}
+
+/// The patched name for `page`, if it has a patch that [`applies`] to its revision from
+/// `timestamp`.
+pub fn patched_name<'a, N>(
+    patches: &'a BTreeMap<PageName, Patch<N>>,
+    page: &PageName,
+    timestamp: Timestamp,
+) -> Option<&'a N> {
+    let (fixed_at, name) = patches.get(page)?;
+    applies(*fixed_at, timestamp).then_some(name)
+}
+
+/// Whether a patch for a fix applied to Wikipedia at `fixed_at` applies to a revision from
+/// `timestamp`: patches for fixes that haven't been applied always do, and the rest only apply to
+/// earlier revisions, with one minute of leeway.
+pub fn applies(fixed_at: Option<Timestamp>, timestamp: Timestamp) -> bool {
+    fixed_at.is_none_or(|fixed_at| timestamp.saturating_add(1.minute()) < fixed_at)
 }
 
 /// Edges confirmed incorrect that should be filtered out during datagen.
@@ -355,3 +912,298 @@ pub fn edges_to_accept() -> BTreeSet<(GenreName, GenreName, EdgeType)> {
     })
     .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_builtin_patches_are_all_of_the_patch_files() {
+        // Tests run from the crate's directory
+        let loaded = DataPatches::load(Path::new("patches"), "en").unwrap();
+        let builtin = DataPatches::builtin("en").unwrap();
+        assert!(!builtin.exclusions.is_empty() && !builtin.genres.is_empty());
+        assert_eq!(loaded.exclusions, builtin.exclusions);
+        assert_eq!(loaded.inclusions, builtin.inclusions);
+        assert_eq!(loaded.genres, builtin.genres);
+        assert_eq!(loaded.artists, builtin.artists);
+    }
+
+    #[test]
+    fn fixed_patches_only_apply_to_earlier_revisions() {
+        let mut patches = DataPatches::default();
+        patches
+            .add(
+                r#"
+                [[genre]]
+                page = "Fixed"
+                name = "Fixed name"
+                fixed_at = 2025-04-26T20:32:00Z
+
+                [[genre]]
+                page = "Unclear"
+                heading = "Section"
+                name = "Unclear name"
+                "#,
+            )
+            .unwrap();
+        let name = |page: &PageName, timestamp: &str| {
+            patched_name(&patches.genres, page, timestamp.parse().unwrap())
+                .map(|name| name.0.as_str())
+        };
+
+        let fixed = PageName::new("Fixed", None);
+        assert_eq!(name(&fixed, "2025-04-01T00:00:00Z"), Some("Fixed name"));
+        assert_eq!(name(&fixed, "2025-04-26T20:31:30Z"), None);
+        assert_eq!(name(&fixed, "2025-05-01T00:00:00Z"), None);
+
+        let unclear = PageName::new("Unclear", Some("Section".to_string()));
+        assert_eq!(name(&unclear, "2030-01-01T00:00:00Z"), Some("Unclear name"));
+        assert_eq!(
+            name(&PageName::new("Unclear", None), "2025-04-01T00:00:00Z"),
+            None
+        );
+
+        assert!(
+            patches
+                .add("[[genre]]\npage = \"Fixed\"\nname = \"Again\"")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn genre_patches_are_kept_in_order_by_page() {
+        let mut patches = DataPatches::default();
+        patches
+            .add(
+                r#"
+                [[description]]
+                page = "Techno"
+                description = "A '''genre'''."
+
+                [[origin]]
+                page = "Techno"
+                decade = 1980
+                fixed_at = 2025-01-01T00:00:00Z
+
+                [[add_relationship]]
+                page = "Techno"
+                relationship = "stylistic_origin"
+                target = "Chicago house"
+
+                [[remove_relationship]]
+                page = "Acid techno"
+                heading = "History"
+                relationship = "fusion_genre"
+                target = "Acoustic music"
+                "#,
+            )
+            .unwrap();
+
+        assert_eq!(
+            patches.genre_patches[&PageName::new("Techno", None)],
+            [
+                (None, GenrePatch::Description("A '''genre'''.".to_string())),
+                (
+                    Some("2025-01-01T00:00:00Z".parse().unwrap()),
+                    GenrePatch::OriginDecade(1980)
+                ),
+                (
+                    None,
+                    GenrePatch::AddRelationship(
+                        Relationship::StylisticOrigin,
+                        "Chicago house".to_string()
+                    )
+                ),
+            ]
+        );
+        assert_eq!(
+            patches.genre_patches[&PageName::new("Acid techno", Some("History".to_string()))],
+            [(
+                None,
+                GenrePatch::RemoveRelationship(
+                    Relationship::FusionGenre,
+                    "Acoustic music".to_string()
+                )
+            )]
+        );
+        assert!(
+            patches
+                .add("[[origin]]\npage = \"Techno\"\ndecade = \"1980s\"")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn inclusions_exclusions_and_merges_are_checked() {
+        let mut patches = DataPatches::default();
+        patches
+            .add(
+                r#"
+                [[include]]
+                page = "Styles of pop music"
+                heading = "Sophisti-pop"
+                name = "Sophisti-pop"
+                reason = "The section describes the genre, but has no infobox"
+                as_of = 2025-05-01T00:00:00Z
+                stylistic_origins = ["New wave", "Jazz"]
+
+                [[exclude]]
+                page = "Outline of jazz"
+                reason = "duplicate"
+                "#,
+            )
+            .unwrap();
+
+        let inclusion =
+            &patches.inclusions[&PageName::new("Styles of pop music", Some("Sophisti-pop".into()))];
+        assert_eq!(inclusion.name, GenreName("Sophisti-pop".to_string()));
+        assert_eq!(inclusion.as_of, "2025-05-01T00:00:00Z".parse().unwrap());
+        assert_eq!(inclusion.stylistic_origins, ["New wave", "Jazz"]);
+        assert!(inclusion.derivatives.is_empty());
+        assert_eq!(
+            patches.exclusions[&PageName::new("Outline of jazz", None)],
+            Exclusion {
+                reason: ExclusionReason::Duplicate,
+                note: None
+            }
+        );
+
+        // Exclusions need a reason, and inclusions need a name and a date
+        assert!(patches.add("[[exclude]]\npage = \"Techno\"").is_err());
+        assert!(
+            patches
+                .add("[[exclude]]\npage = \"Techno\"\nreason = \"dislike\"")
+                .is_err()
+        );
+        assert!(
+            patches
+                .add("[[include]]\npage = \"Techno\"\nname = \"Techno\"\nreason = \"\"")
+                .is_err()
+        );
+
+        // Merges can't be chained
+        patches
+            .add("[[merge]]\npage = \"Neo soul (music)\"\ninto = \"Soul music\"\ninto_heading = \"Neo soul\"")
+            .unwrap();
+        assert_eq!(
+            patches.merges[&PageName::new("Neo soul (music)", None)],
+            PageName::new("Soul music", Some("Neo soul".to_string()))
+        );
+        assert!(
+            patches
+                .add("[[merge]]\npage = \"Neo-soul\"\ninto = \"Neo soul (music)\"")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn shared_patches_apply_to_the_pages_of_their_wikidata_items() {
+        let mut patches = DataPatches::default();
+        patches
+            .add_file(
+                r#"
+                [[exclude]]
+                qid = "Q1"
+                reason = "not_a_genre"
+
+                [[origin]]
+                qid = "Q2"
+                decade = 1980
+
+                [[merge]]
+                qid = "Q3"
+                into = "Q2"
+
+                [[origin]]
+                qid = "Q4"
+                decade = 1990
+                "#,
+                true,
+            )
+            .unwrap();
+        assert!(patches.exclusions.is_empty());
+
+        let page = |name: &str| PageName::new(name, None);
+        let wikidata_ids = [
+            (page("Hardvapour"), "Q1".to_string()),
+            (page("Acid house"), "Q2".to_string()),
+            (page("Acid House"), "Q3".to_string()),
+        ]
+        .into();
+        assert_eq!(patches.resolve_shared(&wikidata_ids).unwrap(), ["Q4"]);
+        assert_eq!(
+            patches.exclusions[&page("Hardvapour")].reason,
+            ExclusionReason::NotAGenre
+        );
+        assert_eq!(
+            patches.genre_patches[&page("Acid house")],
+            [(None, GenrePatch::OriginDecade(1980))]
+        );
+        assert_eq!(patches.merges[&page("Acid House")], page("Acid house"));
+    }
+
+    #[test]
+    fn edge_patches_must_change_their_edge_once() {
+        let mut patches = DataPatches::default();
+        let edge = "[[reclassify_edge]]\nsource = \"House music\"\ntarget = \"Deep house\"\ntype = \"derivative\"\n";
+        assert!(patches.add(edge).is_err());
+        patches
+            .add(&format!("{edge}new_type = \"subgenre\""))
+            .unwrap();
+        assert_eq!(
+            patches.edge_patches,
+            [EdgePatch {
+                source: PageName::new("House music", None),
+                target: PageName::new("Deep house", None),
+                ty: EdgeType::Derivative,
+                new_ty: EdgeType::Subgenre,
+                reverse: false,
+                note: None,
+            }]
+        );
+        assert!(patches.add(&format!("{edge}reverse = true")).is_err());
+    }
+
+    #[test]
+    fn pinned_genres_keep_their_positions_through_the_layout() {
+        let mut patches = DataPatches::default();
+        patches
+            .add("[pins]\n\"Rock music\" = [100.0, -50.0]\n")
+            .unwrap();
+        assert!(
+            patches
+                .add("[pins]\n\"Rock music\" = [0.0, 0.0]\n")
+                .is_err()
+        );
+
+        // A ring of genres, one of which is pinned
+        let labels: Vec<GenreName> = (0..12)
+            .map(|i| match i {
+                4 => GenreName("Rock music".to_string()),
+                _ => GenreName(format!("Genre {i}")),
+            })
+            .collect();
+        let adjacency: Vec<(usize, usize)> = (0..12).map(|i| (i, (i + 1) % 12)).collect();
+        let pins: Vec<Option<[f64; 2]>> = labels
+            .iter()
+            .map(|label| patches.pins.get(label).copied())
+            .collect();
+        let input = layout::LayoutInput {
+            num_nodes: labels.len(),
+            adjacency: &adjacency,
+            edge_types: &[],
+            communities: &[],
+            pins: &pins,
+            initial_positions: &[],
+            seed: 0,
+            initialization: Default::default(),
+            precision: Default::default(),
+        };
+        let positions = layout::Algorithm::default()
+            .layout()
+            .compute(&input, &mut |_| {});
+        assert_eq!(positions[4], [100.0, -50.0]);
+        assert_ne!(positions[5], positions[4]);
+    }
+}
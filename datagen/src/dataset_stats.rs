@@ -0,0 +1,186 @@
+//! Summary statistics for the dataset (`stats.json`), for the website's "about" page and for
+//! spotting regressions between dumps at a glance.
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::{export::NodeDetails, frontend_types::FrontendData};
+
+/// Summary statistics for the dataset.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct DatasetStats {
+    /// The domain of the Wikipedia the dump is from.
+    pub wikipedia_domain: String,
+    /// The name of the Wikipedia database the dump is from.
+    pub wikipedia_db_name: String,
+    /// The date of the dump.
+    pub dump_date: String,
+    /// The number of genres.
+    pub node_count: usize,
+    /// The number of edges.
+    pub edge_count: usize,
+    /// The number of edges of each type.
+    pub edges_by_type: BTreeMap<&'static str, usize>,
+    /// The number of genres with each degree.
+    pub degree_distribution: BTreeMap<usize, usize>,
+    /// The highest degree of any genre.
+    pub max_degree: usize,
+    /// The average degree of a genre.
+    pub average_degree: f64,
+    /// The number of genres without any edges.
+    pub isolated_node_count: usize,
+    /// The number of genres with a description.
+    pub genres_with_description: usize,
+    /// The number of genres with at least one mix.
+    pub genres_with_mixes: usize,
+    /// The number of genres with each number of top artists.
+    pub artists_per_genre: BTreeMap<usize, usize>,
+}
+impl DatasetStats {
+    /// Calculate the statistics for `graph`. `details` and `has_description` are indexed by
+    /// node ID.
+    pub fn calculate(
+        graph: &FrontendData,
+        details: &[NodeDetails],
+        has_description: &[bool],
+    ) -> Self {
+        let mut edges_by_type = BTreeMap::new();
+        let mut degrees = vec![0; graph.nodes.len()];
+        for edge in &graph.edges {
+            *edges_by_type.entry(edge.ty.name()).or_default() += 1;
+            degrees[edge.source.0] += 1;
+            degrees[edge.target.0] += 1;
+        }
+
+        let mut degree_distribution = BTreeMap::new();
+        for degree in &degrees {
+            *degree_distribution.entry(*degree).or_default() += 1;
+        }
+
+        let mut artists_per_genre = BTreeMap::new();
+        for details in details {
+            *artists_per_genre
+                .entry(details.top_artists.len())
+                .or_default() += 1;
+        }
+
+        Self {
+            wikipedia_domain: graph.wikipedia_domain.clone(),
+            wikipedia_db_name: graph.wikipedia_db_name.clone(),
+            dump_date: graph.dump_date.clone(),
+            node_count: graph.nodes.len(),
+            edge_count: graph.edges.len(),
+            edges_by_type,
+            degree_distribution,
+            max_degree: degrees.iter().copied().max().unwrap_or(0),
+            average_degree: if graph.nodes.is_empty() {
+                0.0
+            } else {
+                2.0 * graph.edges.len() as f64 / graph.nodes.len() as f64
+            },
+            isolated_node_count: degrees.iter().filter(|degree| **degree == 0).count(),
+            genres_with_description: has_description.iter().filter(|d| **d).count(),
+            genres_with_mixes: details.iter().filter(|d| !d.mixes.is_empty()).count(),
+            artists_per_genre,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+    use crate::{
+        frontend_types::{EdgeData, EdgeType, NodeData},
+        types::{GenreMix, GenreName, PageDataId, PageName},
+    };
+
+    #[test]
+    fn counts_edges_degrees_and_coverage() {
+        let node = |label: &str| NodeData {
+            page_title: None,
+            label: GenreName(label.to_string()),
+            aliases: vec![],
+            links: 0,
+            popularity: 0,
+            x: 0.0,
+            y: 0.0,
+            z: None,
+            hue: 0.0,
+            community: 0,
+            in_degree: 0,
+            out_degree: 0,
+            pagerank: 0.0,
+            betweenness: 0.0,
+            depth: None,
+            wikidata_qid: None,
+            musicbrainz_genre_id: None,
+        };
+        let edge = |source, target, ty| EdgeData {
+            source: PageDataId(source),
+            target: PageDataId(target),
+            ty,
+            corroborated: false,
+        };
+        let graph = FrontendData {
+            wikipedia_domain: "en.wikipedia.org".to_string(),
+            wikipedia_db_name: "enwiki".to_string(),
+            dump_date: "2026-02-01".to_string(),
+            nodes: vec![node("House"), node("Techno"), node("Trance"), node("Polka")],
+            edges: BTreeSet::from_iter([
+                edge(0, 1, EdgeType::Derivative),
+                edge(1, 2, EdgeType::Derivative),
+                edge(0, 2, EdgeType::Related),
+            ]),
+            max_degree: 2,
+            communities: vec![],
+            sharded_files: false,
+            layout_seed: None,
+        };
+        let details = |top_artists: usize, mixes: bool| NodeDetails {
+            page: PageName::new("Genre", None),
+            origin_decade: None,
+            cluster: None,
+            top_artists: vec![PageName::new("Artist", None); top_artists],
+            mixes: if mixes {
+                vec![GenreMix::Video {
+                    video: "abc123".to_string(),
+                    note: None,
+                    details: Default::default(),
+                }]
+            } else {
+                vec![]
+            },
+            last_revision_date: jiff::Timestamp::UNIX_EPOCH,
+        };
+        let details = vec![
+            details(10, true),
+            details(10, true),
+            details(3, false),
+            details(0, false),
+        ];
+
+        let stats = DatasetStats::calculate(&graph, &details, &[true, true, true, false]);
+
+        assert_eq!(stats.node_count, 4);
+        assert_eq!(stats.edge_count, 3);
+        assert_eq!(
+            stats.edges_by_type,
+            BTreeMap::from_iter([("derivative", 2), ("related", 1)])
+        );
+        assert_eq!(
+            stats.degree_distribution,
+            BTreeMap::from_iter([(0, 1), (2, 3)])
+        );
+        assert_eq!(stats.max_degree, 2);
+        assert_eq!(stats.average_degree, 1.5);
+        assert_eq!(stats.isolated_node_count, 1);
+        assert_eq!(stats.genres_with_description, 3);
+        assert_eq!(stats.genres_with_mixes, 2);
+        assert_eq!(
+            stats.artists_per_genre,
+            BTreeMap::from_iter([(0, 1), (3, 1), (10, 2)])
+        );
+    }
+}
@@ -0,0 +1,184 @@
+//! Rendering descriptions to sanitized HTML ahead of time (`description_html = true` in
+//! `[output]`), so that the website can show them without parsing their wikitext.
+//!
+//! Only what the website renders the same way everywhere is supported: text, formatting, links
+//! and lists. Descriptions with anything else (templates in particular, which the website
+//! renders with its own components) are left for the website to render from the wikitext.
+use wikitext_simplified::{Spanned, WikitextSimplifiedNode as Node};
+use wikitext_util::parse_wiki_text_2 as pwt;
+
+use crate::types::PageDataId;
+
+/// Renders descriptions to HTML.
+pub struct Renderer<'a> {
+    pwt_configuration: &'a pwt::Configuration,
+    /// The base URL of Wikipedia pages (e.g. "https://en.wikipedia.org/wiki").
+    wiki_url: String,
+    /// The genre a link leads to, if any; those links are to the genre on the website.
+    genre_for_link: &'a dyn Fn(&str) -> Option<PageDataId>,
+}
+impl<'a> Renderer<'a> {
+    /// Create a renderer for descriptions from the Wikipedia at `wikipedia_domain`.
+    pub fn new(
+        pwt_configuration: &'a pwt::Configuration,
+        wikipedia_domain: &str,
+        genre_for_link: &'a dyn Fn(&str) -> Option<PageDataId>,
+    ) -> Self {
+        Self {
+            pwt_configuration,
+            wiki_url: format!("https://{wikipedia_domain}/wiki"),
+            genre_for_link,
+        }
+    }
+
+    /// Render `wikitext` to HTML, one string per paragraph: it's split at line breaks, like the
+    /// website does to show only the first paragraph. Returns `None` if the wikitext has
+    /// anything that can't be rendered.
+    pub fn render(&self, wikitext: &str) -> Option<Vec<String>> {
+        let mut paragraphs = vec![];
+        let mut paragraph = String::new();
+        for node in self.simplify(wikitext)? {
+            if matches!(node.value, Node::ParagraphBreak | Node::Newline) {
+                if !paragraph.trim().is_empty() {
+                    paragraphs.push(std::mem::take(&mut paragraph));
+                }
+                paragraph.clear();
+            } else {
+                self.render_node(&node.value, &mut paragraph)?;
+            }
+        }
+        if !paragraph.trim().is_empty() {
+            paragraphs.push(paragraph);
+        }
+        Some(paragraphs)
+    }
+
+    fn simplify(&self, wikitext: &str) -> Option<Vec<Spanned<Node>>> {
+        let parsed = self
+            .pwt_configuration
+            .parse_with_timeout(wikitext, std::time::Duration::from_secs(1))
+            .ok()?;
+        wikitext_simplified::simplify_wikitext_nodes(wikitext, &parsed.nodes).ok()
+    }
+
+    fn render_nodes(&self, nodes: &[Spanned<Node>], out: &mut String) -> Option<()> {
+        for node in nodes {
+            self.render_node(&node.value, out)?;
+        }
+        Some(())
+    }
+
+    fn render_node(&self, node: &Node, out: &mut String) -> Option<()> {
+        match node {
+            Node::Fragment { children, .. } => self.render_nodes(children, out)?,
+            Node::Bold { children, .. } => self.render_element("strong", children, out)?,
+            Node::Italic { children, .. } => self.render_element("em", children, out)?,
+            Node::Superscript { children, .. } => self.render_element("sup", children, out)?,
+            Node::Subscript { children, .. } => self.render_element("sub", children, out)?,
+            Node::Small { children, .. } => self.render_element("small", children, out)?,
+            Node::Text { text, .. } => out.push_str(&escape(text)),
+            Node::Link { text, title, .. } => {
+                match (self.genre_for_link)(title) {
+                    // The website selects genres by the ID in the URL's fragment
+                    Some(id) => out.push_str(&format!(r##"<a href="#{}">"##, id.0)),
+                    None => out.push_str(&format!(
+                        r#"<a href="{}/{}" target="_blank" rel="noopener noreferrer">"#,
+                        self.wiki_url,
+                        escape(&title.replace(' ', "_"))
+                    )),
+                }
+                self.render_nodes(&self.simplify(text)?, out)?;
+                out.push_str("</a>");
+            }
+            Node::ExtLink { link, text, .. } => {
+                if !is_web_url(link) {
+                    return None;
+                }
+                out.push_str(&format!(
+                    r#"<a href="{}" target="_blank" rel="noopener noreferrer">"#,
+                    escape(link)
+                ));
+                match text {
+                    Some(text) => self.render_nodes(&self.simplify(text)?, out)?,
+                    None => out.push_str(&escape(link)),
+                }
+                out.push_str("</a>");
+            }
+            Node::UnorderedList { items, .. } | Node::OrderedList { items, .. } => {
+                let tag = if matches!(node, Node::OrderedList { .. }) {
+                    "ol"
+                } else {
+                    "ul"
+                };
+                out.push_str(&format!("<{tag}>"));
+                for item in items {
+                    self.render_element("li", &item.content, out)?;
+                }
+                out.push_str(&format!("</{tag}>"));
+            }
+            Node::ParagraphBreak => out.push_str("<br><br>"),
+            Node::Newline => out.push_str("<br>"),
+            _ => return None,
+        }
+        Some(())
+    }
+
+    fn render_element(
+        &self,
+        tag: &str,
+        children: &[Spanned<Node>],
+        out: &mut String,
+    ) -> Option<()> {
+        out.push_str(&format!("<{tag}>"));
+        self.render_nodes(children, out)?;
+        out.push_str(&format!("</{tag}>"));
+        Some(())
+    }
+}
+
+/// Escape text for use in HTML, including in attribute values.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Whether `link` is safe to link to: an absolute `http(s)` or protocol-relative URL, rather
+/// than something like `javascript:`.
+fn is_web_url(link: &str) -> bool {
+    let lower = link.trim_start().to_ascii_lowercase();
+    ["http://", "https://", "//"]
+        .iter()
+        .any(|scheme| lower.starts_with(scheme))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_markup_and_quotes() {
+        assert_eq!(
+            escape(r#"<script>alert("Rock & 'roll'")</script>"#),
+            "&lt;script&gt;alert(&quot;Rock &amp; &#39;roll&#39;&quot;)&lt;/script&gt;"
+        );
+    }
+
+    #[test]
+    fn only_links_to_the_web() {
+        assert!(is_web_url("https://example.com/techno"));
+        assert!(is_web_url("//example.com"));
+        assert!(!is_web_url("javascript:alert(1)"));
+        assert!(!is_web_url(" JavaScript:alert(1)"));
+        assert!(!is_web_url("data:text/html,hi"));
+    }
+}
@@ -0,0 +1,651 @@
+//! Compares two website output directories (e.g. from consecutive dumps, or from before and after
+//! a pipeline change on the same dump) and writes a changelog of what changed between them, and
+//! an Atom feed of the new and rewritten genres for following them.
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write as _,
+    path::Path,
+};
+
+use anyhow::Context as _;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    description_html,
+    export::{WEBSITE_URL, escape_xml},
+    frontend_types::FrontendData,
+    types::{PageDataId, PageName},
+};
+
+/// What's compared about a genre.
+#[derive(Debug, Clone, PartialEq)]
+struct GenreSummary {
+    /// The genre's ID, which only identifies it within its output.
+    id: PageDataId,
+    label: String,
+    description: Option<String>,
+    top_artists: Vec<String>,
+}
+
+/// What's compared about an output directory: its genres by page, and its edges as
+/// `(source page, target page, type)`.
+#[derive(Debug, Default)]
+struct DatasetSummary {
+    dump_date: String,
+    wikipedia_domain: String,
+    genres: BTreeMap<String, GenreSummary>,
+    edges: BTreeSet<(String, String, &'static str)>,
+}
+
+/// The parts of a genre file that are compared.
+#[derive(Deserialize)]
+struct GenreFile {
+    description: Option<String>,
+    #[serde(default)]
+    top_artists: Vec<PageName>,
+}
+
+/// A genre whose label or page changed.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct RenamedGenre {
+    /// The genre's page before.
+    pub old_page: String,
+    /// The genre's label before.
+    pub old_label: String,
+    /// The genre's page now.
+    pub page: String,
+    /// The genre's label now.
+    pub label: String,
+}
+
+/// An edge that was added or removed, by the labels of its genres.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct EdgeChange {
+    /// The label of the source genre.
+    pub source: String,
+    /// The label of the target genre.
+    pub target: String,
+    /// The type of the edge (e.g. `derivative`).
+    #[serde(rename = "type")]
+    pub ty: &'static str,
+}
+
+/// Changes to a genre's top artists.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct TopArtistChanges {
+    /// Artists that are now among the genre's top artists.
+    pub added: Vec<String>,
+    /// Artists that are no longer among the genre's top artists.
+    pub removed: Vec<String>,
+}
+
+/// The changes between two output directories. Genres are referred to by their current label.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Changelog {
+    /// The dump date of the old output.
+    pub old_dump_date: String,
+    /// The dump date of the new output.
+    pub new_dump_date: String,
+    /// Genres that are new.
+    pub added_genres: Vec<String>,
+    /// Genres that no longer exist, by their old label.
+    pub removed_genres: Vec<String>,
+    /// Genres whose label changed, or whose page moved to one with the same label.
+    pub renamed_genres: Vec<RenamedGenre>,
+    /// Edges that are new.
+    pub added_edges: Vec<EdgeChange>,
+    /// Edges that no longer exist, by the old labels of their genres.
+    pub removed_edges: Vec<EdgeChange>,
+    /// Genres whose description changed.
+    pub changed_descriptions: Vec<String>,
+    /// Genres whose description changed so much that it's been rewritten rather than edited,
+    /// or that have a description for the first time.
+    pub rewritten_descriptions: Vec<String>,
+    /// Genres whose top artists changed.
+    pub top_artist_changes: BTreeMap<String, TopArtistChanges>,
+}
+
+/// How much of their words a genre's old and new descriptions can have in common for the new one
+/// to count as a rewrite.
+const REWRITE_SIMILARITY: f64 = 0.5;
+
+/// Compare the output in `old_path` with the output in `new_path`, and write the changes to
+/// `changelog.json` and `changelog.md` in `new_path`, and the new and rewritten genres to
+/// `feed.xml`.
+pub fn run(old_path: &Path, new_path: &Path) -> anyhow::Result<()> {
+    let old = DatasetSummary::read(old_path)?;
+    let new = DatasetSummary::read(new_path)?;
+    let changelog = Changelog::between(&old, &new);
+
+    std::fs::write(
+        new_path.join("changelog.json"),
+        serde_json::to_string_pretty(&changelog)?,
+    )
+    .context("Failed to write changelog.json")?;
+    let markdown = changelog.to_markdown();
+    std::fs::write(new_path.join("changelog.md"), &markdown)
+        .context("Failed to write changelog.md")?;
+    std::fs::write(new_path.join("feed.xml"), atom_feed(&changelog, &new))
+        .context("Failed to write feed.xml")?;
+    println!("{markdown}");
+
+    Ok(())
+}
+
+impl DatasetSummary {
+    fn read(path: &Path) -> anyhow::Result<Self> {
+        let (graph, _) = FrontendData::read(path)?;
+
+        // Genre files may have content hashes in their names
+        let paths_path = path.join("paths.json");
+        let paths: BTreeMap<String, String> = if paths_path.is_file() {
+            serde_json::from_slice(&std::fs::read(&paths_path)?)
+                .with_context(|| format!("Failed to parse {paths_path:?}"))?
+        } else {
+            BTreeMap::new()
+        };
+
+        let mut pages = vec![];
+        let mut genres = BTreeMap::new();
+        for (index, node) in graph.nodes.iter().enumerate() {
+            let page = node
+                .page_title
+                .clone()
+                .unwrap_or_else(|| node.label.0.clone());
+            let page_name: PageName = page.parse().unwrap();
+            let genre_path = page_name.data_file_path("genres", graph.sharded_files);
+            let genre_path = path.join(paths.get(&genre_path).unwrap_or(&genre_path));
+            let genre: GenreFile = serde_json::from_slice(
+                &std::fs::read(&genre_path)
+                    .with_context(|| format!("Failed to read {genre_path:?}"))?,
+            )
+            .with_context(|| format!("Failed to parse {genre_path:?}"))?;
+
+            pages.push(page.clone());
+            genres.insert(
+                page,
+                GenreSummary {
+                    id: PageDataId(index),
+                    label: node.label.0.clone(),
+                    description: genre.description,
+                    top_artists: genre.top_artists.iter().map(|a| a.to_string()).collect(),
+                },
+            );
+        }
+
+        let page = |id: PageDataId| pages[id.0].clone();
+        let edges = graph
+            .edges
+            .iter()
+            .map(|edge| (page(edge.source), page(edge.target), edge.ty.name()))
+            .collect();
+
+        Ok(Self {
+            dump_date: graph.dump_date,
+            wikipedia_domain: graph.wikipedia_domain,
+            genres,
+            edges,
+        })
+    }
+}
+
+impl Changelog {
+    fn between(old: &DatasetSummary, new: &DatasetSummary) -> Self {
+        let mut added: BTreeSet<&String> = new
+            .genres
+            .keys()
+            .filter(|page| !old.genres.contains_key(*page))
+            .collect();
+        let mut removed: BTreeSet<&String> = old
+            .genres
+            .keys()
+            .filter(|page| !new.genres.contains_key(*page))
+            .collect();
+
+        // Maps old pages to new pages, for genres whose page moved
+        let mut moved_pages = BTreeMap::new();
+        let mut renamed_genres = vec![];
+        for old_page in removed.clone() {
+            let label = &old.genres[old_page].label;
+            if let Some(new_page) = added
+                .iter()
+                .copied()
+                .find(|page| new.genres[*page].label == *label)
+            {
+                added.remove(new_page);
+                removed.remove(old_page);
+                moved_pages.insert(old_page.clone(), new_page.clone());
+            }
+        }
+        let current_page =
+            |old_page: &String| moved_pages.get(old_page).unwrap_or(old_page).clone();
+
+        let mut changed_descriptions = vec![];
+        let mut rewritten_descriptions = vec![];
+        let mut top_artist_changes = BTreeMap::new();
+        for (old_page, old_genre) in &old.genres {
+            let page = current_page(old_page);
+            let Some(genre) = new.genres.get(&page) else {
+                continue;
+            };
+            if genre.label != old_genre.label || page != *old_page {
+                renamed_genres.push(RenamedGenre {
+                    old_page: old_page.clone(),
+                    old_label: old_genre.label.clone(),
+                    page: page.clone(),
+                    label: genre.label.clone(),
+                });
+            }
+            if genre.description != old_genre.description {
+                changed_descriptions.push(genre.label.clone());
+                if is_rewrite(
+                    old_genre.description.as_deref(),
+                    genre.description.as_deref(),
+                ) {
+                    rewritten_descriptions.push(genre.label.clone());
+                }
+            }
+            let added_artists: Vec<String> = genre
+                .top_artists
+                .iter()
+                .filter(|a| !old_genre.top_artists.contains(a))
+                .cloned()
+                .collect();
+            let removed_artists: Vec<String> = old_genre
+                .top_artists
+                .iter()
+                .filter(|a| !genre.top_artists.contains(a))
+                .cloned()
+                .collect();
+            if !added_artists.is_empty() || !removed_artists.is_empty() {
+                top_artist_changes.insert(
+                    genre.label.clone(),
+                    TopArtistChanges {
+                        added: added_artists,
+                        removed: removed_artists,
+                    },
+                );
+            }
+        }
+
+        let old_edges: BTreeSet<(String, String, &'static str)> = old
+            .edges
+            .iter()
+            .map(|(source, target, ty)| (current_page(source), current_page(target), *ty))
+            .collect();
+        let edge_change =
+            |genres: &BTreeMap<String, GenreSummary>,
+             old_pages: &BTreeMap<String, String>,
+             (source, target, ty): &(String, String, &'static str)| {
+                let label = |page: &String| {
+                    genres
+                        .get(old_pages.get(page).unwrap_or(page))
+                        .map_or_else(|| page.clone(), |genre| genre.label.clone())
+                };
+                EdgeChange {
+                    source: label(source),
+                    target: label(target),
+                    ty,
+                }
+            };
+        // Removed edges are described by their old labels, so current pages are mapped back
+        let old_pages: BTreeMap<String, String> = moved_pages
+            .iter()
+            .map(|(old_page, new_page)| (new_page.clone(), old_page.clone()))
+            .collect();
+        let added_edges = new
+            .edges
+            .difference(&old_edges)
+            .map(|edge| edge_change(&new.genres, &BTreeMap::new(), edge))
+            .collect();
+        let removed_edges = old_edges
+            .difference(&new.edges)
+            .map(|edge| edge_change(&old.genres, &old_pages, edge))
+            .collect();
+
+        Self {
+            old_dump_date: old.dump_date.clone(),
+            new_dump_date: new.dump_date.clone(),
+            added_genres: added.iter().map(|p| new.genres[*p].label.clone()).collect(),
+            removed_genres: removed
+                .iter()
+                .map(|p| old.genres[*p].label.clone())
+                .collect(),
+            renamed_genres,
+            added_edges,
+            removed_edges,
+            changed_descriptions,
+            rewritten_descriptions,
+            top_artist_changes,
+        }
+    }
+
+    /// Describe the changes in Markdown.
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        writeln!(
+            out,
+            "# Changes from {} to {}",
+            self.old_dump_date, self.new_dump_date
+        )
+        .unwrap();
+
+        let mut section = |title: &str, items: Vec<String>| {
+            if items.is_empty() {
+                return;
+            }
+            writeln!(out, "\n## {title} ({})\n", items.len()).unwrap();
+            for item in items {
+                writeln!(out, "- {item}").unwrap();
+            }
+        };
+        let edge = |edge: &EdgeChange| format!("{} → {} ({})", edge.source, edge.target, edge.ty);
+
+        section("Added genres", self.added_genres.clone());
+        section("Removed genres", self.removed_genres.clone());
+        section(
+            "Renamed genres",
+            self.renamed_genres
+                .iter()
+                .map(|r| {
+                    if r.old_label == r.label {
+                        format!("{}: moved from {} to {}", r.label, r.old_page, r.page)
+                    } else {
+                        format!("{} → {}", r.old_label, r.label)
+                    }
+                })
+                .collect(),
+        );
+        section("Added edges", self.added_edges.iter().map(edge).collect());
+        section(
+            "Removed edges",
+            self.removed_edges.iter().map(edge).collect(),
+        );
+        section("Changed descriptions", self.changed_descriptions.clone());
+        section(
+            "Changed top artists",
+            self.top_artist_changes
+                .iter()
+                .map(|(genre, changes)| {
+                    let mut parts = vec![];
+                    if !changes.added.is_empty() {
+                        parts.push(format!("added {}", changes.added.join(", ")));
+                    }
+                    if !changes.removed.is_empty() {
+                        parts.push(format!("removed {}", changes.removed.join(", ")));
+                    }
+                    format!("{genre}: {}", parts.join("; "))
+                })
+                .collect(),
+        );
+        out
+    }
+}
+
+/// Whether `new` has so few of the words in `old` in common that it's a rewrite, rather than an
+/// edit. A description where there was none counts.
+fn is_rewrite(old: Option<&str>, new: Option<&str>) -> bool {
+    let words = |text: Option<&str>| -> BTreeSet<String> {
+        text.unwrap_or_default()
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .collect()
+    };
+    let (old, new) = (words(old), words(new));
+    let all = old.union(&new).count();
+    all > 0 && (old.intersection(&new).count() as f64) < all as f64 * REWRITE_SIMILARITY
+}
+
+/// An Atom feed of the genres that are new in `new`, and those whose descriptions were
+/// rewritten, with the first paragraph of their descriptions where it can be rendered.
+fn atom_feed(changelog: &Changelog, new: &DatasetSummary) -> String {
+    let pwt_configuration = wikitext_util::wikipedia_pwt_configuration();
+    let genre_for_link = |title: &str| new.genres.get(title).map(|genre| genre.id);
+    let renderer =
+        description_html::Renderer::new(&pwt_configuration, &new.wikipedia_domain, &genre_for_link);
+    let by_label: BTreeMap<&str, (&String, &GenreSummary)> = new
+        .genres
+        .iter()
+        .map(|(page, genre)| (genre.label.as_str(), (page, genre)))
+        .collect();
+
+    let updated = format!("{}T00:00:00Z", new.dump_date);
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    // Genre links in the descriptions are relative to the website
+    writeln!(
+        out,
+        "<feed xmlns=\"http://www.w3.org/2005/Atom\" xml:base=\"{WEBSITE_URL}\">"
+    )
+    .unwrap();
+    writeln!(out, "  <title>New genres on genresin.space</title>").unwrap();
+    writeln!(out, "  <id>{WEBSITE_URL}feed.xml</id>").unwrap();
+    writeln!(out, "  <link rel=\"self\" href=\"{WEBSITE_URL}feed.xml\"/>").unwrap();
+    writeln!(out, "  <link href=\"{WEBSITE_URL}\"/>").unwrap();
+    writeln!(out, "  <updated>{updated}</updated>").unwrap();
+    writeln!(out, "  <author><name>genresin.space</name></author>").unwrap();
+
+    let entries = changelog
+        .added_genres
+        .iter()
+        .map(|label| ("added", "New genre", label))
+        .chain(
+            changelog
+                .rewritten_descriptions
+                .iter()
+                .filter(|label| !changelog.added_genres.contains(label))
+                .map(|label| ("rewritten", "Rewritten", label)),
+        );
+    for (kind, heading, label) in entries {
+        let Some((page, genre)) = by_label.get(label.as_str()) else {
+            continue;
+        };
+        writeln!(out, "  <entry>").unwrap();
+        writeln!(out, "    <title>{heading}: {}</title>", escape_xml(label)).unwrap();
+        // Tag URIs stay the same however the genre's ID changes in later outputs
+        writeln!(
+            out,
+            "    <id>tag:genresin.space,{}:{kind}/{}</id>",
+            new.dump_date,
+            escape_xml(&page.replace(' ', "_"))
+        )
+        .unwrap();
+        writeln!(out, "    <link href=\"{WEBSITE_URL}#{}\"/>", genre.id.0).unwrap();
+        writeln!(out, "    <updated>{updated}</updated>").unwrap();
+        writeln!(out, "    <category term=\"{kind}\"/>").unwrap();
+        if let Some(paragraph) = genre
+            .description
+            .as_deref()
+            .and_then(|description| renderer.render(description))
+            .and_then(|paragraphs| paragraphs.into_iter().next())
+        {
+            writeln!(
+                out,
+                "    <summary type=\"html\">{}</summary>",
+                escape_xml(&paragraph)
+            )
+            .unwrap();
+        }
+        writeln!(out, "  </entry>").unwrap();
+    }
+    out.push_str("</feed>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn genre(label: &str, description: &str, top_artists: &[&str]) -> GenreSummary {
+        GenreSummary {
+            id: PageDataId(0),
+            label: label.to_string(),
+            description: Some(description.to_string()),
+            top_artists: top_artists.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+
+    fn dataset(
+        dump_date: &str,
+        genres: &[(&str, GenreSummary)],
+        edges: &[(&str, &str, &'static str)],
+    ) -> DatasetSummary {
+        DatasetSummary {
+            dump_date: dump_date.to_string(),
+            wikipedia_domain: "en.wikipedia.org".to_string(),
+            genres: genres
+                .iter()
+                .map(|(page, genre)| (page.to_string(), genre.clone()))
+                .collect(),
+            edges: edges
+                .iter()
+                .map(|(source, target, ty)| (source.to_string(), target.to_string(), *ty))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn finds_added_removed_and_renamed_genres_and_edges() {
+        let old = dataset(
+            "2026-01-01",
+            &[
+                (
+                    "House music",
+                    genre("House", "A genre.", &["Frankie Knuckles"]),
+                ),
+                ("Techno", genre("Techno", "A genre.", &[])),
+                ("Jungle music", genre("Jungle", "A genre.", &[])),
+                ("Polka", genre("Polka", "A dance.", &[])),
+            ],
+            &[
+                ("House music", "Techno", "derivative"),
+                ("Jungle music", "Techno", "related"),
+                ("Polka", "Techno", "related"),
+            ],
+        );
+        let new = dataset(
+            "2026-02-01",
+            &[
+                (
+                    "House music",
+                    genre(
+                        "House music",
+                        "A genre.",
+                        &["Frankie Knuckles", "Larry Heard"],
+                    ),
+                ),
+                ("Techno", genre("Techno", "A different genre.", &[])),
+                ("Jungle (music)", genre("Jungle", "A genre.", &[])),
+                ("Trance music", genre("Trance", "A genre.", &[])),
+            ],
+            &[
+                ("House music", "Techno", "derivative"),
+                ("Jungle (music)", "Techno", "related"),
+                ("Techno", "Trance music", "derivative"),
+            ],
+        );
+
+        let changelog = Changelog::between(&old, &new);
+
+        assert_eq!(changelog.added_genres, ["Trance"]);
+        assert_eq!(changelog.removed_genres, ["Polka"]);
+        assert_eq!(
+            changelog.renamed_genres,
+            [
+                RenamedGenre {
+                    old_page: "House music".to_string(),
+                    old_label: "House".to_string(),
+                    page: "House music".to_string(),
+                    label: "House music".to_string(),
+                },
+                RenamedGenre {
+                    old_page: "Jungle music".to_string(),
+                    old_label: "Jungle".to_string(),
+                    page: "Jungle (music)".to_string(),
+                    label: "Jungle".to_string(),
+                },
+            ]
+        );
+        // The moved page's edge is unchanged
+        assert_eq!(
+            changelog.added_edges,
+            [EdgeChange {
+                source: "Techno".to_string(),
+                target: "Trance".to_string(),
+                ty: "derivative",
+            }]
+        );
+        assert_eq!(
+            changelog.removed_edges,
+            [EdgeChange {
+                source: "Polka".to_string(),
+                target: "Techno".to_string(),
+                ty: "related",
+            }]
+        );
+        assert_eq!(changelog.changed_descriptions, ["Techno"]);
+        assert!(changelog.rewritten_descriptions.is_empty());
+        assert_eq!(
+            changelog.top_artist_changes,
+            BTreeMap::from_iter([(
+                "House music".to_string(),
+                TopArtistChanges {
+                    added: vec!["Larry Heard".to_string()],
+                    removed: vec![],
+                }
+            )])
+        );
+
+        let markdown = changelog.to_markdown();
+        assert!(markdown.starts_with("# Changes from 2026-01-01 to 2026-02-01\n"));
+        assert!(markdown.contains("\n## Renamed genres (2)\n\n- House → House music\n"));
+        assert!(markdown.contains("- Jungle: moved from Jungle music to Jungle (music)\n"));
+        assert!(markdown.contains("- House music: added Larry Heard\n"));
+    }
+
+    #[test]
+    fn rewrites_share_few_words() {
+        assert!(!is_rewrite(
+            Some("Techno is a genre of electronic dance music."),
+            Some("Techno is a genre of electronic music.")
+        ));
+        assert!(is_rewrite(
+            Some("Techno is a genre."),
+            Some("A style of dance music from Detroit, with repetitive beats.")
+        ));
+        assert!(is_rewrite(None, Some("Techno is a genre.")));
+        assert!(!is_rewrite(None, None));
+    }
+
+    #[test]
+    fn feed_lists_new_and_rewritten_genres() {
+        let old = dataset(
+            "2026-01-01",
+            &[("Techno", genre("Techno", "A genre.", &[]))],
+            &[],
+        );
+        let mut new = dataset(
+            "2026-02-01",
+            &[
+                (
+                    "Techno",
+                    genre("Techno", "Electronic dance music from Detroit.", &[]),
+                ),
+                ("Drum and bass", genre("Drum & bass", "A genre.", &[])),
+            ],
+            &[],
+        );
+        new.genres.get_mut("Drum and bass").unwrap().id = PageDataId(1);
+
+        let changelog = Changelog::between(&old, &new);
+        assert_eq!(changelog.rewritten_descriptions, ["Techno"]);
+
+        let feed = atom_feed(&changelog, &new);
+        assert!(feed.contains("<updated>2026-02-01T00:00:00Z</updated>"));
+        assert!(feed.contains("<title>New genre: Drum &amp; bass</title>"));
+        assert!(feed.contains("<id>tag:genresin.space,2026-02-01:added/Drum_and_bass</id>"));
+        assert!(feed.contains("<link href=\"https://genresin.space/#1\"/>"));
+        assert!(feed.contains("<title>Rewritten: Techno</title>"));
+        assert_eq!(feed.matches("<entry>").count(), 2);
+    }
+}
@@ -0,0 +1,1087 @@
+//! Exports the genre graph in formats that other tools can open directly (GraphML for Gephi and
+//! yEd, GEXF for animating it in Gephi, CSV for spreadsheets and pandas, Parquet for DuckDB and
+//! dataframes, SQLite for everything else, and JSON-LD for knowledge-graph tooling), so the
+//! dataset can be used without scraping `data.json`. Also writes the sitemap that points search
+//! engines at each genre.
+use std::{fmt::Write as _, path::Path};
+
+use anyhow::Context as _;
+
+use crate::{
+    frontend_types::FrontendData,
+    og_cards,
+    types::{GenreMix, PageDataId, PageName},
+};
+
+/// Information about a node that's exported, but isn't part of the frontend data.
+pub struct NodeDetails {
+    /// The genre's page.
+    pub page: PageName,
+    /// The decade the genre originated in, if known.
+    pub origin_decade: Option<i32>,
+    /// The cluster the genre belongs to: the first of its navbox families.
+    pub cluster: Option<String>,
+    /// The genre's top artists, best first.
+    pub top_artists: Vec<PageName>,
+    /// The genre's mixes.
+    pub mixes: Vec<GenreMix>,
+    /// When the genre's page was last edited.
+    pub last_revision_date: jiff::Timestamp,
+}
+
+/// An artist that's exported to the SQLite database.
+pub struct ArtistDetails {
+    /// The artist's page.
+    pub page: PageName,
+    /// The artist's name.
+    pub name: String,
+    /// The number of inbound links to the artist's page.
+    pub links: usize,
+    /// The Wikidata item ID of the artist's page, if it has one.
+    pub wikidata_qid: Option<String>,
+    /// The genres the artist is associated with.
+    pub genres: Vec<PageDataId>,
+}
+
+/// Write the graph as GraphML. `details` is indexed by node ID.
+pub fn graphml(graph: &FrontendData, details: &[NodeDetails]) -> String {
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+    for (id, domain, ty) in [
+        ("label", "node", "string"),
+        ("page", "node", "string"),
+        ("origin_decade", "node", "int"),
+        ("cluster", "node", "string"),
+        ("links", "node", "int"),
+        ("x", "node", "double"),
+        ("y", "node", "double"),
+        ("hue", "node", "double"),
+        ("type", "edge", "string"),
+    ] {
+        writeln!(
+            out,
+            "  <key id=\"{id}\" for=\"{domain}\" attr.name=\"{id}\" attr.type=\"{ty}\"/>"
+        )
+        .unwrap();
+    }
+    writeln!(out, "  <graph id=\"genres\" edgedefault=\"directed\">").unwrap();
+
+    for (index, (node, details)) in graph.nodes.iter().zip(details).enumerate() {
+        writeln!(out, "    <node id=\"{}\">", node_id(PageDataId(index))).unwrap();
+        let mut data = |key: &str, value: &str| {
+            writeln!(
+                out,
+                "      <data key=\"{key}\">{}</data>",
+                escape_xml(value)
+            )
+            .unwrap();
+        };
+        data("label", &node.label.0);
+        data("page", &details.page.to_string());
+        if let Some(decade) = details.origin_decade {
+            data("origin_decade", &decade.to_string());
+        }
+        if let Some(cluster) = &details.cluster {
+            data("cluster", cluster);
+        }
+        data("links", &node.links.to_string());
+        data("x", &node.x.to_string());
+        data("y", &node.y.to_string());
+        data("hue", &node.hue.to_string());
+        writeln!(out, "    </node>").unwrap();
+    }
+
+    for (index, edge) in graph.edges.iter().enumerate() {
+        writeln!(
+            out,
+            "    <edge id=\"e{index}\" source=\"{}\" target=\"{}\">",
+            node_id(edge.source),
+            node_id(edge.target)
+        )
+        .unwrap();
+        writeln!(out, "      <data key=\"type\">{}</data>", edge.ty.name()).unwrap();
+        writeln!(out, "    </edge>").unwrap();
+    }
+
+    out.push_str("  </graph>\n</graphml>\n");
+    out
+}
+
+/// Write the graph as GEXF. `details` is indexed by node ID.
+///
+/// Nodes with a known origin decade start in that decade, and edges start once both of their
+/// genres exist, so Gephi's timeline can animate the graph growing over time.
+pub fn gexf(graph: &FrontendData, details: &[NodeDetails]) -> String {
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(
+        "<gexf xmlns=\"http://gexf.net/1.3\" xmlns:viz=\"http://gexf.net/1.3/viz\" version=\"1.3\">\n",
+    );
+    writeln!(
+        out,
+        "  <meta lastmodifieddate=\"{}\">\n    <creator>genresin.space</creator>\n    <description>Music genres on {} and their relationships</description>\n  </meta>",
+        escape_xml(&graph.dump_date),
+        escape_xml(&graph.wikipedia_domain)
+    )
+    .unwrap();
+    out.push_str(
+        "  <graph mode=\"dynamic\" defaultedgetype=\"directed\" timeformat=\"integer\">\n",
+    );
+    out.push_str("    <attributes class=\"node\" mode=\"static\">\n");
+    for (id, ty) in [
+        ("page", "string"),
+        ("origin_decade", "integer"),
+        ("cluster", "string"),
+        ("links", "integer"),
+    ] {
+        writeln!(
+            out,
+            "      <attribute id=\"{id}\" title=\"{id}\" type=\"{ty}\"/>"
+        )
+        .unwrap();
+    }
+    out.push_str("    </attributes>\n");
+    out.push_str("    <attributes class=\"edge\" mode=\"static\">\n");
+    out.push_str("      <attribute id=\"type\" title=\"type\" type=\"string\"/>\n");
+    out.push_str("    </attributes>\n");
+
+    out.push_str("    <nodes>\n");
+    for (index, (node, details)) in graph.nodes.iter().zip(details).enumerate() {
+        write!(
+            out,
+            "      <node id=\"{}\" label=\"{}\"",
+            node_id(PageDataId(index)),
+            escape_xml(&node.label.0)
+        )
+        .unwrap();
+        if let Some(decade) = details.origin_decade {
+            write!(out, " start=\"{decade}\"").unwrap();
+        }
+        out.push_str(">\n        <attvalues>\n");
+        let mut attribute = |id: &str, value: &str| {
+            writeln!(
+                out,
+                "          <attvalue for=\"{id}\" value=\"{}\"/>",
+                escape_xml(value)
+            )
+            .unwrap();
+        };
+        attribute("page", &details.page.to_string());
+        if let Some(decade) = details.origin_decade {
+            attribute("origin_decade", &decade.to_string());
+        }
+        if let Some(cluster) = &details.cluster {
+            attribute("cluster", cluster);
+        }
+        attribute("links", &node.links.to_string());
+        out.push_str("        </attvalues>\n");
+        writeln!(
+            out,
+            "        <viz:position x=\"{}\" y=\"{}\" z=\"0\"/>\n      </node>",
+            node.x, node.y
+        )
+        .unwrap();
+    }
+    out.push_str("    </nodes>\n");
+
+    out.push_str("    <edges>\n");
+    for (index, edge) in graph.edges.iter().enumerate() {
+        write!(
+            out,
+            "      <edge id=\"e{index}\" source=\"{}\" target=\"{}\"",
+            node_id(edge.source),
+            node_id(edge.target)
+        )
+        .unwrap();
+        let decade = |id: PageDataId| details.get(id.0).and_then(|d| d.origin_decade);
+        if let Some(start) = decade(edge.source).max(decade(edge.target)) {
+            write!(out, " start=\"{start}\"").unwrap();
+        }
+        writeln!(
+            out,
+            ">\n        <attvalues>\n          <attvalue for=\"type\" value=\"{}\"/>\n        </attvalues>\n      </edge>",
+            edge.ty.name()
+        )
+        .unwrap();
+    }
+    out.push_str("    </edges>\n");
+
+    out.push_str("  </graph>\n</gexf>\n");
+    out
+}
+
+/// Write the nodes as CSV, with their ID in the frontend data. `details` is indexed by node ID.
+pub fn nodes_csv(graph: &FrontendData, details: &[NodeDetails]) -> String {
+    let mut out = String::from("id,label,page,decade,cluster\n");
+    for (index, (node, details)) in graph.nodes.iter().zip(details).enumerate() {
+        writeln!(
+            out,
+            "{index},{},{},{},{}",
+            escape_csv(&node.label.0),
+            escape_csv(&details.page.to_string()),
+            details
+                .origin_decade
+                .map(|decade| decade.to_string())
+                .unwrap_or_default(),
+            escape_csv(details.cluster.as_deref().unwrap_or_default())
+        )
+        .unwrap();
+    }
+    out
+}
+
+/// Write the edges as CSV, referring to nodes by their ID in [`nodes_csv`].
+pub fn edges_csv(graph: &FrontendData) -> String {
+    let mut out = String::from("source,target,type\n");
+    for edge in &graph.edges {
+        writeln!(
+            out,
+            "{},{},{}",
+            edge.source.0,
+            edge.target.0,
+            edge.ty.name()
+        )
+        .unwrap();
+    }
+    out
+}
+
+/// Write the dataset as a SQLite database at `path`, replacing any existing one. `details` is
+/// indexed by node ID.
+pub fn sqlite(
+    path: &Path,
+    graph: &FrontendData,
+    details: &[NodeDetails],
+    artists: &[ArtistDetails],
+) -> anyhow::Result<()> {
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+    let mut connection =
+        rusqlite::Connection::open(path).with_context(|| format!("Failed to create {path:?}"))?;
+    write_sqlite(&mut connection, graph, details, artists)
+        .with_context(|| format!("Failed to write {path:?}"))
+}
+
+/// The schema of the database written by [`sqlite`].
+const SQLITE_SCHEMA: &str = "
+CREATE TABLE metadata (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+CREATE TABLE nodes (
+    id INTEGER PRIMARY KEY,
+    label TEXT NOT NULL,
+    page TEXT NOT NULL,
+    origin_decade INTEGER,
+    cluster TEXT,
+    links INTEGER NOT NULL,
+    x REAL NOT NULL,
+    y REAL NOT NULL,
+    hue REAL NOT NULL,
+    wikidata_qid TEXT,
+    musicbrainz_genre_id TEXT
+);
+CREATE TABLE edges (
+    source INTEGER NOT NULL REFERENCES nodes (id),
+    target INTEGER NOT NULL REFERENCES nodes (id),
+    type TEXT NOT NULL
+);
+CREATE TABLE artists (
+    page TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    links INTEGER NOT NULL,
+    wikidata_qid TEXT
+);
+CREATE TABLE genre_artists (
+    genre INTEGER NOT NULL REFERENCES nodes (id),
+    artist TEXT NOT NULL REFERENCES artists (page),
+    rank INTEGER NOT NULL,
+    PRIMARY KEY (genre, artist)
+);
+CREATE TABLE aliases (genre INTEGER NOT NULL REFERENCES nodes (id), alias TEXT NOT NULL);
+CREATE TABLE mixes (
+    genre INTEGER NOT NULL REFERENCES nodes (id),
+    kind TEXT NOT NULL,
+    youtube_id TEXT,
+    note TEXT,
+    url TEXT NOT NULL
+);
+CREATE INDEX nodes_page ON nodes (page);
+CREATE INDEX nodes_origin_decade ON nodes (origin_decade);
+CREATE INDEX nodes_cluster ON nodes (cluster);
+CREATE INDEX nodes_wikidata_qid ON nodes (wikidata_qid);
+CREATE INDEX edges_source ON edges (source);
+CREATE INDEX edges_target ON edges (target);
+CREATE INDEX genre_artists_artist ON genre_artists (artist);
+CREATE INDEX aliases_genre ON aliases (genre);
+CREATE INDEX aliases_alias ON aliases (alias COLLATE NOCASE);
+CREATE INDEX mixes_genre ON mixes (genre);
+";
+
+fn write_sqlite(
+    connection: &mut rusqlite::Connection,
+    graph: &FrontendData,
+    details: &[NodeDetails],
+    artists: &[ArtistDetails],
+) -> rusqlite::Result<()> {
+    use rusqlite::params;
+
+    let transaction = connection.transaction()?;
+    transaction.execute_batch(SQLITE_SCHEMA)?;
+    {
+        let mut insert = transaction.prepare("INSERT INTO metadata VALUES (?1, ?2)")?;
+        insert.execute(params!["wikipedia_domain", graph.wikipedia_domain])?;
+        insert.execute(params!["wikipedia_db_name", graph.wikipedia_db_name])?;
+        insert.execute(params!["dump_date", graph.dump_date])?;
+
+        let mut insert_artist =
+            transaction.prepare("INSERT INTO artists VALUES (?1, ?2, ?3, ?4)")?;
+        for artist in artists {
+            insert_artist.execute(params![
+                artist.page.to_string(),
+                artist.name,
+                artist.links,
+                artist.wikidata_qid,
+            ])?;
+        }
+
+        let mut insert_node = transaction
+            .prepare("INSERT INTO nodes VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)")?;
+        let mut insert_alias = transaction.prepare("INSERT INTO aliases VALUES (?1, ?2)")?;
+        // Top artists without an artist row are left out, rather than dangling
+        let mut insert_genre_artist = transaction.prepare(
+            "INSERT OR IGNORE INTO genre_artists SELECT ?1, page, ?3 FROM artists WHERE page = ?2",
+        )?;
+        let mut insert_mix =
+            transaction.prepare("INSERT INTO mixes VALUES (?1, ?2, ?3, ?4, ?5)")?;
+        for (index, (node, details)) in graph.nodes.iter().zip(details).enumerate() {
+            insert_node.execute(params![
+                index,
+                node.label.0,
+                details.page.to_string(),
+                details.origin_decade,
+                details.cluster,
+                node.links,
+                node.x,
+                node.y,
+                node.hue,
+                node.wikidata_qid,
+                node.musicbrainz_genre_id,
+            ])?;
+            for alias in &node.aliases {
+                insert_alias.execute(params![index, alias])?;
+            }
+            for (rank, artist) in details.top_artists.iter().enumerate() {
+                insert_genre_artist.execute(params![index, artist.to_string(), rank + 1])?;
+            }
+            for mix in &details.mixes {
+                let (kind, youtube_id) = match mix {
+                    GenreMix::Playlist { playlist, .. } => ("playlist", Some(playlist)),
+                    GenreMix::Video { video, .. } => ("video", Some(video)),
+                    GenreMix::External { provider, .. } => (provider.name(), None),
+                };
+                insert_mix.execute(params![index, kind, youtube_id, mix.note(), mix.url()])?;
+            }
+        }
+
+        let mut insert_edge = transaction.prepare("INSERT INTO edges VALUES (?1, ?2, ?3)")?;
+        for edge in &graph.edges {
+            insert_edge.execute(params![edge.source.0, edge.target.0, edge.ty.name()])?;
+        }
+    }
+    transaction.commit()
+}
+
+/// Write the nodes, edges and genres' top artists as `nodes.parquet`, `edges.parquet` and
+/// `genre_artists.parquet` in `output_path`, for loading into DuckDB or pandas. The tables are
+/// those of [`sqlite`], with the artists' names in `genre_artists`. `details` is indexed by node ID.
+pub fn parquet(
+    output_path: &Path,
+    graph: &FrontendData,
+    details: &[NodeDetails],
+    artists: &[ArtistDetails],
+) -> anyhow::Result<()> {
+    use ParquetColumn as C;
+
+    let nodes = graph.nodes.iter().zip(details);
+    write_parquet(
+        &output_path.join("nodes.parquet"),
+        vec![
+            ("id", C::Int64((0..graph.nodes.len() as i64).collect())),
+            (
+                "label",
+                C::Text(nodes.clone().map(|(n, _)| n.label.0.clone()).collect()),
+            ),
+            (
+                "page",
+                C::Text(details.iter().map(|d| d.page.to_string()).collect()),
+            ),
+            (
+                "origin_decade",
+                C::OptionalInt32(details.iter().map(|d| d.origin_decade).collect()),
+            ),
+            (
+                "cluster",
+                C::OptionalText(details.iter().map(|d| d.cluster.clone()).collect()),
+            ),
+            (
+                "links",
+                C::Int64(nodes.clone().map(|(n, _)| n.links as i64).collect()),
+            ),
+            (
+                "popularity",
+                C::Int64(nodes.clone().map(|(n, _)| n.popularity as i64).collect()),
+            ),
+            (
+                "community",
+                C::Int64(nodes.clone().map(|(n, _)| n.community as i64).collect()),
+            ),
+            (
+                "pagerank",
+                C::Double(nodes.clone().map(|(n, _)| n.pagerank).collect()),
+            ),
+            (
+                "betweenness",
+                C::Double(nodes.clone().map(|(n, _)| n.betweenness).collect()),
+            ),
+            ("x", C::Double(nodes.clone().map(|(n, _)| n.x).collect())),
+            ("y", C::Double(nodes.clone().map(|(n, _)| n.y).collect())),
+            (
+                "hue",
+                C::Double(nodes.clone().map(|(n, _)| n.hue).collect()),
+            ),
+            (
+                "wikidata_qid",
+                C::OptionalText(nodes.clone().map(|(n, _)| n.wikidata_qid.clone()).collect()),
+            ),
+            (
+                "musicbrainz_genre_id",
+                C::OptionalText(nodes.map(|(n, _)| n.musicbrainz_genre_id.clone()).collect()),
+            ),
+        ],
+    )?;
+
+    write_parquet(
+        &output_path.join("edges.parquet"),
+        vec![
+            (
+                "source",
+                C::Int64(graph.edges.iter().map(|e| e.source.0 as i64).collect()),
+            ),
+            (
+                "target",
+                C::Int64(graph.edges.iter().map(|e| e.target.0 as i64).collect()),
+            ),
+            (
+                "type",
+                C::Text(
+                    graph
+                        .edges
+                        .iter()
+                        .map(|e| e.ty.name().to_string())
+                        .collect(),
+                ),
+            ),
+            (
+                "corroborated",
+                C::Boolean(graph.edges.iter().map(|e| e.corroborated).collect()),
+            ),
+        ],
+    )?;
+
+    let names: std::collections::BTreeMap<&PageName, &str> = artists
+        .iter()
+        .map(|artist| (&artist.page, artist.name.as_str()))
+        .collect();
+    let genre_artists: Vec<(usize, &PageName, usize)> = details
+        .iter()
+        .enumerate()
+        .flat_map(|(index, details)| {
+            let top_artists = details.top_artists.iter().enumerate();
+            top_artists.map(move |(rank, artist)| (index, artist, rank))
+        })
+        .collect();
+    write_parquet(
+        &output_path.join("genre_artists.parquet"),
+        vec![
+            (
+                "genre",
+                C::Int64(genre_artists.iter().map(|a| a.0 as i64).collect()),
+            ),
+            (
+                "artist",
+                C::Text(genre_artists.iter().map(|a| a.1.to_string()).collect()),
+            ),
+            (
+                "name",
+                C::OptionalText(
+                    genre_artists
+                        .iter()
+                        .map(|a| names.get(a.1).map(|name| name.to_string()))
+                        .collect(),
+                ),
+            ),
+            (
+                "rank",
+                C::Int64(genre_artists.iter().map(|a| a.2 as i64 + 1).collect()),
+            ),
+        ],
+    )
+}
+
+/// The values of a column written by [`write_parquet`].
+enum ParquetColumn {
+    Int64(Vec<i64>),
+    OptionalInt32(Vec<Option<i32>>),
+    Double(Vec<f64>),
+    Boolean(Vec<bool>),
+    Text(Vec<String>),
+    OptionalText(Vec<Option<String>>),
+}
+impl ParquetColumn {
+    /// The column's type in a Parquet message type.
+    fn schema_type(&self) -> &'static str {
+        match self {
+            Self::Int64(_) => "REQUIRED INT64",
+            Self::OptionalInt32(_) => "OPTIONAL INT32",
+            Self::Double(_) => "REQUIRED DOUBLE",
+            Self::Boolean(_) => "REQUIRED BOOLEAN",
+            Self::Text(_) => "REQUIRED BYTE_ARRAY",
+            Self::OptionalText(_) => "OPTIONAL BYTE_ARRAY",
+        }
+    }
+}
+
+/// Write `columns` to a Parquet file at `path`, as one row group.
+fn write_parquet(path: &Path, columns: Vec<(&str, ParquetColumn)>) -> anyhow::Result<()> {
+    use parquet::{
+        basic::{Compression, ZstdLevel},
+        data_type::{BoolType, ByteArray, ByteArrayType, DoubleType, Int32Type, Int64Type},
+        file::{properties::WriterProperties, writer::SerializedFileWriter},
+    };
+
+    // Which values are present, as Parquet's definition levels
+    fn levels<T>(values: &[Option<T>]) -> Vec<i16> {
+        values.iter().map(|v| v.is_some() as i16).collect()
+    }
+    fn text(values: impl IntoIterator<Item = impl AsRef<str>>) -> Vec<ByteArray> {
+        values
+            .into_iter()
+            .map(|value| ByteArray::from(value.as_ref()))
+            .collect()
+    }
+
+    let mut schema = String::from("message schema {\n");
+    for (name, column) in &columns {
+        let annotation = if column.schema_type().ends_with("BYTE_ARRAY") {
+            " (UTF8)"
+        } else {
+            ""
+        };
+        writeln!(schema, "  {} {name}{annotation};", column.schema_type()).unwrap();
+    }
+    schema.push('}');
+    let schema = parquet::schema::parser::parse_message_type(&schema)?;
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::ZSTD(ZstdLevel::default()))
+        .build();
+
+    let file = std::fs::File::create(path).with_context(|| format!("Failed to create {path:?}"))?;
+    let mut writer = SerializedFileWriter::new(file, schema.into(), properties.into())?;
+    let mut row_group = writer.next_row_group()?;
+    for (_, column) in &columns {
+        let mut writer = row_group
+            .next_column()?
+            .context("Parquet schema has fewer columns than written")?;
+        match column {
+            ParquetColumn::Int64(values) => writer
+                .typed::<Int64Type>()
+                .write_batch(values, None, None)?,
+            ParquetColumn::OptionalInt32(values) => writer.typed::<Int32Type>().write_batch(
+                &values.iter().flatten().copied().collect::<Vec<_>>(),
+                Some(&levels(values)),
+                None,
+            )?,
+            ParquetColumn::Double(values) => writer
+                .typed::<DoubleType>()
+                .write_batch(values, None, None)?,
+            ParquetColumn::Boolean(values) => {
+                writer.typed::<BoolType>().write_batch(values, None, None)?
+            }
+            ParquetColumn::Text(values) => {
+                writer
+                    .typed::<ByteArrayType>()
+                    .write_batch(&text(values), None, None)?
+            }
+            ParquetColumn::OptionalText(values) => writer.typed::<ByteArrayType>().write_batch(
+                &text(values.iter().flatten()),
+                Some(&levels(values)),
+                None,
+            )?,
+        };
+        writer.close()?;
+    }
+    row_group.close()?;
+    writer
+        .close()
+        .with_context(|| format!("Failed to write {path:?}"))?;
+    Ok(())
+}
+
+/// The URL of the website, under which genres in the JSON-LD and sitemap have their pages.
+pub(crate) const WEBSITE_URL: &str = "https://genresin.space/";
+
+/// Percent-encode `path` for a URL, keeping its `/`s.
+pub(crate) fn url_path(path: &str) -> String {
+    let mut out = String::new();
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte) {
+            out.push(byte as char);
+        } else {
+            write!(out, "%{byte:02X}").unwrap();
+        }
+    }
+    out
+}
+
+/// The Wikidata item for "music genre", which genres in the JSON-LD are an instance of.
+const WIKIDATA_MUSIC_GENRE: &str = "https://www.wikidata.org/wiki/Q188451";
+
+/// Write the dataset as schema.org JSON-LD. `details` is indexed by node ID.
+///
+/// schema.org has no type for genres, so they're `DefinedTerm`s in the site's term set,
+/// identified by their pages (see [`og_cards::page_url`]), with
+/// Wikidata's "music genre" as their `additionalType`. Artists are `MusicGroup`s whose `genre`s
+/// refer to those terms. Both are linked to their Wikipedia pages and Wikidata items by `sameAs`.
+pub fn json_ld(
+    graph: &FrontendData,
+    details: &[NodeDetails],
+    artists: &[ArtistDetails],
+) -> serde_json::Value {
+    use serde_json::json;
+
+    let genre_id = |id: PageDataId| og_cards::page_url(&details[id.0].page);
+    let same_as = |page: &PageName, wikidata_qid: Option<&str>| {
+        let mut same_as = vec![format!(
+            "https://{}/wiki/{}",
+            graph.wikipedia_domain,
+            page.linksafe()
+        )];
+        // Without the page's item, Wikidata can look it up by title; sections of a page don't
+        // have their own item
+        match wikidata_qid {
+            Some(qid) => same_as.push(format!("https://www.wikidata.org/wiki/{qid}")),
+            None if page.heading.is_none() => same_as.push(format!(
+                "https://www.wikidata.org/wiki/Special:ItemByTitle/{}/{}",
+                graph.wikipedia_db_name,
+                page.linksafe()
+            )),
+            None => {}
+        }
+        same_as
+    };
+
+    let genres = graph
+        .nodes
+        .iter()
+        .zip(details)
+        .enumerate()
+        .map(|(index, (node, details))| {
+            let mut same_as = same_as(&details.page, node.wikidata_qid.as_deref());
+            if let Some(mbid) = &node.musicbrainz_genre_id {
+                same_as.push(format!("https://musicbrainz.org/genre/{mbid}"));
+            }
+            let mut genre = json!({
+                "@type": "DefinedTerm",
+                "@id": genre_id(PageDataId(index)),
+                "additionalType": WIKIDATA_MUSIC_GENRE,
+                "inDefinedTermSet": WEBSITE_URL,
+                "name": node.label.0,
+                "sameAs": same_as,
+            });
+            if !node.aliases.is_empty() {
+                genre["alternateName"] = json!(node.aliases);
+            }
+            genre
+        });
+    let artists = artists.iter().map(|artist| {
+        json!({
+            "@type": "MusicGroup",
+            "name": artist.name,
+            "sameAs": same_as(&artist.page, artist.wikidata_qid.as_deref()),
+            "genre": artist.genres.iter().map(|id| genre_id(*id)).collect::<Vec<_>>(),
+        })
+    });
+
+    json!({
+        "@context": "https://schema.org",
+        "@graph": genres.chain(artists).collect::<Vec<_>>(),
+    })
+}
+
+/// Write a sitemap of the website and each genre's page (see [`og_cards::page_url`]). `details`
+/// is indexed by node ID.
+///
+/// Each genre's `lastmod` is when its Wikipedia page was last edited, as that's where everything
+/// shown for it comes from.
+pub fn sitemap(graph: &FrontendData, details: &[NodeDetails]) -> String {
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n");
+    let mut url = |loc: &str, lastmod: &str| {
+        writeln!(
+            out,
+            "  <url><loc>{}</loc><lastmod>{}</lastmod></url>",
+            escape_xml(loc),
+            escape_xml(lastmod)
+        )
+        .unwrap();
+    };
+    url(WEBSITE_URL, &graph.dump_date);
+    for details in details {
+        let lastmod = details
+            .last_revision_date
+            .to_zoned(jiff::tz::TimeZone::UTC)
+            .date();
+        url(&og_cards::page_url(&details.page), &lastmod.to_string());
+    }
+    out.push_str("</urlset>\n");
+    out
+}
+
+/// Write a `robots.txt` that allows everything and points to the sitemap.
+pub fn robots_txt() -> String {
+    format!("User-agent: *\nAllow: /\n\nSitemap: {WEBSITE_URL}sitemap.xml\n")
+}
+
+/// The ID of a node in exported graphs.
+fn node_id(id: PageDataId) -> String {
+    format!("n{}", id.0)
+}
+
+/// Escape text for use in XML content or attribute values.
+pub(crate) fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Quote a CSV field if it contains a separator, quote or newline.
+pub(crate) fn escape_csv(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+    use crate::{
+        frontend_types::{EdgeData, EdgeType, NodeData},
+        types::GenreName,
+    };
+
+    fn test_graph() -> (FrontendData, Vec<NodeDetails>) {
+        let node = |label: &str| NodeData {
+            page_title: None,
+            label: GenreName(label.to_string()),
+            aliases: vec![],
+            links: 10,
+            popularity: 0,
+            x: 0.5,
+            y: -1.0,
+            z: None,
+            hue: 90.0,
+            community: 0,
+            in_degree: 0,
+            out_degree: 0,
+            pagerank: 0.0,
+            betweenness: 0.0,
+            depth: None,
+            wikidata_qid: None,
+            musicbrainz_genre_id: None,
+        };
+        let graph = FrontendData {
+            wikipedia_domain: "en.wikipedia.org".to_string(),
+            wikipedia_db_name: "enwiki".to_string(),
+            dump_date: "2026-02-01".to_string(),
+            nodes: vec![node("Drum and bass"), node("Jungle & breaks")],
+            edges: BTreeSet::from_iter([EdgeData {
+                source: PageDataId(0),
+                target: PageDataId(1),
+                ty: EdgeType::Derivative,
+                corroborated: false,
+            }]),
+            max_degree: 1,
+            communities: vec![],
+            sharded_files: false,
+            layout_seed: None,
+        };
+        let details = vec![
+            NodeDetails {
+                page: PageName::new("Drum and bass", None),
+                origin_decade: Some(1990),
+                cluster: Some("Electronic dance music".to_string()),
+                top_artists: vec![
+                    PageName::new("Goldie", None),
+                    PageName::new("Roni Size", None),
+                ],
+                mixes: vec![GenreMix::Video {
+                    video: "abc123".to_string(),
+                    note: Some("Classics".to_string()),
+                    details: Default::default(),
+                }],
+                last_revision_date: "2026-01-15T12:30:00Z".parse().unwrap(),
+            },
+            NodeDetails {
+                page: PageName::new("Jungle music", None),
+                origin_decade: None,
+                cluster: None,
+                top_artists: vec![],
+                mixes: vec![],
+                last_revision_date: "2025-11-02T00:00:00Z".parse().unwrap(),
+            },
+        ];
+        (graph, details)
+    }
+
+    #[test]
+    fn graphml_includes_attributes_and_escapes_text() {
+        let (graph, details) = test_graph();
+        let graphml = graphml(&graph, &details);
+
+        assert!(graphml.contains("<node id=\"n0\">"));
+        assert!(graphml.contains("<data key=\"origin_decade\">1990</data>"));
+        assert!(graphml.contains("<data key=\"cluster\">Electronic dance music</data>"));
+        assert!(graphml.contains("<data key=\"label\">Jungle &amp; breaks</data>"));
+        assert!(graphml.contains(
+            "<edge id=\"e0\" source=\"n0\" target=\"n1\">\n      <data key=\"type\">derivative</data>"
+        ));
+        // Unknown decades are left out rather than written as empty
+        assert_eq!(graphml.matches("key=\"origin_decade\">").count(), 1);
+    }
+
+    #[test]
+    fn gexf_starts_nodes_and_edges_at_their_decade() {
+        let (graph, details) = test_graph();
+        let gexf = gexf(&graph, &details);
+
+        assert!(gexf.contains("<graph mode=\"dynamic\""));
+        assert!(gexf.contains("<node id=\"n0\" label=\"Drum and bass\" start=\"1990\">"));
+        // No origin decade, so the node exists throughout
+        assert!(gexf.contains("<node id=\"n1\" label=\"Jungle &amp; breaks\">"));
+        // The edge starts once both genres exist
+        assert!(gexf.contains("<edge id=\"e0\" source=\"n0\" target=\"n1\" start=\"1990\">"));
+        assert!(gexf.contains("<attvalue for=\"type\" value=\"derivative\"/>"));
+    }
+
+    #[test]
+    fn sitemap_has_a_url_per_genre() {
+        let (graph, details) = test_graph();
+        let sitemap = sitemap(&graph, &details);
+
+        assert!(sitemap.contains(
+            "<url><loc>https://genresin.space/</loc><lastmod>2026-02-01</lastmod></url>"
+        ));
+        assert!(sitemap.contains(
+            "<url><loc>https://genresin.space/og/Drum_and_bass.html</loc><lastmod>2026-01-15</lastmod></url>"
+        ));
+        assert!(sitemap.contains(
+            "<url><loc>https://genresin.space/og/Jungle_music.html</loc><lastmod>2025-11-02</lastmod></url>"
+        ));
+        assert!(robots_txt().contains("Sitemap: https://genresin.space/sitemap.xml"));
+    }
+
+    #[test]
+    fn csv_has_a_row_per_node_and_edge() {
+        let (graph, details) = test_graph();
+
+        let nodes = nodes_csv(&graph, &details);
+        let mut lines = nodes.lines();
+        assert_eq!(lines.next(), Some("id,label,page,decade,cluster"));
+        assert!(
+            lines
+                .next()
+                .unwrap()
+                .starts_with("0,Drum and bass,Drum and bass,1990,")
+        );
+        // No decade or cluster, so those fields are empty
+        assert!(lines.next().unwrap().ends_with(",,"));
+
+        assert_eq!(edges_csv(&graph), "source,target,type\n0,1,derivative\n");
+    }
+
+    #[test]
+    fn parquet_has_a_row_per_item() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+
+        let (graph, details) = test_graph();
+        let artists = [ArtistDetails {
+            page: PageName::new("Goldie", None),
+            name: "Goldie".to_string(),
+            links: 500,
+            wikidata_qid: None,
+            genres: vec![PageDataId(0)],
+        }];
+        let dir = std::env::temp_dir().join(format!("datagen-parquet-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        parquet(&dir, &graph, &details, &artists).unwrap();
+
+        let rows = |name: &str| -> Vec<String> {
+            let file = std::fs::File::open(dir.join(name)).unwrap();
+            let reader = SerializedFileReader::new(file).unwrap();
+            reader
+                .get_row_iter(None)
+                .unwrap()
+                .map(|row| row.unwrap().to_string())
+                .collect()
+        };
+        let nodes = rows("nodes.parquet");
+        assert_eq!(nodes.len(), 2);
+        assert!(nodes[0].starts_with(
+            r#"{id: 0, label: "Drum and bass", page: "Drum and bass", origin_decade: 1990, "#
+        ));
+        assert!(nodes[1].contains("origin_decade: null, cluster: null"));
+        assert_eq!(
+            rows("edges.parquet"),
+            [r#"{source: 0, target: 1, type: "derivative", corroborated: false}"#]
+        );
+        assert_eq!(
+            rows("genre_artists.parquet"),
+            [
+                r#"{genre: 0, artist: "Goldie", name: "Goldie", rank: 1}"#,
+                r#"{genre: 0, artist: "Roni Size", name: null, rank: 2}"#,
+            ]
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn escape_csv_quotes_fields_with_separators() {
+        assert_eq!(escape_csv("Techno"), "Techno");
+        assert_eq!(escape_csv("Rock, pop"), "\"Rock, pop\"");
+        assert_eq!(escape_csv("\"Weird\" Al"), "\"\"\"Weird\"\" Al\"");
+    }
+
+    #[test]
+    fn sqlite_has_a_row_per_item() {
+        let (mut graph, details) = test_graph();
+        graph.nodes[0].aliases = vec!["DnB".to_string(), "D&B".to_string()];
+        let artists = [
+            ArtistDetails {
+                page: PageName::new("Goldie", None),
+                name: "Goldie".to_string(),
+                links: 500,
+                wikidata_qid: None,
+                genres: vec![PageDataId(0)],
+            },
+            ArtistDetails {
+                page: PageName::new("Roni Size", None),
+                name: "Roni Size".to_string(),
+                links: 200,
+                wikidata_qid: None,
+                genres: vec![PageDataId(0)],
+            },
+        ];
+
+        let mut connection = rusqlite::Connection::open_in_memory().unwrap();
+        write_sqlite(&mut connection, &graph, &details, &artists).unwrap();
+
+        let query =
+            |sql: &str| -> String { connection.query_row(sql, [], |row| row.get(0)).unwrap() };
+        assert_eq!(
+            query("SELECT value FROM metadata WHERE key = 'dump_date'"),
+            "2026-02-01"
+        );
+        assert_eq!(
+            query("SELECT label FROM nodes WHERE origin_decade = 1990"),
+            "Drum and bass"
+        );
+        assert_eq!(
+            query(
+                "SELECT target.page FROM edges
+                JOIN nodes target ON target.id = edges.target
+                WHERE edges.type = 'derivative'"
+            ),
+            "Jungle music"
+        );
+        assert_eq!(
+            query(
+                "SELECT group_concat(artists.name, ';') FROM genre_artists
+                JOIN artists ON artists.page = genre_artists.artist
+                WHERE genre = 0 ORDER BY rank"
+            ),
+            "Goldie;Roni Size"
+        );
+        assert_eq!(
+            query(
+                "SELECT nodes.label FROM aliases JOIN nodes ON nodes.id = aliases.genre WHERE alias = 'D&B'"
+            ),
+            "Drum and bass"
+        );
+        assert_eq!(
+            query("SELECT kind || ':' || youtube_id || ':' || note FROM mixes WHERE genre = 0"),
+            "video:abc123:Classics"
+        );
+    }
+
+    #[test]
+    fn json_ld_links_artists_to_genres() {
+        let (mut graph, details) = test_graph();
+        graph.nodes[1].wikidata_qid = Some("Q123".to_string());
+        graph.nodes[1].musicbrainz_genre_id =
+            Some("d0c9a0d6-0000-4000-8000-000000000000".to_string());
+        let artists = [ArtistDetails {
+            page: PageName::new("Goldie", None),
+            name: "Goldie".to_string(),
+            links: 500,
+            wikidata_qid: Some("Q456".to_string()),
+            genres: vec![PageDataId(0)],
+        }];
+
+        let json_ld = json_ld(&graph, &details, &artists);
+        let items = json_ld["@graph"].as_array().unwrap();
+        assert_eq!(items.len(), 3);
+
+        assert_eq!(
+            items[0]["@id"],
+            "https://genresin.space/og/Drum_and_bass.html"
+        );
+        assert_eq!(items[0]["name"], "Drum and bass");
+        assert_eq!(
+            items[0]["sameAs"],
+            serde_json::json!([
+                "https://en.wikipedia.org/wiki/Drum_and_bass",
+                "https://www.wikidata.org/wiki/Special:ItemByTitle/enwiki/Drum_and_bass",
+            ])
+        );
+
+        assert_eq!(
+            items[1]["sameAs"],
+            serde_json::json!([
+                "https://en.wikipedia.org/wiki/Jungle_music",
+                "https://www.wikidata.org/wiki/Q123",
+                "https://musicbrainz.org/genre/d0c9a0d6-0000-4000-8000-000000000000",
+            ])
+        );
+
+        assert_eq!(items[2]["@type"], "MusicGroup");
+        assert_eq!(items[2]["sameAs"][1], "https://www.wikidata.org/wiki/Q456");
+        assert_eq!(
+            items[2]["genre"],
+            serde_json::json!(["https://genresin.space/og/Drum_and_bass.html"])
+        );
+    }
+}
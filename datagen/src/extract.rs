@@ -12,7 +12,7 @@ use rayon::iter::{IntoParallelRefIterator as _, ParallelIterator as _};
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    types::{PageName, WikipediaPaths},
+    types::{AliasSource, PageName, WikipediaPaths},
     util,
 };
 
@@ -67,6 +67,9 @@ pub struct WikitextHeader {
     pub timestamp: jiff::Timestamp,
     /// The ID of the page.
     pub id: u64,
+    /// The ID of the revision the text is from; `None` for pages extracted before it was recorded.
+    #[serde(default)]
+    pub revision_id: Option<u64>,
 }
 
 /// Metadata about the Wikipedia dump.
@@ -90,6 +93,8 @@ pub struct ExtractedData {
     pub artists: ArtistPages,
     /// All redirects found in the dump.
     pub redirects: AllRedirects,
+    /// The redirects whose redirect category marks them as an alternative name, and what kind.
+    pub alias_redirects: BTreeMap<PageName, AliasSource>,
     /// All Wikipedia page IDs to page names.
     pub id_to_page_names: BTreeMap<u64, PageName>,
 }
@@ -103,6 +108,8 @@ struct IntermediateData {
     artist_pages: BTreeMap<PageName, PathBuf>,
     /// Redirects found so far.
     redirects: BTreeMap<PageName, PageName>,
+    /// Alternative-name redirects found so far.
+    alias_redirects: BTreeMap<PageName, AliasSource>,
     /// Page IDs to page names
     id_to_page_names: BTreeMap<u64, PageName>,
 }
@@ -112,6 +119,7 @@ impl IntermediateData {
         self.genre_pages.extend(other.genre_pages);
         self.artist_pages.extend(other.artist_pages);
         self.redirects.extend(other.redirects);
+        self.alias_redirects.extend(other.alias_redirects);
         self.id_to_page_names.extend(other.id_to_page_names);
     }
 }
@@ -131,12 +139,14 @@ pub fn from_data_dump(
     let genres_path = output_path.join("genres");
     let artists_path = output_path.join("artists");
     let redirects_path = output_path.join("all_redirects.json");
+    let alias_redirects_path = output_path.join("alias_redirects.json");
     let id_to_page_names_path = output_path.join("id_to_page_names.json");
 
     // Already exists, just load from file
     if genres_path.is_dir()
         && artists_path.is_dir()
         && redirects_path.is_file()
+        && alias_redirects_path.is_file()
         && id_to_page_names_path.is_file()
         && meta_path.is_file()
     {
@@ -172,12 +182,15 @@ pub fn from_data_dump(
 
         let id_to_page_names =
             serde_json::from_str(&std::fs::read_to_string(&id_to_page_names_path)?)?;
+        let alias_redirects =
+            serde_json::from_str(&std::fs::read_to_string(&alias_redirects_path)?)?;
 
         return Ok(ExtractedData {
             dump_meta: meta,
             genres: GenrePages(genre_pages),
             artists: ArtistPages(artist_pages),
             redirects: AllRedirects::LazyLoad(redirects_path, start),
+            alias_redirects,
             id_to_page_names,
         });
     }
@@ -237,6 +250,12 @@ pub fn from_data_dump(
     )
     .context("Failed to write redirects")?;
 
+    std::fs::write(
+        &alias_redirects_path,
+        &serde_json::to_string_pretty(&intermediate_data.alias_redirects)?,
+    )
+    .context("Failed to write alias redirects")?;
+
     std::fs::write(
         &id_to_page_names_path,
         &serde_json::to_string_pretty(&intermediate_data.id_to_page_names)?,
@@ -260,6 +279,7 @@ pub fn from_data_dump(
         genres: GenrePages(intermediate_data.genre_pages),
         artists: ArtistPages(intermediate_data.artist_pages),
         redirects: AllRedirects::InMemory(intermediate_data.redirects),
+        alias_redirects: intermediate_data.alias_redirects,
         id_to_page_names: intermediate_data.id_to_page_names,
     })
 }
@@ -404,6 +424,12 @@ fn process_offset_slice(
     let mut page_id = String::new();
     let mut recording_page_id = false;
 
+    // Likewise, the revision ID is the first ID after the revision tag (the contributor's
+    // ID comes later).
+    let mut in_revision = false;
+    let mut revision_id = String::new();
+    let mut recording_revision_id = false;
+
     loop {
         match reader.read_event_into(&mut buf) {
             Ok(Event::Eof) => break,
@@ -419,11 +445,16 @@ fn process_offset_slice(
                     timestamp.clear();
                     recording_timestamp = true;
                 } else if name == b"page" {
-                    // Reset the page ID when we see a new page
+                    // Reset the page and revision IDs when we see a new page
                     page_id.clear();
+                    revision_id.clear();
+                } else if name == b"revision" {
+                    in_revision = true;
                 } else if name == b"id" && page_id.is_empty() {
                     // Don't start recording if we've already seen an ID
                     recording_page_id = true;
+                } else if name == b"id" && in_revision && revision_id.is_empty() {
+                    recording_revision_id = true;
                 }
             }
             Ok(Event::Text(e)) => {
@@ -435,6 +466,8 @@ fn process_offset_slice(
                     timestamp.push_str(&e.unescape().unwrap());
                 } else if recording_page_id {
                     page_id.push_str(&e.unescape().unwrap());
+                } else if recording_revision_id {
+                    revision_id.push_str(&e.unescape().unwrap());
                 }
             }
             Ok(Event::End(e)) => {
@@ -447,6 +480,9 @@ fn process_offset_slice(
                     recording_timestamp = false;
                 } else if tag_name == b"id" {
                     recording_page_id = false;
+                    recording_revision_id = false;
+                } else if tag_name == b"revision" {
+                    in_revision = false;
                 } else if tag_name == b"page" {
                     let page = PageName {
                         name: title.clone(),
@@ -456,6 +492,9 @@ fn process_offset_slice(
                         // Parse the redirect and add it to the redirects map
                         match parse_redirect_text(wikipedia_domain, &text) {
                             Ok(redirect) => {
+                                if let Some(source) = parse_redirect_alias_source(&text) {
+                                    data.alias_redirects.insert(page.clone(), source);
+                                }
                                 data.redirects.insert(page.clone(), redirect);
                             }
                             Err(e) => {
@@ -512,6 +551,7 @@ fn process_offset_slice(
                         serde_json::to_string(&WikitextHeader {
                             timestamp,
                             id: page_id,
+                            revision_id: revision_id.parse().ok(),
                         })
                         .context("Failed to serialize WikitextHeader")
                         .unwrap()
@@ -546,6 +586,15 @@ fn process_offset_slice(
     data
 }
 
+/// Find the first redirect category template (e.g. `{{R from alternative name}}`, possibly
+/// inside a `{{Redirect category shell}}`) that marks a redirect as an alternative name.
+fn parse_redirect_alias_source(text: &str) -> Option<AliasSource> {
+    text.split("{{").skip(1).find_map(|template| {
+        let name_end = template.find(['|', '}']).unwrap_or(template.len());
+        AliasSource::from_redirect_category(&template[..name_end])
+    })
+}
+
 #[derive(Debug)]
 enum RedirectParseError {
     InvalidRedirect { text: String },
@@ -695,6 +744,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_redirect_alias_source() {
+        let text = "#REDIRECT [[Hip-hop music]]
+{{Redirect category shell|
+{{R from alternative name}}
+{{R from move}}
+}}";
+        assert_eq!(
+            parse_redirect_alias_source(text),
+            Some(AliasSource::AlternativeName)
+        );
+        assert_eq!(
+            parse_redirect_alias_source("#REDIRECT [[House music]] {{R from acronym|HM}}"),
+            Some(AliasSource::Abbreviation)
+        );
+        assert_eq!(
+            parse_redirect_alias_source("#REDIRECT [[Techno]] {{R from misspelling}}"),
+            None
+        );
+        assert_eq!(parse_redirect_alias_source("#REDIRECT [[Techno]]"), None);
+    }
+
     #[test]
     fn test_parse_redirect_invalid() {
         let text = "Not a redirect";
@@ -1,24 +1,116 @@
-//! Serialization types and paths for the frontend data.json file.
+//! Serialization types and paths for the frontend graph data file (`data.json`, or
+//! `data.msgpack` in the MessagePack format, or the files listed by `chunks.json` in the chunked
+//! format), and the index (`data_index.json`) that says which of them was written.
 //!
 //! These are shared between the main datagen pipeline and the `relayout` binary.
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::Path;
 
+use anyhow::Context as _;
+
 /// Path to the website public directory (output root), relative to the repo root.
 pub const WEBSITE_PUBLIC_PATH: &str = "website/public";
 
-/// Path to `data.json` relative to the repository root.
-pub fn data_json_path() -> &'static Path {
-    Path::new("website/public/data.json")
-}
+/// Path to the TypeScript types generated for the data files, relative to the repo root.
+pub const WEBSITE_TYPES_PATH: &str = "website/src/dataTypes.ts";
+
+/// The [`DataIndex`], relative to the output root.
+pub const DATA_INDEX_FILE: &str = "data_index.json";
+
+/// The directory of the [`Neighborhood`] files, relative to the output root.
+pub const NEIGHBORHOOD_DIRECTORY: &str = "neighborhood";
 
 use serde::{Deserialize, Serialize, ser::SerializeTuple};
 
 use crate::types::{GenreName, PageDataId};
 
-/// The root structure serialized to `data.json`.
-#[derive(Debug, Serialize, Deserialize)]
+pub use shared::EdgeType;
+
+/// Serialize `value` as JSON: compact, or indented for reading by hand if `pretty`.
+pub fn to_json<T: Serialize + ?Sized>(value: &T, pretty: bool) -> serde_json::Result<Vec<u8>> {
+    if pretty {
+        serde_json::to_vec_pretty(value)
+    } else {
+        serde_json::to_vec(value)
+    }
+}
+
+/// The format the graph data is written in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "lowercase")]
+pub enum DataFormat {
+    /// JSON (`data.json`), which is easy to inspect and diff (when written with `pretty`).
+    #[default]
+    Json,
+    /// MessagePack (`data.msgpack`), which is smaller and faster for the website to decode.
+    MessagePack,
+    /// Several files described by a manifest (`chunks.json`), so that the website can draw the
+    /// layout before every genre's details have arrived:
+    /// - `meta.json`: everything but the nodes and edges ([`ChunkedMeta`])
+    /// - `nodes.json`: what's needed to draw each node ([`NodeSkeleton`])
+    /// - `edges.bin`: [`EDGE_BYTES`] per edge; the source and target as little-endian `u32`s,
+    ///   then the type's index, plus 4 if the edge is corroborated
+    /// - `clusters/<community>.json`: the full data of each community's nodes ([`ClusterChunk`])
+    Chunked,
+}
+impl DataFormat {
+    /// Every format, in the order they're looked for when reading.
+    pub const ALL: [Self; 3] = [Self::Json, Self::MessagePack, Self::Chunked];
+
+    /// The name of the graph data file in this format.
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Self::Json => "data.json",
+            Self::MessagePack => "data.msgpack",
+            Self::Chunked => "chunks.json",
+        }
+    }
+
+    /// Remove the graph data written in this format from `dir`, if there is any.
+    fn remove(self, dir: &Path) -> anyhow::Result<()> {
+        let mut paths = vec![dir.join(self.file_name())];
+        if self == Self::Chunked {
+            paths.extend([META_FILE, NODES_FILE, EDGES_FILE].map(|file| dir.join(file)));
+            let clusters = dir.join(CLUSTERS_DIRECTORY);
+            if clusters.is_dir() {
+                std::fs::remove_dir_all(&clusters)
+                    .with_context(|| format!("Failed to remove {clusters:?}"))?;
+            }
+        }
+        for path in paths {
+            if path.is_file() {
+                std::fs::remove_file(&path)
+                    .with_context(|| format!("Failed to remove {path:?}"))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Which format the graph data was written in ([`DATA_INDEX_FILE`]), so that the website
+/// fetches only that file rather than looking for each in turn.
+#[derive(Debug, Serialize, Deserialize, ts_rs::TS)]
+#[serde(deny_unknown_fields)]
+pub struct DataIndex {
+    /// The format of the graph data.
+    pub format: DataFormat,
+    /// The path of its file ([`DataFormat::file_name`]), relative to the index.
+    pub path: String,
+}
+
+/// The files of the chunked format, besides the manifest, relative to the output directory.
+const META_FILE: &str = "meta.json";
+const NODES_FILE: &str = "nodes.json";
+const EDGES_FILE: &str = "edges.bin";
+const CLUSTERS_DIRECTORY: &str = "clusters";
+
+/// The size of each edge in `edges.bin`.
+pub const EDGE_BYTES: usize = 9;
+
+/// The root structure serialized to the graph data file.
+#[derive(Debug, Serialize, Deserialize, ts_rs::TS, schemars::JsonSchema)]
+#[ts(rename = "DataOnDisk")]
 pub struct FrontendData {
     /// The Wikipedia domain (e.g. "en.wikipedia.org").
     pub wikipedia_domain: String,
@@ -32,48 +124,485 @@ pub struct FrontendData {
     pub edges: BTreeSet<EdgeData>,
     /// The maximum degree of any node.
     pub max_degree: usize,
+    /// The label of each community, indexed by [`NodeData::community`]: that of its
+    /// highest-degree genre.
+    #[serde(default)]
+    pub communities: Vec<GenreName>,
+    /// Whether the genre and artist files are sharded into subdirectories by the start of their
+    /// names (e.g. `genres/ab/Abstract hip hop.json`), so that no one directory has tens of
+    /// thousands of files. Older data has them directly in `genres/` and `artists/`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    #[ts(as = "Option<bool>", optional)]
+    pub sharded_files: bool,
+    /// The seed the layout was started from (see [`layout::LayoutInput::seed`]),
+    /// so that it can be reproduced. Older data doesn't record it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub layout_seed: Option<u32>,
+}
+
+impl FrontendData {
+    /// Read the graph data from `dir`, in the format its [`DataIndex`] gives, or whichever
+    /// format it was written in for data from before there was an index.
+    pub fn read(dir: &Path) -> anyhow::Result<(Self, DataFormat)> {
+        let index_path = dir.join(DATA_INDEX_FILE);
+        let format = if index_path.is_file() {
+            let index: DataIndex = serde_json::from_slice(
+                &std::fs::read(&index_path)
+                    .with_context(|| format!("Failed to read {index_path:?}"))?,
+            )
+            .with_context(|| format!("Failed to parse {index_path:?}"))?;
+            index.format
+        } else {
+            DataFormat::ALL
+                .into_iter()
+                .find(|format| dir.join(format.file_name()).is_file())
+                .with_context(|| format!("No graph data file in {dir:?}"))?
+        };
+        let path = dir.join(format.file_name());
+        let bytes = std::fs::read(&path).with_context(|| format!("Failed to read {path:?}"))?;
+        let data = match format {
+            DataFormat::Json => serde_json::from_slice(&bytes)?,
+            DataFormat::MessagePack => rmp_serde::from_slice(&bytes)?,
+            DataFormat::Chunked => Self::read_chunks(dir, serde_json::from_slice(&bytes)?)?,
+        };
+        Ok((data, format))
+    }
+
+    /// Write the graph data to `dir` in `format`, and a [`DataIndex`] pointing to it, removing
+    /// any data in another format so that the website can't load stale data. JSON is indented if
+    /// `pretty`. Returns the path written to (the manifest, for the chunked format).
+    pub fn write(
+        &self,
+        dir: &Path,
+        format: DataFormat,
+        pretty: bool,
+    ) -> anyhow::Result<std::path::PathBuf> {
+        for other in DataFormat::ALL.into_iter().filter(|f| *f != format) {
+            other.remove(dir)?;
+        }
+        let path = dir.join(format.file_name());
+        let bytes = match format {
+            DataFormat::Json => to_json(self, pretty)?,
+            // Named, so that the website sees the same objects as with JSON
+            DataFormat::MessagePack => rmp_serde::to_vec_named(self)?,
+            DataFormat::Chunked => {
+                // Clusters from a previous run may no longer exist
+                format.remove(dir)?;
+                to_json(&self.write_chunks(dir, pretty)?, pretty)?
+            }
+        };
+        std::fs::write(&path, bytes).with_context(|| format!("Failed to write {path:?}"))?;
+
+        let index = DataIndex {
+            format,
+            path: format.file_name().to_string(),
+        };
+        let index_path = dir.join(DATA_INDEX_FILE);
+        std::fs::write(&index_path, to_json(&index, pretty)?)
+            .with_context(|| format!("Failed to write {index_path:?}"))?;
+        Ok(path)
+    }
+
+    /// Write every file of the chunked format but the manifest, and return the manifest.
+    fn write_chunks(&self, dir: &Path, pretty: bool) -> anyhow::Result<ChunkManifest> {
+        let write_json = |path: &Path, bytes: Vec<u8>| -> anyhow::Result<()> {
+            std::fs::write(path, bytes).with_context(|| format!("Failed to write {path:?}"))
+        };
+
+        let meta = ChunkedMeta {
+            wikipedia_domain: self.wikipedia_domain.clone(),
+            wikipedia_db_name: self.wikipedia_db_name.clone(),
+            dump_date: self.dump_date.clone(),
+            max_degree: self.max_degree,
+            communities: self.communities.clone(),
+            sharded_files: self.sharded_files,
+            layout_seed: self.layout_seed,
+            node_count: self.nodes.len(),
+        };
+        write_json(&dir.join(META_FILE), to_json(&meta, pretty)?)?;
+
+        let skeletons: Vec<NodeSkeleton> = self
+            .nodes
+            .iter()
+            .map(|node| NodeSkeleton {
+                label: node.label.clone(),
+                x: node.x,
+                y: node.y,
+                z: node.z,
+                hue: node.hue,
+                community: node.community,
+                popularity: node.popularity,
+            })
+            .collect();
+        write_json(&dir.join(NODES_FILE), to_json(&skeletons, pretty)?)?;
+
+        let mut edges = Vec::with_capacity(self.edges.len() * EDGE_BYTES);
+        for edge in &self.edges {
+            edges.extend_from_slice(&u32::try_from(edge.source.0)?.to_le_bytes());
+            edges.extend_from_slice(&u32::try_from(edge.target.0)?.to_le_bytes());
+            edges.push(edge.ty.index() | if edge.corroborated { 4 } else { 0 });
+        }
+        let edges_path = dir.join(EDGES_FILE);
+        std::fs::write(&edges_path, edges)
+            .with_context(|| format!("Failed to write {edges_path:?}"))?;
+
+        let mut members: BTreeMap<usize, Vec<PageDataId>> = BTreeMap::new();
+        for (id, node) in self.nodes.iter().enumerate() {
+            members
+                .entry(node.community)
+                .or_default()
+                .push(PageDataId(id));
+        }
+        let clusters_path = dir.join(CLUSTERS_DIRECTORY);
+        std::fs::create_dir_all(&clusters_path)
+            .with_context(|| format!("Failed to create {clusters_path:?}"))?;
+        let mut clusters = vec![];
+        for (community, ids) in members {
+            let nodes: Vec<NodeData> = ids.iter().map(|id| self.nodes[id.0].clone()).collect();
+            let path = format!("{CLUSTERS_DIRECTORY}/{community}.json");
+            let mut bounds = [
+                f64::INFINITY,
+                f64::INFINITY,
+                f64::NEG_INFINITY,
+                f64::NEG_INFINITY,
+            ];
+            for node in &nodes {
+                bounds = [
+                    bounds[0].min(node.x),
+                    bounds[1].min(node.y),
+                    bounds[2].max(node.x),
+                    bounds[3].max(node.y),
+                ];
+            }
+            clusters.push(ClusterChunkInfo {
+                path: path.clone(),
+                community,
+                label: self.communities.get(community).cloned(),
+                node_count: ids.len(),
+                bounds,
+            });
+            write_json(
+                &dir.join(&path),
+                to_json(&ClusterChunk { ids, nodes }, pretty)?,
+            )?;
+        }
+        // Largest first, so that the website can start on the chunks that fill in the most
+        clusters.sort_by(|a, b| {
+            b.node_count
+                .cmp(&a.node_count)
+                .then(a.community.cmp(&b.community))
+        });
+
+        Ok(ChunkManifest {
+            meta: META_FILE.to_string(),
+            nodes: NODES_FILE.to_string(),
+            edges: EDGES_FILE.to_string(),
+            edge_count: self.edges.len(),
+            clusters,
+        })
+    }
+
+    /// Reassemble the graph data from the files listed by `manifest`.
+    fn read_chunks(dir: &Path, manifest: ChunkManifest) -> anyhow::Result<Self> {
+        fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> anyhow::Result<T> {
+            let bytes = std::fs::read(path).with_context(|| format!("Failed to read {path:?}"))?;
+            serde_json::from_slice(&bytes).with_context(|| format!("Failed to parse {path:?}"))
+        }
+
+        let meta: ChunkedMeta = read_json(&dir.join(&manifest.meta))?;
+
+        let mut nodes: Vec<Option<NodeData>> = (0..meta.node_count).map(|_| None).collect();
+        for cluster in &manifest.clusters {
+            let chunk: ClusterChunk = read_json(&dir.join(&cluster.path))?;
+            anyhow::ensure!(
+                chunk.ids.len() == chunk.nodes.len(),
+                "{} has {} IDs but {} nodes",
+                cluster.path,
+                chunk.ids.len(),
+                chunk.nodes.len()
+            );
+            for (id, node) in chunk.ids.into_iter().zip(chunk.nodes) {
+                let slot = nodes
+                    .get_mut(id.0)
+                    .with_context(|| format!("{} has out-of-range node {}", cluster.path, id.0))?;
+                *slot = Some(node);
+            }
+        }
+        let nodes = nodes
+            .into_iter()
+            .enumerate()
+            .map(|(id, node)| node.with_context(|| format!("Node {id} is in no cluster")))
+            .collect::<anyhow::Result<_>>()?;
+
+        let edges_path = dir.join(&manifest.edges);
+        let bytes =
+            std::fs::read(&edges_path).with_context(|| format!("Failed to read {edges_path:?}"))?;
+        anyhow::ensure!(
+            bytes.len() == manifest.edge_count * EDGE_BYTES,
+            "{edges_path:?} has {} bytes, but should have {} edges",
+            bytes.len(),
+            manifest.edge_count
+        );
+        let edges = bytes
+            .chunks_exact(EDGE_BYTES)
+            .map(|edge| {
+                let id = |bytes: &[u8]| {
+                    PageDataId(u32::from_le_bytes(bytes.try_into().unwrap()) as usize)
+                };
+                let flags = edge[8];
+                Ok(EdgeData {
+                    source: id(&edge[0..4]),
+                    target: id(&edge[4..8]),
+                    ty: EdgeType::from_index(flags & 3)
+                        .with_context(|| format!("Unknown edge type in {flags}"))?,
+                    corroborated: flags & 4 != 0,
+                })
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Self {
+            wikipedia_domain: meta.wikipedia_domain,
+            wikipedia_db_name: meta.wikipedia_db_name,
+            dump_date: meta.dump_date,
+            nodes,
+            edges,
+            max_degree: meta.max_degree,
+            communities: meta.communities,
+            sharded_files: meta.sharded_files,
+            layout_seed: meta.layout_seed,
+        })
+    }
+
+    /// The neighbourhood of each node, in node order: the nodes within `hops` edges of it in
+    /// either direction, and every edge between them.
+    pub fn neighborhoods(&self, hops: usize) -> impl Iterator<Item = Neighborhood> + '_ {
+        let mut neighbours: Vec<Vec<usize>> = vec![vec![]; self.nodes.len()];
+        let mut outgoing: Vec<Vec<&EdgeData>> = vec![vec![]; self.nodes.len()];
+        for edge in &self.edges {
+            neighbours[edge.source.0].push(edge.target.0);
+            neighbours[edge.target.0].push(edge.source.0);
+            outgoing[edge.source.0].push(edge);
+        }
+
+        (0..self.nodes.len()).map(move |center| {
+            let mut distances = BTreeMap::from([(center, 0)]);
+            let mut frontier = vec![center];
+            for distance in 1..=hops {
+                let mut next = vec![];
+                for node in frontier {
+                    for &neighbour in &neighbours[node] {
+                        if let std::collections::btree_map::Entry::Vacant(entry) =
+                            distances.entry(neighbour)
+                        {
+                            entry.insert(distance);
+                            next.push(neighbour);
+                        }
+                    }
+                }
+                frontier = next;
+            }
+
+            let mut ids: Vec<usize> = distances.keys().copied().collect();
+            ids.sort_by_key(|id| (distances[id], *id));
+            Neighborhood {
+                hops: ids.iter().map(|id| distances[id]).collect(),
+                nodes: ids.iter().map(|&id| self.nodes[id].clone()).collect(),
+                edges: ids
+                    .iter()
+                    .flat_map(|&id| &outgoing[id])
+                    .filter(|edge| distances.contains_key(&edge.target.0))
+                    .map(|&edge| edge.clone())
+                    .collect(),
+                ids: ids.into_iter().map(PageDataId).collect(),
+            }
+        })
+    }
+}
+
+/// The manifest of the chunked format (`chunks.json`). Paths are relative to it.
+#[derive(Debug, Serialize, Deserialize, ts_rs::TS, schemars::JsonSchema)]
+pub struct ChunkManifest {
+    /// The path of the graph's metadata ([`ChunkedMeta`]).
+    pub meta: String,
+    /// The path of the node skeletons ([`NodeSkeleton`]), indexed by node ID.
+    pub nodes: String,
+    /// The path of the binary edge list.
+    pub edges: String,
+    /// The number of edges in the edge list.
+    pub edge_count: usize,
+    /// The chunk of each community, from the largest down (ties by community).
+    pub clusters: Vec<ClusterChunkInfo>,
+}
+
+/// A community's chunk in [`ChunkManifest`].
+#[derive(Debug, Serialize, Deserialize, ts_rs::TS, schemars::JsonSchema)]
+pub struct ClusterChunkInfo {
+    /// The path of the chunk ([`ClusterChunk`]).
+    pub path: String,
+    /// The community, as in [`NodeData::community`].
+    pub community: usize,
+    /// The community's label, as in [`FrontendData::communities`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub label: Option<GenreName>,
+    /// The number of nodes in the community.
+    pub node_count: usize,
+    /// The bounds of the community's nodes: `[min x, min y, max x, max y]`.
+    pub bounds: [f64; 4],
+}
+
+/// The graph data of the chunked format, without its nodes or edges (`meta.json`).
+#[derive(Debug, Serialize, Deserialize, ts_rs::TS, schemars::JsonSchema)]
+pub struct ChunkedMeta {
+    /// The Wikipedia domain (e.g. "en.wikipedia.org").
+    pub wikipedia_domain: String,
+    /// The Wikipedia database name (e.g. "enwiki").
+    pub wikipedia_db_name: String,
+    /// The dump date (e.g. "2026-02-01").
+    pub dump_date: String,
+    /// The maximum degree of any node.
+    pub max_degree: usize,
+    /// The label of each community, as in [`FrontendData::communities`].
+    pub communities: Vec<GenreName>,
+    /// Whether the genre and artist files are sharded, as in [`FrontendData::sharded_files`].
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    #[ts(as = "Option<bool>", optional)]
+    pub sharded_files: bool,
+    /// The layout's seed, as in [`FrontendData::layout_seed`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub layout_seed: Option<u32>,
+    /// The number of nodes.
+    pub node_count: usize,
+}
+
+/// What's needed to draw a node before its [`ClusterChunk`] arrives (`nodes.json`).
+#[derive(Debug, Serialize, Deserialize, ts_rs::TS, schemars::JsonSchema)]
+pub struct NodeSkeleton {
+    /// The display label.
+    pub label: GenreName,
+    /// X position from force-directed layout.
+    pub x: f64,
+    /// Y position from force-directed layout.
+    pub y: f64,
+    /// Z position from force-directed layout, if it was laid out in 3D.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub z: Option<f64>,
+    /// Hue (0–360) from color propagation.
+    pub hue: f64,
+    /// The community, as in [`NodeData::community`].
+    pub community: usize,
+    /// The popularity tier, as in [`NodeData::popularity`].
+    pub popularity: usize,
+}
+
+/// The full data of a community's nodes (`clusters/<community>.json`).
+#[derive(Debug, Serialize, Deserialize, ts_rs::TS, schemars::JsonSchema)]
+pub struct ClusterChunk {
+    /// The ID of each node.
+    pub ids: Vec<PageDataId>,
+    /// The data of each node, in the same order as `ids`.
+    pub nodes: Vec<NodeData>,
+}
+
+/// A node's neighbourhood (`neighborhood/<id>.json`): the nodes within a few edges of it, and
+/// the edges between them, so that the website can draw a linked genre and its surroundings
+/// before the whole graph has loaded.
+#[derive(Debug, Serialize, Deserialize, ts_rs::TS, schemars::JsonSchema)]
+pub struct Neighborhood {
+    /// The ID of each node: the node itself first, then the rest by how far away they are.
+    pub ids: Vec<PageDataId>,
+    /// The number of edges between the node and each node, in the same order as `ids`.
+    pub hops: Vec<usize>,
+    /// The data of each node, in the same order as `ids`.
+    pub nodes: Vec<NodeData>,
+    /// The edges between the nodes, by node ID.
+    pub edges: Vec<EdgeData>,
 }
 
 /// A genre node in the graph.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ts_rs::TS, schemars::JsonSchema)]
+#[ts(rename = "NodeOnDiskData")]
 pub struct NodeData {
     /// The Wikipedia page title, if different from the label.
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
     pub page_title: Option<String>,
     /// The display label.
     pub label: GenreName,
     /// Alternative names, derived from Wikipedia redirects (cleaned and deduplicated).
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[ts(as = "Option<Vec<String>>", optional)]
     pub aliases: Vec<String>,
     /// Inbound Wikipedia link count for the genre's page and its redirects.
     #[serde(default, skip_serializing_if = "is_zero")]
+    #[ts(as = "Option<usize>", optional)]
     pub links: usize,
+    /// The genre's popularity tier by `links`, from 0 (the least-linked fifth of genres) to 4
+    /// (the most-linked fifth), for scaling genres by notability rather than degree.
+    #[serde(default)]
+    pub popularity: usize,
     /// X position from force-directed layout.
     pub x: f64,
     /// Y position from force-directed layout.
     pub y: f64,
+    /// Z position from force-directed layout, if it was laid out in 3D.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub z: Option<f64>,
     /// Hue (0–360) from color propagation.
     #[serde(default)]
     pub hue: f64,
+    /// The community (a cluster of densely-connected genres) from community detection,
+    /// numbered from the largest down.
+    #[serde(default)]
+    pub community: usize,
+    /// The number of edges into the genre.
+    #[serde(default)]
+    pub in_degree: usize,
+    /// The number of edges out of the genre.
+    #[serde(default)]
+    pub out_degree: usize,
+    /// PageRank (summing to 1 across genres) over the non-`Related` edges reversed, so that a
+    /// genre ranks highly when influential genres derive from it.
+    #[serde(default)]
+    pub pagerank: f64,
+    /// Betweenness centrality (0–1) over the non-`Related` edges: how many of the shortest paths
+    /// between other genres pass through the genre.
+    #[serde(default)]
+    pub betweenness: f64,
+    /// The fewest non-`Related` edges from a root genre (one that doesn't derive from any other)
+    /// to this one, or `None` if it only derives from genres in a cycle. Roots have a depth of 0.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub depth: Option<usize>,
+    /// The Wikidata item ID of the genre's page, if it has one. Genres that are sections of a
+    /// page don't have their own item.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub wikidata_qid: Option<String>,
+    /// The MusicBrainz genre ID of the genre's Wikidata item, if it's mapped to one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub musicbrainz_genre_id: Option<String>,
 }
 
-fn is_zero(n: &usize) -> bool {
-    *n == 0
-}
+/// The number of popularity tiers that genres are split into by inbound links.
+pub const POPULARITY_TIERS: usize = 5;
 
-/// The type of relationship between two genres.
-#[derive(Debug, Serialize, Deserialize, Hash, PartialEq, Eq, PartialOrd, Ord)]
-pub enum EdgeType {
-    /// A derivative genre relationship.
-    Derivative,
-    /// A subgenre relationship.
-    Subgenre,
-    /// A fusion genre relationship.
-    FusionGenre,
+pub(crate) fn is_zero(n: &usize) -> bool {
+    *n == 0
 }
 
-/// An edge between two genre nodes, serialized as a `[source, target, type]` tuple.
-#[derive(Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+/// An edge between two genre nodes, serialized as a `[source, target, type]` tuple, with a
+/// fourth `true` element if it's corroborated.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord, ts_rs::TS)]
+#[ts(
+    rename = "EdgeOnDiskData",
+    type = "[PageDataId, PageDataId, 0 | 1 | 2 | 3, true?]"
+)]
 pub struct EdgeData {
     /// The source node ID.
     pub source: PageDataId,
@@ -81,6 +610,10 @@ pub struct EdgeData {
     pub target: PageDataId,
     /// The edge type.
     pub ty: EdgeType,
+    /// Whether the infoboxes of both genres assert the edge: the source lists the target as a
+    /// derivative, and the target lists the source as a stylistic origin. Only derivative edges
+    /// can be corroborated.
+    pub corroborated: bool,
 }
 
 impl Serialize for EdgeData {
@@ -88,31 +621,238 @@ impl Serialize for EdgeData {
     where
         S: serde::Serializer,
     {
-        let mut tup = serializer.serialize_tuple(3)?;
+        let mut tup = serializer.serialize_tuple(if self.corroborated { 4 } else { 3 })?;
         tup.serialize_element(&self.source)?;
         tup.serialize_element(&self.target)?;
-        tup.serialize_element(&match self.ty {
-            EdgeType::Derivative => 0,
-            EdgeType::Subgenre => 1,
-            EdgeType::FusionGenre => 2,
-        })?;
+        tup.serialize_element(&self.ty.index())?;
+        if self.corroborated {
+            tup.serialize_element(&true)?;
+        }
         tup.end()
     }
 }
 
+impl schemars::JsonSchema for EdgeData {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "EdgeData".into()
+    }
+
+    fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        // The type is the index of the variant in `EdgeType`, and the last element is optional
+        let mut schema = <(PageDataId, PageDataId, u8, bool)>::json_schema(generator);
+        schema.insert("minItems".to_string(), 3.into());
+        schema.insert(
+            "description".to_string(),
+            "An edge between two genre nodes: `[source, target, type]`, where the type is 0 for \
+             derivative, 1 for subgenre, 2 for fusion genre and 3 for related, followed by `true` \
+             if both genres' infoboxes assert the edge."
+                .into(),
+        );
+        schema
+    }
+}
+
 impl<'de> Deserialize<'de> for EdgeData {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        let (source, target, ty): (PageDataId, PageDataId, u8) =
-            Deserialize::deserialize(deserializer)?;
-        let ty = match ty {
-            0 => EdgeType::Derivative,
-            1 => EdgeType::Subgenre,
-            2 => EdgeType::FusionGenre,
-            _ => return Err(serde::de::Error::custom(format!("unknown edge type: {ty}"))),
-        };
-        Ok(EdgeData { source, target, ty })
+        struct EdgeVisitor;
+        impl<'de> serde::de::Visitor<'de> for EdgeVisitor {
+            type Value = EdgeData;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a [source, target, type] tuple, optionally followed by `true`")
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> Result<EdgeData, A::Error> {
+                use serde::de::Error as _;
+                let source = seq
+                    .next_element()?
+                    .ok_or_else(|| A::Error::invalid_length(0, &self))?;
+                let target = seq
+                    .next_element()?
+                    .ok_or_else(|| A::Error::invalid_length(1, &self))?;
+                let ty: u8 = seq
+                    .next_element()?
+                    .ok_or_else(|| A::Error::invalid_length(2, &self))?;
+                let corroborated = seq.next_element()?.unwrap_or(false);
+                let ty = EdgeType::from_index(ty)
+                    .ok_or_else(|| A::Error::custom(format!("unknown edge type: {ty}")))?;
+                Ok(EdgeData {
+                    source,
+                    target,
+                    ty,
+                    corroborated,
+                })
+            }
+        }
+        deserializer.deserialize_tuple(4, EdgeVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_data() -> FrontendData {
+        FrontendData {
+            wikipedia_domain: "en.wikipedia.org".to_string(),
+            wikipedia_db_name: "enwiki".to_string(),
+            dump_date: "2026-02-01".to_string(),
+            nodes: vec![
+                NodeData {
+                    page_title: None,
+                    label: GenreName("Techno".to_string()),
+                    aliases: vec![],
+                    links: 12,
+                    popularity: 0,
+                    x: 1.5,
+                    y: -2.0,
+                    z: None,
+                    hue: 120.0,
+                    community: 0,
+                    in_degree: 0,
+                    out_degree: 0,
+                    pagerank: 0.0,
+                    betweenness: 0.0,
+                    depth: None,
+                    wikidata_qid: None,
+                    musicbrainz_genre_id: None,
+                },
+                NodeData {
+                    page_title: Some("House music".to_string()),
+                    label: GenreName("House".to_string()),
+                    aliases: vec!["Deep house".to_string()],
+                    links: 30,
+                    popularity: 4,
+                    x: -3.25,
+                    y: 4.0,
+                    z: None,
+                    hue: 30.0,
+                    community: 1,
+                    in_degree: 1,
+                    out_degree: 0,
+                    pagerank: 0.5,
+                    betweenness: 0.0,
+                    depth: Some(0),
+                    wikidata_qid: None,
+                    musicbrainz_genre_id: None,
+                },
+            ],
+            edges: BTreeSet::from_iter([
+                EdgeData {
+                    source: PageDataId(0),
+                    target: PageDataId(0),
+                    ty: EdgeType::Subgenre,
+                    corroborated: false,
+                },
+                EdgeData {
+                    source: PageDataId(0),
+                    target: PageDataId(1),
+                    ty: EdgeType::Derivative,
+                    corroborated: true,
+                },
+            ]),
+            max_degree: 1,
+            communities: vec![
+                GenreName("Techno".to_string()),
+                GenreName("House".to_string()),
+            ],
+            sharded_files: true,
+            layout_seed: Some(7),
+        }
+    }
+
+    #[test]
+    fn message_pack_round_trips() {
+        let data = sample_data();
+
+        let bytes = rmp_serde::to_vec_named(&data).unwrap();
+        let decoded: FrontendData = rmp_serde::from_slice(&bytes).unwrap();
+        assert_eq!(
+            serde_json::to_value(&decoded).unwrap(),
+            serde_json::to_value(&data).unwrap()
+        );
+        assert!(bytes.len() < serde_json::to_vec(&data).unwrap().len());
+    }
+
+    #[test]
+    fn chunks_round_trip() {
+        let dir = std::env::temp_dir().join(format!("datagen-chunks-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let data = sample_data();
+        data.write(&dir, DataFormat::Json, false).unwrap();
+
+        data.write(&dir, DataFormat::Chunked, false).unwrap();
+        assert!(!dir.join("data.json").exists());
+        assert!(dir.join("clusters/1.json").is_file());
+        assert_eq!(
+            std::fs::metadata(dir.join("edges.bin")).unwrap().len(),
+            2 * EDGE_BYTES as u64
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.join(DATA_INDEX_FILE)).unwrap(),
+            r#"{"format":"chunked","path":"chunks.json"}"#
+        );
+        let (decoded, format) = FrontendData::read(&dir).unwrap();
+        assert_eq!(format, DataFormat::Chunked);
+        assert_eq!(
+            serde_json::to_value(&decoded).unwrap(),
+            serde_json::to_value(&data).unwrap()
+        );
+
+        data.write(&dir, DataFormat::Json, false).unwrap();
+        assert!(!dir.join("chunks.json").exists());
+        assert!(!dir.join("clusters").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn neighborhoods_reach_both_ways_within_hops() {
+        // 0 -> 1, and 2 -> 3 -> 1
+        let mut data = sample_data();
+        data.nodes.extend(data.nodes.clone());
+        data.edges.extend([
+            EdgeData {
+                source: PageDataId(2),
+                target: PageDataId(3),
+                ty: EdgeType::Derivative,
+                corroborated: false,
+            },
+            EdgeData {
+                source: PageDataId(3),
+                target: PageDataId(1),
+                ty: EdgeType::Related,
+                corroborated: false,
+            },
+        ]);
+
+        let neighborhoods: Vec<Neighborhood> = data.neighborhoods(1).collect();
+        assert_eq!(neighborhoods.len(), 4);
+        let house = &neighborhoods[1];
+        assert_eq!(house.ids, vec![PageDataId(1), PageDataId(0), PageDataId(3)]);
+        assert_eq!(house.hops, vec![0, 1, 1]);
+        assert_eq!(house.nodes[1].label, data.nodes[0].label);
+        // Including the self-loop, but not 2 -> 3, which leaves the neighbourhood
+        assert_eq!(house.edges.len(), 3);
+
+        let far = data.neighborhoods(2).nth(2).unwrap();
+        assert_eq!(far.ids, vec![PageDataId(2), PageDataId(3), PageDataId(1)]);
+        assert_eq!(far.edges.len(), 2);
+    }
+
+    #[test]
+    fn edges_only_carry_corroboration_when_corroborated() {
+        let edges: Vec<EdgeData> = serde_json::from_str("[[0, 1, 0, true], [1, 2, 3]]").unwrap();
+        assert!(edges[0].corroborated);
+        assert!(!edges[1].corroborated);
+        assert_eq!(
+            serde_json::to_string(&edges).unwrap(),
+            "[[0,1,0,true],[1,2,3]]"
+        );
     }
 }
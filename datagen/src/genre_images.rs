@@ -0,0 +1,248 @@
+//! Downloading each genre's infobox image (`genre_images = true` in `[output]`) and writing a
+//! small WebP thumbnail of it for the website, alongside the generated icons.
+//!
+//! Only Wikipedia's own thumbnails of the images are downloaded, never the originals, and those
+//! larger than [`MAX_DOWNLOAD_BYTES`] are skipped. Thumbnails are cached on disk by file name and
+//! revision, so that later runs only download images that are new or have been re-uploaded.
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::Read as _,
+    path::Path,
+    time::Duration,
+};
+
+use anyhow::Context as _;
+use serde::{Deserialize, Serialize};
+
+use crate::util;
+
+/// Where thumbnails are cached, shared by every dump's output.
+pub const CACHE_PATH: &str = "output/genre_images";
+
+/// The directory of the website's public files that thumbnails are written to.
+pub const IMAGES_DIRECTORY: &str = "images";
+
+/// The width thumbnails are requested at and scaled down to fit.
+const THUMBNAIL_WIDTH: u32 = 320;
+
+/// The tallest a thumbnail can be, so that portrait images don't take over the sidebar.
+const MAX_THUMBNAIL_HEIGHT: u32 = 400;
+
+/// The largest thumbnail that's downloaded.
+const MAX_DOWNLOAD_BYTES: u64 = 2 * 1024 * 1024;
+
+/// How many files are asked about in each API request, which is the API's limit.
+const FILES_PER_REQUEST: usize = 50;
+
+/// How long to wait between requests, as Wikimedia asks of API clients.
+const REQUEST_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Who's asking, as Wikimedia requires of API clients.
+const USER_AGENT: &str = "genresinspace-datagen (https://genresin.space/)";
+
+/// A genre's infobox image, as written to the website.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ts_rs::TS, schemars::JsonSchema)]
+pub struct GenreImage {
+    /// The path of the thumbnail from the website's root (e.g. `images/1a2b3c4d5e6f7a8b.webp`).
+    pub path: String,
+    /// The width of the thumbnail.
+    pub width: u32,
+    /// The height of the thumbnail.
+    pub height: u32,
+    /// The URL of the image's description page, which credits its author and gives its license.
+    pub description_url: String,
+}
+
+/// What the API says about a file.
+#[derive(Debug)]
+struct FileInfo {
+    /// The SHA-1 of the file's current revision.
+    sha1: String,
+    /// The URL of a thumbnail of the file.
+    thumbnail_url: String,
+    /// The URL of the file's description page.
+    description_url: String,
+}
+
+/// Write a thumbnail of each of `files` (image file names, without the `File:` prefix) from the
+/// Wikipedia at `wikipedia_domain` to the `images` directory of `output_path`, reusing those in
+/// the cache at `cache_path`. Returns the images by file name; files that are missing, aren't
+/// images, or can't be downloaded are left out.
+pub fn write(
+    start: std::time::Instant,
+    wikipedia_domain: &str,
+    files: &BTreeSet<String>,
+    cache_path: &Path,
+    output_path: &Path,
+) -> anyhow::Result<BTreeMap<String, GenreImage>> {
+    let client = reqwest::blocking::Client::builder()
+        .user_agent(USER_AGENT)
+        .build()?;
+    std::fs::create_dir_all(cache_path)
+        .with_context(|| format!("Failed to create {cache_path:?}"))?;
+    let images_path = output_path.join(IMAGES_DIRECTORY);
+    std::fs::create_dir_all(&images_path)?;
+
+    let files: Vec<&String> = files.iter().collect();
+    let mut file_infos = BTreeMap::new();
+    for (index, batch) in files.chunks(FILES_PER_REQUEST).enumerate() {
+        if index > 0 {
+            std::thread::sleep(REQUEST_INTERVAL);
+        }
+        file_infos.extend(
+            query_file_infos(&client, wikipedia_domain, batch)
+                .context("Failed to query image info")?,
+        );
+    }
+
+    let mut images = BTreeMap::new();
+    let mut downloaded = 0;
+    for (file, info) in &file_infos {
+        let name = format!(
+            "{:016x}.webp",
+            util::content_hash(format!("{file}\n{}", info.sha1).as_bytes())
+        );
+        let cached_path = cache_path.join(&name);
+        if !cached_path.is_file() {
+            if downloaded > 0 {
+                std::thread::sleep(REQUEST_INTERVAL);
+            }
+            downloaded += 1;
+            if let Err(e) = download_thumbnail(&client, &info.thumbnail_url, &cached_path) {
+                eprintln!("Warning: failed to download the image {file}: {e:?}");
+                continue;
+            }
+        }
+        let (width, height) = image::image_dimensions(&cached_path)
+            .with_context(|| format!("Failed to read {cached_path:?}"))?;
+        std::fs::copy(&cached_path, images_path.join(&name))?;
+        images.insert(
+            file.clone(),
+            GenreImage {
+                path: format!("{IMAGES_DIRECTORY}/{name}"),
+                width,
+                height,
+                description_url: info.description_url.clone(),
+            },
+        );
+    }
+
+    println!(
+        "{:.2}s: wrote {} genre images ({downloaded} downloaded)",
+        start.elapsed().as_secs_f32(),
+        images.len()
+    );
+    Ok(images)
+}
+
+/// Ask the API about `files`, returning what it says about those that exist and are images.
+fn query_file_infos(
+    client: &reqwest::blocking::Client,
+    wikipedia_domain: &str,
+    files: &[&String],
+) -> anyhow::Result<BTreeMap<String, FileInfo>> {
+    #[derive(Debug, Deserialize)]
+    struct Response {
+        query: Query,
+    }
+    #[derive(Debug, Deserialize)]
+    struct Query {
+        #[serde(default)]
+        normalized: Vec<Normalized>,
+        #[serde(default)]
+        pages: Vec<Page>,
+    }
+    #[derive(Debug, Deserialize)]
+    struct Normalized {
+        from: String,
+        to: String,
+    }
+    #[derive(Debug, Deserialize)]
+    struct Page {
+        title: String,
+        #[serde(default)]
+        imageinfo: Vec<ImageInfo>,
+    }
+    #[derive(Debug, Deserialize)]
+    struct ImageInfo {
+        sha1: String,
+        mime: String,
+        thumburl: Option<String>,
+        descriptionurl: String,
+    }
+
+    let titles: Vec<String> = files.iter().map(|file| format!("File:{file}")).collect();
+    let response: Response = client
+        .get(format!("https://{wikipedia_domain}/w/api.php"))
+        .query(&[
+            ("action", "query"),
+            ("format", "json"),
+            ("formatversion", "2"),
+            ("prop", "imageinfo"),
+            ("iiprop", "url|sha1|mime"),
+            ("iiurlwidth", &THUMBNAIL_WIDTH.to_string()),
+            ("titles", &titles.join("|")),
+        ])
+        .send()?
+        .error_for_status()?
+        .json()?;
+
+    // The API answers with each title normalized (e.g. with its first letter capitalized)
+    let mut title_to_file: BTreeMap<String, &String> =
+        titles.iter().cloned().zip(files.iter().copied()).collect();
+    for normalized in response.query.normalized {
+        if let Some(file) = title_to_file.remove(&normalized.from) {
+            title_to_file.insert(normalized.to, file);
+        }
+    }
+
+    Ok(response
+        .query
+        .pages
+        .into_iter()
+        .filter_map(|page| {
+            let file = title_to_file.get(&page.title)?;
+            let info = page.imageinfo.into_iter().next()?;
+            if !info.mime.starts_with("image/") {
+                return None;
+            }
+            Some((
+                (*file).clone(),
+                FileInfo {
+                    sha1: info.sha1,
+                    thumbnail_url: info.thumburl?,
+                    description_url: info.descriptionurl,
+                },
+            ))
+        })
+        .collect())
+}
+
+/// Download the thumbnail at `url` and write it to `path` as a WebP, scaled down to fit.
+fn download_thumbnail(
+    client: &reqwest::blocking::Client,
+    url: &str,
+    path: &Path,
+) -> anyhow::Result<()> {
+    let response = client.get(url).send()?.error_for_status()?;
+    if let Some(length) = response.content_length() {
+        anyhow::ensure!(
+            length <= MAX_DOWNLOAD_BYTES,
+            "the thumbnail is {length} bytes, more than the limit of {MAX_DOWNLOAD_BYTES}"
+        );
+    }
+    let mut bytes = vec![];
+    response
+        .take(MAX_DOWNLOAD_BYTES + 1)
+        .read_to_end(&mut bytes)?;
+    anyhow::ensure!(
+        bytes.len() as u64 <= MAX_DOWNLOAD_BYTES,
+        "the thumbnail is more than the limit of {MAX_DOWNLOAD_BYTES} bytes"
+    );
+
+    let thumbnail = image::load_from_memory(&bytes)?
+        .thumbnail(THUMBNAIL_WIDTH, MAX_THUMBNAIL_HEIGHT)
+        .into_rgba8();
+    thumbnail.save_with_format(path, image::ImageFormat::WebP)?;
+    Ok(())
+}
@@ -5,20 +5,206 @@ use std::{
 };
 
 use anyhow::Context as _;
+use serde::{Deserialize, Serialize};
 
 use crate::{links, process, types};
 
-/// A map of genre page names to their top artists.
-pub type GenreTopArtists = BTreeMap<types::PageName, Vec<(types::PageName, f32)>>;
+/// A map of genre page names to their top artists, in descending order of score.
+pub type GenreTopArtists = BTreeMap<types::PageName, Vec<(types::PageName, ArtistScore)>>;
+
+/// How an artist's rank within a genre was calculated.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, ts_rs::TS, schemars::JsonSchema,
+)]
+pub struct ArtistScore {
+    /// The score the artist is ranked by: `popularity * weight * diversity_penalty`.
+    pub score: f32,
+    /// The artist's popularity, from the configured ranking metric.
+    pub popularity: f32,
+    /// How representative the artist is of the genre, from its position in their infobox and
+    /// how many other genres are listed with it.
+    pub weight: f32,
+    /// The penalty for acts ranked above the artist that they're associated with (1.0 for none).
+    pub diversity_penalty: f32,
+}
+
+/// How much an artist's score is multiplied by for each associated act (member, side project,
+/// etc.) ranked above them, so a dominant band's orbit doesn't crowd out the rest of the scene.
+const DIVERSITY_PENALTY: f32 = 0.5;
+
+/// How many of a genre's top artists are reranked for diversity; the rest keep their order.
+const DIVERSITY_WINDOW: usize = 50;
 
 /// A map of artist page names to their genres.
 pub type ArtistGenres = BTreeMap<types::PageName, BTreeSet<types::PageName>>;
 
+/// Where the category-to-genre patterns are loaded from.
+pub const CATEGORY_GENRES_PATH: &str = "datagen/category_genres.toml";
+
+/// Patterns mapping artist categories to genres, for artists whose infobox lists no genres.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CategoryGenres {
+    #[serde(default)]
+    patterns: Vec<CategoryGenrePattern>,
+}
+#[derive(Debug, Clone, Deserialize)]
+struct CategoryGenrePattern {
+    /// The category to match, case-insensitively; `*` matches any text.
+    category: String,
+    /// The genre link target for artists in a matching category.
+    genre: String,
+}
+impl CategoryGenres {
+    /// Load the patterns from a TOML file.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let patterns = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read category genre patterns from {path:?}"))?;
+        toml::from_str(&patterns)
+            .with_context(|| format!("Failed to parse category genre patterns from {path:?}"))
+    }
+
+    /// Get the genres for an artist's categories, in category order and without duplicates.
+    pub fn genres(&self, categories: &[String]) -> Vec<String> {
+        let mut genres = vec![];
+        for category in categories {
+            let category = category.to_lowercase();
+            for pattern in &self.patterns {
+                if wildcard_match(&pattern.category.to_lowercase(), &category)
+                    && !genres.contains(&pattern.genre)
+                {
+                    genres.push(pattern.genre.clone());
+                }
+            }
+        }
+        genres
+    }
+}
+
+/// Whether `text` matches `pattern`, where `*` in `pattern` matches any (possibly empty) text.
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No wildcard
+        return rest.is_empty();
+    };
+    for part in middle {
+        let Some(index) = rest.find(part) else {
+            return false;
+        };
+        rest = &rest[index + part.len()..];
+    }
+    rest.ends_with(last)
+}
+
+/// Artists with more genres than this are reported in [`CoverageStats`], as they're usually
+/// the result of a malformed infobox rather than a genuinely eclectic artist.
+const MAX_PLAUSIBLE_GENRES: usize = 12;
+
+/// Statistics on how well genres are covered by artists, for tuning the ranking heuristics.
+///
+/// Written to `coverage_stats.json` (named for the ranking, like the top artists) alongside the
+/// cached top artists; the website's `stats.json` is [`crate::dataset_stats::DatasetStats`].
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct CoverageStats {
+    /// The number of artists associated with each genre.
+    artists_per_genre: BTreeMap<types::PageName, usize>,
+    /// Genres that no artist is associated with.
+    genres_without_artists: Vec<types::PageName>,
+    /// The distribution of the (aggregated) inbound link counts of artists with a genre.
+    artist_link_counts: LinkCountDistribution,
+    /// Artists with more than [`MAX_PLAUSIBLE_GENRES`] genres, with their genre counts.
+    artists_with_many_genres: BTreeMap<types::PageName, usize>,
+}
+impl CoverageStats {
+    /// Calculate the statistics for `genres`, given each artist's (aggregated) inbound link count.
+    pub fn calculate<'a>(
+        genres: impl IntoIterator<Item = &'a types::PageName>,
+        genre_top_artists: &GenreTopArtists,
+        artist_genres: &ArtistGenres,
+        link_count: impl Fn(&types::PageName) -> usize,
+    ) -> Self {
+        let artists_per_genre: BTreeMap<types::PageName, usize> = genres
+            .into_iter()
+            .map(|genre| {
+                let count = genre_top_artists.get(genre).map_or(0, |a| a.len());
+                (genre.clone(), count)
+            })
+            .collect();
+        let genres_without_artists = artists_per_genre
+            .iter()
+            .filter(|(_, count)| **count == 0)
+            .map(|(genre, _)| genre.clone())
+            .collect();
+
+        let mut link_counts: Vec<usize> = artist_genres.keys().map(link_count).collect();
+        link_counts.sort_unstable();
+
+        let artists_with_many_genres = artist_genres
+            .iter()
+            .filter(|(_, genres)| genres.len() > MAX_PLAUSIBLE_GENRES)
+            .map(|(artist, genres)| (artist.clone(), genres.len()))
+            .collect();
+
+        Self {
+            artists_per_genre,
+            genres_without_artists,
+            artist_link_counts: LinkCountDistribution::from_sorted(&link_counts),
+            artists_with_many_genres,
+        }
+    }
+}
+
+/// Percentiles of a set of inbound link counts.
+#[derive(Debug, Default, PartialEq, Serialize)]
+struct LinkCountDistribution {
+    /// The number of link counts the percentiles are of.
+    count: usize,
+    min: usize,
+    p25: usize,
+    median: usize,
+    p75: usize,
+    p90: usize,
+    p99: usize,
+    max: usize,
+}
+impl LinkCountDistribution {
+    fn from_sorted(link_counts: &[usize]) -> Self {
+        if link_counts.is_empty() {
+            return Self::default();
+        }
+        // Nearest-rank percentile
+        let percentile = |p: usize| link_counts[((link_counts.len() - 1) * p).div_ceil(100)];
+        Self {
+            count: link_counts.len(),
+            min: link_counts[0],
+            p25: percentile(25),
+            median: percentile(50),
+            p75: percentile(75),
+            p90: percentile(90),
+            p99: percentile(99),
+            max: link_counts[link_counts.len() - 1],
+        }
+    }
+}
+
 /// Calculate the top artists for each genre.
+///
+/// Artists are ranked by the popularity signal chosen in `ranking`; `pageviews` is only
+/// needed when it involves pageviews. Artists whose infobox lists no genres are associated
+/// with the genres their categories map to in `category_genres`.
+#[allow(clippy::too_many_arguments)]
 pub fn calculate(
     start: std::time::Instant,
     processed_artists: &process::ProcessedArtists,
+    category_genres: &CategoryGenres,
     inbound_link_counts: &BTreeMap<types::PageName, usize>,
+    pageviews: Option<&BTreeMap<types::PageName, usize>>,
+    ranking: &types::RankingConfig,
     page_aliases: &links::PageAliases,
     links_to_articles: &links::LinksToArticles,
     output_path_gta: &Path,
@@ -29,16 +215,20 @@ pub fn calculate(
             "{:.2}s: loading genre top artists and artist genres",
             start.elapsed().as_secs_f32(),
         );
-        return Ok((
-            serde_json::from_slice(
-                &std::fs::read(output_path_gta).context("Failed to read genre top artists")?,
-            )
-            .context("Failed to parse genre top artists")?,
-            serde_json::from_slice(
-                &std::fs::read(output_path_ag).context("Failed to read artist genres")?,
-            )
-            .context("Failed to parse artist genres")?,
-        ));
+        let gta: Option<GenreTopArtists> = serde_json::from_slice(
+            &std::fs::read(output_path_gta).context("Failed to read genre top artists")?,
+        )
+        .ok();
+        // Files from before scores were recorded don't parse, and are recalculated
+        if let Some(gta) = gta {
+            return Ok((
+                gta,
+                serde_json::from_slice(
+                    &std::fs::read(output_path_ag).context("Failed to read artist genres")?,
+                )
+                .context("Failed to parse artist genres")?,
+            ));
+        }
     }
 
     println!(
@@ -46,59 +236,80 @@ pub fn calculate(
         start.elapsed().as_secs_f32(),
     );
 
-    let mut intermediate_gta = BTreeMap::<types::PageName, BTreeMap<types::PageName, f32>>::new();
+    let mut intermediate_gta =
+        BTreeMap::<types::PageName, BTreeMap<types::PageName, ArtistScore>>::new();
     let mut artist_genres = ArtistGenres::new();
 
+    // Associations are symmetric: a band's members are associated with the band and vice versa
+    let mut associated_acts = BTreeMap::<types::PageName, BTreeSet<types::PageName>>::new();
     for (artist_page, artist) in &processed_artists.0 {
-        // Includes links via the artist's redirects (e.g. "2Pac" → Tupac Shakur)
-        let link_count =
-            page_aliases.aggregated_link_count(artist_page, inbound_link_counts) as f32;
-
-        for (genre_index, genre) in artist.genres.iter().enumerate() {
-            let Some(page_name) = links_to_articles.map(genre) else {
+        for act in &artist.associated_acts {
+            let Some(act_page) = links_to_articles.map(act) else {
                 continue;
             };
+            if act_page == artist_page {
+                continue;
+            }
+            associated_acts
+                .entry(artist_page.clone())
+                .or_default()
+                .insert(act_page.clone());
+            associated_acts
+                .entry(act_page.clone())
+                .or_default()
+                .insert(artist_page.clone());
+        }
+    }
 
-            // Calculate weight based on genre position
-            // First genre gets full weight (1.0), last genre gets minimal weight (0.1)
-            // Use exponential decay: weight = 0.1 + 0.9 * (0.5 ^ (index / (total_genres - 1)))
-            let total_genres = artist.genres.len();
-            let weight = if total_genres == 1 {
-                1.0
-            } else {
-                let normalized_index = genre_index as f32 / (total_genres - 1) as f32;
-                0.1 + 0.9 * (0.5_f32.powf(normalized_index))
-            };
+    for (artist_page, artist) in &processed_artists.0 {
+        // Includes links and views via the artist's redirects (e.g. "2Pac" → Tupac Shakur)
+        let link_count = page_aliases.aggregated_link_count(artist_page, inbound_link_counts);
+        let views = pageviews
+            .map(|pageviews| page_aliases.aggregated_link_count(artist_page, pageviews))
+            .unwrap_or(0);
+        let popularity = ranking.popularity(link_count, views);
 
-            let weighted_score = link_count * weight;
+        let category_fallback;
+        let genres = if artist.genres.is_empty() {
+            category_fallback = category_genres.genres(&artist.categories);
+            &category_fallback
+        } else {
+            &artist.genres
+        };
 
-            *intermediate_gta
+        for (genre_index, genre) in genres.iter().enumerate() {
+            let Some(page_name) = links_to_articles.map(genre) else {
+                continue;
+            };
+
+            // An artist can link to the same genre more than once (e.g. via a redirect)
+            let score = intermediate_gta
                 .entry(page_name.clone())
                 .or_default()
                 .entry(artist_page.clone())
-                .or_default() += weighted_score;
+                .or_insert(ArtistScore {
+                    diversity_penalty: 1.0,
+                    ..Default::default()
+                });
+            score.popularity = popularity;
+            score.weight += genre_position_weight(genre_index, genres.len());
+            score.score = score.popularity * score.weight;
 
             artist_genres
                 .entry(artist_page.clone())
                 .or_default()
-                .insert(page_name);
+                .insert(page_name.clone());
         }
     }
 
-    let mut gta: BTreeMap<types::PageName, Vec<(types::PageName, f32)>> = intermediate_gta
+    let mut gta: GenreTopArtists = intermediate_gta
         .into_iter()
         .map(|(genre, artists)| (genre, artists.into_iter().collect::<Vec<_>>()))
         .collect();
 
     for artists in gta.values_mut() {
-        artists.sort_by(|(page_a, score_a), (page_b, score_b)| {
-            let score_cmp = score_b.partial_cmp(score_a).unwrap();
-            if score_cmp == std::cmp::Ordering::Equal {
-                page_a.cmp(page_b)
-            } else {
-                score_cmp
-            }
-        });
+        sort_by_score(artists);
+        diversify(artists, &associated_acts);
     }
 
     std::fs::write(output_path_gta, serde_json::to_string_pretty(&gta)?)?;
@@ -114,3 +325,252 @@ pub fn calculate(
 
     Ok((gta, artist_genres))
 }
+
+/// Sort artists by descending score, breaking ties by page name.
+fn sort_by_score(artists: &mut [(types::PageName, ArtistScore)]) {
+    artists.sort_by(|(page_a, score_a), (page_b, score_b)| {
+        score_b
+            .score
+            .total_cmp(&score_a.score)
+            .then_with(|| page_a.cmp(page_b))
+    });
+}
+
+/// Rerank the top of a genre's sorted artists so that acts associated with higher-ranked ones
+/// are penalised by [`DIVERSITY_PENALTY`] for each such act.
+///
+/// Artists are picked greedily: each position goes to the best remaining artist after
+/// penalising them for the associated acts already picked.
+fn diversify(
+    artists: &mut Vec<(types::PageName, ArtistScore)>,
+    associated_acts: &BTreeMap<types::PageName, BTreeSet<types::PageName>>,
+) {
+    let mut remaining = std::mem::take(artists);
+    while artists.len() < DIVERSITY_WINDOW && !remaining.is_empty() {
+        for (page, score) in &mut remaining {
+            let associated_above = associated_acts.get(page).map_or(0, |acts| {
+                artists
+                    .iter()
+                    .filter(|(picked, _)| acts.contains(picked))
+                    .count()
+            });
+            score.diversity_penalty = DIVERSITY_PENALTY.powi(associated_above as i32);
+            score.score = score.popularity * score.weight * score.diversity_penalty;
+        }
+        // `remaining` keeps its sorted order, so ties go to the artist that was ranked higher
+        let best = remaining
+            .iter()
+            .enumerate()
+            .fold(0, |best, (index, (_, score))| {
+                if score.score > remaining[best].1.score {
+                    index
+                } else {
+                    best
+                }
+            });
+        artists.push(remaining.remove(best));
+    }
+    // Beyond the window, keep the unpenalised order
+    for (_, score) in &mut remaining {
+        score.diversity_penalty = 1.0;
+        score.score = score.popularity * score.weight;
+    }
+    artists.extend(remaining);
+}
+
+/// How representative an artist is of the genre at `index` of their infobox's `total` genres.
+///
+/// It's the product of two terms:
+/// - the position, which decays exponentially from 1.0 for the first genre to 0.55 for the
+///   sixth: `0.1 + 0.9 * 0.5 ^ (index / 5)`;
+/// - the length, which shares the weight out among the genres listed:
+///   `1 / (1 + 0.1 * (total - 1))`, from 1.0 for an artist's only genre to 0.5 for eleven.
+///
+/// So a genre counts for more near the top of a list, and for more in a shorter list. This keeps
+/// superstars who dabbled in many genres from outranking the artists a genre is actually
+/// associated with.
+fn genre_position_weight(index: usize, total: usize) -> f32 {
+    let position = 0.1 + 0.9 * 0.5_f32.powf(index as f32 / 5.0);
+    let length = 1.0 / (1.0 + 0.1 * total.saturating_sub(1) as f32);
+    position * length
+}
+
+/// Select the artists to show for a genre from its ranked artists, given each artist's
+/// (aggregated) inbound link count.
+pub fn select<S: Clone>(
+    ranked_artists: &[(types::PageName, S)],
+    config: &types::TopArtistsConfig,
+    link_count: impl Fn(&types::PageName) -> usize,
+) -> Vec<(types::PageName, S)> {
+    let eligible: Vec<(types::PageName, S)> = ranked_artists
+        .iter()
+        .filter(|(artist, _)| link_count(artist) >= config.min_links)
+        .cloned()
+        .collect();
+    if eligible.len() < config.min_artists {
+        return vec![];
+    }
+    eligible.into_iter().take(config.count).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn select_applies_count_and_thresholds() {
+        let pn = |name: &str| types::PageName::new(name, None);
+        let ranked = vec![
+            (pn("A"), 30.0),
+            (pn("B"), 20.0),
+            (pn("C"), 10.0),
+            (pn("D"), 1.0),
+        ];
+        let links = |artist: &types::PageName| match artist.name.as_str() {
+            "A" => 30,
+            "B" => 2,
+            "C" => 10,
+            _ => 1,
+        };
+        let config = |count, min_links, min_artists| types::TopArtistsConfig {
+            count,
+            min_links,
+            min_artists,
+        };
+
+        assert_eq!(
+            select(&ranked, &config(2, 0, 0), links),
+            vec![(pn("A"), 30.0), (pn("B"), 20.0)]
+        );
+        assert_eq!(
+            select(&ranked, &config(10, 5, 0), links),
+            vec![(pn("A"), 30.0), (pn("C"), 10.0)]
+        );
+        assert_eq!(select(&ranked, &config(10, 5, 3), links), vec![]);
+    }
+
+    #[test]
+    fn coverage_stats_reports_empty_genres_and_outliers() {
+        let pn = |name: &str| types::PageName::new(name, None);
+        let score = ArtistScore::default();
+        let gta =
+            GenreTopArtists::from_iter([(pn("House"), vec![(pn("A"), score), (pn("B"), score)])]);
+        let many_genres: BTreeSet<_> = (0..=MAX_PLAUSIBLE_GENRES)
+            .map(|i| pn(&format!("Genre {i}")))
+            .collect();
+        let artist_genres = ArtistGenres::from_iter([
+            (pn("A"), BTreeSet::from_iter([pn("House")])),
+            (pn("B"), many_genres),
+        ]);
+        let links = |artist: &types::PageName| if artist.name == "A" { 10 } else { 2 };
+
+        let stats =
+            CoverageStats::calculate(&[pn("House"), pn("Techno")], &gta, &artist_genres, links);
+
+        assert_eq!(
+            stats.artists_per_genre,
+            BTreeMap::from_iter([(pn("House"), 2), (pn("Techno"), 0)])
+        );
+        assert_eq!(stats.genres_without_artists, vec![pn("Techno")]);
+        assert_eq!(
+            stats.artists_with_many_genres,
+            BTreeMap::from_iter([(pn("B"), MAX_PLAUSIBLE_GENRES + 1)])
+        );
+        assert_eq!(stats.artist_link_counts.count, 2);
+        assert_eq!(stats.artist_link_counts.min, 2);
+        assert_eq!(stats.artist_link_counts.median, 10);
+        assert_eq!(stats.artist_link_counts.max, 10);
+    }
+
+    #[test]
+    fn category_genres_match_wildcard_patterns() {
+        let category_genres: CategoryGenres = toml::from_str(
+            r#"
+            [[patterns]]
+            category = "* house musicians"
+            genre = "House music"
+
+            [[patterns]]
+            category = "* rappers"
+            genre = "Hip-hop"
+
+            [[patterns]]
+            category = "* hip-hop musicians"
+            genre = "Hip-hop"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            category_genres.genres(&[
+                "1990 births".to_string(),
+                "English house musicians".to_string(),
+                "English rappers".to_string(),
+                "English hip-hop musicians".to_string(),
+            ]),
+            ["House music", "Hip-hop"]
+        );
+        assert!(
+            category_genres
+                .genres(&["House music".to_string()])
+                .is_empty()
+        );
+        assert!(wildcard_match(
+            "* house musicians",
+            "english house musicians"
+        ));
+        assert!(!wildcard_match("* house musicians", "english house music"));
+        assert!(wildcard_match("house", "house"));
+        assert!(!wildcard_match("house", "acid house"));
+    }
+
+    #[test]
+    fn diversify_penalises_associated_acts() {
+        let pn = |name: &str| types::PageName::new(name, None);
+        let score = |popularity| ArtistScore {
+            score: popularity,
+            popularity,
+            weight: 1.0,
+            diversity_penalty: 1.0,
+        };
+        let mut artists = vec![
+            (pn("Band"), score(100.0)),
+            (pn("Singer"), score(80.0)),
+            (pn("Side project"), score(60.0)),
+            (pn("Other band"), score(50.0)),
+        ];
+        let associated_acts = BTreeMap::from_iter([
+            (
+                pn("Band"),
+                BTreeSet::from_iter([pn("Singer"), pn("Side project")]),
+            ),
+            (
+                pn("Singer"),
+                BTreeSet::from_iter([pn("Band"), pn("Side project")]),
+            ),
+            (
+                pn("Side project"),
+                BTreeSet::from_iter([pn("Band"), pn("Singer")]),
+            ),
+        ]);
+
+        diversify(&mut artists, &associated_acts);
+
+        let order: Vec<_> = artists.iter().map(|(page, _)| page.name.as_str()).collect();
+        assert_eq!(order, ["Band", "Other band", "Singer", "Side project"]);
+        assert_eq!(artists[2].1.diversity_penalty, 0.5);
+        assert_eq!(artists[2].1.score, 40.0);
+        // Associated with both the band and the singer
+        assert_eq!(artists[3].1.diversity_penalty, 0.25);
+    }
+
+    #[test]
+    fn genre_position_weight_decays_with_position_and_list_length() {
+        assert_eq!(genre_position_weight(0, 1), 1.0);
+        assert!((genre_position_weight(5, 6) - 0.55 / 1.5).abs() < 1e-6);
+        assert!(genre_position_weight(1, 6) > genre_position_weight(2, 6));
+        // The same position counts for more in a shorter list
+        assert!(genre_position_weight(0, 1) > genre_position_weight(0, 6));
+        assert!(genre_position_weight(1, 2) > genre_position_weight(1, 6));
+    }
+}
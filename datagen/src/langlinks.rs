@@ -0,0 +1,168 @@
+//! Reads the compressed Wikipedia interlanguage links dump SQL (`*-langlinks.sql.gz`) to get the
+//! titles of genre pages in other languages' Wikipedias, so that genres can be searched for by
+//! those names too.
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+use anyhow::Context as _;
+
+use crate::{link_counts::common, types::PageName, util};
+
+/// The titles of each page in other languages' Wikipedias, keyed by language code (e.g. `de`).
+pub type LangLinks = BTreeMap<PageName, BTreeMap<String, String>>;
+
+/// The columns of the `langlinks` table that are used.
+#[derive(Debug, Clone, Copy)]
+struct LangLinkColumns {
+    from: usize,
+    lang: usize,
+    title: usize,
+}
+
+/// Read the titles of `tracked_pages` in other languages, or load them from `output_path` if
+/// they've already been read for the same pages. `id_to_page_names` maps page IDs to the pages they belong to.
+pub(crate) fn read(
+    start: std::time::Instant,
+    langlinks_path: &Path,
+    id_to_page_names: &BTreeMap<u64, PageName>,
+    tracked_pages: &BTreeSet<PageName>,
+    output_path: &Path,
+) -> anyhow::Result<LangLinks> {
+    let output_file_path = output_path.join("langlinks.json");
+    let tracked_pages_hash = util::pages_hash(tracked_pages);
+    if let Some(langlinks) = util::load_pages_cache(&output_file_path, tracked_pages_hash) {
+        return Ok(langlinks);
+    }
+    if output_file_path.is_file() {
+        println!(
+            "{:.2}s: cached langlinks are for other pages, reading them again",
+            start.elapsed().as_secs_f32()
+        );
+    }
+
+    println!("{:.2}s: reading langlinks", start.elapsed().as_secs_f32());
+
+    let tracked_ids: BTreeMap<u64, &PageName> = id_to_page_names
+        .iter()
+        .filter(|(_, page)| tracked_pages.contains(*page))
+        .map(|(id, page)| (*id, page))
+        .collect();
+
+    let mut langlinks_file = common::open_sql_dump(langlinks_path)?;
+    let header = common::read_until_prefix(&mut langlinks_file, b"INSERT INTO `langlinks` VALUES ")
+        .context("Failed to find INSERT INTO `langlinks` VALUES statement in langlinks file")?;
+    let column_names = common::column_names(&header, "langlinks");
+    let index = |name: &str, default: usize| {
+        column_names
+            .iter()
+            .position(|c| c == name)
+            .unwrap_or(default)
+    };
+    let columns = LangLinkColumns {
+        from: index("ll_from", 0),
+        lang: index("ll_lang", 1),
+        title: index("ll_title", 2),
+    };
+
+    let langlinks = parse_langlinks_tuple_stream(&mut langlinks_file, start, columns, &tracked_ids)
+        .context("Failed to parse langlinks tuples from stream")?;
+
+    util::save_pages_cache(&output_file_path, tracked_pages_hash, &langlinks)
+        .context("Failed to write langlinks")?;
+
+    println!(
+        "{:.2}s: read titles in other languages for {} pages",
+        start.elapsed().as_secs_f32(),
+        langlinks.len()
+    );
+
+    Ok(langlinks)
+}
+
+fn parse_langlinks_tuple_stream(
+    stream: &mut (impl std::io::BufRead + Send),
+    start: std::time::Instant,
+    columns: LangLinkColumns,
+    tracked_ids: &BTreeMap<u64, &PageName>,
+) -> anyhow::Result<LangLinks> {
+    let (langlinks, tuples_parsed) = common::parse_tuples_parallel(
+        stream,
+        LangLinks::new,
+        |langlinks, values| {
+            if let (Some(page), Some(lang), Some(title)) = (
+                values
+                    .get(columns.from)
+                    .and_then(|v| v.as_integer())
+                    .and_then(|id| tracked_ids.get(&(id as u64))),
+                values.get(columns.lang).and_then(|v| v.as_str()),
+                values.get(columns.title).and_then(|v| v.as_str()),
+            ) && !title.is_empty()
+            {
+                langlinks
+                    .entry((*page).clone())
+                    .or_default()
+                    .insert(lang.to_string(), title.replace('_', " "));
+            }
+        },
+        |mut a, b| {
+            for (page, titles) in b {
+                a.entry(page).or_default().extend(titles);
+            }
+            a
+        },
+        |tuples_parsed| {
+            println!(
+                "{:.2}s: parsed {tuples_parsed} langlink tuples",
+                start.elapsed().as_secs_f32(),
+            );
+        },
+    )?;
+
+    println!(
+        "{:.2}s: parsed {tuples_parsed} langlink tuples",
+        start.elapsed().as_secs_f32(),
+    );
+
+    Ok(langlinks)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_langlinks_tuples() {
+        let techno = PageName::new("Techno", None);
+        let tracked_ids = BTreeMap::from_iter([(1, &techno)]);
+        let data = "INSERT INTO `langlinks` VALUES (1,'de','Techno'),(1,'ja','テクノ'),\
+                    (1,'fr','Techno_(musique)'),(2,'de','Schlager'),(1,'es','');\n";
+
+        let langlinks = parse_langlinks_tuple_stream(
+            &mut Cursor::new(data.as_bytes()),
+            std::time::Instant::now(),
+            LangLinkColumns {
+                from: 0,
+                lang: 1,
+                title: 2,
+            },
+            &tracked_ids,
+        )
+        .unwrap();
+
+        assert_eq!(
+            langlinks,
+            BTreeMap::from_iter([(
+                techno,
+                BTreeMap::from_iter([
+                    ("de".to_string(), "Techno".to_string()),
+                    ("fr".to_string(), "Techno (musique)".to_string()),
+                    ("ja".to_string(), "テクノ".to_string()),
+                ])
+            )])
+        );
+    }
+}
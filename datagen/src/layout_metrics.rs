@@ -0,0 +1,305 @@
+//! Measures of a layout's quality, reported after each layout (in `layout_metrics.json`, and by
+//! `relayout`) so that parameter changes can be compared by number instead of by eye.
+//!
+//! The stress and edge crossings are estimated from samples, which are the same from run to
+//! run, so that differences between layouts of the same graph are the layouts'.
+
+use std::collections::BTreeMap;
+
+use rayon::prelude::*;
+use serde::Serialize;
+
+use layout::{EdgeSprings, node_radii};
+
+use crate::frontend_types::EdgeType;
+
+/// How many nodes the stress is measured from.
+const STRESS_SOURCES: usize = 100;
+
+/// How many pairs of edges are tested for crossings.
+const CROSSING_SAMPLES: usize = 200_000;
+
+/// How good a layout is; see the [module documentation](self).
+#[derive(Debug, PartialEq, Serialize)]
+pub struct LayoutMetrics {
+    /// How far the distances between connected nodes are from the lengths of the shortest paths
+    /// between them, at the scale that fits them best: from 0 (exactly proportional) to 1. Each
+    /// pair's error is relative to the length of its path, so that near pairs count as much as
+    /// far ones.
+    pub normalized_stress: f64,
+    /// The mean length of an edge, in output units.
+    pub mean_edge_length: f64,
+    /// The mean length of each type of edge, in output units.
+    pub mean_edge_length_by_type: BTreeMap<&'static str, f64>,
+    /// The RMS difference between the edges' lengths and their rest lengths (their type's
+    /// multiple of `LINK_DISTANCE`, at the scale that fits them best), relative to the rest
+    /// lengths: 0 if every edge is at its rest length.
+    pub edge_length_error: f64,
+    /// The number of pairs of edges that cross, estimated from a sample of them.
+    pub estimated_edge_crossings: f64,
+    /// The number of pairs of nodes that overlap, by more than 1%, the distance the layout keeps
+    /// them apart (see `NODE_MIN_DIST` and `HUB_RADIUS`).
+    pub node_overlaps: usize,
+}
+
+impl LayoutMetrics {
+    /// Measure the layout of a graph with the given edges (as `(source, target)` pairs) and edge
+    /// types, with the nodes at `positions`.
+    pub fn measure(
+        positions: &[[f64; 2]],
+        adjacency: &[(usize, usize)],
+        edge_types: &[EdgeType],
+    ) -> Self {
+        let mut neighbors = vec![vec![]; positions.len()];
+        for &(source, target) in adjacency {
+            neighbors[source].push(target);
+            neighbors[target].push(source);
+        }
+        let length =
+            |(source, target): (usize, usize)| distance(positions[source], positions[target]);
+
+        let springs = EdgeSprings::from_env();
+        let mut lengths_by_type: BTreeMap<&'static str, (f64, usize)> = BTreeMap::new();
+        let (mut length_times_rest, mut rest_squared) = (0.0, 0.0);
+        for (index, &edge) in adjacency.iter().enumerate() {
+            let ty = edge_types.get(index).copied();
+            let sums = lengths_by_type
+                .entry(ty.as_ref().map_or("untyped", EdgeType::name))
+                .or_default();
+            sums.0 += length(edge);
+            sums.1 += 1;
+            let rest = springs.get(ty).1;
+            length_times_rest += length(edge) * rest;
+            rest_squared += rest * rest;
+        }
+        // The scale that fits the lengths to the rest lengths best, in the least-squares sense
+        let unit = if rest_squared > 0.0 {
+            length_times_rest / rest_squared
+        } else {
+            0.0
+        };
+        let edge_length_error = if unit > 0.0 {
+            let sum_squared: f64 = adjacency
+                .iter()
+                .enumerate()
+                .map(|(index, &edge)| {
+                    let rest = unit * springs.get(edge_types.get(index).copied()).1;
+                    ((length(edge) - rest) / rest).powi(2)
+                })
+                .sum();
+            (sum_squared / adjacency.len() as f64).sqrt()
+        } else {
+            0.0
+        };
+
+        let mut degrees = vec![0u32; positions.len()];
+        for &(source, target) in adjacency {
+            degrees[source] += 1;
+            degrees[target] += 1;
+        }
+
+        Self {
+            normalized_stress: normalized_stress(positions, &neighbors),
+            mean_edge_length: if adjacency.is_empty() {
+                0.0
+            } else {
+                adjacency.iter().map(|&edge| length(edge)).sum::<f64>() / adjacency.len() as f64
+            },
+            mean_edge_length_by_type: lengths_by_type
+                .into_iter()
+                .map(|(ty, (sum, count))| (ty, sum / count as f64))
+                .collect(),
+            edge_length_error,
+            estimated_edge_crossings: estimated_edge_crossings(positions, adjacency, &degrees),
+            node_overlaps: node_overlaps(positions, &node_radii(&degrees)),
+        }
+    }
+
+    /// Print the metrics on one line.
+    pub fn print(&self) {
+        println!(
+            "  layout metrics: normalized stress {:.4}, mean edge length {:.1} \
+             (rest length error {:.3}), ~{:.0} edge crossings, {} node overlaps",
+            self.normalized_stress,
+            self.mean_edge_length,
+            self.edge_length_error,
+            self.estimated_edge_crossings,
+            self.node_overlaps
+        );
+    }
+}
+
+fn distance(a: [f64; 2], b: [f64; 2]) -> f64 {
+    ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2)).sqrt()
+}
+
+/// The normalized stress (see [`LayoutMetrics::normalized_stress`]) of the pairs of connected
+/// nodes that have one of [`STRESS_SOURCES`] evenly spaced nodes in them.
+fn normalized_stress(positions: &[[f64; 2]], neighbors: &[Vec<usize>]) -> f64 {
+    let num_nodes = positions.len();
+    let mut sources: Vec<usize> = (0..STRESS_SOURCES.min(num_nodes))
+        .map(|k| k * num_nodes / STRESS_SOURCES.min(num_nodes))
+        .collect();
+    sources.dedup();
+    // For each pair, with r its distance and d the length of its path: Σ r/d, Σ r²/d², and the
+    // number of pairs
+    let (sum, sum_squared, pairs) = sources
+        .par_iter()
+        .map(|&source| {
+            let mut lengths = vec![usize::MAX; num_nodes];
+            lengths[source] = 0;
+            let mut queue = std::collections::VecDeque::from([source]);
+            let mut sums = (0.0, 0.0, 0usize);
+            while let Some(node) = queue.pop_front() {
+                for &neighbor in &neighbors[node] {
+                    if lengths[neighbor] == usize::MAX {
+                        lengths[neighbor] = lengths[node] + 1;
+                        queue.push_back(neighbor);
+                        let ratio = distance(positions[source], positions[neighbor])
+                            / lengths[neighbor] as f64;
+                        sums.0 += ratio;
+                        sums.1 += ratio * ratio;
+                        sums.2 += 1;
+                    }
+                }
+            }
+            sums
+        })
+        .collect::<Vec<_>>()
+        .into_iter()
+        .fold((0.0, 0.0, 0), |a, b| (a.0 + b.0, a.1 + b.1, a.2 + b.2));
+    if pairs == 0 || sum_squared <= 0.0 {
+        return 0.0;
+    }
+    // Σ (s·r/d - 1)² is least at s = Σ (r/d) / Σ (r²/d²), where it's this
+    (pairs as f64 - sum * sum / sum_squared) / pairs as f64
+}
+
+/// The number of pairs of edges that cross, estimated from [`CROSSING_SAMPLES`] random pairs of
+/// edges that don't share a node.
+fn estimated_edge_crossings(
+    positions: &[[f64; 2]],
+    adjacency: &[(usize, usize)],
+    degrees: &[u32],
+) -> f64 {
+    let edges = adjacency.len();
+    // The pairs of edges that share a node can't cross
+    let sharing: f64 = degrees
+        .iter()
+        .map(|&d| d as f64 * (d as f64 - 1.0) / 2.0)
+        .sum();
+    let disjoint_pairs = edges as f64 * (edges as f64 - 1.0) / 2.0 - sharing;
+    if edges < 2 || disjoint_pairs <= 0.0 {
+        return 0.0;
+    }
+
+    let mut rng_state: u64 = 0x2545_F491_4F6C_DD1D;
+    let mut next_index = || {
+        rng_state ^= rng_state << 13;
+        rng_state ^= rng_state >> 7;
+        rng_state ^= rng_state << 17;
+        (rng_state % edges as u64) as usize
+    };
+    let (mut sampled, mut crossing) = (0usize, 0usize);
+    for _ in 0..CROSSING_SAMPLES {
+        let (a, b) = (adjacency[next_index()], adjacency[next_index()]);
+        if a.0 == b.0 || a.0 == b.1 || a.1 == b.0 || a.1 == b.1 {
+            continue;
+        }
+        sampled += 1;
+        if segments_cross(
+            positions[a.0],
+            positions[a.1],
+            positions[b.0],
+            positions[b.1],
+        ) {
+            crossing += 1;
+        }
+    }
+    if sampled == 0 {
+        return 0.0;
+    }
+    crossing as f64 / sampled as f64 * disjoint_pairs
+}
+
+/// Whether the segments `a`–`b` and `c`–`d` cross (touching doesn't count).
+fn segments_cross(a: [f64; 2], b: [f64; 2], c: [f64; 2], d: [f64; 2]) -> bool {
+    // Which side of the line through `p` and `q` that `r` is on
+    let side = |p: [f64; 2], q: [f64; 2], r: [f64; 2]| {
+        ((q[0] - p[0]) * (r[1] - p[1]) - (q[1] - p[1]) * (r[0] - p[0])).signum()
+    };
+    side(a, b, c) * side(a, b, d) < 0.0 && side(c, d, a) * side(c, d, b) < 0.0
+}
+
+/// The number of pairs of nodes that are closer than the sum of their `radii` (less 1%). Uses a
+/// uniform grid, as the collision pass does, so that each node is only tested against those in
+/// the 3×3 block of cells around it.
+fn node_overlaps(positions: &[[f64; 2]], radii: &[f64]) -> usize {
+    let max_radius = radii.iter().copied().fold(0.0_f64, f64::max);
+    if max_radius <= 0.0 {
+        return 0;
+    }
+    let cell = max_radius * 2.0;
+    let mut grid: BTreeMap<(i64, i64), Vec<usize>> = BTreeMap::new();
+    for (i, p) in positions.iter().enumerate() {
+        let key = ((p[0] / cell).floor() as i64, (p[1] / cell).floor() as i64);
+        grid.entry(key).or_default().push(i);
+    }
+    let mut overlaps = 0;
+    for (&(cx, cy), members) in &grid {
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let Some(others) = grid.get(&(cx + dx, cy + dy)) else {
+                    continue;
+                };
+                for &i in members {
+                    for &j in others {
+                        if j > i
+                            && distance(positions[i], positions[j]) < 0.99 * (radii[i] + radii[j])
+                        {
+                            overlaps += 1;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    overlaps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn an_evenly_spaced_path_has_no_stress_crossings_or_overlaps() {
+        // 0 - 1 - 2 - 3, each edge 100 units long
+        let positions = [[0.0, 0.0], [100.0, 0.0], [200.0, 0.0], [300.0, 0.0]];
+        let adjacency = [(0, 1), (1, 2), (2, 3)];
+        let metrics = LayoutMetrics::measure(&positions, &adjacency, &[EdgeType::Derivative; 3]);
+        assert!(metrics.normalized_stress.abs() < 1e-9);
+        assert_eq!(metrics.mean_edge_length, 100.0);
+        assert_eq!(
+            metrics.mean_edge_length_by_type,
+            BTreeMap::from([("derivative", 100.0)])
+        );
+        assert!(metrics.edge_length_error.abs() < 1e-9);
+        assert_eq!(metrics.estimated_edge_crossings, 0.0);
+        assert_eq!(metrics.node_overlaps, 0);
+    }
+
+    #[test]
+    fn crossing_diagonals_and_stacked_nodes_are_counted() {
+        // The diagonals of a square cross, and a fifth node sits on a corner
+        let positions = [
+            [0.0, 0.0],
+            [100.0, 0.0],
+            [100.0, 100.0],
+            [0.0, 100.0],
+            [0.0, 0.0],
+        ];
+        let metrics = LayoutMetrics::measure(&positions, &[(0, 2), (1, 3)], &[]);
+        assert_eq!(metrics.estimated_edge_crossings, 1.0);
+        assert_eq!(metrics.node_overlaps, 1);
+    }
+}
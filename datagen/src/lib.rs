@@ -1,8 +1,10 @@
 //! Library re-exports for binary tools.
 #![warn(missing_docs)]
 
+pub mod centrality;
 pub mod color_propagation;
+pub mod communities;
 pub mod data_patches;
-pub mod force_layout;
 pub mod frontend_types;
+pub mod layout_metrics;
 pub mod types;
@@ -1,59 +1,182 @@
 //! Reads the compressed Wikipedia links dump SQL to extract the number of links to each page we track.
+//!
+//! Both generations of the `pagelinks` schema are supported: the current one, where links reference
+//! a `linktarget` row by ID, and the legacy one, where links store the target's namespace and title.
+//!
+//! Only links from articles are counted. If a `page` dump is available, it's used to find the
+//! articles (optionally excluding list and disambiguation pages); otherwise, the namespace of the
+//! linking page recorded in `pagelinks` is used.
 
 use std::{
     collections::{BTreeMap, BTreeSet},
-    io::Read as _,
     path::Path,
 };
 
 use anyhow::Context as _;
+use serde::{Deserialize, Serialize};
 
-use crate::types;
+use crate::{types, util};
 
+/// Identifies the inputs that a set of link counts was parsed from, so that cached counts are
+/// only reused when they would be identical to a fresh parse.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct LinkCountsCacheKey {
+    dump_date: jiff::civil::Date,
+    links_hash: u64,
+    linktargets_hash: Option<u64>,
+    page_hash: Option<u64>,
+    exclude_list_pages: bool,
+    tracked_pages_hash: u64,
+}
+
+/// The binary (postcard) cache of parsed link counts.
+#[derive(Serialize, Deserialize)]
+struct LinkCountsCache {
+    key: LinkCountsCacheKey,
+    counts: BTreeMap<types::PageName, usize>,
+}
+
+/// Read the number of inbound links to each tracked page, or load them from the cache in
+/// `output_path` if they've already been read from the same dumps.
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn read(
     start: std::time::Instant,
-    wikipedia_linktargets_path: &Path,
+    dump_date: jiff::civil::Date,
+    wikipedia_page_path: Option<&Path>,
+    wikipedia_linktargets_path: Option<&Path>,
     wikipedia_links_path: &Path,
     tracked_pages: &BTreeSet<types::PageName>,
+    config: &types::LinkCountsConfig,
     output_path: &Path,
+    previous_output_path: Option<&Path>,
 ) -> anyhow::Result<BTreeMap<types::PageName, usize>> {
-    let output_file_path = output_path.join("inbound_link_counts.json");
-    if output_file_path.is_file() {
-        return serde_json::from_str(&std::fs::read_to_string(&output_file_path).with_context(
-            || {
-                format!(
-                    "Failed to read existing link counts file: {}",
-                    output_file_path.display()
-                )
-            },
-        )?)
-        .with_context(|| {
-            format!(
-                "Failed to parse JSON from existing link counts file: {}",
-                output_file_path.display()
-            )
-        });
+    anyhow::ensure!(
+        !config.exclude_list_pages || wikipedia_page_path.is_some(),
+        "Excluding links from list pages requires a page dump (*-page.sql.gz) in the dump directory"
+    );
+
+    let cache_path = output_path.join(CACHE_FILE_NAME);
+
+    if config.reuse_previous {
+        // The reused counts aren't cached, so they're never mistaken for a full parse (or reused
+        // again by the next dump, compounding the drift).
+        match previous_output_path.and_then(|path| load_cache(&path.join(CACHE_FILE_NAME))) {
+            Some(previous) => {
+                let (mut counts, new_pages) =
+                    reuse_previous_counts(&previous.counts, tracked_pages);
+                println!(
+                    "{:.2}s: reused inbound link counts from the {} dump, reading them for {} newly tracked pages",
+                    start.elapsed().as_secs_f32(),
+                    previous.key.dump_date,
+                    new_pages.len()
+                );
+                // The previous dump didn't count the links to pages it didn't track, so theirs
+                // are read from this one (without caching the linktargets, which would then
+                // only cover the new pages)
+                if !new_pages.is_empty() {
+                    counts.extend(parse_counts(
+                        start,
+                        wikipedia_page_path,
+                        wikipedia_linktargets_path,
+                        wikipedia_links_path,
+                        &new_pages,
+                        config,
+                        None,
+                    )?);
+                }
+                return Ok(counts);
+            }
+            None => println!(
+                "{:.2}s: no previous inbound link counts to reuse, parsing links",
+                start.elapsed().as_secs_f32()
+            ),
+        }
     }
 
-    let linktargets = linktargets::read(
+    println!("{:.2}s: hashing link dumps", start.elapsed().as_secs_f32());
+    let key = LinkCountsCacheKey {
+        dump_date,
+        links_hash: util::file_content_hash(wikipedia_links_path)?,
+        linktargets_hash: wikipedia_linktargets_path
+            .map(util::file_content_hash)
+            .transpose()?,
+        page_hash: wikipedia_page_path
+            .map(util::file_content_hash)
+            .transpose()?,
+        exclude_list_pages: config.exclude_list_pages,
+        tracked_pages_hash: tracked_pages
+            .iter()
+            .fold(util::content_hash(&[]), |hash, page| {
+                util::extend_content_hash(hash, format!("{page}\n").as_bytes())
+            }),
+    };
+
+    if cache_path.is_file() {
+        match load_cache(&cache_path) {
+            Some(cache) if cache.key == key => {
+                println!(
+                    "{:.2}s: loaded cached inbound link counts",
+                    start.elapsed().as_secs_f32()
+                );
+                return Ok(cache.counts);
+            }
+            _ => println!(
+                "{:.2}s: cached inbound link counts are out of date, reparsing",
+                start.elapsed().as_secs_f32()
+            ),
+        }
+    }
+
+    let counts = parse_counts(
         start,
+        wikipedia_page_path,
         wikipedia_linktargets_path,
+        wikipedia_links_path,
         tracked_pages,
-        output_path,
+        config,
+        Some(output_path),
+    )?;
+
+    let cache = LinkCountsCache { key, counts };
+    std::fs::write(
+        &cache_path,
+        postcard::to_stdvec(&cache).context("Failed to serialize inbound link counts")?,
     )
     .with_context(|| {
         format!(
-            "Failed to read linktargets from: {}",
-            wikipedia_linktargets_path.display()
+            "Failed to write inbound link counts to file: {}",
+            cache_path.display()
         )
     })?;
 
+    Ok(cache.counts)
+}
+
+/// Parse the number of inbound links to each of `tracked_pages` from the dumps, caching the
+/// tracked linktargets in `linktargets_cache_path`, if given.
+fn parse_counts(
+    start: std::time::Instant,
+    wikipedia_page_path: Option<&Path>,
+    wikipedia_linktargets_path: Option<&Path>,
+    wikipedia_links_path: &Path,
+    tracked_pages: &BTreeSet<types::PageName>,
+    config: &types::LinkCountsConfig,
+    linktargets_cache_path: Option<&Path>,
+) -> anyhow::Result<BTreeMap<types::PageName, usize>> {
+    let articles = wikipedia_page_path
+        .map(|path| {
+            pages::read(start, path, config.exclude_list_pages)
+                .with_context(|| format!("Failed to read pages from: {}", path.display()))
+        })
+        .transpose()?;
+
     links::read(
         start,
         wikipedia_links_path,
-        &linktargets,
+        wikipedia_linktargets_path,
+        articles.as_ref(),
         tracked_pages,
-        &output_file_path,
+        linktargets_cache_path,
     )
     .with_context(|| {
         format!(
@@ -63,16 +186,157 @@ pub(crate) fn read(
     })
 }
 
-mod common {
-    use anyhow::Context as _;
+/// The name of the link counts cache within an output directory.
+const CACHE_FILE_NAME: &str = "inbound_link_counts.bin";
+
+/// Load a link counts cache, if there is one and it's readable.
+fn load_cache(path: &Path) -> Option<LinkCountsCache> {
+    postcard::from_bytes(&std::fs::read(path).ok()?).ok()
+}
 
-    pub fn skip_until_prefix(
+/// Get the counts of the tracked pages from a previous dump's counts, and the tracked pages that
+/// weren't tracked then (and so need counting).
+fn reuse_previous_counts(
+    previous_counts: &BTreeMap<types::PageName, usize>,
+    tracked_pages: &BTreeSet<types::PageName>,
+) -> (BTreeMap<types::PageName, usize>, BTreeSet<types::PageName>) {
+    let mut new_pages = BTreeSet::new();
+    let counts = tracked_pages
+        .iter()
+        .filter_map(|page| match previous_counts.get(page) {
+            Some(count) => Some((page.clone(), *count)),
+            None => {
+                new_pages.insert(page.clone());
+                None
+            }
+        })
+        .collect();
+    (counts, new_pages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reuse_previous_counts() {
+        let pn = |name: &str| types::PageName::new(name, None);
+        let previous = BTreeMap::from_iter([(pn("Daft Punk"), 120), (pn("Justice (band)"), 40)]);
+        let tracked = BTreeSet::from_iter([pn("Daft Punk"), pn("Fred Again")]);
+
+        let (counts, new_pages) = reuse_previous_counts(&previous, &tracked);
+        assert_eq!(counts, BTreeMap::from_iter([(pn("Daft Punk"), 120)]));
+        assert_eq!(new_pages, BTreeSet::from_iter([pn("Fred Again")]));
+    }
+
+    #[test]
+    fn test_reused_counts_read_newly_tracked_pages() {
+        use std::io::Write as _;
+
+        let pn = |name: &str| types::PageName::new(name, None);
+        let dir = std::env::temp_dir().join(format!("datagen-link-counts-{}", std::process::id()));
+        let previous_path = dir.join("previous");
+        std::fs::create_dir_all(&previous_path).unwrap();
+
+        let previous = LinkCountsCache {
+            key: LinkCountsCacheKey {
+                dump_date: jiff::civil::date(2026, 1, 1),
+                links_hash: 0,
+                linktargets_hash: None,
+                page_hash: None,
+                exclude_list_pages: false,
+                tracked_pages_hash: 0,
+            },
+            counts: BTreeMap::from_iter([(pn("Daft Punk"), 120)]),
+        };
+        std::fs::write(
+            previous_path.join(CACHE_FILE_NAME),
+            postcard::to_stdvec(&previous).unwrap(),
+        )
+        .unwrap();
+
+        let links_path = dir.join("pagelinks.sql.gz");
+        let mut links = flate2::write::GzEncoder::new(Vec::new(), Default::default());
+        links
+            .write_all(
+                b"CREATE TABLE `pagelinks` (\n  \
+                `pl_from` int(8) unsigned NOT NULL DEFAULT 0,\n  \
+                `pl_namespace` int(11) NOT NULL DEFAULT 0,\n  \
+                `pl_title` varbinary(255) NOT NULL DEFAULT '',\n  \
+                `pl_from_namespace` int(11) NOT NULL DEFAULT 0\n\
+                ) ENGINE=InnoDB;\n\
+                INSERT INTO `pagelinks` VALUES \
+                (1,0,'Fred_Again',0),(2,0,'Fred_Again',0),(3,0,'Daft_Punk',0);\n",
+            )
+            .unwrap();
+        std::fs::write(&links_path, links.finish().unwrap()).unwrap();
+
+        let counts = read(
+            std::time::Instant::now(),
+            jiff::civil::date(2026, 2, 1),
+            None,
+            None,
+            &links_path,
+            &BTreeSet::from_iter([pn("Daft Punk"), pn("Fred Again")]),
+            &types::LinkCountsConfig {
+                exclude_list_pages: false,
+                reuse_previous: true,
+            },
+            &dir,
+            Some(&previous_path),
+        )
+        .unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+
+        // Daft Punk's count is reused, and Fred Again's is read from this dump's links
+        assert_eq!(
+            counts,
+            BTreeMap::from_iter([(pn("Daft Punk"), 120), (pn("Fred Again"), 2)])
+        );
+    }
+}
+
+pub(crate) mod common {
+    use super::*;
+
+    /// The size of the buffers used to read the compressed and decompressed dumps.
+    const READ_BUFFER_SIZE: usize = 1 << 20;
+
+    /// The longest header (everything before the first `INSERT`) that will be read. Real headers
+    /// are a few kilobytes; this stops a malformed dump from being read into memory whole.
+    const MAX_HEADER_LENGTH: usize = 1 << 20;
+
+    /// The size of the batches of the dump that are parsed in parallel by [`parse_tuples_parallel`].
+    const BATCH_SIZE: usize = 16 << 20;
+
+    /// How often [`parse_tuples_parallel`] reports progress, in tuples.
+    const PROGRESS_INTERVAL: u64 = 100_000_000;
+
+    /// The longest value that will be kept in full. Page titles are at most 255 bytes, so
+    /// anything longer is truncated rather than buffered.
+    pub const MAX_VALUE_LENGTH: usize = 1024;
+
+    /// Open a gzipped SQL dump for streaming: it's decompressed and read in fixed-size chunks,
+    /// so memory use doesn't depend on the size of the dump.
+    pub fn open_sql_dump(path: &Path) -> anyhow::Result<impl std::io::BufRead> {
+        let file = std::fs::File::open(path)
+            .with_context(|| format!("Failed to open SQL dump: {}", path.display()))?;
+        Ok(std::io::BufReader::with_capacity(
+            READ_BUFFER_SIZE,
+            flate2::bufread::GzDecoder::new(std::io::BufReader::with_capacity(
+                READ_BUFFER_SIZE,
+                file,
+            )),
+        ))
+    }
+
+    /// Read bytes until `target_prefix` has been consumed, returning everything before it
+    /// (for SQL dumps, the header containing the `CREATE TABLE` statement).
+    pub fn read_until_prefix(
         stream: &mut impl std::io::Read,
         target_prefix: &[u8],
-    ) -> anyhow::Result<()> {
-        // Skip bytes until we find the  prefix
-        let mut buffer = vec![0u8; target_prefix.len()];
-        let mut buffer_pos = 0;
+    ) -> anyhow::Result<String> {
+        let mut header = vec![];
         let mut byte = [0u8; 1];
 
         loop {
@@ -83,58 +347,438 @@ mod common {
                 )
             })? == 0
             {
-                // End of file reached without finding the INSERT statement
-                panic!("End of file reached without finding the INSERT statement");
+                anyhow::bail!(
+                    "End of file reached without finding {:?}",
+                    String::from_utf8_lossy(target_prefix)
+                );
+            }
+            if header.len() >= MAX_HEADER_LENGTH + target_prefix.len() {
+                anyhow::bail!(
+                    "No {:?} within the first {MAX_HEADER_LENGTH} bytes",
+                    String::from_utf8_lossy(target_prefix)
+                );
             }
 
-            // Add byte to circular buffer
-            buffer[buffer_pos] = byte[0];
-            buffer_pos = (buffer_pos + 1) % buffer.len();
+            header.push(byte[0]);
+            if header.ends_with(target_prefix) {
+                header.truncate(header.len() - target_prefix.len());
+                return Ok(String::from_utf8_lossy(&header).into_owned());
+            }
+        }
+    }
 
-            // Check if buffer matches our target prefix
-            let mut matches = true;
-            for (i, &expected_byte) in target_prefix.iter().enumerate() {
-                let buf_idx = (buffer_pos + i) % buffer.len();
-                if buffer[buf_idx] != expected_byte {
-                    matches = false;
-                    break;
-                }
+    /// Get the column names of `table`, in order, from its `CREATE TABLE` statement in `header`.
+    pub fn column_names(header: &str, table: &str) -> Vec<String> {
+        let Some((_, definition)) = header.split_once(&format!("CREATE TABLE `{table}` (")) else {
+            return vec![];
+        };
+        definition
+            .lines()
+            .map(str::trim)
+            .take_while(|line| line.is_empty() || line.starts_with('`'))
+            .filter_map(|line| {
+                let (name, _) = line.strip_prefix('`')?.split_once('`')?;
+                Some(name.to_string())
+            })
+            .collect()
+    }
+
+    /// A value in an SQL `INSERT` tuple.
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum SqlValue {
+        Integer(i64),
+        String(String),
+        Null,
+        /// Any other literal (e.g. a float), unparsed.
+        Other(String),
+    }
+    impl SqlValue {
+        pub fn as_integer(&self) -> Option<i64> {
+            match self {
+                Self::Integer(value) => Some(*value),
+                _ => None,
+            }
+        }
+
+        pub fn as_str(&self) -> Option<&str> {
+            match self {
+                Self::String(value) => Some(value),
+                _ => None,
             }
+        }
+    }
+
+    /// Parse the tuples of the `INSERT` statements in an SQL dump, calling `on_tuple` with the
+    /// values of each. Returns the number of tuples parsed.
+    ///
+    /// The stream is read a buffered chunk at a time and values longer than [`MAX_VALUE_LENGTH`]
+    /// are truncated, so memory use doesn't depend on the size of the dump.
+    pub fn parse_tuples(
+        stream: &mut impl std::io::BufRead,
+        mut on_tuple: impl FnMut(&[SqlValue]),
+    ) -> anyhow::Result<u64> {
+        enum ParseState {
+            SearchingForTupleStart,
+            ValueStart,
+            Integer { value: i64, is_negative: bool },
+            String,
+            StringEscape,
+            StringEnd,
+            Literal,
+        }
+
+        let mut state = ParseState::SearchingForTupleStart;
+        let mut values = vec![];
+        let mut buffer = vec![];
+        let mut tuples_parsed = 0;
 
-            if matches {
-                // Found the  prefix, ready for parsing
+        loop {
+            let chunk = stream
+                .fill_buf()
+                .context("Failed to read chunk from SQL dump")?;
+            if chunk.is_empty() {
                 break;
             }
+            let chunk_len = chunk.len();
+
+            for &byte in chunk {
+                let mut finished_value = None;
+
+                state = match (state, byte) {
+                    (ParseState::SearchingForTupleStart, b'(') => ParseState::ValueStart,
+                    (ParseState::SearchingForTupleStart, _) => ParseState::SearchingForTupleStart,
+
+                    (ParseState::ValueStart, b'\'') => ParseState::String,
+                    (ParseState::ValueStart, b'-') => ParseState::Integer {
+                        value: 0,
+                        is_negative: true,
+                    },
+                    (ParseState::ValueStart, b'0'..=b'9') => ParseState::Integer {
+                        value: (byte - b'0') as i64,
+                        is_negative: false,
+                    },
+                    (ParseState::ValueStart, _) => {
+                        push_bounded(&mut buffer, byte);
+                        ParseState::Literal
+                    }
+
+                    (ParseState::Integer { value, is_negative }, b'0'..=b'9') => {
+                        ParseState::Integer {
+                            value: value * 10 + (byte - b'0') as i64,
+                            is_negative,
+                        }
+                    }
+                    (ParseState::Integer { value, is_negative }, b',' | b')') => {
+                        finished_value =
+                            Some(SqlValue::Integer(if is_negative { -value } else { value }));
+                        ParseState::ValueStart
+                    }
+                    (ParseState::Integer { value, is_negative }, _) => {
+                        // Not an integer after all (e.g. a float); keep it as a literal
+                        if is_negative {
+                            buffer.push(b'-');
+                        }
+                        buffer.extend(value.to_string().bytes());
+                        push_bounded(&mut buffer, byte);
+                        ParseState::Literal
+                    }
+
+                    (ParseState::String, b'\\') => ParseState::StringEscape,
+                    (ParseState::String, b'\'') => ParseState::StringEnd,
+                    (ParseState::String | ParseState::StringEscape, _) => {
+                        // Escaped characters are added as-is
+                        push_bounded(&mut buffer, byte);
+                        ParseState::String
+                    }
+                    (ParseState::StringEnd, b',' | b')') => {
+                        finished_value = Some(SqlValue::String(
+                            String::from_utf8_lossy(&buffer).into_owned(),
+                        ));
+                        buffer.clear();
+                        ParseState::ValueStart
+                    }
+                    (ParseState::StringEnd, _) => {
+                        anyhow::bail!("Unexpected {:?} after SQL string", byte as char)
+                    }
+
+                    (ParseState::Literal, b',' | b')') => {
+                        let literal = String::from_utf8_lossy(&buffer).into_owned();
+                        buffer.clear();
+                        finished_value = Some(if literal == "NULL" {
+                            SqlValue::Null
+                        } else {
+                            SqlValue::Other(literal)
+                        });
+                        ParseState::ValueStart
+                    }
+                    (ParseState::Literal, _) => {
+                        push_bounded(&mut buffer, byte);
+                        ParseState::Literal
+                    }
+                };
+
+                if let Some(value) = finished_value {
+                    values.push(value);
+                    if byte == b')' {
+                        on_tuple(&values);
+                        values.clear();
+                        tuples_parsed += 1;
+                        state = ParseState::SearchingForTupleStart;
+                    }
+                }
+            }
+
+            stream.consume(chunk_len);
         }
 
+        Ok(tuples_parsed)
+    }
+
+    /// Like [`parse_tuples`], but the tuples are parsed across rayon workers. Returns the
+    /// accumulated result and the number of tuples parsed.
+    ///
+    /// The stream is read into batches of whole lines on a separate thread; each `INSERT`
+    /// statement is on its own line, so tuples never span batches. Each worker accumulates into
+    /// its own `T` (starting from `identity()`) with `on_tuple`, and these are combined with
+    /// `merge` at the end. `on_progress` is called with the number of tuples parsed so far every
+    /// [`PROGRESS_INTERVAL`] tuples or so.
+    ///
+    /// Only a few batches are in flight at once, so memory use doesn't depend on the size of the dump.
+    pub fn parse_tuples_parallel<T: Send>(
+        stream: &mut (impl std::io::BufRead + Send),
+        identity: impl Fn() -> T + Send + Sync,
+        on_tuple: impl Fn(&mut T, &[SqlValue]) + Send + Sync,
+        merge: impl Fn(T, T) -> T + Send + Sync,
+        on_progress: impl Fn(u64) + Send + Sync,
+    ) -> anyhow::Result<(T, u64)> {
+        use rayon::iter::{ParallelBridge as _, ParallelIterator as _};
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        let (sender, receiver) = std::sync::mpsc::sync_channel::<anyhow::Result<Vec<u8>>>(
+            rayon::current_num_threads() * 2,
+        );
+        let tuples_parsed = AtomicU64::new(0);
+
+        std::thread::scope(|scope| {
+            scope.spawn(move || {
+                loop {
+                    let mut batch = Vec::with_capacity(BATCH_SIZE);
+                    let batch = match read_batch(stream, &mut batch, BATCH_SIZE) {
+                        Ok(()) if batch.is_empty() => break,
+                        Ok(()) => Ok(batch),
+                        Err(e) => Err(e),
+                    };
+                    let failed = batch.is_err();
+                    // The receiver is dropped if parsing fails, which ends reading
+                    if sender.send(batch).is_err() || failed {
+                        break;
+                    }
+                }
+            });
+
+            receiver
+                .into_iter()
+                .par_bridge()
+                .try_fold(
+                    || (identity(), 0),
+                    |(mut accumulator, count), batch| {
+                        let batch_count = parse_tuples(&mut batch?.as_slice(), |values| {
+                            on_tuple(&mut accumulator, values)
+                        })?;
+                        let before = tuples_parsed.fetch_add(batch_count, Ordering::Relaxed);
+                        let after = before + batch_count;
+                        if before / PROGRESS_INTERVAL != after / PROGRESS_INTERVAL {
+                            on_progress(after);
+                        }
+                        anyhow::Ok((accumulator, count + batch_count))
+                    },
+                )
+                .try_reduce(
+                    || (identity(), 0),
+                    |(a, count_a), (b, count_b)| Ok((merge(a, b), count_a + count_b)),
+                )
+        })
+    }
+
+    /// Read at least `batch_size` bytes (unless the stream ends first) into `batch`, continuing to
+    /// the end of the line so that the batch ends on a statement boundary.
+    fn read_batch(
+        stream: &mut impl std::io::BufRead,
+        batch: &mut Vec<u8>,
+        batch_size: usize,
+    ) -> anyhow::Result<()> {
+        while batch.len() < batch_size {
+            let chunk = stream
+                .fill_buf()
+                .context("Failed to read chunk from SQL dump")?;
+            if chunk.is_empty() {
+                return Ok(());
+            }
+            let length = chunk.len().min(batch_size - batch.len());
+            batch.extend_from_slice(&chunk[..length]);
+            stream.consume(length);
+        }
+        stream
+            .read_until(b'\n', batch)
+            .context("Failed to read to the end of an SQL statement")?;
         Ok(())
     }
 
-    pub fn parse_digit(number: u64, c: char) -> u64 {
-        number * 10 + (c as u64 - '0' as u64)
+    /// Add `byte` to a value being parsed, dropping it if the value is already too long.
+    fn push_bounded(buffer: &mut Vec<u8>, byte: u8) {
+        if buffer.len() < MAX_VALUE_LENGTH {
+            buffer.push(byte);
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use std::io::{Cursor, Read as _};
+
+        #[test]
+        fn test_column_names() {
+            let header = "DROP TABLE IF EXISTS `pagelinks`;\n\
+                CREATE TABLE `pagelinks` (\n  \
+                `pl_from` int(8) unsigned NOT NULL DEFAULT 0,\n  \
+                `pl_from_namespace` int(11) NOT NULL DEFAULT 0,\n  \
+                `pl_target_id` bigint(20) unsigned NOT NULL,\n  \
+                PRIMARY KEY (`pl_from`,`pl_target_id`),\n  \
+                KEY `pl_target_id` (`pl_target_id`,`pl_from`)\n\
+                ) ENGINE=InnoDB;\n";
+            assert_eq!(
+                column_names(header, "pagelinks"),
+                ["pl_from", "pl_from_namespace", "pl_target_id"]
+            );
+            assert!(column_names(header, "linktarget").is_empty());
+        }
+
+        #[test]
+        fn test_read_until_prefix() {
+            let mut stream = Cursor::new(b"header\nINSERT INTO `t` VALUES (1)".as_slice());
+            assert_eq!(
+                read_until_prefix(&mut stream, b"INSERT INTO `t` VALUES ").unwrap(),
+                "header\n"
+            );
+            let mut rest = String::new();
+            stream.read_to_string(&mut rest).unwrap();
+            assert_eq!(rest, "(1)");
+        }
+
+        #[test]
+        fn test_parse_tuples() {
+            let mut tuples = vec![];
+            let data = "(1,-2,'It\\'s_a_title',NULL,0.5),(2,0,'',3,4);\nINSERT INTO `t` VALUES (3,0,'Ünïcödé',1,1);";
+            let count = parse_tuples(&mut Cursor::new(data.as_bytes()), |values| {
+                tuples.push(values.to_vec())
+            })
+            .unwrap();
+
+            assert_eq!(count, 3);
+            assert_eq!(
+                tuples[0],
+                [
+                    SqlValue::Integer(1),
+                    SqlValue::Integer(-2),
+                    SqlValue::String("It's_a_title".to_string()),
+                    SqlValue::Null,
+                    SqlValue::Other("0.5".to_string()),
+                ]
+            );
+            assert_eq!(tuples[1][2], SqlValue::String(String::new()));
+            assert_eq!(tuples[2][2], SqlValue::String("Ünïcödé".to_string()));
+        }
+
+        #[test]
+        fn test_read_batch_ends_on_line_boundaries() {
+            let data = "INSERT INTO `t` VALUES (1),(2);\nINSERT INTO `t` VALUES (3);\n";
+            let mut stream = std::io::BufReader::with_capacity(4, Cursor::new(data.as_bytes()));
+            let mut batches = vec![];
+            loop {
+                let mut batch = vec![];
+                read_batch(&mut stream, &mut batch, 10).unwrap();
+                if batch.is_empty() {
+                    break;
+                }
+                batches.push(String::from_utf8(batch).unwrap());
+            }
+            assert_eq!(
+                batches,
+                [
+                    "INSERT INTO `t` VALUES (1),(2);\n",
+                    "INSERT INTO `t` VALUES (3);\n"
+                ]
+            );
+        }
+
+        #[test]
+        fn test_parse_tuples_parallel() {
+            let data: String = (0..1000)
+                .map(|line| {
+                    let tuples: Vec<String> = (0..100)
+                        .map(|i| format!("({},'a')", line * 100 + i))
+                        .collect();
+                    format!("INSERT INTO `t` VALUES {};\n", tuples.join(","))
+                })
+                .collect();
+            let (sum, count) = parse_tuples_parallel(
+                &mut Cursor::new(data.as_bytes()),
+                || 0,
+                |sum, values| *sum += values[0].as_integer().unwrap(),
+                |a, b| a + b,
+                |_| {},
+            )
+            .unwrap();
+            assert_eq!(count, 100_000);
+            assert_eq!(sum, (0..100_000).sum::<i64>());
+        }
+
+        #[test]
+        fn test_parse_tuples_across_chunks_with_overlong_values() {
+            let long_title = "a".repeat(MAX_VALUE_LENGTH * 2);
+            let data = format!("(1,0,'{long_title}'),(2,0,'Short_title');");
+            // A tiny read buffer forces tuples to be split across chunks
+            let mut stream = std::io::BufReader::with_capacity(3, Cursor::new(data.as_bytes()));
+            let mut tuples = vec![];
+            let count = parse_tuples(&mut stream, |values| tuples.push(values.to_vec())).unwrap();
+
+            assert_eq!(count, 2);
+            assert_eq!(tuples[0][2], SqlValue::String("a".repeat(MAX_VALUE_LENGTH)));
+            assert_eq!(
+                tuples[1],
+                [
+                    SqlValue::Integer(2),
+                    SqlValue::Integer(0),
+                    SqlValue::String("Short_title".to_string()),
+                ]
+            );
+        }
     }
 }
 
 mod linktargets {
     use super::*;
-    use common::parse_digit;
 
+    /// Read the linktargets of the tracked pages, loading them from and saving them to
+    /// `linktargets_tracked.json` in `output_path`, if given.
     pub(crate) fn read(
         start: std::time::Instant,
         wikipedia_linktargets_path: &Path,
         tracked_pages: &BTreeSet<types::PageName>,
-        output_path: &Path,
+        output_path: Option<&Path>,
     ) -> anyhow::Result<BTreeMap<u64, types::PageName>> {
-        let output_file_path = output_path.join("linktargets_tracked.json");
-        if output_file_path.is_file() {
-            return serde_json::from_str(
-                &std::fs::read_to_string(&output_file_path).with_context(|| {
+        let output_file_path = output_path.map(|path| path.join("linktargets_tracked.json"));
+        if let Some(output_file_path) = &output_file_path
+            && output_file_path.is_file()
+        {
+            return serde_json::from_str(&std::fs::read_to_string(output_file_path).with_context(
+                || {
                     format!(
                         "Failed to read existing linktargets file: {}",
                         output_file_path.display()
                     )
-                })?,
-            )
+                },
+            )?)
             .with_context(|| {
                 format!(
                     "Failed to parse JSON from existing linktargets file: {}",
@@ -145,19 +789,9 @@ mod linktargets {
 
         println!("{:.2}s: reading linktargets", start.elapsed().as_secs_f32());
 
-        let linktargets_file =
-            std::fs::File::open(wikipedia_linktargets_path).with_context(|| {
-                format!(
-                    "Failed to open Wikipedia linktargets file: {}",
-                    wikipedia_linktargets_path.display()
-                )
-            })?;
-
-        let mut linktargets_file = std::io::BufReader::new(flate2::bufread::GzDecoder::new(
-            std::io::BufReader::new(linktargets_file),
-        ));
+        let mut linktargets_file = common::open_sql_dump(wikipedia_linktargets_path)?;
 
-        common::skip_until_prefix(&mut linktargets_file, b"INSERT INTO `linktarget` VALUES ")
+        common::read_until_prefix(&mut linktargets_file, b"INSERT INTO `linktarget` VALUES ")
             .context(
                 "Failed to find INSERT INTO `linktarget` VALUES statement in linktargets file",
             )?;
@@ -172,209 +806,54 @@ mod linktargets {
         )
         .context("Failed to parse linktarget tuples from stream")?;
 
-        std::fs::write(
-            &output_file_path,
-            serde_json::to_string_pretty(&linktargets)
-                .context("Failed to serialize linktargets to JSON")?,
-        )
-        .with_context(|| {
-            format!(
-                "Failed to write linktargets to file: {}",
-                output_file_path.display()
+        if let Some(output_file_path) = output_file_path {
+            std::fs::write(
+                &output_file_path,
+                serde_json::to_string_pretty(&linktargets)
+                    .context("Failed to serialize linktargets to JSON")?,
             )
-        })?;
+            .with_context(|| {
+                format!(
+                    "Failed to write linktargets to file: {}",
+                    output_file_path.display()
+                )
+            })?;
+        }
 
         Ok(linktargets)
     }
 
+    /// Parse `(lt_id, lt_namespace, lt_title)` tuples, keeping those for tracked pages.
     fn parse_linktarget_tuple_stream(
         stream: &mut impl std::io::BufRead,
         start: std::time::Instant,
         tracked_pages: &BTreeSet<types::PageName>,
         output: &mut BTreeMap<u64, types::PageName>,
     ) -> anyhow::Result<()> {
-        enum ParseState {
-            SearchingForTupleStart,
-            LtId {
-                lt_id: u64,
-            },
-            LtNamespace {
-                lt_id: u64,
-                lt_namespace: i64,
-                is_negative: bool,
-            },
-            LtTitleStart {
-                lt_id: u64,
-                lt_namespace: i64,
-            },
-            LtTitle {
-                lt_id: u64,
-                lt_namespace: i64,
-                lt_title: String,
-            },
-            LtTitleEscape {
-                lt_id: u64,
-                lt_namespace: i64,
-                lt_title: String,
-            },
-            WaitingForTupleEnd {
-                lt_id: u64,
-                lt_namespace: i64,
-                lt_title: String,
-            },
-        }
-
-        let mut state = ParseState::SearchingForTupleStart;
-        let mut tuples_parsed = 0;
-
-        // Read the rest of the file byte by byte
-        for byte in stream.bytes() {
-            let byte = byte.context("Failed to read byte from linktargets file")?;
-            let c = byte as char;
-
-            state = match state {
-                ParseState::SearchingForTupleStart => {
-                    if c == '(' {
-                        ParseState::LtId { lt_id: 0 }
-                    } else {
-                        ParseState::SearchingForTupleStart
-                    }
-                }
-                ParseState::LtId { lt_id } => {
-                    if c.is_ascii_digit() {
-                        ParseState::LtId {
-                            lt_id: parse_digit(lt_id, c),
-                        }
-                    } else if c == ',' {
-                        ParseState::LtNamespace {
-                            lt_id,
-                            lt_namespace: 0,
-                            is_negative: false,
-                        }
-                    } else {
-                        unreachable!()
-                    }
-                }
-                ParseState::LtNamespace {
-                    lt_id,
-                    lt_namespace,
-                    is_negative,
-                } => {
-                    if c.is_ascii_digit() {
-                        ParseState::LtNamespace {
-                            lt_id,
-                            lt_namespace: parse_digit(lt_namespace as u64, c) as i64,
-                            is_negative,
-                        }
-                    } else if c == '-' && lt_namespace == 0 {
-                        ParseState::LtNamespace {
-                            lt_id,
-                            lt_namespace: 0,
-                            is_negative: true,
-                        }
-                    } else if c == ',' {
-                        let final_namespace = if is_negative {
-                            -lt_namespace
-                        } else {
-                            lt_namespace
-                        };
-                        ParseState::LtTitleStart {
-                            lt_id,
-                            lt_namespace: final_namespace,
-                        }
-                    } else {
-                        unreachable!()
-                    }
-                }
-                ParseState::LtTitleStart {
-                    lt_id,
-                    lt_namespace,
-                } => {
-                    if c == '\'' {
-                        ParseState::LtTitle {
-                            lt_id,
-                            lt_namespace,
-                            lt_title: String::new(),
-                        }
-                    } else {
-                        unreachable!()
-                    }
-                }
-                ParseState::LtTitle {
-                    lt_id,
-                    lt_namespace,
-                    mut lt_title,
-                } => {
-                    if c == '\'' {
-                        ParseState::WaitingForTupleEnd {
-                            lt_id,
-                            lt_namespace,
-                            lt_title,
-                        }
-                    } else if c == '\\' {
-                        ParseState::LtTitleEscape {
-                            lt_id,
-                            lt_namespace,
-                            lt_title,
-                        }
-                    } else {
-                        // Convert underscores to spaces during parsing
-                        let char_to_add = if c == '_' { ' ' } else { c };
-                        lt_title.push(char_to_add);
-                        ParseState::LtTitle {
-                            lt_id,
-                            lt_namespace,
-                            lt_title,
-                        }
-                    }
-                }
-                ParseState::LtTitleEscape {
-                    lt_id,
-                    lt_namespace,
-                    mut lt_title,
-                } => {
-                    // Add the escaped character as-is (don't convert underscores in escaped chars)
-                    lt_title.push(c);
-                    ParseState::LtTitle {
-                        lt_id,
-                        lt_namespace,
-                        lt_title,
-                    }
+        let mut tuples_parsed = 0u64;
+        let tuples_parsed = common::parse_tuples(stream, |values| {
+            // Only process tuples with namespace 0
+            if let [lt_id, lt_namespace, lt_title, ..] = values
+                && let (Some(lt_id), Some(0), Some(lt_title)) = (
+                    lt_id.as_integer(),
+                    lt_namespace.as_integer(),
+                    lt_title.as_str(),
+                )
+            {
+                let page_name = types::PageName::new(lt_title.replace('_', " "), None);
+                if tracked_pages.contains(&page_name) {
+                    output.insert(lt_id as u64, page_name);
                 }
-                ParseState::WaitingForTupleEnd {
-                    lt_id,
-                    lt_namespace,
-                    lt_title,
-                } => {
-                    if c == ')' {
-                        // Only process tuples with namespace 0
-                        if lt_namespace == 0 {
-                            let page_name = types::PageName::new(&lt_title, None);
-                            if tracked_pages.contains(&page_name) {
-                                output.insert(lt_id, page_name);
-                            }
-                        }
-
-                        tuples_parsed += 1;
-                        if tuples_parsed % 10_000_000 == 0 {
-                            println!(
-                                "{:.2}s: parsed {tuples_parsed} linktarget tuples",
-                                start.elapsed().as_secs_f32(),
-                            );
-                        }
+            }
 
-                        ParseState::SearchingForTupleStart
-                    } else {
-                        // Continue waiting for tuple end
-                        ParseState::WaitingForTupleEnd {
-                            lt_id,
-                            lt_namespace,
-                            lt_title,
-                        }
-                    }
-                }
+            tuples_parsed += 1;
+            if tuples_parsed.is_multiple_of(10_000_000) {
+                println!(
+                    "{:.2}s: parsed {tuples_parsed} linktarget tuples",
+                    start.elapsed().as_secs_f32(),
+                );
             }
-        }
+        })?;
 
         println!(
             "{:.2}s: parsed {tuples_parsed} linktarget tuples",
@@ -501,30 +980,272 @@ mod linktargets {
     }
 }
 
+mod pages {
+    use super::*;
+
+    /// A set of page IDs. Page IDs are dense, so this is a bitset indexed by ID.
+    #[derive(Debug, Default)]
+    pub(crate) struct PageIdSet(Vec<u64>);
+    impl PageIdSet {
+        pub(crate) fn insert(&mut self, id: u64) {
+            let word = (id / 64) as usize;
+            if word >= self.0.len() {
+                self.0.resize(word + 1, 0);
+            }
+            self.0[word] |= 1 << (id % 64);
+        }
+
+        pub(crate) fn contains(&self, id: u64) -> bool {
+            self.0
+                .get((id / 64) as usize)
+                .is_some_and(|word| word & (1 << (id % 64)) != 0)
+        }
+
+        pub(crate) fn len(&self) -> usize {
+            self.0.iter().map(|word| word.count_ones() as usize).sum()
+        }
+    }
+
+    /// The columns of the `page` table that are used.
+    #[derive(Debug, Clone, Copy)]
+    struct PageColumns {
+        id: usize,
+        namespace: usize,
+        title: usize,
+    }
+
+    /// Read the IDs of the articles (namespace 0) in the `page` dump, optionally excluding list
+    /// and disambiguation pages.
+    pub(crate) fn read(
+        start: std::time::Instant,
+        wikipedia_page_path: &Path,
+        exclude_list_pages: bool,
+    ) -> anyhow::Result<PageIdSet> {
+        println!("{:.2}s: reading pages", start.elapsed().as_secs_f32());
+
+        let mut page_file = common::open_sql_dump(wikipedia_page_path)?;
+
+        let header = common::read_until_prefix(&mut page_file, b"INSERT INTO `page` VALUES ")
+            .context("Failed to find INSERT INTO `page` VALUES statement in page file")?;
+        let column_names = common::column_names(&header, "page");
+        let index = |name: &str, default: usize| {
+            column_names
+                .iter()
+                .position(|c| c == name)
+                .unwrap_or(default)
+        };
+        let columns = PageColumns {
+            id: index("page_id", 0),
+            namespace: index("page_namespace", 1),
+            title: index("page_title", 2),
+        };
+
+        let mut articles = PageIdSet::default();
+        parse_page_tuple_stream(
+            &mut page_file,
+            start,
+            columns,
+            exclude_list_pages,
+            &mut articles,
+        )
+        .context("Failed to parse page tuples from stream")?;
+
+        println!(
+            "{:.2}s: found {} articles to count links from",
+            start.elapsed().as_secs_f32(),
+            articles.len()
+        );
+
+        Ok(articles)
+    }
+
+    fn parse_page_tuple_stream(
+        stream: &mut impl std::io::BufRead,
+        start: std::time::Instant,
+        columns: PageColumns,
+        exclude_list_pages: bool,
+        output: &mut PageIdSet,
+    ) -> anyhow::Result<()> {
+        let mut tuples_parsed = 0u64;
+        let tuples_parsed = common::parse_tuples(stream, |values| {
+            if let (Some(id), Some(0), Some(title)) = (
+                values.get(columns.id).and_then(|v| v.as_integer()),
+                values.get(columns.namespace).and_then(|v| v.as_integer()),
+                values.get(columns.title).and_then(|v| v.as_str()),
+            ) && !(exclude_list_pages && is_list_or_disambiguation(title))
+            {
+                output.insert(id as u64);
+            }
+
+            tuples_parsed += 1;
+            if tuples_parsed.is_multiple_of(10_000_000) {
+                println!(
+                    "{:.2}s: parsed {tuples_parsed} page tuples",
+                    start.elapsed().as_secs_f32(),
+                );
+            }
+        })?;
+
+        println!(
+            "{:.2}s: parsed {tuples_parsed} page tuples",
+            start.elapsed().as_secs_f32(),
+        );
+
+        Ok(())
+    }
+
+    /// Whether an article is a list or disambiguation page, judging by its title.
+    fn is_list_or_disambiguation(title: &str) -> bool {
+        title.starts_with("List_of_")
+            || title.starts_with("Lists_of_")
+            || title.ends_with("_(disambiguation)")
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use std::io::Cursor;
+
+        #[test]
+        fn test_parse_page_tuples() {
+            let data = "(1,0,'Daft_Punk',0),(2,1,'Daft_Punk',0),(3,0,'List_of_house_music_artists',0),\
+                (4,0,'Homework_(disambiguation)',0),(200,0,'Techno',0);";
+            let columns = PageColumns {
+                id: 0,
+                namespace: 1,
+                title: 2,
+            };
+            let parse = |exclude_list_pages| {
+                let mut output = PageIdSet::default();
+                parse_page_tuple_stream(
+                    &mut Cursor::new(data.as_bytes()),
+                    std::time::Instant::now(),
+                    columns,
+                    exclude_list_pages,
+                    &mut output,
+                )
+                .unwrap();
+                output
+            };
+
+            let articles = parse(false);
+            assert_eq!(articles.len(), 4);
+            // Talk pages aren't articles
+            assert!(!articles.contains(2));
+            assert!(articles.contains(200));
+            assert!(!articles.contains(1000));
+
+            let articles = parse(true);
+            assert_eq!(articles.len(), 2);
+            assert!(articles.contains(1));
+            assert!(!articles.contains(3));
+            assert!(!articles.contains(4));
+        }
+    }
+}
+
 mod links {
     use super::*;
-    use common::parse_digit;
+
+    /// Which links are counted, by the page they're from.
+    #[derive(Debug, Default)]
+    pub(crate) struct SourceFilter<'a> {
+        /// The column of the linking page's ID.
+        pub(crate) from: Option<usize>,
+        /// The column of the linking page's namespace.
+        pub(crate) from_namespace: Option<usize>,
+        /// The articles to count links from, if there's a page dump; otherwise, links from any
+        /// page in namespace 0 are counted.
+        pub(crate) articles: Option<&'a pages::PageIdSet>,
+    }
+    impl SourceFilter<'_> {
+        fn counts(&self, values: &[common::SqlValue]) -> bool {
+            let column = |index: Option<usize>| {
+                index
+                    .and_then(|index| values.get(index))
+                    .and_then(|v| v.as_integer())
+            };
+            match self.articles {
+                Some(articles) => column(self.from).is_some_and(|id| articles.contains(id as u64)),
+                None => column(self.from_namespace).is_none_or(|namespace| namespace == 0),
+            }
+        }
+    }
+
+    /// The layout of the `pagelinks` table, which differs between dump generations.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub(crate) enum PagelinksSchema {
+        /// Links reference a `linktarget` row by ID (dumps from 2024 onwards).
+        LinkTarget { target_id: usize },
+        /// Links store the target's namespace and title directly (older dumps).
+        Title { namespace: usize, title: usize },
+    }
+    impl PagelinksSchema {
+        /// Detect the schema from the table's columns. Some dumps from the migration period
+        /// have both sets of columns; the titles are used if there's no linktarget dump to join with.
+        pub(crate) fn detect(columns: &[String], has_linktargets: bool) -> anyhow::Result<Self> {
+            let index = |name: &str| columns.iter().position(|c| c == name);
+            match (
+                index("pl_target_id"),
+                index("pl_namespace"),
+                index("pl_title"),
+            ) {
+                (Some(target_id), _, _) if has_linktargets => Ok(Self::LinkTarget { target_id }),
+                (_, Some(namespace), Some(title)) => Ok(Self::Title { namespace, title }),
+                (Some(_), _, _) => anyhow::bail!(
+                    "the pagelinks dump references linktarget IDs, but there is no linktarget dump"
+                ),
+                _ => anyhow::bail!("unrecognised pagelinks schema with columns {columns:?}"),
+            }
+        }
+    }
 
     pub(crate) fn read(
         start: std::time::Instant,
         wikipedia_links_path: &Path,
-        linktargets: &BTreeMap<u64, types::PageName>,
+        wikipedia_linktargets_path: Option<&Path>,
+        articles: Option<&pages::PageIdSet>,
         tracked_pages: &BTreeSet<types::PageName>,
-        output_file_path: &Path,
+        linktargets_cache_path: Option<&Path>,
     ) -> anyhow::Result<BTreeMap<types::PageName, usize>> {
+        let mut links_file = common::open_sql_dump(wikipedia_links_path)?;
+
+        let header = common::read_until_prefix(&mut links_file, b"INSERT INTO `pagelinks` VALUES ")
+            .context("Failed to find INSERT INTO `pagelinks` VALUES statement in links file")?;
+        let columns = common::column_names(&header, "pagelinks");
+        let schema = PagelinksSchema::detect(&columns, wikipedia_linktargets_path.is_some())?;
+        let source_filter = SourceFilter {
+            from: columns.iter().position(|c| c == "pl_from"),
+            from_namespace: columns.iter().position(|c| c == "pl_from_namespace"),
+            articles,
+        };
         println!(
-            "{:.2}s: generating page inbound link counts",
+            "{:.2}s: detected pagelinks schema: {schema:?}",
             start.elapsed().as_secs_f32()
         );
 
-        let links_file = std::fs::File::open(wikipedia_links_path)
-            .context("Failed to open Wikipedia links file")?;
-        let mut links_file = std::io::BufReader::new(flate2::bufread::GzDecoder::new(
-            std::io::BufReader::new(links_file),
-        ));
+        let linktargets = match (schema, wikipedia_linktargets_path) {
+            (PagelinksSchema::LinkTarget { .. }, Some(wikipedia_linktargets_path)) => {
+                linktargets::read(
+                    start,
+                    wikipedia_linktargets_path,
+                    tracked_pages,
+                    linktargets_cache_path,
+                )
+                .with_context(|| {
+                    format!(
+                        "Failed to read linktargets from: {}",
+                        wikipedia_linktargets_path.display()
+                    )
+                })?
+            }
+            _ => BTreeMap::new(),
+        };
 
-        common::skip_until_prefix(&mut links_file, b"INSERT INTO `pagelinks` VALUES ")
-            .context("Failed to find INSERT INTO `pagelinks` VALUES statement in links file")?;
+        println!(
+            "{:.2}s: generating page inbound link counts",
+            start.elapsed().as_secs_f32()
+        );
 
         let mut inbound_link_counts: BTreeMap<types::PageName, usize> =
             tracked_pages.iter().map(|id| (id.clone(), 0)).collect();
@@ -532,127 +1253,74 @@ mod links {
         parse_tuple_byte_stream(
             &mut links_file,
             start,
-            linktargets,
+            &schema,
+            &source_filter,
+            &linktargets,
             &mut inbound_link_counts,
         )
         .context("Failed to parse pagelinks tuples from stream")?;
 
-        std::fs::write(
-            output_file_path,
-            serde_json::to_string_pretty(&inbound_link_counts)
-                .context("Failed to serialize inbound link counts to JSON")?,
-        )
-        .with_context(|| {
-            format!(
-                "Failed to write inbound link counts to file: {}",
-                output_file_path.display()
-            )
-        })?;
-
         Ok(inbound_link_counts)
     }
 
     fn parse_tuple_byte_stream(
-        stream: &mut impl std::io::BufRead,
+        stream: &mut (impl std::io::BufRead + Send),
         start: std::time::Instant,
+        schema: &PagelinksSchema,
+        source_filter: &SourceFilter,
         linktargets: &BTreeMap<u64, types::PageName>,
         output: &mut BTreeMap<types::PageName, usize>,
     ) -> anyhow::Result<()> {
-        enum ParseState {
-            SearchingForTupleStart,
-            SourceId {
-                source_id: u64,
+        let tracked_pages = &*output;
+        let (counts, tuples_parsed) = common::parse_tuples_parallel(
+            stream,
+            BTreeMap::<&types::PageName, usize>::new,
+            |counts, values| {
+                if !source_filter.counts(values) {
+                    return;
+                }
+                let page = match *schema {
+                    PagelinksSchema::LinkTarget { target_id } => values
+                        .get(target_id)
+                        .and_then(|v| v.as_integer())
+                        .and_then(|id| linktargets.get(&(id as u64))),
+                    PagelinksSchema::Title { namespace, title } => values
+                        .get(namespace)
+                        .and_then(|v| v.as_integer())
+                        .filter(|namespace| *namespace == 0)
+                        .and(values.get(title))
+                        .and_then(|v| v.as_str())
+                        .and_then(|title| {
+                            tracked_pages
+                                .get_key_value(&types::PageName::new(title.replace('_', " "), None))
+                                .map(|(page, _)| page)
+                        }),
+                };
+                if let Some(page) = page {
+                    *counts.entry(page).or_default() += 1;
+                }
             },
-            SourceNamespace {
-                source_id: u64,
-                source_namespace: u64,
+            |mut a, b| {
+                for (page, count) in b {
+                    *a.entry(page).or_default() += count;
+                }
+                a
             },
-            DestinationId {
-                source_id: u64,
-                source_namespace: u64,
-                destination_id: u64,
+            |tuples_parsed| {
+                println!(
+                    "{:.2}s: parsed {tuples_parsed} pagelink tuples",
+                    start.elapsed().as_secs_f32(),
+                );
             },
-        }
-
-        let mut state = ParseState::SearchingForTupleStart;
-        let mut tuples_parsed = 0;
+        )?;
 
-        // Read the rest of the file byte by byte
-        for byte in stream.bytes() {
-            let byte = byte.context("Failed to read byte from links file")?;
-            let c = byte as char;
-
-            state = match state {
-                ParseState::SearchingForTupleStart => {
-                    if c == '(' {
-                        ParseState::SourceId { source_id: 0 }
-                    } else {
-                        ParseState::SearchingForTupleStart
-                    }
-                }
-                ParseState::SourceId { source_id } => {
-                    if c.is_ascii_digit() {
-                        ParseState::SourceId {
-                            source_id: parse_digit(source_id, c),
-                        }
-                    } else if c == ',' {
-                        ParseState::SourceNamespace {
-                            source_id,
-                            source_namespace: 0,
-                        }
-                    } else {
-                        unreachable!()
-                    }
-                }
-                ParseState::SourceNamespace {
-                    source_id,
-                    source_namespace,
-                } => {
-                    if c.is_ascii_digit() {
-                        ParseState::SourceNamespace {
-                            source_id,
-                            source_namespace: parse_digit(source_namespace, c),
-                        }
-                    } else if c == ',' {
-                        ParseState::DestinationId {
-                            source_id,
-                            source_namespace,
-                            destination_id: 0,
-                        }
-                    } else {
-                        unreachable!()
-                    }
-                }
-                ParseState::DestinationId {
-                    source_id,
-                    source_namespace,
-                    destination_id,
-                } => {
-                    if c.is_ascii_digit() {
-                        ParseState::DestinationId {
-                            source_id,
-                            source_namespace,
-                            destination_id: parse_digit(destination_id, c),
-                        }
-                    } else if c == ')' {
-                        if let Some(count) = linktargets
-                            .get(&destination_id)
-                            .and_then(|pn| output.get_mut(pn))
-                        {
-                            *count += 1;
-                        }
-                        tuples_parsed += 1;
-                        if tuples_parsed % 100_000_000 == 0 {
-                            println!(
-                                "{:.2}s: parsed {tuples_parsed} pagelink tuples",
-                                start.elapsed().as_secs_f32(),
-                            );
-                        }
-                        ParseState::SearchingForTupleStart
-                    } else {
-                        unreachable!()
-                    }
-                }
+        let counts: Vec<(types::PageName, usize)> = counts
+            .into_iter()
+            .map(|(page, count)| (page.clone(), count))
+            .collect();
+        for (page, count) in counts {
+            if let Some(total) = output.get_mut(&page) {
+                *total += count;
             }
         }
 
@@ -689,6 +1357,8 @@ mod links {
             parse_tuple_byte_stream(
                 &mut stream,
                 std::time::Instant::now(),
+                &PagelinksSchema::LinkTarget { target_id: 2 },
+                &SourceFilter::default(),
                 &LINK_TARGETS,
                 &mut output,
             )
@@ -708,6 +1378,8 @@ mod links {
             parse_tuple_byte_stream(
                 &mut stream,
                 std::time::Instant::now(),
+                &PagelinksSchema::LinkTarget { target_id: 2 },
+                &SourceFilter::default(),
                 &LINK_TARGETS,
                 &mut output,
             )
@@ -725,6 +1397,8 @@ mod links {
             parse_tuple_byte_stream(
                 &mut stream,
                 std::time::Instant::now(),
+                &PagelinksSchema::LinkTarget { target_id: 2 },
+                &SourceFilter::default(),
                 &LINK_TARGETS,
                 &mut output,
             )
@@ -733,5 +1407,100 @@ mod links {
             assert_eq!(output.get(&pn("Page 456")), None);
             assert_eq!(output.get(&pn("Page 789")), Some(&1));
         }
+
+        #[test]
+        fn test_parse_legacy_title_tuples() {
+            let mut output = BTreeMap::from_iter([(pn("Page 123"), 0), (pn("Pixadão"), 0)]);
+            let data =
+                "(1,0,'Page_123',0),(2,1,'Page_123',0),(3,0,'Pixadão',0),(4,0,'Page_123',0);";
+            let mut stream = Cursor::new(data.as_bytes());
+            parse_tuple_byte_stream(
+                &mut stream,
+                std::time::Instant::now(),
+                &PagelinksSchema::Title {
+                    namespace: 1,
+                    title: 2,
+                },
+                &SourceFilter::default(),
+                &BTreeMap::new(),
+                &mut output,
+            )
+            .unwrap();
+            // The link from namespace 1 doesn't count
+            assert_eq!(output.get(&pn("Page 123")), Some(&2));
+            assert_eq!(output.get(&pn("Pixadão")), Some(&1));
+        }
+
+        #[test]
+        fn test_parse_tuples_only_counts_links_from_articles() {
+            let data = b"(1,0,123),(2,1,123),(3,2,123),(4,0,123);";
+            let parse = |source_filter: &SourceFilter| {
+                let mut output = BTreeMap::from_iter([(pn("Page 123"), 0)]);
+                parse_tuple_byte_stream(
+                    &mut Cursor::new(data),
+                    std::time::Instant::now(),
+                    &PagelinksSchema::LinkTarget { target_id: 2 },
+                    source_filter,
+                    &LINK_TARGETS,
+                    &mut output,
+                )
+                .unwrap();
+                output[&pn("Page 123")]
+            };
+
+            // Without a page dump, the linking page's namespace is used
+            let by_namespace = SourceFilter {
+                from: Some(0),
+                from_namespace: Some(1),
+                articles: None,
+            };
+            assert_eq!(parse(&by_namespace), 2);
+
+            let mut articles = pages::PageIdSet::default();
+            articles.insert(1);
+            let by_page_dump = SourceFilter {
+                articles: Some(&articles),
+                ..by_namespace
+            };
+            assert_eq!(parse(&by_page_dump), 1);
+        }
+
+        #[test]
+        fn test_detect_schema() {
+            let columns = |names: &[&str]| names.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+            let modern = columns(&["pl_from", "pl_from_namespace", "pl_target_id"]);
+            let legacy = columns(&["pl_from", "pl_namespace", "pl_title", "pl_from_namespace"]);
+            let transitional = columns(&[
+                "pl_from",
+                "pl_namespace",
+                "pl_title",
+                "pl_from_namespace",
+                "pl_target_id",
+            ]);
+
+            assert_eq!(
+                PagelinksSchema::detect(&modern, true).unwrap(),
+                PagelinksSchema::LinkTarget { target_id: 2 }
+            );
+            assert!(PagelinksSchema::detect(&modern, false).is_err());
+            assert_eq!(
+                PagelinksSchema::detect(&legacy, false).unwrap(),
+                PagelinksSchema::Title {
+                    namespace: 1,
+                    title: 2
+                }
+            );
+            assert_eq!(
+                PagelinksSchema::detect(&transitional, true).unwrap(),
+                PagelinksSchema::LinkTarget { target_id: 4 }
+            );
+            assert_eq!(
+                PagelinksSchema::detect(&transitional, false).unwrap(),
+                PagelinksSchema::Title {
+                    namespace: 1,
+                    title: 2
+                }
+            );
+        }
     }
 }
@@ -5,16 +5,324 @@ use std::{
 };
 
 use anyhow::Context as _;
+use serde::{Deserialize, Serialize};
 
 use crate::{extract, types::PageName};
 
+/// The path to the name normalization rules, relative to the repo root.
+pub const NAME_NORMALIZATION_PATH: &str = "datagen/name_normalization.toml";
+
+/// Rules for normalizing names so that trivially different spellings of a page
+/// ("Techno music" and "Techno", "Hip hop" and "Hip-hop") match each other,
+/// even when Wikipedia has no redirect between them.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct NameNormalization {
+    /// Strings treated as equivalent to a space (e.g. `-`).
+    #[serde(default)]
+    pub space_equivalents: Vec<String>,
+    /// Suffixes stripped from the end of a name (e.g. ` music`), compared case-insensitively.
+    #[serde(default)]
+    pub strip_suffixes: Vec<String>,
+}
+impl NameNormalization {
+    /// Load the normalization rules from a TOML file.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let rules = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read name normalization rules from {path:?}"))?;
+        toml::from_str(&rules)
+            .with_context(|| format!("Failed to parse name normalization rules from {path:?}"))
+    }
+
+    /// Normalize a name: case-fold it (titles are matched case-insensitively throughout),
+    /// unify space-like separators, collapse whitespace, then strip any configured suffixes.
+    pub fn normalize(&self, name: &str) -> String {
+        let mut normalized = name.to_lowercase();
+        for separator in &self.space_equivalents {
+            normalized = normalized.replace(separator.as_str(), " ");
+        }
+        let mut normalized = normalized.split_whitespace().collect::<Vec<_>>().join(" ");
+        while let Some(stripped) = self.strip_suffixes.iter().find_map(|suffix| {
+            normalized
+                .strip_suffix(suffix.to_lowercase().as_str())
+                .map(str::trim_end)
+                .filter(|s| !s.is_empty())
+        }) {
+            normalized = stripped.to_string();
+        }
+        normalized
+    }
+}
+
 /// A map of links to page names.
-pub struct LinksToArticles(pub BTreeMap<String, PageName>);
+///
+/// There are millions of links, most of them redirects to a few thousand pages, so each page is
+/// stored once and the link keys are packed into a single sorted string table.
+pub struct LinksToArticles {
+    /// Every page a link resolves to, indexed by the links' [`PageIndex`]es.
+    pages: Vec<PageName>,
+    /// Lower-cased page names and redirects ([`link_key`]s), mapped to the pages they resolve to.
+    links: KeyTable,
+    /// The [`NameNormalization::normalize`]d forms of `links`, used when a link has no
+    /// exact match. Forms that would resolve to more than one page are left out.
+    normalized_links: KeyTable,
+    normalization: NameNormalization,
+}
 impl LinksToArticles {
+    /// Build the map, deriving the normalized links from `links`.
+    pub fn new(links: BTreeMap<String, PageName>, normalization: NameNormalization) -> Self {
+        let mut pages = vec![];
+        let mut page_indices: BTreeMap<PageName, PageIndex> = BTreeMap::new();
+        let mut normalized_candidates: BTreeMap<String, Option<PageIndex>> = BTreeMap::new();
+
+        let links = KeyTable::from_sorted(links.into_iter().map(|(link, page)| {
+            let index = *page_indices.entry(page).or_insert_with_key(|page| {
+                pages.push(page.clone());
+                PageIndex(pages.len() as u32 - 1)
+            });
+            normalized_candidates
+                .entry(normalization.normalize(&link))
+                // A form shared by more than one page is ambiguous
+                .and_modify(|candidate| {
+                    if *candidate != Some(index) {
+                        *candidate = None;
+                    }
+                })
+                .or_insert(Some(index));
+            (link, index)
+        }));
+        let normalized_links = KeyTable::from_sorted(
+            normalized_candidates
+                .into_iter()
+                .filter_map(|(link, index)| Some((link, index?))),
+        );
+
+        Self {
+            pages,
+            links,
+            normalized_links,
+            normalization,
+        }
+    }
+
     /// Get the page name for a link.
-    pub fn map(&self, link: &str) -> Option<PageName> {
-        self.0.get(&link.to_lowercase()).map(|s| s.to_owned())
+    ///
+    /// Links to a heading resolve to the genre under that heading if there is one, and to the
+    /// page the heading is on otherwise. Links to other wikis never resolve.
+    pub fn map(&self, link: &str) -> Option<&PageName> {
+        if interwiki_prefix(link).is_some() {
+            return None;
+        }
+        let key = link_key(link);
+        let index = self
+            .links
+            .get(&key)
+            .or_else(|| {
+                self.normalized_links
+                    .get(&self.normalization.normalize(&key))
+            })
+            .or_else(|| {
+                let (page, _heading) = key.split_once('#')?;
+                self.links.get(page)
+            })?;
+        Some(&self.pages[index.0 as usize])
+    }
+
+    /// Make the links that resolve to `page` resolve to `into` instead, for a genre merged into
+    /// another.
+    pub fn merge(&mut self, page: &PageName, into: &PageName) {
+        for resolved in &mut self.pages {
+            if resolved == page {
+                *resolved = into.clone();
+            }
+        }
+    }
+
+    /// All links and the pages they resolve to, in order of link.
+    pub fn links(&self) -> impl Iterator<Item = (&str, &PageName)> {
+        self.links
+            .iter()
+            .map(|(link, index)| (link, &self.pages[index.0 as usize]))
+    }
+}
+
+/// An index into [`LinksToArticles`]'s table of pages.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct PageIndex(u32);
+
+/// Sorted string keys stored back to back in one allocation, each mapped to a [`PageIndex`].
+struct KeyTable {
+    text: String,
+    /// The end of each key in `text` (its start is the end of the previous key), and its value.
+    entries: Vec<(u32, PageIndex)>,
+}
+impl KeyTable {
+    /// Build the table from keys that are already in sorted order.
+    fn from_sorted(keys: impl Iterator<Item = (String, PageIndex)>) -> Self {
+        let mut text = String::new();
+        let mut entries = vec![];
+        for (key, index) in keys {
+            text.push_str(&key);
+            entries.push((text.len() as u32, index));
+        }
+        text.shrink_to_fit();
+        entries.shrink_to_fit();
+        Self { text, entries }
+    }
+
+    fn key(&self, entry: usize) -> &str {
+        let start = entry
+            .checked_sub(1)
+            .map_or(0, |previous| self.entries[previous].0 as usize);
+        &self.text[start..self.entries[entry].0 as usize]
+    }
+
+    fn get(&self, key: &str) -> Option<PageIndex> {
+        let mut range = 0..self.entries.len();
+        // A binary search by hand, as `entries` doesn't hold the keys themselves
+        while !range.is_empty() {
+            let middle = range.start + range.len() / 2;
+            match self.key(middle).cmp(key) {
+                std::cmp::Ordering::Less => range.start = middle + 1,
+                std::cmp::Ordering::Greater => range.end = middle,
+                std::cmp::Ordering::Equal => return Some(self.entries[middle].1),
+            }
+        }
+        None
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (&str, PageIndex)> {
+        (0..self.entries.len()).map(|entry| (self.key(entry), self.entries[entry].1))
+    }
+}
+
+/// Prefixes that link to other Wikimedia projects rather than to English Wikipedia articles.
+const INTERWIKI_PREFIXES: &[&str] = &[
+    "b",
+    "c",
+    "commons",
+    "d",
+    "m",
+    "meta",
+    "mw",
+    "n",
+    "q",
+    "s",
+    "simple",
+    "species",
+    "v",
+    "voy",
+    "wikibooks",
+    "wikidata",
+    "wikinews",
+    "wikiquote",
+    "wikisource",
+    "wikispecies",
+    "wikiversity",
+    "wikivoyage",
+    "wikt",
+    "wiktionary",
+];
+
+/// If `link` points to another wiki (`[[wikt:riddim]]`) or another language's Wikipedia
+/// (`[[:fr:Zouk]]`), get its prefix.
+///
+/// Language prefixes are recognised by their shape (`fr`, `pt-br`, `zh-yue`), so they must
+/// be written in lower case, as they conventionally are; this avoids mistaking titles like
+/// "MC: The Album" for interlanguage links.
+pub fn interwiki_prefix(link: &str) -> Option<&str> {
+    let (prefix, rest) = link.trim().trim_start_matches(':').split_once(':')?;
+    let prefix = prefix.trim();
+    if rest.trim().is_empty() {
+        return None;
+    }
+    let is_interwiki = INTERWIKI_PREFIXES.contains(&prefix.to_lowercase().as_str());
+    let is_language = {
+        let mut parts = prefix.split('-');
+        parts
+            .next()
+            .is_some_and(|code| (2..=3).contains(&code.len()))
+            && prefix
+                .split('-')
+                .all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_lowercase()))
+    };
+    (is_interwiki || is_language).then_some(prefix)
+}
+
+/// The key for a link in [`LinksToArticles::links`], following MediaWiki's title rules: only the
+/// first letter is case-insensitive (and lower-cased here), and underscores are spaces.
+///
+/// The heading (if any) is written as it appears on the page rather than as a URL fragment
+/// (`Scouse_house`, `%C3%A9`), and lower-cased entirely, as anchors in links and redirects are
+/// often cased differently from the heading they point to.
+pub fn link_key(link: &str) -> String {
+    let link = link.trim();
+    let (page, heading) = match link.split_once('#') {
+        Some((page, heading)) => (page, Some(heading)),
+        None => (link, None),
+    };
+
+    let page = page.trim().replace('_', " ");
+    let mut chars = page.chars();
+    let mut key: String = chars
+        .next()
+        .map(|first| first.to_lowercase().chain(chars).collect())
+        .unwrap_or_default();
+    if let Some(heading) = heading {
+        key.push('#');
+        key.push_str(&decode_fragment(heading.trim()).to_lowercase());
+    }
+    key
+}
+
+/// Titles that differ only in case beyond their first letter but resolve to different pages.
+///
+/// Links used to be matched case-insensitively, which conflated these; this lists them so that
+/// the change in how they resolve can be reviewed.
+fn case_collisions(
+    links_to_articles: &BTreeMap<String, PageName>,
+) -> BTreeMap<String, BTreeMap<String, PageName>> {
+    let mut by_folded_key: BTreeMap<String, BTreeMap<String, PageName>> = BTreeMap::new();
+    for (key, page) in links_to_articles {
+        by_folded_key
+            .entry(key.to_lowercase())
+            .or_default()
+            .insert(key.clone(), page.clone());
     }
+    by_folded_key
+        .into_iter()
+        .filter(|(_, keys)| {
+            let mut pages = keys.values();
+            let first = pages.next();
+            pages.any(|page| Some(page) != first)
+        })
+        .collect()
+}
+
+/// Decode the percent-encoding and underscores of a URL fragment. Anything that isn't validly
+/// encoded is left as-is.
+fn decode_fragment(fragment: &str) -> String {
+    let mut bytes = Vec::with_capacity(fragment.len());
+    let mut rest = fragment.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        rest = tail;
+        match byte {
+            b'_' => bytes.push(b' '),
+            b'%' if rest.len() >= 2 => {
+                match std::str::from_utf8(&rest[..2])
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                {
+                    Some(decoded) => {
+                        bytes.push(decoded);
+                        rest = &rest[2..];
+                    }
+                    None => bytes.push(byte),
+                }
+            }
+            _ => bytes.push(byte),
+        }
+    }
+    String::from_utf8(bytes).unwrap_or_else(|_| fragment.replace('_', " "))
 }
 
 /// Original-cased redirect titles that resolve to each tracked page.
@@ -50,6 +358,98 @@ impl PageAliases {
             .sum();
         own + via_redirects
     }
+
+    /// Make the aliases of `page`, and its title, aliases of `into`, for a genre merged into
+    /// another.
+    pub fn merge(&mut self, page: &PageName, into: &PageName) {
+        let mut aliases = self.0.remove(page).unwrap_or_default();
+        aliases.insert(page.to_string());
+        self.0.entry(into.clone()).or_default().extend(aliases);
+    }
+}
+
+/// The longest chain of redirects that will be followed when looking for issues.
+const MAX_REDIRECT_CHAIN: usize = 32;
+
+/// Problems with redirects found while resolving links. Many explain why an expected edge or
+/// alias is missing, and some are fixable on Wikipedia.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct RedirectIssues {
+    /// Redirects whose target is itself a redirect, with the chain of targets that follows.
+    /// Chains that end in a cycle are included, and also listed in `cycles`.
+    pub double_redirects: BTreeMap<String, Vec<String>>,
+    /// Redirects that lead back to themselves, starting from the alphabetically first.
+    pub cycles: BTreeSet<Vec<String>>,
+    /// Redirects to a heading of a tracked page that has no genre under it, mapped to their
+    /// target. These resolve to the page itself, which is usually a renamed or removed section.
+    pub missing_heading_targets: BTreeMap<String, String>,
+}
+impl RedirectIssues {
+    /// Find the issues in `redirects`, given the fully resolved links.
+    ///
+    /// Redirects to pages that don't exist at all can't be found, as only genre and artist
+    /// pages are kept from the dump.
+    fn find(
+        redirects: &BTreeMap<PageName, PageName>,
+        links_to_articles: &BTreeMap<String, PageName>,
+    ) -> Self {
+        let mut issues = Self::default();
+        for (page, target) in redirects {
+            let mut chain = vec![page.to_string()];
+            let mut next = target;
+            while let Some(next_target) = redirects.get(&next.with_opt_heading(None)) {
+                let next_name = next.with_opt_heading(None).to_string();
+                if let Some(cycle_start) = chain.iter().position(|p| *p == next_name) {
+                    let mut cycle = chain[cycle_start..].to_vec();
+                    let first = (0..cycle.len()).min_by_key(|&i| &cycle[i]).unwrap_or(0);
+                    cycle.rotate_left(first);
+                    issues.cycles.insert(cycle);
+                    break;
+                }
+                chain.push(next_name);
+                if chain.len() > MAX_REDIRECT_CHAIN {
+                    break;
+                }
+                next = next_target;
+            }
+            if chain.len() > 1 {
+                chain.remove(0);
+                issues.double_redirects.insert(page.to_string(), chain);
+            }
+
+            if target.heading.is_some() {
+                let key = link_key(&target.to_string());
+                let page_key = link_key(&target.with_opt_heading(None).to_string());
+                if !links_to_articles.contains_key(&key)
+                    && links_to_articles.contains_key(&page_key)
+                {
+                    issues
+                        .missing_heading_targets
+                        .insert(page.to_string(), target.to_string());
+                }
+            }
+        }
+        issues
+    }
+}
+
+/// Bump this whenever the keys of the links to articles cache change (see [`link_key`]), so that
+/// caches written with the old keys are resolved again, rather than quietly missing lookups.
+const LINKS_TO_ARTICLES_VERSION: u32 = 1;
+
+/// The links to articles cache, as written by [`resolve`].
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LinksToArticlesCache {
+    /// The [`LINKS_TO_ARTICLES_VERSION`] the cache was written with.
+    version: u32,
+    links: BTreeMap<String, PageName>,
+}
+
+/// Load the links to articles cache at `path`, if it's readable and in the current version.
+fn load_links_to_articles(path: &Path) -> Option<BTreeMap<String, PageName>> {
+    let cache: LinksToArticlesCache = serde_json::from_slice(&std::fs::read(path).ok()?).ok()?;
+    (cache.version == LINKS_TO_ARTICLES_VERSION).then_some(cache.links)
 }
 
 /// Construct a map of links (lower-case page names and redirects) to pages,
@@ -59,19 +459,33 @@ impl PageAliases {
 ///
 /// This will loop over all redirects and find redirects to already-resolved pages, adding them to the map.
 /// It will continue to do this until no new links are found.
+///
+/// Any [`RedirectIssues`] found along the way are written to `redirect_issues_path`, and titles
+/// that only differ in case (see [`link_key`]) to `case_collisions_path`.
+#[allow(clippy::too_many_arguments)]
 pub fn resolve<'a>(
     start: std::time::Instant,
     links_to_articles_path: &Path,
     page_aliases_path: &Path,
+    redirect_issues_path: &Path,
+    case_collisions_path: &Path,
     pages: impl Iterator<Item = &'a PageName>,
     all_redirects: extract::AllRedirects,
+    normalization: &NameNormalization,
 ) -> anyhow::Result<(LinksToArticles, PageAliases)> {
-    // Only use the cache when both files exist; otherwise recompute both.
-    if links_to_articles_path.is_file() && page_aliases_path.is_file() {
-        let links_to_articles: BTreeMap<String, PageName> = serde_json::from_slice(
-            &std::fs::read(links_to_articles_path).context("Failed to read links to articles")?,
-        )
-        .context("Failed to parse links to articles")?;
+    // Only use the cache when both files exist (and the links are in the current format);
+    // otherwise recompute both.
+    let links_to_articles = page_aliases_path
+        .is_file()
+        .then(|| load_links_to_articles(links_to_articles_path))
+        .flatten();
+    if links_to_articles.is_none() && links_to_articles_path.is_file() {
+        println!(
+            "{:.2}s: cached links to articles are out of date, resolving them again",
+            start.elapsed().as_secs_f32()
+        );
+    }
+    if let Some(links_to_articles) = links_to_articles {
         let page_aliases: BTreeMap<PageName, BTreeSet<String>> = serde_json::from_slice(
             &std::fs::read(page_aliases_path).context("Failed to read page aliases")?,
         )
@@ -83,7 +497,7 @@ pub fn resolve<'a>(
             page_aliases.len()
         );
         return Ok((
-            LinksToArticles(links_to_articles),
+            LinksToArticles::new(links_to_articles, normalization.clone()),
             PageAliases(page_aliases),
         ));
     }
@@ -99,7 +513,7 @@ pub fn resolve<'a>(
 
     let mut links_to_articles: BTreeMap<String, PageName> = BTreeMap::new();
     for page in pages {
-        links_to_articles.insert(page.to_string().to_lowercase(), page.clone());
+        links_to_articles.insert(link_key(&page.to_string()), page.clone());
     }
 
     let mut page_aliases: BTreeMap<PageName, BTreeSet<String>> = BTreeMap::new();
@@ -108,10 +522,15 @@ pub fn resolve<'a>(
     loop {
         let mut added = false;
         for (page, redirect) in &all_redirects {
-            let page_lower = page.to_string().to_lowercase();
-            let redirect = redirect.to_string().to_lowercase();
+            let page_lower = link_key(&page.to_string());
+            let redirect = link_key(&redirect.to_string());
 
-            if let Some(target) = links_to_articles.get(&redirect) {
+            // Redirects to a heading that isn't a genre of its own go to the heading's page
+            let target = links_to_articles.get(&redirect).or_else(|| {
+                let (redirect_page, _heading) = redirect.split_once('#')?;
+                links_to_articles.get(redirect_page)
+            });
+            if let Some(target) = target {
                 let target = target.clone();
                 let newly_added = links_to_articles
                     .insert(page_lower, target.clone())
@@ -143,11 +562,16 @@ pub fn resolve<'a>(
     );
 
     // Save links to articles and page aliases to file
+    let cache = LinksToArticlesCache {
+        version: LINKS_TO_ARTICLES_VERSION,
+        links: links_to_articles,
+    };
     std::fs::write(
         links_to_articles_path,
-        serde_json::to_string_pretty(&links_to_articles)?,
+        serde_json::to_string_pretty(&cache)?,
     )
     .context("Failed to write links to articles")?;
+    let links_to_articles = cache.links;
     std::fs::write(
         page_aliases_path,
         serde_json::to_string_pretty(&page_aliases)?,
@@ -158,8 +582,244 @@ pub fn resolve<'a>(
         now.elapsed().as_secs_f32()
     );
 
+    let case_collisions = case_collisions(&links_to_articles);
+    std::fs::write(
+        case_collisions_path,
+        serde_json::to_string_pretty(&case_collisions)?,
+    )
+    .context("Failed to write case collisions")?;
+    println!(
+        "{:.2}s: found {} titles that only differ in case beyond their first letter",
+        start.elapsed().as_secs_f32(),
+        case_collisions.len()
+    );
+
+    let redirect_issues = RedirectIssues::find(&all_redirects, &links_to_articles);
+    std::fs::write(
+        redirect_issues_path,
+        serde_json::to_string_pretty(&redirect_issues)?,
+    )
+    .context("Failed to write redirect issues")?;
+    println!(
+        "{:.2}s: found {} double redirects, {} redirect cycles and {} redirects to missing headings",
+        start.elapsed().as_secs_f32(),
+        redirect_issues.double_redirects.len(),
+        redirect_issues.cycles.len(),
+        redirect_issues.missing_heading_targets.len()
+    );
+
     Ok((
-        LinksToArticles(links_to_articles),
+        LinksToArticles::new(links_to_articles, normalization.clone()),
         PageAliases(page_aliases),
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normalization() -> NameNormalization {
+        NameNormalization {
+            space_equivalents: vec!["_".into(), "-".into()],
+            strip_suffixes: vec![" music".into(), " (music)".into()],
+        }
+    }
+
+    #[test]
+    fn normalize_unifies_separators_and_strips_suffixes() {
+        let normalization = normalization();
+        assert_eq!(normalization.normalize("Techno music"), "techno");
+        assert_eq!(normalization.normalize("Hip-hop"), "hip hop");
+        assert_eq!(
+            normalization.normalize("Drum_and_bass  (music)"),
+            "drum and bass"
+        );
+        // A suffix is never stripped down to nothing
+        assert_eq!(normalization.normalize("Music"), "music");
+    }
+
+    #[test]
+    fn map_falls_back_to_unambiguous_normalized_links() {
+        let links = BTreeMap::from_iter([
+            ("techno".to_string(), PageName::new("Techno", None)),
+            ("hip-hop".to_string(), PageName::new("Hip-hop", None)),
+            (
+                "house music".to_string(),
+                PageName::new("House music", None),
+            ),
+            (
+                "house".to_string(),
+                PageName::new("House (disambiguation)", None),
+            ),
+        ]);
+        let links_to_articles = LinksToArticles::new(links, normalization());
+
+        assert_eq!(
+            links_to_articles.map("Techno music").cloned(),
+            Some(PageName::new("Techno", None))
+        );
+        assert_eq!(
+            links_to_articles.map("Hip hop").cloned(),
+            Some(PageName::new("Hip-hop", None))
+        );
+        // Exact matches take precedence
+        assert_eq!(
+            links_to_articles.map("House").cloned(),
+            Some(PageName::new("House (disambiguation)", None))
+        );
+        // ...but ambiguous normalized forms are not used
+        assert_eq!(links_to_articles.map("House (music)").cloned(), None);
+    }
+
+    #[test]
+    fn redirect_issues_finds_chains_cycles_and_missing_headings() {
+        let pn = |name: &str| name.parse::<PageName>().unwrap();
+        let redirects = BTreeMap::from_iter([
+            (pn("Acid"), pn("Acid house")),
+            (pn("Acid house music"), pn("Acid")),
+            (pn("Loop A"), pn("Loop B")),
+            (pn("Loop B"), pn("Loop A")),
+            (pn("Scouse house"), pn("UK hard house#Scouse house")),
+            (pn("Hardbag"), pn("UK hard house#Hardbag")),
+        ]);
+        let links_to_articles = BTreeMap::from_iter([
+            ("acid house".to_string(), pn("Acid house")),
+            ("uK hard house".to_string(), pn("UK hard house")),
+            (
+                "uK hard house#scouse house".to_string(),
+                pn("UK hard house#Scouse house"),
+            ),
+        ]);
+
+        let issues = RedirectIssues::find(&redirects, &links_to_articles);
+
+        assert_eq!(
+            issues.double_redirects,
+            BTreeMap::from_iter([
+                ("Acid house music".to_string(), vec!["Acid".to_string()]),
+                ("Loop A".to_string(), vec!["Loop B".to_string()]),
+                ("Loop B".to_string(), vec!["Loop A".to_string()]),
+            ])
+        );
+        assert_eq!(
+            issues.cycles,
+            BTreeSet::from_iter([vec!["Loop A".to_string(), "Loop B".to_string()]])
+        );
+        assert_eq!(
+            issues.missing_heading_targets,
+            BTreeMap::from_iter([("Hardbag".to_string(), "UK hard house#Hardbag".to_string())])
+        );
+    }
+
+    #[test]
+    fn link_keys_only_fold_the_first_letter() {
+        assert_eq!(link_key("Hip hop"), "hip hop");
+        assert_eq!(link_key("hip hop"), "hip hop");
+        assert_eq!(link_key("Hip Hop"), "hip Hop");
+        assert_eq!(link_key(" UK_garage "), "uK garage");
+        assert_eq!(link_key("Éthio-jazz"), "éthio-jazz");
+        assert_eq!(
+            link_key("UK hard house#Scouse_House"),
+            "uK hard house#scouse house"
+        );
+
+        let pn = |name: &str| PageName::new(name, None);
+        let links = BTreeMap::from_iter([
+            ("aCID".to_string(), pn("ACID")),
+            ("acid".to_string(), pn("Acid")),
+            ("hip hop".to_string(), pn("Hip hop")),
+            ("hip Hop".to_string(), pn("Hip hop")),
+        ]);
+        assert_eq!(
+            case_collisions(&links),
+            BTreeMap::from_iter([(
+                "acid".to_string(),
+                BTreeMap::from_iter([
+                    ("aCID".to_string(), pn("ACID")),
+                    ("acid".to_string(), pn("Acid")),
+                ])
+            )])
+        );
+        let links_to_articles = LinksToArticles::new(links, NameNormalization::default());
+        assert_eq!(links_to_articles.map("ACID").cloned(), Some(pn("ACID")));
+        assert_eq!(links_to_articles.map("acid").cloned(), Some(pn("Acid")));
+    }
+
+    #[test]
+    fn interwiki_links_are_detected_and_never_resolve() {
+        assert_eq!(interwiki_prefix(":fr:Zouk"), Some("fr"));
+        assert_eq!(interwiki_prefix("wikt:riddim"), Some("wikt"));
+        assert_eq!(interwiki_prefix(":zh-yue:粵語流行曲"), Some("zh-yue"));
+        assert_eq!(interwiki_prefix("Commons:Category:Zouk"), Some("Commons"));
+        assert_eq!(interwiki_prefix("MC: The Album"), None);
+        assert_eq!(interwiki_prefix("Zouk"), None);
+        assert_eq!(interwiki_prefix("fr:"), None);
+
+        let links = BTreeMap::from_iter([("zouk".to_string(), PageName::new("Zouk", None))]);
+        let links_to_articles = LinksToArticles::new(links, normalization());
+        assert_eq!(links_to_articles.map(":fr:Zouk").cloned(), None);
+        assert_eq!(
+            links_to_articles.map("Zouk").cloned(),
+            Some(PageName::new("Zouk", None))
+        );
+    }
+
+    #[test]
+    fn map_prefers_heading_genres_over_their_page() {
+        let uk_hard_house = PageName::new("UK hard house", None);
+        let scouse_house = PageName::new("UK hard house", "Scouse house".to_string());
+        let links = BTreeMap::from_iter([
+            ("uK hard house".to_string(), uk_hard_house.clone()),
+            (
+                "uK hard house#scouse house".to_string(),
+                scouse_house.clone(),
+            ),
+            ("scouse house".to_string(), scouse_house.clone()),
+        ]);
+        let links_to_articles = LinksToArticles::new(links, NameNormalization::default());
+
+        assert_eq!(
+            links_to_articles.map("Scouse house").cloned(),
+            Some(scouse_house.clone())
+        );
+        assert_eq!(
+            links_to_articles.map("UK hard house#Scouse_house").cloned(),
+            Some(scouse_house.clone())
+        );
+        assert_eq!(
+            links_to_articles
+                .map("UK_hard_house#Scouse%20house")
+                .cloned(),
+            Some(scouse_house)
+        );
+        // Headings without a genre of their own resolve to their page
+        assert_eq!(
+            links_to_articles.map("UK hard house#History").cloned(),
+            Some(uk_hard_house)
+        );
+    }
+
+    #[test]
+    fn links_to_articles_cache_in_an_old_format_is_not_loaded() {
+        let dir = std::env::temp_dir().join(format!("datagen-links-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("links_to_articles.json");
+        let links = BTreeMap::from_iter([("techno".to_string(), PageName::new("Techno", None))]);
+
+        std::fs::write(&path, serde_json::to_string(&links).unwrap()).unwrap();
+        assert!(load_links_to_articles(&path).is_none());
+
+        std::fs::write(
+            &path,
+            serde_json::to_string(&LinksToArticlesCache {
+                version: LINKS_TO_ARTICLES_VERSION,
+                links: links.clone(),
+            })
+            .unwrap(),
+        )
+        .unwrap();
+        let loaded = load_links_to_articles(&path);
+        std::fs::remove_dir_all(&dir).ok();
+        assert_eq!(loaded, Some(links));
+    }
+}
@@ -2,29 +2,77 @@
 #![warn(missing_docs)]
 
 use anyhow::Context;
+use clap::Parser as _;
 
 use std::path::Path;
 
+pub mod attribution;
+pub mod bundle;
 pub mod check_mixes;
+pub mod cli;
+pub mod countries;
 pub mod data_patches;
+pub mod dataset_stats;
+pub mod description_html;
+pub mod diff;
+pub mod export;
 pub mod extract;
-pub mod force_layout;
 pub mod frontend_types;
+pub mod genre_images;
 pub mod genre_top_artists;
+pub mod langlinks;
+pub mod layout_metrics;
 pub mod link_counts;
 pub mod links;
+pub mod migrate_mixes;
+pub mod mix_embeds;
+pub mod mixes_csv;
+pub mod mixes_help;
+pub mod og_cards;
 pub mod output;
+pub mod pageviews;
+pub mod patch_report;
 pub mod populate_mixes;
 pub mod process;
+pub mod prose_mentions;
+pub mod snapshot;
 pub mod types;
 pub mod util;
+pub mod validate_patches;
+pub mod wikidata;
 
 fn main() -> anyhow::Result<()> {
-    let config: types::Config = {
+    let cli = cli::Cli::parse();
+    let command = cli.command.unwrap_or(cli::Command::All);
+    let args = cli.pipeline;
+    args.validate()?;
+
+    match &command {
+        cli::Command::Diff {
+            old_output,
+            new_output,
+        } => return diff::run(old_output, new_output),
+        cli::Command::MigrateMixes => return migrate_mixes::run(Path::new("mixes")),
+        cli::Command::Mixes {
+            command: cli::MixesCommand::Export { csv },
+        } => return mixes_csv::export(Path::new("mixes"), csv),
+        _ => {}
+    }
+
+    let mut config: types::Config = {
         let config_str =
             std::fs::read_to_string("config.toml").context("Failed to read config.toml")?;
         toml::from_str(&config_str).context("Failed to parse config.toml")?
     };
+    config.output.pretty |= args.pretty;
+
+    if let cli::Command::ValidateMixes { mark } = command {
+        return check_mixes::validate(Path::new("mixes"), &config.youtube_api_key, mark);
+    }
+    // The rest run the pipeline, as far as this stage
+    let last_stage = command
+        .stage()
+        .expect("The commands that don't run the pipeline have returned");
 
     let wiki_paths = config
         .resolve_wikipedia_paths()
@@ -57,39 +105,155 @@ fn main() -> anyhow::Result<()> {
 
     let output_path = Path::new("output").join(dump_date.to_string());
     let start = std::time::Instant::now();
+    args.clear_forced(&output_path)?;
+
+    // The previous dump's output lets us skip reprocessing pages that haven't changed.
+    let previous_output_path = args
+        .previous_output
+        .clone()
+        .or_else(|| util::find_previous_output(Path::new("output"), dump_date));
+    if let Some(previous_output_path) = &previous_output_path {
+        println!(
+            "using previous output at {}",
+            previous_output_path.display()
+        );
+    }
+
+    // The layout is warm-started from the previous dump's, unless another layout is given
+    let previous_layout_path = match args.previous_layout.clone() {
+        Some(path) => {
+            anyhow::ensure!(path.is_file(), "No layout at {}", path.display());
+            Some(path)
+        }
+        None => previous_output_path
+            .as_ref()
+            .map(|path| path.join(output::LAYOUT_FILE_NAME)),
+    };
 
     let extracted_data = extract::from_data_dump(&wiki_paths, start, dump_date, &output_path)?;
 
+    // Read before processing so that the shared patches can be resolved to pages
+    let wikidata_ids = wiki_paths
+        .page_props_path
+        .as_deref()
+        .map(|page_props_path| {
+            let pages = extracted_data
+                .genres
+                .0
+                .keys()
+                .filter(|page| page.heading.is_none())
+                .chain(extracted_data.artists.0.keys())
+                .cloned()
+                .collect();
+            wikidata::read(
+                start,
+                page_props_path,
+                &extracted_data.id_to_page_names,
+                &pages,
+                &output_path,
+            )
+        })
+        .transpose()?;
+
+    let mut data_patches = data_patches::DataPatches::load(
+        Path::new(data_patches::PATCHES_PATH),
+        data_patches::language(&extracted_data.dump_meta.wikipedia_db_name),
+    )?;
+    let unresolved_patches = match &wikidata_ids {
+        Some(wikidata_ids) => data_patches.resolve_shared(wikidata_ids)?,
+        None => {
+            let shared = &data_patches.shared;
+            if !shared.exclude.is_empty() || !shared.origin.is_empty() || !shared.merge.is_empty() {
+                println!(
+                    "warning: no page props dump to resolve the shared patches with, so they won't be applied"
+                );
+            }
+            vec![]
+        }
+    };
+
+    if let cli::Command::ValidatePatches = command {
+        let processed_genres_path = std::iter::once(output_path.as_path())
+            .chain(previous_output_path.as_deref())
+            .map(|path| path.join("processed_genres"))
+            .find(|path| path.is_dir());
+        return validate_patches::run(
+            &data_patches,
+            &unresolved_patches,
+            extracted_data,
+            processed_genres_path.as_deref(),
+        );
+    }
+    for qid in &unresolved_patches {
+        println!("warning: the shared patch for {qid} isn't for a page in the extracted data");
+    }
+
+    if let cli::Command::Snapshot { path } = &command {
+        return snapshot::run(start, &extracted_data, &data_patches, path);
+    }
+    if last_stage == cli::Stage::Extract {
+        return Ok(());
+    }
+
+    let mut patch_report = patch_report::PatchReport::default();
+
     let processed_genres = process::genres(
         start,
         &extracted_data.genres,
         &output_path.join("processed_genres"),
+        previous_output_path.as_deref(),
+        &data_patches,
+        &mut patch_report,
+        args.dump_page.as_deref(),
     )?;
 
     let mixes_path = Path::new("mixes");
-    if std::env::args().any(|arg| arg == "--populate-mixes") {
-        populate_mixes::run(mixes_path, &extracted_data.dump_meta, &processed_genres)?;
-    }
-
-    if std::env::args().any(|arg| arg == "--check-mixes") {
-        check_mixes::run(mixes_path, &config.youtube_api_key)?;
-        return Ok(());
+    if let cli::Command::Mixes {
+        command: cli::MixesCommand::Import { csv },
+    } = &command
+    {
+        return mixes_csv::import(mixes_path, csv, &processed_genres);
     }
 
     let processed_artists = process::artists(
         start,
         &extracted_data.artists,
         &output_path.join("processed_artists"),
+        previous_output_path.as_deref(),
+        &data_patches,
+        &mut patch_report,
+        args.dump_page.as_deref(),
     )?;
+    patch_report.finish(&data_patches, &processed_genres, &processed_artists);
+    // Written again once the output has applied the edge patches
+    patch_report.write(&output_path.join("patch_report.json"))?;
+    if last_stage == cli::Stage::Process {
+        return Ok(());
+    }
+
+    let name_normalization =
+        links::NameNormalization::load(Path::new(links::NAME_NORMALIZATION_PATH))?;
 
     // Resolved before link counting so that redirect pages can be tracked too.
-    let (links_to_articles, page_aliases) = links::resolve(
+    let (mut links_to_articles, mut page_aliases) = links::resolve(
         start,
         &output_path.join("links_to_articles.json"),
         &output_path.join("page_aliases.json"),
-        processed_genres.0.keys().chain(processed_artists.0.keys()),
+        &output_path.join("redirect_issues.json"),
+        &output_path.join("case_collisions.json"),
+        processed_genres
+            .0
+            .keys()
+            .chain(processed_artists.0.keys())
+            // Merged genres are resolved, so that their links go to the genres they're merged into
+            .chain(data_patches.merges.keys()),
         extracted_data.redirects,
+        &name_normalization,
     )?;
+    for (page, into) in &data_patches.merges {
+        links_to_articles.merge(page, into);
+        page_aliases.merge(page, into);
+    }
 
     // Count inbound links to artist pages, genre root pages, and every
     // redirect page that resolves to either — redirect-page counts are what
@@ -116,30 +280,140 @@ fn main() -> anyhow::Result<()> {
 
     let inbound_link_counts = link_counts::read(
         start,
-        &wiki_paths.linktargets_path,
+        dump_date,
+        wiki_paths.page_path.as_deref(),
+        wiki_paths.linktargets_path.as_deref(),
         &wiki_paths.links_path,
         &tracked_pages,
+        &config.link_counts,
         &output_path,
+        previous_output_path.as_deref(),
     )?;
 
+    let pageviews = if config.ranking.metric == types::RankingMetric::Links {
+        None
+    } else {
+        let pageviews_path = wiki_paths.pageviews_path.as_deref().with_context(|| {
+            format!(
+                "Ranking by {:?} requires a pageview dump (pageviews-*-user.bz2) in the dump directory",
+                config.ranking.metric
+            )
+        })?;
+        Some(pageviews::read(
+            start,
+            pageviews_path,
+            &tracked_pages,
+            &output_path,
+        )?)
+    };
+
+    if let cli::Command::PopulateMixes { order } = command {
+        let order = match order {
+            cli::MixOrder::Popularity => populate_mixes::QueueOrder::by_popularity(
+                &processed_genres,
+                &page_aliases,
+                &inbound_link_counts,
+                pageviews.as_ref(),
+                &config.ranking,
+            ),
+            cli::MixOrder::Edges => populate_mixes::QueueOrder::Edges,
+        };
+        return populate_mixes::run(
+            mixes_path,
+            &extracted_data.dump_meta,
+            &processed_genres,
+            &config.youtube_api_key,
+            order,
+        );
+    }
+
+    if let cli::Command::CheckMixes = command {
+        anyhow::ensure!(
+            !config.youtube_api_key.is_empty(),
+            "Checking mixes needs a youtube_api_key in config.toml"
+        );
+        return check_mixes::run(mixes_path, &config.youtube_api_key);
+    }
+
+    let prose_mentions = args
+        .prose_mentions
+        .then(|| {
+            prose_mentions::mine(
+                start,
+                &extracted_data.genres,
+                &output_path.join("prose_mentions.json"),
+            )
+        })
+        .transpose()?;
+
+    let langlinks = wiki_paths
+        .langlinks_path
+        .as_deref()
+        .map(|langlinks_path| {
+            let genre_pages = processed_genres
+                .0
+                .keys()
+                .filter(|page| page.heading.is_none())
+                .cloned()
+                .collect();
+            langlinks::read(
+                start,
+                langlinks_path,
+                &extracted_data.id_to_page_names,
+                &genre_pages,
+                &output_path,
+            )
+        })
+        .transpose()?;
+    if last_stage == cli::Stage::Links {
+        return Ok(());
+    }
+
+    let ranking_suffix = config.ranking.cache_suffix();
+    let category_genres = genre_top_artists::CategoryGenres::load(Path::new(
+        genre_top_artists::CATEGORY_GENRES_PATH,
+    ))?;
     let (genre_top_artists, artist_genres) = genre_top_artists::calculate(
         start,
         &processed_artists,
+        &category_genres,
         &inbound_link_counts,
+        pageviews.as_ref(),
+        &config.ranking,
         &page_aliases,
         &links_to_articles,
-        &output_path.join("genre_top_artists.json"),
-        &output_path.join("artist_genres.json"),
+        &output_path.join(format!("genre_top_artists{ranking_suffix}.json")),
+        &output_path.join(format!("artist_genres{ranking_suffix}.json")),
     )?;
 
+    let coverage_stats = genre_top_artists::CoverageStats::calculate(
+        processed_genres.0.keys(),
+        &genre_top_artists,
+        &artist_genres,
+        |artist| page_aliases.aggregated_link_count(artist, &inbound_link_counts),
+    );
+    std::fs::write(
+        output_path.join(format!("coverage_stats{ranking_suffix}.json")),
+        serde_json::to_string_pretty(&coverage_stats)?,
+    )
+    .context("Failed to write coverage stats")?;
+    if last_stage == cli::Stage::Artists {
+        return Ok(());
+    }
+
+    let musicbrainz_genres =
+        wikidata::MusicBrainzGenres::load(Path::new(wikidata::MUSICBRAINZ_GENRES_PATH))?;
+
     let website_public_path = Path::new(frontend_types::WEBSITE_PUBLIC_PATH);
+    // Laying out the graph alone leaves the website's data as it is
+    let layout_only = last_stage == cli::Stage::Layout;
 
-    std::fs::remove_dir_all(website_public_path).ok();
-    std::fs::create_dir_all(website_public_path)?;
+    if !layout_only {
+        std::fs::remove_dir_all(website_public_path).ok();
+        std::fs::create_dir_all(website_public_path)?;
 
-    std::fs::write(website_public_path.join("CNAME"), "genresin.space")?;
+        std::fs::write(website_public_path.join("CNAME"), "genresin.space")?;
 
-    {
         let icon = image::open(Path::new("assets/icon.png"))?;
 
         icon.resize(128, 128, image::imageops::FilterType::Lanczos3)
@@ -154,17 +428,75 @@ fn main() -> anyhow::Result<()> {
         );
     }
 
+    let genre_images = (config.output.genre_images && !layout_only)
+        .then(|| {
+            let files = processed_genres
+                .0
+                .values()
+                .filter_map(|genre| genre.image.clone())
+                .collect();
+            genre_images::write(
+                start,
+                &extracted_data.dump_meta.wikipedia_domain,
+                &files,
+                Path::new(genre_images::CACHE_PATH),
+                website_public_path,
+            )
+        })
+        .transpose()?;
+
+    let inputs = output::Inputs {
+        dump_meta: &extracted_data.dump_meta,
+        mixes_path,
+        previous_layout_path: previous_layout_path.as_deref(),
+        skip_layout: args.skips(cli::Stage::Layout),
+        links_to_articles: &links_to_articles,
+        page_aliases: &page_aliases,
+        alias_redirects: &extracted_data.alias_redirects,
+        name_normalization: &name_normalization,
+        prose_mentions: prose_mentions.as_ref(),
+        langlinks: langlinks.as_ref(),
+        wikidata_ids: wikidata_ids.as_ref(),
+        musicbrainz_genres: &musicbrainz_genres,
+        genre_images: genre_images.as_ref(),
+        inbound_link_counts: &inbound_link_counts,
+        processed_genres: &processed_genres,
+        processed_artists: &processed_artists,
+        data_patches: &data_patches,
+        genre_top_artists: &genre_top_artists,
+        artist_genres: &artist_genres,
+        top_artists_config: &config.top_artists,
+        layout_config: &config.layout,
+    };
     output::produce(
         start,
-        &extracted_data.dump_meta,
-        mixes_path,
+        &inputs,
+        &config.output,
         website_public_path,
-        &links_to_articles,
-        &page_aliases,
-        &inbound_link_counts,
-        &processed_genres,
-        &processed_artists,
-        &genre_top_artists,
-        &artist_genres,
-    )
+        &output_path,
+        &mut patch_report,
+        layout_only,
+    )?;
+    if layout_only {
+        return Ok(());
+    }
+    patch_report.write(&output_path.join("patch_report.json"))?;
+    output::write_type_definitions(
+        start,
+        Path::new(frontend_types::WEBSITE_TYPES_PATH),
+        website_public_path,
+    )?;
+
+    // Before the files are renamed, so that the bundle has their plain names
+    if config.output.bundle {
+        bundle::write(start, website_public_path, &extracted_data.dump_meta)?;
+    }
+    if config.output.hashed_filenames {
+        output::hash_filenames(start, website_public_path)?;
+    }
+    if config.output.precompress {
+        output::precompress(start, website_public_path)?;
+    }
+
+    Ok(())
 }
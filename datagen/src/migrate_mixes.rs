@@ -0,0 +1,129 @@
+//! Converting the mixes files from the plain text format to the TOML one
+//! (`datagen migrate-mixes`); see [`MixesFile`].
+use std::path::Path;
+
+use anyhow::Context as _;
+
+use crate::{
+    mix_embeds::{self, MixEmbeds},
+    types::{GenreMix, MixesFile},
+};
+
+/// Convert every mixes file in `mixes_path` that's still in the plain text format to TOML, in
+/// place. The mixes' titles are filled in from the mix embed cache, if they've been looked up.
+pub fn run(mixes_path: &Path) -> anyhow::Result<()> {
+    let cache_path = Path::new(mix_embeds::CACHE_PATH);
+    let embeds: MixEmbeds = if cache_path.is_file() {
+        serde_json::from_slice(&std::fs::read(cache_path)?)
+            .with_context(|| format!("Failed to parse {cache_path:?}"))?
+    } else {
+        MixEmbeds::new()
+    };
+
+    let (mut migrated, mut titled) = (0, 0);
+    for entry in std::fs::read_dir(mixes_path)? {
+        let path = entry?.path();
+        if !path.is_file() {
+            continue;
+        }
+        let contents = std::fs::read_to_string(&path)?;
+        if MixesFile::is_toml(&contents) {
+            continue;
+        }
+        let (file, comments) = migrate(&contents, &embeds);
+        titled += file.mixes.iter().filter(|mix| mix.title.is_some()).count();
+        std::fs::write(&path, file.to_toml(&comments)?)
+            .with_context(|| format!("Failed to write {path:?}"))?;
+        migrated += 1;
+    }
+    println!("Migrated {migrated} mixes files, with {titled} mixes' titles from {cache_path:?}");
+    Ok(())
+}
+
+/// A plain text mixes file as TOML, with its comment lines, and the titles of the mixes in
+/// `embeds`.
+fn migrate(contents: &str, embeds: &MixEmbeds) -> (MixesFile, Vec<String>) {
+    let (mut file, comments) = MixesFile::from_plain_text(contents);
+    for entry in &mut file.mixes {
+        let Some(mix) = GenreMix::from_url(&entry.url, None, Default::default()) else {
+            continue;
+        };
+        let Some((id, _)) = mix_embeds::mix_url(&mix) else {
+            continue;
+        };
+        if let Some(Some(embed)) = embeds.get(id) {
+            entry.title = Some(embed.title.clone());
+        }
+    }
+    (file, comments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mix_embeds::MixEmbed, types::GenreMixes};
+
+    #[test]
+    fn plain_text_mixes_are_migrated() {
+        let contents = "https://www.youtube.com/playlist?list=PL123 # A great mix\n\
+            # dead (deleted): https://www.youtube.com/watch?v=gone\n\
+            https://youtu.be/abc\n";
+        let embeds = MixEmbeds::from([
+            (
+                "abc".to_string(),
+                Some(MixEmbed {
+                    title: "Two hours of acid house".to_string(),
+                    channel: "Someone".to_string(),
+                    channel_url: "https://www.youtube.com/@someone".to_string(),
+                    thumbnail_url: "https://i.ytimg.com/vi/abc/hqdefault.jpg".to_string(),
+                }),
+            ),
+            ("PL123".to_string(), None),
+        ]);
+        let (file, comments) = migrate(contents, &embeds);
+        let migrated = file.to_toml(&comments).unwrap();
+        assert_eq!(
+            migrated,
+            "# dead (deleted): https://www.youtube.com/watch?v=gone\n\
+             \n\
+             [[mix]]\n\
+             url = \"https://www.youtube.com/playlist?list=PL123\"\n\
+             note = \"A great mix\"\n\
+             \n\
+             [[mix]]\n\
+             url = \"https://www.youtube.com/watch?v=abc\"\n\
+             title = \"Two hours of acid house\"\n"
+        );
+
+        let GenreMixes::Mixes(mut mixes) = GenreMixes::parse(&migrated).unwrap() else {
+            panic!("expected mixes");
+        };
+        let GenreMix::Video { details, .. } = &mut mixes[1] else {
+            panic!("expected a video");
+        };
+        assert_eq!(
+            details.title.take().as_deref(),
+            Some("Two hours of acid house")
+        );
+        assert_eq!(
+            GenreMixes::Mixes(mixes),
+            GenreMixes::parse(contents).unwrap()
+        );
+    }
+
+    #[test]
+    fn help_is_migrated() {
+        let (file, comments) = migrate("help: not ready", &MixEmbeds::new());
+        let migrated = file.to_toml(&comments).unwrap();
+        assert_eq!(migrated, "help = \"not ready\"\n");
+        assert_eq!(
+            GenreMixes::parse(&migrated).unwrap(),
+            GenreMixes::parse("help: not ready").unwrap()
+        );
+        let (file, _) = migrate("help", &MixEmbeds::new());
+        assert_eq!(
+            GenreMixes::from(file),
+            GenreMixes::Help { help_reason: None }
+        );
+    }
+}
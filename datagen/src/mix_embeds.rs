@@ -0,0 +1,146 @@
+//! Looking up each mix's title, channel and thumbnail with YouTube's oEmbed endpoint
+//! (`mix_embeds = true` in `[output]`), so that the website can show them without calling
+//! YouTube itself. Lookups are cached on disk across runs, as mixes rarely change.
+use std::{collections::BTreeMap, path::Path, time::Duration};
+
+use anyhow::Context as _;
+use serde::{Deserialize, Serialize};
+
+use crate::types::GenreMix;
+
+/// Where looked-up mixes are cached, shared by every dump's output.
+pub const CACHE_PATH: &str = "output/mix_embeds.json";
+
+/// How long to wait between requests, to stay well clear of YouTube's rate limits.
+pub(crate) const REQUEST_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How many lookups are made between saves of the cache, so that an interrupted run keeps
+/// most of its progress.
+const SAVE_EVERY: usize = 50;
+
+/// What YouTube's oEmbed endpoint says about a mix.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ts_rs::TS, schemars::JsonSchema)]
+pub struct MixEmbed {
+    /// The title of the video or playlist.
+    pub title: String,
+    /// The name of the channel that uploaded it.
+    pub channel: String,
+    /// The URL of the channel.
+    pub channel_url: String,
+    /// The URL of its thumbnail image.
+    pub thumbnail_url: String,
+}
+
+/// Looked-up mixes by their video or playlist ID; those that YouTube says are unavailable (or
+/// private) are `None`, so that they aren't looked up again.
+pub type MixEmbeds = BTreeMap<String, Option<MixEmbed>>;
+
+/// The ID and oEmbed URL of a mix, if it's on YouTube.
+pub(crate) fn mix_url(mix: &GenreMix) -> Option<(&str, String)> {
+    match mix {
+        GenreMix::Playlist { playlist, .. } => Some((playlist, mix.url())),
+        GenreMix::Video { video, .. } => Some((video, mix.url())),
+        GenreMix::External { .. } => None,
+    }
+}
+
+/// Look up the embed metadata of the YouTube mixes in `mixes`, reusing what's in the cache at `cache_path` and
+/// adding what isn't to it. Mixes that can't be looked up (e.g. because YouTube can't be
+/// reached) are left out, and are retried on the next run.
+pub fn resolve<'a>(
+    start: std::time::Instant,
+    mixes: impl IntoIterator<Item = &'a GenreMix>,
+    cache_path: &Path,
+) -> anyhow::Result<MixEmbeds> {
+    let mut cache: MixEmbeds = if cache_path.is_file() {
+        serde_json::from_slice(&std::fs::read(cache_path)?)
+            .with_context(|| format!("Failed to parse {cache_path:?}"))?
+    } else {
+        MixEmbeds::new()
+    };
+    let save = |cache: &MixEmbeds| -> anyhow::Result<()> {
+        if let Some(parent) = cache_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(cache_path, serde_json::to_string_pretty(cache)?)
+            .with_context(|| format!("Failed to write {cache_path:?}"))
+    };
+
+    let client = reqwest::blocking::Client::new();
+    let mut embeds = MixEmbeds::new();
+    let mut cached = 0;
+    let mut looked_up = 0;
+    let mut failed = 0;
+    for mix in mixes {
+        let Some((id, url)) = mix_url(mix) else {
+            continue;
+        };
+        if let Some(embed) = cache.get(id) {
+            embeds.insert(id.to_string(), embed.clone());
+            cached += 1;
+            continue;
+        }
+        if looked_up > 0 {
+            std::thread::sleep(REQUEST_INTERVAL);
+        }
+        looked_up += 1;
+        match look_up(&client, &url) {
+            Ok(embed) => {
+                cache.insert(id.to_string(), embed.clone());
+                embeds.insert(id.to_string(), embed);
+            }
+            Err(e) => {
+                eprintln!("Warning: failed to look up mix {url}: {e:?}");
+                failed += 1;
+            }
+        }
+        if looked_up % SAVE_EVERY == 0 {
+            save(&cache)?;
+        }
+    }
+    if looked_up > 0 {
+        save(&cache)?;
+    }
+
+    println!(
+        "{:.2}s: looked up {looked_up} mixes ({failed} failed), and reused {cached} from the cache",
+        start.elapsed().as_secs_f32(),
+    );
+    Ok(embeds)
+}
+
+/// Look up a mix's URL with the oEmbed endpoint, returning `None` if YouTube says it's
+/// unavailable.
+pub(crate) fn look_up(
+    client: &reqwest::blocking::Client,
+    url: &str,
+) -> anyhow::Result<Option<MixEmbed>> {
+    #[derive(Debug, Deserialize)]
+    struct OEmbedResponse {
+        title: String,
+        author_name: String,
+        author_url: String,
+        thumbnail_url: String,
+    }
+
+    let response = client
+        .get("https://www.youtube.com/oembed")
+        .query(&[("url", url), ("format", "json")])
+        .send()?;
+    // Removed and private videos and playlists are "Unauthorized" or "Not Found"
+    if matches!(
+        response.status(),
+        reqwest::StatusCode::UNAUTHORIZED
+            | reqwest::StatusCode::FORBIDDEN
+            | reqwest::StatusCode::NOT_FOUND
+    ) {
+        return Ok(None);
+    }
+    let response: OEmbedResponse = response.error_for_status()?.json()?;
+    Ok(Some(MixEmbed {
+        title: response.title,
+        channel: response.author_name,
+        channel_url: response.author_url,
+        thumbnail_url: response.thumbnail_url,
+    }))
+}
@@ -0,0 +1,453 @@
+//! Exporting the mixes to CSV and importing them from it (`datagen mixes export <csv>` and
+//! `datagen mixes import <csv>`), so that they can be maintained in a spreadsheet.
+//!
+//! The CSV has a header row naming its columns, which can be in any order: `genre` and `url` are
+//! required, and `note`, `title`, `duration` (in seconds), `submitter` and `added`
+//! (`YYYY-MM-DD`) are as in the TOML mixes files (see [`MixesFile`]). Other columns are ignored.
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+use anyhow::Context as _;
+
+use crate::{
+    export::escape_csv,
+    process::ProcessedGenres,
+    types::{GenreMix, MixEntry, MixesFile, PageName},
+};
+
+/// The columns written by [`export`].
+const COLUMNS: [&str; 7] = [
+    "genre",
+    "url",
+    "note",
+    "title",
+    "duration",
+    "submitter",
+    "added",
+];
+
+/// Write every mix in `mixes_path` to `csv_path`, a row each. Mixes marked as dead, and genres
+/// that need help finding a mix, are left out.
+pub fn export(mixes_path: &Path, csv_path: &Path) -> anyhow::Result<()> {
+    let mut csv = COLUMNS.join(",");
+    csv.push('\n');
+    let (mut mixes, mut help) = (0, 0);
+    for (genre, file) in read_mixes_files(mixes_path)? {
+        if file.help.is_some() {
+            help += 1;
+        }
+        for entry in file.mixes.iter().filter(|entry| entry.dead.is_none()) {
+            let fields = [
+                genre.to_string(),
+                entry.url.clone(),
+                entry.note.clone().unwrap_or_default(),
+                entry.title.clone().unwrap_or_default(),
+                entry.duration.map(|d| d.to_string()).unwrap_or_default(),
+                entry.submitter.clone().unwrap_or_default(),
+                entry.added.map(|a| a.to_string()).unwrap_or_default(),
+            ];
+            let fields: Vec<String> = fields.iter().map(|field| escape_csv(field)).collect();
+            csv.push_str(&fields.join(","));
+            csv.push('\n');
+            mixes += 1;
+        }
+    }
+    std::fs::write(csv_path, csv).with_context(|| format!("Failed to write {csv_path:?}"))?;
+    println!(
+        "Exported {mixes} mixes to {csv_path:?} ({help} genres that need help finding a mix were left out)"
+    );
+    Ok(())
+}
+
+/// Add the mixes in `csv_path` to the genres' files in `mixes_path`. Each row's genre must be
+/// one of `processed_genres` (by page name, or by name, ignoring case), and its URL a mix (see
+/// [`GenreMix::from_url`]); the rows that aren't, or whose other columns can't be read, are reported
+/// and skipped. Mixes that a genre already has are skipped too.
+pub fn import(
+    mixes_path: &Path,
+    csv_path: &Path,
+    processed_genres: &ProcessedGenres,
+) -> anyhow::Result<()> {
+    let csv = std::fs::read_to_string(csv_path)
+        .with_context(|| format!("Failed to read {csv_path:?}"))?;
+    let lookup = GenreLookup::new(
+        processed_genres
+            .0
+            .iter()
+            .map(|(page, genre)| (page, genre.name.0.as_str())),
+    );
+    let Rows { entries, problems } = parse_rows(&csv, &lookup)?;
+    for (line, problem) in &problems {
+        println!("{csv_path:?}:{line}: {problem}");
+    }
+
+    let mut by_genre: BTreeMap<&PageName, Vec<MixEntry>> = BTreeMap::new();
+    for (page, entry) in entries {
+        by_genre.entry(page).or_default().push(entry);
+    }
+    let (mut added, mut existing, mut genres) = (0, 0, 0);
+    for (page, entries) in by_genre {
+        let path = mixes_path.join(page.sanitize());
+        let contents = std::fs::read_to_string(&path).unwrap_or_default();
+        let (contents, new) = add_mixes(&contents, entries)
+            .with_context(|| format!("Failed to add mixes to {path:?}"))?;
+        existing += new.1;
+        if new.0 > 0 {
+            std::fs::write(&path, contents).with_context(|| format!("Failed to write {path:?}"))?;
+            added += new.0;
+            genres += 1;
+        }
+    }
+    println!(
+        "Imported {added} mixes into {genres} genres' files; {existing} were already there, and {} rows were skipped",
+        problems.len()
+    );
+    Ok(())
+}
+
+/// Each genre's mixes file in `mixes_path`, in the TOML format (whichever format it's in).
+fn read_mixes_files(mixes_path: &Path) -> anyhow::Result<BTreeMap<PageName, MixesFile>> {
+    let mut files = BTreeMap::new();
+    for entry in std::fs::read_dir(mixes_path)? {
+        let path = entry?.path();
+        if !path.is_file() {
+            continue;
+        }
+        let contents = std::fs::read_to_string(&path)?;
+        let file = if MixesFile::is_toml(&contents) {
+            MixesFile::parse(&contents).with_context(|| format!("Failed to parse {path:?}"))?
+        } else {
+            MixesFile::from_plain_text(&contents).0
+        };
+        let genre = PageName::unsanitize(&path.file_name().unwrap().to_string_lossy());
+        files.insert(genre, file);
+    }
+    Ok(files)
+}
+
+/// Finds genres by what a spreadsheet might call them.
+struct GenreLookup<'a> {
+    by_page: BTreeMap<String, &'a PageName>,
+    /// By lowercased page name or genre name; a name shared by several genres has them all.
+    by_lowercase: BTreeMap<String, BTreeSet<&'a PageName>>,
+}
+
+impl<'a> GenreLookup<'a> {
+    /// A lookup of genres by their pages and names.
+    fn new(genres: impl IntoIterator<Item = (&'a PageName, &'a str)>) -> Self {
+        let mut lookup = Self {
+            by_page: BTreeMap::new(),
+            by_lowercase: BTreeMap::new(),
+        };
+        for (page, name) in genres {
+            lookup.by_page.insert(page.to_string(), page);
+            for name in [page.to_string(), name.to_string()] {
+                lookup
+                    .by_lowercase
+                    .entry(name.to_lowercase())
+                    .or_default()
+                    .insert(page);
+            }
+        }
+        lookup
+    }
+
+    /// The genre called `name`, or why there isn't one.
+    fn find(&self, name: &str) -> Result<&'a PageName, String> {
+        if let Some(page) = self.by_page.get(name) {
+            return Ok(page);
+        }
+        match self.by_lowercase.get(&name.to_lowercase()) {
+            Some(pages) if pages.len() == 1 => Ok(pages.first().unwrap()),
+            Some(pages) => Err(format!(
+                "genre {name:?} could be any of {}",
+                pages
+                    .iter()
+                    .map(|page| format!("{:?}", page.to_string()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+            None => Err(format!("no genre is called {name:?}")),
+        }
+    }
+}
+
+/// The rows of an imported CSV.
+struct Rows<'a> {
+    /// The mixes, with their genres.
+    entries: Vec<(&'a PageName, MixEntry)>,
+    /// The (1-based) lines of the rows that couldn't be read, with why.
+    problems: Vec<(usize, String)>,
+}
+
+/// Read the mixes in a CSV.
+fn parse_rows<'a>(csv: &str, genres: &GenreLookup<'a>) -> anyhow::Result<Rows<'a>> {
+    let mut rows = parse_csv(csv)?.into_iter();
+    let Some((_, header)) = rows.next() else {
+        anyhow::bail!("The CSV is empty");
+    };
+    let column = |name: &str| header.iter().position(|column| column.trim() == name);
+    let (Some(genre_column), Some(url_column)) = (column("genre"), column("url")) else {
+        anyhow::bail!("The CSV needs `genre` and `url` columns");
+    };
+    let optional_columns = [
+        column("note"),
+        column("title"),
+        column("duration"),
+        column("submitter"),
+        column("added"),
+    ];
+
+    let mut entries = vec![];
+    let mut problems = vec![];
+    for (line, row) in rows {
+        if row.iter().all(|field| field.trim().is_empty()) {
+            continue;
+        }
+        let field = |column: Option<usize>| {
+            column
+                .and_then(|column| row.get(column))
+                .map(|field| field.trim())
+                .filter(|field| !field.is_empty())
+        };
+        let [note, title, duration, submitter, added] = optional_columns.map(field);
+        let entry = (|| {
+            let genre = field(Some(genre_column)).ok_or("no genre")?;
+            let page = genres.find(genre)?;
+            let url = field(Some(url_column)).ok_or("no URL")?;
+            let mix = GenreMix::from_url(url, None, Default::default())
+                .filter(|mix| matches!(mix, GenreMix::External { .. }) || is_youtube_url(url))
+                .ok_or_else(|| {
+                    format!("{url:?} isn't a YouTube, Bandcamp, SoundCloud or Mixcloud mix")
+                })?;
+            let duration = duration
+                .map(|duration| {
+                    duration
+                        .parse()
+                        .map_err(|_| format!("duration {duration:?} isn't a number of seconds"))
+                })
+                .transpose()?;
+            let added = added
+                .map(|added| match added.parse::<toml::value::Datetime>() {
+                    Ok(date) if date.time.is_none() && date.date.is_some() => Ok(date),
+                    _ => Err(format!("added {added:?} isn't a YYYY-MM-DD date")),
+                })
+                .transpose()?;
+            Ok::<_, String>((
+                page,
+                MixEntry {
+                    url: mix.url(),
+                    note: note.map(str::to_string),
+                    title: title.map(str::to_string),
+                    duration,
+                    submitter: submitter.map(str::to_string),
+                    added,
+                    dead: None,
+                },
+            ))
+        })();
+        match entry {
+            Ok(entry) => entries.push(entry),
+            Err(problem) => problems.push((line, problem)),
+        }
+    }
+    Ok(Rows { entries, problems })
+}
+
+/// Whether `url` is on YouTube.
+fn is_youtube_url(url: &str) -> bool {
+    let Some(rest) = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+    else {
+        return false;
+    };
+    let host = rest.split(['/', '?']).next().unwrap_or_default();
+    matches!(
+        host,
+        "youtube.com" | "www.youtube.com" | "m.youtube.com" | "music.youtube.com" | "youtu.be"
+    )
+}
+
+/// Add `entries` to a genre's mixes file (which may be empty, or in the plain text format, in
+/// which case it's converted to TOML), leaving out the mixes it already has. Returns the new
+/// contents, and how many mixes were added and left out.
+fn add_mixes(contents: &str, entries: Vec<MixEntry>) -> anyhow::Result<(String, (usize, usize))> {
+    let contents = if contents.trim().is_empty() || MixesFile::is_toml(contents) {
+        contents.to_string()
+    } else {
+        let (file, comments) = MixesFile::from_plain_text(contents);
+        file.to_toml(&comments)?
+    };
+    // Edited in place, to keep the file's comments and layout
+    let mut document: toml_edit::DocumentMut = contents.parse()?;
+    let mut urls: BTreeSet<String> = MixesFile::parse(&contents)?
+        .mixes
+        .iter()
+        .filter_map(|entry| GenreMix::from_url(&entry.url, None, Default::default()))
+        .map(|mix| mix.url())
+        .collect();
+
+    let (mut added, mut existing) = (0, 0);
+    for entry in entries {
+        if !urls.insert(entry.url.clone()) {
+            existing += 1;
+            continue;
+        }
+        let table = toml_edit::ser::to_document(&entry)?.as_table().clone();
+        document
+            .entry("mix")
+            .or_insert_with(|| toml_edit::ArrayOfTables::new().into())
+            .as_array_of_tables_mut()
+            .context("`mix` isn't an array of tables")?
+            .push(table);
+        added += 1;
+    }
+    if added > 0 {
+        // The genre has a mix now
+        document.remove("help");
+    }
+    Ok((document.to_string(), (added, existing)))
+}
+
+/// The rows of a CSV (as RFC 4180 has it, with quoted fields), with the lines they start on.
+fn parse_csv(csv: &str) -> anyhow::Result<Vec<(usize, Vec<String>)>> {
+    let mut rows = vec![];
+    let mut row = vec![];
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let (mut line, mut row_line) = (1, 1);
+    let mut chars = csv.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            ('"', true) => in_quotes = false,
+            ('"', false) if field.is_empty() => in_quotes = true,
+            (',', false) => row.push(std::mem::take(&mut field)),
+            ('\r', false) if chars.peek() == Some(&'\n') => {}
+            ('\n', false) => {
+                row.push(std::mem::take(&mut field));
+                rows.push((row_line, std::mem::take(&mut row)));
+                line += 1;
+                row_line = line;
+            }
+            (c, _) => {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+        }
+    }
+    anyhow::ensure!(!in_quotes, "The CSV ends inside a quoted field");
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push((row_line, row));
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::GenreMixes;
+
+    #[test]
+    fn csv_fields_can_be_quoted() {
+        assert_eq!(
+            parse_csv("a,\"b, \"\"c\"\"\"\r\n\"multi\nline\",d\n").unwrap(),
+            vec![
+                (1, vec!["a".to_string(), "b, \"c\"".to_string()]),
+                (2, vec!["multi\nline".to_string(), "d".to_string()]),
+            ]
+        );
+        assert!(parse_csv("\"unterminated").is_err());
+    }
+
+    #[test]
+    fn rows_are_matched_to_genres_and_validated() {
+        let genres = [
+            (PageName::new("Techno", None), "Techno"),
+            (PageName::new("Acid house", None), "Acid house"),
+            (PageName::new("Jungle music", None), "Jungle"),
+        ];
+        let lookup = GenreLookup::new(genres.iter().map(|(page, name)| (page, *name)));
+        let csv = "url,genre,duration,added,comments\n\
+            https://www.youtube.com/watch?v=abc&t=10,Techno,3600,2025-01-31,great\n\
+            https://youtu.be/def,acid house,,,\n\
+            https://www.youtube.com/playlist?list=PL1,jungle,,,\n\
+            https://www.youtube.com/watch?v=ghi,Gabber,,,\n\
+            https://example.com/watch?v=jkl,Techno,,,\n\
+            https://youtu.be/mno,Techno,1:00:00,,\n\
+            https://youtu.be/pqr,Techno,,31/01/2025,\n\
+            ,,,,\n";
+        let Rows { entries, problems } = parse_rows(csv, &lookup).unwrap();
+        assert_eq!(
+            entries
+                .iter()
+                .map(|(page, entry)| (page.to_string(), entry.url.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("Techno".to_string(), "https://www.youtube.com/watch?v=abc"),
+                (
+                    "Acid house".to_string(),
+                    "https://www.youtube.com/watch?v=def"
+                ),
+                (
+                    "Jungle music".to_string(),
+                    "https://www.youtube.com/playlist?list=PL1"
+                ),
+            ]
+        );
+        assert_eq!(entries[0].1.duration, Some(3600));
+        assert_eq!(
+            entries[0].1.added.map(|added| added.to_string()).as_deref(),
+            Some("2025-01-31")
+        );
+        assert_eq!(
+            problems.iter().map(|(line, _)| *line).collect::<Vec<_>>(),
+            vec![5, 6, 7, 8]
+        );
+    }
+
+    #[test]
+    fn imported_mixes_are_added_to_existing_files() {
+        let entry = |url: &str| MixEntry {
+            url: url.to_string(),
+            submitter: Some("someone".to_string()),
+            ..Default::default()
+        };
+        let (contents, counts) = add_mixes(
+            "https://www.youtube.com/watch?v=abc # The classics\n",
+            vec![
+                entry("https://www.youtube.com/watch?v=abc"),
+                entry("https://www.youtube.com/watch?v=def"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(counts, (1, 1));
+        let GenreMixes::Mixes(mixes) = GenreMixes::parse(&contents).unwrap() else {
+            panic!("expected mixes");
+        };
+        assert_eq!(
+            mixes.iter().map(GenreMix::url).collect::<Vec<_>>(),
+            vec![
+                "https://www.youtube.com/watch?v=abc",
+                "https://www.youtube.com/watch?v=def"
+            ]
+        );
+
+        let (contents, counts) =
+            add_mixes("help = \"\"\n", vec![entry("https://youtu.be/abc")]).unwrap();
+        assert_eq!(counts, (1, 0));
+        assert!(matches!(
+            GenreMixes::parse(&contents).unwrap(),
+            GenreMixes::Mixes(mixes) if mixes.len() == 1
+        ));
+    }
+}
@@ -0,0 +1,170 @@
+//! The genres that need help finding a mix (`help` in their mixes files), reported during
+//! output as `mixes_help.json` and `mixes_help.md`, so that they can be posted as a call for
+//! contributions.
+use std::{fmt::Write as _, path::Path};
+
+use serde::Serialize;
+use wikitext_util::{InnerTextConfig, nodes_inner_text_with_config, wikipedia_pwt_configuration};
+
+use crate::{
+    export::WEBSITE_URL,
+    types::{PageDataId, PageName},
+};
+
+/// A genre that needs help finding a mix.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HelpRequest {
+    /// The genre's name.
+    pub genre: String,
+    /// The genre's page.
+    pub page: PageName,
+    /// Why a mix couldn't be found, if it's known.
+    pub reason: Option<String>,
+    /// The first sentence of the genre's description, as plain text.
+    pub description: Option<String>,
+    /// The number of inbound links to the genre's page, which the requests are ordered by.
+    pub links: usize,
+    /// The genre on the website.
+    pub url: String,
+    /// The genre's Wikipedia article.
+    pub wikipedia_url: String,
+}
+
+impl HelpRequest {
+    /// The request for the genre with `id`, with the first sentence of its `wikitext_description`.
+    pub fn new(
+        genre: &str,
+        page: &PageName,
+        id: PageDataId,
+        reason: Option<String>,
+        wikitext_description: Option<&str>,
+        links: usize,
+        wikipedia_domain: &str,
+    ) -> Self {
+        let description = wikitext_description
+            .map(|wikitext| {
+                let mut description = nodes_inner_text_with_config(
+                    &wikipedia_pwt_configuration().parse(wikitext).unwrap().nodes,
+                    InnerTextConfig {
+                        stop_after_br: true,
+                    },
+                );
+                if let Some(dot_idx) = description.find(". ") {
+                    description.truncate(dot_idx + 1);
+                }
+                description.trim().to_string()
+            })
+            .filter(|description| !description.is_empty());
+        Self {
+            genre: genre.to_string(),
+            page: page.clone(),
+            reason,
+            description,
+            links,
+            url: format!("{WEBSITE_URL}#{}", id.0),
+            wikipedia_url: format!("https://{wikipedia_domain}/wiki/{}", page.linksafe()),
+        }
+    }
+}
+
+/// Write the `requests` to `mixes_help.json` and `mixes_help.md` in `diagnostics_path`, the
+/// most linked-to genres first.
+pub fn write(diagnostics_path: &Path, mut requests: Vec<HelpRequest>) -> anyhow::Result<()> {
+    requests.sort_by(|a, b| b.links.cmp(&a.links).then_with(|| a.page.cmp(&b.page)));
+    std::fs::write(
+        diagnostics_path.join("mixes_help.json"),
+        serde_json::to_string_pretty(&requests)?,
+    )?;
+    std::fs::write(diagnostics_path.join("mixes_help.md"), markdown(&requests))?;
+    Ok(())
+}
+
+/// The requests as a Markdown list, introduced as a call for contributions.
+fn markdown(requests: &[HelpRequest]) -> String {
+    let mut out = String::new();
+    writeln!(out, "# Genres that need a mix\n").unwrap();
+    writeln!(
+        out,
+        "These {} genres on [Genres in Space]({WEBSITE_URL}) don't have a mix yet, as none \
+         could be found that represents them well. If you know of one, please suggest it!\n",
+        requests.len()
+    )
+    .unwrap();
+    for request in requests {
+        write!(
+            out,
+            "- **[{}]({})** ([Wikipedia]({}))",
+            escape_markdown(&request.genre),
+            request.url,
+            request.wikipedia_url
+        )
+        .unwrap();
+        if let Some(reason) = &request.reason {
+            write!(out, ": {}", escape_markdown(reason)).unwrap();
+        }
+        out.push('\n');
+        if let Some(description) = &request.description {
+            writeln!(out, "  > {}", escape_markdown(description)).unwrap();
+        }
+    }
+    out
+}
+
+/// Escape the characters that Markdown would otherwise format or link.
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '*' | '_' | '[' | ']' | '`' | '<' | '>' | '#') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_are_listed_most_linked_first() {
+        let request = |genre: &str, id, reason: Option<&str>, links| {
+            HelpRequest::new(
+                genre,
+                &PageName::new(genre, None),
+                PageDataId(id),
+                reason.map(str::to_string),
+                None,
+                links,
+                "en.wikipedia.org",
+            )
+        };
+        let dir = std::env::temp_dir().join(format!("datagen-mixes-help-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        write(
+            &dir,
+            vec![
+                request("Acid jazz", 0, None, 5),
+                request("Rock_n_roll", 1, Some("too *broad*"), 50),
+            ],
+        )
+        .unwrap();
+
+        let markdown = std::fs::read_to_string(dir.join("mixes_help.md")).unwrap();
+        let list: Vec<&str> = markdown.lines().skip(4).collect();
+        assert_eq!(
+            list,
+            vec![
+                "- **[Rock\\_n\\_roll](https://genresin.space/#1)** \
+                 ([Wikipedia](https://en.wikipedia.org/wiki/Rock_n_roll)): too \\*broad\\*",
+                "- **[Acid jazz](https://genresin.space/#0)** \
+                 ([Wikipedia](https://en.wikipedia.org/wiki/Acid_jazz))",
+            ]
+        );
+        let json: Vec<serde_json::Value> =
+            serde_json::from_slice(&std::fs::read(dir.join("mixes_help.json")).unwrap()).unwrap();
+        assert_eq!(json[0]["genre"], "Rock_n_roll");
+        assert_eq!(json[1]["reason"], serde_json::Value::Null);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
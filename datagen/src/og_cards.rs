@@ -0,0 +1,526 @@
+//! A page for each genre, and share images for them (`og_cards = true` in `[output]`), so that
+//! links to genres unfurl with a picture of the genre on social platforms.
+//!
+//! As crawlers and link previews don't see the URL's fragment or run the website's JavaScript,
+//! each genre gets a page (`og/<page>.html`, named after its Wikipedia page so that it keeps its
+//! URL across dumps) that sends visitors on to the genre on the website. These are the genres'
+//! URLs in the sitemap and JSON-LD, and the links to share. Each card shows the genre's name, the
+//! genres it comes from, and its neighbourhood drawn from the layout, and is in its page's
+//! metadata.
+use std::path::Path;
+
+use ab_glyph::{Font as _, FontRef, PxScale, ScaleFont as _};
+use anyhow::Context as _;
+use image::{Rgb, RgbImage};
+
+use crate::{
+    export::{WEBSITE_URL, escape_xml, url_path},
+    frontend_types::{EdgeType, FrontendData, Neighborhood},
+    types::PageName,
+};
+
+/// The font the cards' text is drawn in.
+pub const FONT_PATH: &str = "assets/fonts/DejaVuSerif-Bold.ttf";
+
+/// The directory of the website's public files that the cards and their pages are written to.
+pub const OG_DIRECTORY: &str = "og";
+
+/// The size of the cards, which is what the platforms show at full size.
+const WIDTH: u32 = 1200;
+const HEIGHT: u32 = 630;
+
+/// How many edges out the neighbourhood on a card reaches.
+const HOPS: usize = 1;
+
+/// How many of the genres a genre comes from are named on its card.
+const ORIGINS_SHOWN: usize = 3;
+
+/// The website's colours: its background, text, secondary text and brass accents.
+const BACKGROUND: [u8; 3] = [0x04, 0x06, 0x0f];
+const TEXT: [u8; 3] = [0xe9, 0xe3, 0xd3];
+const SECONDARY_TEXT: [u8; 3] = [0xa6, 0xb2, 0xc9];
+const BRASS: [u8; 3] = [0xd9, 0xc0, 0x8a];
+
+/// The path of the page for the genre on Wikipedia page `page` (without its extension), relative
+/// to the website: `og/Drum_&_bass` for "Drum & bass".
+pub fn page_path(page: &PageName) -> String {
+    format!("{OG_DIRECTORY}/{}", page.linksafe().sanitize())
+}
+
+/// The URL of the page for the genre on Wikipedia page `page`.
+pub fn page_url(page: &PageName) -> String {
+    format!("{WEBSITE_URL}{}.html", url_path(&page_path(page)))
+}
+
+/// Write a page (`og/<page>.html`) for each genre in `graph` to `output_path`, where `pages` are
+/// the genres' Wikipedia pages by node ID. With a `font_path`, also draw a card for each
+/// (`og/<page>.png`) for its page to share, once the layout and colours are known.
+pub fn write(
+    start: std::time::Instant,
+    graph: &FrontendData,
+    pages: &[PageName],
+    font_path: Option<&Path>,
+    output_path: &Path,
+) -> anyhow::Result<()> {
+    let font_data = font_path
+        .map(|font_path| {
+            std::fs::read(font_path).with_context(|| format!("Failed to read {font_path:?}"))
+        })
+        .transpose()?;
+    let font = font_data
+        .as_deref()
+        .map(|font_data| FontRef::try_from_slice(font_data).context("Failed to parse the font"))
+        .transpose()?;
+
+    std::fs::create_dir_all(output_path.join(OG_DIRECTORY))?;
+    for (index, neighborhood) in graph.neighborhoods(HOPS).enumerate() {
+        let origins = origins(graph, index);
+        let path = page_path(&pages[index]);
+        if let Some(font) = &font {
+            render(font, graph, &neighborhood, &origins)
+                .save(output_path.join(format!("{path}.png")))
+                .with_context(|| format!("Failed to write the card for genre {index}"))?;
+        }
+        std::fs::write(
+            output_path.join(format!("{path}.html")),
+            share_page(graph, index, &pages[index], &origins, font.is_some()),
+        )?;
+    }
+
+    println!(
+        "{:.2}s: saved pages{} for {} genres",
+        start.elapsed().as_secs_f32(),
+        if font.is_some() {
+            " and share cards"
+        } else {
+            ""
+        },
+        graph.nodes.len()
+    );
+    Ok(())
+}
+
+/// The names of the most-linked genres that genre `index` comes from (those with
+/// non-`Related` edges to it).
+fn origins(graph: &FrontendData, index: usize) -> Vec<&str> {
+    let mut origins: Vec<usize> = graph
+        .edges
+        .iter()
+        .filter(|edge| edge.target.0 == index && edge.ty != EdgeType::Related)
+        .map(|edge| edge.source.0)
+        .collect();
+    origins.sort_by_key(|&origin| std::cmp::Reverse(graph.nodes[origin].links));
+    origins.dedup();
+    origins
+        .into_iter()
+        .take(ORIGINS_SHOWN)
+        .map(|origin| graph.nodes[origin].label.0.as_str())
+        .collect()
+}
+
+/// The page for genre `index`, which has its card in its metadata (if it has one) and redirects
+/// to the genre on the website.
+fn share_page(
+    graph: &FrontendData,
+    index: usize,
+    page: &PageName,
+    origins: &[&str],
+    has_card: bool,
+) -> String {
+    let label = escape_xml(&graph.nodes[index].label.0);
+    let description = if origins.is_empty() {
+        format!(
+            "{} on genres in space, a graph of every music genre on Wikipedia.",
+            graph.nodes[index].label.0
+        )
+    } else {
+        format!(
+            "{}, from {}, on genres in space, a graph of every music genre on Wikipedia.",
+            graph.nodes[index].label.0,
+            origins.join(", ")
+        )
+    };
+    let description = escape_xml(&description);
+    let genre_url = format!("{WEBSITE_URL}#{index}");
+    let page_url = escape_xml(&page_url(page));
+    let card = if has_card {
+        format!(
+            r#"
+    <meta property="og:image" content="{}.png" />
+    <meta property="og:image:width" content="{WIDTH}" />
+    <meta property="og:image:height" content="{HEIGHT}" />
+    <meta name="twitter:card" content="summary_large_image" />"#,
+            page_url.strip_suffix(".html").unwrap_or(&page_url)
+        )
+    } else {
+        String::new()
+    };
+    format!(
+        r#"<!doctype html>
+<html lang="en">
+  <head>
+    <meta charset="UTF-8" />
+    <title>{label} – genres in space</title>
+    <meta name="description" content="{description}" />
+    <meta property="og:type" content="website" />
+    <meta property="og:site_name" content="genres in space" />
+    <meta property="og:title" content="{label}" />
+    <meta property="og:description" content="{description}" />
+    <meta property="og:url" content="{page_url}" />{card}
+    <link rel="canonical" href="{page_url}" />
+    <meta http-equiv="refresh" content="0; url={genre_url}" />
+  </head>
+  <body>
+    <a href="{genre_url}">{label}</a>
+  </body>
+</html>
+"#
+    )
+}
+
+/// Draw the card for the node at the centre of `neighborhood`: its name and origins on the left,
+/// and its neighbourhood on the right.
+fn render(
+    font: &FontRef,
+    graph: &FrontendData,
+    neighborhood: &Neighborhood,
+    origins: &[&str],
+) -> RgbImage {
+    let mut image = RgbImage::from_pixel(WIDTH, HEIGHT, Rgb(BACKGROUND));
+
+    // The neighbourhood, scaled to fit the right half of the card
+    let (left, top, size) = (WIDTH as f32 / 2.0, 40.0, HEIGHT as f32 - 80.0);
+    let positions: Vec<(f32, f32)> = neighborhood
+        .nodes
+        .iter()
+        .map(|node| (node.x as f32, node.y as f32))
+        .collect();
+    let (min_x, max_x, min_y, max_y) = positions.iter().fold(
+        (f32::MAX, f32::MIN, f32::MAX, f32::MIN),
+        |(min_x, max_x, min_y, max_y), &(x, y)| {
+            (min_x.min(x), max_x.max(x), min_y.min(y), max_y.max(y))
+        },
+    );
+    let scale = size / (max_x - min_x).max(max_y - min_y).max(f32::EPSILON);
+    let project = |(x, y): (f32, f32)| {
+        (
+            left + size / 2.0 + (x - (min_x + max_x) / 2.0) * scale,
+            top + size / 2.0 + (y - (min_y + max_y) / 2.0) * scale,
+        )
+    };
+    let position_of = |id: usize| {
+        neighborhood
+            .ids
+            .iter()
+            .position(|other| other.0 == id)
+            .map(|index| project(positions[index]))
+    };
+    let colours: Vec<[u8; 3]> = neighborhood
+        .nodes
+        .iter()
+        .map(|node| {
+            let degree = (node.in_degree + node.out_degree) as f32;
+            let saturation = (degree / graph.max_degree.max(1) as f32) * 0.8 + 0.2;
+            hsl_to_rgb(node.hue as f32, saturation, 0.6)
+        })
+        .collect();
+    for edge in &neighborhood.edges {
+        if let (Some(from), Some(to)) = (position_of(edge.source.0), position_of(edge.target.0)) {
+            draw_line(&mut image, from, to, SECONDARY_TEXT, 0.35);
+        }
+    }
+    // The centre is drawn last, so that it's on top
+    for (index, &position) in positions.iter().enumerate().rev() {
+        let radius = if index == 0 { 16.0 } else { 7.0 };
+        draw_circle(&mut image, project(position), radius, colours[index]);
+    }
+
+    // The name, as large as fits, then the origins beneath it
+    let text_left = 60.0;
+    let text_width = WIDTH as f32 / 2.0 - text_left - 40.0;
+    let label = &neighborhood.nodes[0].label.0;
+    let (label, label_size) = fit_text(font, label, text_width, &[80.0, 68.0, 56.0, 48.0]);
+    draw_text(
+        &mut image,
+        font,
+        &label,
+        label_size,
+        (text_left, 220.0),
+        TEXT,
+    );
+    if !origins.is_empty() {
+        let (origins, origins_size) = fit_text(
+            font,
+            &format!("from {}", origins.join(", ")),
+            text_width,
+            &[32.0, 26.0],
+        );
+        draw_text(
+            &mut image,
+            font,
+            &origins,
+            origins_size,
+            (text_left, 230.0 + label_size),
+            SECONDARY_TEXT,
+        );
+    }
+    draw_text(
+        &mut image,
+        font,
+        "genresin.space",
+        30.0,
+        (text_left, HEIGHT as f32 - 90.0),
+        BRASS,
+    );
+
+    image
+}
+
+/// The largest of `sizes` that `text` fits in `width` at, or `text` shortened with an ellipsis
+/// to fit at the smallest.
+fn fit_text(font: &FontRef, text: &str, width: f32, sizes: &[f32]) -> (String, f32) {
+    let smallest = sizes.last().copied().unwrap_or(32.0);
+    for &size in sizes {
+        if text_width(font, text, size) <= width {
+            return (text.to_string(), size);
+        }
+    }
+    let mut shortened: String = text.to_string();
+    while !shortened.is_empty() && text_width(font, &format!("{shortened}…"), smallest) > width {
+        shortened.pop();
+    }
+    (format!("{}…", shortened.trim_end()), smallest)
+}
+
+/// The width of `text` at `size` pixels.
+fn text_width(font: &FontRef, text: &str, size: f32) -> f32 {
+    let font = font.as_scaled(PxScale::from(size));
+    let mut width = 0.0;
+    let mut previous = None;
+    for c in text.chars() {
+        let glyph = font.glyph_id(c);
+        if let Some(previous) = previous {
+            width += font.kern(previous, glyph);
+        }
+        width += font.h_advance(glyph);
+        previous = Some(glyph);
+    }
+    width
+}
+
+/// Draw `text` at `size` pixels, with the top of its line at `(x, y)`.
+fn draw_text(
+    image: &mut RgbImage,
+    font: &FontRef,
+    text: &str,
+    size: f32,
+    (x, y): (f32, f32),
+    colour: [u8; 3],
+) {
+    let scale = PxScale::from(size);
+    let scaled = font.as_scaled(scale);
+    let baseline = y + scaled.ascent();
+    let mut caret = x;
+    let mut previous = None;
+    for c in text.chars() {
+        let id = scaled.glyph_id(c);
+        if let Some(previous) = previous {
+            caret += scaled.kern(previous, id);
+        }
+        let glyph = id.with_scale_and_position(scale, ab_glyph::point(caret, baseline));
+        caret += scaled.h_advance(id);
+        previous = Some(id);
+        if let Some(outlined) = font.outline_glyph(glyph) {
+            let bounds = outlined.px_bounds();
+            outlined.draw(|gx, gy, coverage| {
+                blend(
+                    image,
+                    bounds.min.x as i64 + gx as i64,
+                    bounds.min.y as i64 + gy as i64,
+                    colour,
+                    coverage,
+                );
+            });
+        }
+    }
+}
+
+/// Draw an antialiased line from `from` to `to`.
+fn draw_line(
+    image: &mut RgbImage,
+    from: (f32, f32),
+    to: (f32, f32),
+    colour: [u8; 3],
+    opacity: f32,
+) {
+    let length = ((to.0 - from.0).powi(2) + (to.1 - from.1).powi(2)).sqrt();
+    let steps = (length * 2.0).ceil().max(1.0) as usize;
+    // Each step covers half a pixel, spread over the four pixels around it
+    for step in 0..=steps {
+        let t = step as f32 / steps as f32;
+        let x = from.0 + (to.0 - from.0) * t;
+        let y = from.1 + (to.1 - from.1) * t;
+        let (fx, fy) = (x.fract(), y.fract());
+        for (dx, dy, weight) in [
+            (0, 0, (1.0 - fx) * (1.0 - fy)),
+            (1, 0, fx * (1.0 - fy)),
+            (0, 1, (1.0 - fx) * fy),
+            (1, 1, fx * fy),
+        ] {
+            blend(
+                image,
+                x as i64 + dx,
+                y as i64 + dy,
+                colour,
+                opacity * weight * 0.5,
+            );
+        }
+    }
+}
+
+/// Draw an antialiased filled circle.
+fn draw_circle(image: &mut RgbImage, (cx, cy): (f32, f32), radius: f32, colour: [u8; 3]) {
+    let reach = radius.ceil() as i64 + 1;
+    for y in cy as i64 - reach..=cy as i64 + reach {
+        for x in cx as i64 - reach..=cx as i64 + reach {
+            let distance = ((x as f32 + 0.5 - cx).powi(2) + (y as f32 + 0.5 - cy).powi(2)).sqrt();
+            blend(
+                image,
+                x,
+                y,
+                colour,
+                (radius + 0.5 - distance).clamp(0.0, 1.0),
+            );
+        }
+    }
+}
+
+/// Blend `colour` into the pixel at `(x, y)` by `alpha`, if it's on the image.
+fn blend(image: &mut RgbImage, x: i64, y: i64, colour: [u8; 3], alpha: f32) {
+    if x < 0 || y < 0 || x >= image.width() as i64 || y >= image.height() as i64 || alpha <= 0.0 {
+        return;
+    }
+    let alpha = alpha.min(1.0);
+    let pixel = image.get_pixel_mut(x as u32, y as u32);
+    for (channel, &target) in pixel.0.iter_mut().zip(&colour) {
+        *channel = (*channel as f32 + (target as f32 - *channel as f32) * alpha).round() as u8;
+    }
+}
+
+/// Convert a colour from HSL (hue in degrees, saturation and lightness from 0 to 1) to RGB.
+fn hsl_to_rgb(hue: f32, saturation: f32, lightness: f32) -> [u8; 3] {
+    let saturation = saturation.clamp(0.0, 1.0);
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let hue = hue.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+    let (r, g, b) = match hue as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = lightness - chroma / 2.0;
+    [r, g, b].map(|c| ((c + m) * 255.0).round() as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+    use crate::{
+        frontend_types::{EdgeData, NodeData},
+        types::{GenreName, PageDataId},
+    };
+
+    #[test]
+    fn writes_a_card_and_share_page_per_genre() {
+        let node = |label: &str, x: f64, links: usize| NodeData {
+            page_title: None,
+            label: GenreName(label.to_string()),
+            aliases: vec![],
+            links,
+            popularity: 0,
+            x,
+            y: 0.0,
+            z: None,
+            hue: 200.0,
+            community: 0,
+            in_degree: 1,
+            out_degree: 1,
+            pagerank: 0.0,
+            betweenness: 0.0,
+            depth: None,
+            wikidata_qid: None,
+            musicbrainz_genre_id: None,
+        };
+        let edge = |source: usize, target: usize, ty: EdgeType| EdgeData {
+            source: PageDataId(source),
+            target: PageDataId(target),
+            ty,
+            corroborated: false,
+        };
+        let graph = FrontendData {
+            wikipedia_domain: "en.wikipedia.org".to_string(),
+            wikipedia_db_name: "enwiki".to_string(),
+            dump_date: "2026-02-01".to_string(),
+            nodes: vec![
+                node("Drum & bass", 0.0, 10),
+                node("Jungle", 1.0, 20),
+                node("Breakbeat", 2.0, 30),
+                node("Liquid funk", 3.0, 5),
+            ],
+            edges: BTreeSet::from_iter([
+                edge(1, 0, EdgeType::Derivative),
+                edge(2, 0, EdgeType::Derivative),
+                edge(0, 3, EdgeType::Subgenre),
+                edge(3, 0, EdgeType::Related),
+            ]),
+            max_degree: 3,
+            communities: vec![],
+            sharded_files: false,
+            layout_seed: None,
+        };
+        assert_eq!(origins(&graph, 0), ["Breakbeat", "Jungle"]);
+
+        let dir = std::env::temp_dir().join(format!("datagen-og-{}", std::process::id()));
+        let font_path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("..")
+            .join(FONT_PATH);
+        let pages: Vec<PageName> = graph
+            .nodes
+            .iter()
+            .map(|node| PageName::new(node.label.0.clone(), None))
+            .collect();
+        write(
+            std::time::Instant::now(),
+            &graph,
+            &pages,
+            Some(&font_path),
+            &dir,
+        )
+        .unwrap();
+
+        assert_eq!(
+            image::image_dimensions(dir.join("og/Drum_&_bass.png")).unwrap(),
+            (WIDTH, HEIGHT)
+        );
+        let page = std::fs::read_to_string(dir.join("og/Drum_&_bass.html")).unwrap();
+        assert!(page.contains(r#"<meta property="og:title" content="Drum &amp; bass" />"#));
+        assert!(page.contains(r#"content="https://genresin.space/og/Drum_%26_bass.png""#));
+        assert!(page.contains(
+            r#"<link rel="canonical" href="https://genresin.space/og/Drum_%26_bass.html" />"#
+        ));
+        assert!(page.contains(r#"content="0; url=https://genresin.space/#0""#));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        // Without cards, the pages are still written for the sitemap to list
+        write(std::time::Instant::now(), &graph, &pages, None, &dir).unwrap();
+        let page = std::fs::read_to_string(dir.join("og/Jungle.html")).unwrap();
+        assert!(!page.contains("og:image"));
+        assert!(!dir.join("og/Jungle.png").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,4 +1,4 @@
-//! Produces the data.json file for the frontend.
+//! Produces the graph data file (`data.json`) and per-genre/artist files for the frontend.
 use std::{
     collections::{BTreeMap, BTreeSet},
     path::Path,
@@ -8,49 +8,257 @@ use anyhow::Context as _;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    data_patches, extract,
-    frontend_types::{EdgeData, EdgeType, FrontendData, NodeData},
-    genre_top_artists, links, process,
-    types::{GenreMixes, GenreName, PageDataId, PageName},
+    attribution, data_patches, dataset_stats, export, extract,
+    frontend_types::{
+        ChunkManifest, ChunkedMeta, ClusterChunk, ClusterChunkInfo, DataFormat, DataIndex,
+        EdgeData, EdgeType, FrontendData, NEIGHBORHOOD_DIRECTORY, Neighborhood, NodeData,
+        NodeSkeleton, POPULARITY_TIERS, is_zero, to_json,
+    },
+    genre_images, genre_top_artists, langlinks, links, mix_embeds, mixes_help, og_cards,
+    patch_report, process, prose_mentions,
+    types::{
+        AliasSource, GenreMix, GenreMixes, GenreName, LayoutConfig, MixProvider, OutputConfig,
+        PageDataId, PageName, TopArtistsConfig,
+    },
+    wikidata,
 };
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Genre data from the genre files.
+#[derive(Debug, Serialize, Deserialize, ts_rs::TS, schemars::JsonSchema)]
 struct GenreFileData {
+    /// The genre's Wikipedia wikitext description.
     description: Option<String>,
+    /// The description rendered to sanitized HTML, one string per paragraph, if it was written
+    /// with `description_html` and has nothing that only the website can render.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    description_html: Option<Vec<String>>,
+    /// The genre's last revision date (ISO 8601).
+    #[ts(type = "string")]
+    #[schemars(with = "String")]
     last_revision_date: jiff::Timestamp,
+    /// The genre's mixes.
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
     mixes: Option<GenreMixes>,
+    /// The title, channel and thumbnail of each of the genre's mixes, by video or playlist ID,
+    /// if they were looked up with `mix_embeds`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    #[ts(as = "Option<BTreeMap<String, mix_embeds::MixEmbed>>", optional)]
+    mix_embeds: BTreeMap<String, mix_embeds::MixEmbed>,
+    /// A thumbnail of the genre's infobox image, if it was written with `genre_images`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    image: Option<genre_images::GenreImage>,
+    /// The genre's top artists, as page names.
+    #[ts(as = "Vec<String>")]
+    #[schemars(with = "Vec<String>")]
     top_artists: Vec<PageName>,
+    /// The scores behind `top_artists`, in the same order.
+    #[serde(default)]
+    top_artist_scores: Vec<genre_top_artists::ArtistScore>,
+    /// The top artists among those active in each decade (e.g. 1990 for the 1990s).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    #[ts(as = "Option<BTreeMap<i32, Vec<String>>>", optional)]
+    #[schemars(with = "BTreeMap<i32, Vec<String>>")]
+    top_artists_by_decade: BTreeMap<i32, Vec<PageName>>,
+    /// The genres this one derives from, by generation: its stylistic origins and parent
+    /// genres, then theirs, and so on. Each genre appears once, in its nearest generation.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[ts(as = "Option<Vec<Vec<PageDataId>>>", optional)]
+    ancestors: Vec<Vec<PageDataId>>,
+    /// The genres that derive from this one (its derivatives, subgenres and fusion genres, then
+    /// theirs, and so on), by generation.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[ts(as = "Option<Vec<Vec<PageDataId>>>", optional)]
+    descendants: Vec<Vec<PageDataId>>,
+    /// The countries the genre originated in.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[ts(as = "Option<Vec<String>>", optional)]
+    origin_countries: Vec<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Artist data from the artist files.
+#[derive(Debug, Serialize, Deserialize, ts_rs::TS, schemars::JsonSchema)]
 struct ArtistFileData {
+    /// The artist's name.
     name: String,
+    /// The artist's description (wikitext).
     description: Option<String>,
+    /// The description rendered to sanitized HTML, as in the genre files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    description_html: Option<Vec<String>>,
+    /// The artist's last revision date (ISO 8601).
+    #[ts(type = "string")]
+    #[schemars(with = "String")]
     last_revision_date: jiff::Timestamp,
+    /// The artist's genres, as page IDs.
     genres: BTreeSet<PageDataId>,
+    /// Inbound Wikipedia link count for the artist's page and its redirects.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    #[ts(as = "Option<usize>", optional)]
+    links: usize,
+    /// The Wikidata item ID of the artist's page, if it has one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    wikidata_qid: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Links from genre infoboxes and "See also" sections that didn't resolve to a page,
+/// and how many times each appears.
+#[derive(Debug, Default, Serialize)]
+struct UnresolvedLinks {
+    /// Links to other wikis or other languages' Wikipedias, which are skipped.
+    interwiki: BTreeMap<String, usize>,
+    /// Links to pages that aren't tracked (or don't exist).
+    unresolved: BTreeMap<String, usize>,
+}
+
+/// Each genre's position in the layout, saved so that the next dump's layout can be
+/// warm-started from it.
+type Layout = BTreeMap<PageName, [f64; 2]>;
+
+/// The name of the file the layout is saved to, in the diagnostics output.
+pub const LAYOUT_FILE_NAME: &str = "layout.json";
+
+#[derive(Debug, Serialize, Deserialize, ts_rs::TS, schemars::JsonSchema)]
 #[serde(transparent)]
 /// Maps link targets to page IDs.
 struct LinksToPageIds(BTreeMap<String, PageDataId>);
 
-/// Given processed genres, produce a graph and save it to `data.json` to be rendered by the website.
-#[allow(clippy::too_many_arguments)]
+#[derive(Debug, Default, Serialize, Deserialize, ts_rs::TS, schemars::JsonSchema)]
+#[serde(transparent)]
+/// Maps the alternative names of genres to the genres they name. Unlike [`LinksToPageIds`],
+/// this only has names that are known to be names, not every redirect.
+struct Aliases(BTreeMap<String, Vec<AliasTarget>>);
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, ts_rs::TS, schemars::JsonSchema)]
+/// A genre that an alternative name refers to.
+struct AliasTarget {
+    /// The genre's page ID.
+    id: PageDataId,
+    /// Where the name comes from.
+    source: AliasSource,
+}
+
+/// A key of `decades.json`: the decade a genre originated in (e.g. `1970` for the 1970s), or
+/// `"unknown"` if it couldn't be parsed. Unknown sorts last.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Decade {
+    Known(i32),
+    Unknown,
+}
+impl Serialize for Decade {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Decade::Known(decade) => serializer.serialize_i32(*decade),
+            Decade::Unknown => serializer.serialize_str("unknown"),
+        }
+    }
+}
+
+/// What the output is produced from: the results of the earlier stages, and the settings for
+/// the top artists and layout.
+#[derive(Clone, Copy)]
+pub struct Inputs<'a> {
+    /// The dump the data is from.
+    pub dump_meta: &'a extract::DumpMeta,
+    /// The directory of mix files.
+    pub mixes_path: &'a Path,
+    /// The layout to warm-start from, if there is one.
+    pub previous_layout_path: Option<&'a Path>,
+    /// Take the layout from `previous_layout_path` as it is, instead of laying out the graph.
+    pub skip_layout: bool,
+    /// The pages that links resolve to.
+    pub links_to_articles: &'a links::LinksToArticles,
+    /// The other names of each page.
+    pub page_aliases: &'a links::PageAliases,
+    /// The redirects to genres that are their other names.
+    pub alias_redirects: &'a BTreeMap<PageName, AliasSource>,
+    /// How names are normalized for matching.
+    pub name_normalization: &'a links::NameNormalization,
+    /// The genres each genre's article mentions, if related edges are drawn from them.
+    pub prose_mentions: Option<&'a prose_mentions::ProseMentions>,
+    /// Each page's counterparts in other languages' Wikipedias, if they were loaded.
+    pub langlinks: Option<&'a langlinks::LangLinks>,
+    /// Each page's Wikidata ID, if they were loaded.
+    pub wikidata_ids: Option<&'a wikidata::WikidataIds>,
+    /// The MusicBrainz genres, by Wikidata ID.
+    pub musicbrainz_genres: &'a wikidata::MusicBrainzGenres,
+    /// The genres' thumbnails, by image file name, if they were written.
+    pub genre_images: Option<&'a BTreeMap<String, genre_images::GenreImage>>,
+    /// The number of links to each page.
+    pub inbound_link_counts: &'a BTreeMap<PageName, usize>,
+    /// The processed genres.
+    pub processed_genres: &'a process::ProcessedGenres,
+    /// The processed artists.
+    pub processed_artists: &'a process::ProcessedArtists,
+    /// The patches to apply.
+    pub data_patches: &'a data_patches::DataPatches,
+    /// Each genre's ranked top artists.
+    pub genre_top_artists: &'a genre_top_artists::GenreTopArtists,
+    /// Each artist's genres.
+    pub artist_genres: &'a genre_top_artists::ArtistGenres,
+    /// Which of the ranked artists are shown for each genre.
+    pub top_artists_config: &'a TopArtistsConfig,
+    /// How the graph is laid out.
+    pub layout_config: &'a LayoutConfig,
+}
+
+/// Given processed genres, produce a graph and save it in `config.data_format` (`data.json` by
+/// default) to be rendered by the website.
+///
+/// Reports that aren't for the website are written to `diagnostics_path`, including the layout
+/// (as [`LAYOUT_FILE_NAME`]). With `layout_only`, nothing is written to `output_path`, and it
+/// stops once the layout has been written.
 pub fn produce(
     start: std::time::Instant,
-    dump_meta: &extract::DumpMeta,
-    mixes_path: &Path,
+    inputs: &Inputs,
+    config: &OutputConfig,
     output_path: &Path,
-    links_to_articles: &links::LinksToArticles,
-    page_aliases: &links::PageAliases,
-    inbound_link_counts: &BTreeMap<PageName, usize>,
-    processed_genres: &process::ProcessedGenres,
-    processed_artists: &process::ProcessedArtists,
-    genre_top_artists: &genre_top_artists::GenreTopArtists,
-    artist_genres: &genre_top_artists::ArtistGenres,
+    diagnostics_path: &Path,
+    patch_report: &mut patch_report::PatchReport,
+    layout_only: bool,
 ) -> anyhow::Result<()> {
+    let Inputs {
+        dump_meta,
+        mixes_path,
+        previous_layout_path,
+        skip_layout,
+        links_to_articles,
+        page_aliases,
+        alias_redirects,
+        name_normalization,
+        prose_mentions,
+        langlinks,
+        wikidata_ids,
+        musicbrainz_genres,
+        genre_images,
+        inbound_link_counts,
+        processed_genres,
+        processed_artists,
+        data_patches,
+        genre_top_artists,
+        artist_genres,
+        top_artists_config,
+        layout_config,
+    } = *inputs;
+    let OutputConfig {
+        data_format,
+        lineage_depth,
+        neighborhood_hops,
+        pretty,
+        description_html,
+        all_artists,
+        parquet,
+        mix_embeds: look_up_mix_embeds,
+        og_cards,
+        related_mention_threshold,
+        pagerank_damping,
+        pagerank_iterations,
+        ..
+    } = *config;
     println!(
         "{:.2}s: producing output data",
         start.elapsed().as_secs_f32()
@@ -63,6 +271,9 @@ pub fn produce(
         nodes: vec![],
         edges: BTreeSet::new(),
         max_degree: 0,
+        communities: vec![],
+        sharded_files: true,
+        layout_seed: Some(layout_config.seed),
     };
 
     let mut node_order = processed_genres.0.keys().cloned().collect::<Vec<_>>();
@@ -72,72 +283,214 @@ pub fn produce(
 
     let mut artists_to_copy = BTreeSet::new();
 
-    let genres_path = output_path.join("genres");
-    std::fs::create_dir_all(&genres_path)?;
+    // Genres grouped by the decade they originated in, for the timeline
+    let mut decades: BTreeMap<Decade, Vec<PageDataId>> = BTreeMap::new();
+
+    // Genres grouped by the countries they originated in, for browsing by place
+    let mut countries: BTreeMap<String, Vec<PageDataId>> = BTreeMap::new();
+
+    // The alternative names of genres, for the website's search
+    let mut aliases = Aliases::default();
+
+    // Genres grouped by the navboxes on their pages, as "families" of related genres
+    let mut families: BTreeMap<String, Vec<PageDataId>> = BTreeMap::new();
+
+    // The top artists and mixes of each genre, by ID, for the exports
+    let mut genre_exports: Vec<(Vec<PageName>, Vec<GenreMix>)> = vec![];
+
+    // The genre files, by ID; they're written once the edges are known
+    let mut genre_files: Vec<GenreFileData> = vec![];
+
+    // The genres that need help finding a mix
+    let mut help_requests = vec![];
 
     // First pass: create nodes
     for page in &node_order {
         let processed_genre = &processed_genres.0[page];
         let id = PageDataId(graph.nodes.len());
 
-        let mixes = std::fs::read_to_string(mixes_path.join(PageName::sanitize(page)))
+        // A genre without mixes of its own gets those of the first genre merged into it with some
+        let mix_path = std::iter::once(page)
+            .chain(&processed_genre.merged_pages)
+            .map(|page| mixes_path.join(PageName::sanitize(page)))
+            .find(|path| path.is_file())
+            .unwrap_or_else(|| mixes_path.join(PageName::sanitize(page)));
+        let mixes = std::fs::read_to_string(&mix_path)
             .ok()
-            .map(|f| GenreMixes::parse(&f));
+            .map(|f| GenreMixes::parse(&f))
+            .transpose()
+            .with_context(|| format!("Failed to parse {mix_path:?}"))?;
 
         let page_title = page.to_string();
+        let wikidata_qid = wikidata_ids.and_then(|ids| ids.get(page)).cloned();
 
         let node = NodeData {
             aliases: clean_aliases(
                 &processed_genre.name.0,
                 &page_title,
                 page_aliases.0.get(page),
+                name_normalization,
             ),
             links: page_aliases.aggregated_link_count(page, inbound_link_counts),
+            popularity: 0,
             page_title: (processed_genre.name.0 != page_title).then_some(page_title),
             label: processed_genre.name.clone(),
             x: 0.0,
             y: 0.0,
+            z: None,
             hue: 0.0,
+            community: 0,
+            in_degree: 0,
+            out_degree: 0,
+            pagerank: 0.0,
+            betweenness: 0.0,
+            depth: None,
+            musicbrainz_genre_id: wikidata_qid
+                .as_deref()
+                .and_then(|qid| musicbrainz_genres.get(qid))
+                .map(str::to_string),
+            wikidata_qid,
         };
 
+        if let Some(GenreMixes::Help { help_reason }) = &mixes {
+            help_requests.push(mixes_help::HelpRequest::new(
+                &processed_genre.name.0,
+                page,
+                id,
+                help_reason.clone(),
+                processed_genre.wikitext_description.as_deref(),
+                node.links,
+                &dump_meta.wikipedia_domain,
+            ));
+        }
+
         graph.nodes.push(node);
         page_to_id.insert(page.clone(), id);
+        decades
+            .entry(
+                processed_genre
+                    .origin_decade
+                    .map_or(Decade::Unknown, Decade::Known),
+            )
+            .or_default()
+            .push(id);
+        for (name, source) in genre_aliases(
+            &processed_genre.name.0,
+            &processed_genre.other_names,
+            page_aliases.0.get(page),
+            alias_redirects,
+            name_normalization,
+        ) {
+            let targets = aliases.0.entry(name).or_default();
+            if !targets.iter().any(|target| target.id == id) {
+                targets.push(AliasTarget { id, source });
+            }
+        }
+        for country in &processed_genre.origin_countries {
+            countries.entry(country.clone()).or_default().push(id);
+        }
+        for navbox in &processed_genre.navboxes {
+            families.entry(navbox.clone()).or_default().push(id);
+        }
         let page_without_heading = page.with_opt_heading(None);
         // Add fallback page ID for pages where the main music box is under a heading
         page_to_id.entry(page_without_heading).or_insert(id);
 
-        let top_artists = {
-            let top_artist_pages: Vec<PageName> = genre_top_artists
-                .get(page)
-                .map(|artists| {
-                    artists
-                        .iter()
-                        .map(|(artist, _)| artist.clone())
-                        .take(10)
-                        .collect()
-                })
-                .unwrap_or_default();
+        let (top_artists, top_artist_scores) = {
+            let top_artist_pages: Vec<(PageName, genre_top_artists::ArtistScore)> =
+                genre_top_artists
+                    .get(page)
+                    .map(|artists| {
+                        genre_top_artists::select(artists, top_artists_config, |artist| {
+                            page_aliases.aggregated_link_count(artist, inbound_link_counts)
+                        })
+                    })
+                    .unwrap_or_default();
 
             let mut top_artists = vec![];
-            for artist_page in top_artist_pages {
+            let mut top_artist_scores = vec![];
+            for (artist_page, score) in top_artist_pages {
                 artists_to_copy.insert(artist_page.clone());
                 top_artists.push(artist_page);
+                top_artist_scores.push(score);
             }
-            top_artists
+            (top_artists, top_artist_scores)
         };
 
-        std::fs::write(
-            genres_path.join(format!("{}.json", PageName::sanitize(page))),
-            serde_json::to_string_pretty(&GenreFileData {
-                description: processed_genre.wikitext_description.clone(),
-                last_revision_date: processed_genre.last_revision_date,
-                mixes,
-                top_artists,
-            })?,
-        )?;
+        let top_artists_by_decade = {
+            let ranked_artists = genre_top_artists
+                .get(page)
+                .map(|artists| artists.as_slice())
+                .unwrap_or_default();
+            let mut by_decade: BTreeMap<i32, Vec<(PageName, genre_top_artists::ArtistScore)>> =
+                BTreeMap::new();
+            for (artist_page, score) in ranked_artists {
+                let Some(artist) = processed_artists.0.get(artist_page) else {
+                    continue;
+                };
+                for decade in &artist.active_decades {
+                    by_decade
+                        .entry(*decade)
+                        .or_default()
+                        .push((artist_page.clone(), *score));
+                }
+            }
+
+            let mut top_artists_by_decade = BTreeMap::new();
+            for (decade, artists) in by_decade {
+                let selected = genre_top_artists::select(&artists, top_artists_config, |artist| {
+                    page_aliases.aggregated_link_count(artist, inbound_link_counts)
+                });
+                if selected.is_empty() {
+                    continue;
+                }
+                let artists: Vec<PageName> = selected.into_iter().map(|(page, _)| page).collect();
+                artists_to_copy.extend(artists.iter().cloned());
+                top_artists_by_decade.insert(decade, artists);
+            }
+            top_artists_by_decade
+        };
+
+        genre_exports.push((
+            top_artists.clone(),
+            match &mixes {
+                Some(GenreMixes::Mixes(mixes)) => mixes.clone(),
+                _ => vec![],
+            },
+        ));
+
+        genre_files.push(GenreFileData {
+            description: processed_genre.wikitext_description.clone(),
+            description_html: None,
+            last_revision_date: processed_genre.last_revision_date,
+            mixes,
+            mix_embeds: BTreeMap::new(),
+            image: processed_genre
+                .image
+                .as_ref()
+                .and_then(|file| genre_images?.get(file))
+                .cloned(),
+            top_artists,
+            top_artist_scores,
+            top_artists_by_decade,
+            ancestors: vec![],
+            descendants: vec![],
+            origin_countries: processed_genre.origin_countries.clone(),
+        });
+    }
+
+    let links: Vec<usize> = graph.nodes.iter().map(|node| node.links).collect();
+    for (node, popularity) in graph.nodes.iter_mut().zip(popularity_tiers(&links)) {
+        node.popularity = popularity;
     }
 
     // Second pass: create edges
+    let mut related_edges = vec![];
+    // Derivative edges asserted by their target's stylistic origins and by their source's
+    // derivatives, by `(source, target)`; those asserted by both are corroborated
+    let mut asserted_as_origin = BTreeSet::new();
+    let mut asserted_as_derivative = BTreeSet::new();
+    let mut unresolved_links = UnresolvedLinks::default();
     for page in &node_order {
         let processed_genre = &processed_genres.0[page];
         let genre_id = *page_to_id.get(page).with_context(|| {
@@ -151,19 +504,26 @@ pub fn produce(
             links_to_articles: &links::LinksToArticles,
             processed_genres: &process::ProcessedGenres,
             page_to_id: &BTreeMap<PageName, PageDataId>,
+            unresolved_links: &mut UnresolvedLinks,
             source_page: &process::ProcessedGenre,
             ty: &str,
             link: &str,
         ) -> anyhow::Result<Option<(PageDataId, GenreName)>> {
             // Not all links correspond to a genre, so we return an `Option`
             let Some(page) = links_to_articles.map(link) else {
+                let links = if links::interwiki_prefix(link).is_some() {
+                    &mut unresolved_links.interwiki
+                } else {
+                    &mut unresolved_links.unresolved
+                };
+                *links.entry(link.to_string()).or_default() += 1;
                 return Ok(None);
             };
-            let Some(genre) = processed_genres.0.get(&page) else {
+            let Some(genre) = processed_genres.0.get(page) else {
                 // This isn't a genre, so we don't need to get its ID
                 return Ok(None);
             };
-            let id = page_to_id.get(&page).copied().with_context(|| {
+            let id = page_to_id.get(page).copied().with_context(|| {
                 format!("{}: Missing page ID for {ty} `{link}`", source_page.page)
             })?;
             Ok(Some((id, genre.name.clone())))
@@ -176,6 +536,7 @@ pub fn produce(
                 links_to_articles,
                 processed_genres,
                 &page_to_id,
+                &mut unresolved_links,
                 processed_genre,
                 "stylistic origin",
                 stylistic_origin,
@@ -192,10 +553,12 @@ pub fn produce(
                     continue;
                 }
 
+                asserted_as_origin.insert((source_id, genre_id));
                 graph.edges.insert(EdgeData {
                     source: source_id,
                     target: genre_id,
                     ty: EdgeType::Derivative,
+                    corroborated: false,
                 });
             }
         }
@@ -204,6 +567,7 @@ pub fn produce(
                 links_to_articles,
                 processed_genres,
                 &page_to_id,
+                &mut unresolved_links,
                 processed_genre,
                 "derivative",
                 derivative,
@@ -220,10 +584,12 @@ pub fn produce(
                     continue;
                 }
 
+                asserted_as_derivative.insert((genre_id, target_id));
                 graph.edges.insert(EdgeData {
                     source: genre_id,
                     target: target_id,
                     ty: EdgeType::Derivative,
+                    corroborated: false,
                 });
             }
         }
@@ -232,6 +598,7 @@ pub fn produce(
                 links_to_articles,
                 processed_genres,
                 &page_to_id,
+                &mut unresolved_links,
                 processed_genre,
                 "subgenre",
                 subgenre,
@@ -252,6 +619,7 @@ pub fn produce(
                     source: genre_id,
                     target: target_id,
                     ty: EdgeType::Subgenre,
+                    corroborated: false,
                 });
             }
         }
@@ -260,6 +628,7 @@ pub fn produce(
                 links_to_articles,
                 processed_genres,
                 &page_to_id,
+                &mut unresolved_links,
                 processed_genre,
                 "fusion genre",
                 fusion_genre,
@@ -280,43 +649,351 @@ pub fn produce(
                     source: genre_id,
                     target: target_id,
                     ty: EdgeType::FusionGenre,
+                    corroborated: false,
                 });
             }
         }
-        // If this genre comes from a heading of another page, attempt to add the parent page
-        // as a subgenre relationship, as long as it's not the same page (this can happen in
+        // If this genre comes from a heading of another page, attempt to add its parent
+        // as a subgenre relationship: the closest enclosing heading that is itself a genre,
+        // or otherwise the page. This must not be the genre itself (this can happen in
         // a few strange cases, like "Satirical music#History").
+        let enclosing_pages = processed_genre
+            .heading_path
+            .iter()
+            .rev()
+            .skip(1)
+            .map(|heading| Some(heading.clone()))
+            .chain(std::iter::once(None))
+            .map(|heading| page.with_opt_heading(heading));
+        for see_also in &processed_genre.see_also {
+            if let Some((target_id, target_name)) = get_id_for_page(
+                links_to_articles,
+                processed_genres,
+                &page_to_id,
+                &mut unresolved_links,
+                processed_genre,
+                "see also",
+                see_also,
+            )? {
+                if target_id == genre_id {
+                    continue;
+                }
+                let edge_key = (processed_genre.name.clone(), target_name, EdgeType::Related);
+                if rejected_edges.contains(&edge_key) {
+                    continue;
+                }
+
+                related_edges.push(EdgeData {
+                    source: genre_id,
+                    target: target_id,
+                    ty: EdgeType::Related,
+                    corroborated: false,
+                });
+            }
+        }
         if page.heading.is_some()
-            && let Some(parent_page) = page_to_id
-                .get(&page.with_opt_heading(None))
-                .copied()
-                .filter(|pp| *pp != genre_id)
+            && let Some(parent_page) = enclosing_pages
+                .filter_map(|p| page_to_id.get(&p).copied())
+                .find(|pp| *pp != genre_id)
         {
             graph.edges.insert(EdgeData {
                 source: parent_page,
                 target: genre_id,
                 ty: EdgeType::Subgenre,
+                corroborated: false,
             });
         }
     }
 
+    graph.edges = std::mem::take(&mut graph.edges)
+        .into_iter()
+        .map(|mut edge| {
+            let key = (edge.source, edge.target);
+            edge.corroborated = edge.ty == EdgeType::Derivative
+                && asserted_as_origin.contains(&key)
+                && asserted_as_derivative.contains(&key);
+            edge
+        })
+        .collect();
+    println!(
+        "{:.2}s: {} of {} derivative edges are corroborated by both genres",
+        start.elapsed().as_secs_f32(),
+        graph.edges.iter().filter(|edge| edge.corroborated).count(),
+        graph
+            .edges
+            .iter()
+            .filter(|edge| edge.ty == EdgeType::Derivative)
+            .count()
+    );
+
+    let help_requested = help_requests.len();
+    mixes_help::write(diagnostics_path, help_requests)?;
+    println!(
+        "{:.2}s: {help_requested} genres need help finding a mix; listed in mixes_help.md",
+        start.elapsed().as_secs_f32(),
+    );
+
+    std::fs::write(
+        diagnostics_path.join("unresolved_links.json"),
+        serde_json::to_string_pretty(&unresolved_links)?,
+    )?;
+    println!(
+        "{:.2}s: {} distinct genre links didn't resolve, and {} linked to other wikis",
+        start.elapsed().as_secs_f32(),
+        unresolved_links.unresolved.len(),
+        unresolved_links.interwiki.len()
+    );
+
+    // Genres whose articles mention each other often enough are related, too
+    if let Some(prose_mentions) = prose_mentions {
+        let rejected_edges = data_patches::edges_to_reject();
+        let genre_id = |page: &PageName| {
+            processed_genres
+                .0
+                .contains_key(page)
+                .then(|| page_to_id.get(page).copied())
+                .flatten()
+        };
+        let related_pairs = prose_mentions.related_pairs(
+            |page| page_to_id.get(page).copied(),
+            |link| genre_id(links_to_articles.map(link)?),
+            related_mention_threshold,
+        );
+        for &(a, b, _) in &related_pairs {
+            let (a_name, b_name) = (&graph.nodes[a.0].label, &graph.nodes[b.0].label);
+            if rejected_edges.contains(&(a_name.clone(), b_name.clone(), EdgeType::Related))
+                || rejected_edges.contains(&(b_name.clone(), a_name.clone(), EdgeType::Related))
+            {
+                continue;
+            }
+            related_edges.push(EdgeData {
+                source: a,
+                target: b,
+                ty: EdgeType::Related,
+                corroborated: false,
+            });
+        }
+        // Not `prose_mentions.json`, which is the mentions' cache in the dump's output directory
+        if !layout_only {
+            std::fs::write(
+                output_path.join("related_mentions.json"),
+                to_json(&related_pairs, pretty)?,
+            )?;
+        }
+        println!(
+            "{:.2}s: found {} genre pairs related by prose mentions",
+            start.elapsed().as_secs_f32(),
+            related_pairs.len()
+        );
+    }
+
+    // "See also" links are only worth a `Related` edge if the genres aren't already
+    // connected (in either direction) by a stronger relationship or a reciprocal link.
+    {
+        let mut connected: BTreeSet<(PageDataId, PageDataId)> = graph
+            .edges
+            .iter()
+            .map(|e| (e.source.min(e.target), e.source.max(e.target)))
+            .collect();
+        for edge in related_edges {
+            if connected.insert((edge.source.min(edge.target), edge.source.max(edge.target))) {
+                graph.edges.insert(edge);
+            }
+        }
+    }
+
+    reclassify_edges(
+        &mut graph.edges,
+        &data_patches.edge_patches,
+        &page_to_id,
+        patch_report,
+    );
+
+    // Descriptions are rendered once every genre has an ID, so that links to them can be resolved
+    let pwt_configuration = wikitext_util::wikipedia_pwt_configuration();
+    let genre_for_link = |link: &str| {
+        links_to_articles
+            .map(link)
+            .and_then(|page| page_to_id.get(page).copied())
+    };
+    let description_renderer = description_html.then(|| {
+        crate::description_html::Renderer::new(
+            &pwt_configuration,
+            &dump_meta.wikipedia_domain,
+            &genre_for_link,
+        )
+    });
+
+    let embeds = (look_up_mix_embeds && !layout_only)
+        .then(|| {
+            mix_embeds::resolve(
+                start,
+                genre_exports.iter().flat_map(|(_, mixes)| mixes),
+                Path::new(mix_embeds::CACHE_PATH),
+            )
+        })
+        .transpose()?
+        .unwrap_or_default();
+
+    // Write the genre files, with each genre's lineage along the non-`Related` edges, and
+    // find each genre's depth below the roots
+    {
+        let mut parents = vec![vec![]; graph.nodes.len()];
+        let mut children = vec![vec![]; graph.nodes.len()];
+        for edge in graph.edges.iter().filter(|e| e.ty != EdgeType::Related) {
+            parents[edge.target.0].push(edge.source);
+            children[edge.source.0].push(edge.target);
+        }
+        for (node, depth) in graph.nodes.iter_mut().zip(depths(&parents, &children)) {
+            node.depth = depth;
+        }
+
+        // The genre files are part of the website's data, which laying out alone leaves be
+        if !layout_only {
+            for (index, (page, mut genre_file)) in node_order.iter().zip(genre_files).enumerate() {
+                genre_file.description_html = genre_file
+                    .description
+                    .as_deref()
+                    .and_then(|description| description_renderer.as_ref()?.render(description));
+                if let Some(GenreMixes::Mixes(mixes)) = &genre_file.mixes {
+                    genre_file.mix_embeds = mixes
+                        .iter()
+                        .filter_map(|mix| {
+                            let (id, _) = mix_embeds::mix_url(mix)?;
+                            Some((id.to_string(), embeds.get(id)?.clone()?))
+                        })
+                        .collect();
+                }
+                genre_file.ancestors = lineage(&parents, PageDataId(index), lineage_depth);
+                genre_file.descendants = lineage(&children, PageDataId(index), lineage_depth);
+                let path = output_path.join(page.data_file_path("genres", graph.sharded_files));
+                std::fs::create_dir_all(path.parent().unwrap())?;
+                std::fs::write(path, to_json(&genre_file, pretty)?)?;
+            }
+        }
+    }
+
     // Run force-directed layout to compute node positions
     {
+        // `Related` edges are too loose to shape the layout or colouring
         let adjacency: Vec<(usize, usize)> = graph
             .edges
             .iter()
+            .filter(|e| e.ty != EdgeType::Related)
             .map(|e| (e.source.0, e.target.0))
             .collect();
-        let positions = crate::force_layout::compute(graph.nodes.len(), &adjacency);
-        for (node, pos) in graph.nodes.iter_mut().zip(positions.iter()) {
+        let edge_types: Vec<EdgeType> = graph
+            .edges
+            .iter()
+            .filter(|e| e.ty != EdgeType::Related)
+            .map(|e| e.ty)
+            .collect();
+        let previous_layout: Layout = previous_layout_path
+            .filter(|path| path.is_file())
+            .map(|path| -> anyhow::Result<Layout> {
+                serde_json::from_slice(&std::fs::read(path)?)
+                    .with_context(|| format!("Failed to parse {path:?}"))
+            })
+            .transpose()?
+            .unwrap_or_default();
+        // Communities are detected first, so that the layout can gather them
+        let communities = datagen::communities::detect(graph.nodes.len(), &adjacency);
+        let pins: Vec<Option<[f64; 2]>> = graph
+            .nodes
+            .iter()
+            .map(|node| data_patches.pins.get(&node.label).copied())
+            .collect();
+        let initial_positions: Vec<Option<[f64; 2]>> = node_order
+            .iter()
+            .map(|page| previous_layout.get(page).copied())
+            .collect();
+
+        let input = layout::LayoutInput {
+            num_nodes: graph.nodes.len(),
+            adjacency: &adjacency,
+            edge_types: &edge_types,
+            communities: &communities,
+            pins: &pins,
+            initial_positions: &initial_positions,
+            seed: layout_config.seed,
+            initialization: layout_config.initialization,
+            precision: layout_config.precision,
+        };
+        let algorithm = layout_config.algorithm.layout();
+        // The heights are kept apart, as the saved layout (and so the next warm start) is 2D
+        let (positions, heights): (Vec<[f64; 2]>, Option<Vec<f64>>) = if skip_layout {
+            let missing = initial_positions.iter().filter(|pos| pos.is_none()).count();
+            anyhow::ensure!(
+                missing == 0,
+                "Can't skip the layout, as {missing} genres aren't in the previous layout"
+            );
+            (initial_positions.iter().flatten().copied().collect(), None)
+        } else if layout_config.three_d {
+            let positions = algorithm
+                .compute_3d(&input, &mut layout::print_progress)
+                .with_context(|| {
+                    format!(
+                        "The {:?} layout can't lay out in 3D",
+                        layout_config.algorithm
+                    )
+                })?;
+            let heights = positions.iter().map(|&[_, _, z]| z).collect();
+            (
+                positions.into_iter().map(|[x, y, _]| [x, y]).collect(),
+                Some(heights),
+            )
+        } else {
+            (algorithm.compute(&input, &mut layout::print_progress), None)
+        };
+        // Rounded, so that the output doesn't churn on last-digit differences
+        let round = |v: f64| (v * 100.0).round() / 100.0;
+        let positions: Vec<[f64; 2]> = positions.into_iter().map(|pos| pos.map(round)).collect();
+        for (i, (node, pos)) in graph.nodes.iter_mut().zip(positions.iter()).enumerate() {
             node.x = pos[0];
             node.y = pos[1];
+            node.z = heights.as_ref().map(|heights| round(heights[i]));
         }
-        println!(
-            "{:.2}s: computed force-directed layout for {} nodes",
-            start.elapsed().as_secs_f32(),
-            graph.nodes.len()
+        std::fs::write(
+            diagnostics_path.join(LAYOUT_FILE_NAME),
+            serde_json::to_string(
+                &node_order
+                    .iter()
+                    .cloned()
+                    .zip(positions)
+                    .collect::<Layout>(),
+            )?,
+        )?;
+        if skip_layout {
+            println!(
+                "{:.2}s: reused the previous layout for {} nodes",
+                start.elapsed().as_secs_f32(),
+                graph.nodes.len()
+            );
+        } else {
+            println!(
+                "{:.2}s: computed force-directed layout for {} nodes ({} warm-started)",
+                start.elapsed().as_secs_f32(),
+                graph.nodes.len(),
+                initial_positions.iter().flatten().count()
+            );
+        }
+        let metrics = crate::layout_metrics::LayoutMetrics::measure(
+            &graph
+                .nodes
+                .iter()
+                .map(|node| [node.x, node.y])
+                .collect::<Vec<_>>(),
+            &adjacency,
+            &edge_types,
         );
+        metrics.print();
+        std::fs::write(
+            diagnostics_path.join("layout_metrics.json"),
+            serde_json::to_string_pretty(&metrics)?,
+        )?;
+        if layout_only {
+            return Ok(());
+        }
 
         let hues = datagen::color_propagation::compute_hues(graph.nodes.len(), &adjacency);
         for (node, &hue) in graph.nodes.iter_mut().zip(hues.iter()) {
@@ -327,6 +1004,61 @@ pub fn produce(
             start.elapsed().as_secs_f32(),
             graph.nodes.len()
         );
+
+        let mut degrees = vec![0; graph.nodes.len()];
+        for &(source, target) in &adjacency {
+            degrees[source] += 1;
+            degrees[target] += 1;
+        }
+        // Each community is labelled after its highest-degree genre (the first, on ties)
+        let mut label_nodes: Vec<Option<usize>> = vec![];
+        for (index, &community) in communities.iter().enumerate() {
+            if community >= label_nodes.len() {
+                label_nodes.resize(community + 1, None);
+            }
+            let label_node = &mut label_nodes[community];
+            if label_node.is_none_or(|label_node| degrees[index] > degrees[label_node]) {
+                *label_node = Some(index);
+            }
+        }
+        graph.communities = label_nodes
+            .into_iter()
+            .flatten()
+            .map(|index| graph.nodes[index].label.clone())
+            .collect();
+        for (node, community) in graph.nodes.iter_mut().zip(communities) {
+            node.community = community;
+        }
+        println!(
+            "{:.2}s: detected {} communities",
+            start.elapsed().as_secs_f32(),
+            graph.communities.len()
+        );
+
+        // Rank flows from a genre to the genres it derives from
+        let reversed: Vec<(usize, usize)> = adjacency.iter().map(|&(s, t)| (t, s)).collect();
+        let pageranks = datagen::centrality::pagerank(
+            graph.nodes.len(),
+            &reversed,
+            pagerank_damping,
+            pagerank_iterations,
+        );
+        let betweenness = datagen::centrality::betweenness(graph.nodes.len(), &adjacency);
+        for ((node, pagerank), betweenness) in
+            graph.nodes.iter_mut().zip(pageranks).zip(betweenness)
+        {
+            node.pagerank = pagerank;
+            node.betweenness = betweenness;
+        }
+        for edge in &graph.edges {
+            graph.nodes[edge.source.0].out_degree += 1;
+            graph.nodes[edge.target.0].in_degree += 1;
+        }
+        println!(
+            "{:.2}s: computed centrality for {} nodes",
+            start.elapsed().as_secs_f32(),
+            graph.nodes.len()
+        );
     }
 
     // Third pass (over edges): build node->edges sets for calculating max degree
@@ -349,50 +1081,626 @@ pub fn produce(
         .max()
         .unwrap_or(0);
 
+    // Write each genre's neighbourhood, for the website to draw a linked genre with
+    if neighborhood_hops > 0 {
+        let neighborhood_path = output_path.join(NEIGHBORHOOD_DIRECTORY);
+        std::fs::create_dir_all(&neighborhood_path)?;
+        for (index, neighborhood) in graph.neighborhoods(neighborhood_hops).enumerate() {
+            std::fs::write(
+                neighborhood_path.join(format!("{index}.json")),
+                to_json(&neighborhood, pretty)?,
+            )?;
+        }
+        println!(
+            "{:.2}s: saved the neighbourhoods of {} genres",
+            start.elapsed().as_secs_f32(),
+            graph.nodes.len()
+        );
+    }
+
+    og_cards::write(
+        start,
+        &graph,
+        &node_order,
+        og_cards.then_some(Path::new(og_cards::FONT_PATH)),
+        output_path,
+    )?;
+
     // Fifth pass (over links_to_articles): update links_to_page_ids
     std::fs::write(
         output_path.join("links_to_page_ids.json"),
-        serde_json::to_string_pretty(&LinksToPageIds(BTreeMap::from_iter(
-            links_to_articles
-                .0
-                .iter()
-                .filter_map(|(link, page)| page_to_id.get(page).map(|id| (link.clone(), *id))),
-        )))?,
+        to_json(
+            &LinksToPageIds(BTreeMap::from_iter(links_to_articles.links().filter_map(
+                |(link, page)| page_to_id.get(page).map(|id| (link.to_string(), *id)),
+            ))),
+            pretty,
+        )?,
+    )?;
+
+    std::fs::write(output_path.join("aliases.json"), to_json(&aliases, pretty)?)?;
+    // The era index only has the genres whose decade is known, for browsing by decade
+    let eras: BTreeMap<i32, &Vec<PageDataId>> = decades
+        .iter()
+        .filter_map(|(decade, ids)| match decade {
+            Decade::Known(decade) => Some((*decade, ids)),
+            Decade::Unknown => None,
+        })
+        .collect();
+    std::fs::write(output_path.join("eras.json"), to_json(&eras, pretty)?)?;
+    std::fs::write(output_path.join("decades.json"), to_json(&decades, pretty)?)?;
+    std::fs::write(
+        output_path.join("countries.json"),
+        to_json(&countries, pretty)?,
+    )?;
+
+    // A family of one isn't a grouping (members are pushed per genre, so duplicates are adjacent)
+    families.retain(|_, members| {
+        members.dedup();
+        members.len() > 1
+    });
+    std::fs::write(
+        output_path.join("families.json"),
+        to_json(&families, pretty)?,
     )?;
 
-    // Copy artist data
-    let artists_path = output_path.join("artists");
-    std::fs::create_dir_all(&artists_path)?;
-    for artist_page in &artists_to_copy {
-        if let Some(artist) = processed_artists.0.get(artist_page) {
+    // Copy artist data: that of the genres' top artists, or of every artist
+    let artists_to_write: Vec<&PageName> = if all_artists {
+        processed_artists.0.keys().collect()
+    } else {
+        artists_to_copy.iter().collect()
+    };
+    std::fs::create_dir_all(output_path.join("artists"))?;
+    for artist_page in &artists_to_write {
+        if let Some(artist) = processed_artists.0.get(*artist_page) {
             let data = ArtistFileData {
                 name: artist.name.0.clone(),
                 last_revision_date: artist.last_revision_date,
                 description: artist.wikitext_description.clone(),
+                description_html: artist
+                    .wikitext_description
+                    .as_deref()
+                    .and_then(|description| description_renderer.as_ref()?.render(description)),
                 genres: artist_genres
-                    .get(artist_page)
+                    .get(*artist_page)
                     .map(|gs| gs.iter().flat_map(|g| page_to_id.get(g).copied()).collect())
                     .unwrap_or_default(),
+                links: page_aliases.aggregated_link_count(artist_page, inbound_link_counts),
+                wikidata_qid: wikidata_ids.and_then(|ids| ids.get(*artist_page)).cloned(),
             };
-            std::fs::write(
-                artists_path.join(format!("{}.json", PageName::sanitize(artist_page))),
-                serde_json::to_string_pretty(&data)?,
-            )?;
+            let path = output_path.join(artist_page.data_file_path("artists", graph.sharded_files));
+            std::fs::create_dir_all(path.parent().unwrap())?;
+            std::fs::write(path, to_json(&data, pretty)?)?;
         }
     }
     println!(
         "{:.2}s: saved {} artists",
         start.elapsed().as_secs_f32(),
-        artists_to_copy.len()
+        artists_to_write.len()
+    );
+
+    // Credit the pages the genre and artist text was taken from
+    let attribution = attribution::Attribution::new(
+        &graph.wikipedia_domain,
+        &graph.dump_date,
+        node_order.iter().map(|page| {
+            let genre = &processed_genres.0[page];
+            (page, genre.revision_id, genre.last_revision_date)
+        }),
+        artists_to_write.iter().filter_map(|page| {
+            let artist = processed_artists.0.get(*page)?;
+            Some((*page, artist.revision_id, artist.last_revision_date))
+        }),
+    );
+    std::fs::write(
+        output_path.join("attribution.json"),
+        to_json(&attribution, pretty)?,
+    )?;
+    std::fs::write(output_path.join("attribution.html"), attribution.html())?;
+
+    let node_details: Vec<export::NodeDetails> = node_order
+        .iter()
+        .zip(genre_exports)
+        .map(|(page, (top_artists, mixes))| {
+            let processed_genre = &processed_genres.0[page];
+            export::NodeDetails {
+                page: page.clone(),
+                origin_decade: processed_genre.origin_decade,
+                cluster: processed_genre
+                    .navboxes
+                    .iter()
+                    .find(|navbox| families.contains_key(*navbox))
+                    .cloned(),
+                top_artists,
+                mixes,
+                last_revision_date: processed_genre.last_revision_date,
+            }
+        })
+        .collect();
+    std::fs::write(
+        output_path.join("genres.graphml"),
+        export::graphml(&graph, &node_details),
+    )?;
+    std::fs::write(
+        output_path.join("genres.gexf"),
+        export::gexf(&graph, &node_details),
+    )?;
+    std::fs::write(
+        output_path.join("nodes.csv"),
+        export::nodes_csv(&graph, &node_details),
+    )?;
+    std::fs::write(output_path.join("edges.csv"), export::edges_csv(&graph))?;
+    std::fs::write(
+        output_path.join("sitemap.xml"),
+        export::sitemap(&graph, &node_details),
+    )?;
+    std::fs::write(output_path.join("robots.txt"), export::robots_txt())?;
+    let has_description: Vec<bool> = node_order
+        .iter()
+        .map(|page| processed_genres.0[page].wikitext_description.is_some())
+        .collect();
+    std::fs::write(
+        output_path.join("stats.json"),
+        to_json(
+            &dataset_stats::DatasetStats::calculate(&graph, &node_details, &has_description),
+            pretty,
+        )?,
+    )?;
+    let exported_artists: Vec<export::ArtistDetails> = artists_to_copy
+        .iter()
+        .filter_map(|artist_page| {
+            let artist = processed_artists.0.get(artist_page)?;
+            Some(export::ArtistDetails {
+                page: artist_page.clone(),
+                name: artist.name.0.clone(),
+                links: page_aliases.aggregated_link_count(artist_page, inbound_link_counts),
+                wikidata_qid: wikidata_ids.and_then(|ids| ids.get(artist_page)).cloned(),
+                genres: artist_genres
+                    .get(artist_page)
+                    .map(|gs| gs.iter().flat_map(|g| page_to_id.get(g).copied()).collect())
+                    .unwrap_or_default(),
+            })
+        })
+        .collect();
+    export::sqlite(
+        &output_path.join("genres.sqlite"),
+        &graph,
+        &node_details,
+        &exported_artists,
+    )?;
+    if parquet {
+        export::parquet(output_path, &graph, &node_details, &exported_artists)?;
+    }
+    std::fs::write(
+        output_path.join("genres.jsonld"),
+        serde_json::to_string(&export::json_ld(&graph, &node_details, &exported_artists))?,
+    )?;
+
+    let search_index: Vec<shared::SearchIndexNode> = graph
+        .nodes
+        .iter()
+        .zip(&node_details)
+        .map(|(node, details)| {
+            let artists = details
+                .top_artists
+                .iter()
+                .filter_map(|artist| processed_artists.0.get(artist))
+                .map(|artist| artist.name.0.as_str());
+            let translations = langlinks
+                .filter(|_| details.page.heading.is_none())
+                .and_then(|langlinks| langlinks.get(&details.page))
+                .into_iter()
+                .flat_map(|titles| titles.values().map(String::as_str));
+            search_index_node(node, artists, translations, name_normalization)
+        })
+        .collect();
+    std::fs::write(
+        output_path.join("search_index.json"),
+        serde_json::to_string(&search_index)?,
+    )?;
+
+    graph.write(output_path, data_format, pretty)?;
+    println!(
+        "{:.2}s: saved {}",
+        start.elapsed().as_secs_f32(),
+        data_format.file_name()
+    );
+
+    validate_output(start, output_path, diagnostics_path, &node_order)
+}
+
+/// Read back the files written by [`produce`] and check that they refer to each other
+/// consistently, failing with a report (also written to `integrity_issues.json` in
+/// `diagnostics_path`) if they don't.
+///
+/// Only the genre files for `node_order` and the artist files they refer to are read, as files
+/// from previous runs are left in place.
+fn validate_output(
+    start: std::time::Instant,
+    output_path: &Path,
+    diagnostics_path: &Path,
+    node_order: &[PageName],
+) -> anyhow::Result<()> {
+    fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> anyhow::Result<T> {
+        serde_json::from_slice(
+            &std::fs::read(path).with_context(|| format!("Failed to read {path:?}"))?,
+        )
+        .with_context(|| format!("Failed to parse {path:?}"))
+    }
+
+    let (graph, _) = FrontendData::read(output_path)?;
+    let links_to_page_ids: LinksToPageIds = read_json(&output_path.join("links_to_page_ids.json"))?;
+    let genre_files = node_order
+        .iter()
+        .map(|page| {
+            let path = output_path.join(page.data_file_path("genres", graph.sharded_files));
+            Ok((page.clone(), read_json(&path)?))
+        })
+        .collect::<anyhow::Result<Vec<(PageName, GenreFileData)>>>()?;
+    let mut artist_files = BTreeMap::new();
+    for (_, genre_file) in &genre_files {
+        for artist in genre_file
+            .top_artists
+            .iter()
+            .chain(genre_file.top_artists_by_decade.values().flatten())
+        {
+            if !artist_files.contains_key(artist) {
+                let path = output_path.join(artist.data_file_path("artists", graph.sharded_files));
+                let file = path.is_file().then(|| read_json(&path)).transpose()?;
+                artist_files.insert(artist.clone(), file);
+            }
+        }
+    }
+
+    let issues = integrity_issues(&graph, &genre_files, &artist_files, &links_to_page_ids);
+    std::fs::write(
+        diagnostics_path.join("integrity_issues.json"),
+        serde_json::to_string_pretty(&issues)?,
+    )?;
+    if !issues.is_empty() {
+        const SHOWN: usize = 20;
+        anyhow::bail!(
+            "the output has {} referential integrity issues (see integrity_issues.json):\n{}{}",
+            issues.len(),
+            issues[..issues.len().min(SHOWN)].join("\n"),
+            if issues.len() > SHOWN { "\n..." } else { "" }
+        );
+    }
+    println!(
+        "{:.2}s: validated the output against {} genre and {} artist files",
+        start.elapsed().as_secs_f32(),
+        genre_files.len(),
+        artist_files.len()
     );
+    Ok(())
+}
+
+/// Find references between the output files that don't resolve: edges and genre IDs outside
+/// the graph, and top artists without an artist file (`None` in `artist_files`).
+fn integrity_issues(
+    graph: &FrontendData,
+    genre_files: &[(PageName, GenreFileData)],
+    artist_files: &BTreeMap<PageName, Option<ArtistFileData>>,
+    links_to_page_ids: &LinksToPageIds,
+) -> Vec<String> {
+    let node_count = graph.nodes.len();
+    let in_range = |id: &PageDataId| id.0 < node_count;
+    let mut issues = vec![];
+
+    for edge in &graph.edges {
+        if !in_range(&edge.source) || !in_range(&edge.target) {
+            issues.push(format!(
+                "edge {} -> {} ({}) has an endpoint outside the {node_count} nodes",
+                edge.source.0,
+                edge.target.0,
+                edge.ty.name()
+            ));
+        }
+    }
+    if genre_files.len() != node_count {
+        issues.push(format!(
+            "there are {} genre files for {node_count} nodes",
+            genre_files.len()
+        ));
+    }
+    for (page, genre_file) in genre_files {
+        for id in genre_file
+            .ancestors
+            .iter()
+            .chain(&genre_file.descendants)
+            .flatten()
+            .filter(|id| !in_range(id))
+        {
+            issues.push(format!(
+                "genre `{page}` has lineage to missing genre {}",
+                id.0
+            ));
+        }
+        for artist in genre_file
+            .top_artists
+            .iter()
+            .chain(genre_file.top_artists_by_decade.values().flatten())
+        {
+            if artist_files.get(artist).is_none_or(Option::is_none) {
+                issues.push(format!(
+                    "genre `{page}` has top artist `{artist}` without an artist file"
+                ));
+            }
+        }
+    }
+    for (artist, artist_file) in artist_files {
+        for id in artist_file
+            .iter()
+            .flat_map(|file| &file.genres)
+            .filter(|id| !in_range(id))
+        {
+            issues.push(format!("artist `{artist}` has missing genre {}", id.0));
+        }
+    }
+    for (link, id) in &links_to_page_ids.0 {
+        if !in_range(id) {
+            issues.push(format!("link `{link}` goes to missing genre {}", id.0));
+        }
+    }
+    issues
+}
+
+/// Write the TypeScript types of the website's data files to `types_path`, and their JSON Schemas
+/// to `schema/` in `output_path`, so that neither drifts from the types here.
+pub fn write_type_definitions(
+    start: std::time::Instant,
+    types_path: &Path,
+    output_path: &Path,
+) -> anyhow::Result<()> {
+    std::fs::write(types_path, typescript_definitions())
+        .with_context(|| format!("Failed to write {types_path:?}"))?;
 
-    let data_path = output_path.join("data.json");
-    std::fs::write(data_path, serde_json::to_string_pretty(&graph)?)?;
-    println!("{:.2}s: saved data.json", start.elapsed().as_secs_f32());
+    let schema_path = output_path.join("schema");
+    std::fs::create_dir_all(&schema_path)?;
+    fn write_schema<T: schemars::JsonSchema>(path: &Path) -> anyhow::Result<()> {
+        let schema = schemars::generate::SchemaSettings::draft2020_12()
+            .for_serialize()
+            .into_generator()
+            .into_root_schema_for::<T>();
+        std::fs::write(path, serde_json::to_string_pretty(&schema)?)
+            .with_context(|| format!("Failed to write {path:?}"))
+    }
+    write_schema::<FrontendData>(&schema_path.join("data.schema.json"))?;
+    write_schema::<ChunkManifest>(&schema_path.join("chunks.schema.json"))?;
+    write_schema::<ClusterChunk>(&schema_path.join("cluster.schema.json"))?;
+    write_schema::<Neighborhood>(&schema_path.join("neighborhood.schema.json"))?;
+    write_schema::<GenreFileData>(&schema_path.join("genre.schema.json"))?;
+    write_schema::<ArtistFileData>(&schema_path.join("artist.schema.json"))?;
+    write_schema::<LinksToPageIds>(&schema_path.join("links_to_page_ids.schema.json"))?;
+    write_schema::<Aliases>(&schema_path.join("aliases.schema.json"))?;
 
+    println!(
+        "{:.2}s: wrote type definitions",
+        start.elapsed().as_secs_f32()
+    );
     Ok(())
 }
 
+/// The TypeScript types of the website's data files, as a module.
+fn typescript_definitions() -> String {
+    use ts_rs::TS;
+
+    fn push<T: TS>(out: &mut String) {
+        out.push('\n');
+        if let Some(docs) = T::docs() {
+            out.push_str(&docs);
+        }
+        out.push_str("export ");
+        out.push_str(&T::decl());
+        out.push('\n');
+    }
+
+    let mut out = String::from(
+        "// Generated by `datagen` from the types in `datagen/src`; do not edit by hand.\n",
+    );
+    push::<FrontendData>(&mut out);
+    push::<NodeData>(&mut out);
+    push::<EdgeData>(&mut out);
+    push::<DataIndex>(&mut out);
+    push::<DataFormat>(&mut out);
+    push::<ChunkManifest>(&mut out);
+    push::<ClusterChunkInfo>(&mut out);
+    push::<ChunkedMeta>(&mut out);
+    push::<NodeSkeleton>(&mut out);
+    push::<ClusterChunk>(&mut out);
+    push::<Neighborhood>(&mut out);
+    push::<GenreName>(&mut out);
+    push::<PageDataId>(&mut out);
+    push::<GenreFileData>(&mut out);
+    push::<GenreMixes>(&mut out);
+    push::<GenreMix>(&mut out);
+    push::<MixProvider>(&mut out);
+    push::<mix_embeds::MixEmbed>(&mut out);
+    push::<genre_images::GenreImage>(&mut out);
+    push::<genre_top_artists::ArtistScore>(&mut out);
+    push::<ArtistFileData>(&mut out);
+    push::<LinksToPageIds>(&mut out);
+    push::<Aliases>(&mut out);
+    push::<AliasTarget>(&mut out);
+    push::<AliasSource>(&mut out);
+
+    // ts-rs leaves trailing spaces after each field
+    let mut trimmed: String = out
+        .lines()
+        .map(|line| line.trim_end())
+        .collect::<Vec<_>>()
+        .join("\n");
+    trimmed.push('\n');
+    trimmed
+}
+
+/// The directories whose files are renamed by [`hash_filenames`].
+const HASHED_DIRECTORIES: &[&str] = &["genres", "artists"];
+
+/// Rename every genre and artist file to include a hash of its contents
+/// (e.g. `genres/Techno.1a2b3c4d.json`), and write a `paths.json` mapping the original paths
+/// to the hashed ones.
+pub fn hash_filenames(start: std::time::Instant, output_path: &Path) -> anyhow::Result<()> {
+    // Sharded genre and artist files are one directory further down
+    fn hash_directory(
+        output_path: &Path,
+        directory: &str,
+        paths: &mut BTreeMap<String, String>,
+    ) -> anyhow::Result<()> {
+        let directory_path = output_path.join(directory);
+        for entry in std::fs::read_dir(&directory_path)
+            .with_context(|| format!("Failed to read {directory_path:?}"))?
+        {
+            let path = entry?.path();
+            let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+            if path.is_dir() {
+                hash_directory(output_path, &format!("{directory}/{file_name}"), paths)?;
+                continue;
+            }
+            let content =
+                std::fs::read(&path).with_context(|| format!("Failed to read {path:?}"))?;
+            let Some(hashed_file_name) = hashed_file_name(&file_name, &content) else {
+                continue;
+            };
+            std::fs::rename(&path, directory_path.join(&hashed_file_name))
+                .with_context(|| format!("Failed to rename {path:?}"))?;
+            paths.insert(
+                format!("{directory}/{file_name}"),
+                format!("{directory}/{hashed_file_name}"),
+            );
+        }
+        Ok(())
+    }
+
+    let mut paths = BTreeMap::new();
+    for directory in HASHED_DIRECTORIES {
+        hash_directory(output_path, directory, &mut paths)?;
+    }
+
+    std::fs::write(
+        output_path.join("paths.json"),
+        serde_json::to_string(&paths)?,
+    )?;
+    println!(
+        "{:.2}s: added content hashes to the names of {} files",
+        start.elapsed().as_secs_f32(),
+        paths.len()
+    );
+
+    Ok(())
+}
+
+/// The name of a JSON file with the first eight hex digits of a hash of its `content` added
+/// before the extension, or `None` if it isn't a JSON file.
+fn hashed_file_name(file_name: &str, content: &[u8]) -> Option<String> {
+    let stem = file_name.strip_suffix(".json")?;
+    let hash = format!("{:016x}", crate::util::content_hash(content));
+    Some(format!("{stem}.{}.json", &hash[..8]))
+}
+
+/// A data file in the output manifest.
+#[derive(Debug, PartialEq, Serialize)]
+struct ManifestEntry {
+    /// The size of the file, in bytes.
+    size: u64,
+    /// A hash of the file's contents, as hex.
+    hash: String,
+    /// The size of the `.gz` copy, in bytes.
+    gzip_size: u64,
+    /// The size of the `.br` copy, in bytes.
+    brotli_size: u64,
+}
+
+/// Write brotli (`.br`) and gzip (`.gz`) compressed copies of every data file (JSON,
+/// MessagePack or binary edges) under `output_path`, and a `manifest.json` of the files with their sizes and hashes.
+pub fn precompress(start: std::time::Instant, output_path: &Path) -> anyhow::Result<()> {
+    use rayon::prelude::*;
+
+    fn data_files(dir: &Path, files: &mut Vec<std::path::PathBuf>) -> anyhow::Result<()> {
+        for entry in std::fs::read_dir(dir).with_context(|| format!("Failed to read {dir:?}"))? {
+            let path = entry?.path();
+            if path.is_dir() {
+                data_files(&path, files)?;
+            } else if path
+                .extension()
+                .is_some_and(|e| e == "json" || e == "msgpack" || e == "bin")
+            {
+                files.push(path);
+            }
+        }
+        Ok(())
+    }
+
+    let manifest_path = output_path.join("manifest.json");
+    let mut files = vec![];
+    data_files(output_path, &mut files)?;
+    files.retain(|path| *path != manifest_path);
+
+    let manifest = files
+        .par_iter()
+        .map(|path| {
+            let content =
+                std::fs::read(path).with_context(|| format!("Failed to read {path:?}"))?;
+            let (gzip, brotli) = compress(&content)?;
+            let with_extension = |extension: &str| {
+                let mut path = path.clone().into_os_string();
+                path.push(extension);
+                std::path::PathBuf::from(path)
+            };
+            std::fs::write(with_extension(".gz"), &gzip)?;
+            std::fs::write(with_extension(".br"), &brotli)?;
+
+            let relative_path = path
+                .strip_prefix(output_path)?
+                .to_string_lossy()
+                .replace('\\', "/");
+            anyhow::Ok((
+                relative_path,
+                ManifestEntry {
+                    size: content.len() as u64,
+                    hash: format!("{:016x}", crate::util::content_hash(&content)),
+                    gzip_size: gzip.len() as u64,
+                    brotli_size: brotli.len() as u64,
+                },
+            ))
+        })
+        .collect::<anyhow::Result<BTreeMap<String, ManifestEntry>>>()?;
+
+    std::fs::write(&manifest_path, serde_json::to_string_pretty(&manifest)?)?;
+
+    let total = |size: fn(&ManifestEntry) -> u64| manifest.values().map(size).sum::<u64>();
+    println!(
+        "{:.2}s: precompressed {} files ({} bytes; {} gzipped, {} with brotli)",
+        start.elapsed().as_secs_f32(),
+        manifest.len(),
+        total(|e| e.size),
+        total(|e| e.gzip_size),
+        total(|e| e.brotli_size),
+    );
+
+    Ok(())
+}
+
+/// Compress `content` with gzip and brotli, both at their highest quality.
+fn compress(content: &[u8]) -> anyhow::Result<(Vec<u8>, Vec<u8>)> {
+    use std::io::Write as _;
+
+    let mut gzip = flate2::write::GzEncoder::new(vec![], flate2::Compression::best());
+    gzip.write_all(content)?;
+    let gzip = gzip.finish()?;
+
+    let mut brotli = vec![];
+    brotli::BrotliCompress(
+        &mut &content[..],
+        &mut brotli,
+        &brotli::enc::BrotliEncoderParams {
+            quality: 11,
+            ..Default::default()
+        },
+    )?;
+
+    Ok((gzip, brotli))
+}
+
 /// Maximum aliases kept per genre; a defensive cap against redirect-farm pages.
 const MAX_ALIASES_PER_GENRE: usize = 32;
 /// Aliases longer than this are list-style redirect noise, not names.
@@ -400,24 +1708,120 @@ const MAX_ALIAS_LENGTH: usize = 60;
 
 /// Clean up raw redirect titles into display-worthy search aliases:
 /// strip one trailing parenthetical qualifier ("Bebop (music)" → "Bebop"),
-/// drop empties/overlong titles, and deduplicate (diacritic/case-insensitively)
-/// against the label, the page title, and each other.
+/// drop empties/overlong titles, and deduplicate (diacritic/case-insensitively,
+/// and per the [`links::NameNormalization`] rules) against the label, the page title,
+/// and each other.
+/// The genres reachable from `start` through `next` within `depth` steps, by generation, with
+/// each genre in the generation it's first reached in.
+fn lineage(next: &[Vec<PageDataId>], start: PageDataId, depth: usize) -> Vec<Vec<PageDataId>> {
+    let mut seen = BTreeSet::from([start]);
+    let mut generations: Vec<Vec<PageDataId>> = vec![];
+    let mut current = vec![start];
+    for _ in 0..depth {
+        let mut generation: Vec<PageDataId> = current
+            .iter()
+            .flat_map(|id| &next[id.0])
+            .copied()
+            .filter(|id| seen.insert(*id))
+            .collect();
+        if generation.is_empty() {
+            break;
+        }
+        generation.sort();
+        generations.push(generation.clone());
+        current = generation;
+    }
+    generations
+}
+
+/// Split genres into [`POPULARITY_TIERS`] tiers by their inbound `links`, by the share of genres
+/// with fewer links, so that genres with the same count share a tier.
+fn popularity_tiers(links: &[usize]) -> Vec<usize> {
+    let mut sorted = links.to_vec();
+    sorted.sort_unstable();
+    links
+        .iter()
+        .map(|count| sorted.partition_point(|other| other < count) * POPULARITY_TIERS / links.len())
+        .collect()
+}
+
+/// Apply the `edge_patches` to the `edges` between the genres in `page_to_id`, recording each
+/// in the `patch_report`. A changed edge isn't corroborated, as the infoboxes don't assert it.
+fn reclassify_edges(
+    edges: &mut BTreeSet<EdgeData>,
+    edge_patches: &[data_patches::EdgePatch],
+    page_to_id: &BTreeMap<PageName, PageDataId>,
+    patch_report: &mut patch_report::PatchReport,
+) {
+    for patch in edge_patches {
+        let edge = page_to_id
+            .get(&patch.source)
+            .zip(page_to_id.get(&patch.target))
+            .and_then(|(source, target)| {
+                edges
+                    .iter()
+                    .find(|edge| {
+                        (edge.source, edge.target, edge.ty) == (*source, *target, patch.ty)
+                    })
+                    .cloned()
+            });
+        patch_report.record_edge_patch(patch, edge.is_some());
+        let Some(mut edge) = edge else {
+            println!(
+                "warning: there's no {} edge from {} to {} to reclassify",
+                patch.ty.name(),
+                patch.source,
+                patch.target
+            );
+            continue;
+        };
+        edges.remove(&edge);
+        if patch.reverse {
+            std::mem::swap(&mut edge.source, &mut edge.target);
+        }
+        edge.ty = patch.new_ty;
+        edge.corroborated = false;
+        edges.insert(edge);
+    }
+}
+
+/// The fewest steps through `children` from a genre without `parents` to each genre, if any.
+fn depths(parents: &[Vec<PageDataId>], children: &[Vec<PageDataId>]) -> Vec<Option<usize>> {
+    let mut depths = vec![None; parents.len()];
+    let mut queue = std::collections::VecDeque::new();
+    for (index, parents) in parents.iter().enumerate() {
+        if parents.is_empty() {
+            depths[index] = Some(0);
+            queue.push_back(index);
+        }
+    }
+    while let Some(index) = queue.pop_front() {
+        let depth = depths[index].map(|depth| depth + 1);
+        for child in &children[index] {
+            if depths[child.0].is_none() {
+                depths[child.0] = depth;
+                queue.push_back(child.0);
+            }
+        }
+    }
+    depths
+}
+
 fn clean_aliases(
     label: &str,
     page_title: &str,
     raw_aliases: Option<&BTreeSet<String>>,
+    name_normalization: &links::NameNormalization,
 ) -> Vec<String> {
-    let mut seen: BTreeSet<String> = [label, page_title]
-        .iter()
-        .map(|s| shared::normalize_search_text(s))
-        .collect();
+    let normalize = |s: &str| name_normalization.normalize(&shared::normalize_search_text(s));
+    let mut seen: BTreeSet<String> = [label, page_title].iter().map(|s| normalize(s)).collect();
     let mut aliases: Vec<String> = vec![];
     for alias in raw_aliases.into_iter().flatten() {
         let alias = strip_parenthetical(alias).trim();
         if alias.is_empty() || alias.chars().count() > MAX_ALIAS_LENGTH {
             continue;
         }
-        let normalized = shared::normalize_search_text(alias);
+        let normalized = normalize(alias);
         if normalized.is_empty() || !seen.insert(normalized) {
             continue;
         }
@@ -434,6 +1838,84 @@ fn clean_aliases(
     aliases
 }
 
+/// Build the search index entry for `node` from its label and aliases, the names of its top
+/// artists, and its titles in other languages. Terms that are the same as an earlier one (per
+/// [`links::NameNormalization`], ignoring case and diacritics) are left out, as are parenthetical
+/// qualifiers on translations ("Techno (musique)" → "Techno").
+fn search_index_node<'a>(
+    node: &NodeData,
+    artists: impl IntoIterator<Item = &'a str>,
+    translations: impl IntoIterator<Item = &'a str>,
+    name_normalization: &links::NameNormalization,
+) -> shared::SearchIndexNode {
+    let normalize = |s: &str| name_normalization.normalize(&shared::normalize_search_text(s));
+    let mut seen: BTreeSet<String> = std::iter::once(&node.label.0)
+        .chain(&node.aliases)
+        .map(|term| normalize(term))
+        .collect();
+    let mut unseen = |terms: &mut dyn Iterator<Item = &'a str>| -> Vec<String> {
+        terms
+            .map(str::trim)
+            .filter(|term| !term.is_empty() && seen.insert(normalize(term)))
+            .map(str::to_string)
+            .collect()
+    };
+    let artists = unseen(&mut artists.into_iter());
+    let mut translations = unseen(
+        &mut translations
+            .into_iter()
+            .map(strip_parenthetical)
+            .filter(|title| title.chars().count() <= MAX_ALIAS_LENGTH),
+    );
+    translations.truncate(MAX_ALIASES_PER_GENRE);
+
+    shared::SearchIndexNode {
+        label: node.label.0.clone(),
+        aliases: node.aliases.clone(),
+        artists,
+        translations,
+        links: node.links,
+    }
+}
+
+/// The alternative names of a genre for `aliases.json`, each with where it's from: its infobox
+/// `other_names`, the redirects to it that are categorized as alternative names, and the
+/// normalized forms of its label and those names. Each name is kept once, with its first source.
+fn genre_aliases(
+    label: &str,
+    other_names: &[String],
+    redirects: Option<&BTreeSet<String>>,
+    alias_redirects: &BTreeMap<PageName, AliasSource>,
+    name_normalization: &links::NameNormalization,
+) -> Vec<(String, AliasSource)> {
+    let mut aliases: Vec<(String, AliasSource)> = other_names
+        .iter()
+        .map(|name| (name.clone(), AliasSource::OtherName))
+        .collect();
+    for title in redirects.into_iter().flatten() {
+        if let Some(source) = alias_redirects.get(&PageName::new(title.as_str(), None)) {
+            aliases.push((strip_parenthetical(title).trim().to_string(), *source));
+        }
+    }
+    let normalized: Vec<(String, AliasSource)> = std::iter::once(label)
+        .chain(aliases.iter().map(|(name, _)| name.as_str()))
+        .filter_map(|name| {
+            let normalized = name_normalization.normalize(name);
+            (normalized != name.to_lowercase()).then_some((normalized, AliasSource::Normalized))
+        })
+        .collect();
+    aliases.extend(normalized);
+
+    // Names are matched case-insensitively, so a change of case isn't another name
+    let mut seen = BTreeSet::from([label.to_lowercase()]);
+    aliases.retain(|(name, _)| {
+        !name.is_empty()
+            && name.chars().count() <= MAX_ALIAS_LENGTH
+            && seen.insert(name.to_lowercase())
+    });
+    aliases
+}
+
 /// Strip one trailing parenthetical qualifier: "Pop (music)" → "Pop".
 fn strip_parenthetical(alias: &str) -> &str {
     match alias.rsplit_once(" (") {
@@ -450,13 +1932,58 @@ mod tests {
         Some(raw.iter().map(|s| s.to_string()).collect())
     }
 
+    #[test]
+    fn decades_are_keyed_by_year_with_unknown_last() {
+        let decades = BTreeMap::from_iter([
+            (Decade::Unknown, vec![PageDataId(2)]),
+            (Decade::Known(1970), vec![PageDataId(0)]),
+            (Decade::Known(1800), vec![PageDataId(1)]),
+        ]);
+        assert_eq!(
+            serde_json::to_string(&decades).unwrap(),
+            r#"{"1800":[1],"1970":[0],"unknown":[2]}"#
+        );
+    }
+
+    #[test]
+    fn hashed_file_name_depends_on_content() {
+        let techno = hashed_file_name("Techno.json", b"{}").unwrap();
+        assert!(techno.starts_with("Techno.") && techno.ends_with(".json"));
+        assert_eq!(techno.len(), "Techno.12345678.json".len());
+        assert_eq!(hashed_file_name("Techno.json", b"{}").unwrap(), techno);
+        assert_ne!(hashed_file_name("Techno.json", b"[]").unwrap(), techno);
+        assert_eq!(hashed_file_name("Techno.json.br", b"{}"), None);
+    }
+
+    #[test]
+    fn compress_round_trips() {
+        use std::io::Read as _;
+
+        let content = serde_json::to_vec_pretty(&vec!["Drum and bass"; 100]).unwrap();
+        let (gzip, brotli) = compress(&content).unwrap();
+        assert!(gzip.len() < content.len() && brotli.len() < content.len());
+
+        let mut decompressed = vec![];
+        flate2::read::GzDecoder::new(gzip.as_slice())
+            .read_to_end(&mut decompressed)
+            .unwrap();
+        assert_eq!(decompressed, content);
+
+        let mut decompressed = vec![];
+        brotli::Decompressor::new(brotli.as_slice(), 4096)
+            .read_to_end(&mut decompressed)
+            .unwrap();
+        assert_eq!(decompressed, content);
+    }
+
     #[test]
     fn clean_aliases_strips_parenthetical_qualifiers() {
         assert_eq!(
             clean_aliases(
                 "Bebop",
                 "Bebop",
-                aliases(&["Bebop (music)", "Bop (jazz)"]).as_ref()
+                aliases(&["Bebop (music)", "Bop (jazz)"]).as_ref(),
+                &links::NameNormalization::default()
             ),
             vec!["Bop"]
         );
@@ -468,7 +1995,8 @@ mod tests {
             clean_aliases(
                 "Hip-hop",
                 "Hip-hop music",
-                aliases(&["Hip hop", "HIP-HOP", "Hip-hop Music", "Rap music"]).as_ref()
+                aliases(&["Hip hop", "HIP-HOP", "Hip-hop Music", "Rap music"]).as_ref(),
+                &links::NameNormalization::default()
             ),
             // "Hip hop" survives ("hip hop" != "hip-hop" normalized); exact
             // case/diacritic variants of the label and page title do not.
@@ -476,11 +2004,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn clean_aliases_dedups_per_name_normalization() {
+        let name_normalization = links::NameNormalization {
+            space_equivalents: vec!["-".into()],
+            strip_suffixes: vec![" music".into()],
+        };
+        assert_eq!(
+            clean_aliases(
+                "Hip-hop",
+                "Hip-hop music",
+                aliases(&["Hip hop", "Hip hop music", "Rap music"]).as_ref(),
+                &name_normalization
+            ),
+            vec!["Rap music"]
+        );
+    }
+
     #[test]
     fn clean_aliases_drops_overlong_titles() {
         let long = "List of every single genre that was ever considered hip hop by anyone";
         assert_eq!(
-            clean_aliases("Hip-hop", "Hip-hop", aliases(&[long, "Rap"]).as_ref()),
+            clean_aliases(
+                "Hip-hop",
+                "Hip-hop",
+                aliases(&[long, "Rap"]).as_ref(),
+                &links::NameNormalization::default()
+            ),
             vec!["Rap"]
         );
     }
@@ -491,16 +2041,332 @@ mod tests {
             clean_aliases(
                 "Drum and bass",
                 "Drum and bass",
-                aliases(&["Jungle music", "DnB", "D&B"]).as_ref()
+                aliases(&["Jungle music", "DnB", "D&B"]).as_ref(),
+                &links::NameNormalization::default()
             ),
             vec!["D&B", "DnB", "Jungle music"]
         );
     }
 
+    #[test]
+    fn genre_aliases_keep_only_categorized_redirects() {
+        let name_normalization = links::NameNormalization {
+            space_equivalents: vec!["-".into()],
+            strip_suffixes: vec![" music".into()],
+        };
+        let alias_redirects = BTreeMap::from_iter([
+            (
+                PageName::new("Rap music", None),
+                AliasSource::AlternativeName,
+            ),
+            (
+                PageName::new("Hiphop (genre)", None),
+                AliasSource::AlternativeSpelling,
+            ),
+        ]);
+        assert_eq!(
+            genre_aliases(
+                "Hip-hop music",
+                &["Rap".to_string()],
+                aliases(&["Rap music", "Hiphop (genre)", "Hiphop muisc"]).as_ref(),
+                &alias_redirects,
+                &name_normalization,
+            ),
+            vec![
+                ("Rap".to_string(), AliasSource::OtherName),
+                ("Hiphop".to_string(), AliasSource::AlternativeSpelling),
+                ("Rap music".to_string(), AliasSource::AlternativeName),
+                ("hip hop".to_string(), AliasSource::Normalized),
+            ]
+        );
+    }
+
     #[test]
     fn strip_parenthetical_leaves_inner_parens_alone() {
         assert_eq!(strip_parenthetical("A (B) (C)"), "A (B)");
         assert_eq!(strip_parenthetical("(What) genre"), "(What) genre");
         assert_eq!(strip_parenthetical("No qualifier"), "No qualifier");
     }
+
+    #[test]
+    fn lineage_groups_genres_by_nearest_generation() {
+        // 0 -> 1 -> 2 -> 3, and 0 -> 2 directly
+        let next = vec![
+            vec![PageDataId(1), PageDataId(2)],
+            vec![PageDataId(2)],
+            vec![PageDataId(3)],
+            vec![],
+        ];
+        assert_eq!(
+            lineage(&next, PageDataId(0), 5),
+            [vec![PageDataId(1), PageDataId(2)], vec![PageDataId(3)]]
+        );
+        assert_eq!(
+            lineage(&next, PageDataId(0), 1),
+            [vec![PageDataId(1), PageDataId(2)]]
+        );
+        assert!(lineage(&next, PageDataId(3), 5).is_empty());
+    }
+
+    #[test]
+    fn popularity_tiers_split_by_share_of_less_linked_genres() {
+        assert_eq!(
+            popularity_tiers(&[0, 10, 20, 30, 40, 50, 60, 70, 80, 90]),
+            [0, 0, 1, 1, 2, 2, 3, 3, 4, 4]
+        );
+        // Ties share the lowest tier of the group
+        assert_eq!(popularity_tiers(&[5, 5, 5, 5, 100]), [0, 0, 0, 0, 4]);
+    }
+
+    #[test]
+    fn depths_count_from_the_nearest_root() {
+        // 0 -> 1 -> 2 and 3 -> 2, and 4 <-> 5 in a cycle
+        let children = vec![
+            vec![PageDataId(1)],
+            vec![PageDataId(2)],
+            vec![],
+            vec![PageDataId(2)],
+            vec![PageDataId(5)],
+            vec![PageDataId(4)],
+        ];
+        let mut parents = vec![vec![]; children.len()];
+        for (index, children) in children.iter().enumerate() {
+            for child in children {
+                parents[child.0].push(PageDataId(index));
+            }
+        }
+        assert_eq!(
+            depths(&parents, &children),
+            [Some(0), Some(1), Some(1), Some(0), None, None]
+        );
+    }
+
+    #[test]
+    fn search_index_node_skips_terms_already_present() {
+        let node = NodeData {
+            page_title: None,
+            label: GenreName("Techno".to_string()),
+            aliases: vec!["Techno music".to_string()],
+            links: 100,
+            popularity: 0,
+            x: 0.0,
+            y: 0.0,
+            z: None,
+            hue: 0.0,
+            community: 0,
+            in_degree: 0,
+            out_degree: 0,
+            pagerank: 0.0,
+            betweenness: 0.0,
+            depth: None,
+            wikidata_qid: None,
+            musicbrainz_genre_id: None,
+        };
+        let name_normalization = links::NameNormalization {
+            space_equivalents: vec![],
+            strip_suffixes: vec![],
+        };
+        let search_index_node = search_index_node(
+            &node,
+            ["Juan Atkins", "Derrick May", "juan atkins"],
+            ["Techno", "Techno (musique)", "Tecno", "テクノ", "Técno"],
+            &name_normalization,
+        );
+        assert_eq!(
+            search_index_node,
+            shared::SearchIndexNode {
+                label: "Techno".to_string(),
+                aliases: vec!["Techno music".to_string()],
+                artists: vec!["Juan Atkins".to_string(), "Derrick May".to_string()],
+                translations: vec!["Tecno".to_string(), "テクノ".to_string()],
+                links: 100,
+            }
+        );
+    }
+
+    #[test]
+    fn integrity_issues_find_dangling_references() {
+        let node = |label: &str| NodeData {
+            page_title: None,
+            label: GenreName(label.to_string()),
+            aliases: vec![],
+            links: 0,
+            popularity: 0,
+            x: 0.0,
+            y: 0.0,
+            z: None,
+            hue: 0.0,
+            community: 0,
+            in_degree: 0,
+            out_degree: 0,
+            pagerank: 0.0,
+            betweenness: 0.0,
+            depth: None,
+            wikidata_qid: None,
+            musicbrainz_genre_id: None,
+        };
+        let graph = FrontendData {
+            wikipedia_domain: "en.wikipedia.org".to_string(),
+            wikipedia_db_name: "enwiki".to_string(),
+            dump_date: "2026-02-01".to_string(),
+            nodes: vec![node("House"), node("Techno")],
+            edges: BTreeSet::from_iter([
+                EdgeData {
+                    source: PageDataId(0),
+                    target: PageDataId(1),
+                    ty: EdgeType::Derivative,
+                    corroborated: false,
+                },
+                EdgeData {
+                    source: PageDataId(1),
+                    target: PageDataId(2),
+                    ty: EdgeType::Subgenre,
+                    corroborated: false,
+                },
+            ]),
+            max_degree: 1,
+            communities: vec![],
+            sharded_files: false,
+            layout_seed: None,
+        };
+        let genre_file = |top_artists: &[&str], descendants: Vec<Vec<PageDataId>>| GenreFileData {
+            description: None,
+            description_html: None,
+            last_revision_date: jiff::Timestamp::UNIX_EPOCH,
+            mixes: None,
+            mix_embeds: BTreeMap::new(),
+            image: None,
+            top_artists: top_artists
+                .iter()
+                .map(|artist| PageName::new(*artist, None))
+                .collect(),
+            top_artist_scores: vec![],
+            top_artists_by_decade: BTreeMap::new(),
+            ancestors: vec![],
+            descendants,
+            origin_countries: vec![],
+        };
+        let genre_files = vec![
+            (
+                PageName::new("House music", None),
+                genre_file(&["Frankie Knuckles"], vec![vec![PageDataId(1)]]),
+            ),
+            (
+                PageName::new("Techno", None),
+                genre_file(&["Juan Atkins"], vec![vec![PageDataId(5)]]),
+            ),
+        ];
+        let artist_files = BTreeMap::from_iter([
+            (
+                PageName::new("Frankie Knuckles", None),
+                Some(ArtistFileData {
+                    name: "Frankie Knuckles".to_string(),
+                    description: None,
+                    description_html: None,
+                    last_revision_date: jiff::Timestamp::UNIX_EPOCH,
+                    genres: BTreeSet::from_iter([PageDataId(0), PageDataId(3)]),
+                    links: 0,
+                    wikidata_qid: None,
+                }),
+            ),
+            (PageName::new("Juan Atkins", None), None),
+        ]);
+        let links_to_page_ids = LinksToPageIds(BTreeMap::from_iter([
+            ("house music".to_string(), PageDataId(0)),
+            ("acid house".to_string(), PageDataId(7)),
+        ]));
+
+        assert_eq!(
+            integrity_issues(&graph, &genre_files, &artist_files, &links_to_page_ids),
+            [
+                "edge 1 -> 2 (subgenre) has an endpoint outside the 2 nodes",
+                "genre `Techno` has lineage to missing genre 5",
+                "genre `Techno` has top artist `Juan Atkins` without an artist file",
+                "artist `Frankie Knuckles` has missing genre 3",
+                "link `acid house` goes to missing genre 7",
+            ]
+        );
+    }
+
+    #[test]
+    fn typescript_definitions_match_serialization() {
+        let definitions = typescript_definitions();
+        // Renamed to the names the website already uses
+        assert!(definitions.contains("export type DataOnDisk = {"));
+        // Serialized as a tuple
+        assert!(definitions.contains(
+            "export type EdgeOnDiskData = [PageDataId, PageDataId, 0 | 1 | 2 | 3, true?];"
+        ));
+        // Skipped when empty, so optional
+        assert!(definitions.contains("aliases?: Array<string>,"));
+        // Always serialized, so nullable rather than optional
+        assert!(definitions.contains("description: string | null,"));
+        assert!(!definitions.lines().any(|line| line.ends_with(' ')));
+    }
+
+    #[test]
+    fn edge_patches_reclassify_and_reverse_edges() {
+        let page = |name: &str| PageName::new(name, None);
+        let page_to_id = BTreeMap::from_iter([
+            (page("Techno"), PageDataId(0)),
+            (page("Acid techno"), PageDataId(1)),
+            (page("Minimal techno"), PageDataId(2)),
+        ]);
+        let edge = |source, target, ty, corroborated| EdgeData {
+            source: PageDataId(source),
+            target: PageDataId(target),
+            ty,
+            corroborated,
+        };
+        let patch = |source: &str, target: &str, new_ty, reverse| data_patches::EdgePatch {
+            source: page(source),
+            target: page(target),
+            ty: EdgeType::Derivative,
+            new_ty,
+            reverse,
+            note: None,
+        };
+        let mut edges = BTreeSet::from_iter([
+            edge(0, 1, EdgeType::Derivative, true),
+            edge(2, 0, EdgeType::Derivative, false),
+        ]);
+        let mut report = patch_report::PatchReport::default();
+        reclassify_edges(
+            &mut edges,
+            &[
+                patch("Techno", "Acid techno", EdgeType::Subgenre, false),
+                patch("Minimal techno", "Techno", EdgeType::Derivative, true),
+                patch("Techno", "Hardcore techno", EdgeType::Subgenre, false),
+            ],
+            &page_to_id,
+            &mut report,
+        );
+        assert_eq!(
+            edges,
+            BTreeSet::from_iter([
+                edge(0, 1, EdgeType::Subgenre, false),
+                edge(0, 2, EdgeType::Derivative, false),
+            ])
+        );
+        assert_eq!(
+            report
+                .reclassified_edges
+                .iter()
+                .map(|edge| edge.applied)
+                .collect::<Vec<_>>(),
+            [true, true, false]
+        );
+    }
+
+    #[test]
+    fn website_types_are_up_to_date() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("..")
+            .join(crate::frontend_types::WEBSITE_TYPES_PATH);
+        let committed = std::fs::read_to_string(&path).unwrap();
+        assert!(
+            committed == typescript_definitions(),
+            "{path:?} is out of date; run datagen to regenerate it"
+        );
+    }
 }
@@ -0,0 +1,119 @@
+//! Reads a Wikimedia pageview dump to get the number of views of each page we track.
+//!
+//! The dump is a monthly `pageview_complete` file (e.g. `pageviews-202501-user.bz2`) from
+//! <https://dumps.wikimedia.org/other/pageview_complete/monthly/>.
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::BufRead as _,
+    path::Path,
+};
+
+use anyhow::Context as _;
+
+use crate::{types, util};
+
+/// The project code for the English Wikipedia in pageview dumps.
+const PROJECT: &str = "en.wikipedia";
+
+/// Read the number of views of each tracked page, or load them from `output_path` if they've
+/// already been read for the same pages.
+pub(crate) fn read(
+    start: std::time::Instant,
+    pageviews_path: &Path,
+    tracked_pages: &BTreeSet<types::PageName>,
+    output_path: &Path,
+) -> anyhow::Result<BTreeMap<types::PageName, usize>> {
+    let output_file_path = output_path.join("pageviews.json");
+    let tracked_pages_hash = util::pages_hash(tracked_pages);
+    if let Some(pageviews) = util::load_pages_cache(&output_file_path, tracked_pages_hash) {
+        return Ok(pageviews);
+    }
+    if output_file_path.is_file() {
+        println!(
+            "{:.2}s: cached pageviews are for other pages, reading them again",
+            start.elapsed().as_secs_f32()
+        );
+    }
+
+    println!("{:.2}s: reading pageviews", start.elapsed().as_secs_f32());
+
+    let file = std::fs::File::open(pageviews_path).with_context(|| {
+        format!(
+            "Failed to open pageviews dump: {}",
+            pageviews_path.display()
+        )
+    })?;
+    let reader = std::io::BufReader::new(bzip2::bufread::MultiBzDecoder::new(
+        std::io::BufReader::new(file),
+    ));
+
+    let mut pageviews: BTreeMap<types::PageName, usize> =
+        tracked_pages.iter().map(|page| (page.clone(), 0)).collect();
+    for line in reader.split(b'\n') {
+        let line = line.context("Failed to read line from pageviews dump")?;
+        let line = String::from_utf8_lossy(&line);
+        let Some((title, views)) = parse_pageview_line(&line) else {
+            continue;
+        };
+        // Each page has a line per access method (desktop, mobile web, mobile app)
+        if let Some(count) = pageviews.get_mut(&types::PageName::new(title.replace('_', " "), None))
+        {
+            *count += views;
+        }
+    }
+
+    util::save_pages_cache(&output_file_path, tracked_pages_hash, &pageviews)
+        .context("Failed to write pageviews")?;
+
+    println!(
+        "{:.2}s: read pageviews for {} pages",
+        start.elapsed().as_secs_f32(),
+        pageviews.len()
+    );
+
+    Ok(pageviews)
+}
+
+/// Parse an English Wikipedia line of a `pageview_complete` dump, returning the page title and
+/// its view count.
+///
+/// Lines are `project title page_id access_method views hourly_views`; the page ID is absent
+/// for some pages.
+fn parse_pageview_line(line: &str) -> Option<(&str, usize)> {
+    let fields: Vec<&str> = line.split(' ').collect();
+    let (project, title, views) = match fields.as_slice() {
+        [project, title, _page_id, _access, views, _hourly] => (*project, *title, *views),
+        [project, title, _access, views, _hourly] => (*project, *title, *views),
+        _ => return None,
+    };
+    if project != PROJECT {
+        return None;
+    }
+    Some((title, views.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pageview_line() {
+        assert_eq!(
+            parse_pageview_line("en.wikipedia Drum_and_bass 175045 desktop 91251 A3021B2987"),
+            Some(("Drum_and_bass", 91251))
+        );
+        assert_eq!(
+            parse_pageview_line("en.wikipedia Drum_and_bass null mobile-web 120 A4B6"),
+            Some(("Drum_and_bass", 120))
+        );
+        assert_eq!(
+            parse_pageview_line("en.wikipedia Drum_and_bass mobile-app 7 A7"),
+            Some(("Drum_and_bass", 7))
+        );
+        assert_eq!(
+            parse_pageview_line("de.wikipedia Drum_and_Bass 12345 desktop 500 A500"),
+            None
+        );
+        assert_eq!(parse_pageview_line("en.wikipedia malformed"), None);
+    }
+}
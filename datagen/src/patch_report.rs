@@ -0,0 +1,338 @@
+//! The report of what the patches did in a run (`patch_report.json`), so that the dataset's
+//! manual interventions are transparent: the pages they included and excluded, and the patches
+//! for fixes made on Wikipedia that no longer apply, as their pages have been revised since, so
+//! that they can be deleted once the dump has the fix.
+use std::path::Path;
+
+use anyhow::Context as _;
+use jiff::Timestamp;
+use serde::Serialize;
+
+use crate::{
+    data_patches::{self, DataPatches, EdgePatch, ExclusionReason, GenrePatch},
+    process::{ProcessedArtists, ProcessedGenre, ProcessedGenres},
+    types::{GenreName, PageName},
+};
+
+/// What the patches did in a run.
+#[derive(Debug, Default, Serialize)]
+pub struct PatchReport {
+    /// The genres included from pages without an infobox.
+    pub included: Vec<IncludedPage>,
+    /// The genres and artists excluded.
+    pub excluded: Vec<ExcludedPage>,
+    /// The genres merged into others.
+    pub merged: Vec<MergedPage>,
+    /// The edges reclassified or reversed.
+    pub reclassified_edges: Vec<ReclassifiedEdge>,
+    /// The pages that are excluded, but weren't there to exclude.
+    pub unused_exclusions: Vec<PageName>,
+    /// The patches that no longer apply.
+    pub stale: Vec<StalePatch>,
+}
+
+/// A genre included from a page without an infobox.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IncludedPage {
+    /// The page (and heading) the genre is on.
+    pub page: PageName,
+    /// The genre's name.
+    pub name: GenreName,
+    /// Why it's included.
+    pub reason: String,
+    /// Whether it was included; it isn't if the page has an infobox for it now, in which case
+    /// the patch can be deleted.
+    pub applied: bool,
+}
+
+/// A genre or artist that was excluded.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExcludedPage {
+    /// The page (and heading) the genre or artist is on.
+    pub page: PageName,
+    /// `genre` or `artist`.
+    pub kind: &'static str,
+    /// Why it's excluded.
+    pub reason: ExclusionReason,
+    /// More about why it's excluded.
+    pub note: Option<String>,
+}
+
+/// A genre that was merged into another one, as they're the same genre.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MergedPage {
+    /// The page (and heading) the genre is on.
+    pub page: PageName,
+    /// The genre's name, which is now an other name of the genre it was merged into.
+    pub name: GenreName,
+    /// The page (and heading) of the genre it was merged into.
+    pub into: PageName,
+}
+
+/// An edge that was reclassified or reversed.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReclassifiedEdge {
+    /// The page (and heading) of the edge's source genre in the data.
+    pub source: PageName,
+    /// The page (and heading) of the edge's target genre in the data.
+    pub target: PageName,
+    /// The edge's type in the data (e.g. `fusion_genre`).
+    pub from: &'static str,
+    /// The type it was given.
+    pub to: &'static str,
+    /// Whether its source and target were swapped.
+    pub reversed: bool,
+    /// Why it was changed.
+    pub note: Option<String>,
+    /// Whether it was changed; it isn't if the data no longer has the edge, in which case the
+    /// patch can be deleted.
+    pub applied: bool,
+}
+
+impl PatchReport {
+    /// Record that `page`, a `kind` (`genre` or `artist`), was excluded by `patches`.
+    pub fn record_exclusion(&mut self, patches: &DataPatches, page: &PageName, kind: &'static str) {
+        let exclusion = &patches.exclusions[page];
+        self.excluded.push(ExcludedPage {
+            page: page.clone(),
+            kind,
+            reason: exclusion.reason,
+            note: exclusion.note.clone(),
+        });
+    }
+
+    /// Record that `patch` was applied to an edge, or that there was no edge to apply it to.
+    pub fn record_edge_patch(&mut self, patch: &EdgePatch, applied: bool) {
+        self.reclassified_edges.push(ReclassifiedEdge {
+            source: patch.source.clone(),
+            target: patch.target.clone(),
+            from: patch.ty.name(),
+            to: patch.new_ty.name(),
+            reversed: patch.reverse,
+            note: patch.note.clone(),
+            applied,
+        });
+    }
+
+    /// Fill in the exclusions that weren't used and the stale patches, once everything has been
+    /// processed.
+    pub fn finish(
+        &mut self,
+        patches: &DataPatches,
+        processed_genres: &ProcessedGenres,
+        processed_artists: &ProcessedArtists,
+    ) {
+        self.unused_exclusions = patches
+            .exclusions
+            .keys()
+            .filter(|page| !self.excluded.iter().any(|excluded| excluded.page == **page))
+            .cloned()
+            .collect();
+        self.stale = find_stale(patches, processed_genres, processed_artists);
+    }
+
+    /// Write the report to `path`, and say what needs looking at.
+    pub fn write(&self, path: &Path) -> anyhow::Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+            .context("Failed to write patch report")?;
+        let unneeded_inclusions = self.included.iter().filter(|page| !page.applied).count();
+        let unneeded_edge_patches = self
+            .reclassified_edges
+            .iter()
+            .filter(|edge| !edge.applied)
+            .count();
+        let matching = self.stale.iter().filter(|patch| patch.matches).count();
+        if !self.stale.is_empty()
+            || !self.unused_exclusions.is_empty()
+            || unneeded_inclusions > 0
+            || unneeded_edge_patches > 0
+        {
+            println!(
+                "{} patches no longer apply ({matching} now match Wikipedia and can be deleted), \
+                 {} exclusions weren't used, {unneeded_inclusions} inclusions have infoboxes now \
+                 and {unneeded_edge_patches} reclassified edges aren't there; see {}",
+                self.stale.len(),
+                self.unused_exclusions.len(),
+                path.display()
+            );
+        }
+        Ok(())
+    }
+}
+
+/// A patch that no longer applies.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StalePatch {
+    /// The patched page.
+    pub page: PageName,
+    /// What the patch changes: `genre_name`, `artist_name`, `description`, `origin`,
+    /// `add_relationship` or `remove_relationship`.
+    pub kind: &'static str,
+    /// When the fix was made on Wikipedia, according to the patch.
+    pub fixed_at: Timestamp,
+    /// When the page was last revised, which is after the fix.
+    pub last_revision_date: Timestamp,
+    /// Whether the page's data now matches the patch, in which case the patch can be deleted;
+    /// otherwise, the fix may have been reverted.
+    pub matches: bool,
+}
+
+/// Find the patches with a `fixed_at` before the last revision of their page.
+pub fn find_stale(
+    patches: &DataPatches,
+    processed_genres: &ProcessedGenres,
+    processed_artists: &ProcessedArtists,
+) -> Vec<StalePatch> {
+    let mut stale = vec![];
+    let mut check = |page: &PageName,
+                     kind: &'static str,
+                     fixed_at: Option<Timestamp>,
+                     last_revision_date: Timestamp,
+                     matches: bool| {
+        let Some(fixed_at) = fixed_at else {
+            return;
+        };
+        if !data_patches::applies(Some(fixed_at), last_revision_date) {
+            stale.push(StalePatch {
+                page: page.clone(),
+                kind,
+                fixed_at,
+                last_revision_date,
+                matches,
+            });
+        }
+    };
+
+    for (page, (fixed_at, name)) in &patches.genres {
+        if let Some(genre) = processed_genres.0.get(page) {
+            let matches = genre.name == *name;
+            check(
+                page,
+                "genre_name",
+                *fixed_at,
+                genre.last_revision_date,
+                matches,
+            );
+        }
+    }
+    for (page, (fixed_at, name)) in &patches.artists {
+        if let Some(artist) = processed_artists.0.get(page) {
+            let matches = artist.name == *name;
+            check(
+                page,
+                "artist_name",
+                *fixed_at,
+                artist.last_revision_date,
+                matches,
+            );
+        }
+    }
+    for (page, genre_patches) in &patches.genre_patches {
+        let Some(genre) = processed_genres.0.get(page) else {
+            continue;
+        };
+        for (fixed_at, patch) in genre_patches {
+            let (kind, matches) = genre_patch_matches(genre, patch);
+            check(page, kind, *fixed_at, genre.last_revision_date, matches);
+        }
+    }
+    stale
+}
+
+/// The kind of a genre patch, and whether `genre` already matches it.
+fn genre_patch_matches(genre: &ProcessedGenre, patch: &GenrePatch) -> (&'static str, bool) {
+    match patch {
+        GenrePatch::Description(description) => (
+            "description",
+            genre.wikitext_description.as_deref() == Some(description.trim()),
+        ),
+        GenrePatch::OriginDecade(decade) => ("origin", genre.origin_decade == Some(*decade)),
+        GenrePatch::AddRelationship(relationship, target) => (
+            "add_relationship",
+            genre.relationship(*relationship).contains(target),
+        ),
+        GenrePatch::RemoveRelationship(relationship, target) => (
+            "remove_relationship",
+            !genre.relationship(*relationship).contains(target),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn genre(page: &str, name: &str, last_revision_date: &str) -> ProcessedGenre {
+        serde_json::from_value(serde_json::json!({
+            "name": name,
+            "page": page,
+            "wikitext_description": null,
+            "last_revision_date": last_revision_date,
+            "stylistic_origins": [],
+            "derivatives": [],
+            "subgenres": [],
+            "fusion_genres": ["Disco"],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn patches_for_fixes_before_the_last_revision_are_stale() {
+        let fixed_at: Timestamp = "2025-04-26T20:32:00Z".parse().unwrap();
+        let page = |name: &str| PageName::new(name, None);
+        let patches = DataPatches {
+            genres: BTreeMap::from([
+                (
+                    page("Fixed"),
+                    (Some(fixed_at), GenreName("Fixed".to_string())),
+                ),
+                (
+                    page("Reverted"),
+                    (Some(fixed_at), GenreName("Patched".to_string())),
+                ),
+                (
+                    page("Unrevised"),
+                    (Some(fixed_at), GenreName("Patched".to_string())),
+                ),
+                (page("Unclear"), (None, GenreName("Patched".to_string()))),
+            ]),
+            genre_patches: BTreeMap::from([(
+                page("Fixed"),
+                vec![(
+                    Some(fixed_at),
+                    GenrePatch::RemoveRelationship(
+                        data_patches::Relationship::FusionGenre,
+                        "Disco".to_string(),
+                    ),
+                )],
+            )]),
+            ..Default::default()
+        };
+        let genres = ProcessedGenres(
+            [
+                genre("Fixed", "Fixed", "2025-06-01T00:00:00Z"),
+                genre("Reverted", "Reverted", "2025-06-01T00:00:00Z"),
+                genre("Unrevised", "Unrevised", "2025-01-01T00:00:00Z"),
+                genre("Unclear", "Unclear", "2025-06-01T00:00:00Z"),
+            ]
+            .into_iter()
+            .map(|genre| (genre.page.clone(), genre))
+            .collect(),
+        );
+
+        let stale = find_stale(&patches, &genres, &ProcessedArtists(BTreeMap::new()));
+        let summary: Vec<_> = stale
+            .iter()
+            .map(|patch| (patch.page.name.as_str(), patch.kind, patch.matches))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("Fixed", "genre_name", true),
+                ("Reverted", "genre_name", false),
+                ("Fixed", "remove_relationship", false),
+            ]
+        );
+    }
+}
@@ -1,20 +1,80 @@
 //! CLI for populating mixes for genres.
+//!
+//! With a YouTube API key configured, the top search results for each genre are listed in the
+//! terminal to be picked with one key; without one (or if the search fails), the search is opened
+//! in the browser and the chosen mix's URL pasted in.
 use std::{
-    collections::HashSet,
+    collections::{BTreeMap, BTreeSet, HashSet, VecDeque},
     io::Write as _,
     path::Path,
     time::{Duration, Instant},
 };
 
+use anyhow::Context as _;
+use serde::{Deserialize, Serialize};
 use wikitext_util::{InnerTextConfig, nodes_inner_text_with_config, wikipedia_pwt_configuration};
 
-use crate::{extract, process, types::PageName};
+use crate::{
+    extract, links, process,
+    types::{self, MixEntry, MixesFile, PageName},
+};
+
+/// How many search results are offered for each genre, each picked with its number key.
+const CANDIDATE_COUNT: usize = 5;
+
+/// Where the progress of a `populate-mixes` session is kept, so that the next one carries on
+/// from it.
+pub const SESSION_PATH: &str = "output/populate_mixes_session.json";
+
+/// The order genres are asked about in.
+pub enum QueueOrder {
+    /// The most popular first, by each genre's popularity (see [`QueueOrder::by_popularity`]),
+    /// so that the mixes visitors are most likely to see are filled in first.
+    Popularity(BTreeMap<PageName, f32>),
+    /// The most connected first, by number of edges.
+    Edges,
+}
+
+impl QueueOrder {
+    /// Order by popularity, by the same signal as artists are ranked by (see
+    /// [`types::RankingConfig`]), counting the links to and views of each genre's redirects.
+    pub fn by_popularity(
+        processed_genres: &process::ProcessedGenres,
+        page_aliases: &links::PageAliases,
+        inbound_link_counts: &BTreeMap<PageName, usize>,
+        pageviews: Option<&BTreeMap<PageName, usize>>,
+        ranking: &types::RankingConfig,
+    ) -> Self {
+        QueueOrder::Popularity(
+            processed_genres
+                .0
+                .keys()
+                .map(|page| {
+                    let links = page_aliases.aggregated_link_count(page, inbound_link_counts);
+                    let views = pageviews
+                        .map(|pageviews| page_aliases.aggregated_link_count(page, pageviews))
+                        .unwrap_or(0);
+                    (page.clone(), ranking.popularity(links, views))
+                })
+                .collect(),
+        )
+    }
+}
 
-/// Loops over all genres that don't have a mix yet and prompts the user to fill in a mix.
+/// Loops over all genres that don't have a mix yet and prompts the user to fill in a mix,
+/// suggesting some from YouTube's search results if there's a `youtube_api_key`. The genres are
+/// asked about in `order`, most important first.
+///
+/// Genres can also be skipped (not asked about again), deferred (asked about after the rest),
+/// marked as needing help, and the last of these undone; see [`Command`]. What's been skipped,
+/// deferred and done is kept in [`SESSION_PATH`], so that quitting and starting again carries on
+/// where the last session left off.
 pub fn run(
     mixes_path: &Path,
     dump_meta: &extract::DumpMeta,
     processed_genres: &process::ProcessedGenres,
+    youtube_api_key: &str,
+    order: QueueOrder,
 ) -> anyhow::Result<()> {
     let pwt_configuration = wikipedia_pwt_configuration();
 
@@ -25,27 +85,58 @@ pub fn run(
         .map(|p| PageName::unsanitize(&p.file_name().unwrap().to_string_lossy()))
         .collect::<HashSet<_>>();
 
+    let session_path = Path::new(SESSION_PATH);
+    let mut session = Session::load(session_path)?;
+
     let mut needs_filling = processed_genres
         .0
         .values()
         .filter(|pg| !already_existing_mixes.contains(&pg.page))
+        .filter(|pg| !session.skipped.contains(&pg.page))
         .collect::<Vec<_>>();
-    needs_filling.sort_by(|a, b| {
-        a.edge_count()
-            .cmp(&b.edge_count())
-            .then_with(|| a.page.cmp(&b.page))
-    });
+    match &order {
+        QueueOrder::Popularity(popularity) => {
+            let popularity = |pg: &process::ProcessedGenre| {
+                popularity.get(&pg.page).copied().unwrap_or_default()
+            };
+            needs_filling.sort_by(|a, b| {
+                popularity(a)
+                    .total_cmp(&popularity(b))
+                    .then_with(|| a.edge_count().cmp(&b.edge_count()))
+                    .then_with(|| a.page.cmp(&b.page))
+            });
+        }
+        QueueOrder::Edges => needs_filling.sort_by(|a, b| {
+            a.edge_count()
+                .cmp(&b.edge_count())
+                .then_with(|| a.page.cmp(&b.page))
+        }),
+    }
     needs_filling.reverse();
+    // The deferred genres come after the rest, in the order they were deferred
+    let deferred_order = |pg: &process::ProcessedGenre| {
+        session
+            .deferred
+            .iter()
+            .position(|page| page == &pg.page)
+            .map_or(0, |position| position + 1)
+    };
+    needs_filling.sort_by_key(|pg| deferred_order(pg));
+    let mut queue = VecDeque::from(needs_filling);
 
     println!(
-        "{} genres to go, bucko ({} already have mixes)",
-        needs_filling.len(),
-        already_existing_mixes.len()
+        "{} genres to go, bucko ({} already have mixes, {} skipped, {} deferred)",
+        queue.len(),
+        already_existing_mixes.len(),
+        session.skipped.len(),
+        session.deferred.len()
     );
 
+    let client = reqwest::blocking::Client::new();
     let mut total_response_time = Duration::new(0, 0);
+    let mut answered = 0;
 
-    for (index, pg) in needs_filling.iter().enumerate() {
+    while let Some(pg) = queue.pop_front() {
         let mut description = nodes_inner_text_with_config(
             &pwt_configuration
                 .parse(pg.wikitext_description.as_deref().unwrap_or_default())
@@ -65,73 +156,634 @@ pub fn run(
             pg.page.linksafe()
         );
 
-        let avg_response_time = if index > 0 {
-            format!("avg response: {:?}", total_response_time / index as u32)
+        let avg_response_time = if answered > 0 {
+            format!(" (avg response: {:?})", total_response_time / answered)
         } else {
             String::new()
         };
 
         println!(
-            "==> {}/{}: {} ({}) {}{}",
-            index + 1,
-            needs_filling.len(),
+            "==> {} to go: {} ({}) {}{}",
+            queue.len() + 1,
             pg.page,
             wikipedia_page_link,
             description,
-            if index > 0 {
-                format!(" ({avg_response_time})")
-            } else {
-                String::new()
-            }
+            avg_response_time
         );
 
+        // A genre under a heading shares its page with its parent (if that's a genre too) and
+        // its siblings, so they're shown to tell them apart, and the mix can go to the parent
+        let parent = pg
+            .page
+            .heading
+            .as_ref()
+            .and_then(|_| processed_genres.0.get(&pg.page.with_opt_heading(None)));
+        if pg.page.heading.is_some() {
+            let has_mix = |page: &PageName| mixes_path.join(page.sanitize()).is_file();
+            println!(
+                "    Under: {}",
+                std::iter::once(pg.page.name.as_str())
+                    .chain(pg.heading_path.iter().map(String::as_str))
+                    .collect::<Vec<_>>()
+                    .join(" > ")
+            );
+            let on_page = processed_genres
+                .0
+                .values()
+                .filter(|other| other.page.name == pg.page.name && other.page != pg.page)
+                .map(|other| {
+                    let role = if other.page.heading.is_none() {
+                        "the page"
+                    } else {
+                        "a sibling"
+                    };
+                    let mix = if has_mix(&other.page) {
+                        ", has a mix"
+                    } else {
+                        ""
+                    };
+                    format!("{} ({role}{mix})", other.name.0)
+                })
+                .collect::<Vec<_>>();
+            if !on_page.is_empty() {
+                println!("    Also on the page: {}", on_page.join(", "));
+            }
+        }
+
         let genre_name = &pg.name.0;
-        let link = format!(
-            "https://www.youtube.com/results?search_query={}&sp=EgQQARgC",
-            (if genre_name.to_lowercase().contains("music") {
-                format!("\"{genre_name}\" mix")
+        let query = if genre_name.to_lowercase().contains("music") {
+            format!("\"{genre_name}\" mix")
+        } else {
+            format!("\"{genre_name}\" music mix")
+        };
+        let start_time = Instant::now();
+
+        let candidates = if youtube_api_key.is_empty() {
+            vec![]
+        } else {
+            search_candidates(&client, youtube_api_key, &query).unwrap_or_else(|e| {
+                println!("YouTube search failed, so opening it in the browser instead: {e:#}");
+                vec![]
+            })
+        };
+        // The genre whose file the mix goes in: this one, or its parent, with `parent`
+        let mut target = pg;
+        let mut browsing = false;
+        let command = loop {
+            if let Some(parent) = parent {
+                let other = if target.page == pg.page { parent } else { pg };
+                let replacing = if mixes_path.join(target.page.sanitize()).is_file() {
+                    ", replacing its mixes file"
+                } else {
+                    ""
+                };
+                println!(
+                    "    Assigning to {}{replacing} ([p] to assign to {} instead)",
+                    target.page, other.page
+                );
+            }
+            let picked = if browsing {
+                None
             } else {
-                format!("\"{genre_name}\" music mix")
+                pick_candidate(&candidates, parent.is_some())?
+            };
+            let command = match picked {
+                Some(command) => command,
+                None => {
+                    if !browsing {
+                        let link = format!(
+                            "https://www.youtube.com/results?search_query={}&sp=EgQQARgC",
+                            query.replace(" ", "%20").replace("&", "%26")
+                        );
+                        open::that(link)?;
+                        browsing = true;
+                    }
+                    println!(
+                        "Paste the mix's URL, or [s]kip, [d]efer, help[: reason], {}[u]ndo, [q]uit",
+                        if parent.is_some() { "[p]arent, " } else { "" }
+                    );
+                    read_command()?
+                }
+            };
+            if command != Command::Parent {
+                break command;
+            }
+            match parent {
+                Some(parent) if target.page == pg.page => target = parent,
+                Some(_) => target = pg,
+                None => println!("{} isn't under a heading of another genre", pg.page),
+            }
+        };
+        total_response_time += start_time.elapsed();
+        answered += 1;
+
+        let mix_path = mixes_path.join(PageName::sanitize(&pg.page));
+        match command {
+            Command::Mixes(file) if target.page != pg.page => {
+                write_mixes(&mixes_path.join(target.page.sanitize()), file)?;
+                session.deferred.retain(|page| page != &target.page);
+                session.history.push((target.page.clone(), Action::Wrote));
+                queue.retain(|queued| queued.page != target.page);
+                // The subgenre still has no mix of its own
+                println!("Wrote the mix for {}; now for {}", target.page, pg.page);
+                queue.push_front(pg);
+            }
+            Command::Quit => {
+                queue.push_front(pg);
+                break;
+            }
+            Command::Parent => unreachable!("the target is switched while prompting"),
+            Command::Mixes(file) => {
+                write_mixes(&mix_path, file)?;
+                session.deferred.retain(|page| page != &pg.page);
+                session.history.push((pg.page.clone(), Action::Wrote));
+            }
+            Command::Skip => {
+                session.deferred.retain(|page| page != &pg.page);
+                session.skipped.insert(pg.page.clone());
+                session.history.push((pg.page.clone(), Action::Skipped));
+            }
+            Command::Defer => {
+                session.deferred.retain(|page| page != &pg.page);
+                session.deferred.push(pg.page.clone());
+                session.history.push((pg.page.clone(), Action::Deferred));
+                queue.push_back(pg);
+            }
+            Command::Undo => {
+                queue.push_front(pg);
+                let Some((page, action)) = session.history.pop() else {
+                    println!("Nothing to undo");
+                    continue;
+                };
+                match action {
+                    Action::Wrote => {
+                        let path = mixes_path.join(PageName::sanitize(&page));
+                        if path.is_file() {
+                            std::fs::remove_file(path)?;
+                        }
+                    }
+                    Action::Skipped => {
+                        session.skipped.remove(&page);
+                    }
+                    Action::Deferred => {
+                        session.deferred.retain(|deferred| deferred != &page);
+                        if let Some(position) = queue.iter().rposition(|pg| pg.page == page) {
+                            queue.remove(position);
+                        }
+                    }
+                }
+                println!("Undid {} {page}", action.describe());
+                match processed_genres.0.get(&page) {
+                    Some(undone) => queue.push_front(undone),
+                    None => println!("{page} isn't a genre any more, so it won't be asked about"),
+                }
+            }
+        }
+        session.save(session_path)?;
+    }
+
+    session.save(session_path)?;
+    println!(
+        "{} genres left ({} deferred); progress saved to {session_path:?}",
+        queue.len(),
+        session.deferred.len()
+    );
+
+    Ok(())
+}
+
+/// What to do with a genre.
+#[derive(Debug, PartialEq)]
+enum Command {
+    /// Write its mixes file (with a mix, or saying it needs help finding one).
+    Mixes(MixesFile),
+    /// Don't ask about it again.
+    Skip,
+    /// Ask about it after all the others.
+    Defer,
+    /// Undo what was last done, and ask about that genre again.
+    Undo,
+    /// Switch between giving the mix to a genre under a heading and to the genre of its page.
+    Parent,
+    /// Stop, keeping the progress for the next session.
+    Quit,
+}
+
+/// What was done to a genre in a session, so that it can be undone.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+enum Action {
+    Wrote,
+    Skipped,
+    Deferred,
+}
+
+impl Action {
+    fn describe(self) -> &'static str {
+        match self {
+            Action::Wrote => "writing the mixes of",
+            Action::Skipped => "skipping",
+            Action::Deferred => "deferring",
+        }
+    }
+}
+
+/// The progress of `populate-mixes` sessions, kept in [`SESSION_PATH`].
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Session {
+    /// Genres not to ask about again.
+    skipped: BTreeSet<PageName>,
+    /// Genres to ask about after the rest, in the order they were deferred.
+    deferred: Vec<PageName>,
+    /// What was done to each genre, most recent last.
+    history: Vec<(PageName, Action)>,
+}
+
+impl Session {
+    fn load(path: &Path) -> anyhow::Result<Self> {
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+        serde_json::from_slice(&std::fs::read(path)?)
+            .with_context(|| format!("Failed to parse {path:?}"))
+    }
+
+    fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {path:?}"))
+    }
+}
+
+/// Offer the `candidates` to be picked with a key each, or `None` to browse the search instead
+/// (as there are no candidates, or because the user asked to). With `has_parent`, the mix can
+/// also be given to the genre's parent.
+fn pick_candidate(candidates: &[Candidate], has_parent: bool) -> anyhow::Result<Option<Command>> {
+    if candidates.is_empty() {
+        return Ok(None);
+    }
+    for (number, candidate) in candidates.iter().enumerate() {
+        println!("  {}. {candidate}", number + 1);
+    }
+    print!(
+        "[1-{}] pick, [b]rowse the search, [s]kip, [d]efer, [h]elp, {}[u]ndo, [q]uit: ",
+        candidates.len(),
+        if has_parent { "[p]arent, " } else { "" }
+    );
+    std::io::stdout().flush()?;
+    let choice = loop {
+        let key = read_key()?;
+        let is_candidate = key
+            .to_digit(10)
+            .is_some_and(|number| (1..=candidates.len()).contains(&(number as usize)));
+        if is_candidate
+            || matches!(key, 'b' | 's' | 'd' | 'h' | 'u' | 'q')
+            || (has_parent && key == 'p')
+        {
+            break key;
+        }
+    };
+    println!("{choice}");
+    Ok(Some(match choice {
+        'b' => return Ok(None),
+        's' => Command::Skip,
+        'd' => Command::Defer,
+        'u' => Command::Undo,
+        'p' => Command::Parent,
+        'q' => Command::Quit,
+        'h' => {
+            print!("Why? (blank for no reason) ");
+            std::io::stdout().flush()?;
+            let mut reason = String::new();
+            std::io::stdin().read_line(&mut reason)?;
+            Command::Mixes(MixesFile {
+                help: Some(reason.trim().to_string()),
+                mixes: vec![],
             })
-            .replace(" ", "%20")
-            .replace("&", "%26")
-        );
-        open::that(link)?;
+        }
+        number => {
+            let candidate = &candidates[number.to_digit(10).unwrap() as usize - 1];
+            Command::Mixes(MixesFile {
+                help: None,
+                mixes: vec![MixEntry {
+                    url: format!("https://www.youtube.com/watch?v={}", candidate.video_id),
+                    title: Some(candidate.title.clone()),
+                    duration: parse_duration(&candidate.duration),
+                    ..Default::default()
+                }],
+            })
+        }
+    }))
+}
 
+/// Read lines until one is a command (see [`parse_command`]).
+fn read_command() -> anyhow::Result<Command> {
+    loop {
         print!("> ");
         std::io::stdout().flush()?;
-
-        let start_time = Instant::now();
         let mut line = String::new();
+        if std::io::stdin().read_line(&mut line)? == 0 {
+            // Standard input was closed
+            return Ok(Command::Quit);
+        }
+        match parse_command(&line) {
+            Ok(Some(command)) => return Ok(command),
+            Ok(None) => {}
+            Err(problem) => println!("{problem}"),
+        }
+    }
+}
 
-        loop {
-            line.clear();
-            std::io::stdin().read_line(&mut line)?;
-
-            if line.trim() == "finish" {
-                break;
+/// A typed command: a mix's URL, `help` (or `help: <reason>`) as in the plain text mixes
+/// format, or one of `skip`, `defer`, `undo`, `parent` and `quit` (or their first letters). A
+/// blank line is `None`.
+fn parse_command(line: &str) -> Result<Option<Command>, String> {
+    let line = line.trim();
+    Ok(Some(match line {
+        "" => return Ok(None),
+        "s" | "skip" => Command::Skip,
+        "d" | "defer" => Command::Defer,
+        "u" | "undo" => Command::Undo,
+        "p" | "parent" => Command::Parent,
+        "q" | "quit" => Command::Quit,
+        _ => {
+            let (file, _) = MixesFile::from_plain_text(line);
+            if file.help.is_none() && file.mixes.is_empty() {
+                return Err(format!(
+                    "{line:?} isn't a mix's URL, help[: reason], or one of [s]kip, [d]efer, [u]ndo, [p]arent and [q]uit"
+                ));
             }
+            Command::Mixes(file)
+        }
+    }))
+}
 
-            if !line.trim().is_empty() {
-                break;
-            }
+/// Write a genre's mixes file, with its mixes added today.
+fn write_mixes(mix_path: &Path, mut file: MixesFile) -> anyhow::Result<()> {
+    let today: toml::value::Datetime = jiff::Zoned::now().date().to_string().parse()?;
+    for mix in &mut file.mixes {
+        mix.added = Some(today);
+    }
+    std::fs::write(mix_path, file.to_toml(&[])?)?;
+    Ok(())
+}
 
-            print!("> ");
-            std::io::stdout().flush()?;
+/// A search result offered as a genre's mix.
+struct Candidate {
+    video_id: String,
+    title: String,
+    channel: String,
+    /// The video's length, as YouTube gives it (ISO 8601, e.g. `PT1H2M3S`).
+    duration: String,
+    view_count: Option<u64>,
+}
+
+impl std::fmt::Display for Candidate {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{} - {} ({}",
+            self.title,
+            self.channel,
+            format_duration(&self.duration)
+        )?;
+        if let Some(view_count) = self.view_count {
+            write!(f, ", {} views", format_count(view_count))?;
         }
+        write!(f, ")")
+    }
+}
+
+/// The top [`CANDIDATE_COUNT`] long videos YouTube finds for `query`, with their lengths and
+/// view counts (which the search doesn't give, so they're looked up after).
+fn search_candidates(
+    client: &reqwest::blocking::Client,
+    key: &str,
+    query: &str,
+) -> anyhow::Result<Vec<Candidate>> {
+    #[derive(Debug, Deserialize)]
+    struct SearchResponse {
+        items: Vec<SearchResult>,
+    }
+    #[derive(Debug, Deserialize)]
+    struct SearchResult {
+        id: SearchResultId,
+        snippet: SearchResultSnippet,
+    }
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct SearchResultId {
+        video_id: String,
+    }
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct SearchResultSnippet {
+        title: String,
+        channel_title: String,
+    }
+    let search = client
+        .get("https://www.googleapis.com/youtube/v3/search")
+        .query(&[
+            ("part", "snippet"),
+            ("type", "video"),
+            // Matches the browser search's filter (`sp=EgQQARgC`): videos over 20 minutes
+            ("videoDuration", "long"),
+            ("maxResults", &CANDIDATE_COUNT.to_string()),
+            ("q", query),
+            ("key", key),
+        ])
+        .send()?
+        .error_for_status()?
+        .json::<SearchResponse>()?;
+    if search.items.is_empty() {
+        return Ok(vec![]);
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct VideosResponse {
+        items: Vec<Video>,
+    }
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Video {
+        id: String,
+        content_details: VideoContentDetails,
+        #[serde(default)]
+        statistics: VideoStatistics,
+    }
+    #[derive(Debug, Deserialize)]
+    struct VideoContentDetails {
+        duration: String,
+    }
+    #[derive(Debug, Default, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct VideoStatistics {
+        /// A number, as a string; left out if the uploader hides it.
+        view_count: Option<String>,
+    }
+    let ids = search
+        .items
+        .iter()
+        .map(|item| item.id.video_id.as_str())
+        .collect::<Vec<_>>()
+        .join(",");
+    let videos = client
+        .get("https://www.googleapis.com/youtube/v3/videos")
+        .query(&[
+            ("part", "contentDetails,statistics"),
+            ("id", &ids),
+            ("key", key),
+        ])
+        .send()?
+        .error_for_status()?
+        .json::<VideosResponse>()?
+        .items
+        .into_iter()
+        .map(|video| (video.id.clone(), video))
+        .collect::<BTreeMap<_, _>>();
+
+    Ok(search
+        .items
+        .into_iter()
+        .map(|item| {
+            let video = videos.get(&item.id.video_id);
+            Candidate {
+                title: unescape_html(&item.snippet.title),
+                channel: unescape_html(&item.snippet.channel_title),
+                duration: video
+                    .map(|video| video.content_details.duration.clone())
+                    .unwrap_or_default(),
+                view_count: video
+                    .and_then(|video| video.statistics.view_count.as_deref())
+                    .and_then(|count| count.parse().ok()),
+                video_id: item.id.video_id,
+            }
+        })
+        .collect())
+}
 
-        let response_time = start_time.elapsed();
+/// Undo the escaping of the characters that YouTube escapes in search results' titles.
+fn unescape_html(text: &str) -> String {
+    text.replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
 
-        total_response_time += response_time;
+/// An ISO 8601 duration (`PT1H2M3S`) in seconds, if it is one.
+fn parse_duration(duration: &str) -> Option<u32> {
+    let mut rest = duration.strip_prefix("PT")?;
+    let mut total = 0;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let value = rest[..digits].parse::<u32>().ok()?;
+        total += value
+            * match rest[digits..].chars().next()? {
+                'H' => 3600,
+                'M' => 60,
+                'S' => 1,
+                _ => return None,
+            };
+        rest = &rest[digits + 1..];
+    }
+    Some(total)
+}
 
-        if let Some(amp_idx) = line.find('&') {
-            line.truncate(amp_idx);
+/// An ISO 8601 duration (`PT1H2M3S`) as a clock time (`1:02:03`), or `?` if it isn't one.
+fn format_duration(duration: &str) -> String {
+    let Some(total) = parse_duration(duration) else {
+        return "?".to_string();
+    };
+    let (hours, minutes, seconds) = (total / 3600, total / 60 % 60, total % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes}:{seconds:02}")
+    }
+}
+
+/// `count`, shortened to thousands or millions (`12K`, `1.2M`).
+fn format_count(count: u64) -> String {
+    match count {
+        0..1_000 => count.to_string(),
+        1_000..1_000_000 => format!("{}K", count / 1_000),
+        _ => format!("{:.1}M", count as f64 / 1_000_000.0),
+    }
+}
+
+/// Read one key press, without waiting for enter. If stdin isn't a terminal (or this isn't
+/// Unix), the first character of a line is read instead.
+fn read_key() -> anyhow::Result<char> {
+    #[cfg(unix)]
+    {
+        use rustix::termios::{self, LocalModes, OptionalActions, SpecialCodeIndex};
+        use std::io::Read as _;
+
+        let stdin = std::io::stdin();
+        if let Ok(original) = termios::tcgetattr(&stdin) {
+            let mut raw = original.clone();
+            raw.local_modes
+                .remove(LocalModes::ICANON | LocalModes::ECHO);
+            raw.special_codes[SpecialCodeIndex::VMIN] = 1;
+            raw.special_codes[SpecialCodeIndex::VTIME] = 0;
+            termios::tcsetattr(&stdin, OptionalActions::Now, &raw)?;
+            let mut byte = [0];
+            let read = stdin.lock().read_exact(&mut byte);
+            termios::tcsetattr(&stdin, OptionalActions::Now, &original)?;
+            read?;
+            return Ok(byte[0] as char);
         }
-        line = line.trim().to_string();
+    }
+    let mut line = String::new();
+    std::io::stdin().read_line(&mut line)?;
+    Ok(line.trim().chars().next().unwrap_or('\n'))
+}
 
-        let mix_path = mixes_path.join(PageName::sanitize(&pg.page));
-        std::fs::write(mix_path, line)?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations_and_counts_are_shortened() {
+        assert_eq!(format_duration("PT1H2M3S"), "1:02:03");
+        assert_eq!(format_duration("PT45M"), "45:00");
+        assert_eq!(format_duration("PT59S"), "0:59");
+        assert_eq!(format_duration("P1DT2H"), "?");
+        assert_eq!(parse_duration("PT1H2M3S"), Some(3723));
+        assert_eq!(format_count(999), "999");
+        assert_eq!(format_count(12_345), "12K");
+        assert_eq!(format_count(1_234_567), "1.2M");
     }
 
-    Ok(())
+    #[test]
+    fn commands_are_parsed() {
+        assert_eq!(parse_command("  \n"), Ok(None));
+        assert_eq!(parse_command("s\n"), Ok(Some(Command::Skip)));
+        assert_eq!(parse_command("defer"), Ok(Some(Command::Defer)));
+        assert_eq!(parse_command("u"), Ok(Some(Command::Undo)));
+        assert_eq!(parse_command("parent"), Ok(Some(Command::Parent)));
+        assert_eq!(parse_command("quit"), Ok(Some(Command::Quit)));
+        assert_eq!(
+            parse_command("help: only one song exists"),
+            Ok(Some(Command::Mixes(MixesFile {
+                help: Some("only one song exists".to_string()),
+                mixes: vec![],
+            })))
+        );
+        assert_eq!(
+            parse_command("https://www.youtube.com/watch?v=abc&list=PL1&index=2"),
+            Ok(Some(Command::Mixes(MixesFile {
+                help: None,
+                mixes: vec![MixEntry {
+                    url: "https://www.youtube.com/playlist?list=PL1".to_string(),
+                    ..Default::default()
+                }],
+            })))
+        );
+        assert!(parse_command("finish").is_err());
+    }
 }
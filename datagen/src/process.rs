@@ -1,11 +1,10 @@
 //! Processes the wikitext for each genre page to extract the genre infobox's information.
 use std::{
-    collections::{BTreeMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     path::Path,
     sync::{LazyLock, atomic::AtomicUsize},
 };
 
-use jiff::ToSpan as _;
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use wikitext_util::{
@@ -14,17 +13,35 @@ use wikitext_util::{
 };
 
 use crate::{
-    data_patches, extract,
+    countries,
+    data_patches::{self, DataPatches, GenrePatch, Patch, Relationship},
+    extract,
+    patch_report::{IncludedPage, MergedPage, PatchReport},
     types::{ArtistName, GenreName, PageName},
+    util,
 };
 
+/// Bump this whenever processing changes in a way that affects the processed output, so that
+/// pages reused from a previous output (see [`ProcessedPage::wikitext_hash`]) are regenerated.
+const PROCESSING_VERSION: u32 = 13;
+
 trait ProcessedPage:
     Send + Sync + Clone + std::fmt::Debug + serde::Serialize + for<'de> serde::Deserialize<'de>
 {
     type NameType: Clone;
     fn name(&self) -> &PageName;
     fn update_description(&mut self, description: String);
+    fn description(&self) -> Option<&str>;
     fn get_display_name(&self) -> String;
+    fn wikitext_hash(&self) -> Option<u64>;
+    fn set_wikitext_hash(&mut self, hash: u64);
+    fn set_revision_id(&mut self, revision_id: Option<u64>);
+    /// Record the links in the page's "See also" section; ignored by default.
+    fn set_see_also(&mut self, _see_also: Vec<String>) {}
+    /// Record the navboxes transcluded at the bottom of the page; ignored by default.
+    fn set_navboxes(&mut self, _navboxes: Vec<String>) {}
+    /// Record the categories the page is in; ignored by default.
+    fn set_categories(&mut self, _categories: Vec<String>) {}
 
     fn save(&self, processed_path: &Path) -> anyhow::Result<()> {
         std::fs::write(
@@ -42,14 +59,27 @@ pub struct ProcessedGenre {
     pub name: GenreName,
     /// The page name of the genre.
     pub page: PageName,
+    /// The headings enclosing the genre's infobox, outermost first; empty for the page root.
+    ///
+    /// The last heading is the same as `page.heading`.
+    #[serde(default)]
+    pub heading_path: Vec<String>,
     /// The description of the genre, extracted from the page.
     ///
     /// This is all text after the infobox to the next heading.
     /// There are some nuances around what "after" means; we
     /// bodge the extraction to handle the case where the infobox was misplaced.
+    /// If that yields nothing, the page's lead section is used instead.
     pub wikitext_description: Option<String>,
     /// The timestamp of the last revision of the page.
     pub last_revision_date: jiff::Timestamp,
+    /// The ID of the last revision of the page, which the text was taken from.
+    #[serde(default)]
+    pub revision_id: Option<u64>,
+    /// A hash of the page's wikitext and the patches to its name, used to skip reprocessing
+    /// unchanged pages in later dumps.
+    #[serde(default)]
+    pub wikitext_hash: Option<u64>,
     // the following are unresolved links: we do this
     // so that we can defer link resolution to the end of the pipeline
     // to make sure we've gotten the links to headings under pages
@@ -61,6 +91,30 @@ pub struct ProcessedGenre {
     pub subgenres: Vec<String>,
     /// Fusion genres of the genre.
     pub fusion_genres: Vec<String>,
+    /// Links from the page's "See also" section, if this is the page's main genre.
+    ///
+    /// These are looser than the relationships above, and are only used for `Related` edges.
+    #[serde(default)]
+    pub see_also: Vec<String>,
+    /// Names of the navboxes (e.g. `Electronic dance music`) at the bottom of the page, if this is
+    /// the page's main genre. Genres sharing a navbox belong to the same family.
+    #[serde(default)]
+    pub navboxes: Vec<String>,
+    /// The decade the genre originated in (e.g. `1970` for the 1970s), parsed from its cultural origins.
+    #[serde(default)]
+    pub origin_decade: Option<i32>,
+    /// The countries the genre originated in, parsed from its cultural origins.
+    #[serde(default)]
+    pub origin_countries: Vec<String>,
+    /// Other names for the genre, from the infobox's `other_names`.
+    #[serde(default)]
+    pub other_names: Vec<String>,
+    /// The file name of the infobox's image (e.g. `Techno DJ.jpg`), without the `File:` prefix.
+    #[serde(default)]
+    pub image: Option<String>,
+    /// The pages of the genres merged into this one by patches, as they're the same genre.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub merged_pages: Vec<PageName>,
 }
 impl ProcessedPage for ProcessedGenre {
     type NameType = GenreName;
@@ -70,9 +124,27 @@ impl ProcessedPage for ProcessedGenre {
     fn update_description(&mut self, description: String) {
         self.wikitext_description = Some(description.trim().to_string());
     }
+    fn description(&self) -> Option<&str> {
+        self.wikitext_description.as_deref()
+    }
     fn get_display_name(&self) -> String {
         self.name.0.clone()
     }
+    fn wikitext_hash(&self) -> Option<u64> {
+        self.wikitext_hash
+    }
+    fn set_wikitext_hash(&mut self, hash: u64) {
+        self.wikitext_hash = Some(hash);
+    }
+    fn set_revision_id(&mut self, revision_id: Option<u64>) {
+        self.revision_id = revision_id;
+    }
+    fn set_see_also(&mut self, see_also: Vec<String>) {
+        self.see_also = see_also;
+    }
+    fn set_navboxes(&mut self, navboxes: Vec<String>) {
+        self.navboxes = navboxes;
+    }
 }
 impl ProcessedGenre {
     /// The number of edges in the genre's graph.
@@ -82,77 +154,277 @@ impl ProcessedGenre {
             + self.subgenres.len()
             + self.fusion_genres.len()
     }
+
+    /// The (unresolved) links in one of the genre's relationship lists.
+    pub fn relationship(&self, relationship: Relationship) -> &[String] {
+        match relationship {
+            Relationship::StylisticOrigin => &self.stylistic_origins,
+            Relationship::Derivative => &self.derivatives,
+            Relationship::Subgenre => &self.subgenres,
+            Relationship::FusionGenre => &self.fusion_genres,
+        }
+    }
+
+    fn relationship_mut(&mut self, relationship: Relationship) -> &mut Vec<String> {
+        match relationship {
+            Relationship::StylisticOrigin => &mut self.stylistic_origins,
+            Relationship::Derivative => &mut self.derivatives,
+            Relationship::Subgenre => &mut self.subgenres,
+            Relationship::FusionGenre => &mut self.fusion_genres,
+        }
+    }
 }
 
 /// A map of page names to their processed genre.
 pub struct ProcessedGenres(pub BTreeMap<PageName, ProcessedGenre>);
 /// Given raw genre wikitext, extract the relevant information and save it to file.
+///
+/// If `previous_output_path` is provided, genres whose wikitext is unchanged since that
+/// output was produced are reused instead of being reprocessed.
 pub fn genres(
     start: std::time::Instant,
     genres: &extract::GenrePages,
     processed_genres_path: &Path,
+    previous_output_path: Option<&Path>,
+    patches: &DataPatches,
+    report: &mut PatchReport,
+    dump_page: Option<&str>,
 ) -> anyhow::Result<ProcessedGenres> {
-    let all_patches = data_patches::genre_all();
-
     let genre_processor = |parameters: BTreeMap<String, &[pwt::Node]>,
                            original_page: &PageName,
-                           last_heading: Option<String>,
+                           heading_path: &[String],
                            timestamp: jiff::Timestamp|
      -> ProcessedGenre {
         let mut name = extract_name_from_parameter(parameters.get("name").copied(), original_page);
 
-        if let Some((patch_timestamp, new_name)) = all_patches.get(original_page) {
-            // Check whether the article has been updated since the last revision date
-            // with one minute of leeway. If it has, don't apply the patch.
-            if patch_timestamp
-                .map(|ts| timestamp.saturating_add(1.minute()) < ts)
-                .unwrap_or(true)
-            {
-                name = new_name.0.clone();
-            }
+        if let Some(new_name) =
+            data_patches::patched_name(&patches.genres, original_page, timestamp)
+        {
+            name = new_name.0.clone();
         }
 
         let stylistic_origins = parameters
             .get("stylistic_origins")
-            .map(|ns| get_links_from_nodes(ns))
+            .map(|ns| get_list_entries_from_nodes(ns))
             .unwrap_or_default();
         let derivatives = parameters
             .get("derivatives")
-            .map(|ns| get_links_from_nodes(ns))
+            .map(|ns| get_list_entries_from_nodes(ns))
             .unwrap_or_default();
         let subgenres = parameters
             .get("subgenres")
-            .map(|ns| get_links_from_nodes(ns))
+            .map(|ns| get_list_entries_from_nodes(ns))
             .unwrap_or_default();
         let fusion_genres = parameters
             .get("fusiongenres")
-            .map(|ns| get_links_from_nodes(ns))
+            .map(|ns| get_list_entries_from_nodes(ns))
+            .unwrap_or_default();
+        let other_names = parameters
+            .get("other_names")
+            .map(|ns| split_plain_text_list(&plain_text_list(ns)))
+            .unwrap_or_default();
+        let image = parameters
+            .get("image")
+            .and_then(|ns| parse_image_file_name(ns));
+        let cultural_origins = parameters
+            .get("cultural_origins")
+            .map(|ns| nodes_inner_text(ns));
+        let origin_decade = cultural_origins.as_deref().and_then(parse_origin_decade);
+        let origin_countries = cultural_origins
+            .as_deref()
+            .map(countries::parse)
             .unwrap_or_default();
 
         ProcessedGenre {
             name: GenreName(name),
-            page: original_page.with_opt_heading(last_heading),
+            page: original_page.with_opt_heading(heading_path.last().cloned()),
+            heading_path: heading_path.to_vec(),
             wikitext_description: None,
             last_revision_date: timestamp,
+            revision_id: None,
+            wikitext_hash: None,
             stylistic_origins,
             derivatives,
             subgenres,
             fusion_genres,
+            see_also: vec![],
+            navboxes: vec![],
+            origin_decade,
+            origin_countries,
+            other_names,
+            image,
+            merged_pages: vec![],
         }
     };
 
-    let processed_genres = process_pages(
+    let mut processed_genres = process_pages(
         start,
         &genres.0,
         processed_genres_path,
+        previous_output_path
+            .map(|p| p.join("processed_genres"))
+            .as_deref(),
+        &patch_hashes(&patches.genres),
+        patches,
+        report,
         "infobox music genre",
         genre_processor,
         "genre",
+        dump_page,
     )?;
+    include_genres(&mut processed_genres, patches, report);
+    apply_genre_patches(&mut processed_genres, &patches.genre_patches);
+    merge_genres(&mut processed_genres, &patches.merges, report);
 
     Ok(ProcessedGenres(processed_genres))
 }
 
+/// Add the genres included from pages without an infobox, unless they have one now.
+fn include_genres(
+    processed_genres: &mut BTreeMap<PageName, ProcessedGenre>,
+    patches: &DataPatches,
+    report: &mut PatchReport,
+) {
+    for (page, inclusion) in &patches.inclusions {
+        let applied = !processed_genres.contains_key(page);
+        if applied {
+            processed_genres.insert(
+                page.clone(),
+                ProcessedGenre {
+                    name: inclusion.name.clone(),
+                    page: page.clone(),
+                    heading_path: page.heading.iter().cloned().collect(),
+                    wikitext_description: inclusion.description.clone(),
+                    last_revision_date: inclusion.as_of,
+                    revision_id: None,
+                    wikitext_hash: None,
+                    stylistic_origins: inclusion.stylistic_origins.clone(),
+                    derivatives: inclusion.derivatives.clone(),
+                    subgenres: inclusion.subgenres.clone(),
+                    fusion_genres: inclusion.fusion_genres.clone(),
+                    see_also: vec![],
+                    navboxes: vec![],
+                    origin_decade: inclusion.origin_decade,
+                    origin_countries: vec![],
+                    other_names: vec![],
+                    image: None,
+                    merged_pages: vec![],
+                },
+            );
+        }
+        report.included.push(IncludedPage {
+            page: page.clone(),
+            name: inclusion.name.clone(),
+            reason: inclusion.reason.clone(),
+            applied,
+        });
+    }
+}
+
+/// Merge each genre in `merges` into the genre it's the same as: its relationships, other names,
+/// origins and navboxes are added to the other genre's, and it fills in what the other genre is
+/// missing. Its name becomes one of the other genre's other names.
+fn merge_genres(
+    processed_genres: &mut BTreeMap<PageName, ProcessedGenre>,
+    merges: &BTreeMap<PageName, PageName>,
+    report: &mut PatchReport,
+) {
+    for (page, into) in merges {
+        if !processed_genres.contains_key(into) {
+            println!("warning: there's no genre at {into} to merge {page} into");
+            continue;
+        }
+        let Some(merged) = processed_genres.remove(page) else {
+            println!("warning: there's no genre at {page} to merge into {into}");
+            continue;
+        };
+        let genre = processed_genres.get_mut(into).unwrap();
+        let extend = |links: &mut Vec<String>, merged: Vec<String>| {
+            for link in merged {
+                if !links.contains(&link) {
+                    links.push(link);
+                }
+            }
+        };
+        extend(&mut genre.stylistic_origins, merged.stylistic_origins);
+        extend(&mut genre.derivatives, merged.derivatives);
+        extend(&mut genre.subgenres, merged.subgenres);
+        extend(&mut genre.fusion_genres, merged.fusion_genres);
+        extend(&mut genre.see_also, merged.see_also);
+        extend(&mut genre.navboxes, merged.navboxes);
+        extend(&mut genre.origin_countries, merged.origin_countries);
+        extend(
+            &mut genre.other_names,
+            std::iter::once(merged.name.0.clone())
+                .chain(merged.other_names)
+                .filter(|name| *name != genre.name.0)
+                .collect(),
+        );
+        if genre.wikitext_description.is_none() {
+            genre.wikitext_description = merged.wikitext_description;
+        }
+        genre.origin_decade = genre.origin_decade.or(merged.origin_decade);
+        genre.image = genre.image.take().or(merged.image);
+        genre.merged_pages.push(page.clone());
+        report.merged.push(MergedPage {
+            page: page.clone(),
+            name: merged.name,
+            into: into.clone(),
+        });
+    }
+}
+
+/// Apply the patches to the genres' descriptions, origins and relationships that apply to their
+/// revisions, warning about those that no longer have anything to patch.
+fn apply_genre_patches(
+    processed_genres: &mut BTreeMap<PageName, ProcessedGenre>,
+    patches: &BTreeMap<PageName, Vec<data_patches::Patch<GenrePatch>>>,
+) {
+    for (page, genre_patches) in patches {
+        let Some(genre) = processed_genres.get_mut(page) else {
+            println!("warning: there's no genre at {page} to patch");
+            continue;
+        };
+        for (fixed_at, patch) in genre_patches {
+            if !data_patches::applies(*fixed_at, genre.last_revision_date) {
+                continue;
+            }
+            match patch {
+                GenrePatch::Description(description) => {
+                    genre.wikitext_description = Some(description.clone());
+                }
+                GenrePatch::OriginDecade(decade) => genre.origin_decade = Some(*decade),
+                GenrePatch::AddRelationship(relationship, target) => {
+                    let links = genre.relationship_mut(*relationship);
+                    if !links.contains(target) {
+                        links.push(target.clone());
+                    }
+                }
+                GenrePatch::RemoveRelationship(relationship, target) => {
+                    let links = genre.relationship_mut(*relationship);
+                    let count = links.len();
+                    links.retain(|link| link != target);
+                    if links.len() == count {
+                        println!(
+                            "warning: {page} has no {relationship:?} link to {target} to remove"
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The artist infobox parameters that link to closely tied acts.
+const ASSOCIATED_ACT_PARAMETERS: &[&str] = &[
+    "associated_acts",
+    "current_members",
+    "past_members",
+    "members",
+    "spinoffs",
+    "spinoff_of",
+];
+
 /// A processed artist containing all the information we can extract from the infobox.
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ProcessedArtist {
@@ -160,19 +432,42 @@ pub struct ProcessedArtist {
     pub name: ArtistName,
     /// The page name of the artist.
     pub page: PageName,
+    /// The headings enclosing the artist's infobox, outermost first; empty for the page root.
+    ///
+    /// The last heading is the same as `page.heading`.
+    #[serde(default)]
+    pub heading_path: Vec<String>,
     /// The description of the artist, extracted from the page.
     ///
     /// This is all text after the infobox to the next heading.
     /// There are some nuances around what "after" means; we
     /// bodge the extraction to handle the case where the infobox was misplaced.
+    /// If that yields nothing, the page's lead section is used instead.
     pub wikitext_description: Option<String>,
     /// The timestamp of the last revision of the page.
     pub last_revision_date: jiff::Timestamp,
+    /// The ID of the last revision of the page, which the text was taken from.
+    #[serde(default)]
+    pub revision_id: Option<u64>,
+    /// A hash of the page's wikitext and the patches to its name, used to skip reprocessing
+    /// unchanged pages in later dumps.
+    #[serde(default)]
+    pub wikitext_hash: Option<u64>,
     // the following are unresolved links: we do this
     // so that we can defer link resolution to the end of the pipeline
     // to make sure we've gotten the links to headings under pages
     /// Genres of the artist.
     pub genres: Vec<String>,
+    /// Acts closely tied to the artist: associated acts, members, and spinoffs.
+    #[serde(default)]
+    pub associated_acts: Vec<String>,
+    /// The decades the artist was active in (e.g. 1990 for the 1990s), from `years_active`.
+    #[serde(default)]
+    pub active_decades: Vec<i32>,
+    /// The categories the artist's page is in (e.g. `English house musicians`), without the
+    /// `Category:` prefix. Used to infer genres when the infobox doesn't list any.
+    #[serde(default)]
+    pub categories: Vec<String>,
 }
 impl ProcessedPage for ProcessedArtist {
     type NameType = ArtistName;
@@ -182,37 +477,52 @@ impl ProcessedPage for ProcessedArtist {
     fn update_description(&mut self, description: String) {
         self.wikitext_description = Some(description.trim().to_string());
     }
+    fn description(&self) -> Option<&str> {
+        self.wikitext_description.as_deref()
+    }
     fn get_display_name(&self) -> String {
         self.name.0.clone()
     }
+    fn wikitext_hash(&self) -> Option<u64> {
+        self.wikitext_hash
+    }
+    fn set_wikitext_hash(&mut self, hash: u64) {
+        self.wikitext_hash = Some(hash);
+    }
+    fn set_revision_id(&mut self, revision_id: Option<u64>) {
+        self.revision_id = revision_id;
+    }
+    fn set_categories(&mut self, categories: Vec<String>) {
+        self.categories = categories;
+    }
 }
 
 /// A map of page names to their processed artist.
 pub struct ProcessedArtists(pub BTreeMap<PageName, ProcessedArtist>);
 /// Given raw artist wikitext, extract the relevant information and save it to file.
+///
+/// If `previous_output_path` is provided, artists whose wikitext is unchanged since that
+/// output was produced are reused instead of being reprocessed.
 pub fn artists(
     start: std::time::Instant,
     artists: &extract::ArtistPages,
     processed_artists_path: &Path,
+    previous_output_path: Option<&Path>,
+    patches: &DataPatches,
+    report: &mut PatchReport,
+    dump_page: Option<&str>,
 ) -> anyhow::Result<ProcessedArtists> {
-    let all_patches = data_patches::artist_all();
-
     let artist_processor = |parameters: BTreeMap<String, &[pwt::Node]>,
                             original_page: &PageName,
-                            last_heading: Option<String>,
+                            heading_path: &[String],
                             timestamp: jiff::Timestamp|
      -> ProcessedArtist {
         let mut name = extract_name_from_parameter(parameters.get("name").copied(), original_page);
 
-        if let Some((patch_timestamp, new_name)) = all_patches.get(original_page) {
-            // Check whether the article has been updated since the last revision date
-            // with one minute of leeway. If it has, don't apply the patch.
-            if patch_timestamp
-                .map(|ts| timestamp.saturating_add(1.minute()) < ts)
-                .unwrap_or(true)
-            {
-                name = new_name.0.clone();
-            }
+        if let Some(new_name) =
+            data_patches::patched_name(&patches.artists, original_page, timestamp)
+        {
+            name = new_name.0.clone();
         }
 
         let genres = parameters
@@ -220,12 +530,34 @@ pub fn artists(
             .map(|ns| get_links_from_nodes(ns))
             .unwrap_or_default();
 
+        let associated_acts = ASSOCIATED_ACT_PARAMETERS
+            .iter()
+            .filter_map(|parameter| parameters.get(*parameter))
+            .flat_map(|ns| get_links_from_nodes(ns))
+            .collect();
+
+        let active_decades = parameters
+            .get("years_active")
+            .map(|ns| {
+                parse_active_decades(
+                    &plain_text_list(ns),
+                    timestamp.to_zoned(jiff::tz::TimeZone::UTC).year() as i32,
+                )
+            })
+            .unwrap_or_default();
+
         ProcessedArtist {
             name: ArtistName(name),
-            page: original_page.with_opt_heading(last_heading),
+            page: original_page.with_opt_heading(heading_path.last().cloned()),
+            heading_path: heading_path.to_vec(),
             wikitext_description: None,
             last_revision_date: timestamp,
+            revision_id: None,
+            wikitext_hash: None,
             genres,
+            associated_acts,
+            active_decades,
+            categories: vec![],
         }
     };
 
@@ -233,29 +565,58 @@ pub fn artists(
         start,
         &artists.0,
         processed_artists_path,
+        previous_output_path
+            .map(|p| p.join("processed_artists"))
+            .as_deref(),
+        &patch_hashes(&patches.artists),
+        patches,
+        report,
         "infobox musical artist",
         artist_processor,
         "artist",
+        dump_page,
     )?;
 
     Ok(ProcessedArtists(processed_artists))
 }
 
+/// A hash of the name patches to each patched page, which is folded into the hash its processed
+/// items are saved with, so that the page is reprocessed whenever its patches are added, changed
+/// or removed.
+fn patch_hashes<T: std::fmt::Debug>(
+    patches: &BTreeMap<PageName, Patch<T>>,
+) -> BTreeMap<String, u64> {
+    let mut hashes: BTreeMap<String, u64> = BTreeMap::new();
+    for (page, patch) in patches {
+        let hash = hashes
+            .entry(page.name.clone())
+            .or_insert_with(|| util::content_hash(&[]));
+        *hash = util::extend_content_hash(*hash, format!("{page:?}{patch:?}").as_bytes());
+    }
+    hashes
+}
+
 /// Generic function to process pages and extract infobox information.
+#[allow(clippy::too_many_arguments)]
 fn process_pages<T: ProcessedPage>(
     start: std::time::Instant,
     pages: &BTreeMap<PageName, std::path::PathBuf>,
     processed_path: &Path,
+    previous_processed_path: Option<&Path>,
+    patch_hashes: &BTreeMap<String, u64>,
+    patches: &DataPatches,
+    report: &mut PatchReport,
     template_name: &str,
     process_template: impl Fn(
         BTreeMap<String, &[pwt::Node]>,
         &PageName,
-        Option<String>,
+        &[String],
         jiff::Timestamp,
     ) -> T
     + Send
     + Sync,
-    entity_type: &str,
+    entity_type: &'static str,
+    dump_page: Option<&str>,
 ) -> anyhow::Result<BTreeMap<PageName, T>> {
     if processed_path.is_dir() {
         println!(
@@ -263,22 +624,13 @@ fn process_pages<T: ProcessedPage>(
             start.elapsed().as_secs_f32()
         );
 
-        let mut processed_items = BTreeMap::default();
-        let entries: Vec<_> = std::fs::read_dir(processed_path)?.collect::<Result<Vec<_>, _>>()?;
-
-        let loaded_items: Vec<(PageName, T)> = entries
-            .par_iter()
-            .filter_map(|entry| {
-                let path = entry.path();
-                let file_stem = path.file_stem()?;
-                let page_name = PageName::unsanitize(&file_stem.to_string_lossy());
-                let item: T = serde_json::from_slice(&std::fs::read(&path).ok()?).ok()?;
-                Some((page_name, item))
-            })
-            .collect();
-
-        processed_items.extend(loaded_items);
-        remove_ignored_pages_and_detect_duplicates(&mut processed_items);
+        let mut processed_items = load_processed_pages(processed_path)?;
+        remove_excluded_pages_and_detect_duplicates(
+            &mut processed_items,
+            patches,
+            report,
+            entity_type,
+        );
 
         println!(
             "{:.2}s: loaded processed {} {entity_type}s",
@@ -295,6 +647,22 @@ fn process_pages<T: ProcessedPage>(
 
     std::fs::create_dir_all(processed_path)?;
 
+    // Group the previous output's items by the page they came from, so that pages with
+    // unchanged wikitext can reuse them wholesale.
+    let mut previous_items: BTreeMap<String, Vec<T>> = BTreeMap::new();
+    if let Some(previous_processed_path) = previous_processed_path.filter(|p| p.is_dir()) {
+        for (page, item) in load_processed_pages::<T>(previous_processed_path)? {
+            previous_items.entry(page.name).or_default().push(item);
+        }
+        println!(
+            "{:.2}s: loaded previously processed {entity_type}s from {} pages in {}",
+            start.elapsed().as_secs_f32(),
+            previous_items.len(),
+            previous_processed_path.display()
+        );
+    }
+    let reused_count = AtomicUsize::new(0);
+
     let pwt_configuration = wikipedia_pwt_configuration();
 
     let item_count = AtomicUsize::new(0);
@@ -303,30 +671,59 @@ fn process_pages<T: ProcessedPage>(
     let last_reported_milestone = AtomicUsize::new(0);
     let start_time = start; // Capture start time to avoid shadowing in closure
 
-    let dump_page = std::env::var("DUMP_PAGE").ok();
-
     let processed_items: BTreeMap<PageName, T> = pages.par_iter().flat_map(|(original_page, path)| {
         let wikitext = std::fs::read_to_string(path).unwrap();
         let (wikitext_header, wikitext) = wikitext.split_once("\n").unwrap();
         let wikitext_header: extract::WikitextHeader = serde_json::from_str(wikitext_header).unwrap();
 
+        let mut wikitext_hash = util::extend_content_hash(
+            util::content_hash(&PROCESSING_VERSION.to_le_bytes()),
+            wikitext.as_bytes(),
+        );
+        if let Some(patch_hash) = patch_hashes.get(&original_page.name) {
+            wikitext_hash = util::extend_content_hash(wikitext_hash, &patch_hash.to_le_bytes());
+        }
+        if let Some(previous) = previous_items.get(&original_page.name)
+            && previous.iter().all(|item| item.wikitext_hash() == Some(wikitext_hash))
+            && dump_page != Some(original_page.name.as_str())
+        {
+            reused_count.fetch_add(previous.len(), std::sync::atomic::Ordering::Relaxed);
+            for item in previous {
+                item.save(processed_path).unwrap();
+            }
+            return previous
+                .iter()
+                .map(|item| (item.name().clone(), item.clone()))
+                .collect();
+        }
+
         let wikitext = remove_comments_from_wikitext_the_painful_way(
             &pwt_configuration,
-            dump_page.as_deref(),
+            dump_page,
             original_page,
             wikitext,
         );
         let parsed_wikitext = pwt_configuration
             .parse_with_timeout(&wikitext, std::time::Duration::from_secs(1))
             .unwrap_or_else(|e| panic!("failed to parse wikitext ({original_page}): {e:?}"));
-        if dump_page
-            .as_deref()
-            .is_some_and(|s| s == original_page.name)
+        if dump_page.is_some_and(|s| s == original_page.name)
         {
             println!("--- AFTER ---");
             dump_page_nodes(&wikitext, &parsed_wikitext.nodes, 0);
         }
 
+        // Used in lieu of a description for pages whose infobox is placed somewhere
+        // that leaves nothing to capture (e.g. at the very end of the page).
+        let lead_section = lead_section_wikitext(&wikitext, &parsed_wikitext.nodes);
+        let apply_lead_section_fallback = |processed_item: &mut T| {
+            if processed_item.name().heading.is_none()
+                && processed_item.description().is_none_or(|d| d.is_empty())
+                && !lead_section.is_empty()
+            {
+                processed_item.update_description(lead_section.clone());
+            }
+        };
+
         let mut description: Option<String> = None;
         let mut pause_recording_description = false;
         // The `start` of a node doesn't always correspond to the `end` of the last node,
@@ -335,13 +732,17 @@ fn process_pages<T: ProcessedPage>(
         fn start_including_last_node(last_node: &mut Option<NodeMetadata>, start: usize) -> usize {
             last_node.take().map(|t| t.end).filter(|&end| end < start).unwrap_or(start)
         }
-        let mut last_heading = None;
+        let mut headings = HeadingTracker::default();
 
         let mut processed_item: Option<T> = None;
         let mut page_results = Vec::new();
+        let mut see_also = vec![];
 
         for node in &parsed_wikitext.nodes {
             let node_metadata = NodeMetadata::for_node(node);
+            if headings.is_within("See also") && !matches!(node, pwt::Node::Heading { .. }) {
+                see_also.extend(get_links_from_nodes(std::slice::from_ref(node)));
+            }
             match node {
                 pwt::Node::Template {
                     name,
@@ -430,11 +831,9 @@ fn process_pages<T: ProcessedPage>(
                         if let Some(description) = description.take() {
                             processed_item.update_description(description);
                         }
+                        apply_lead_section_fallback(&mut processed_item);
                         page_results.push((new_page.clone(), processed_item.clone()));
-                        processed_item.save(processed_path).unwrap();
-                        if dump_page
-                            .as_deref()
-                            .is_some_and(|s| s == original_page.name)
+                        if dump_page.is_some_and(|s| s == original_page.name)
                         {
                             println!(
                                 "Saving due to new {entity_type}: {new_page:?} | {}",
@@ -445,12 +844,15 @@ fn process_pages<T: ProcessedPage>(
                     }
 
                     // Let the closure handle the specific processing
-                    processed_item = Some(process_template(
+                    let mut new_item = process_template(
                         target_parameters,
                         original_page,
-                        last_heading.clone(),
+                        &headings.path(),
                         wikitext_header.timestamp,
-                    ));
+                    );
+                    new_item.set_wikitext_hash(wikitext_hash);
+                    new_item.set_revision_id(wikitext_header.revision_id);
+                    processed_item = Some(new_item);
                     description = Some(String::new());
                     let current_count = item_count.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1;
 
@@ -519,9 +921,7 @@ fn process_pages<T: ProcessedPage>(
                             };
 
                             let new_fragment = &wikitext[new_start..*end];
-                            if dump_page
-                                .as_deref()
-                                .is_some_and(|s| s == original_page.name)
+                            if dump_page.is_some_and(|s| s == original_page.name)
                             {
                                 println!("Description: {description:?}");
                                 println!("New fragment: {new_fragment:?}");
@@ -533,7 +933,7 @@ fn process_pages<T: ProcessedPage>(
                         }
                     last_node = Some(node_metadata);
                 }
-                pwt::Node::Heading { nodes, .. } => {
+                pwt::Node::Heading { nodes, level, .. } => {
                     if let Some(processed_item) = &mut processed_item {
                         // We continue going if the description so far is empty: some infoboxes are placed
                         // before a heading, with the content following after the heading, so we offer
@@ -545,7 +945,7 @@ fn process_pages<T: ProcessedPage>(
                         }
                     }
 
-                    last_heading = Some(nodes_inner_text(nodes));
+                    headings.enter(*level, nodes_inner_text(nodes));
                 }
                 pwt::Node::Image { .. } | pwt::Node::Comment { .. } => {
                     last_node = Some(node_metadata);
@@ -558,11 +958,9 @@ fn process_pages<T: ProcessedPage>(
             if let Some(description) = description.take() {
                 processed_item.update_description(description);
             }
+            apply_lead_section_fallback(processed_item);
             page_results.push((new_page.clone(), processed_item.clone()));
-            processed_item.save(processed_path).unwrap();
-            if dump_page
-                .as_deref()
-                .is_some_and(|s| s == original_page.name)
+            if dump_page.is_some_and(|s| s == original_page.name)
             {
                 println!(
                     "End-of-page save: {new_page:?} | {}",
@@ -571,20 +969,62 @@ fn process_pages<T: ProcessedPage>(
             }
         }
 
+        // The "See also" section, navboxes and categories belong to the page as a whole, so attribute
+        // them to the page's main item (the one not under a heading, or failing that, the first one).
+        let main_item_index = page_results
+            .iter()
+            .position(|(page, _)| page.heading.is_none())
+            .unwrap_or(0);
+        if let Some((_, main_item)) = page_results.get_mut(main_item_index) {
+            main_item.set_see_also(see_also);
+            main_item.set_navboxes(trailing_navboxes(&parsed_wikitext.nodes));
+            main_item.set_categories(page_categories(&parsed_wikitext.nodes));
+        }
+        for (_, item) in &page_results {
+            item.save(processed_path).unwrap();
+        }
+
         page_results
     }).collect();
 
     println!(
-        "{:.2}s: processed all {} {entity_type}s",
+        "{:.2}s: processed all {} {entity_type}s (reused {} unchanged from previous output)",
         start.elapsed().as_secs_f32(),
-        item_count.load(std::sync::atomic::Ordering::Relaxed)
+        item_count.load(std::sync::atomic::Ordering::Relaxed),
+        reused_count.load(std::sync::atomic::Ordering::Relaxed)
     );
 
     let mut processed_items = processed_items;
-    remove_ignored_pages_and_detect_duplicates(&mut processed_items);
+    remove_excluded_pages_and_detect_duplicates(&mut processed_items, patches, report, entity_type);
     Ok(processed_items)
 }
 
+/// Load all processed pages from a directory of processed JSON files.
+/// Load the genres processed into `processed_genres_path` by an earlier run, as they were saved
+/// (without the patches that are applied after processing).
+pub fn load_processed_genres(processed_genres_path: &Path) -> anyhow::Result<ProcessedGenres> {
+    Ok(ProcessedGenres(load_processed_pages(
+        processed_genres_path,
+    )?))
+}
+
+fn load_processed_pages<T: ProcessedPage>(
+    processed_path: &Path,
+) -> anyhow::Result<BTreeMap<PageName, T>> {
+    let entries: Vec<_> = std::fs::read_dir(processed_path)?.collect::<Result<Vec<_>, _>>()?;
+
+    Ok(entries
+        .par_iter()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let file_stem = path.file_stem()?;
+            let page_name = PageName::unsanitize(&file_stem.to_string_lossy());
+            let item: T = serde_json::from_slice(&std::fs::read(&path).ok()?).ok()?;
+            Some((page_name, item))
+        })
+        .collect())
+}
+
 fn dump_page_nodes(wikitext: &str, nodes: &[pwt::Node], depth: usize) {
     for node in nodes {
         print!("{:indent$}", "", indent = depth * 2);
@@ -647,11 +1087,16 @@ fn remove_comments_from_wikitext_the_painful_way(
     new_wikitext
 }
 
-fn remove_ignored_pages_and_detect_duplicates<T: ProcessedPage>(
+fn remove_excluded_pages_and_detect_duplicates<T: ProcessedPage>(
     processed_pages: &mut BTreeMap<PageName, T>,
+    patches: &DataPatches,
+    report: &mut PatchReport,
+    entity_type: &'static str,
 ) {
-    for page in data_patches::pages_to_ignore() {
-        processed_pages.remove(&page);
+    for page in patches.exclusions.keys() {
+        if processed_pages.remove(page).is_some() {
+            report.record_exclusion(patches, page, entity_type);
+        }
     }
 
     let mut previously_encountered_pages = BTreeMap::new();
@@ -667,7 +1112,239 @@ fn remove_ignored_pages_and_detect_duplicates<T: ProcessedPage>(
     }
 }
 
-fn get_links_from_nodes(nodes: &[pwt::Node]) -> Vec<String> {
+/// Tracks the hierarchy of headings (H2 > H3 > ...) enclosing the current position in a page.
+#[derive(Default)]
+struct HeadingTracker {
+    /// The enclosing headings and their levels, outermost first.
+    stack: Vec<(u8, String)>,
+    /// How many times each heading text has been seen on the page so far.
+    seen: HashMap<String, usize>,
+}
+impl HeadingTracker {
+    /// Enter a new heading, leaving any headings at the same or a deeper level.
+    ///
+    /// Repeated heading texts are disambiguated the same way MediaWiki does for section anchors
+    /// (i.e. the second "1990s" becomes "1990s_2"), so that they remain linkable.
+    fn enter(&mut self, level: u8, text: String) {
+        while self.stack.last().is_some_and(|(l, _)| *l >= level) {
+            self.stack.pop();
+        }
+        let count = self.seen.entry(text.clone()).or_default();
+        *count += 1;
+        let anchor = if *count == 1 {
+            text
+        } else {
+            format!("{text}_{count}")
+        };
+        self.stack.push((level, anchor));
+    }
+
+    /// Whether the current position is within a heading with the given text (case-insensitively).
+    fn is_within(&self, text: &str) -> bool {
+        self.stack.iter().any(|(_, t)| t.eq_ignore_ascii_case(text))
+    }
+
+    /// The current heading path, outermost first.
+    fn path(&self) -> Vec<String> {
+        self.stack.iter().map(|(_, text)| text.clone()).collect()
+    }
+}
+
+/// Extract the wikitext of the page's lead section (everything before the first heading),
+/// dropping templates, references, images and other non-prose nodes.
+fn lead_section_wikitext(wikitext: &str, nodes: &[pwt::Node]) -> String {
+    let mut lead = String::new();
+    let mut in_ref = false;
+    let mut last_link_end = None;
+    for node in nodes {
+        match node {
+            pwt::Node::Heading { .. } => break,
+            pwt::Node::StartTag { name, .. } if name == "ref" => in_ref = true,
+            pwt::Node::EndTag { name, .. } if name == "ref" => in_ref = false,
+            pwt::Node::Bold { start, end }
+            | pwt::Node::BoldItalic { start, end }
+            | pwt::Node::Italic { start, end }
+            | pwt::Node::CharacterEntity { start, end, .. }
+            | pwt::Node::ExternalLink { start, end, .. }
+            | pwt::Node::Link { start, end, .. }
+            | pwt::Node::ParagraphBreak { start, end }
+            | pwt::Node::Text { start, end, .. }
+                if !in_ref =>
+            {
+                // Links can consume trailing text through linktrails; skip what they've already covered.
+                let start = last_link_end.map_or(*start, |link_end: usize| link_end.max(*start));
+                if start < *end {
+                    lead.push_str(&wikitext[start..*end]);
+                }
+                last_link_end = matches!(node, pwt::Node::Link { .. }).then_some(*end);
+            }
+            _ => {}
+        }
+    }
+    lead.trim().to_string()
+}
+
+/// Get the names of the categories a page is in, without the `Category:` prefix.
+fn page_categories(nodes: &[pwt::Node]) -> Vec<String> {
+    let mut categories = vec![];
+    for node in nodes {
+        let pwt::Node::Category { target, .. } = node else {
+            continue;
+        };
+        let target = target.trim();
+        let name = target
+            .split_once(':')
+            .filter(|(namespace, _)| namespace.trim().eq_ignore_ascii_case("category"))
+            .map_or(target, |(_, name)| name)
+            .trim()
+            .replace('_', " ");
+        if !name.is_empty() && !categories.contains(&name) {
+            categories.push(name);
+        }
+    }
+    categories
+}
+
+/// Find the navboxes transcluded at the bottom of a page: the run of templates at the end of the
+/// page (ignoring categories and whitespace), minus the maintenance templates that also live there.
+/// Navboxes grouped with `{{Navboxes|list=...}}` are unwrapped.
+fn trailing_navboxes(nodes: &[pwt::Node]) -> Vec<String> {
+    fn is_navbox(name: &str) -> bool {
+        const NON_NAVBOX_PREFIXES: &[&str] = &[
+            "authority control",
+            "portal",
+            "defaultsort",
+            "reflist",
+            "refend",
+            "commons",
+            "wiktionary",
+            "wikiquote",
+            "sister project",
+            "clear",
+            "coord",
+            "taxonbar",
+            "use ",
+        ];
+        let name = name.to_lowercase();
+        !name.is_empty()
+            && !name.ends_with("-stub")
+            && !NON_NAVBOX_PREFIXES.iter().any(|p| name.starts_with(p))
+    }
+    fn template_name(name: &[pwt::Node]) -> String {
+        let name = nodes_inner_text(name).trim().replace('_', " ");
+        let mut chars = name.chars();
+        chars
+            .next()
+            .map(|first| first.to_uppercase().chain(chars).collect())
+            .unwrap_or_default()
+    }
+
+    let mut navboxes = vec![];
+    for node in nodes.iter().rev() {
+        match node {
+            pwt::Node::Template {
+                name, parameters, ..
+            } => {
+                let name = template_name(name);
+                if name.eq_ignore_ascii_case("navboxes") {
+                    for parameter in parameters {
+                        for nested in &parameter.value {
+                            if let pwt::Node::Template { name, .. } = nested {
+                                navboxes.push(template_name(name));
+                            }
+                        }
+                    }
+                } else {
+                    navboxes.push(name);
+                }
+            }
+            pwt::Node::Category { .. }
+            | pwt::Node::Comment { .. }
+            | pwt::Node::ParagraphBreak { .. } => {}
+            pwt::Node::Text { value, .. } if value.trim().is_empty() => {}
+            _ => break,
+        }
+    }
+    navboxes.retain(|name| is_navbox(name));
+    navboxes.reverse();
+    navboxes
+}
+
+/// Get the entries of a list parameter: its links, or if it has none (some infoboxes list names
+/// without linking them), its plain-text entries, which are resolved like links at output time.
+fn get_list_entries_from_nodes(nodes: &[pwt::Node]) -> Vec<String> {
+    let links = get_links_from_nodes(nodes);
+    if !links.is_empty() {
+        return links;
+    }
+    split_plain_text_list(&plain_text_list(nodes))
+}
+
+/// Flatten the text of a list parameter, putting each list item (including those of list
+/// templates like `{{hlist}}`) on its own line. References are dropped.
+fn plain_text_list(nodes: &[pwt::Node]) -> String {
+    fn is_list_template(name: &str) -> bool {
+        matches!(
+            name,
+            "hlist"
+                | "flatlist"
+                | "flat list"
+                | "plainlist"
+                | "plain list"
+                | "ubl"
+                | "unbulleted list"
+        )
+    }
+
+    let mut output = String::new();
+    let mut in_ref = false;
+    for node in nodes {
+        match node {
+            pwt::Node::StartTag { name, .. } if name == "ref" => in_ref = true,
+            pwt::Node::EndTag { name, .. } if name == "ref" => in_ref = false,
+            _ if in_ref => {}
+            pwt::Node::Text { value, .. } => output.push_str(value),
+            pwt::Node::ParagraphBreak { .. } => output.push('\n'),
+            pwt::Node::StartTag { name, .. } if name == "br" => output.push('\n'),
+            pwt::Node::UnorderedList { items, .. } | pwt::Node::OrderedList { items, .. } => {
+                for item in items {
+                    output.push('\n');
+                    output.push_str(&plain_text_list(&item.nodes));
+                }
+                output.push('\n');
+            }
+            pwt::Node::Template {
+                name, parameters, ..
+            } if is_list_template(&nodes_inner_text(name).trim().to_lowercase()) => {
+                for parameter in parameters.iter().filter(|p| p.name.is_none()) {
+                    output.push('\n');
+                    output.push_str(&plain_text_list(&parameter.value));
+                }
+                output.push('\n');
+            }
+            _ => {}
+        }
+    }
+    output
+}
+
+/// Split a plain-text list into its entries, on newlines and the usual inline separators.
+/// Trailing parenthetical notes ("Acid house (late 1980s)") are dropped.
+fn split_plain_text_list(text: &str) -> Vec<String> {
+    text.split(['\n', ',', ';', '•', '·'])
+        .map(|entry| entry.trim().trim_start_matches(['*', '#']).trim())
+        .map(|entry| match entry.rsplit_once(" (") {
+            Some((head, tail)) if tail.ends_with(')') && !head.is_empty() => head.trim(),
+            _ => entry,
+        })
+        .filter(|entry| {
+            !entry.is_empty() && !matches!(entry.to_lowercase().as_str(), "etc." | "others")
+        })
+        .map(|entry| entry.to_string())
+        .collect()
+}
+
+pub(crate) fn get_links_from_nodes(nodes: &[pwt::Node]) -> Vec<String> {
     let mut output = vec![];
     nodes_recurse(nodes, &mut output, |output, node| {
         if let pwt::Node::Link { target, .. } = node {
@@ -799,3 +1476,444 @@ fn extract_name_from_parameter(
         }
     }
 }
+
+/// Parse the decade a genre originated in from the text of its `cultural_origins` parameter.
+///
+/// The first date-like mention wins: a decade ("mid-1980s"), a year ("1977"), or a century
+/// ("late 19th century"), where centuries are approximated by their early, middle or late decade.
+fn parse_origin_decade(cultural_origins: &str) -> Option<i32> {
+    let words: Vec<String> = cultural_origins
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect();
+
+    for (i, word) in words.iter().enumerate() {
+        let digits = word.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+        let suffix = &word[digits.len()..];
+        let Ok(number) = digits.parse::<i32>() else {
+            continue;
+        };
+
+        match suffix {
+            // "1970s", "1977"
+            "s" | "" if digits.len() == 4 && (1000..2100).contains(&number) => {
+                return Some(number - number % 10);
+            }
+            // "19th century"
+            "st" | "nd" | "rd" | "th"
+                if (1..=21).contains(&number)
+                    && words.get(i + 1).is_some_and(|w| w == "century") =>
+            {
+                let offset = match i.checked_sub(1).map(|j| words[j].as_str()) {
+                    Some("mid" | "middle") => 40,
+                    Some("late") => 70,
+                    _ => 0,
+                };
+                return Some((number - 1) * 100 + offset);
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Get the file name of the image in an infobox's `image` parameter, which is either the file
+/// name on its own (`Techno DJ.jpg`) or an image link (`[[File:Techno DJ.jpg|250px]]`).
+fn parse_image_file_name(nodes: &[pwt::Node]) -> Option<String> {
+    let target = nodes
+        .iter()
+        .find_map(|node| match node {
+            pwt::Node::Image { target, .. } => Some(target.to_string()),
+            _ => None,
+        })
+        .unwrap_or_else(|| nodes_inner_text(nodes));
+    clean_image_file_name(&target)
+}
+
+/// Strip the `File:` or `Image:` prefix from an image's name and normalize its spaces, or
+/// return `None` if it doesn't look like a file name.
+fn clean_image_file_name(target: &str) -> Option<String> {
+    let target = target.trim();
+    let name = ["File:", "Image:"]
+        .iter()
+        .find_map(|prefix| {
+            target
+                .get(..prefix.len())
+                .filter(|p| p.eq_ignore_ascii_case(prefix))
+                .map(|_| &target[prefix.len()..])
+        })
+        .unwrap_or(target);
+    let name = name.replace('_', " ").trim().to_string();
+    (name.contains('.') && !name.contains(['{', '[', '|', '<', '\n'])).then_some(name)
+}
+
+/// Get the decades covered by an artist's `years_active`, e.g. "1985–1992, 2005–present".
+///
+/// "present" is taken to be `present_year`.
+fn parse_active_decades(years_active: &str, present_year: i32) -> Vec<i32> {
+    let mut decades = BTreeSet::new();
+    for period in years_active.split(['\n', ',', ';', '•', '·']) {
+        let words: Vec<String> = period
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(|w| w.to_lowercase())
+            .collect();
+        // Each year, and whether it's a decade ("1990s")
+        let years: Vec<(i32, bool)> = words
+            .iter()
+            .filter_map(|word| {
+                let digits = word.strip_suffix('s').unwrap_or(word);
+                let year = digits.parse::<i32>().ok()?;
+                (digits.len() == 4 && (1000..2100).contains(&year))
+                    .then_some((year, digits.len() != word.len()))
+            })
+            .collect();
+        let (Some(&(start, _)), Some(&(last, last_is_decade))) = (years.first(), years.last())
+        else {
+            continue;
+        };
+        let end = if words.iter().any(|w| w == "present") {
+            present_year
+        } else if last_is_decade {
+            last + 9
+        } else {
+            last
+        };
+        if end < start || end - start > 100 {
+            continue;
+        }
+        decades.extend((start - start % 10..=end - end % 10).step_by(10));
+    }
+    decades.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn heading_tracker_tracks_nesting_and_disambiguates_repeats() {
+        let mut headings = HeadingTracker::default();
+        assert!(headings.path().is_empty());
+
+        headings.enter(2, "History".to_string());
+        headings.enter(3, "1990s".to_string());
+        assert_eq!(headings.path(), ["History", "1990s"]);
+
+        headings.enter(2, "Scenes".to_string());
+        headings.enter(3, "1990s".to_string());
+        headings.enter(4, "UK".to_string());
+        assert_eq!(headings.path(), ["Scenes", "1990s_2", "UK"]);
+
+        headings.enter(3, "2000s".to_string());
+        assert_eq!(headings.path(), ["Scenes", "2000s"]);
+
+        headings.enter(2, "See also".to_string());
+        headings.enter(3, "Lists".to_string());
+        assert!(headings.is_within("see also"));
+        headings.enter(2, "References".to_string());
+        assert!(!headings.is_within("see also"));
+    }
+
+    #[test]
+    fn parse_origin_decade_handles_decades_and_years() {
+        assert_eq!(
+            parse_origin_decade("Early 1970s, New York City"),
+            Some(1970)
+        );
+        assert_eq!(parse_origin_decade("Mid-1980s, Chicago"), Some(1980));
+        assert_eq!(parse_origin_decade("1977, United Kingdom"), Some(1970));
+        assert_eq!(parse_origin_decade("1960s–1970s, Jamaica"), Some(1960));
+    }
+
+    #[test]
+    fn parse_active_decades_covers_every_period() {
+        assert_eq!(
+            parse_active_decades("1985–1992, 2005–present", 2025),
+            [1980, 1990, 2000, 2010, 2020]
+        );
+        assert_eq!(parse_active_decades("1994", 2025), [1990]);
+        assert_eq!(parse_active_decades("1990s–2000s", 2025), [1990, 2000]);
+        assert_eq!(
+            parse_active_decades("1977–1983\n1996–1999", 2025),
+            [1970, 1980, 1990]
+        );
+        assert_eq!(parse_active_decades("Unknown", 2025), Vec::<i32>::new());
+    }
+
+    #[test]
+    fn parse_origin_decade_approximates_centuries() {
+        assert_eq!(parse_origin_decade("19th century, Spain"), Some(1800));
+        assert_eq!(parse_origin_decade("Mid-19th century"), Some(1840));
+        assert_eq!(
+            parse_origin_decade("Late 19th century, Deep South"),
+            Some(1870)
+        );
+    }
+
+    #[test]
+    fn trailing_navboxes_skips_maintenance_templates() {
+        let template = |name: &'static str| pwt::Node::Template {
+            start: 0,
+            end: 0,
+            name: vec![pwt::Node::Text {
+                start: 0,
+                end: 0,
+                value: name,
+            }],
+            parameters: vec![],
+        };
+        let text = |value: &'static str| pwt::Node::Text {
+            start: 0,
+            end: 0,
+            value,
+        };
+        let nodes = vec![
+            template("Reflist"),
+            text("Some trailing prose\n"),
+            template("Electronic_dance_music"),
+            text("\n"),
+            template("house music"),
+            template("Authority control"),
+            text("\n"),
+        ];
+        assert_eq!(
+            trailing_navboxes(&nodes),
+            ["Electronic dance music", "House music"]
+        );
+    }
+
+    #[test]
+    fn page_categories_strips_namespace() {
+        let category = |target: &'static str| pwt::Node::Category {
+            start: 0,
+            end: 0,
+            ordinal: vec![],
+            target,
+        };
+        let nodes = vec![
+            category("Category:English_house_musicians"),
+            pwt::Node::Text {
+                start: 0,
+                end: 0,
+                value: "\n",
+            },
+            category("category: 1990 births"),
+            category("Category:English house musicians"),
+        ];
+        assert_eq!(
+            page_categories(&nodes),
+            ["English house musicians", "1990 births"]
+        );
+    }
+
+    #[test]
+    fn split_plain_text_list_splits_on_separators() {
+        assert_eq!(
+            split_plain_text_list("Acid house (late 1980s), Hip house;\n* Deep house\n\netc."),
+            ["Acid house", "Hip house", "Deep house"]
+        );
+        assert_eq!(
+            split_plain_text_list("Hardcore • Gabber"),
+            ["Hardcore", "Gabber"]
+        );
+        assert!(split_plain_text_list(" \n ").is_empty());
+    }
+
+    #[test]
+    fn lead_section_wikitext_stops_at_first_heading_and_strips_refs() {
+        let wikitext = "Intro.<ref>cite</ref> More.\n==Heading==\nBody";
+        let span = |needle: &str| {
+            let start = wikitext.find(needle).unwrap();
+            (start, start + needle.len())
+        };
+        let text = |needle| {
+            let (start, end) = span(needle);
+            pwt::Node::Text {
+                start,
+                end,
+                value: &wikitext[start..end],
+            }
+        };
+        let (ref_start, ref_end) = (span("<ref>"), span("</ref>"));
+        let (heading_start, heading_end) = span("==Heading==");
+        let nodes = vec![
+            text("Intro."),
+            pwt::Node::StartTag {
+                start: ref_start.0,
+                end: ref_start.1,
+                name: "ref".into(),
+            },
+            text("cite"),
+            pwt::Node::EndTag {
+                start: ref_end.0,
+                end: ref_end.1,
+                name: "ref".into(),
+            },
+            text(" More.\n"),
+            pwt::Node::Heading {
+                start: heading_start,
+                end: heading_end,
+                level: 2,
+                nodes: vec![],
+            },
+            text("Body"),
+        ];
+        assert_eq!(lead_section_wikitext(wikitext, &nodes), "Intro. More.");
+    }
+
+    #[test]
+    fn parse_origin_decade_ignores_non_dates() {
+        assert_eq!(parse_origin_decade("Detroit, Michigan"), None);
+        assert_eq!(parse_origin_decade("Roland TR-808 users"), None);
+        assert_eq!(parse_origin_decade(""), None);
+    }
+
+    #[test]
+    fn clean_image_file_name_strips_prefixes() {
+        assert_eq!(
+            clean_image_file_name(" Techno_DJ.jpg "),
+            Some("Techno DJ.jpg".to_string())
+        );
+        assert_eq!(
+            clean_image_file_name("File:Techno DJ.jpg"),
+            Some("Techno DJ.jpg".to_string())
+        );
+        assert_eq!(
+            clean_image_file_name("image:Techno DJ.jpg"),
+            Some("Techno DJ.jpg".to_string())
+        );
+        assert_eq!(clean_image_file_name(""), None);
+        assert_eq!(clean_image_file_name("{{Photo needed}}"), None);
+    }
+
+    #[test]
+    fn genre_patches_apply_to_revisions_before_the_fix() {
+        let page = PageName::new("Techno", None);
+        let genre = |last_revision_date: &str| ProcessedGenre {
+            name: GenreName("Techno".to_string()),
+            page: page.clone(),
+            heading_path: vec![],
+            wikitext_description: Some("Old description".to_string()),
+            last_revision_date: last_revision_date.parse().unwrap(),
+            revision_id: None,
+            wikitext_hash: None,
+            stylistic_origins: vec!["Electro".to_string(), "Acoustic music".to_string()],
+            derivatives: vec![],
+            subgenres: vec![],
+            fusion_genres: vec![],
+            see_also: vec![],
+            navboxes: vec![],
+            origin_decade: Some(1990),
+            origin_countries: vec![],
+            other_names: vec![],
+            image: None,
+            merged_pages: vec![],
+        };
+        let fixed_at = Some("2025-01-01T00:00:00Z".parse().unwrap());
+        let patches = BTreeMap::from([(
+            page.clone(),
+            vec![
+                (None, GenrePatch::Description("New description".to_string())),
+                (fixed_at, GenrePatch::OriginDecade(1980)),
+                (
+                    None,
+                    GenrePatch::AddRelationship(
+                        Relationship::StylisticOrigin,
+                        "Chicago house".to_string(),
+                    ),
+                ),
+                (
+                    None,
+                    GenrePatch::RemoveRelationship(
+                        Relationship::StylisticOrigin,
+                        "Acoustic music".to_string(),
+                    ),
+                ),
+            ],
+        )]);
+
+        let mut genres = BTreeMap::from([(page.clone(), genre("2024-06-01T00:00:00Z"))]);
+        apply_genre_patches(&mut genres, &patches);
+        let patched = &genres[&page];
+        assert_eq!(
+            patched.wikitext_description.as_deref(),
+            Some("New description")
+        );
+        assert_eq!(patched.origin_decade, Some(1980));
+        assert_eq!(patched.stylistic_origins, ["Electro", "Chicago house"]);
+
+        // The origin was fixed on Wikipedia, so a later revision keeps its own
+        let mut genres = BTreeMap::from([(page.clone(), genre("2025-02-01T00:00:00Z"))]);
+        apply_genre_patches(&mut genres, &patches);
+        assert_eq!(genres[&page].origin_decade, Some(1990));
+        assert_eq!(
+            genres[&page].wikitext_description.as_deref(),
+            Some("New description")
+        );
+    }
+
+    #[test]
+    fn merged_genres_are_added_to_the_genres_they_are_merged_into() {
+        let genre = |page: PageName, name: &str, derivatives: &[&str]| ProcessedGenre {
+            name: GenreName(name.to_string()),
+            heading_path: page.heading.iter().cloned().collect(),
+            page,
+            wikitext_description: None,
+            last_revision_date: jiff::Timestamp::UNIX_EPOCH,
+            revision_id: None,
+            wikitext_hash: None,
+            stylistic_origins: vec![],
+            derivatives: derivatives.iter().map(|d| d.to_string()).collect(),
+            subgenres: vec![],
+            fusion_genres: vec![],
+            see_also: vec![],
+            navboxes: vec![],
+            origin_decade: None,
+            origin_countries: vec![],
+            other_names: vec![],
+            image: None,
+            merged_pages: vec![],
+        };
+        let stub = PageName::new("Neo soul (music)", None);
+        let section = PageName::new("Soul music", Some("Neo soul".to_string()));
+        let mut genres = BTreeMap::from([
+            (stub.clone(), {
+                let mut stub = genre(stub.clone(), "Neo-soul", &["Alternative R&B"]);
+                stub.origin_decade = Some(1990);
+                stub
+            }),
+            (
+                section.clone(),
+                genre(section.clone(), "Neo soul", &["Alternative R&B", "PBR&B"]),
+            ),
+        ]);
+
+        let mut report = PatchReport::default();
+        merge_genres(
+            &mut genres,
+            &BTreeMap::from([(stub.clone(), section.clone())]),
+            &mut report,
+        );
+        assert_eq!(genres.len(), 1);
+        let merged = &genres[&section];
+        assert_eq!(merged.name.0, "Neo soul");
+        assert_eq!(merged.derivatives, ["Alternative R&B", "PBR&B"]);
+        assert_eq!(merged.other_names, ["Neo-soul"]);
+        assert_eq!(merged.origin_decade, Some(1990));
+        assert_eq!(merged.merged_pages, [stub]);
+        assert_eq!(report.merged[0].into, section);
+    }
+
+    #[test]
+    fn patch_hashes_change_with_the_patches() {
+        let page = PageName::new("Soul music", Some("Neo soul".to_string()));
+        let patches =
+            |name: &str| BTreeMap::from([(page.clone(), (None, GenreName(name.to_string())))]);
+        let neo_soul = patch_hashes(&patches("Neo soul"));
+        assert_eq!(neo_soul.keys().collect::<Vec<_>>(), ["Soul music"]);
+        assert_eq!(neo_soul, patch_hashes(&patches("Neo soul")));
+        assert_ne!(neo_soul, patch_hashes(&patches("Neo-soul")));
+    }
+}
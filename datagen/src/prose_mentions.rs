@@ -0,0 +1,174 @@
+//! Mines genre article prose for links to other pages, so that genres which mention each other
+//! can be connected even when their infoboxes don't.
+use std::{collections::BTreeMap, path::Path};
+
+use anyhow::Context as _;
+use rayon::prelude::*;
+use wikitext_util::{nodes_inner_text, parse_wiki_text_2 as pwt, wikipedia_pwt_configuration};
+
+use crate::{extract, process, types::PageName};
+
+/// Sections after which an article no longer contains prose worth mining.
+const NON_PROSE_SECTIONS: &[&str] = &[
+    "see also",
+    "notes",
+    "references",
+    "sources",
+    "bibliography",
+    "further reading",
+    "external links",
+];
+
+/// For each genre page, the (unresolved) links in its prose and how many times each appears.
+pub struct ProseMentions(pub BTreeMap<PageName, BTreeMap<String, usize>>);
+impl ProseMentions {
+    /// Sum how often each pair of genres mentions the other (in either direction), and return
+    /// the pairs mentioned at least `threshold` times as `(a, b, weight)` with `a < b`.
+    ///
+    /// `page_id` maps a mining page to its genre and `link_id` resolves a link to a genre.
+    pub fn related_pairs<Id: Copy + Ord>(
+        &self,
+        page_id: impl Fn(&PageName) -> Option<Id>,
+        link_id: impl Fn(&str) -> Option<Id>,
+        threshold: usize,
+    ) -> Vec<(Id, Id, usize)> {
+        let mut weights: BTreeMap<(Id, Id), usize> = BTreeMap::new();
+        for (page, links) in &self.0 {
+            let Some(source) = page_id(page) else {
+                continue;
+            };
+            for (link, count) in links {
+                let Some(target) = link_id(link).filter(|t| *t != source) else {
+                    continue;
+                };
+                *weights
+                    .entry((source.min(target), source.max(target)))
+                    .or_default() += count;
+            }
+        }
+        weights
+            .into_iter()
+            .filter(|(_, weight)| *weight >= threshold)
+            .map(|((a, b), weight)| (a, b, weight))
+            .collect()
+    }
+}
+
+/// Collect the links in the prose of every genre page, or load them from `mentions_path`
+/// if they've already been collected.
+pub fn mine(
+    start: std::time::Instant,
+    genres: &extract::GenrePages,
+    mentions_path: &Path,
+) -> anyhow::Result<ProseMentions> {
+    if mentions_path.is_file() {
+        let mentions = serde_json::from_slice(
+            &std::fs::read(mentions_path).context("Failed to read prose mentions")?,
+        )
+        .context("Failed to parse prose mentions")?;
+        println!(
+            "{:.2}s: loaded prose mentions",
+            start.elapsed().as_secs_f32()
+        );
+        return Ok(ProseMentions(mentions));
+    }
+
+    println!(
+        "{:.2}s: mining prose mentions",
+        start.elapsed().as_secs_f32()
+    );
+
+    let pwt_configuration = wikipedia_pwt_configuration();
+    let mentions: BTreeMap<PageName, BTreeMap<String, usize>> = genres
+        .0
+        .par_iter()
+        .map(|(page, path)| {
+            let wikitext = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read wikitext for {page}"))?;
+            let (_header, wikitext) = wikitext.split_once('\n').unwrap_or_default();
+            let parsed_wikitext = pwt_configuration
+                .parse_with_timeout(wikitext, std::time::Duration::from_secs(1))
+                .map_err(|e| anyhow::anyhow!("failed to parse wikitext ({page}): {e:?}"))?;
+
+            let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+            for link in prose_links(&parsed_wikitext.nodes) {
+                *counts.entry(link).or_default() += 1;
+            }
+            Ok((page.clone(), counts))
+        })
+        .collect::<anyhow::Result<_>>()?;
+
+    std::fs::write(mentions_path, serde_json::to_string_pretty(&mentions)?)
+        .context("Failed to write prose mentions")?;
+    println!(
+        "{:.2}s: mined prose mentions from {} genre pages",
+        start.elapsed().as_secs_f32(),
+        mentions.len()
+    );
+
+    Ok(ProseMentions(mentions))
+}
+
+/// The links in an article's prose: everything up to the first non-prose section, excluding
+/// templates (infoboxes, navboxes), references and other non-prose nodes.
+fn prose_links(nodes: &[pwt::Node]) -> Vec<String> {
+    let mut links = vec![];
+    let mut in_ref = false;
+    for node in nodes {
+        match node {
+            pwt::Node::Heading { nodes, .. } => {
+                let heading = nodes_inner_text(nodes).trim().to_lowercase();
+                if NON_PROSE_SECTIONS.contains(&heading.as_str()) {
+                    break;
+                }
+            }
+            pwt::Node::StartTag { name, .. } if name == "ref" => in_ref = true,
+            pwt::Node::EndTag { name, .. } if name == "ref" => in_ref = false,
+            pwt::Node::Template { .. }
+            | pwt::Node::Tag { .. }
+            | pwt::Node::Image { .. }
+            | pwt::Node::Category { .. }
+            | pwt::Node::Comment { .. } => {}
+            _ if !in_ref => {
+                links.extend(process::get_links_from_nodes(std::slice::from_ref(node)));
+            }
+            _ => {}
+        }
+    }
+    links
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn related_pairs_sums_mentions_in_both_directions() {
+        let mentions = ProseMentions(BTreeMap::from_iter([
+            (
+                PageName::new("Techno", None),
+                BTreeMap::from_iter([("House music".to_string(), 1), ("Techno".to_string(), 2)]),
+            ),
+            (
+                PageName::new("House music", None),
+                BTreeMap::from_iter([("Techno".to_string(), 1), ("Disco".to_string(), 1)]),
+            ),
+        ]));
+        let ids = |name: &str| match name {
+            "Techno" => Some(0),
+            "House music" => Some(1),
+            "Disco" => Some(2),
+            _ => None,
+        };
+
+        assert_eq!(
+            mentions.related_pairs(|page| ids(&page.name), ids, 2),
+            // Self-mentions are ignored, and the one-way mention of Disco is below the threshold
+            vec![(0, 1, 2)]
+        );
+        assert_eq!(
+            mentions.related_pairs(|page| ids(&page.name), ids, 1),
+            vec![(0, 1, 2), (1, 2, 1)]
+        );
+    }
+}
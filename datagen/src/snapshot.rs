@@ -0,0 +1,209 @@
+//! Snapshot comparison mode: reprocess all pages from scratch and compare the results against a
+//! baseline, so that changes to processing can be checked for unintended differences.
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+use anyhow::Context as _;
+
+use crate::{data_patches::DataPatches, extract, patch_report::PatchReport, process};
+
+/// Fields that are expected to change between runs without the processed data changing.
+const IGNORED_FIELDS: &[&str] = &["wikitext_hash"];
+
+/// The processed data directories that are compared.
+const PROCESSED_DIRS: &[(&str, &str)] = &[
+    ("processed_genres", "genre"),
+    ("processed_artists", "artist"),
+];
+
+/// Reprocess all genres and artists into `snapshot_path/current`, without using any cached or
+/// previously processed data, and compare them against `snapshot_path/baseline`.
+///
+/// If there is no baseline yet, the current results become the baseline. Delete the baseline
+/// directory to start over.
+pub fn run(
+    start: std::time::Instant,
+    extracted_data: &extract::ExtractedData,
+    patches: &DataPatches,
+    snapshot_path: &Path,
+) -> anyhow::Result<()> {
+    let current_path = snapshot_path.join("current");
+    let baseline_path = snapshot_path.join("baseline");
+
+    if current_path.exists() {
+        std::fs::remove_dir_all(&current_path).context("Failed to clear previous snapshot")?;
+    }
+    process::genres(
+        start,
+        &extracted_data.genres,
+        &current_path.join("processed_genres"),
+        None,
+        patches,
+        &mut PatchReport::default(),
+        None,
+    )?;
+    process::artists(
+        start,
+        &extracted_data.artists,
+        &current_path.join("processed_artists"),
+        None,
+        patches,
+        &mut PatchReport::default(),
+        None,
+    )?;
+
+    if !baseline_path.exists() {
+        std::fs::rename(&current_path, &baseline_path)
+            .context("Failed to save snapshot baseline")?;
+        println!(
+            "{:.2}s: no baseline found; saved snapshot as the baseline at {}",
+            start.elapsed().as_secs_f32(),
+            baseline_path.display()
+        );
+        return Ok(());
+    }
+
+    let mut any_changes = false;
+    for (dir, entity_type) in PROCESSED_DIRS {
+        let diff = SnapshotDiff::between(
+            &load_snapshot(&baseline_path.join(dir))?,
+            &load_snapshot(&current_path.join(dir))?,
+        );
+        println!(
+            "{entity_type}s: {} added, {} removed, {} changed",
+            diff.added.len(),
+            diff.removed.len(),
+            diff.changed.len()
+        );
+        for name in &diff.added {
+            println!("  + {name}");
+        }
+        for name in &diff.removed {
+            println!("  - {name}");
+        }
+        for (name, fields) in &diff.changed {
+            println!(
+                "  ~ {name} ({})",
+                fields.iter().cloned().collect::<Vec<_>>().join(", ")
+            );
+        }
+        any_changes |= !diff.is_empty();
+    }
+
+    if !any_changes {
+        println!("snapshot matches the baseline");
+    }
+
+    Ok(())
+}
+
+/// Load every processed JSON file in a directory, keyed by file stem.
+fn load_snapshot(path: &Path) -> anyhow::Result<BTreeMap<String, serde_json::Value>> {
+    let mut snapshot = BTreeMap::new();
+    for entry in std::fs::read_dir(path).with_context(|| format!("Failed to read {path:?}"))? {
+        let path = entry?.path();
+        let Some(name) = path.file_stem().map(|s| s.to_string_lossy().to_string()) else {
+            continue;
+        };
+        let value = serde_json::from_slice(&std::fs::read(&path)?)
+            .with_context(|| format!("Failed to parse {path:?}"))?;
+        snapshot.insert(name, value);
+    }
+    Ok(snapshot)
+}
+
+/// The differences between two snapshots.
+#[derive(Debug, Default, PartialEq)]
+struct SnapshotDiff {
+    /// Items only in the current snapshot.
+    added: Vec<String>,
+    /// Items only in the baseline.
+    removed: Vec<String>,
+    /// Items in both whose fields differ, with the names of those fields.
+    changed: Vec<(String, BTreeSet<String>)>,
+}
+impl SnapshotDiff {
+    fn between(
+        baseline: &BTreeMap<String, serde_json::Value>,
+        current: &BTreeMap<String, serde_json::Value>,
+    ) -> Self {
+        let mut diff = Self::default();
+        for (name, current_value) in current {
+            let Some(baseline_value) = baseline.get(name) else {
+                diff.added.push(name.clone());
+                continue;
+            };
+            let empty = serde_json::Map::new();
+            let (baseline_fields, current_fields) = (
+                baseline_value.as_object().unwrap_or(&empty),
+                current_value.as_object().unwrap_or(&empty),
+            );
+            let changed_fields: BTreeSet<String> = baseline_fields
+                .keys()
+                .chain(current_fields.keys())
+                .filter(|field| !IGNORED_FIELDS.contains(&field.as_str()))
+                .filter(|field| baseline_fields.get(*field) != current_fields.get(*field))
+                .cloned()
+                .collect();
+            if !changed_fields.is_empty() {
+                diff.changed.push((name.clone(), changed_fields));
+            }
+        }
+        diff.removed = baseline
+            .keys()
+            .filter(|name| !current.contains_key(*name))
+            .cloned()
+            .collect();
+        diff
+    }
+
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn snapshot_diff_reports_added_removed_and_changed_fields() {
+        let baseline = BTreeMap::from_iter([
+            (
+                "Techno".to_string(),
+                json!({ "name": "Techno", "subgenres": ["Acid techno"], "wikitext_hash": 1 }),
+            ),
+            ("Disco".to_string(), json!({ "name": "Disco" })),
+            (
+                "House".to_string(),
+                json!({ "name": "House", "wikitext_hash": 1 }),
+            ),
+        ]);
+        let current = BTreeMap::from_iter([
+            (
+                "Techno".to_string(),
+                json!({ "name": "Techno", "subgenres": [], "wikitext_hash": 2 }),
+            ),
+            (
+                "House".to_string(),
+                json!({ "name": "House", "wikitext_hash": 2 }),
+            ),
+            ("Jungle".to_string(), json!({ "name": "Jungle" })),
+        ]);
+
+        assert_eq!(
+            SnapshotDiff::between(&baseline, &current),
+            SnapshotDiff {
+                added: vec!["Jungle".to_string()],
+                removed: vec!["Disco".to_string()],
+                changed: vec![(
+                    "Techno".to_string(),
+                    BTreeSet::from_iter(["subgenres".to_string()])
+                )],
+            }
+        );
+    }
+}
@@ -10,8 +10,213 @@ pub use shared::PageName;
 pub struct Config {
     /// The directory containing Wikipedia dump files.
     pub wikipedia_dump_dir: PathBuf,
-    /// The YouTube API key.
+    /// The YouTube API key, which `check-mixes` needs, and with which `populate-mixes`
+    /// suggests mixes from YouTube's search results. Empty if there isn't one.
+    #[serde(default)]
     pub youtube_api_key: String,
+    /// How artists are ranked within each genre.
+    #[serde(default)]
+    pub ranking: RankingConfig,
+    /// Which of the ranked artists are shown for each genre.
+    #[serde(default)]
+    pub top_artists: TopArtistsConfig,
+    /// Which links are counted towards a page's inbound link count.
+    #[serde(default)]
+    pub link_counts: LinkCountsConfig,
+    /// How the website's data files are written.
+    #[serde(default)]
+    pub output: OutputConfig,
+    /// How the graph is laid out.
+    #[serde(default)]
+    pub layout: LayoutConfig,
+}
+
+/// How the graph is laid out.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct LayoutConfig {
+    /// The layout algorithm. Its parameters are environment variables, documented in
+    /// the [`layout`] crate.
+    pub algorithm: layout::Algorithm,
+    /// Lay the graph out in 3D, giving each node a `z` as well (with an octree in place of the
+    /// quadtree). Only the force-directed algorithms can.
+    pub three_d: bool,
+    /// How the force-directed layouts place the genres before the simulation.
+    pub initialization: layout::Initialization,
+    /// The seed of the random numbers the layout starts from, recorded in the output
+    /// (`layout_seed`) so that the layout can be reproduced.
+    pub seed: u32,
+    /// The precision the force-directed layouts are computed in: `f32` is faster for large
+    /// graphs, and stays close to the `f64` layout.
+    pub precision: layout::Precision,
+}
+
+/// How the website's data files are written.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct OutputConfig {
+    /// Write brotli (`.br`) and gzip (`.gz`) compressed copies of every data file, along with a
+    /// `manifest.json` of their sizes and hashes, for static hosts that don't compress on the fly.
+    pub precompress: bool,
+    /// The format the graph data is written in.
+    pub data_format: crate::frontend_types::DataFormat,
+    /// Include a hash of each genre and artist file's contents in its name, along with a
+    /// `paths.json` mapping the plain names to the hashed ones, so that they can be cached
+    /// indefinitely without going stale across deploys.
+    pub hashed_filenames: bool,
+    /// How many generations of ancestors and descendants each genre file lists.
+    pub lineage_depth: usize,
+    /// How many edges out each genre's neighbourhood file (`neighborhood/<id>.json`) reaches,
+    /// or 0 to not write them.
+    pub neighborhood_hops: usize,
+    /// Indent the JSON files written for the website, for reading them by hand; they're
+    /// otherwise compact. Also set by the `--pretty` flag.
+    pub pretty: bool,
+    /// Also write each genre and artist description as HTML, where it can be rendered ahead of
+    /// time, so that the website doesn't need to parse its wikitext.
+    pub description_html: bool,
+    /// Write a file for every processed artist, rather than only the genres' top artists, so
+    /// that artists linked from descriptions can be shown too.
+    pub all_artists: bool,
+    /// Also write the machine-readable outputs (the graph, genre and artist files, exports and
+    /// schemas) to a `genresinspace-<dump date>.tar.zst` bundle, for downloading all at once.
+    pub bundle: bool,
+    /// Also export the nodes, edges and genres' top artists as Parquet files, for loading into
+    /// DuckDB or pandas.
+    pub parquet: bool,
+    /// Look up each mix's title, channel and thumbnail with YouTube's oEmbed endpoint and add
+    /// them to the genre files, so that the website can show them without calling YouTube.
+    pub mix_embeds: bool,
+    /// Download each genre's infobox image and write a small WebP thumbnail of it to the
+    /// website, referenced from the genre files.
+    pub genre_images: bool,
+    /// Draw a share image of each genre and put it in the metadata of the genre's page, so that
+    /// shared links to genres unfurl with a picture of them.
+    pub og_cards: bool,
+    /// With `--prose-mentions`, how many times two genres must mention each other in their prose
+    /// to be related. Articles rarely link the same page twice, so 2 usually means that the genres
+    /// mention each other.
+    pub related_mention_threshold: usize,
+    /// The damping factor of each genre's PageRank: the probability that rank keeps flowing
+    /// along an edge, rather than being spread over every genre.
+    pub pagerank_damping: f64,
+    /// The most power iterations PageRank runs for; it stops sooner once the ranks settle.
+    pub pagerank_iterations: usize,
+}
+impl Default for OutputConfig {
+    fn default() -> Self {
+        Self {
+            precompress: false,
+            data_format: Default::default(),
+            hashed_filenames: false,
+            lineage_depth: 3,
+            neighborhood_hops: 2,
+            pretty: false,
+            description_html: false,
+            all_artists: false,
+            bundle: false,
+            parquet: false,
+            mix_embeds: false,
+            genre_images: false,
+            og_cards: false,
+            related_mention_threshold: 2,
+            pagerank_damping: 0.85,
+            pagerank_iterations: 100,
+        }
+    }
+}
+
+/// Which links are counted towards a page's inbound link count.
+///
+/// Only links from articles are counted; talk, user and project pages are always excluded.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct LinkCountsConfig {
+    /// Also exclude links from list and disambiguation pages (requires a page dump), which link
+    /// to every artist they cover without saying anything about their popularity.
+    pub exclude_list_pages: bool,
+    /// Reuse the previous output's link counts, only parsing the links dump for pages that weren't
+    /// tracked then. This is much faster when no pages are new, but approximate: the reused counts
+    /// don't reflect links added or removed since then. Leave this off for a release.
+    pub reuse_previous: bool,
+}
+
+/// Which of the ranked artists are shown for each genre.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct TopArtistsConfig {
+    /// The most artists to show for a genre.
+    pub count: usize,
+    /// The fewest inbound links (including via redirects) an artist needs to be shown.
+    pub min_links: usize,
+    /// The fewest artists that must meet `min_links` for a genre to show any; genres with fewer
+    /// show none rather than a handful of barely-relevant acts.
+    pub min_artists: usize,
+}
+impl Default for TopArtistsConfig {
+    fn default() -> Self {
+        Self {
+            count: 10,
+            min_links: 0,
+            min_artists: 0,
+        }
+    }
+}
+
+/// How artists are ranked within each genre.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct RankingConfig {
+    /// The popularity signal to rank by.
+    pub metric: RankingMetric,
+    /// For [`RankingMetric::Blend`], how much pageviews count relative to links, from 0 (links only)
+    /// to 1 (pageviews only).
+    pub pageview_weight: f32,
+}
+impl Default for RankingConfig {
+    fn default() -> Self {
+        Self {
+            metric: RankingMetric::default(),
+            pageview_weight: 0.5,
+        }
+    }
+}
+impl RankingConfig {
+    /// Combine an artist's inbound link count and pageviews into a single popularity score.
+    pub fn popularity(&self, links: usize, pageviews: usize) -> f32 {
+        match self.metric {
+            RankingMetric::Links => links as f32,
+            RankingMetric::Pageviews => pageviews as f32,
+            // A weighted geometric mean, as the two are on very different scales
+            RankingMetric::Blend => {
+                let weight = self.pageview_weight.clamp(0.0, 1.0);
+                (links as f32 + 1.0).powf(1.0 - weight) * (pageviews as f32 + 1.0).powf(weight)
+                    - 1.0
+            }
+        }
+    }
+
+    /// A suffix for files derived from the ranking, so that changing it doesn't reuse stale results.
+    pub fn cache_suffix(&self) -> String {
+        match self.metric {
+            RankingMetric::Links => String::new(),
+            RankingMetric::Pageviews => "_pageviews".to_string(),
+            RankingMetric::Blend => format!("_blend{}", self.pageview_weight),
+        }
+    }
+}
+
+/// A popularity signal for ranking artists.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RankingMetric {
+    /// The number of inbound links to the artist's article.
+    #[default]
+    Links,
+    /// The number of views of the artist's article (requires a pageview dump).
+    Pageviews,
+    /// A blend of links and pageviews, weighted by [`RankingConfig::pageview_weight`].
+    Blend,
 }
 
 /// Resolved paths to Wikipedia dump files within the dump directory.
@@ -21,9 +226,21 @@ pub struct WikipediaPaths {
     /// The path to the Wikipedia index (*-index.txt.bz2).
     pub index_path: PathBuf,
     /// The path to the Wikipedia link targets SQL dump (*-linktarget.sql.gz).
-    pub linktargets_path: PathBuf,
+    ///
+    /// Older dumps predate the `linktarget` table and don't have one.
+    pub linktargets_path: Option<PathBuf>,
     /// The path to the Wikipedia links SQL dump (*-pagelinks.sql.gz).
     pub links_path: PathBuf,
+    /// The path to the Wikipedia page SQL dump (*-page.sql.gz), if one has been downloaded.
+    pub page_path: Option<PathBuf>,
+    /// The path to the monthly pageview dump (pageviews-*-user.bz2), if one has been downloaded.
+    pub pageviews_path: Option<PathBuf>,
+    /// The path to the Wikipedia interlanguage links SQL dump (*-langlinks.sql.gz), if one has
+    /// been downloaded.
+    pub langlinks_path: Option<PathBuf>,
+    /// The path to the Wikipedia page properties SQL dump (*-page_props.sql.gz), if one has been
+    /// downloaded.
+    pub page_props_path: Option<PathBuf>,
 }
 
 impl Config {
@@ -35,8 +252,8 @@ impl Config {
             "wikipedia_dump_dir {dir:?} is not a directory"
         );
 
-        /// Find exactly one file in `dir` whose name ends with `suffix`.
-        fn find(dir: &Path, suffix: &str) -> anyhow::Result<PathBuf> {
+        /// Find at most one file in `dir` whose name ends with `suffix`.
+        fn find_optional(dir: &Path, suffix: &str) -> anyhow::Result<Option<PathBuf>> {
             let mut found = None;
             for entry in std::fs::read_dir(dir)? {
                 let entry = entry?;
@@ -50,20 +267,43 @@ impl Config {
                     found = Some(entry.path());
                 }
             }
-            found.ok_or_else(|| anyhow::anyhow!("no file matching *{suffix} in {dir:?}"))
+            Ok(found)
+        }
+
+        /// Find exactly one file in `dir` whose name ends with `suffix`.
+        fn find(dir: &Path, suffix: &str) -> anyhow::Result<PathBuf> {
+            find_optional(dir, suffix)?
+                .ok_or_else(|| anyhow::anyhow!("no file matching *{suffix} in {dir:?}"))
         }
 
         Ok(WikipediaPaths {
             dump_path: find(dir, "-pages-articles-multistream.xml.bz2")?,
             index_path: find(dir, "-pages-articles-multistream-index.txt.bz2")?,
-            linktargets_path: find(dir, "-linktarget.sql.gz")?,
+            linktargets_path: find_optional(dir, "-linktarget.sql.gz")?,
             links_path: find(dir, "-pagelinks.sql.gz")?,
+            page_path: find_optional(dir, "-page.sql.gz")?,
+            pageviews_path: find_optional(dir, "-user.bz2")?,
+            langlinks_path: find_optional(dir, "-langlinks.sql.gz")?,
+            page_props_path: find_optional(dir, "-page_props.sql.gz")?,
         })
     }
 }
 
 /// A newtype for an ID assigned to a page for the graph.
-#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    ts_rs::TS,
+    schemars::JsonSchema,
+)]
 #[serde(transparent)]
 pub struct PageDataId(pub usize);
 impl std::fmt::Display for PageDataId {
@@ -72,7 +312,19 @@ impl std::fmt::Display for PageDataId {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(
+    Clone,
+    Debug,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    ts_rs::TS,
+    schemars::JsonSchema,
+)]
 #[serde(transparent)]
 /// A newtype for a genre name.
 pub struct GenreName(pub String);
@@ -91,29 +343,256 @@ impl std::fmt::Display for ArtistName {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    ts_rs::TS,
+    schemars::JsonSchema,
+)]
+#[serde(rename_all = "camelCase")]
+/// Where an alternative name for a genre comes from. Redirects are only kept as alternative names
+/// when their redirect category says they are one, so misspellings and the like are left out.
+pub enum AliasSource {
+    /// The infobox's `other_names`.
+    OtherName,
+    /// A redirect from an alternative name or synonym.
+    AlternativeName,
+    /// A redirect from an alternative spelling.
+    AlternativeSpelling,
+    /// A redirect from the name in another language.
+    AlternativeLanguage,
+    /// A redirect from a shorter form of the name.
+    ShortName,
+    /// A redirect from a longer form of the name.
+    LongName,
+    /// A redirect from an acronym, initialism or abbreviation.
+    Abbreviation,
+    /// A redirect from a former name.
+    FormerName,
+    /// The normalized form of the genre's name or one of its other names (e.g. "hip hop" for
+    /// "Hip-hop music"), where it's more than a change of case.
+    Normalized,
+}
+impl AliasSource {
+    /// The source for a redirect category template (e.g. `R from alternative name`), if it marks
+    /// an alternative name.
+    pub fn from_redirect_category(template: &str) -> Option<Self> {
+        Some(match template.trim().to_lowercase().as_str() {
+            "r from alternative name"
+            | "r from alternate name"
+            | "r from other name"
+            | "r from synonym" => Self::AlternativeName,
+            "r from alternative spelling"
+            | "r from alternate spelling"
+            | "r from other spelling" => Self::AlternativeSpelling,
+            "r from alternative language"
+            | "r from alternate language"
+            | "r from other language" => Self::AlternativeLanguage,
+            "r from short name" | "r from shortening" => Self::ShortName,
+            "r from long name" => Self::LongName,
+            "r from acronym" | "r from initialism" | "r from abbreviation" => Self::Abbreviation,
+            "r from former name" | "r from old name" => Self::FormerName,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ts_rs::TS, schemars::JsonSchema)]
 #[serde(untagged)]
-/// A mix for a genre, consisting of a playlist or a video.
+/// A mix for a genre, consisting of a YouTube playlist or video, or a mix on another
+/// [`MixProvider`].
 pub enum GenreMix {
     /// A playlist mix.
     Playlist {
         /// The ID of the playlist.
         playlist: String,
         #[serde(skip_serializing_if = "Option::is_none")]
+        #[ts(optional)]
         /// A note about the mix.
         note: Option<String>,
+        #[serde(flatten)]
+        /// What else is known about the mix.
+        details: MixDetails,
     },
     /// A video mix.
     Video {
         /// The ID of the video.
         video: String,
         #[serde(skip_serializing_if = "Option::is_none")]
+        #[ts(optional)]
         /// A note about the mix.
         note: Option<String>,
+        #[serde(flatten)]
+        /// What else is known about the mix.
+        details: MixDetails,
     },
+    /// A mix on another provider than YouTube.
+    External {
+        /// Where the mix is.
+        provider: MixProvider,
+        /// The URL of the mix's page, as [`MixProvider::normalize_url`] has it.
+        url: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[ts(optional)]
+        /// A note about the mix.
+        note: Option<String>,
+        #[serde(flatten)]
+        /// What else is known about the mix.
+        details: MixDetails,
+    },
+}
+impl GenreMix {
+    /// The mix's URL.
+    pub fn url(&self) -> String {
+        match self {
+            GenreMix::Playlist { playlist, .. } => {
+                format!("https://www.youtube.com/playlist?list={playlist}")
+            }
+            GenreMix::Video { video, .. } => format!("https://www.youtube.com/watch?v={video}"),
+            GenreMix::External { url, .. } => url.clone(),
+        }
+    }
+
+    /// The note about the mix, if there is one.
+    pub fn note(&self) -> Option<&str> {
+        match self {
+            GenreMix::Playlist { note, .. }
+            | GenreMix::Video { note, .. }
+            | GenreMix::External { note, .. } => note.as_deref(),
+        }
+    }
+
+    /// The mix at a URL, if it's a YouTube playlist or video, or a mix on another
+    /// [`MixProvider`].
+    pub fn from_url(url: &str, note: Option<String>, details: MixDetails) -> Option<Self> {
+        fn extract_playlist_id(url: &str) -> Option<String> {
+            url.find("list=").map(|list| {
+                url[list + 5..]
+                    .split(['&', '#'])
+                    .next()
+                    .unwrap()
+                    .to_string()
+            })
+        }
+
+        fn extract_video_id(url: &str) -> Option<String> {
+            if let Some(v) = url.find("v=") {
+                Some(url[v + 2..].split(['&', '#']).next().unwrap().to_string())
+            } else if url.contains("youtu.be/") {
+                url.split('/')
+                    .next_back()
+                    .map(|s| s.split(['&', '#']).next().unwrap().to_string())
+            } else {
+                None
+            }
+        }
+
+        if let Some((provider, url)) = MixProvider::normalize_url(url) {
+            Some(GenreMix::External {
+                provider,
+                url,
+                note,
+                details,
+            })
+        } else if let Some(playlist) = extract_playlist_id(url) {
+            Some(GenreMix::Playlist {
+                playlist,
+                note,
+                details,
+            })
+        } else {
+            extract_video_id(url).map(|video| GenreMix::Video {
+                video,
+                note,
+                details,
+            })
+        }
+    }
+}
+
+#[derive(
+    Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ts_rs::TS, schemars::JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+/// Where a mix that isn't on YouTube is.
+pub enum MixProvider {
+    /// A Bandcamp album or track.
+    Bandcamp,
+    /// A SoundCloud track or set.
+    SoundCloud,
+    /// A Mixcloud show.
+    Mixcloud,
+}
+impl MixProvider {
+    /// The provider's name.
+    pub fn name(self) -> &'static str {
+        match self {
+            MixProvider::Bandcamp => "bandcamp",
+            MixProvider::SoundCloud => "soundcloud",
+            MixProvider::Mixcloud => "mixcloud",
+        }
+    }
+
+    /// The provider of the mix at `url`, and the URL without its query, fragment, or trailing
+    /// slash, if it's the page of a mix on one of them.
+    pub fn normalize_url(url: &str) -> Option<(Self, String)> {
+        let rest = url
+            .strip_prefix("https://")
+            .or_else(|| url.strip_prefix("http://"))?;
+        let rest = rest.split(['?', '#']).next().unwrap_or_default();
+        let (host, path) = rest.split_once('/')?;
+        let path = path.trim_end_matches('/');
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        let (provider, host) = match host.to_lowercase().as_str() {
+            host if host.ends_with(".bandcamp.com")
+                && matches!(segments.as_slice(), ["album" | "track", _]) =>
+            {
+                (MixProvider::Bandcamp, host.to_string())
+            }
+            "soundcloud.com" | "www.soundcloud.com" | "m.soundcloud.com" if segments.len() >= 2 => {
+                (MixProvider::SoundCloud, "soundcloud.com".to_string())
+            }
+            "mixcloud.com" | "www.mixcloud.com" | "m.mixcloud.com" if segments.len() >= 2 => {
+                (MixProvider::Mixcloud, "www.mixcloud.com".to_string())
+            }
+            _ => return None,
+        };
+        Some((provider, format!("https://{host}/{}", segments.join("/"))))
+    }
+}
+
+#[derive(
+    Debug, Clone, Default, Serialize, Deserialize, PartialEq, ts_rs::TS, schemars::JsonSchema,
+)]
+/// What's known about a mix besides where it is, from mixes files in the TOML format (see
+/// [`MixesFile`]).
+pub struct MixDetails {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    /// The title of the video or playlist, as it was when the mix was added.
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    /// The length of the video, in seconds.
+    pub duration: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    /// Who added the mix.
+    pub submitter: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    /// When the mix was added, as `YYYY-MM-DD`.
+    pub added: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ts_rs::TS, schemars::JsonSchema)]
 #[serde(untagged)]
 /// A list of mixes for a genre.
 pub enum GenreMixes {
@@ -126,8 +605,18 @@ pub enum GenreMixes {
     Mixes(Vec<GenreMix>),
 }
 impl GenreMixes {
-    /// Parse a list of mixes from a string.
-    pub fn parse(input: &str) -> Self {
+    /// Parse a genre's mixes file, in either format: TOML (see [`MixesFile`]), or the older plain
+    /// text, with a URL and an optional `# note` on each line (or `help`, or `help: <reason>`).
+    pub fn parse(input: &str) -> anyhow::Result<Self> {
+        if MixesFile::is_toml(input) {
+            Ok(MixesFile::parse(input)?.into())
+        } else {
+            Ok(Self::parse_plain_text(input))
+        }
+    }
+
+    /// Parse a list of mixes in the plain text format.
+    fn parse_plain_text(input: &str) -> Self {
         let input = input.trim();
 
         if let Some(help_reason) = input.strip_prefix("help:") {
@@ -151,42 +640,157 @@ impl GenreMixes {
                 (line, None)
             };
 
-            if let Some(playlist_id) = extract_playlist_id(url) {
-                mixes.push(GenreMix::Playlist {
-                    playlist: playlist_id,
-                    note,
-                });
-            } else if let Some(video_id) = extract_video_id(url) {
-                mixes.push(GenreMix::Video {
-                    video: video_id,
-                    note,
-                });
-            }
+            mixes.extend(GenreMix::from_url(url, note, MixDetails::default()));
         }
 
-        fn extract_playlist_id(url: &str) -> Option<String> {
-            url.find("list=").map(|list| {
-                url[list + 5..]
-                    .split(['&', '#'])
-                    .next()
-                    .unwrap()
-                    .to_string()
+        GenreMixes::Mixes(mixes)
+    }
+}
+
+/// A genre's mixes file in the TOML format, which, unlike the plain text format, can say where
+/// each mix came from:
+///
+/// ```toml
+/// [[mix]]
+/// url = "https://www.youtube.com/watch?v=dQw4w9WgXcQ"
+/// note = "The classics"
+/// title = "Two hours of acid house"
+/// duration = 7200
+/// submitter = "someone"
+/// added = 2025-01-31
+/// ```
+///
+/// A genre without a mix has `help = "<reason>"` (or `help = ""`) in place of its mixes.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MixesFile {
+    /// Why the genre has no mix, if it hasn't.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub help: Option<String>,
+    /// The mixes.
+    #[serde(default, rename = "mix", skip_serializing_if = "Vec::is_empty")]
+    pub mixes: Vec<MixEntry>,
+}
+
+/// A mix in a [`MixesFile`].
+///
+/// Besides YouTube videos and playlists, Bandcamp albums and tracks, SoundCloud tracks and sets,
+/// and Mixcloud shows can be given by URL (see [`MixProvider`]).
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MixEntry {
+    /// The URL of the video, playlist or other mix.
+    pub url: String,
+    /// A note about the mix, shown with it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// See [`MixDetails::title`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// See [`MixDetails::duration`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration: Option<u32>,
+    /// See [`MixDetails::submitter`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub submitter: Option<String>,
+    /// See [`MixDetails::added`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub added: Option<toml::value::Datetime>,
+    /// Why the mix can't be played any more, if it can't (e.g. `"deleted"`, as marked by
+    /// `datagen validate-mixes --mark`); such mixes are left out.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dead: Option<String>,
+}
+
+impl MixesFile {
+    /// Whether a mixes file is in this format rather than the plain text one: whether its first
+    /// line (that isn't blank or a comment) is a table header or a `key = value`.
+    pub fn is_toml(input: &str) -> bool {
+        let Some(line) = input
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with('#'))
+        else {
+            return false;
+        };
+        line.starts_with('[')
+            || line.split_once('=').is_some_and(|(key, _)| {
+                let key = key.trim();
+                !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
             })
-        }
+    }
 
-        fn extract_video_id(url: &str) -> Option<String> {
-            if let Some(v) = url.find("v=") {
-                Some(url[v + 2..].split(['&', '#']).next().unwrap().to_string())
-            } else if url.contains("youtu.be/") {
-                url.split('/')
-                    .next_back()
-                    .map(|s| s.split(['&', '#']).next().unwrap().to_string())
-            } else {
-                None
-            }
+    /// Parse a mixes file in this format.
+    pub fn parse(input: &str) -> anyhow::Result<Self> {
+        Ok(toml::from_str(input)?)
+    }
+
+    /// Convert mixes in the plain text format to this one, keeping the plain text's comment lines
+    /// as comments.
+    pub fn from_plain_text(input: &str) -> (Self, Vec<String>) {
+        let comments = input
+            .lines()
+            .map(str::trim)
+            .filter(|line| line.starts_with('#'))
+            .map(|line| line.to_string())
+            .collect();
+        let file = match GenreMixes::parse_plain_text(input) {
+            GenreMixes::Help { help_reason } => MixesFile {
+                help: Some(help_reason.unwrap_or_default()),
+                mixes: vec![],
+            },
+            GenreMixes::Mixes(mixes) => MixesFile {
+                help: None,
+                mixes: mixes
+                    .iter()
+                    .map(|mix| MixEntry {
+                        url: mix.url(),
+                        note: mix.note().map(str::to_string),
+                        ..Default::default()
+                    })
+                    .collect(),
+            },
+        };
+        (file, comments)
+    }
+
+    /// The file's contents, after the given comment lines.
+    pub fn to_toml(&self, comments: &[String]) -> anyhow::Result<String> {
+        let mut out = String::new();
+        for comment in comments {
+            out.push_str(comment);
+            out.push('\n');
+        }
+        if !comments.is_empty() {
+            out.push('\n');
         }
+        out.push_str(&toml::to_string(self)?);
+        Ok(out)
+    }
+}
 
-        GenreMixes::Mixes(mixes)
+impl From<MixesFile> for GenreMixes {
+    fn from(file: MixesFile) -> Self {
+        if let Some(help) = file.help {
+            return GenreMixes::Help {
+                help_reason: (!help.is_empty()).then_some(help),
+            };
+        }
+        GenreMixes::Mixes(
+            file.mixes
+                .into_iter()
+                .filter(|entry| entry.dead.is_none())
+                .filter_map(|entry| {
+                    let details = MixDetails {
+                        title: entry.title,
+                        duration: entry.duration,
+                        submitter: entry.submitter,
+                        added: entry.added.map(|added| added.to_string()),
+                    };
+                    GenreMix::from_url(&entry.url, entry.note, details)
+                })
+                .collect(),
+        )
     }
 }
 
@@ -197,13 +801,13 @@ mod tests {
     #[test]
     fn test_help() {
         assert_eq!(
-            GenreMixes::parse("help: not ready"),
+            GenreMixes::parse("help: not ready").unwrap(),
             GenreMixes::Help {
                 help_reason: Some("not ready".to_string())
             }
         );
         assert_eq!(
-            GenreMixes::parse("help"),
+            GenreMixes::parse("help").unwrap(),
             GenreMixes::Help { help_reason: None }
         );
     }
@@ -215,19 +819,23 @@ mod tests {
                 "https://www.youtube.com/playlist?list=PLMC9KNkIncKvYin_USF1qoJQnIyMAfRxl
                  https://www.youtube.com/playlist?list=PLH22-xSMERQrmeOAp7kJy-0BHfGJbl4Jg # A great mix
                  https://youtu.be/dQw4w9WgXcQ # You're on your own with finding a mix for this."
-            ),
+            )
+            .unwrap(),
             GenreMixes::Mixes(vec![
                 GenreMix::Playlist {
                     playlist: "PLMC9KNkIncKvYin_USF1qoJQnIyMAfRxl".to_string(),
-                    note: None
+                    note: None,
+                    details: MixDetails::default(),
                 },
                 GenreMix::Playlist {
                         playlist: "PLH22-xSMERQrmeOAp7kJy-0BHfGJbl4Jg".to_string(),
-                    note: Some("A great mix".to_string())
+                    note: Some("A great mix".to_string()),
+                    details: MixDetails::default(),
                 },
                 GenreMix::Video {
                     video: "dQw4w9WgXcQ".to_string(),
-                    note: Some("You're on your own with finding a mix for this.".to_string())
+                    note: Some("You're on your own with finding a mix for this.".to_string()),
+                    details: MixDetails::default(),
                 }
             ])
         );
@@ -239,17 +847,123 @@ mod tests {
             GenreMixes::parse(
                 "https://www.youtube.com/watch?v=dQw4w9WgXcQ
                  https://youtu.be/dQw4w9WgXcQ"
-            ),
+            )
+            .unwrap(),
             GenreMixes::Mixes(vec![
                 GenreMix::Video {
                     video: "dQw4w9WgXcQ".to_string(),
-                    note: None
+                    note: None,
+                    details: MixDetails::default(),
+                },
+                GenreMix::Video {
+                    video: "dQw4w9WgXcQ".to_string(),
+                    note: None,
+                    details: MixDetails::default(),
+                }
+            ])
+        );
+    }
+    #[test]
+    fn test_other_providers() {
+        let external = |provider, url: &str| GenreMix::External {
+            provider,
+            url: url.to_string(),
+            note: None,
+            details: MixDetails::default(),
+        };
+        assert_eq!(
+            GenreMixes::parse(
+                "https://someartist.bandcamp.com/album/a-compilation?from=search
+                 https://soundcloud.com/someone/sets/a-set/
+                 https://m.mixcloud.com/someone/a-show/?utm_source=share
+                 https://someartist.bandcamp.com/music
+                 https://soundcloud.com/someone"
+            )
+            .unwrap(),
+            GenreMixes::Mixes(vec![
+                external(
+                    MixProvider::Bandcamp,
+                    "https://someartist.bandcamp.com/album/a-compilation"
+                ),
+                external(
+                    MixProvider::SoundCloud,
+                    "https://soundcloud.com/someone/sets/a-set"
+                ),
+                external(
+                    MixProvider::Mixcloud,
+                    "https://www.mixcloud.com/someone/a-show"
+                ),
+            ])
+        );
+        assert_eq!(
+            serde_json::to_value(external(
+                MixProvider::SoundCloud,
+                "https://soundcloud.com/someone/a-track"
+            ))
+            .unwrap(),
+            serde_json::json!({
+                "provider": "soundcloud",
+                "url": "https://soundcloud.com/someone/a-track"
+            })
+        );
+    }
+
+    #[test]
+    fn test_toml() {
+        assert_eq!(
+            GenreMixes::parse(
+                r#"# The classics
+                [[mix]]
+                url = "https://www.youtube.com/playlist?list=PLMC9KNkIncKvYin_USF1qoJQnIyMAfRxl"
+                note = "A great mix"
+                title = "Acid house classics"
+                submitter = "someone"
+                added = 2025-01-31
+
+                [[mix]]
+                url = "https://youtu.be/dQw4w9WgXcQ"
+                duration = 7200
+                dead = "deleted"
+
+                [[mix]]
+                url = "https://www.youtube.com/watch?v=dQw4w9WgXcQ&t=10"
+                duration = 7200"#
+            )
+            .unwrap(),
+            GenreMixes::Mixes(vec![
+                GenreMix::Playlist {
+                    playlist: "PLMC9KNkIncKvYin_USF1qoJQnIyMAfRxl".to_string(),
+                    note: Some("A great mix".to_string()),
+                    details: MixDetails {
+                        title: Some("Acid house classics".to_string()),
+                        duration: None,
+                        submitter: Some("someone".to_string()),
+                        added: Some("2025-01-31".to_string()),
+                    },
                 },
                 GenreMix::Video {
                     video: "dQw4w9WgXcQ".to_string(),
-                    note: None
+                    note: None,
+                    details: MixDetails {
+                        duration: Some(7200),
+                        ..Default::default()
+                    },
                 }
             ])
         );
+        assert_eq!(
+            GenreMixes::parse("help = \"not ready\"").unwrap(),
+            GenreMixes::Help {
+                help_reason: Some("not ready".to_string())
+            }
+        );
+        assert_eq!(
+            GenreMixes::parse("help = \"\"").unwrap(),
+            GenreMixes::Help { help_reason: None }
+        );
+        assert!(GenreMixes::parse("[[mix]]\nurl = 1").is_err());
+        assert!(!MixesFile::is_toml(
+            "# A comment\nhttps://youtu.be/dQw4w9WgXcQ # a=b"
+        ));
     }
 }
@@ -1,4 +1,12 @@
 //! Utility functions used throughout the program.
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::types::PageName;
 
 /// Extracts the domain from a URL.
 pub fn extract_domain(url: &str) -> Option<&str> {
@@ -28,6 +36,94 @@ pub fn parse_wiki_dump_date(filename: &str) -> Option<jiff::civil::Date> {
     Some(jiff::civil::date(year, month, day))
 }
 
+/// A stable 64-bit FNV-1a hash of some content.
+///
+/// Unlike `std`'s `DefaultHasher`, this is guaranteed to produce the same value
+/// across Rust versions and platforms, so it can be persisted and compared between runs.
+pub fn content_hash(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    extend_content_hash(OFFSET_BASIS, bytes)
+}
+
+/// Continue a [`content_hash`] with more content, as if `bytes` had been appended to the original content.
+pub fn extend_content_hash(hash: u64, bytes: &[u8]) -> u64 {
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    bytes
+        .iter()
+        .fold(hash, |hash, &byte| (hash ^ byte as u64).wrapping_mul(PRIME))
+}
+
+/// A [`content_hash`] of a file's contents, read in chunks so that large dumps can be hashed.
+pub fn file_content_hash(path: &Path) -> anyhow::Result<u64> {
+    use anyhow::Context as _;
+    use std::io::Read as _;
+
+    let mut file =
+        std::fs::File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut buffer = vec![0u8; 1 << 20];
+    let mut hash = content_hash(&[]);
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        if read == 0 {
+            return Ok(hash);
+        }
+        hash = extend_content_hash(hash, &buffer[..read]);
+    }
+}
+
+/// A [`content_hash`] of a set of pages, to key what's read from a dump for them by.
+pub fn pages_hash(pages: &BTreeSet<PageName>) -> u64 {
+    pages.iter().fold(content_hash(&[]), |hash, page| {
+        extend_content_hash(hash, format!("{page:?}\n").as_bytes())
+    })
+}
+
+/// What's been read from a dump for the pages with [`pages_hash`] `pages_hash`.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PagesCache<T> {
+    pages_hash: u64,
+    data: T,
+}
+
+/// Load what was read from a dump for the pages with [`pages_hash`] `pages_hash` from `path`,
+/// or `None` if nothing was, or it was read for other pages (or cached in an older format).
+pub fn load_pages_cache<T: DeserializeOwned>(path: &Path, pages_hash: u64) -> Option<T> {
+    let cache: PagesCache<T> = serde_json::from_slice(&std::fs::read(path).ok()?).ok()?;
+    (cache.pages_hash == pages_hash).then_some(cache.data)
+}
+
+/// Save what was read from a dump for the pages with [`pages_hash`] `pages_hash` to `path`, for
+/// [`load_pages_cache`].
+pub fn save_pages_cache<T: Serialize>(
+    path: &Path,
+    pages_hash: u64,
+    data: &T,
+) -> anyhow::Result<()> {
+    use anyhow::Context as _;
+
+    let cache = PagesCache { pages_hash, data };
+    std::fs::write(path, serde_json::to_string_pretty(&cache)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Find the most recent output directory (named after its dump date, e.g. `output/2025-01-23`)
+/// that predates `dump_date`.
+pub fn find_previous_output(output_root: &Path, dump_date: jiff::civil::Date) -> Option<PathBuf> {
+    std::fs::read_dir(output_root)
+        .ok()?
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| {
+            let date: jiff::civil::Date = entry.file_name().to_str()?.parse().ok()?;
+            (date < dump_date).then(|| (date, entry.path()))
+        })
+        .max_by_key(|(date, _)| *date)
+        .map(|(_, path)| path)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -55,4 +151,40 @@ mod tests {
         );
         assert_eq!(parse_wiki_dump_date("invalid"), None);
     }
+
+    #[test]
+    fn test_content_hash_is_stable() {
+        assert_eq!(content_hash(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(content_hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_ne!(content_hash(b"techno"), content_hash(b"Techno"));
+        assert_eq!(
+            extend_content_hash(content_hash(b"drum and "), b"bass"),
+            content_hash(b"drum and bass")
+        );
+    }
+
+    #[test]
+    fn pages_caches_are_only_loaded_for_the_same_pages() {
+        let path = std::env::temp_dir().join(format!("datagen-pages-cache-{}", std::process::id()));
+        let pages = |names: &[&str]| -> BTreeSet<PageName> {
+            names
+                .iter()
+                .map(|name| PageName::new(*name, None))
+                .collect()
+        };
+        let techno = pages_hash(&pages(&["Techno"]));
+        save_pages_cache(&path, techno, &vec![1, 2, 3]).unwrap();
+
+        assert_eq!(
+            load_pages_cache::<Vec<u32>>(&path, techno),
+            Some(vec![1, 2, 3])
+        );
+        let more = pages_hash(&pages(&["Techno", "House music"]));
+        assert_eq!(load_pages_cache::<Vec<u32>>(&path, more), None);
+
+        // Caches from before they were keyed by the pages are read again
+        std::fs::write(&path, "[1, 2, 3]").unwrap();
+        assert_eq!(load_pages_cache::<Vec<u32>>(&path, techno), None);
+        std::fs::remove_file(&path).ok();
+    }
 }
@@ -0,0 +1,245 @@
+//! Checking the patches in `datagen/patches` for the configured wiki against the extracted data (`datagen
+//! validate-patches`), so that typos are caught before a full run trips over them.
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+use jiff::Timestamp;
+
+use crate::{
+    data_patches::DataPatches,
+    extract::ExtractedData,
+    process::{self, ProcessedGenres},
+    types::{GenreName, PageName},
+};
+
+/// Check the loaded `patches` (loading them checks that they, and their times, parse), printing
+/// every problem found, and fail if there are any. The Wikidata items of `unresolved_patches`
+/// are those of shared patches that aren't on any page.
+///
+/// Genre names are checked for duplicates against the processed genres in
+/// `processed_genres_path`, if there are any.
+pub fn run(
+    patches: &DataPatches,
+    unresolved_patches: &[String],
+    extracted_data: ExtractedData,
+    processed_genres_path: Option<&Path>,
+) -> anyhow::Result<()> {
+    let redirects: BTreeMap<PageName, PageName> = extracted_data.redirects.try_into()?;
+    let processed_genres = processed_genres_path
+        .map(process::load_processed_genres)
+        .transpose()?;
+    if processed_genres.is_none() {
+        println!("No processed genres to check genre names against; run datagen first");
+    }
+
+    let pages = Pages {
+        genres: extracted_data.genres.0.keys().cloned().collect(),
+        artists: extracted_data.artists.0.keys().cloned().collect(),
+        redirects,
+    };
+    let mut problems = find_problems(patches, &pages, processed_genres.as_ref(), Timestamp::now());
+    problems.extend(
+        unresolved_patches.iter().map(|qid| {
+            format!("The shared patch for {qid} isn't for a page in the extracted data")
+        }),
+    );
+    for problem in &problems {
+        println!("{problem}");
+    }
+    anyhow::ensure!(
+        problems.is_empty(),
+        "Found {} problems with the patches",
+        problems.len()
+    );
+    println!("The patches are fine");
+    Ok(())
+}
+
+/// The pages in the extracted data.
+struct Pages {
+    genres: BTreeSet<PageName>,
+    artists: BTreeSet<PageName>,
+    redirects: BTreeMap<PageName, PageName>,
+}
+
+/// Where a patched page is in the extracted data.
+enum Found {
+    Page,
+    Redirect(PageName),
+    Missing,
+}
+
+impl Pages {
+    /// Find `page` (ignoring its heading) in `pages`, or as a redirect to one of them.
+    fn find(&self, pages: &BTreeSet<PageName>, page: &PageName) -> Found {
+        let page = page.with_opt_heading(None);
+        if pages.contains(&page) {
+            return Found::Page;
+        }
+        match self.redirects.get(&page) {
+            Some(target) if pages.contains(&target.with_opt_heading(None)) => {
+                Found::Redirect(target.clone())
+            }
+            _ => Found::Missing,
+        }
+    }
+}
+
+/// The problems with `patches`, given the extracted `pages` and, if there are any, the
+/// `processed_genres`. Patches to redirects aren't problems, but as they won't match the pages
+/// they redirect to, they're printed.
+fn find_problems(
+    patches: &DataPatches,
+    pages: &Pages,
+    processed_genres: Option<&ProcessedGenres>,
+    now: Timestamp,
+) -> Vec<String> {
+    let mut problems = vec![];
+    let all_pages = pages.genres.union(&pages.artists).cloned().collect();
+    let mut check_page =
+        |kind: &str, page: &PageName, pages_of_kind: &BTreeSet<PageName>| match pages
+            .find(pages_of_kind, page)
+        {
+            Found::Page => {}
+            Found::Redirect(target) => {
+                println!("The {kind} patch for {page} is for a redirect to {target}");
+            }
+            Found::Missing => problems.push(format!(
+                "The {kind} patch for {page} isn't for a page in the extracted data"
+            )),
+        };
+    for page in patches.genres.keys() {
+        check_page("genre name", page, &pages.genres);
+    }
+    for page in patches.artists.keys() {
+        check_page("artist name", page, &pages.artists);
+    }
+    for page in patches.genre_patches.keys() {
+        check_page("genre", page, &pages.genres);
+    }
+    for page in patches.exclusions.keys() {
+        check_page("exclusion", page, &all_pages);
+    }
+    for (page, into) in &patches.merges {
+        check_page("merge", page, &pages.genres);
+        check_page("merge", into, &pages.genres);
+    }
+    for patch in &patches.edge_patches {
+        check_page("edge", &patch.source, &pages.genres);
+        check_page("edge", &patch.target, &pages.genres);
+    }
+
+    let times = patches
+        .genres
+        .iter()
+        .map(|(page, (fixed_at, _))| (page, *fixed_at))
+        .chain(
+            patches
+                .artists
+                .iter()
+                .map(|(page, (fixed_at, _))| (page, *fixed_at)),
+        )
+        .chain(
+            patches
+                .genre_patches
+                .iter()
+                .flat_map(|(page, genre_patches)| {
+                    genre_patches
+                        .iter()
+                        .map(move |(fixed_at, _)| (page, *fixed_at))
+                }),
+        )
+        .chain(
+            patches
+                .inclusions
+                .iter()
+                .map(|(page, inclusion)| (page, Some(inclusion.as_of))),
+        );
+    for (page, time) in times {
+        if let Some(time) = time.filter(|time| *time > now) {
+            problems.push(format!("The patch for {page} is from the future ({time})"));
+        }
+    }
+
+    // The genre names that the renames and inclusions introduce, and the pages they're for
+    let mut new_names: BTreeMap<&GenreName, Vec<&PageName>> = BTreeMap::new();
+    for (page, (_, name)) in &patches.genres {
+        new_names.entry(name).or_default().push(page);
+    }
+    for (page, inclusion) in &patches.inclusions {
+        new_names.entry(&inclusion.name).or_default().push(page);
+    }
+    for (name, patched_pages) in &new_names {
+        if let [first, second, ..] = patched_pages.as_slice() {
+            problems.push(format!(
+                "{first} and {second} are both patched to be named {}",
+                name.0
+            ));
+        }
+    }
+    if let Some(processed_genres) = processed_genres {
+        for genre in processed_genres.0.values() {
+            let original_page = genre.page.with_opt_heading(None);
+            let is_patched = |page: &&PageName| **page == genre.page || **page == original_page;
+            if patches.exclusions.contains_key(&genre.page) {
+                continue;
+            }
+            let Some(patched_pages) = new_names.get(&genre.name) else {
+                continue;
+            };
+            if let Some(page) = patched_pages.iter().find(|page| !is_patched(page)) {
+                problems.push(format!(
+                    "{page} is patched to be named {}, which {} is already named",
+                    genre.name.0, genre.page
+                ));
+            }
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patches_need_pages_times_in_the_past_and_unique_names() {
+        let page = |name: &str| PageName::new(name, None);
+        let pages = Pages {
+            genres: [page("Techno"), page("House music")].into(),
+            artists: [page("Kraftwerk")].into(),
+            redirects: [(page("House"), page("House music"))].into(),
+        };
+        let now: Timestamp = "2025-06-01T00:00:00Z".parse().unwrap();
+        let genre_name = |fixed_at: Option<&str>, name: &str| {
+            (
+                fixed_at.map(|fixed_at| fixed_at.parse().unwrap()),
+                GenreName(name.to_string()),
+            )
+        };
+
+        let mut patches = DataPatches::default();
+        patches
+            .genres
+            .insert(page("Techno"), genre_name(None, "Detroit techno"));
+        patches
+            .genres
+            .insert(page("House"), genre_name(None, "House"));
+        assert!(find_problems(&patches, &pages, None, now).is_empty());
+
+        patches.genres.insert(
+            page("Tecno"),
+            genre_name(Some("2205-01-01T00:00:00Z"), "House"),
+        );
+        assert_eq!(
+            find_problems(&patches, &pages, None, now),
+            [
+                "The genre name patch for Tecno isn't for a page in the extracted data",
+                "The patch for Tecno is from the future (2205-01-01T00:00:00Z)",
+                "House and Tecno are both patched to be named House",
+            ]
+        );
+    }
+}
@@ -0,0 +1,192 @@
+//! Reads the compressed Wikipedia page properties dump SQL (`*-page_props.sql.gz`) to get the
+//! Wikidata items of genre and artist pages, and maps genres' items to their MusicBrainz genres,
+//! so that the dataset can be joined with other music data by those IDs.
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+use anyhow::Context as _;
+use serde::Deserialize;
+
+use crate::{link_counts::common, types::PageName};
+
+/// The Wikidata item ID (`Q` followed by a number) of each page that has one.
+pub type WikidataIds = BTreeMap<PageName, String>;
+
+/// Where the Wikidata-to-MusicBrainz genre mapping is loaded from.
+pub const MUSICBRAINZ_GENRES_PATH: &str = "datagen/musicbrainz_genres.toml";
+
+/// The MusicBrainz genre ID of each Wikidata item that's mapped to one.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct MusicBrainzGenres {
+    #[serde(default)]
+    genres: BTreeMap<String, String>,
+}
+impl MusicBrainzGenres {
+    /// Load the mapping from a TOML file.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let genres = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read MusicBrainz genres from {path:?}"))?;
+        toml::from_str(&genres)
+            .with_context(|| format!("Failed to parse MusicBrainz genres from {path:?}"))
+    }
+
+    /// Get the MusicBrainz genre ID for a Wikidata item, if it's mapped to one.
+    pub fn get(&self, wikidata_qid: &str) -> Option<&str> {
+        self.genres.get(wikidata_qid).map(String::as_str)
+    }
+}
+
+/// The columns of the `page_props` table that are used.
+#[derive(Debug, Clone, Copy)]
+struct PagePropColumns {
+    page: usize,
+    propname: usize,
+    value: usize,
+}
+
+/// Read the Wikidata items of `tracked_pages`, or load them from `output_path` if they've
+/// already been read. `id_to_page_names` maps page IDs to the pages they belong to.
+pub(crate) fn read(
+    start: std::time::Instant,
+    page_props_path: &Path,
+    id_to_page_names: &BTreeMap<u64, PageName>,
+    tracked_pages: &BTreeSet<PageName>,
+    output_path: &Path,
+) -> anyhow::Result<WikidataIds> {
+    let output_file_path = output_path.join("wikidata_ids.json");
+    if output_file_path.is_file() {
+        return serde_json::from_slice(
+            &std::fs::read(&output_file_path).context("Failed to read existing Wikidata IDs")?,
+        )
+        .context("Failed to parse existing Wikidata IDs");
+    }
+
+    println!("{:.2}s: reading page props", start.elapsed().as_secs_f32());
+
+    let tracked_ids: BTreeMap<u64, &PageName> = id_to_page_names
+        .iter()
+        .filter(|(_, page)| tracked_pages.contains(*page))
+        .map(|(id, page)| (*id, page))
+        .collect();
+
+    let mut page_props_file = common::open_sql_dump(page_props_path)?;
+    let header =
+        common::read_until_prefix(&mut page_props_file, b"INSERT INTO `page_props` VALUES ")
+            .context(
+                "Failed to find INSERT INTO `page_props` VALUES statement in page props file",
+            )?;
+    let column_names = common::column_names(&header, "page_props");
+    let index = |name: &str, default: usize| {
+        column_names
+            .iter()
+            .position(|c| c == name)
+            .unwrap_or(default)
+    };
+    let columns = PagePropColumns {
+        page: index("pp_page", 0),
+        propname: index("pp_propname", 1),
+        value: index("pp_value", 2),
+    };
+
+    let wikidata_ids =
+        parse_page_props_tuple_stream(&mut page_props_file, start, columns, &tracked_ids)
+            .context("Failed to parse page props tuples from stream")?;
+
+    std::fs::write(
+        &output_file_path,
+        serde_json::to_string_pretty(&wikidata_ids)?,
+    )
+    .context("Failed to write Wikidata IDs")?;
+
+    println!(
+        "{:.2}s: read Wikidata items for {} pages",
+        start.elapsed().as_secs_f32(),
+        wikidata_ids.len()
+    );
+
+    Ok(wikidata_ids)
+}
+
+fn parse_page_props_tuple_stream(
+    stream: &mut (impl std::io::BufRead + Send),
+    start: std::time::Instant,
+    columns: PagePropColumns,
+    tracked_ids: &BTreeMap<u64, &PageName>,
+) -> anyhow::Result<WikidataIds> {
+    let (wikidata_ids, tuples_parsed) = common::parse_tuples_parallel(
+        stream,
+        WikidataIds::new,
+        |wikidata_ids, values| {
+            if let (Some(page), Some("wikibase_item"), Some(qid)) = (
+                values
+                    .get(columns.page)
+                    .and_then(|v| v.as_integer())
+                    .and_then(|id| tracked_ids.get(&(id as u64))),
+                values.get(columns.propname).and_then(|v| v.as_str()),
+                values.get(columns.value).and_then(|v| v.as_str()),
+            ) && is_qid(qid)
+            {
+                wikidata_ids.insert((*page).clone(), qid.to_string());
+            }
+        },
+        |mut a, b| {
+            a.extend(b);
+            a
+        },
+        |tuples_parsed| {
+            println!(
+                "{:.2}s: parsed {tuples_parsed} page prop tuples",
+                start.elapsed().as_secs_f32(),
+            );
+        },
+    )?;
+
+    println!(
+        "{:.2}s: parsed {tuples_parsed} page prop tuples",
+        start.elapsed().as_secs_f32(),
+    );
+
+    Ok(wikidata_ids)
+}
+
+/// Whether `value` looks like a Wikidata item ID: `Q` followed by a number.
+fn is_qid(value: &str) -> bool {
+    value
+        .strip_prefix('Q')
+        .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_page_props_tuples() {
+        let techno = PageName::new("Techno", None);
+        let tracked_ids = BTreeMap::from_iter([(1, &techno)]);
+        let data = "INSERT INTO `page_props` VALUES (1,'page_image_free','Techno.jpg',NULL),\
+                    (1,'wikibase_item','Q123',NULL),(2,'wikibase_item','Q456',NULL),\
+                    (1,'wikibase-shortdesc','Genre of electronic dance music',NULL);\n";
+
+        let wikidata_ids = parse_page_props_tuple_stream(
+            &mut Cursor::new(data.as_bytes()),
+            std::time::Instant::now(),
+            PagePropColumns {
+                page: 0,
+                propname: 1,
+                value: 2,
+            },
+            &tracked_ids,
+        )
+        .unwrap();
+
+        assert_eq!(
+            wikidata_ids,
+            BTreeMap::from_iter([(techno, "Q123".to_string())])
+        );
+    }
+}
@@ -0,0 +1,69 @@
+//! The force-directed layout from the `layout` crate (the one datagen lays out the whole graph
+//! with), run an iteration at a time so that the website can lay out a graph across animation
+//! frames instead of blocking.
+
+use wasm_bindgen::prelude::*;
+
+/// A ForceAtlas2 layout of a graph, run an iteration at a time with [`ForceLayout::step`].
+#[wasm_bindgen]
+pub struct ForceLayout {
+    simulation: layout::Simulation,
+}
+
+#[wasm_bindgen]
+impl ForceLayout {
+    /// Start laying out `num_nodes` nodes joined by `edges`, flattened as
+    /// `[source, target, type, source, target, type, ...]` with each type as in serialized edges.
+    /// Related edges are left out, as datagen leaves them out of its layout. `seed` picks the
+    /// random starting positions.
+    #[wasm_bindgen(constructor)]
+    pub fn new(num_nodes: usize, edges: &[u32], seed: u32) -> Result<ForceLayout, JsError> {
+        console_error_panic_hook::set_once();
+
+        let mut adjacency = vec![];
+        let mut edge_types = vec![];
+        for edge in edges.chunks_exact(3) {
+            let (source, target, ty) = (edge[0], edge[1], edge[2]);
+            let ty = u8::try_from(ty)
+                .ok()
+                .and_then(shared::EdgeType::from_index)
+                .ok_or_else(|| JsError::new(&format!("unknown edge type {ty}")))?;
+            if ty == shared::EdgeType::Related {
+                continue;
+            }
+            if source as usize >= num_nodes || target as usize >= num_nodes {
+                return Err(JsError::new(&format!(
+                    "edge {source}-{target} is out of bounds for {num_nodes} nodes"
+                )));
+            }
+            adjacency.push((source as usize, target as usize));
+            edge_types.push(ty);
+        }
+
+        let input = layout::LayoutInput {
+            num_nodes,
+            adjacency: &adjacency,
+            edge_types: &edge_types,
+            communities: &[],
+            pins: &[],
+            initial_positions: &[],
+            seed,
+            initialization: layout::Initialization::default(),
+            precision: layout::Precision::default(),
+        };
+        Ok(ForceLayout {
+            simulation: layout::Simulation::new(&input, true),
+        })
+    }
+
+    /// Run the next iteration, returning whether there was one to run; once this returns
+    /// `false`, the layout has settled.
+    pub fn step(&mut self) -> bool {
+        self.simulation.step().is_some()
+    }
+
+    /// Every node's current position, flattened as `[x, y, x, y, ...]`.
+    pub fn positions(&self) -> Vec<f64> {
+        self.simulation.positions().concat()
+    }
+}
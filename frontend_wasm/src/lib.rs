@@ -2,6 +2,8 @@ use std::{str::FromStr as _, sync::LazyLock};
 
 use wasm_bindgen::prelude::*;
 
+mod force_layout;
+pub use force_layout::*;
 mod search;
 pub use search::*;
 
@@ -18,7 +20,28 @@ pub fn parse_and_simplify_wikitext(
     wikitext_simplified::simplify_wikitext_nodes(wikitext, &output.nodes).unwrap()
 }
 
+/// Decode graph data written in the MessagePack format (`data.msgpack`) into the same objects
+/// that parsing `data.json` would produce.
+#[wasm_bindgen]
+pub fn decode_data(bytes: &[u8]) -> Result<JsValue, JsError> {
+    use serde::Serialize as _;
+
+    console_error_panic_hook::set_once();
+
+    let value = rmpv::decode::read_value(&mut &bytes[..])?;
+    Ok(value.serialize(&serde_wasm_bindgen::Serializer::json_compatible())?)
+}
+
 #[wasm_bindgen]
 pub fn page_name_to_filename(page_name: &str) -> String {
     shared::PageName::from_str(page_name).unwrap().sanitize()
 }
+
+/// The path of the file for `page_name` in `directory`, in its prefix subdirectory if the
+/// files are sharded.
+#[wasm_bindgen]
+pub fn page_name_to_data_file_path(directory: &str, page_name: &str, sharded: bool) -> String {
+    shared::PageName::from_str(page_name)
+        .unwrap()
+        .data_file_path(directory, sharded)
+}
@@ -1,8 +1,9 @@
 //! Fuzzy genre search with externally-tunable ranking.
 //!
-//! The index is built once from node labels, aliases, and inbound link counts;
-//! every query is scored against all entries with weights supplied per call,
-//! so ranking can be tuned from JS without recompiling.
+//! The index is built once, either from node labels, aliases, and inbound link
+//! counts, or from the prebuilt `search_index.json` (which adds top artists and
+//! titles in other languages); every query is scored against all entries with
+//! weights supplied per call, so ranking can be tuned from JS without recompiling.
 
 use serde::{Deserialize, Serialize};
 use tsify_next::Tsify;
@@ -29,10 +30,14 @@ pub struct SearchParams {
     /// Score for a non-contiguous (subsequence) match.
     #[tsify(optional)]
     pub subsequence_weight: f64,
-    /// Multiplier (< 1.0) applied when the best match is an alias rather than
-    /// the genre's label.
+    /// Multiplier (< 1.0) applied when the best match is an alias or a title
+    /// in another language rather than the genre's label.
     #[tsify(optional)]
     pub alias_penalty: f64,
+    /// Multiplier (< 1.0) applied when the best match is one of the genre's
+    /// top artists.
+    #[tsify(optional)]
+    pub artist_penalty: f64,
     /// Additive popularity bonus: `popularity_weight * ln(1+links) / ln(1+max_links)`.
     #[tsify(optional)]
     pub popularity_weight: f64,
@@ -49,6 +54,7 @@ impl Default for SearchParams {
             substring_weight: 100.0,
             subsequence_weight: 25.0,
             alias_penalty: 0.8,
+            artist_penalty: 0.5,
             popularity_weight: 50.0,
             limit: 10,
         }
@@ -64,15 +70,32 @@ pub struct SearchResult {
     /// constructed from — matches the frontend's `NodeData.id`.
     pub id: String,
     pub score: f64,
-    /// The text that matched: the label, or the alias that matched.
+    /// The text that matched: the label, or the alias, artist or title that matched.
     pub matched_text: String,
-    /// True when `matched_text` is an alias rather than the label.
+    /// True when `matched_text` is anything other than the label.
     pub is_alias: bool,
+    /// What `matched_text` is.
+    pub kind: MatchKind,
     /// Match spans within `matched_text`, in UTF-16 code units (directly
     /// usable with JS `String.slice`).
     pub spans: Vec<MatchSpan>,
 }
 
+/// What a search term is, in order of preference when a genre matches equally
+/// well through several.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Tsify)]
+#[serde(rename_all = "camelCase")]
+pub enum MatchKind {
+    /// The genre's label.
+    Label,
+    /// An alternative name for the genre.
+    Alias,
+    /// The genre's title in another language.
+    Translation,
+    /// One of the genre's top artists.
+    Artist,
+}
+
 /// A half-open range of UTF-16 code units.
 #[derive(Debug, Clone, Copy, Serialize, Tsify)]
 pub struct MatchSpan {
@@ -89,11 +112,12 @@ struct IndexEntry {
     /// For each byte of `normalized`, the UTF-16 range in `text` of the
     /// original character that produced it (start at `[i].0`, end at `[i].1`).
     utf16_map: Vec<(u32, u32)>,
-    is_alias: bool,
+    kind: MatchKind,
     popularity_ln: f64,
 }
 
-/// A search index over genre labels and aliases.
+/// A search index over genre labels, aliases, top artists, and titles in other
+/// languages.
 pub struct SearchIndex {
     entries: Vec<IndexEntry>,
     node_count: usize,
@@ -120,24 +144,40 @@ impl SearchIndex {
     /// Build an index from `(label, aliases, inbound_link_count)` per node, in
     /// node order.
     pub fn new(nodes: impl IntoIterator<Item = (String, Vec<String>, usize)>) -> Self {
+        Self::from_index(
+            nodes
+                .into_iter()
+                .map(|(label, aliases, links)| shared::SearchIndexNode {
+                    label,
+                    aliases,
+                    links,
+                    ..Default::default()
+                }),
+        )
+    }
+
+    /// Build an index from the nodes of a prebuilt search index, in node order.
+    pub fn from_index(nodes: impl IntoIterator<Item = shared::SearchIndexNode>) -> Self {
         let mut entries = Vec::new();
         let mut node_count = 0;
         let mut max_popularity_ln = 0.0f64;
-        for (node_index, (label, aliases, links)) in nodes.into_iter().enumerate() {
+        for (node_index, node) in nodes.into_iter().enumerate() {
             node_count += 1;
-            let popularity_ln = (1.0 + links as f64).ln();
+            let popularity_ln = (1.0 + node.links as f64).ln();
             max_popularity_ln = max_popularity_ln.max(popularity_ln);
-            entries.push(IndexEntry::new(
-                node_index as u32,
-                label,
-                false,
-                popularity_ln,
-            ));
-            for alias in aliases {
+            let terms = std::iter::once((node.label, MatchKind::Label))
+                .chain(node.aliases.into_iter().map(|t| (t, MatchKind::Alias)))
+                .chain(
+                    node.translations
+                        .into_iter()
+                        .map(|t| (t, MatchKind::Translation)),
+                )
+                .chain(node.artists.into_iter().map(|t| (t, MatchKind::Artist)));
+            for (text, kind) in terms {
                 entries.push(IndexEntry::new(
                     node_index as u32,
-                    alias,
-                    true,
+                    text,
+                    kind,
                     popularity_ln,
                 ));
             }
@@ -174,10 +214,10 @@ impl SearchIndex {
             let compactness = 1.0
                 + 0.5 * normalized_query.chars().count() as f64
                     / entry.normalized.chars().count().max(1) as f64;
-            let alias_factor = if entry.is_alias {
-                params.alias_penalty
-            } else {
-                1.0
+            let alias_factor = match entry.kind {
+                MatchKind::Label => 1.0,
+                MatchKind::Alias | MatchKind::Translation => params.alias_penalty,
+                MatchKind::Artist => params.artist_penalty,
             };
             let popularity = if self.max_popularity_ln > 0.0 {
                 params.popularity_weight * entry.popularity_ln / self.max_popularity_ln
@@ -189,12 +229,11 @@ impl SearchIndex {
             let slot = &mut best[entry.node_index as usize];
             let replace = match slot {
                 None => true,
-                // Prefer the label over an alias on equal scores.
+                // Prefer the label over an alias (and so on) on equal scores.
                 Some(existing) => {
                     score > existing.score
                         || (score == existing.score
-                            && self.entries[existing.entry_index].is_alias
-                            && !entry.is_alias)
+                            && entry.kind < self.entries[existing.entry_index].kind)
                 }
             };
             if replace {
@@ -226,7 +265,8 @@ impl SearchIndex {
                     id: entry.node_index.to_string(),
                     score: candidate.score,
                     matched_text: entry.text.clone(),
-                    is_alias: entry.is_alias,
+                    is_alias: entry.kind != MatchKind::Label,
+                    kind: entry.kind,
                     spans: entry.spans_to_utf16(&candidate.spans),
                 }
             })
@@ -235,7 +275,7 @@ impl SearchIndex {
 }
 
 impl IndexEntry {
-    fn new(node_index: u32, text: String, is_alias: bool, popularity_ln: f64) -> Self {
+    fn new(node_index: u32, text: String, kind: MatchKind, popularity_ln: f64) -> Self {
         let mut normalized = String::new();
         let mut utf16_map = Vec::new();
         let mut utf16_offset = 0u32;
@@ -253,7 +293,7 @@ impl IndexEntry {
             text,
             normalized,
             utf16_map,
-            is_alias,
+            kind,
             popularity_ln,
         }
     }
@@ -289,6 +329,10 @@ impl IndexEntry {
             };
             return Some((tier, vec![(position, position + query.len())]));
         }
+        // Scattered letters across an artist's name say little about the genre
+        if self.kind == MatchKind::Artist {
+            return None;
+        }
         self.match_subsequence(query)
             .map(|spans| (MatchTier::Subsequence, spans))
     }
@@ -348,7 +392,8 @@ pub struct SearcherNode {
     pub links: usize,
 }
 
-/// Genre search over labels and aliases, weighted by popularity.
+/// Genre search over labels, aliases, top artists, and titles in other
+/// languages, weighted by popularity.
 #[wasm_bindgen]
 pub struct GenreSearcher {
     index: SearchIndex,
@@ -369,6 +414,16 @@ impl GenreSearcher {
         }
     }
 
+    /// Build a searcher from the prebuilt search index (`search_index.json`),
+    /// whose nodes are in `data.nodes` order.
+    pub fn from_index(index: JsValue) -> Result<GenreSearcher, JsError> {
+        console_error_panic_hook::set_once();
+        let nodes: Vec<shared::SearchIndexNode> = serde_wasm_bindgen::from_value(index)?;
+        Ok(GenreSearcher {
+            index: SearchIndex::from_index(nodes),
+        })
+    }
+
     pub fn search(&self, query: &str, params: Option<SearchParams>) -> Vec<SearchResult> {
         self.index.search(query, &params.unwrap_or_default())
     }
@@ -488,4 +543,33 @@ mod tests {
         assert!(idx.search("", &SearchParams::default()).is_empty());
         assert!(idx.search(" ", &SearchParams::default()).is_empty());
     }
+
+    #[test]
+    fn prebuilt_index_matches_artists_and_translations() {
+        let idx = SearchIndex::from_index([
+            shared::SearchIndexNode {
+                label: "Techno".to_string(),
+                artists: vec!["Juan Atkins".to_string()],
+                translations: vec!["テクノ".to_string()],
+                ..Default::default()
+            },
+            shared::SearchIndexNode {
+                label: "Atkins diet rock".to_string(),
+                ..Default::default()
+            },
+        ]);
+
+        let results = idx.search("テクノ", &SearchParams::default());
+        assert_eq!(ids(&results), ["0"]);
+        assert_eq!(results[0].kind, MatchKind::Translation);
+
+        // A genre's own name outranks an artist's.
+        let results = idx.search("atkins", &SearchParams::default());
+        assert_eq!(ids(&results), ["1", "0"]);
+        assert_eq!(results[1].kind, MatchKind::Artist);
+        assert!(results[1].is_alias);
+
+        // Artists aren't matched by subsequence.
+        assert!(idx.search("jnatk", &SearchParams::default()).is_empty());
+    }
 }
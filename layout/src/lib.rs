@@ -0,0 +1,1861 @@
+//! Graph layout algorithms, behind the [`LayoutAlgorithm`] trait so that they
+//! can be swapped and compared: [`ForceAtlas2`] (the default), [`BarnesHut`],
+//! and [`StressMajorization`] (see [`stress_majorization`]), chosen by
+//! [`Algorithm`].
+//!
+//! The two force-directed layouts compute 2D positions (or, with `three_d` in
+//! the `[layout]` section of `config.toml`, 3D ones) for graph nodes using:
+//! - Quadtree-based (octree-based, in 3D) Barnes-Hut approximation for
+//!   O(n log n) repulsion
+//! - Jaccard-weighted spring forces along edges (community-aware attraction)
+//! - Gravity pulling toward center
+//! - Velocity integration with friction damping and exponential cooling
+//! - Optionally, nodes pinned in place (see [`LayoutInput::pins`])
+//!
+//! All layout parameters can be overridden via environment variables for
+//! tuning (see `sweep_layout.sh` for examples). Key parameters:
+//!
+//! ## Energy model
+//! - [`ForceAtlas2`] uses the LinLog energy model. Edge attraction grows as
+//!   `log(1+d)` instead of a linear Hooke spring, and repulsion falls off as
+//!   `1/d` instead of `1/d²`. This pairing squeezes intra-cluster distances
+//!   while opening up inter-cluster gaps, so communities settle into distinct
+//!   clumps instead of one uniform hairball. [`BarnesHut`] uses the original
+//!   Fruchterman-Reingold spring model.
+//! - `REP_DIST_EXP`: repulsion falloff exponent (LinLog defaults to 1.0, FR to
+//!   2.0).
+//! - `ATTRACT_MIN`: in LinLog, the per-edge attraction weight for a
+//!   zero-Jaccard bridge; intra-cluster edges scale up toward 1.0 as Jaccard
+//!   → 1, so bridges stretch while communities contract. (In FR mode Jaccard
+//!   instead modulates the spring rest length via `BRIDGE_MULT`.)
+//!
+//! In LinLog mode the isolated set (see `ISO_COMPONENT_MAX`) is laid out
+//! deterministically in a ring just outside the connected core after the
+//! simulation, rather than being flung out by repulsion + `SPIN`.
+//!
+//! - `NODE_MIN_DIST`: after the layout is normalized, a collision-relaxation
+//!   pass (d3-force `forceCollide` style) pushes apart any nodes closer than
+//!   this many world units. It only acts at short range, so it declutters
+//!   overlapping genres without flattening the cluster density that conveys
+//!   structure. Set to 0 to disable.
+//! - `HUB_RADIUS`: how much further, in world units, the collision pass keeps
+//!   other nodes from the highest-degree node, scaling linearly with degree
+//!   (as the website draws nodes). Each node's mass is `1 + degree`, and a
+//!   colliding pair is pushed apart in inverse proportion to their masses, so
+//!   hubs hold their place while their swarms of neighbours make room around
+//!   them.
+//!
+//! ## Repulsion
+//! - `REPULSION`: Global repulsive force strength.
+//! - `THETA`: Barnes-Hut opening angle. Lower = more accurate but slower.
+//! - `BASE_CHARGE`: Minimum charge for connected nodes (default 3). Higher
+//!   values ensure even low-degree nodes repel each other enough to avoid
+//!   clumping.
+//! - `CHARGE_EXP`: Degree-charge exponent. Each node's charge in the
+//!   quadtree is `BASE_CHARGE + degree^exp`. Values >1 make hubs repel
+//!   super-linearly, creating visible voids between major clusters.
+//! - `ISO_CHARGE`: Fixed charge for isolated (degree-0) nodes. Low values
+//!   reduce how much the connected core pushes them away, so the outer ring
+//!   orbits closer.
+//!
+//! ## Springs (edge attraction)
+//! - `LINK_SPRING`: Spring constant (Hooke's law).
+//! - `LINK_DISTANCE`: Base rest length for intra-cluster edges.
+//! - `BRIDGE_MULT`: Jaccard bridge multiplier. Each edge's rest length is
+//!   `LINK_DISTANCE * (BRIDGE_MULT - (BRIDGE_MULT-1) * jaccard)`, so edges
+//!   between nodes sharing many neighbors (high Jaccard) pull tight, while
+//!   inter-community bridges (low Jaccard) have up to `BRIDGE_MULT`× the
+//!   rest length. This is the single biggest contributor to cluster visibility.
+//! - `SPRING_NORM`: Per-endpoint force is divided by `degree^SPRING_NORM`,
+//!   so hub nodes aren't yanked equally hard by every edge.
+//! - `SUBGENRE_SPRING`/`SUBGENRE_LENGTH`, `DERIVATIVE_SPRING`/`DERIVATIVE_LENGTH`,
+//!   `FUSION_SPRING`/`FUSION_LENGTH`: multipliers on the spring strength and
+//!   rest length of each type of edge. Subgenres pull tighter than the
+//!   default and fusion genres looser, so that families of subgenres stay
+//!   together while fusions sit between the genres they fuse. In LinLog the
+//!   length multiplier scales the distance the attraction grows with, so a
+//!   longer edge pulls more gently at the same distance.
+//!
+//! ## Gravity & isolated-node handling
+//! - `GRAVITY`: Gravity strength for connected nodes (pulls toward origin).
+//! - `COMMUNITY_GRAVITY` (default off): pulls each connected node toward the
+//!   centroid of its community (see [`LayoutInput::communities`]), so that families
+//!   of genres gather into their own regions instead of interleaving with
+//!   their neighbours in dense parts of the layout. Around 0.05 gathers them
+//!   noticeably while keeping the bridges between them; 0.1 and up packs each
+//!   community into its own cell.
+//! - `GRAVITY_ISOLATED`: Gravity for isolated nodes (stronger = tighter ring).
+//! - `SPIN`: Tangential velocity added to isolated nodes so they distribute
+//!   around the ring instead of clumping. Scales with distance from origin.
+//! - `ISO_COMPONENT_MAX`: Maximum component size to treat as "isolated".
+//!   Nodes in components with at most this many members get the isolated
+//!   treatment (reduced charge, stronger gravity, spin).
+//!
+//! ## Simulation
+//! - `ITERATIONS`: Total simulation steps, at most.
+//! - `CONVERGENCE`: The simulation stops early once the connected nodes'
+//!   mean displacement per step, relative to the layout's RMS radius, has
+//!   stayed below this for `CONVERGENCE_WINDOW` steps in a row. Set to 0 to
+//!   always run every iteration.
+//! - `COOLING_RATE`: Exponential cooling: `temperature = exp(-rate * t)`.
+//!   Lower values keep the simulation warm longer, giving clusters more time
+//!   to separate before freezing.
+//! - `FRICTION`: Velocity damping per step.
+//! - `MAX_VELOCITY`: Velocity clamp (scaled by temperature).
+//! - `WARM_START`: How far through the cooling schedule a warm-started layout
+//!   (see [`LayoutInput::initial_positions`]) begins, from 0 (a full run) to 1 (no simulation).
+//!
+//! ## Stepping and parallelism
+//! [`LayoutAlgorithm::compute`] runs a layout to the end, but the force-directed layouts can
+//! also be run an iteration at a time with a [`Simulation`], as the website does through
+//! `frontend_wasm` to lay out a graph without blocking. The `parallel` feature (on by default)
+//! spreads the work of each iteration over every core with rayon; without it, as in wasm, the
+//! layouts run on one thread.
+
+#![warn(missing_docs)]
+
+use shared::EdgeType;
+
+mod simd;
+pub mod stress_majorization;
+use stress_majorization::StressMajorization;
+
+/// `f` of each index below `len`, in order. The calls are spread over every core with the
+/// `parallel` feature.
+pub(crate) fn map_indices<T: Send>(len: usize, f: impl Fn(usize) -> T + Sync + Send) -> Vec<T> {
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        (0..len).into_par_iter().map(f).collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        (0..len).map(f).collect()
+    }
+}
+
+// Layout constants (overridable via environment variables for tuning)
+pub(crate) fn env_f64(name: &str, default: f64) -> f64 {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+pub(crate) fn env_usize(name: &str, default: usize) -> usize {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+const MAX_TREE_DEPTH: usize = 40;
+
+/// The most children a [`Tree`] node can have: 4 for a quadtree in 2D, 8 for
+/// an octree in 3D.
+const MAX_CHILDREN: usize = 8;
+
+/// A node in the Barnes-Hut tree (a quadtree in 2D, an octree in 3D), either a
+/// leaf or an internal node with up to `2^D` children.
+struct Tree<const D: usize> {
+    /// Center of mass
+    center: [f64; D],
+    /// Total mass (number of nodes)
+    mass: f64,
+    /// Bounding box
+    min: [f64; D],
+    max: [f64; D],
+    /// Children, indexed by which half of each axis they cover (bit `d` set
+    /// for the upper half of axis `d`; NW, NE, SW, SE in 2D), as indices into
+    /// the arena
+    children: [Option<usize>; MAX_CHILDREN],
+}
+
+/// The tree's nodes, with the buffers used to build it. Kept across iterations so that
+/// rebuilding the tree doesn't allocate.
+struct TreeArena<const D: usize> {
+    nodes: Vec<Tree<D>>,
+    /// The layout nodes, in the order of the tree's leaves (Morton order)
+    order: Vec<usize>,
+    /// The child cell of each of `order`, while partitioning
+    cells: Vec<u8>,
+    scratch: Vec<usize>,
+}
+
+impl<const D: usize> TreeArena<D> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            nodes: Vec::with_capacity(capacity),
+            order: vec![],
+            cells: vec![],
+            scratch: vec![],
+        }
+    }
+
+    fn alloc(&mut self, min: [f64; D], max: [f64; D]) -> usize {
+        let idx = self.nodes.len();
+        self.nodes.push(Tree {
+            center: [0.0; D],
+            mass: 0.0,
+            min,
+            max,
+            children: [None; MAX_CHILDREN],
+        });
+        idx
+    }
+
+    /// Rebuild the tree over `positions` within `min`..`max`, returning its root.
+    ///
+    /// Rather than inserting the nodes one at a time from the root, they're partitioned into
+    /// cells level by level, which sorts them into Morton order, and each tree node is built
+    /// from its contiguous run of them. A subtree's mass and centre of mass are summed over that
+    /// run, so each point is visited once per level.
+    fn rebuild(
+        &mut self,
+        min: [f64; D],
+        max: [f64; D],
+        positions: &[[f64; D]],
+        charges: &[f64],
+    ) -> usize {
+        self.nodes.clear();
+        self.order.clear();
+        self.order.extend(0..positions.len());
+        self.cells.resize(positions.len(), 0);
+        self.scratch.resize(positions.len(), 0);
+        self.build(min, max, 0, positions.len(), 0, positions, charges)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn build(
+        &mut self,
+        min: [f64; D],
+        max: [f64; D],
+        start: usize,
+        end: usize,
+        depth: usize,
+        positions: &[[f64; D]],
+        charges: &[f64],
+    ) -> usize {
+        let root = self.alloc(min, max);
+        let mut mass = 0.0;
+        let mut sums = [0.0; D];
+        for &i in &self.order[start..end] {
+            mass += charges[i];
+            for (sum, coordinate) in sums.iter_mut().zip(positions[i]) {
+                *sum += coordinate * charges[i];
+            }
+        }
+        let node = &mut self.nodes[root];
+        node.mass = mass;
+        if mass > 0.0 {
+            node.center = sums.map(|sum| sum / mass);
+        }
+        // A single node is a leaf, as are nodes too close together to separate
+        if end - start <= 1 || depth >= MAX_TREE_DEPTH {
+            return root;
+        }
+
+        // Stable counting sort of the run by cell
+        let mut counts = [0; MAX_CHILDREN];
+        for k in start..end {
+            let cell = cell(min, max, positions[self.order[k]]);
+            self.cells[k] = cell as u8;
+            counts[cell] += 1;
+        }
+        let mut offsets = [start; MAX_CHILDREN];
+        for cell in 1..MAX_CHILDREN {
+            offsets[cell] = offsets[cell - 1] + counts[cell - 1];
+        }
+        let mut next = offsets;
+        for k in start..end {
+            let cell = self.cells[k] as usize;
+            self.scratch[next[cell]] = self.order[k];
+            next[cell] += 1;
+        }
+        self.order[start..end].copy_from_slice(&self.scratch[start..end]);
+
+        for cell in 0..1 << D {
+            if counts[cell] == 0 {
+                continue;
+            }
+            let (child_min, child_max) = cell_bounds(min, max, cell);
+            let child = self.build(
+                child_min,
+                child_max,
+                offsets[cell],
+                offsets[cell] + counts[cell],
+                depth + 1,
+                positions,
+                charges,
+            );
+            self.nodes[root].children[cell] = Some(child);
+        }
+        root
+    }
+
+    /// Compute repulsive force on a node from the tree.
+    ///
+    /// `rep_dist_exp` controls the falloff: 2.0 gives classic
+    /// Fruchterman-Reingold `1/d²` repulsion, 1.0 gives the LinLog /
+    /// ForceAtlas2 `1/d` repulsion that separates clusters more cleanly.
+    fn compute_repulsion(
+        &self,
+        root: usize,
+        position: [f64; D],
+        repulsion: f64,
+        theta: f64,
+        rep_dist_exp: f64,
+        force: &mut [f64; D],
+    ) {
+        self.for_each_interaction(root, position, theta, &mut |delta, dist_sq, mass| {
+            let dist = dist_sq.sqrt().max(1.0);
+            // f = repulsion * mass / dist^rep_dist_exp, applied along the unit
+            // vector (delta/dist). The +1 in the exponent accounts for
+            // that normalization.
+            let f = repulsion * mass / dist.powf(rep_dist_exp);
+            for (force, delta) in force.iter_mut().zip(delta) {
+                *force += delta / dist * f;
+            }
+        });
+    }
+
+    /// Call `interact` with the offset from each tree node that acts on a node at `position` as
+    /// a whole (each leaf, and each subtree far enough away, by `theta`, to be approximated by
+    /// its centre of mass), the offset's squared length, and the tree node's mass.
+    fn for_each_interaction(
+        &self,
+        root: usize,
+        position: [f64; D],
+        theta: f64,
+        interact: &mut impl FnMut([f64; D], f64, f64),
+    ) {
+        let node = &self.nodes[root];
+        if node.mass == 0.0 {
+            return;
+        }
+
+        let delta: [f64; D] = std::array::from_fn(|d| position[d] - node.center[d]);
+        let dist_sq: f64 = delta.iter().map(|v| v * v).sum();
+
+        let size = node.max[0] - node.min[0];
+
+        let is_leaf = node.children.iter().all(|c| c.is_none());
+        let is_far = size * size < theta * theta * dist_sq;
+
+        if (is_leaf || is_far) && dist_sq > 1e-6 {
+            interact(delta, dist_sq, node.mass);
+        } else if !is_leaf {
+            for child_idx in node.children.iter().flatten() {
+                self.for_each_interaction(*child_idx, position, theta, interact);
+            }
+        }
+    }
+}
+
+/// The child cell of `min`..`max` that `pos` is in: bit `d` is set if it's in
+/// the upper half of axis `d` (NW, NE, SW or SE in 2D).
+fn cell<const D: usize>(min: [f64; D], max: [f64; D], pos: [f64; D]) -> usize {
+    (0..D)
+        .filter(|&d| pos[d] > (min[d] + max[d]) / 2.0)
+        .map(|d| 1 << d)
+        .sum()
+}
+
+fn cell_bounds<const D: usize>(min: [f64; D], max: [f64; D], cell: usize) -> ([f64; D], [f64; D]) {
+    let mut child_min = min;
+    let mut child_max = max;
+    for d in 0..D {
+        let mid = (min[d] + max[d]) / 2.0;
+        if cell & (1 << d) == 0 {
+            child_max[d] = mid;
+        } else {
+            child_min[d] = mid;
+        }
+    }
+    (child_min, child_max)
+}
+
+/// Clamp a value to [-limit, limit].
+fn clamp_abs(v: f64, limit: f64) -> f64 {
+    v.clamp(-limit, limit)
+}
+
+/// The layout algorithms, as chosen by `algorithm` in the `[layout]` section of `config.toml`
+/// (or `relayout --algorithm`).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Algorithm {
+    /// [`BarnesHut`].
+    BarnesHut,
+    /// [`ForceAtlas2`].
+    #[default]
+    ForceAtlas2,
+    /// [`StressMajorization`].
+    StressMajorization,
+}
+
+impl Algorithm {
+    /// The algorithm's implementation.
+    pub fn layout(self) -> &'static dyn LayoutAlgorithm {
+        match self {
+            Self::BarnesHut => &BarnesHut,
+            Self::ForceAtlas2 => &ForceAtlas2,
+            Self::StressMajorization => &StressMajorization,
+        }
+    }
+}
+
+/// How the force-directed layouts place the nodes before the simulation, as chosen by
+/// `initialization` in the `[layout]` section of `config.toml` (or `relayout --initialization`).
+/// Stress majorization starts from its own embedding instead.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Initialization {
+    /// Scattered uniformly at random.
+    #[default]
+    Random,
+    /// Grown outward from each component's highest-degree node, with each node jittered at
+    /// random around the neighbours placed before it, so that the simulation starts closer to
+    /// the graph's structure.
+    Neighbors,
+}
+
+/// The precision the force-directed layouts compute their forces and velocities in, as chosen by
+/// `precision` in the `[layout]` section of `config.toml` (or `relayout --precision`).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Precision {
+    /// `f64`, a node at a time.
+    #[default]
+    F64,
+    /// `f32`, several nodes at a time with SIMD (see `simd.rs`), which is faster for large
+    /// graphs; the layout stays close to the `f64` one, but isn't the same.
+    F32,
+}
+
+/// The graph to lay out, and what to lay it out around.
+#[derive(Clone, Copy)]
+pub struct LayoutInput<'a> {
+    /// The number of nodes.
+    pub num_nodes: usize,
+    /// The edges, as `(source, target)` pairs.
+    pub adjacency: &'a [(usize, usize)],
+    /// The type of each edge, which sets the strength and length of its spring; edges without a
+    /// type get the default spring.
+    pub edge_types: &'a [EdgeType],
+    /// The community of each node, which `COMMUNITY_GRAVITY` pulls together (if empty, nothing
+    /// is).
+    pub communities: &'a [usize],
+    /// The fixed position, in output coordinates, of each node that's pinned (e.g. from
+    /// `[pins]` in datagen's patches). Pinned nodes never move, but still repel and
+    /// pull on the others. While the layout runs they're held at the same place relative to its
+    /// scale, as the output is rescaled afterwards, and so end up exactly at their pins. The
+    /// layout isn't re-centred when any node is pinned, as the pins fix where it is.
+    pub pins: &'a [Option<[f64; 2]>],
+    /// Where each node starts, if anywhere (e.g. the previous dump's layout), so that the layout
+    /// stays familiar and settles sooner. Nodes with an initial position start there (rescaled to
+    /// the simulation's scale), and nodes without one start beside their positioned
+    /// neighbours. The force-directed layouts then begin `WARM_START` of the way through their
+    /// cooling schedule instead of from the hottest temperature.
+    pub initial_positions: &'a [Option<[f64; 2]>],
+    /// The seed of the random numbers the nodes are placed with, before the simulation. The same
+    /// input and seed always give the same layout.
+    pub seed: u32,
+    /// How the nodes are placed before the simulation, unless they have initial positions.
+    pub initialization: Initialization,
+    /// The precision the force-directed layouts are computed in.
+    pub precision: Precision,
+}
+
+/// A way of laying out the graph, so that the algorithms can be swapped and compared.
+pub trait LayoutAlgorithm: Sync {
+    /// Lay out `input`, calling `on_progress` after each iteration (e.g. with
+    /// [`print_progress`]). Returns one position per node.
+    fn compute(&self, input: &LayoutInput, on_progress: &mut dyn FnMut(&Progress))
+    -> Vec<[f64; 2]>;
+
+    /// Lay out `input` in 3D, as with [`LayoutAlgorithm::compute`], or `None` if the algorithm
+    /// can only lay out in 2D. The pins and initial positions are still 2D, and are used for the
+    /// first two coordinates.
+    fn compute_3d(
+        &self,
+        _input: &LayoutInput,
+        _on_progress: &mut dyn FnMut(&Progress<3>),
+    ) -> Option<Vec<[f64; 3]>> {
+        None
+    }
+}
+
+/// Fruchterman-Reingold: Hooke springs along the edges against `1/d²` repulsion, approximated
+/// with a Barnes-Hut quadtree (an octree in 3D). Isolated nodes are spun into an orbit around the
+/// others.
+pub struct BarnesHut;
+
+impl LayoutAlgorithm for BarnesHut {
+    fn compute(
+        &self,
+        input: &LayoutInput,
+        on_progress: &mut dyn FnMut(&Progress),
+    ) -> Vec<[f64; 2]> {
+        force_directed(false, input, on_progress)
+    }
+
+    fn compute_3d(
+        &self,
+        input: &LayoutInput,
+        on_progress: &mut dyn FnMut(&Progress<3>),
+    ) -> Option<Vec<[f64; 3]>> {
+        Some(force_directed(false, input, on_progress))
+    }
+}
+
+/// The LinLog energy model of ForceAtlas2: logarithmic attraction along the edges against `1/d`
+/// repulsion (with the same Barnes-Hut quadtree), which separates communities into distinct
+/// clumps. The output is rescaled, its isolated nodes placed in a ring around the others, and
+/// overlapping nodes pushed apart.
+pub struct ForceAtlas2;
+
+impl LayoutAlgorithm for ForceAtlas2 {
+    fn compute(
+        &self,
+        input: &LayoutInput,
+        on_progress: &mut dyn FnMut(&Progress),
+    ) -> Vec<[f64; 2]> {
+        force_directed(true, input, on_progress)
+    }
+
+    fn compute_3d(
+        &self,
+        input: &LayoutInput,
+        on_progress: &mut dyn FnMut(&Progress<3>),
+    ) -> Option<Vec<[f64; 3]>> {
+        Some(force_directed(true, input, on_progress))
+    }
+}
+
+/// The state of the layout after an iteration, as given to `on_progress`, in
+/// `D` dimensions.
+pub struct Progress<'a, const D: usize = 2> {
+    /// The iteration just finished, counting from 0 (or, when warm-started, from where the
+    /// cooling schedule was started).
+    pub iteration: usize,
+    /// The most iterations the layout runs for; it stops sooner once it's settled.
+    pub iterations: usize,
+    /// The temperature the iteration ran at, from 1 down (always 1 for stress majorization,
+    /// which doesn't cool).
+    pub temperature: f64,
+    /// The furthest any connected node moved, in the simulation's units.
+    pub max_displacement: f64,
+    /// Every node's position, in the simulation's units. Except with [`BarnesHut`], the positions
+    /// are rescaled and decluttered after the last iteration, and the isolated nodes placed
+    /// around the others.
+    pub positions: &'a [[f64; D]],
+    /// Anything else the layout has to report, for the caller to print if it likes: the
+    /// parameters it runs with (with the first iteration), and that it's converged (with the
+    /// last).
+    pub notes: &'a [String],
+}
+
+/// Print the layout's notes, and every 100th iteration's progress.
+pub fn print_progress<const D: usize>(progress: &Progress<D>) {
+    for note in progress.notes {
+        println!("  {note}");
+    }
+    if progress.iteration.is_multiple_of(100) {
+        println!(
+            "  layout iteration {}/{} (temperature: {:.3}, max displacement: {:.3})",
+            progress.iteration,
+            progress.iterations,
+            progress.temperature,
+            progress.max_displacement
+        );
+    }
+}
+
+/// Lay out `input` in `D` dimensions with the LinLog energy model if `linlog`, or
+/// Fruchterman-Reingold if not.
+fn force_directed<const D: usize>(
+    linlog: bool,
+    input: &LayoutInput,
+    on_progress: &mut dyn FnMut(&Progress<D>),
+) -> Vec<[f64; D]> {
+    let mut simulation = Simulation::new(input, linlog);
+    while let Some(progress) = simulation.step() {
+        on_progress(&progress);
+    }
+    simulation.finish()
+}
+
+/// The force-directed layouts' parameters, from the environment variables documented in the
+/// [crate documentation](crate).
+#[derive(Clone, Copy)]
+struct Parameters {
+    rep_dist_exp: f64,
+    attract_min: f64,
+    repulsion: f64,
+    theta: f64,
+    link_spring: f64,
+    link_distance: f64,
+    gravity: f64,
+    gravity_isolated: f64,
+    community_gravity: f64,
+    spin: f64,
+    friction: f64,
+    iterations: usize,
+    max_velocity: f64,
+    cooling_rate: f64,
+    charge_exponent: f64,
+    spring_norm: f64,
+    bridge_mult: f64,
+    isolated_charge: f64,
+    base_charge: f64,
+    warm_start: f64,
+    convergence: f64,
+    convergence_window: usize,
+    springs: EdgeSprings,
+}
+
+impl Parameters {
+    /// Read the parameters for the LinLog energy model if `linlog`, or Fruchterman-Reingold if
+    /// not.
+    fn from_env(linlog: bool) -> Self {
+        // LinLog / ForceAtlas2-style energy model. When enabled, attraction along
+        // edges grows logarithmically with distance (instead of the linear Hooke
+        // spring) and repulsion falls off as 1/d (instead of 1/d²). This pair is
+        // what makes communities separate into distinct clumps instead of a single
+        // hairball: intra-cluster distances are squeezed while inter-cluster gaps
+        // open up. Jaccard similarity becomes a per-edge attraction *weight* —
+        // high-similarity (intra-cluster) edges pull harder, low-similarity bridges
+        // pull weakly — rather than modulating a rest length.
+        let rep_dist_exp = env_f64("REP_DIST_EXP", if linlog { 1.0 } else { 2.0 });
+        // Minimum attraction weight for a zero-Jaccard bridge edge; intra-cluster
+        // edges scale up toward 1.0 as Jaccard → 1.
+        let attract_min = env_f64("ATTRACT_MIN", 0.25);
+
+        // Repulsion is ~d weaker in LinLog (1/d vs 1/d²), so it needs a much
+        // smaller constant to balance the gentler log attraction.
+        let repulsion = env_f64("REPULSION", if linlog { 350.0 } else { 170000.0 });
+        let theta = env_f64("THETA", 0.8);
+        let link_spring = env_f64("LINK_SPRING", if linlog { 250.0 } else { 28.0 });
+        let link_distance = env_f64("LINK_DISTANCE", 10.0);
+        // Gravity is a weak linear pull toward the origin. LinLog needs only a
+        // gentle centering force — just enough to keep the layout from drifting
+        // apart — so communities can spread instead of being crushed into a disc.
+        let gravity = env_f64("GRAVITY", if linlog { 0.04 } else { 0.75 });
+        let gravity_isolated = env_f64("GRAVITY_ISOLATED", if linlog { 0.15 } else { 1.10 });
+        let community_gravity = env_f64("COMMUNITY_GRAVITY", 0.0);
+        let spin = env_f64("SPIN", 25.0);
+        let friction = env_f64("FRICTION", 0.85);
+        let iterations = env_usize("ITERATIONS", if linlog { 5000 } else { 2000 });
+        let max_velocity = env_f64("MAX_VELOCITY", 25.0);
+        // LinLog separates clusters while warm, then needs a firmer freeze to lock
+        // the knots in place instead of letting them relax back toward a uniform
+        // disc, so it cools harder than the FR default.
+        let cooling_rate = env_f64("COOLING_RATE", if linlog { 2.0 } else { 0.8 });
+        let charge_exponent = env_f64("CHARGE_EXP", 1.2);
+        let spring_norm = env_f64("SPRING_NORM", 0.5);
+        let bridge_mult = env_f64("BRIDGE_MULT", 7.0);
+        let isolated_charge = env_f64("ISO_CHARGE", 0.2);
+        let base_charge = env_f64("BASE_CHARGE", 1.0);
+        let warm_start = env_f64("WARM_START", 0.5).clamp(0.0, 1.0);
+        let convergence = env_f64("CONVERGENCE", 1e-4);
+        let convergence_window = env_usize("CONVERGENCE_WINDOW", 100);
+        let springs = EdgeSprings::from_env();
+
+        Self {
+            rep_dist_exp,
+            attract_min,
+            repulsion,
+            theta,
+            link_spring,
+            link_distance,
+            gravity,
+            gravity_isolated,
+            community_gravity,
+            spin,
+            friction,
+            iterations,
+            max_velocity,
+            cooling_rate,
+            charge_exponent,
+            spring_norm,
+            bridge_mult,
+            isolated_charge,
+            base_charge,
+            warm_start,
+            convergence,
+            convergence_window,
+            springs,
+        }
+    }
+
+    /// The parameters, as lines for [`Progress::notes`].
+    fn notes(&self, linlog: bool) -> Vec<String> {
+        let Self {
+            rep_dist_exp,
+            attract_min,
+            repulsion,
+            theta,
+            link_spring,
+            link_distance,
+            gravity,
+            gravity_isolated,
+            community_gravity,
+            spin,
+            friction,
+            iterations,
+            cooling_rate,
+            charge_exponent,
+            spring_norm,
+            base_charge,
+            convergence,
+            convergence_window,
+            springs:
+                EdgeSprings {
+                    subgenre,
+                    derivative,
+                    fusion,
+                },
+            ..
+        } = self;
+        vec![
+            format!("linlog={linlog} rep_dist_exp={rep_dist_exp} attract_min={attract_min}"),
+            format!(
+                "repulsion={repulsion} theta={theta} spring={link_spring} dist={link_distance}"
+            ),
+            format!(
+                "gravity={gravity} gravity_iso={gravity_isolated} community_gravity={community_gravity} spin={spin}"
+            ),
+            format!("friction={friction} iterations={iterations} cooling={cooling_rate}"),
+            format!("convergence={convergence} convergence_window={convergence_window}"),
+            format!(
+                "charge_exp={charge_exponent} spring_norm={spring_norm} base_charge={base_charge}"
+            ),
+            format!("subgenre={subgenre:?} derivative={derivative:?} fusion={fusion:?}"),
+        ]
+    }
+}
+
+/// A force-directed layout ([`ForceAtlas2`] or [`BarnesHut`]) in `D` dimensions, run an
+/// iteration at a time so that it can be watched as it settles, or spread over animation frames
+/// where it mustn't block (as on the website, through `frontend_wasm`).
+pub struct Simulation<const D: usize = 2> {
+    linlog: bool,
+    precision: Precision,
+    parameters: Parameters,
+    adjacency: Vec<(usize, usize)>,
+    communities: Vec<usize>,
+    pins: Vec<Option<[f64; 2]>>,
+    shape: GraphShape,
+    /// The Jaccard similarity of each edge's ends' neighbourhoods
+    edge_jaccard: Vec<f64>,
+    /// The (strength, rest length) multipliers of each edge's spring
+    edge_springs: Vec<(f64, f64)>,
+    charges: Vec<f64>,
+    positions: Vec<[f64; D]>,
+    velocities: Vec<[f64; D]>,
+    tree: TreeArena<D>,
+    /// The iteration the simulation started from
+    first_iteration: usize,
+    /// The next iteration to run
+    iteration: usize,
+    /// What to report with the next iteration's progress (see [`Progress::notes`])
+    notes: Vec<String>,
+    /// How many iterations in a row the connected nodes have been settled for
+    settled_iterations: usize,
+    /// Whether the connected nodes have settled, which ends the simulation early
+    converged: bool,
+}
+
+impl<const D: usize> Simulation<D> {
+    /// Start laying out `input` with the LinLog energy model of [`ForceAtlas2`] if `linlog`, or
+    /// the Fruchterman-Reingold model of [`BarnesHut`] if not.
+    pub fn new(input: &LayoutInput, linlog: bool) -> Self {
+        let LayoutInput {
+            num_nodes,
+            adjacency,
+            edge_types,
+            communities,
+            pins,
+            precision,
+            ..
+        } = *input;
+        let parameters = Parameters::from_env(linlog);
+        let Parameters {
+            link_distance,
+            iterations,
+            charge_exponent,
+            isolated_charge,
+            base_charge,
+            warm_start,
+            springs,
+            ..
+        } = parameters;
+
+        let shape = GraphShape::new(input);
+        let GraphShape {
+            degrees,
+            neighbors,
+            is_isolated,
+            ..
+        } = &shape;
+
+        // Pre-compute per-edge Jaccard similarity: |N(u)∩N(v)| / |N(u)∪N(v)|.
+        // High similarity → intra-cluster edge (short rest length)
+        // Low similarity → inter-cluster bridge (long rest length)
+        let edge_jaccard: Vec<f64> = adjacency
+            .iter()
+            .map(|&(src, tgt)| {
+                let intersection = neighbors[src].intersection(&neighbors[tgt]).count();
+                let union = neighbors[src].union(&neighbors[tgt]).count();
+                if union == 0 {
+                    0.0
+                } else {
+                    intersection as f64 / union as f64
+                }
+            })
+            .collect();
+
+        let edge_springs: Vec<(f64, f64)> = (0..adjacency.len())
+            .map(|edge_idx| springs.get(edge_types.get(edge_idx).copied()))
+            .collect();
+
+        let (mut positions, warm_started) = starting_positions(input, &shape, link_distance);
+        hold_pins(&mut positions, &shape, pins, linlog);
+
+        // A warm start skips the hottest part of the schedule, which would scramble it
+        let first_iteration = if warm_started {
+            (iterations as f64 * warm_start) as usize
+        } else {
+            0
+        };
+        let charges: Vec<f64> = (0..num_nodes)
+            .map(|i| {
+                // Degree-weighted charge: hub nodes repel more strongly,
+                // creating natural voids between clusters.
+                // Isolated nodes get a reduced charge so they orbit closer to core.
+                if is_isolated[i] {
+                    isolated_charge
+                } else {
+                    base_charge + (degrees[i] as f64).powf(charge_exponent)
+                }
+            })
+            .collect();
+        let mut notes = parameters.notes(linlog);
+        // Only LinLog's layout is tidied up
+        if linlog {
+            notes.push(tidy_note());
+        }
+        // Estimate tree capacity: ~4x nodes for a balanced quadtree (the octree's
+        // more children are mostly empty, so it's no bigger)
+        Self {
+            linlog,
+            precision,
+            parameters,
+            adjacency: adjacency.to_vec(),
+            communities: communities.to_vec(),
+            pins: pins.to_vec(),
+            edge_jaccard,
+            edge_springs,
+            charges,
+            velocities: vec![[0.0; D]; num_nodes],
+            positions,
+            tree: TreeArena::new(num_nodes * 4),
+            first_iteration,
+            iteration: first_iteration,
+            notes,
+            settled_iterations: 0,
+            converged: num_nodes == 0,
+            shape,
+        }
+    }
+
+    /// Every node's current position, in the simulation's units.
+    pub fn positions(&self) -> &[[f64; D]] {
+        &self.positions
+    }
+
+    /// Run the next iteration, and return the state of the layout after it, or `None` once the
+    /// layout has settled or run for its most iterations.
+    pub fn step(&mut self) -> Option<Progress<'_, D>> {
+        let Parameters {
+            rep_dist_exp,
+            attract_min,
+            repulsion,
+            theta,
+            link_spring,
+            link_distance,
+            gravity,
+            gravity_isolated,
+            community_gravity,
+            spin,
+            friction,
+            iterations,
+            max_velocity,
+            cooling_rate,
+            spring_norm,
+            bridge_mult,
+            convergence,
+            convergence_window,
+            ..
+        } = self.parameters;
+        if self.converged || self.iteration >= iterations {
+            return None;
+        }
+        let iter = self.iteration;
+        self.iteration += 1;
+        if iter > self.first_iteration {
+            self.notes.clear();
+        }
+        let (linlog, precision) = (self.linlog, self.precision);
+        let Self {
+            adjacency,
+            communities,
+            pins,
+            shape,
+            edge_jaccard,
+            edge_springs,
+            charges,
+            positions,
+            velocities,
+            tree,
+            settled_iterations,
+            converged,
+            notes,
+            ..
+        } = self;
+        let GraphShape {
+            degrees,
+            is_isolated,
+            is_pinned,
+            ..
+        } = &*shape;
+        let num_nodes = positions.len();
+        let any_pinned = is_pinned.contains(&true);
+        let connected_count = is_isolated.iter().filter(|&&iso| !iso).count().max(1) as f64;
+
+        let temperature = (-cooling_rate * iter as f64 / iterations as f64).exp();
+
+        // Build the quadtree (or octree)
+        let (mut min, mut max) = ([f64::MAX; D], [f64::MIN; D]);
+        for p in positions.iter() {
+            for d in 0..D {
+                min[d] = min[d].min(p[d]);
+                max[d] = max[d].max(p[d]);
+            }
+        }
+        let padding = 1.0;
+        let root = tree.rebuild(
+            min.map(|v| v - padding),
+            max.map(|v| v + padding),
+            positions,
+            charges,
+        );
+
+        // Compute repulsive forces (parallel)
+        let repulsive_forces: Vec<[f64; D]> = map_indices(num_nodes, |i| match precision {
+            Precision::F64 => {
+                let mut force = [0.0; D];
+                tree.compute_repulsion(
+                    root,
+                    positions[i],
+                    repulsion,
+                    theta,
+                    rep_dist_exp,
+                    &mut force,
+                );
+                force
+            }
+            Precision::F32 => {
+                simd::repulsion(tree, root, positions[i], repulsion, theta, rep_dist_exp)
+            }
+        });
+
+        // Compute spring forces along edges (sequential accumulation).
+        // Rest length is modulated by Jaccard similarity: edges between nodes
+        // that share many neighbors (intra-cluster) get shorter rest lengths,
+        // while bridge edges (low similarity) get longer rest lengths.
+        let mut spring_forces = vec![[0.0_f64; D]; num_nodes];
+        for (edge_idx, &(src, tgt)) in adjacency.iter().enumerate() {
+            let delta: [f64; D] = std::array::from_fn(|d| positions[tgt][d] - positions[src][d]);
+            let dist = delta.iter().map(|v| v * v).sum::<f64>().sqrt().max(0.1);
+            let jaccard = edge_jaccard[edge_idx];
+            let (strength, length) = edge_springs[edge_idx];
+            let f = if linlog {
+                // LinLog attraction: force grows as log(1+d), always pulling
+                // endpoints together (repulsion sets the equilibrium spacing).
+                // Jaccard scales the pull: bridges (jaccard≈0) attract weakly
+                // at `attract_min`, intra-cluster edges (jaccard→1) attract at
+                // full strength, so communities contract while bridges stretch.
+                let weight = attract_min + (1.0 - attract_min) * jaccard;
+                link_spring * strength * weight * (1.0 + dist / length).ln()
+            } else {
+                // Hooke spring toward a Jaccard-modulated rest length.
+                // Jaccard=1 → rest_length = link_distance (tight cluster)
+                // Jaccard=0 → rest_length = link_distance * bridge_mult (bridge)
+                let rest_length = link_distance * (bridge_mult - (bridge_mult - 1.0) * jaccard);
+                link_spring * strength * (dist - rest_length * length)
+            };
+            // Weight by inverse degree^spring_norm so hubs aren't yanked as hard
+            let src_weight = 1.0 / (degrees[src] as f64).max(1.0).powf(spring_norm);
+            let tgt_weight = 1.0 / (degrees[tgt] as f64).max(1.0).powf(spring_norm);
+            for (d, delta) in delta.into_iter().enumerate() {
+                let force = delta / dist * f;
+                spring_forces[src][d] += force * src_weight;
+                spring_forces[tgt][d] -= force * tgt_weight;
+            }
+        }
+
+        // The centroid of each community's connected members, for community gravity
+        let community_centroids: Vec<Option<[f64; D]>> = if community_gravity > 0.0 {
+            let community_count = communities.iter().max().map_or(0, |&c| c + 1);
+            let mut sums = vec![([0.0_f64; D], 0.0); community_count];
+            for (i, &community) in communities.iter().enumerate().take(num_nodes) {
+                if !is_isolated[i] {
+                    let (sum, count) = &mut sums[community];
+                    for (sum, coordinate) in sum.iter_mut().zip(positions[i]) {
+                        *sum += coordinate;
+                    }
+                    *count += 1.0;
+                }
+            }
+            sums.into_iter()
+                .map(|(sum, n)| (n > 1.0).then(|| sum.map(|v| v / n)))
+                .collect()
+        } else {
+            vec![]
+        };
+
+        // Integrate forces: first the force on each node, and the spin of each orbiting one
+        let max_vel = max_velocity * temperature;
+        let mut forces = vec![[0.0_f64; D]; num_nodes];
+        let mut spins = vec![[0.0_f64; D]; num_nodes];
+        for i in 0..num_nodes {
+            if is_pinned[i] {
+                continue;
+            }
+            let g = if is_isolated[i] {
+                gravity_isolated
+            } else {
+                gravity
+            };
+            let mut gravity_force = positions[i].map(|v| -v * g);
+            if let Some(centroid) = communities
+                .get(i)
+                .and_then(|&community| community_centroids.get(community).copied().flatten())
+                .filter(|_| !is_isolated[i])
+            {
+                for d in 0..D {
+                    gravity_force[d] += (centroid[d] - positions[i][d]) * community_gravity;
+                }
+            }
+
+            for d in 0..D {
+                forces[i][d] =
+                    (repulsive_forces[i][d] + spring_forces[i][d] + gravity_force[d]) * temperature;
+            }
+
+            // Tangential spin for isolated nodes (FR mode only — in LinLog the
+            // isolated set is arranged into a deterministic ring after the
+            // simulation, so it doesn't need the orbital spin treatment). In
+            // 3D they orbit around the z axis.
+            if is_isolated[i] && !linlog {
+                let dx = positions[i][0];
+                let dy = positions[i][1];
+                let r = (dx * dx + dy * dy).sqrt().max(1.0);
+                let tx = -dy / r;
+                let ty = dx / r;
+                let dist_factor = (r / 200.0).max(0.5);
+                let sp = spin * temperature * dist_factor;
+                spins[i][0] = tx * sp;
+                spins[i][1] = ty * sp;
+            }
+        }
+        match precision {
+            Precision::F64 => {
+                for i in (0..num_nodes).filter(|&i| !is_pinned[i]) {
+                    for d in 0..D {
+                        velocities[i][d] =
+                            clamp_abs((velocities[i][d] + forces[i][d]) * friction, max_vel)
+                                + spins[i][d];
+                        positions[i][d] += velocities[i][d];
+                    }
+                }
+            }
+            Precision::F32 => simd::integrate(
+                positions, velocities, &forces, &spins, is_pinned, friction, max_vel,
+            ),
+        }
+
+        // Re-center: shift all positions so the center of mass is at the origin.
+        // This ensures gravity pulls symmetrically from all directions, which
+        // lets isolated nodes form a full orbit instead of a crescent. Pins fix
+        // where the layout is instead, so they're placed again.
+        let com = if any_pinned {
+            hold_pins(positions, shape, pins, linlog);
+            [0.0; D]
+        } else {
+            let mut sums = [0.0; D];
+            for pos in positions.iter() {
+                for d in 0..D {
+                    sums[d] += pos[d];
+                }
+            }
+            sums.map(|sum| sum / num_nodes as f64)
+        };
+        for pos in positions.iter_mut() {
+            for d in 0..D {
+                pos[d] -= com[d];
+            }
+        }
+
+        // How far the connected nodes moved. Isolated nodes are left out, as
+        // they keep orbiting (or, in LinLog, are placed after the simulation),
+        // and so is the drift of the whole layout that re-centering undoes.
+        let (displacement, max_displacement, radius_sq) =
+            (0..num_nodes).filter(|&i| !is_isolated[i]).fold(
+                (0.0, 0.0_f64, 0.0),
+                |(displacement, max_displacement, radius_sq), i| {
+                    let moved = (0..D)
+                        .map(|d| (velocities[i][d] - com[d]).powi(2))
+                        .sum::<f64>()
+                        .sqrt();
+                    (
+                        displacement + moved,
+                        max_displacement.max(moved),
+                        positions[i].iter().fold(radius_sq, |sum, v| sum + v * v),
+                    )
+                },
+            );
+        // Stop once the connected nodes have settled
+        if convergence > 0.0 {
+            let rms_radius = (radius_sq / connected_count).sqrt().max(1e-6);
+            if displacement / connected_count / rms_radius < convergence {
+                *settled_iterations += 1;
+            } else {
+                *settled_iterations = 0;
+            }
+            if *settled_iterations >= convergence_window {
+                notes.push(format!(
+                    "layout converged after iteration {iter}/{iterations}"
+                ));
+                *converged = true;
+            }
+        }
+
+        Some(Progress {
+            iteration: iter,
+            iterations,
+            temperature,
+            max_displacement,
+            positions,
+            notes,
+        })
+    }
+
+    /// Finish the layout, returning every node's position. The isolated set is placed around
+    /// the connected core, and with LinLog the layout is rescaled and decluttered (see
+    /// [`Progress::positions`]), and the pinned nodes snapped to their pins.
+    pub fn finish(mut self) -> Vec<[f64; D]> {
+        // In LinLog mode the weak 1/d repulsion can't fling the isolated set out
+        // into an orbiting ring the way the old FR forces did, so they pile up near
+        // the center, and the scale is arbitrary; both are tidied up.
+        if self.linlog {
+            tidy(&mut self.positions, &self.shape);
+        }
+        snap_pins(&mut self.positions, &self.pins);
+        self.positions
+    }
+}
+
+/// The (strength, rest length) multipliers of each type of edge's spring, from
+/// `SUBGENRE_SPRING`, `SUBGENRE_LENGTH` and the rest.
+#[derive(Debug, Clone, Copy)]
+pub struct EdgeSprings {
+    /// The multipliers of subgenre edges.
+    pub subgenre: (f64, f64),
+    /// The multipliers of derivative edges.
+    pub derivative: (f64, f64),
+    /// The multipliers of fusion genre edges.
+    pub fusion: (f64, f64),
+}
+
+impl EdgeSprings {
+    /// Read the multipliers from the environment.
+    pub fn from_env() -> Self {
+        Self {
+            subgenre: (
+                env_f64("SUBGENRE_SPRING", 1.25),
+                env_f64("SUBGENRE_LENGTH", 0.8),
+            ),
+            derivative: (
+                env_f64("DERIVATIVE_SPRING", 1.0),
+                env_f64("DERIVATIVE_LENGTH", 1.0),
+            ),
+            fusion: (env_f64("FUSION_SPRING", 0.6), env_f64("FUSION_LENGTH", 1.5)),
+        }
+    }
+
+    /// The multipliers of an edge of type `ty`; edges without a type get the default spring.
+    pub fn get(&self, ty: Option<EdgeType>) -> (f64, f64) {
+        match ty {
+            Some(EdgeType::Subgenre) => self.subgenre,
+            Some(EdgeType::Derivative) => self.derivative,
+            Some(EdgeType::FusionGenre) => self.fusion,
+            Some(EdgeType::Related) | None => (1.0, 1.0),
+        }
+    }
+}
+
+/// What the layouts need to know about the shape of the graph.
+pub(crate) struct GraphShape {
+    /// The number of edges of each node.
+    pub(crate) degrees: Vec<u32>,
+    /// The neighbours of each node. Ordered, so that anything summed over a
+    /// node's neighbours is summed in the same order.
+    pub(crate) neighbors: Vec<std::collections::BTreeSet<usize>>,
+    /// Whether each node is in a component of at most `ISO_COMPONENT_MAX`
+    /// nodes, and so is laid out apart from the connected core.
+    pub(crate) is_isolated: Vec<bool>,
+    /// The connected component of each node.
+    pub(crate) component_id: Vec<usize>,
+    /// The number of nodes in each component.
+    pub(crate) component_size: Vec<usize>,
+    /// Whether each node is pinned.
+    pub(crate) is_pinned: Vec<bool>,
+}
+
+impl GraphShape {
+    pub(crate) fn new(input: &LayoutInput) -> Self {
+        let LayoutInput {
+            num_nodes,
+            adjacency,
+            pins,
+            ..
+        } = *input;
+        // Compute node degrees and adjacency lists for Jaccard similarity
+        let mut degrees = vec![0u32; num_nodes];
+        // Ordered, so that anything summed over a node's neighbours is summed in the same order
+        let mut neighbors: Vec<std::collections::BTreeSet<usize>> =
+            vec![Default::default(); num_nodes];
+        for &(src, tgt) in adjacency {
+            degrees[src] += 1;
+            degrees[tgt] += 1;
+            neighbors[src].insert(tgt);
+            neighbors[tgt].insert(src);
+        }
+
+        // Connected components via BFS — nodes in small components get isolated
+        // treatment (stronger gravity, reduced charge, spin) so they orbit near
+        // the core instead of being flung outside the isolated ring.
+        let iso_component_max = env_usize("ISO_COMPONENT_MAX", 5);
+        let (is_isolated, component_id, component_size) = {
+            let mut component_id = vec![usize::MAX; num_nodes];
+            let mut current_id = 0usize;
+            for start in 0..num_nodes {
+                if component_id[start] != usize::MAX {
+                    continue;
+                }
+                let mut queue = std::collections::VecDeque::new();
+                queue.push_back(start);
+                component_id[start] = current_id;
+                while let Some(node) = queue.pop_front() {
+                    for &nbr in &neighbors[node] {
+                        if component_id[nbr] == usize::MAX {
+                            component_id[nbr] = current_id;
+                            queue.push_back(nbr);
+                        }
+                    }
+                }
+                current_id += 1;
+            }
+            // Count component sizes
+            let mut component_size = vec![0usize; current_id];
+            for &cid in &component_id {
+                component_size[cid] += 1;
+            }
+            // A node is "isolated" if its component has <= iso_component_max nodes
+            let isolated: Vec<bool> = (0..num_nodes)
+                .map(|i| component_size[component_id[i]] <= iso_component_max)
+                .collect();
+            (isolated, component_id, component_size)
+        };
+        let is_pinned = (0..num_nodes)
+            .map(|i| pins.get(i).copied().flatten().is_some())
+            .collect();
+        Self {
+            degrees,
+            neighbors,
+            is_isolated,
+            component_id,
+            component_size,
+            is_pinned,
+        }
+    }
+}
+
+/// Where the nodes start: as `input`'s [`Initialization`] places them
+/// (deterministically, from its seed), unless it has initial positions, and
+/// whether it did.
+pub(crate) fn starting_positions<const D: usize>(
+    input: &LayoutInput,
+    shape: &GraphShape,
+    link_distance: f64,
+) -> (Vec<[f64; D]>, bool) {
+    let LayoutInput {
+        num_nodes,
+        initial_positions,
+        seed,
+        initialization,
+        ..
+    } = *input;
+    let neighbors = &shape.neighbors;
+    // Deterministic initial positions: seeded PRNG for uniform random placement
+    // Using a simple xorshift64 for reproducibility without extra dependencies.
+    // The seed is scrambled into the state, so that nearby seeds give unrelated
+    // placements.
+    let mut rng_state: u64 =
+        (0xDEAD_BEEF_CAFE_BABE ^ u64::from(seed).wrapping_mul(0x9E37_79B9_7F4A_7C15)).max(1);
+    let mut next_f64 = || -> f64 {
+        rng_state ^= rng_state << 13;
+        rng_state ^= rng_state >> 7;
+        rng_state ^= rng_state << 17;
+        (rng_state as f64) / (u64::MAX as f64) * 2.0 - 1.0
+    };
+    let spread = (num_nodes as f64).sqrt() * 15.0;
+
+    let mut positions: Vec<[f64; D]> = (0..num_nodes)
+        .map(|_| std::array::from_fn(|_| next_f64() * spread))
+        .collect();
+
+    let warm_started = initial_positions.iter().any(Option::is_some);
+    if !warm_started && initialization == Initialization::Neighbors {
+        // Each component is grown breadth-first from its highest-degree node,
+        // which keeps its random position, so that every other node has a
+        // placed neighbour to start beside
+        let mut roots: Vec<usize> = (0..num_nodes).collect();
+        roots.sort_by_key(|&i| std::cmp::Reverse(shape.degrees[i]));
+        let mut placed = vec![false; num_nodes];
+        for root in roots {
+            if placed[root] {
+                continue;
+            }
+            placed[root] = true;
+            let mut queue = std::collections::VecDeque::from([root]);
+            while let Some(node) = queue.pop_front() {
+                for &i in &neighbors[node] {
+                    if placed[i] {
+                        continue;
+                    }
+                    let mut sums = [0.0; D];
+                    let mut count = 0.0;
+                    for &n in neighbors[i].iter().filter(|&&n| placed[n]) {
+                        for (sum, coordinate) in sums.iter_mut().zip(positions[n]) {
+                            *sum += coordinate;
+                        }
+                        count += 1.0;
+                    }
+                    positions[i] = sums.map(|sum| sum / count + next_f64() * link_distance);
+                    placed[i] = true;
+                    queue.push_back(i);
+                }
+            }
+        }
+    }
+    if warm_started {
+        // The initial positions are in the emitted scale, so they're rescaled to have the
+        // same RMS radius as the uniform random placement (spread * sqrt(2/3)). They're only
+        // 2D, so in 3D each node keeps its random height.
+        let known: Vec<[f64; 2]> = initial_positions.iter().flatten().copied().collect();
+        let rms = (known.iter().map(|p| p[0] * p[0] + p[1] * p[1]).sum::<f64>()
+            / known.len() as f64)
+            .sqrt();
+        let scale = if rms > 1e-6 {
+            spread * (2.0_f64 / 3.0).sqrt() / rms
+        } else {
+            1.0
+        };
+        let initial = |i: usize| {
+            initial_positions
+                .get(i)
+                .copied()
+                .flatten()
+                .map(|p| [p[0] * scale, p[1] * scale])
+        };
+        for i in 0..num_nodes {
+            if let Some(pos) = initial(i) {
+                positions[i][..2].copy_from_slice(&pos);
+                continue;
+            }
+            // New nodes start beside their positioned neighbours, if they have any
+            let placed: Vec<[f64; 2]> = neighbors[i].iter().filter_map(|&n| initial(n)).collect();
+            if !placed.is_empty() {
+                let n = placed.len() as f64;
+                positions[i][0] =
+                    placed.iter().map(|p| p[0]).sum::<f64>() / n + next_f64() * link_distance;
+                positions[i][1] =
+                    placed.iter().map(|p| p[1]).sum::<f64>() / n + next_f64() * link_distance;
+            }
+        }
+    }
+    (positions, warm_started)
+}
+
+/// Put each pinned node at its pin. If the output will be `rescaled` (see
+/// [`tidy`]), the pins are held at the same place relative to the layout's
+/// current scale, so that they end up at their pins once it's been rescaled.
+/// The pins are 2D, so in 3D they're held at a height of 0.
+pub(crate) fn hold_pins<const D: usize>(
+    positions: &mut [[f64; D]],
+    shape: &GraphShape,
+    pins: &[Option<[f64; 2]>],
+    rescaled: bool,
+) {
+    let scale = if rescaled {
+        core_rms_radius(positions, &shape.is_isolated, &shape.is_pinned)
+            .filter(|&rms| rms > 1e-6)
+            .map_or(1.0, |rms| rms / env_f64("NORM_STD", 850.0))
+    } else {
+        1.0
+    };
+    for (position, pin) in positions.iter_mut().zip(pins) {
+        if let Some(pin) = pin {
+            *position = [0.0; D];
+            position[0] = pin[0] * scale;
+            position[1] = pin[1] * scale;
+        }
+    }
+}
+
+/// Put each pinned node exactly at its pin, rather than to within the
+/// rounding of the rescaling.
+pub(crate) fn snap_pins<const D: usize>(positions: &mut [[f64; D]], pins: &[Option<[f64; 2]>]) {
+    for (position, pin) in positions.iter_mut().zip(pins) {
+        if let Some(pin) = pin {
+            *position = [0.0; D];
+            position[..2].copy_from_slice(pin);
+        }
+    }
+}
+
+/// Ready a layout centred on the origin for the website: lay the isolated
+/// set out in a ring around the connected core, rescale it, and push apart
+/// overlapping nodes.
+pub(crate) fn tidy<const D: usize>(positions: &mut [[f64; D]], shape: &GraphShape) {
+    let GraphShape {
+        degrees,
+        is_isolated,
+        component_id,
+        component_size,
+        is_pinned,
+        ..
+    } = shape;
+    let node_min_dist = env_f64("NODE_MIN_DIST", 28.0);
+
+    // The isolated set's positions carry no edge information, so they're laid
+    // out deterministically in a tidy ring just outside the connected core —
+    // matching the "halo of disconnected genres" look from cosmograph.
+    place_isolated_ring(positions, is_isolated, component_id, component_size);
+
+    // The coordinate scale is arbitrary (LinLog's is ~10× larger than the old
+    // FR layout). The frontend's world-unit constants — node sizes, edge
+    // widths, arrow spacing, fit radii — are all tuned against the historic
+    // scale, so rescale the connected core to a fixed positional std before
+    // emitting. This keeps the new layout a drop-in replacement.
+    // RMS radius about the (already centered) origin.
+    let target_std = env_f64("NORM_STD", 850.0);
+    if let Some(rms) = core_rms_radius(positions, is_isolated, is_pinned).filter(|&rms| rms > 1e-6)
+    {
+        let scale = target_std / rms;
+        for pos in positions.iter_mut() {
+            for coordinate in pos {
+                *coordinate *= scale;
+            }
+        }
+    }
+
+    // Collision relaxation (à la d3-force's forceCollide): nudge apart any
+    // pair of nodes closer than the sum of their radii, which are
+    // NODE_MIN_DIST / 2 plus up to HUB_RADIUS for the highest-degree node.
+    // This only acts at short range, so it declutters overlapping genres
+    // without disturbing the cluster structure the main simulation
+    // produced. Operates on the already-normalized scale, so the distances
+    // are in final world units.
+    if node_min_dist > 0.0 {
+        let radii = node_radii(degrees);
+        // Pinned nodes can't be pushed
+        let masses: Vec<f64> = degrees
+            .iter()
+            .zip(is_pinned)
+            .map(|(&d, &pinned)| {
+                if pinned {
+                    f64::INFINITY
+                } else {
+                    1.0 + d as f64
+                }
+            })
+            .collect();
+        relax_collisions(positions, &radii, &masses);
+    }
+}
+
+/// [`tidy`]'s parameters, as a line for [`Progress::notes`].
+pub(crate) fn tidy_note() -> String {
+    let node_min_dist = env_f64("NODE_MIN_DIST", 28.0);
+    let hub_radius = env_f64("HUB_RADIUS", 30.0);
+    format!("node_min_dist={node_min_dist} hub_radius={hub_radius}")
+}
+
+/// How far, in output units, each node is kept from the others by the collision
+/// pass: `NODE_MIN_DIST / 2`, plus up to `HUB_RADIUS` for the highest-degree
+/// node.
+pub fn node_radii(degrees: &[u32]) -> Vec<f64> {
+    let node_min_dist = env_f64("NODE_MIN_DIST", 28.0);
+    let hub_radius = env_f64("HUB_RADIUS", 30.0);
+    let max_degree = degrees.iter().copied().max().unwrap_or(0).max(1) as f64;
+    degrees
+        .iter()
+        .map(|&d| node_min_dist / 2.0 + hub_radius * d as f64 / max_degree)
+        .collect()
+}
+
+/// The RMS radius about the origin of the connected nodes that aren't pinned, which is what
+/// LinLog's output is rescaled by (if there are any).
+fn core_rms_radius<const D: usize>(
+    positions: &[[f64; D]],
+    is_isolated: &[bool],
+    is_pinned: &[bool],
+) -> Option<f64> {
+    let (sum_sq, count) = positions
+        .iter()
+        .enumerate()
+        .filter(|&(i, _)| !is_isolated[i] && !is_pinned[i])
+        .fold((0.0, 0), |(sum_sq, count), (_, p)| {
+            (p.iter().fold(sum_sq, |sum_sq, v| sum_sq + v * v), count + 1)
+        });
+    (count > 0).then(|| (sum_sq / count as f64).sqrt())
+}
+
+/// Push apart any nodes closer than the sum of their `radii`, iterating a
+/// fixed number of times. Each pair is pushed apart in inverse proportion to
+/// their `masses` (a node of infinite mass isn't moved), and by no more than
+/// the smaller radius per pass, so that a deep overlap is resolved gradually
+/// instead of flinging the lighter node away. Uses a uniform spatial grid (cell size = the largest diameter) so
+/// each node only tests the 3×3 (or, in 3D, 3×3×3) block of cells around it — O(n) per pass.
+fn relax_collisions<const D: usize>(positions: &mut [[f64; D]], radii: &[f64], masses: &[f64]) {
+    let max_radius = radii.iter().copied().fold(0.0_f64, f64::max);
+    if max_radius <= 0.0 || positions.len() < 2 {
+        return;
+    }
+    const PASSES: usize = 60;
+    let cell = max_radius * 2.0;
+    // The offsets of the block of cells around each, the first axis varying fastest
+    let neighbor_offsets: Vec<[i64; D]> = (0..3_i64.pow(D as u32))
+        .map(|k| std::array::from_fn(|d| k / 3_i64.pow(d as u32) % 3 - 1))
+        .collect();
+    for _ in 0..PASSES {
+        // Bin nodes into a grid keyed by integer cell coordinates. Cells are visited in order,
+        // as the pushes are applied in place and the result depends on their order.
+        let mut grid: std::collections::BTreeMap<[i64; D], Vec<usize>> = Default::default();
+        for (i, p) in positions.iter().enumerate() {
+            let key = p.map(|v| (v / cell).floor() as i64);
+            grid.entry(key).or_default().push(i);
+        }
+        let mut moved = false;
+        for (key, members) in &grid {
+            for offset in &neighbor_offsets {
+                let Some(others) = grid.get(&std::array::from_fn(|d| key[d] + offset[d])) else {
+                    continue;
+                };
+                for &i in members {
+                    for &j in others {
+                        // Each unordered pair handled once.
+                        if j <= i {
+                            continue;
+                        }
+                        let min_dist = radii[i] + radii[j];
+                        let delta: [f64; D] =
+                            std::array::from_fn(|d| positions[j][d] - positions[i][d]);
+                        let d_sq: f64 = delta.iter().map(|v| v * v).sum();
+                        if d_sq >= min_dist * min_dist || d_sq < 1e-9 {
+                            continue;
+                        }
+                        let d = d_sq.sqrt();
+                        // The lighter node takes more of the needed separation.
+                        let push = (min_dist - d).min(radii[i].min(radii[j]));
+                        let (push_i, push_j) = match (masses[i].is_finite(), masses[j].is_finite())
+                        {
+                            (true, true) => {
+                                let total_mass = masses[i] + masses[j];
+                                (push * masses[j] / total_mass, push * masses[i] / total_mass)
+                            }
+                            (false, true) => (0.0, push),
+                            (true, false) => (push, 0.0),
+                            (false, false) => continue,
+                        };
+                        for (axis, delta) in delta.into_iter().enumerate() {
+                            let unit = delta / d;
+                            positions[i][axis] -= unit * push_i;
+                            positions[j][axis] += unit * push_j;
+                        }
+                        moved = true;
+                    }
+                }
+            }
+        }
+        if !moved {
+            break;
+        }
+    }
+}
+
+/// Arrange the isolated set (degree-0 nodes and members of tiny components)
+/// into an even ring just outside the connected core. Each small component is
+/// given one angular slot so its members stay clustered together. In 3D the
+/// ring lies flat around the core's equator.
+fn place_isolated_ring<const D: usize>(
+    positions: &mut [[f64; D]],
+    is_isolated: &[bool],
+    component_id: &[usize],
+    component_size: &[usize],
+) {
+    // Radius of the connected core: the largest distance from the origin among
+    // non-isolated nodes (positions are already centered on the origin).
+    let core_radius = positions
+        .iter()
+        .enumerate()
+        .filter(|&(i, _)| !is_isolated[i])
+        .map(|(_, p)| p.iter().map(|v| v * v).sum::<f64>().sqrt())
+        .fold(0.0_f64, f64::max);
+    let ring_radius = if core_radius > 0.0 {
+        core_radius * 1.12
+    } else {
+        100.0
+    };
+
+    // Group isolated nodes by component, preserving a stable order.
+    use std::collections::BTreeMap;
+    let mut groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for (i, &iso) in is_isolated.iter().enumerate() {
+        if iso {
+            groups.entry(component_id[i]).or_default().push(i);
+        }
+    }
+    if groups.is_empty() {
+        return;
+    }
+
+    let num_slots = groups.len();
+    // Golden-angle increment gives a small deterministic radial wobble so the
+    // ring reads as an organic halo rather than a mechanically perfect circle.
+    let golden = std::f64::consts::PI * (3.0 - 5.0_f64.sqrt());
+    for (slot, (&cid, members)) in groups.iter().enumerate() {
+        let angle = std::f64::consts::TAU * slot as f64 / num_slots as f64;
+        let wobble = 1.0 + 0.06 * (golden * slot as f64).sin();
+        let r = ring_radius * wobble;
+        let (ax, ay) = (r * angle.cos(), r * angle.sin());
+        let size = component_size[cid].max(1);
+        let at = |x: f64, y: f64| {
+            let mut position = [0.0; D];
+            position[0] = x;
+            position[1] = y;
+            position
+        };
+        if size == 1 {
+            positions[members[0]] = at(ax, ay);
+        } else {
+            // Spread a multi-node component in a small circle around its anchor
+            // so its internal edges stay legible.
+            let cluster_r = ring_radius * 0.03;
+            for (k, &node) in members.iter().enumerate() {
+                let a = std::f64::consts::TAU * k as f64 / members.len() as f64;
+                positions[node] = at(ax + cluster_r * a.cos(), ay + cluster_r * a.sin());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `count` nodes scattered at random in a 1000-unit square, with charges from 1 to 5.
+    fn random_nodes(count: usize, seed: u64) -> (Vec<[f64; 2]>, Vec<f64>) {
+        let mut state = seed;
+        let mut next_f64 = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state >> 11) as f64 / (1u64 << 53) as f64
+        };
+        let positions = (0..count)
+            .map(|_| [next_f64() * 1000.0, next_f64() * 1000.0])
+            .collect();
+        let charges = (0..count)
+            .map(|_| 1.0 + (next_f64() * 5.0).floor())
+            .collect();
+        (positions, charges)
+    }
+
+    /// The repulsion on a node at `position`, summed over every node, and the sum of the
+    /// magnitudes of the nodes' contributions to it.
+    fn brute_force_repulsion(
+        positions: &[[f64; 2]],
+        charges: &[f64],
+        position: [f64; 2],
+        rep_dist_exp: f64,
+    ) -> ([f64; 2], f64) {
+        let mut force = [0.0; 2];
+        let mut total = 0.0;
+        for (other, charge) in positions.iter().zip(charges) {
+            let delta = [position[0] - other[0], position[1] - other[1]];
+            let dist_sq = delta[0] * delta[0] + delta[1] * delta[1];
+            if dist_sq <= 1e-6 {
+                continue;
+            }
+            let dist = dist_sq.sqrt().max(1.0);
+            let f = 350.0 * charge / dist.powf(rep_dist_exp);
+            for (force, delta) in force.iter_mut().zip(delta) {
+                *force += delta / dist * f;
+            }
+            total += dist_sq.sqrt() / dist * f;
+        }
+        (force, total)
+    }
+
+    #[test]
+    fn quadtree_matches_brute_force() {
+        let (positions, charges) = random_nodes(300, 0x5EED);
+        let mut tree = TreeArena::new(positions.len() * 4);
+        let root = tree.rebuild([-1.0; 2], [1001.0; 2], &positions, &charges);
+
+        // Each cell's mass and centre of mass are those of the nodes inside it
+        for node in &tree.nodes {
+            let inside: Vec<usize> = (0..positions.len())
+                .filter(|&i| (0..2).all(|d| (node.min[d]..=node.max[d]).contains(&positions[i][d])))
+                .collect();
+            let mass: f64 = inside.iter().map(|&i| charges[i]).sum();
+            let center: [f64; 2] = std::array::from_fn(|d| {
+                inside
+                    .iter()
+                    .map(|&i| positions[i][d] * charges[i])
+                    .sum::<f64>()
+                    / mass
+            });
+            assert!((node.mass - mass).abs() < 1e-9, "{} != {mass}", node.mass);
+            assert!((node.center[0] - center[0]).hypot(node.center[1] - center[1]) < 1e-9);
+        }
+
+        for rep_dist_exp in [1.0, 2.0] {
+            for &position in positions.iter().step_by(7) {
+                let (expected, total) =
+                    brute_force_repulsion(&positions, &charges, position, rep_dist_exp);
+                // Opening every cell gives the exact sum, and the approximation stays close to it
+                // (relative to the contributions, which can cancel out to a much smaller sum)
+                for (theta, tolerance) in [(0.0, 1e-12), (0.5, 0.01)] {
+                    let mut actual = [0.0; 2];
+                    tree.compute_repulsion(root, position, 350.0, theta, rep_dist_exp, &mut actual);
+                    let error = (actual[0] - expected[0]).hypot(actual[1] - expected[1]);
+                    assert!(
+                        error <= tolerance * total,
+                        "{actual:?} != {expected:?} with theta {theta}, exponent {rep_dist_exp}"
+                    );
+                }
+            }
+        }
+    }
+
+    /// A ring of 24 nodes with chords across it, and a pair off to the side.
+    fn ring() -> Vec<(usize, usize)> {
+        (0..24)
+            .map(|i| (i, (i + 1) % 24))
+            .chain((0..24).step_by(4).map(|i| (i, (i + 7) % 24)))
+            .chain([(24, 25)])
+            .collect()
+    }
+
+    fn input<'a>(adjacency: &'a [(usize, usize)], pins: &'a [Option<[f64; 2]>]) -> LayoutInput<'a> {
+        LayoutInput {
+            num_nodes: 26,
+            adjacency,
+            edge_types: &[],
+            communities: &[],
+            pins,
+            initial_positions: &[],
+            seed: 0,
+            initialization: Default::default(),
+            precision: Default::default(),
+        }
+    }
+
+    #[test]
+    fn pinned_nodes_keep_their_positions() {
+        let adjacency = ring();
+        let mut pins = vec![None; 26];
+        pins[0] = Some([100.0, -50.0]);
+        pins[12] = Some([-300.0, 200.0]);
+        let input = input(&adjacency, &pins);
+
+        // Fruchterman-Reingold isn't rescaled, so the pins are held exactly as it runs
+        let mut simulation = Simulation::<2>::new(&input, false);
+        let mut iterations = 0;
+        while let Some(progress) = simulation.step() {
+            assert_eq!(progress.positions[0], [100.0, -50.0]);
+            assert_eq!(progress.positions[12], [-300.0, 200.0]);
+            iterations += 1;
+        }
+        assert!(iterations > 0);
+
+        for algorithm in [
+            Algorithm::BarnesHut,
+            Algorithm::ForceAtlas2,
+            Algorithm::StressMajorization,
+        ] {
+            let positions = algorithm.layout().compute(&input, &mut |_| {});
+            assert_eq!(positions[0], [100.0, -50.0], "{algorithm:?}");
+            assert_eq!(positions[12], [-300.0, 200.0], "{algorithm:?}");
+            assert_ne!(positions[1], positions[0], "{algorithm:?}");
+        }
+    }
+
+    #[test]
+    fn the_3d_layouts_have_finite_coordinates_in_every_dimension() {
+        let adjacency = ring();
+        let input = input(&adjacency, &[]);
+        for algorithm in [Algorithm::BarnesHut, Algorithm::ForceAtlas2] {
+            let positions = algorithm.layout().compute_3d(&input, &mut |_| {}).unwrap();
+            assert_eq!(positions.len(), input.num_nodes, "{algorithm:?}");
+            assert!(
+                positions.iter().flatten().all(|v| v.is_finite()),
+                "{algorithm:?}"
+            );
+            // The nodes aren't all laid out in a plane
+            assert!(positions.iter().any(|p| p[2].abs() > 1e-6), "{algorithm:?}");
+        }
+        assert!(
+            Algorithm::StressMajorization
+                .layout()
+                .compute_3d(&input, &mut |_| {})
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn the_parameters_are_noted_with_the_first_iteration_only() {
+        let adjacency = ring();
+        let input = input(&adjacency, &[]);
+        for algorithm in [Algorithm::ForceAtlas2, Algorithm::StressMajorization] {
+            let mut notes = vec![];
+            algorithm.layout().compute(&input, &mut |progress| {
+                notes.push(progress.notes.to_vec());
+            });
+            assert!(
+                notes[0].iter().any(|note| note.contains("node_min_dist=")),
+                "{algorithm:?}"
+            );
+            assert!(
+                notes[1..notes.len() - 1].iter().all(Vec::is_empty),
+                "{algorithm:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,321 @@
+//! The f32 fast path of the force-directed layouts ([`crate::Precision::F32`]).
+//!
+//! The repulsion and the velocity integration are computed [`LANES`] nodes (or tree nodes) at a
+//! time in `f32`, with [`F32x8`], whose fixed-size loops the compiler turns into SIMD
+//! instructions on every target (including wasm's `simd128`) without `unsafe` or nightly
+//! `std::simd`. For the repulsion, the tree is walked as before, but the tree nodes that act on
+//! a node are gathered into an interaction list, which is then summed a lane at a time.
+//!
+//! The positions stay `f64`, so that the layout doesn't drift as its small steps are rounded
+//! away; only the forces and velocities are `f32`.
+
+use std::{
+    cell::RefCell,
+    ops::{Add, Div, Mul, Sub},
+};
+
+use crate::TreeArena;
+
+/// How many `f32`s are computed at once: a 256-bit (AVX) register's worth.
+pub(crate) const LANES: usize = 8;
+
+/// [`LANES`] `f32`s, operated on together.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct F32x8([f32; LANES]);
+
+impl F32x8 {
+    const ZERO: Self = Self([0.0; LANES]);
+
+    #[inline(always)]
+    fn splat(value: f32) -> Self {
+        Self([value; LANES])
+    }
+
+    /// The `f32`s of `values` from `start`, with zeroes past the end.
+    #[inline(always)]
+    fn load(values: &[f32], start: usize) -> Self {
+        let mut lanes = [0.0; LANES];
+        let end = values.len().min(start + LANES);
+        lanes[..end - start].copy_from_slice(&values[start..end]);
+        Self(lanes)
+    }
+
+    #[inline(always)]
+    fn map(self, f: impl Fn(f32) -> f32) -> Self {
+        Self(self.0.map(f))
+    }
+
+    #[inline(always)]
+    fn zip(self, other: Self, f: impl Fn(f32, f32) -> f32) -> Self {
+        Self(std::array::from_fn(|k| f(self.0[k], other.0[k])))
+    }
+
+    #[inline(always)]
+    fn sqrt(self) -> Self {
+        self.map(f32::sqrt)
+    }
+
+    #[inline(always)]
+    fn max(self, other: Self) -> Self {
+        self.zip(other, f32::max)
+    }
+
+    #[inline(always)]
+    fn min(self, other: Self) -> Self {
+        self.zip(other, f32::min)
+    }
+
+    /// The sum of the lanes.
+    #[inline(always)]
+    fn sum(self) -> f32 {
+        self.0.iter().sum()
+    }
+}
+
+macro_rules! lanewise {
+    ($($trait:ident $method:ident $op:tt),*) => {
+        $(
+            impl $trait for F32x8 {
+                type Output = Self;
+
+                #[inline(always)]
+                fn $method(self, other: Self) -> Self {
+                    self.zip(other, |a, b| a $op b)
+                }
+            }
+        )*
+    };
+}
+lanewise!(Add add +, Sub sub -, Mul mul *, Div div /);
+
+/// The tree nodes acting on one node: their offsets from it along each axis (up to 3), and their
+/// masses.
+#[derive(Default)]
+struct Interactions {
+    deltas: [Vec<f32>; 3],
+    masses: Vec<f32>,
+}
+
+thread_local! {
+    /// Each thread's interaction list, kept so that gathering one doesn't allocate.
+    static INTERACTIONS: RefCell<Interactions> = RefCell::default();
+}
+
+/// The repulsive force on a node at `position` from the tree, as
+/// [`TreeArena::compute_repulsion`] computes it, but summed in `f32` lanes.
+pub(crate) fn repulsion<const D: usize>(
+    tree: &TreeArena<D>,
+    root: usize,
+    position: [f64; D],
+    repulsion: f64,
+    theta: f64,
+    rep_dist_exp: f64,
+) -> [f64; D] {
+    INTERACTIONS.with_borrow_mut(|interactions| {
+        for deltas in &mut interactions.deltas {
+            deltas.clear();
+        }
+        interactions.masses.clear();
+        tree.for_each_interaction(root, position, theta, &mut |delta, _, mass| {
+            for (deltas, delta) in interactions.deltas.iter_mut().zip(delta) {
+                deltas.push(delta as f32);
+            }
+            interactions.masses.push(mass as f32);
+        });
+        sum_repulsion(
+            &interactions.deltas,
+            &interactions.masses,
+            repulsion as f32,
+            rep_dist_exp as f32,
+        )
+    })
+}
+
+/// The sum of the repulsive forces `repulsion * mass / dist^rep_dist_exp` from tree nodes at
+/// `deltas` with `masses`, a lane at a time.
+fn sum_repulsion<const D: usize>(
+    deltas: &[Vec<f32>; 3],
+    masses: &[f32],
+    repulsion: f32,
+    rep_dist_exp: f32,
+) -> [f64; D] {
+    let mut sums = [F32x8::ZERO; D];
+    for start in (0..masses.len()).step_by(LANES) {
+        // Past the end the masses are zero, and so are the forces
+        let mass = F32x8::load(masses, start);
+        let delta: [F32x8; D] = std::array::from_fn(|d| F32x8::load(&deltas[d], start));
+        let dist_sq = delta.iter().fold(F32x8::ZERO, |sum, &v| sum + v * v);
+        let dist = dist_sq.sqrt().max(F32x8::splat(1.0));
+        // `powf` doesn't vectorize, so the usual exponents are multiplied out
+        let falloff = if rep_dist_exp == 1.0 {
+            dist
+        } else if rep_dist_exp == 2.0 {
+            dist * dist
+        } else {
+            dist.map(|dist| dist.powf(rep_dist_exp))
+        };
+        let f = F32x8::splat(repulsion) * mass / (falloff * dist);
+        for (sum, delta) in sums.iter_mut().zip(delta) {
+            *sum = *sum + delta * f;
+        }
+    }
+    sums.map(|sum| sum.sum() as f64)
+}
+
+/// Integrate `forces` (already scaled by the temperature) into the `velocities` and `positions`
+/// of the nodes that aren't pinned, as [`crate::Precision::F64`] does, a lane at a time: each velocity
+/// is damped by `friction`, clamped to `max_velocity`, and given its node's `spin`.
+pub(crate) fn integrate<const D: usize>(
+    positions: &mut [[f64; D]],
+    velocities: &mut [[f64; D]],
+    forces: &[[f64; D]],
+    spins: &[[f64; D]],
+    is_pinned: &[bool],
+    friction: f64,
+    max_velocity: f64,
+) {
+    let (friction, max_velocity) = (
+        F32x8::splat(friction as f32),
+        F32x8::splat(max_velocity as f32),
+    );
+    let min_velocity = F32x8::ZERO - max_velocity;
+    for start in (0..positions.len()).step_by(LANES) {
+        let end = positions.len().min(start + LANES);
+        let lane = |values: &[[f64; D]], d: usize| {
+            F32x8(std::array::from_fn(|k| {
+                values.get(start + k).map_or(0.0, |value| value[d] as f32)
+            }))
+        };
+        for d in 0..D {
+            let velocity = ((lane(velocities, d) + lane(forces, d)) * friction)
+                .max(min_velocity)
+                .min(max_velocity)
+                + lane(spins, d);
+            for (k, i) in (start..end).enumerate() {
+                if !is_pinned[i] {
+                    velocities[i][d] = velocity.0[k] as f64;
+                    positions[i][d] += velocities[i][d];
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ForceAtlas2, LayoutAlgorithm as _, LayoutInput, Precision, Simulation};
+
+    /// Evenly spread, deterministic positions in a 1000-unit square.
+    fn scattered(count: usize) -> Vec<[f64; 2]> {
+        (0..count)
+            .map(|i| {
+                let i = i as f64;
+                [
+                    (i * 0.618_034).fract() * 1000.0,
+                    (i * 0.754_878).fract() * 1000.0,
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn repulsion_matches_the_f64_path() {
+        let positions = scattered(500);
+        let charges: Vec<f64> = (0..positions.len()).map(|i| 1.0 + (i % 7) as f64).collect();
+        let mut tree = TreeArena::new(positions.len() * 4);
+        let root = tree.rebuild([-1.0; 2], [1001.0; 2], &positions, &charges);
+        for rep_dist_exp in [1.0, 1.5, 2.0] {
+            for &position in positions.iter().step_by(37) {
+                let mut expected = [0.0; 2];
+                tree.compute_repulsion(root, position, 350.0, 0.8, rep_dist_exp, &mut expected);
+                let actual = repulsion(&tree, root, position, 350.0, 0.8, rep_dist_exp);
+                let magnitude = expected.iter().map(|v| v * v).sum::<f64>().sqrt();
+                for (actual, expected) in actual.into_iter().zip(expected) {
+                    assert!(
+                        (actual - expected).abs() <= 1e-4 * magnitude.max(1.0),
+                        "{actual} != {expected} with exponent {rep_dist_exp}"
+                    );
+                }
+            }
+        }
+    }
+
+    /// A ring of 24 nodes with chords across it.
+    fn ring() -> Vec<(usize, usize)> {
+        (0..24)
+            .map(|i| (i, (i + 1) % 24))
+            .chain((0..24).step_by(4).map(|i| (i, (i + 7) % 24)))
+            .collect()
+    }
+
+    fn input(adjacency: &[(usize, usize)], precision: Precision) -> LayoutInput<'_> {
+        LayoutInput {
+            num_nodes: 24,
+            adjacency,
+            edge_types: &[],
+            communities: &[],
+            pins: &[],
+            initial_positions: &[],
+            seed: 0,
+            initialization: Default::default(),
+            precision,
+        }
+    }
+
+    fn rms_radius(positions: &[[f64; 2]]) -> f64 {
+        (positions
+            .iter()
+            .map(|p| p[0] * p[0] + p[1] * p[1])
+            .sum::<f64>()
+            / positions.len() as f64)
+            .sqrt()
+    }
+
+    #[test]
+    fn the_first_iterations_stay_close_to_the_f64_path() {
+        // Later on, the hot early iterations amplify the differences into a different layout
+        let adjacency = ring();
+        let positions = |precision| {
+            let mut simulation = Simulation::<2>::new(&input(&adjacency, precision), true);
+            for _ in 0..3 {
+                simulation.step();
+            }
+            simulation.positions().to_vec()
+        };
+        let (expected, actual) = (positions(Precision::F64), positions(Precision::F32));
+        let radius = rms_radius(&expected);
+        for (actual, expected) in actual.iter().zip(&expected) {
+            let error =
+                ((actual[0] - expected[0]).powi(2) + (actual[1] - expected[1]).powi(2)).sqrt();
+            assert!(
+                error <= 1e-4 * radius,
+                "{actual:?} is {error} from {expected:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn a_finished_layout_has_the_f64_paths_proportions() {
+        let adjacency = ring();
+        // The mean edge length relative to the layout's radius
+        let proportion = |precision| {
+            let positions = ForceAtlas2.compute(&input(&adjacency, precision), &mut |_| {});
+            let edge_length = adjacency
+                .iter()
+                .map(|&(a, b)| {
+                    ((positions[a][0] - positions[b][0]).powi(2)
+                        + (positions[a][1] - positions[b][1]).powi(2))
+                    .sqrt()
+                })
+                .sum::<f64>()
+                / adjacency.len() as f64;
+            edge_length / rms_radius(&positions)
+        };
+        let (expected, actual) = (proportion(Precision::F64), proportion(Precision::F32));
+        assert!(
+            (actual - expected).abs() <= 0.02 * expected,
+            "{actual} != {expected}"
+        );
+    }
+}
@@ -0,0 +1,398 @@
+//! Stress majorization layout.
+//!
+//! Places the nodes so that the distance between each pair is as close as it
+//! can be to the length of the shortest path between them (in edges), with
+//! each pair weighted by the inverse square of that length so that near pairs
+//! count most. Starting from a pivot MDS embedding (or the initial positions,
+//! if there are any), each iteration moves every node to where it best agrees
+//! with the others (the localized SMACOF update), until the stress stops
+//! falling.
+//!
+//! Without a temperature or random forces, it shows the graph's distances
+//! faithfully, but separates communities less sharply than ForceAtlas2. It
+//! needs the distance between every pair of connected nodes, so it takes
+//! O(n²) memory and time per iteration; the distances are stored as `u16`s to
+//! keep that manageable.
+//!
+//! As with ForceAtlas2, the isolated set is placed in a ring around the
+//! connected core afterwards, and the core rescaled and decluttered (see
+//! [`crate`]). Parameters:
+//! - `STRESS_ITERATIONS`: the most iterations.
+//! - `STRESS_TOLERANCE`: stops once an iteration reduces the stress by less
+//!   than this fraction of it.
+//! - `STRESS_PIVOTS`: how many pivots the starting embedding is computed
+//!   from.
+
+use crate::{
+    GraphShape, LayoutAlgorithm, LayoutInput, Progress, env_f64, env_usize, hold_pins, map_indices,
+    snap_pins, starting_positions, tidy, tidy_note,
+};
+
+/// Stress majorization; see the [module documentation](self).
+pub struct StressMajorization;
+
+impl LayoutAlgorithm for StressMajorization {
+    fn compute(
+        &self,
+        input: &LayoutInput,
+        on_progress: &mut dyn FnMut(&Progress),
+    ) -> Vec<[f64; 2]> {
+        if input.num_nodes == 0 {
+            return vec![];
+        }
+        let iterations = env_usize("STRESS_ITERATIONS", 300);
+        let tolerance = env_f64("STRESS_TOLERANCE", 1e-5);
+        let pivots = env_usize("STRESS_PIVOTS", 50);
+        let mut parameter_notes = vec![
+            format!("iterations={iterations} tolerance={tolerance} pivots={pivots}"),
+            tidy_note(),
+        ];
+
+        let shape = GraphShape::new(input);
+        let core: Vec<usize> = (0..input.num_nodes)
+            .filter(|&i| !shape.is_isolated[i])
+            .collect();
+        let distances = Distances::new(&shape, &core);
+
+        let (mut positions, warm_started) = starting_positions(input, &shape, 1.0);
+        if !warm_started {
+            for (&i, position) in core
+                .iter()
+                .zip(pivot_mds(&shape, &core, &distances, pivots))
+            {
+                positions[i] = position;
+            }
+        }
+        // The starting positions are at an arbitrary scale, so they're scaled to
+        // best match the distances
+        let scale = distances.best_scale(&core, &positions);
+        for position in &mut positions {
+            position[0] *= scale;
+            position[1] *= scale;
+        }
+        hold_pins(&mut positions, &shape, input.pins, true);
+
+        let mut previous_stress = f64::INFINITY;
+        for iteration in 0..iterations {
+            let updates: Vec<([f64; 2], f64)> =
+                map_indices(core.len(), |a| distances.update(&core, &positions, a));
+            let mut stress = 0.0;
+            let mut max_displacement = 0.0_f64;
+            for (&i, (position, node_stress)) in core.iter().zip(updates) {
+                // Each pair is counted from both ends
+                stress += node_stress / 2.0;
+                if shape.is_pinned[i] {
+                    continue;
+                }
+                let (dx, dy) = (position[0] - positions[i][0], position[1] - positions[i][1]);
+                max_displacement = max_displacement.max((dx * dx + dy * dy).sqrt());
+                positions[i] = position;
+            }
+            hold_pins(&mut positions, &shape, input.pins, true);
+
+            let converged = previous_stress - stress < tolerance * previous_stress;
+            let mut notes = std::mem::take(&mut parameter_notes);
+            if converged {
+                notes.push(format!(
+                    "layout converged after iteration {iteration}/{iterations}"
+                ));
+            }
+            on_progress(&Progress {
+                iteration,
+                iterations,
+                temperature: 1.0,
+                max_displacement,
+                positions: &positions,
+                notes: &notes,
+            });
+            if converged {
+                break;
+            }
+            previous_stress = stress;
+        }
+
+        // The isolated ring is placed around the origin, which pins fix instead
+        if !shape.is_pinned.contains(&true) && !core.is_empty() {
+            let n = core.len() as f64;
+            let cx = core.iter().map(|&i| positions[i][0]).sum::<f64>() / n;
+            let cy = core.iter().map(|&i| positions[i][1]).sum::<f64>() / n;
+            for position in &mut positions {
+                position[0] -= cx;
+                position[1] -= cy;
+            }
+        }
+        tidy(&mut positions, &shape);
+        snap_pins(&mut positions, input.pins);
+        positions
+    }
+}
+
+/// The length of the shortest path between each pair of the connected core's
+/// nodes, indexed by their position in the core.
+struct Distances {
+    /// Row-major, `len` × `len`.
+    lengths: Vec<u16>,
+    len: usize,
+}
+
+impl Distances {
+    /// The distances between the `core` nodes of `shape`. Nodes in different
+    /// components are treated as one edge further apart than the furthest
+    /// connected pair.
+    fn new(shape: &GraphShape, core: &[usize]) -> Self {
+        let mut core_index = vec![usize::MAX; shape.neighbors.len()];
+        for (a, &i) in core.iter().enumerate() {
+            core_index[i] = a;
+        }
+        let mut lengths: Vec<u16> = map_indices(core.len(), |a| {
+            let source = core[a];
+            let mut row = vec![u16::MAX; core.len()];
+            row[core_index[source]] = 0;
+            let mut queue = std::collections::VecDeque::from([source]);
+            while let Some(node) = queue.pop_front() {
+                let length = row[core_index[node]];
+                for &neighbor in &shape.neighbors[node] {
+                    let b = core_index[neighbor];
+                    if row[b] == u16::MAX {
+                        row[b] = length.saturating_add(1).min(u16::MAX - 1);
+                        queue.push_back(neighbor);
+                    }
+                }
+            }
+            row
+        })
+        .concat();
+        let unreachable = lengths
+            .iter()
+            .copied()
+            .filter(|&length| length != u16::MAX)
+            .max()
+            .unwrap_or(0)
+            .saturating_add(1)
+            .min(u16::MAX - 1);
+        for length in &mut lengths {
+            if *length == u16::MAX {
+                *length = unreachable;
+            }
+        }
+        Self {
+            lengths,
+            len: core.len(),
+        }
+    }
+
+    fn get(&self, a: usize, b: usize) -> f64 {
+        self.lengths[a * self.len + b] as f64
+    }
+
+    /// The scale that best fits `positions` of the `core` to the distances.
+    fn best_scale(&self, core: &[usize], positions: &[[f64; 2]]) -> f64 {
+        // The stress Σ (s·r - d)² / d² is least at s = Σ (r / d) / Σ (r² / d²)
+        let (numerator, denominator) = map_indices(self.len, |a| {
+            let mut sums = (0.0, 0.0);
+            for b in 0..self.len {
+                let d = self.get(a, b);
+                if a == b || d == 0.0 {
+                    continue;
+                }
+                let [x, y] = positions[core[a]];
+                let [bx, by] = positions[core[b]];
+                let r = ((x - bx).powi(2) + (y - by).powi(2)).sqrt();
+                sums.0 += r / d;
+                sums.1 += r * r / (d * d);
+            }
+            sums
+        })
+        .into_iter()
+        .fold((0.0, 0.0), |(n, d), (a, b)| (n + a, d + b));
+        if denominator > 1e-12 {
+            numerator / denominator
+        } else {
+            1.0
+        }
+    }
+
+    /// Where the `a`th core node best agrees with the others, and its share of
+    /// the current stress.
+    fn update(&self, core: &[usize], positions: &[[f64; 2]], a: usize) -> ([f64; 2], f64) {
+        let [x, y] = positions[core[a]];
+        let (mut sum_x, mut sum_y, mut sum_weight, mut stress) = (0.0, 0.0, 0.0, 0.0);
+        for b in 0..self.len {
+            let d = self.get(a, b);
+            if a == b || d == 0.0 {
+                continue;
+            }
+            let weight = 1.0 / (d * d);
+            let [bx, by] = positions[core[b]];
+            let (dx, dy) = (x - bx, y - by);
+            let r = (dx * dx + dy * dy).sqrt();
+            // Where the pair would be exactly `d` apart, in the direction they are
+            let (tx, ty) = if r > 1e-9 {
+                (bx + d * dx / r, by + d * dy / r)
+            } else {
+                (bx, by)
+            };
+            sum_x += weight * tx;
+            sum_y += weight * ty;
+            sum_weight += weight;
+            stress += weight * (r - d) * (r - d);
+        }
+        if sum_weight > 0.0 {
+            ([sum_x / sum_weight, sum_y / sum_weight], stress)
+        } else {
+            ([x, y], stress)
+        }
+    }
+}
+
+/// Embed the core from its distances to a few pivots (Brandes and Pich's
+/// pivot MDS), returning a position for each core node. The pivots are
+/// picked furthest-first, starting from the highest-degree node.
+fn pivot_mds(
+    shape: &GraphShape,
+    core: &[usize],
+    distances: &Distances,
+    pivots: usize,
+) -> Vec<[f64; 2]> {
+    let len = distances.len;
+    let pivots = pivots.min(len);
+    if len < 3 || pivots < 2 {
+        return (0..len).map(|a| [a as f64, 0.0]).collect();
+    }
+
+    let first = (0..len)
+        .max_by_key(|&a| (shape.degrees[core[a]], std::cmp::Reverse(a)))
+        .unwrap();
+    let mut chosen = vec![first];
+    let mut nearest: Vec<f64> = (0..len).map(|a| distances.get(first, a)).collect();
+    while chosen.len() < pivots {
+        let next = (0..len)
+            .max_by(|&a, &b| nearest[a].total_cmp(&nearest[b]).then(b.cmp(&a)))
+            .unwrap();
+        chosen.push(next);
+        for (a, nearest) in nearest.iter_mut().enumerate() {
+            *nearest = nearest.min(distances.get(next, a));
+        }
+    }
+
+    // Squared distances to the pivots, double-centred
+    let mut c: Vec<Vec<f64>> = (0..len)
+        .map(|a| {
+            chosen
+                .iter()
+                .map(|&p| distances.get(p, a).powi(2))
+                .collect()
+        })
+        .collect();
+    let row_means: Vec<f64> = c
+        .iter()
+        .map(|row| row.iter().sum::<f64>() / pivots as f64)
+        .collect();
+    let column_means: Vec<f64> = (0..pivots)
+        .map(|p| c.iter().map(|row| row[p]).sum::<f64>() / len as f64)
+        .collect();
+    let mean = row_means.iter().sum::<f64>() / len as f64;
+    for (row, row_mean) in c.iter_mut().zip(&row_means) {
+        for (value, column_mean) in row.iter_mut().zip(&column_means) {
+            *value = -0.5 * (*value - row_mean - column_mean + mean);
+        }
+    }
+
+    // The two leading eigenvectors of CᵀC, by power iteration
+    let ctc: Vec<Vec<f64>> = (0..pivots)
+        .map(|p| {
+            (0..pivots)
+                .map(|q| c.iter().map(|row| row[p] * row[q]).sum())
+                .collect()
+        })
+        .collect();
+    let mut eigenvectors: Vec<(Vec<f64>, f64)> = vec![];
+    for component in 0..2 {
+        let mut v: Vec<f64> = (0..pivots)
+            .map(|p| 1.0 + ((p + component) % 3) as f64)
+            .collect();
+        let mut eigenvalue = 0.0;
+        for _ in 0..200 {
+            let mut next: Vec<f64> = ctc
+                .iter()
+                .map(|row| row.iter().zip(&v).map(|(a, b)| a * b).sum())
+                .collect();
+            for (previous, _) in &eigenvectors {
+                let dot: f64 = next.iter().zip(previous).map(|(a, b)| a * b).sum();
+                for (value, previous) in next.iter_mut().zip(previous) {
+                    *value -= dot * previous;
+                }
+            }
+            let norm = next.iter().map(|value| value * value).sum::<f64>().sqrt();
+            if norm < 1e-12 {
+                break;
+            }
+            eigenvalue = norm;
+            v = next.into_iter().map(|value| value / norm).collect();
+        }
+        eigenvectors.push((v, eigenvalue));
+    }
+
+    // C's left singular vectors scaled by the square roots of its singular values, as classical
+    // MDS would place them
+    c.iter()
+        .map(|row| {
+            let coordinate = |(v, eigenvalue): &(Vec<f64>, f64)| {
+                row.iter().zip(v).map(|(a, b)| a * b).sum::<f64>()
+                    / eigenvalue.powf(0.25).max(1e-12)
+            };
+            [coordinate(&eigenvectors[0]), coordinate(&eigenvectors[1])]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stress_never_increases() {
+        // A 6×6 grid
+        let adjacency: Vec<(usize, usize)> = (0..36)
+            .flat_map(|i| {
+                let right = (i % 6 < 5).then_some((i, i + 1));
+                let down = (i < 30).then_some((i, i + 6));
+                right.into_iter().chain(down)
+            })
+            .collect();
+        let input = LayoutInput {
+            num_nodes: 36,
+            adjacency: &adjacency,
+            edge_types: &[],
+            communities: &[],
+            pins: &[],
+            initial_positions: &[],
+            seed: 0,
+            initialization: Default::default(),
+            precision: Default::default(),
+        };
+        let shape = GraphShape::new(&input);
+        let core: Vec<usize> = (0..36).collect();
+        let distances = Distances::new(&shape, &core);
+        let stress = |positions: &[[f64; 2]]| {
+            (0..core.len())
+                .map(|a| distances.update(&core, positions, a).1)
+                .sum::<f64>()
+                / 2.0
+        };
+
+        let mut stresses = vec![];
+        StressMajorization.compute(&input, &mut |progress| {
+            stresses.push(stress(progress.positions));
+        });
+        assert!(stresses.len() > 1);
+        for pair in stresses.windows(2) {
+            assert!(
+                pair[1] <= pair[0] * (1.0 + 1e-9),
+                "{} > {}",
+                pair[1],
+                pair[0]
+            );
+        }
+    }
+}
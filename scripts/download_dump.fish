@@ -75,7 +75,10 @@ function main
         "enwiki-$date_yyyymmdd-pages-articles-multistream.xml.bz2" \
         "enwiki-$date_yyyymmdd-pages-articles-multistream-index.txt.bz2" \
         "enwiki-$date_yyyymmdd-linktarget.sql.gz" \
-        "enwiki-$date_yyyymmdd-pagelinks.sql.gz"
+        "enwiki-$date_yyyymmdd-pagelinks.sql.gz" \
+        "enwiki-$date_yyyymmdd-page.sql.gz" \
+        "enwiki-$date_yyyymmdd-langlinks.sql.gz" \
+        "enwiki-$date_yyyymmdd-page_props.sql.gz"
 
     set -l base_url "https://dumps.wikimedia.org/enwiki/$date_yyyymmdd"
 
@@ -65,6 +65,30 @@ impl PageName {
         output
     }
 
+    /// The subdirectory a page's file is sharded into when there are too many to keep in one:
+    /// the first two characters of its name, lowercased, with anything but an ASCII letter or
+    /// digit (or a missing character) as `_`. "ABBA" is in `ab`, and "!!!" in `__`.
+    pub fn shard(&self) -> String {
+        let mut chars = self.name.chars();
+        std::iter::repeat_with(|| match chars.next() {
+            Some(c) if c.is_ascii_alphanumeric() => c.to_ascii_lowercase(),
+            _ => '_',
+        })
+        .take(2)
+        .collect()
+    }
+
+    /// The path of a page's file in `directory` of the website's data, as in
+    /// `genres/ab/Abstract hip hop.json`, or `genres/Abstract hip hop.json` if the files aren't
+    /// sharded into prefix subdirectories (see [`Self::shard`]).
+    pub fn data_file_path(&self, directory: &str, sharded: bool) -> String {
+        if sharded {
+            format!("{directory}/{}/{}.json", self.shard(), self.sanitize())
+        } else {
+            format!("{directory}/{}.json", self.sanitize())
+        }
+    }
+
     /// Reverses [`Self::sanitize`].
     pub fn unsanitize(title: &str) -> PageName {
         let mut output = title.to_string();
@@ -142,10 +166,103 @@ pub fn normalize_search_text(s: &str) -> String {
         .collect()
 }
 
+/// A genre in the prebuilt search index (`search_index.json`), which lists them in node order.
+///
+/// Written by datagen and loaded by the frontend's search. Terms are deduplicated by
+/// [`normalize_search_text`], so each appears once, in the first category it belongs to.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SearchIndexNode {
+    /// The genre's label.
+    pub label: String,
+    /// Alternative names for the genre.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    /// The names of the genre's top artists.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub artists: Vec<String>,
+    /// The genre's titles in other languages' Wikipedias.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub translations: Vec<String>,
+    /// Inbound Wikipedia link count for the genre's page and its redirects.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub links: usize,
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+/// The type of relationship between two genres.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EdgeType {
+    /// A derivative genre relationship.
+    Derivative,
+    /// A subgenre relationship.
+    Subgenre,
+    /// A fusion genre relationship.
+    FusionGenre,
+    /// A looser relationship from a genre's "See also" section.
+    Related,
+}
+impl EdgeType {
+    /// The name of the edge type in exports (e.g. `fusion_genre`).
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Derivative => "derivative",
+            Self::Subgenre => "subgenre",
+            Self::FusionGenre => "fusion_genre",
+            Self::Related => "related",
+        }
+    }
+
+    /// The index of the edge type in serialized edges.
+    pub fn index(&self) -> u8 {
+        match self {
+            Self::Derivative => 0,
+            Self::Subgenre => 1,
+            Self::FusionGenre => 2,
+            Self::Related => 3,
+        }
+    }
+
+    /// The edge type with the given [`index`](Self::index).
+    pub fn from_index(index: u8) -> Option<Self> {
+        match index {
+            0 => Some(Self::Derivative),
+            1 => Some(Self::Subgenre),
+            2 => Some(Self::FusionGenre),
+            3 => Some(Self::Related),
+            _ => None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn shard_uses_the_first_two_characters() {
+        assert_eq!(PageName::new("ABBA", None).shard(), "ab");
+        assert_eq!(PageName::new("!!!", None).shard(), "__");
+        assert_eq!(PageName::new("Björk", None).shard(), "bj");
+        assert_eq!(PageName::new("Ñ", None).shard(), "__");
+        assert_eq!(PageName::new("X", None).shard(), "x_");
+    }
+
+    #[test]
+    fn data_file_path_includes_the_shard() {
+        let page = PageName::new("Abstract hip hop", None);
+        assert_eq!(
+            page.data_file_path("genres", true),
+            "genres/ab/Abstract hip hop.json"
+        );
+        assert_eq!(
+            page.data_file_path("genres", false),
+            "genres/Abstract hip hop.json"
+        );
+    }
+
     #[test]
     fn normalize_search_text_lowercases() {
         assert_eq!(normalize_search_text("Hip-Hop"), "hip-hop");
@@ -6,7 +6,7 @@ set -e
 run_config() {
     local name="$1"; shift
     echo "=== $name ==="
-    env "$@" cargo run --manifest-path datagen/Cargo.toml --bin relayout --release 2>&1 | tail -1
+    env "$@" cargo run --manifest-path datagen/Cargo.toml --bin relayout --release 2>&1 | grep "layout metrics"
     uv run visualize_layout.py 2>&1 | grep -E "std|Edge|Pairwise"
     cp layout_visualization.png "layout_${name}.png"
     echo
@@ -0,0 +1,68 @@
+# /// script
+# requires-python = ">=3.10"
+# dependencies = [
+#     "matplotlib",
+# ]
+# ///
+"""Visualize how the force-directed layout settles, from the snapshots written by
+`cargo run --bin relayout --release -- --snapshots <every>`."""
+
+import json
+import colorsys
+from pathlib import Path
+import matplotlib.pyplot as plt
+import matplotlib.collections as mc
+import numpy as np
+
+MAX_FRAMES = 12
+
+with open("website/public/data.json", encoding="utf-8") as f:
+    data = json.load(f)
+
+nodes = data["nodes"]
+edges = data["edges"]
+
+snapshots = sorted(Path("layout_snapshots").glob("*.json"))
+if not snapshots:
+    raise SystemExit("No snapshots in layout_snapshots/; run relayout with --snapshots first")
+# Evenly spaced frames, always including the last
+if len(snapshots) > MAX_FRAMES:
+    indices = np.linspace(0, len(snapshots) - 1, MAX_FRAMES).round().astype(int)
+    snapshots = [snapshots[i] for i in indices]
+print(f"Frames: {len(snapshots)}")
+
+degrees = np.zeros(len(nodes), dtype=int)
+for src, tgt, ty in edges:
+    degrees[src] += 1
+    degrees[tgt] += 1
+sizes = 2 + (degrees / max(degrees.max(), 1)) * 20
+
+# The same hues as visualize_layout.py, which are the final layout's
+hues = np.array([n.get("hue", 0.0) for n in nodes])
+node_colors = np.array([colorsys.hsv_to_rgb((h % 360) / 360, 0.65, 1.0) + (0.85,) for h in hues])
+edge_colors_map = {0: (0.8, 0.2, 0.2, 0.12), 1: (0.2, 0.8, 0.2, 0.12), 2: (0.3, 0.3, 0.9, 0.12)}
+
+columns = min(4, len(snapshots))
+rows = (len(snapshots) + columns - 1) // columns
+fig, axes = plt.subplots(rows, columns, figsize=(6 * columns, 6 * rows), squeeze=False)
+for ax in axes.flat[len(snapshots):]:
+    ax.set_axis_off()
+
+for ax, path in zip(axes.flat, snapshots):
+    with open(path, encoding="utf-8") as f:
+        positions = np.array(json.load(f))
+    xs, ys = positions[:, 0], positions[:, 1]
+
+    ax.set_title(f"Iteration {int(path.stem)}")
+    ax.set_facecolor("#111111")
+    ax.set_aspect("equal")
+    lines = [[(xs[src], ys[src]), (xs[tgt], ys[tgt])] for src, tgt, ty in edges]
+    colors = [edge_colors_map.get(ty, (0.5, 0.5, 0.5, 0.1)) for src, tgt, ty in edges]
+    ax.add_collection(mc.LineCollection(lines, colors=colors, linewidths=0.3))
+    ax.scatter(xs, ys, s=sizes, c=node_colors, edgecolors="none", zorder=2)
+    ax.autoscale_view()
+
+plt.tight_layout()
+plt.savefig("layout_snapshots.png", dpi=100, facecolor="#222222")
+print("Saved layout_snapshots.png")
+plt.close()
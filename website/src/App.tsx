@@ -1,4 +1,5 @@
 import { useEffect, useState, useCallback, useRef, useMemo } from "react";
+import { decode_data } from "frontend_wasm";
 
 import { Graph, ZoomRequest } from "./views/Graph";
 import { SearchPanel } from "./views/search/SearchPanel";
@@ -14,6 +15,10 @@ import {
 
 import { Sidebar, SIDEBAR_DEFAULT_WIDTH } from "./views/sidebar/Sidebar";
 import { DataCache, DataCacheContext } from "./services/dataCache";
+import { fetchChunkedData } from "./services/chunkedData";
+import { fetchNeighborhood } from "./services/neighborhood";
+import type { DataIndex, Neighborhood } from "./dataTypes";
+import { NeighborhoodPreview } from "./views/NeighborhoodPreview";
 import { colourStyles } from "./views/colours";
 import { textStyles } from "./views/typography";
 
@@ -91,7 +96,11 @@ function buildHash(
 /** The main app component */
 function App() {
   const loading = useData();
-  const [dataCache] = useState(() => new DataCache());
+  const linkedNeighborhood = useLinkedNeighborhood();
+  // Known once the data has loaded, before anything asks the cache for a file
+  const shardedFiles =
+    loading.state === "loaded" && (loading.data.sharded_files ?? false);
+  const dataCache = useMemo(() => new DataCache(shardedFiles), [shardedFiles]);
 
   if (loading.state === "loading") {
     return (
@@ -103,6 +112,9 @@ function App() {
         }}
       >
         <div className="flex flex-col items-center gap-5 text-center">
+          {linkedNeighborhood && (
+            <NeighborhoodPreview neighborhood={linkedNeighborhood} />
+          )}
           <div
             className={`w-14 h-14 border ${colourStyles.loading.spinner} rounded-full animate-spin`}
           />
@@ -130,15 +142,57 @@ function App() {
   }
 }
 
+/**
+ * Fetch the graph data, in the format datagen wrote it in (see `data_format`),
+ * which `data_index.json` records. With the chunked format, `onSkeleton` is
+ * given enough of it to draw the graph first.
+ */
+async function fetchDataOnDisk(
+  onSkeleton: (data: DataOnDisk) => void
+): Promise<DataOnDisk> {
+  const index: DataIndex = await (await fetch("/data_index.json")).json();
+  const path = `/${index.path}`;
+  switch (index.format) {
+    case "chunked":
+      return fetchChunkedData(path, onSkeleton);
+    case "messagepack": {
+      const response = await fetch(path);
+      const bytes = new Uint8Array(await response.arrayBuffer());
+      return decode_data(bytes) as DataOnDisk;
+    }
+    case "json":
+      return (await fetch(path)).json();
+  }
+}
+
+/**
+ * The neighbourhood of the genre that the page was opened with a link to, if
+ * any, to show while the graph loads.
+ */
+function useLinkedNeighborhood(): Neighborhood | null {
+  const [neighborhood, setNeighborhood] = useState<Neighborhood | null>(null);
+
+  useEffect(() => {
+    const { sourceId } = parseHash(window.location.hash);
+    if (sourceId) {
+      fetchNeighborhood(sourceId).then(setNeighborhood);
+    }
+  }, []);
+
+  return neighborhood;
+}
+
 function useData(): { state: "loading" } | { state: "loaded"; data: Data } {
   const [data, setData] = useState<Data | undefined>();
 
   useEffect(() => {
     async function fetchData() {
       try {
-        const response = await fetch("/data.json");
-        const dataOnDisk: DataOnDisk = await response.json();
-        const data = postProcessData(dataOnDisk);
+        const data = postProcessData(
+          await fetchDataOnDisk((skeleton) =>
+            setData(postProcessData(skeleton))
+          )
+        );
         setData(data);
       } catch (error) {
         console.error("Error loading data:", error);
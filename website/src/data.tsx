@@ -1,24 +1,25 @@
 import { createContext, useContext } from "react";
 
-/** The data that is identical between {@link DataOnDisk} and {@link Data}. */
-export type DataShared = {
-  /** The Wikipedia domain. */
-  wikipedia_domain: string;
-  /** The Wikipedia database name. */
-  wikipedia_db_name: string;
-  /** The date of the dump (ISO 8601). */
-  dump_date: string;
-  /** The maximum degree of any node in the graph. */
-  max_degree: number;
-};
+import type { DataOnDisk, NodeOnDiskData } from "./dataTypes";
+
+// The types of the data files are generated from `datagen`; see `dataTypes.ts`.
+export type {
+  ArtistFileData,
+  ArtistScore,
+  DataOnDisk,
+  EdgeOnDiskData,
+  GenreFileData,
+  GenreImage,
+  GenreMix,
+  GenreMixes,
+  LinksToPageIds,
+  MixEmbed,
+  MixProvider,
+  NodeOnDiskData,
+} from "./dataTypes";
 
-/** The raw data that we load from the network. */
-export type DataOnDisk = DataShared & {
-  /** The nodes in the graph. */
-  nodes: NodeOnDiskData[];
-  /** The edges in the graph. */
-  edges: EdgeOnDiskData[];
-};
+/** The data that is identical between {@link DataOnDisk} and {@link Data}. */
+export type DataShared = Omit<DataOnDisk, "nodes" | "edges">;
 
 /** The global data made available to the frontend after {@link DataOnDisk} is post-processed by {@link postProcessData}. */
 export type Data = DataShared & {
@@ -43,6 +44,7 @@ export function postProcessData(data: DataOnDisk): Data {
       source: edge[0].toString(),
       target: edge[1].toString(),
       ty: edge[2],
+      corroborated: edge[3] ?? false,
     })),
     nodes: data.nodes.map((node, index) => ({
       id: index.toString(),
@@ -62,24 +64,6 @@ export function postProcessData(data: DataOnDisk): Data {
   return newData;
 }
 
-/** A node in the graph, as stored on disk. */
-export type NodeOnDiskData = {
-  /** The node's Wikipedia page title. When not present, the same as {@link label}. */
-  page_title?: string;
-  /** The node's label. */
-  label: string;
-  /** Alternative names, derived from Wikipedia redirects. Absent in older data. */
-  aliases?: string[];
-  /** Inbound Wikipedia link count for the genre's page. Absent in older data. */
-  links?: number;
-  /** The node's precomputed x position from force-directed layout. */
-  x: number;
-  /** The node's precomputed y position from force-directed layout. */
-  y: number;
-  /** The node's precomputed hue (0–360) from color propagation. */
-  hue: number;
-};
-
 /** A node in the graph. */
 export type NodeData = NodeOnDiskData & {
   /** The node's ID (integer as a string). Consider using {@link nodeIdToInt} instead. */
@@ -92,24 +76,32 @@ export const nodeIdToInt = (id: string) => parseInt(id, 10);
 /** Get the page title of a node. */
 export const nodePageTitle = (data: NodeData) => data.page_title ?? data.label;
 
-/** Genre data from the genre JSON files. */
-export type GenreFileData = {
-  /** The genre's Wikipedia wikitext description. */
-  description?: string;
-  /** The node's last revision date (ISO 8601). */
-  last_revision_date: string;
-  /** The node's mixes. */
-  mixes?:
-    | { help_reason: string | null }
-    | { playlist: string; note?: string }[]
-    | { video: string; note?: string }[];
-  /** The node's top artists, as page names. */
-  top_artists: string[];
+/**
+ * The key for a link in {@link LinksToPageIds}. This must match `link_key` in `datagen/src/links.rs`:
+ * only the first letter of the title is case-insensitive, underscores are spaces, and headings
+ * are lower-cased.
+ */
+export const linkKey = (link: string): string => {
+  const trimmed = link.trim();
+  const hashIndex = trimmed.indexOf("#");
+  const page = (hashIndex === -1 ? trimmed : trimmed.slice(0, hashIndex))
+    .trim()
+    .replace(/_/g, " ");
+  const [first = "", ...rest] = Array.from(page);
+  let key = first.toLowerCase() + rest.join("");
+  if (hashIndex !== -1) {
+    const heading = trimmed.slice(hashIndex + 1).trim();
+    let decoded: string;
+    try {
+      decoded = decodeURIComponent(heading);
+    } catch {
+      decoded = heading;
+    }
+    key += "#" + decoded.replace(/_/g, " ").toLowerCase();
+  }
+  return key;
 };
 
-/** A map of links to page IDs. */
-export type LinksToPageIds = Record<string, number>;
-
 /** Values for node colour lightness in different contexts. */
 export const NodeColourLightness = {
   /** The lightness of the darker background colour. */
@@ -198,21 +190,12 @@ export const EdgeType = {
   Derivative: 0,
   Subgenre: 1,
   FusionGenre: 2,
+  Related: 3,
 } as const;
 
 /** The types of edges in the graph (typed values of {@link EdgeType}) */
 export type EdgeType = (typeof EdgeType)[keyof typeof EdgeType];
 
-/** An edge in the graph, as stored on disk. */
-export type EdgeOnDiskData = [
-  /** The edge's source node ID */
-  number,
-  /** The edge's target node ID */
-  number,
-  /** The edge's type */
-  EdgeType,
-];
-
 /** An edge in the graph. */
 export type EdgeData = {
   /** The edge's source node ID (integer as a string). Consider using {@link nodeIdToInt} instead. */
@@ -221,18 +204,8 @@ export type EdgeData = {
   target: string;
   /** The edge's type. */
   ty: EdgeType;
-};
-
-/** Artist data from the artist JSON files. */
-export type ArtistFileData = {
-  /** The artist's name. */
-  name: string;
-  /** The artist's description (wikitext). */
-  description?: string;
-  /** The artist's last revision date (ISO 8601). */
-  last_revision_date: string;
-  /** The artist's genres, as page IDs. */
-  genres: number[];
+  /** Whether both genres' infoboxes assert the edge, rather than just one of them. */
+  corroborated: boolean;
 };
 
 // Ideally, we could integrate this into `commit.json`, but getting the "safe" URL from the checkout
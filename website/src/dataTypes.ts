@@ -0,0 +1,596 @@
+// Generated by `datagen` from the types in `datagen/src`; do not edit by hand.
+
+/**
+ * The root structure serialized to the graph data file.
+ */
+export type DataOnDisk = {
+/**
+ * The Wikipedia domain (e.g. "en.wikipedia.org").
+ */
+wikipedia_domain: string,
+/**
+ * The Wikipedia database name (e.g. "enwiki").
+ */
+wikipedia_db_name: string,
+/**
+ * The dump date (e.g. "2026-02-01").
+ */
+dump_date: string,
+/**
+ * The graph nodes.
+ */
+nodes: Array<NodeOnDiskData>,
+/**
+ * The graph edges.
+ */
+edges: Array<EdgeOnDiskData>,
+/**
+ * The maximum degree of any node.
+ */
+max_degree: number,
+/**
+ * The label of each community, indexed by [`NodeData::community`]: that of its
+ * highest-degree genre.
+ */
+communities: Array<GenreName>,
+/**
+ * Whether the genre and artist files are sharded into subdirectories by the start of their
+ * names (e.g. `genres/ab/Abstract hip hop.json`), so that no one directory has tens of
+ * thousands of files. Older data has them directly in `genres/` and `artists/`.
+ */
+sharded_files?: boolean,
+/**
+ * The seed the layout was started from (see [`layout::LayoutInput::seed`]),
+ * so that it can be reproduced. Older data doesn't record it.
+ */
+layout_seed?: number, };
+
+/**
+ * A genre node in the graph.
+ */
+export type NodeOnDiskData = {
+/**
+ * The Wikipedia page title, if different from the label.
+ */
+page_title?: string,
+/**
+ * The display label.
+ */
+label: GenreName,
+/**
+ * Alternative names, derived from Wikipedia redirects (cleaned and deduplicated).
+ */
+aliases?: Array<string>,
+/**
+ * Inbound Wikipedia link count for the genre's page and its redirects.
+ */
+links?: number,
+/**
+ * The genre's popularity tier by `links`, from 0 (the least-linked fifth of genres) to 4
+ * (the most-linked fifth), for scaling genres by notability rather than degree.
+ */
+popularity: number,
+/**
+ * X position from force-directed layout.
+ */
+x: number,
+/**
+ * Y position from force-directed layout.
+ */
+y: number,
+/**
+ * Z position from force-directed layout, if it was laid out in 3D.
+ */
+z?: number,
+/**
+ * Hue (0–360) from color propagation.
+ */
+hue: number,
+/**
+ * The community (a cluster of densely-connected genres) from community detection,
+ * numbered from the largest down.
+ */
+community: number,
+/**
+ * The number of edges into the genre.
+ */
+in_degree: number,
+/**
+ * The number of edges out of the genre.
+ */
+out_degree: number,
+/**
+ * PageRank (summing to 1 across genres) over the non-`Related` edges reversed, so that a
+ * genre ranks highly when influential genres derive from it.
+ */
+pagerank: number,
+/**
+ * Betweenness centrality (0–1) over the non-`Related` edges: how many of the shortest paths
+ * between other genres pass through the genre.
+ */
+betweenness: number,
+/**
+ * The fewest non-`Related` edges from a root genre (one that doesn't derive from any other)
+ * to this one, or `None` if it only derives from genres in a cycle. Roots have a depth of 0.
+ */
+depth?: number,
+/**
+ * The Wikidata item ID of the genre's page, if it has one. Genres that are sections of a
+ * page don't have their own item.
+ */
+wikidata_qid?: string,
+/**
+ * The MusicBrainz genre ID of the genre's Wikidata item, if it's mapped to one.
+ */
+musicbrainz_genre_id?: string, };
+
+/**
+ * An edge between two genre nodes, serialized as a `[source, target, type]` tuple, with a
+ * fourth `true` element if it's corroborated.
+ */
+export type EdgeOnDiskData = [PageDataId, PageDataId, 0 | 1 | 2 | 3, true?];
+
+/**
+ * Which format the graph data was written in ([`DATA_INDEX_FILE`]), so that the website
+ * fetches only that file rather than looking for each in turn.
+ */
+export type DataIndex = {
+/**
+ * The format of the graph data.
+ */
+format: DataFormat,
+/**
+ * The path of its file ([`DataFormat::file_name`]), relative to the index.
+ */
+path: string, };
+
+/**
+ * The format the graph data is written in.
+ */
+export type DataFormat = "json" | "messagepack" | "chunked";
+
+/**
+ * The manifest of the chunked format (`chunks.json`). Paths are relative to it.
+ */
+export type ChunkManifest = {
+/**
+ * The path of the graph's metadata ([`ChunkedMeta`]).
+ */
+meta: string,
+/**
+ * The path of the node skeletons ([`NodeSkeleton`]), indexed by node ID.
+ */
+nodes: string,
+/**
+ * The path of the binary edge list.
+ */
+edges: string,
+/**
+ * The number of edges in the edge list.
+ */
+edge_count: number,
+/**
+ * The chunk of each community, from the largest down (ties by community).
+ */
+clusters: Array<ClusterChunkInfo>, };
+
+/**
+ * A community's chunk in [`ChunkManifest`].
+ */
+export type ClusterChunkInfo = {
+/**
+ * The path of the chunk ([`ClusterChunk`]).
+ */
+path: string,
+/**
+ * The community, as in [`NodeData::community`].
+ */
+community: number,
+/**
+ * The community's label, as in [`FrontendData::communities`].
+ */
+label?: GenreName,
+/**
+ * The number of nodes in the community.
+ */
+node_count: number,
+/**
+ * The bounds of the community's nodes: `[min x, min y, max x, max y]`.
+ */
+bounds: [number, number, number, number], };
+
+/**
+ * The graph data of the chunked format, without its nodes or edges (`meta.json`).
+ */
+export type ChunkedMeta = {
+/**
+ * The Wikipedia domain (e.g. "en.wikipedia.org").
+ */
+wikipedia_domain: string,
+/**
+ * The Wikipedia database name (e.g. "enwiki").
+ */
+wikipedia_db_name: string,
+/**
+ * The dump date (e.g. "2026-02-01").
+ */
+dump_date: string,
+/**
+ * The maximum degree of any node.
+ */
+max_degree: number,
+/**
+ * The label of each community, as in [`FrontendData::communities`].
+ */
+communities: Array<GenreName>,
+/**
+ * Whether the genre and artist files are sharded, as in [`FrontendData::sharded_files`].
+ */
+sharded_files?: boolean,
+/**
+ * The layout's seed, as in [`FrontendData::layout_seed`].
+ */
+layout_seed?: number,
+/**
+ * The number of nodes.
+ */
+node_count: number, };
+
+/**
+ * What's needed to draw a node before its [`ClusterChunk`] arrives (`nodes.json`).
+ */
+export type NodeSkeleton = {
+/**
+ * The display label.
+ */
+label: GenreName,
+/**
+ * X position from force-directed layout.
+ */
+x: number,
+/**
+ * Y position from force-directed layout.
+ */
+y: number,
+/**
+ * Z position from force-directed layout, if it was laid out in 3D.
+ */
+z?: number,
+/**
+ * Hue (0–360) from color propagation.
+ */
+hue: number,
+/**
+ * The community, as in [`NodeData::community`].
+ */
+community: number,
+/**
+ * The popularity tier, as in [`NodeData::popularity`].
+ */
+popularity: number, };
+
+/**
+ * The full data of a community's nodes (`clusters/<community>.json`).
+ */
+export type ClusterChunk = {
+/**
+ * The ID of each node.
+ */
+ids: Array<PageDataId>,
+/**
+ * The data of each node, in the same order as `ids`.
+ */
+nodes: Array<NodeOnDiskData>, };
+
+/**
+ * A node's neighbourhood (`neighborhood/<id>.json`): the nodes within a few edges of it, and
+ * the edges between them, so that the website can draw a linked genre and its surroundings
+ * before the whole graph has loaded.
+ */
+export type Neighborhood = {
+/**
+ * The ID of each node: the node itself first, then the rest by how far away they are.
+ */
+ids: Array<PageDataId>,
+/**
+ * The number of edges between the node and each node, in the same order as `ids`.
+ */
+hops: Array<number>,
+/**
+ * The data of each node, in the same order as `ids`.
+ */
+nodes: Array<NodeOnDiskData>,
+/**
+ * The edges between the nodes, by node ID.
+ */
+edges: Array<EdgeOnDiskData>, };
+
+/**
+ * A newtype for a genre name.
+ */
+export type GenreName = string;
+
+/**
+ * A newtype for an ID assigned to a page for the graph.
+ */
+export type PageDataId = number;
+
+/**
+ * Genre data from the genre files.
+ */
+export type GenreFileData = {
+/**
+ * The genre's Wikipedia wikitext description.
+ */
+description: string | null,
+/**
+ * The description rendered to sanitized HTML, one string per paragraph, if it was written
+ * with `description_html` and has nothing that only the website can render.
+ */
+description_html?: Array<string>,
+/**
+ * The genre's last revision date (ISO 8601).
+ */
+last_revision_date: string,
+/**
+ * The genre's mixes.
+ */
+mixes?: GenreMixes,
+/**
+ * The title, channel and thumbnail of each of the genre's mixes, by video or playlist ID,
+ * if they were looked up with `mix_embeds`.
+ */
+mix_embeds?: { [key in string]?: MixEmbed },
+/**
+ * A thumbnail of the genre's infobox image, if it was written with `genre_images`.
+ */
+image?: GenreImage,
+/**
+ * The genre's top artists, as page names.
+ */
+top_artists: Array<string>,
+/**
+ * The scores behind `top_artists`, in the same order.
+ */
+top_artist_scores: Array<ArtistScore>,
+/**
+ * The top artists among those active in each decade (e.g. 1990 for the 1990s).
+ */
+top_artists_by_decade?: { [key in number]?: Array<string> },
+/**
+ * The genres this one derives from, by generation: its stylistic origins and parent
+ * genres, then theirs, and so on. Each genre appears once, in its nearest generation.
+ */
+ancestors?: Array<Array<PageDataId>>,
+/**
+ * The genres that derive from this one (its derivatives, subgenres and fusion genres, then
+ * theirs, and so on), by generation.
+ */
+descendants?: Array<Array<PageDataId>>,
+/**
+ * The countries the genre originated in.
+ */
+origin_countries?: Array<string>, };
+
+/**
+ * A list of mixes for a genre.
+ */
+export type GenreMixes = {
+/**
+ * The reason the mix was not available.
+ */
+help_reason: string | null, } | Array<GenreMix>;
+
+/**
+ * A mix for a genre, consisting of a YouTube playlist or video, or a mix on another
+ * [`MixProvider`].
+ */
+export type GenreMix = {
+/**
+ * The ID of the playlist.
+ */
+playlist: string,
+/**
+ * A note about the mix.
+ */
+note?: string,
+/**
+ * The title of the video or playlist, as it was when the mix was added.
+ */
+title?: string,
+/**
+ * The length of the video, in seconds.
+ */
+duration?: number,
+/**
+ * Who added the mix.
+ */
+submitter?: string,
+/**
+ * When the mix was added, as `YYYY-MM-DD`.
+ */
+added?: string, } | {
+/**
+ * The ID of the video.
+ */
+video: string,
+/**
+ * A note about the mix.
+ */
+note?: string,
+/**
+ * The title of the video or playlist, as it was when the mix was added.
+ */
+title?: string,
+/**
+ * The length of the video, in seconds.
+ */
+duration?: number,
+/**
+ * Who added the mix.
+ */
+submitter?: string,
+/**
+ * When the mix was added, as `YYYY-MM-DD`.
+ */
+added?: string, } | {
+/**
+ * Where the mix is.
+ */
+provider: MixProvider,
+/**
+ * The URL of the mix's page, as [`MixProvider::normalize_url`] has it.
+ */
+url: string,
+/**
+ * A note about the mix.
+ */
+note?: string,
+/**
+ * The title of the video or playlist, as it was when the mix was added.
+ */
+title?: string,
+/**
+ * The length of the video, in seconds.
+ */
+duration?: number,
+/**
+ * Who added the mix.
+ */
+submitter?: string,
+/**
+ * When the mix was added, as `YYYY-MM-DD`.
+ */
+added?: string, };
+
+/**
+ * Where a mix that isn't on YouTube is.
+ */
+export type MixProvider = "bandcamp" | "soundcloud" | "mixcloud";
+
+/**
+ * What YouTube's oEmbed endpoint says about a mix.
+ */
+export type MixEmbed = {
+/**
+ * The title of the video or playlist.
+ */
+title: string,
+/**
+ * The name of the channel that uploaded it.
+ */
+channel: string,
+/**
+ * The URL of the channel.
+ */
+channel_url: string,
+/**
+ * The URL of its thumbnail image.
+ */
+thumbnail_url: string, };
+
+/**
+ * A genre's infobox image, as written to the website.
+ */
+export type GenreImage = {
+/**
+ * The path of the thumbnail from the website's root (e.g. `images/1a2b3c4d5e6f7a8b.webp`).
+ */
+path: string,
+/**
+ * The width of the thumbnail.
+ */
+width: number,
+/**
+ * The height of the thumbnail.
+ */
+height: number,
+/**
+ * The URL of the image's description page, which credits its author and gives its license.
+ */
+description_url: string, };
+
+/**
+ * How an artist's rank within a genre was calculated.
+ */
+export type ArtistScore = {
+/**
+ * The score the artist is ranked by: `popularity * weight * diversity_penalty`.
+ */
+score: number,
+/**
+ * The artist's popularity, from the configured ranking metric.
+ */
+popularity: number,
+/**
+ * How representative the artist is of the genre, from its position in their infobox and
+ * how many other genres are listed with it.
+ */
+weight: number,
+/**
+ * The penalty for acts ranked above the artist that they're associated with (1.0 for none).
+ */
+diversity_penalty: number, };
+
+/**
+ * Artist data from the artist files.
+ */
+export type ArtistFileData = {
+/**
+ * The artist's name.
+ */
+name: string,
+/**
+ * The artist's description (wikitext).
+ */
+description: string | null,
+/**
+ * The description rendered to sanitized HTML, as in the genre files.
+ */
+description_html?: Array<string>,
+/**
+ * The artist's last revision date (ISO 8601).
+ */
+last_revision_date: string,
+/**
+ * The artist's genres, as page IDs.
+ */
+genres: Array<PageDataId>,
+/**
+ * Inbound Wikipedia link count for the artist's page and its redirects.
+ */
+links?: number,
+/**
+ * The Wikidata item ID of the artist's page, if it has one.
+ */
+wikidata_qid?: string, };
+
+/**
+ * Maps link targets to page IDs.
+ */
+export type LinksToPageIds = { [key in string]?: PageDataId };
+
+/**
+ * Maps the alternative names of genres to the genres they name. Unlike [`LinksToPageIds`],
+ * this only has names that are known to be names, not every redirect.
+ */
+export type Aliases = { [key in string]?: Array<AliasTarget> };
+
+/**
+ * A genre that an alternative name refers to.
+ */
+export type AliasTarget = {
+/**
+ * The genre's page ID.
+ */
+id: PageDataId,
+/**
+ * Where the name comes from.
+ */
+source: AliasSource, };
+
+/**
+ * Where an alternative name for a genre comes from. Redirects are only kept as alternative names
+ * when their redirect category says they are one, so misspellings and the like are left out.
+ */
+export type AliasSource = "otherName" | "alternativeName" | "alternativeSpelling" | "alternativeLanguage" | "shortName" | "longName" | "abbreviation" | "formerName" | "normalized";
@@ -0,0 +1,90 @@
+import type {
+  ChunkedMeta,
+  ChunkManifest,
+  ClusterChunk,
+  DataOnDisk,
+  EdgeOnDiskData,
+  NodeOnDiskData,
+  NodeSkeleton,
+} from "../dataTypes";
+
+/** The size of each edge in the binary edge list; see `DataFormat::Chunked` in `datagen`. */
+const EDGE_BYTES = 9;
+
+/**
+ * Fetch the graph data in the chunked format, from its manifest at `path`.
+ *
+ * The metadata, node skeletons and edges are enough to draw the graph, so they're passed to
+ * `onSkeleton` (with placeholders for the rest of each node's data) while the clusters'
+ * chunks are still loading.
+ */
+export async function fetchChunkedData(
+  path: string,
+  onSkeleton: (data: DataOnDisk) => void
+): Promise<DataOnDisk> {
+  const manifest: ChunkManifest = await (await fetch(path)).json();
+
+  const clusters = Promise.all(
+    manifest.clusters.map(
+      async (cluster) =>
+        (await fetch(`/${cluster.path}`)).json() as Promise<ClusterChunk>
+    )
+  );
+  const [meta, skeletons, edges] = await Promise.all([
+    fetch(`/${manifest.meta}`).then((r) => r.json() as Promise<ChunkedMeta>),
+    fetch(`/${manifest.nodes}`).then(
+      (r) => r.json() as Promise<NodeSkeleton[]>
+    ),
+    fetch(`/${manifest.edges}`).then(async (r) =>
+      decodeEdges(await r.arrayBuffer())
+    ),
+  ]);
+  const shared = {
+    wikipedia_domain: meta.wikipedia_domain,
+    wikipedia_db_name: meta.wikipedia_db_name,
+    dump_date: meta.dump_date,
+    max_degree: meta.max_degree,
+    communities: meta.communities,
+    sharded_files: meta.sharded_files,
+  };
+
+  const inDegrees = new Array<number>(skeletons.length).fill(0);
+  const outDegrees = new Array<number>(skeletons.length).fill(0);
+  for (const [source, target] of edges) {
+    outDegrees[source]++;
+    inDegrees[target]++;
+  }
+  const nodes: NodeOnDiskData[] = skeletons.map((skeleton, index) => ({
+    ...skeleton,
+    in_degree: inDegrees[index],
+    out_degree: outDegrees[index],
+    pagerank: 0,
+    betweenness: 0,
+  }));
+  onSkeleton({ ...shared, nodes: [...nodes], edges });
+
+  for (const chunk of await clusters) {
+    for (const [index, id] of chunk.ids.entries()) {
+      nodes[id] = chunk.nodes[index];
+    }
+  }
+  return { ...shared, nodes, edges };
+}
+
+/** Decode the binary edge list into the same tuples as `data.json`. */
+function decodeEdges(buffer: ArrayBuffer): EdgeOnDiskData[] {
+  const view = new DataView(buffer);
+  const edges: EdgeOnDiskData[] = [];
+  for (
+    let offset = 0;
+    offset + EDGE_BYTES <= buffer.byteLength;
+    offset += EDGE_BYTES
+  ) {
+    const source = view.getUint32(offset, true);
+    const target = view.getUint32(offset + 4, true);
+    const flags = view.getUint8(offset + 8);
+    const ty = (flags & 3) as 0 | 1 | 2 | 3;
+    edges.push(flags & 4 ? [source, target, ty, true] : [source, target, ty]);
+  }
+  return edges;
+}
@@ -1,6 +1,7 @@
 import { createContext, useContext, useEffect, useState } from "react";
-import { page_name_to_filename } from "frontend_wasm";
+import { page_name_to_data_file_path } from "frontend_wasm";
 import { ArtistFileData, GenreFileData, LinksToPageIds } from "../data";
+import { fetchDataFile } from "./dataFile";
 
 /**
  * A cache for data.
@@ -10,8 +11,15 @@ export class DataCache {
     new Map();
   private linksToPageIds: LinksToPageIds | Promise<LinksToPageIds> | null =
     null;
+  private paths: Promise<Paths | null> | null = null;
+  private shardedFiles: boolean;
 
-  constructor() {
+  /**
+   * @param shardedFiles Whether the genre and artist files are sharded into
+   * subdirectories (see `sharded_files`).
+   */
+  constructor(shardedFiles = false) {
+    this.shardedFiles = shardedFiles;
     this.cache.set("artists", new Map());
     this.cache.set("genres", new Map());
   }
@@ -35,11 +43,13 @@ export class DataCache {
     }
 
     // No cached value or promise, start a new request
-    const requestPromise = fetchDatum(directory, page).then((result) => {
-      // Replace the promise with the actual result
-      directoryCache.set(page, result);
-      return result;
-    });
+    const requestPromise = this.getPaths()
+      .then((paths) => fetchDatum(directory, page, paths, this.shardedFiles))
+      .then((result) => {
+        // Replace the promise with the actual result
+        directoryCache.set(page, result);
+        return result;
+      });
 
     // Store the promise in the cache
     directoryCache.set(page, requestPromise);
@@ -47,6 +57,21 @@ export class DataCache {
     return (await requestPromise) as T | null;
   }
 
+  /**
+   * Get the map of file paths to their content-hashed equivalents, or null if
+   * datagen didn't hash the file names (see `hashed_filenames`).
+   */
+  private getPaths(): Promise<Paths | null> {
+    if (!this.paths) {
+      this.paths = fetchDataFile("/paths.json")
+        .then((response) =>
+          response ? (response.json() as Promise<Paths>) : null
+        )
+        .catch(() => null);
+    }
+    return this.paths;
+  }
+
   /**
    * Get the links to page IDs.
    * @returns The links to page IDs.
@@ -86,13 +111,18 @@ export class DataCache {
   }
 }
 
+/** A map of file paths to their content-hashed equivalents. */
+type Paths = Record<string, string>;
+
 async function fetchDatum<T>(
   directory: string,
-  page: string
+  page: string,
+  paths: Paths | null,
+  sharded: boolean
 ): Promise<T | null> {
   try {
-    const filename = page_name_to_filename(page);
-    const response = await fetch(`/${directory}/${filename}.json`);
+    const path = page_name_to_data_file_path(directory, page, sharded);
+    const response = await fetch(`/${paths?.[path] ?? path}`);
     if (response.ok) {
       return await response.json();
     } else {
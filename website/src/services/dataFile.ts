@@ -0,0 +1,17 @@
+/**
+ * Fetch one of the files datagen may or may not have written (depending on
+ * its output options), or `null` if it isn't there.
+ *
+ * Dev servers answer missing files with the app's HTML rather than a 404, so
+ * that counts as missing too.
+ */
+export async function fetchDataFile(path: string): Promise<Response | null> {
+  const response = await fetch(path);
+  if (
+    !response.ok ||
+    response.headers.get("content-type")?.includes("text/html")
+  ) {
+    return null;
+  }
+  return response;
+}
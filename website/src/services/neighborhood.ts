@@ -0,0 +1,17 @@
+import type { Neighborhood } from "../dataTypes";
+import { fetchDataFile } from "./dataFile";
+
+/**
+ * Fetch the neighbourhood of the genre with the given ID, if datagen wrote
+ * one (see `neighborhood_hops`).
+ */
+export async function fetchNeighborhood(
+  id: string
+): Promise<Neighborhood | null> {
+  try {
+    const response = await fetchDataFile(`/neighborhood/${id}.json`);
+    return response ? ((await response.json()) as Neighborhood) : null;
+  } catch {
+    return null;
+  }
+}
@@ -2,11 +2,15 @@
  * Genre search service: a thin wrapper around the WASM `GenreSearcher`.
  *
  * The searcher is built once per node array and cached by identity; every
- * query is ranked in Rust with the (overridable) weights below.
+ * query is ranked in Rust with the (overridable) weights below. Until the
+ * prebuilt `search_index.json` (which adds top artists and titles in other
+ * languages) has loaded, it's built from the nodes' labels and aliases alone.
  */
+import { useEffect, useState } from "react";
 import { GenreSearcher, SearchParams, SearchResult } from "frontend_wasm";
 
 import { NodeData } from "../data";
+import { fetchDataFile } from "./dataFile";
 
 /** Re-exported WASM searcher types (ranking weights, search hits). */
 export type { SearchParams, SearchResult };
@@ -18,26 +22,82 @@ export type { SearchParams, SearchResult };
  */
 export const DEFAULT_SEARCH_PARAMS: SearchParams = {};
 
+/** The prebuilt search index, once loaded; `null` if it couldn't be. */
+let searchIndex: unknown[] | null | undefined = undefined;
+let searchIndexRequest: Promise<unknown[] | null> | null = null;
+
+function loadSearchIndex(): Promise<unknown[] | null> {
+  if (!searchIndexRequest) {
+    searchIndexRequest = fetchDataFile("/search_index.json")
+      .then((response) =>
+        response ? (response.json() as Promise<unknown[]>) : null
+      )
+      .catch((error) => {
+        console.error("Failed to fetch search index:", error);
+        return null;
+      })
+      .then((index) => {
+        searchIndex = index;
+        return index;
+      });
+  }
+  return searchIndexRequest;
+}
+
+/**
+ * Start loading the prebuilt search index. Returns whether it has been loaded,
+ * so that results can be recomputed once it's available.
+ */
+export function useSearchIndex(): boolean {
+  const [loaded, setLoaded] = useState(!!searchIndex);
+  useEffect(() => {
+    let cancelled = false;
+    loadSearchIndex().then((index) => {
+      if (!cancelled && index) setLoaded(true);
+    });
+    return () => {
+      cancelled = true;
+    };
+  }, []);
+  return loaded;
+}
+
 /** Cache of the searcher, keyed by the nodes array identity. */
-let searcherCache: { nodes: NodeData[]; searcher: GenreSearcher } | null = null;
+let searcherCache: {
+  nodes: NodeData[];
+  indexed: boolean;
+  searcher: GenreSearcher;
+} | null = null;
 
 function getSearcher(nodes: NodeData[]): GenreSearcher {
-  if (searcherCache && searcherCache.nodes === nodes) {
+  // The index is generated alongside the nodes, so it only applies if it
+  // lines up with them
+  const index = searchIndex?.length === nodes.length ? searchIndex : null;
+  if (
+    searcherCache &&
+    searcherCache.nodes === nodes &&
+    searcherCache.indexed === !!index
+  ) {
     return searcherCache.searcher;
   }
   searcherCache?.searcher.free();
-  const searcher = new GenreSearcher(
-    nodes.map((node) => ({
-      label: node.label,
-      aliases: node.aliases ?? [],
-      links: node.links ?? 0,
-    }))
-  );
-  searcherCache = { nodes, searcher };
+  const searcher = index
+    ? GenreSearcher.from_index(index)
+    : new GenreSearcher(
+        nodes.map((node) => ({
+          label: node.label,
+          aliases: node.aliases ?? [],
+          links: node.links ?? 0,
+        }))
+      );
+  searcherCache = { nodes, indexed: !!index, searcher };
   return searcher;
 }
 
-/** Search genres by name/alias, ranked by match quality and popularity. */
+/**
+ * Search genres by name, alias, top artist, or title in another language,
+ * ranked by match quality and popularity.
+ */
 export function searchGenres(
   nodes: NodeData[],
   query: string,
@@ -18,6 +18,7 @@ export type VisibleTypes = {
   [EdgeType.Derivative]: boolean;
   [EdgeType.Subgenre]: boolean;
   [EdgeType.FusionGenre]: boolean;
+  [EdgeType.Related]: boolean;
 };
 
 /** A description of a visible type. */
@@ -40,6 +41,10 @@ export const subgenreColour = (saturation: number = 70, alpha: number = 1) =>
 export const fusionGenreColour = (saturation: number = 70, alpha: number = 1) =>
   `hsla(252, ${saturation}%, 72%, ${alpha})`;
 
+/** The colour of a related genre — muted ochre, quieter than the others */
+export const relatedColour = (saturation: number = 70, alpha: number = 1) =>
+  `hsla(42, ${saturation * 0.6}%, 60%, ${alpha})`;
+
 /** Descriptions of the visible types in the graph */
 export const VISIBLE_TYPES: VisibleTypeDesc[] = [
   {
@@ -63,6 +68,13 @@ export const VISIBLE_TYPES: VisibleTypeDesc[] = [
     description:
       "Genres that combine elements of this genre with other genres.",
   },
+  {
+    color: relatedColour(),
+    label: "Related",
+    type: EdgeType.Related,
+    description:
+      "Genres listed in the See also section of this genre's article, or whose articles mention each other, with no stronger relationship.",
+  },
 ];
 
 /** Map of visible type names to their descriptions */
@@ -153,6 +165,7 @@ export const DEFAULT_SETTINGS: SettingsData = {
     [EdgeType.Derivative]: true,
     [EdgeType.Subgenre]: true,
     [EdgeType.FusionGenre]: true,
+    [EdgeType.Related]: false,
   },
   general: Object.fromEntries(
     GENERAL_CONTROLS.map((control) => [control.name, control.default])
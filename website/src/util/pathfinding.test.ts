@@ -10,15 +10,22 @@ function makeGraph(
   const nodes: NodeData[] = Array.from({ length: nodeCount }, (_, i) => ({
     id: i.toString(),
     label: `Node ${i}`,
+    popularity: 0,
     x: 0,
     y: 0,
     hue: 0,
+    community: 0,
+    in_degree: 0,
+    out_degree: 0,
+    pagerank: 0,
+    betweenness: 0,
     edges: [],
   }));
   const edges: EdgeData[] = edgeTriples.map(([source, target, ty]) => ({
     source: source.toString(),
     target: target.toString(),
     ty,
+    corroborated: false,
   }));
   for (const [index, [source, target]] of edgeTriples.entries()) {
     nodes[source].edges.push(index);
@@ -31,6 +38,7 @@ const ALL_VISIBLE: VisibleTypes = {
   [EdgeType.Derivative]: true,
   [EdgeType.Subgenre]: true,
   [EdgeType.FusionGenre]: true,
+  [EdgeType.Related]: true,
 };
 
 describe("computePath", () => {
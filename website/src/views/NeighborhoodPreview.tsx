@@ -0,0 +1,70 @@
+import type { Neighborhood } from "../dataTypes";
+import { NodeColourLightness } from "../data";
+import { colourStyles } from "./colours";
+import { textStyles } from "./typography";
+
+/** The space around the outermost nodes, as a fraction of the preview's size. */
+const PADDING = 0.1;
+
+/**
+ * Draws a genre's precomputed neighbourhood while the full graph loads, so
+ * that a link to a genre shows it straight away.
+ */
+export function NeighborhoodPreview({
+  neighborhood,
+}: {
+  neighborhood: Neighborhood;
+}) {
+  const { ids, hops, nodes, edges } = neighborhood;
+  const indices = new Map(ids.map((id, index) => [id, index]));
+
+  const xs = nodes.map((node) => node.x);
+  const ys = nodes.map((node) => node.y);
+  const minX = Math.min(...xs);
+  const minY = Math.min(...ys);
+  const size = Math.max(Math.max(...xs) - minX, Math.max(...ys) - minY, 1);
+  const padding = size * PADDING;
+  const radius = size / 150;
+
+  return (
+    <div className="flex flex-col items-center gap-2">
+      <svg
+        className="w-72 h-72 max-w-[80vw] max-h-[80vw]"
+        viewBox={`${minX - padding} ${minY - padding} ${size + padding * 2} ${
+          size + padding * 2
+        }`}
+      >
+        {edges.map(([source, target], index) => {
+          const a = nodes[indices.get(source)!];
+          const b = nodes[indices.get(target)!];
+          return (
+            <line
+              key={index}
+              x1={a.x}
+              y1={a.y}
+              x2={b.x}
+              y2={b.y}
+              stroke="white"
+              strokeOpacity={0.15}
+              strokeWidth={radius / 4}
+            />
+          );
+        })}
+        {nodes.map((node, index) => (
+          <circle
+            key={ids[index]}
+            cx={node.x}
+            cy={node.y}
+            r={hops[index] === 0 ? radius * 2 : radius}
+            fill={`hsl(${node.hue}, 70%, ${NodeColourLightness.GraphNode}%)`}
+          />
+        ))}
+      </svg>
+      <div
+        className={`font-display ${textStyles.body} ${colourStyles.text.brass}`}
+      >
+        {nodes[0].label}
+      </div>
+    </div>
+  );
+}
@@ -63,6 +63,8 @@ export const colourStyles = {
     brass: textBrass,
     link: "text-[#8fd0e0]",
     linkHover: "text-[#8fd0e0] hover:text-[#b8e4ef]",
+    /** For links within pre-rendered HTML, which can't be given classes. */
+    nestedLinks: "[&_a]:text-[#8fd0e0] [&_a:hover]:underline",
     accentLink: "text-[#d9c08a]",
     toggle: "text-[#a6b2c9] hover:text-[#e9e3d3]",
     meta: "text-[#9ba6bc]",
@@ -188,6 +190,7 @@ export const colourStyles = {
     background: "bg-black",
     loadingText: "text-[#8893aa]",
     spinner: "border-[#2a3a5c] border-t-[#c9a86a]",
+    playButton: "bg-black/70 text-white group-hover:bg-[#ff0033]",
   },
 
   // Genre link
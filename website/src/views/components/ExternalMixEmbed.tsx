@@ -0,0 +1,74 @@
+import { MixProvider } from "../../data";
+import { colourStyles } from "../colours";
+import { textStyles } from "../typography";
+
+const providerNames: Record<MixProvider, string> = {
+  bandcamp: "Bandcamp",
+  soundcloud: "SoundCloud",
+  mixcloud: "Mixcloud",
+};
+
+/** The player for a mix that isn't on YouTube, or a link to it where the provider's player
+ * can't be embedded from its URL alone (Bandcamp's needs the album's ID) */
+export function ExternalMixEmbed({
+  provider,
+  url,
+  title,
+  autoplay = false,
+}: {
+  provider: MixProvider;
+  url: string;
+  title?: string;
+  autoplay?: boolean;
+}) {
+  const embed = embedUrl(provider, url, autoplay);
+  if (!embed) {
+    return (
+      <a
+        href={url}
+        target="_blank"
+        rel="noopener noreferrer"
+        className={`block p-2 ${textStyles.body} ${colourStyles.text.linkHover}`}
+      >
+        {title ? `${title} on ` : "Listen on "}
+        {providerNames[provider]}
+      </a>
+    );
+  }
+
+  return (
+    <iframe
+      className="w-full"
+      style={{ height: provider === "mixcloud" ? 120 : 166 }}
+      src={embed.toString()}
+      title={`${providerNames[provider]} player`}
+      allow="autoplay"
+    />
+  );
+}
+
+function embedUrl(
+  provider: MixProvider,
+  url: string,
+  autoplay: boolean,
+): URL | undefined {
+  switch (provider) {
+    case "soundcloud": {
+      const embed = new URL("https://w.soundcloud.com/player/");
+      embed.searchParams.set("url", url);
+      embed.searchParams.set("auto_play", String(autoplay));
+      return embed;
+    }
+    case "mixcloud": {
+      const embed = new URL("https://player-widget.mixcloud.com/widget/iframe/");
+      embed.searchParams.set("hide_cover", "1");
+      embed.searchParams.set("feed", `${new URL(url).pathname}/`);
+      if (autoplay) {
+        embed.searchParams.set("autoplay", "1");
+      }
+      return embed;
+    }
+    case "bandcamp":
+      return undefined;
+  }
+}
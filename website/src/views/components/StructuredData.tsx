@@ -0,0 +1,54 @@
+import {
+  NodeData,
+  nodeIdToInt,
+  nodePageTitle,
+  useDataContext,
+} from "../../data";
+import { wikiPageUrl, wikiUrl } from "./wikipedia/urls";
+
+const WEBSITE_URL = "https://genresin.space/";
+
+/**
+ * Embeds a schema.org description of a genre for search engines, matching the genre's entry in
+ * `genres.jsonld`.
+ */
+export function GenreStructuredData({
+  node,
+  nodeId,
+}: {
+  node: NodeData;
+  nodeId: string;
+}) {
+  const { wikipedia_domain: domain, wikipedia_db_name: dbName } =
+    useDataContext();
+
+  const pageTitle = nodePageTitle(node);
+  const sameAs = [wikiPageUrl(wikiUrl(domain), pageTitle)];
+  // Sections of a page don't have their own Wikidata item
+  if (!pageTitle.includes("#")) {
+    sameAs.push(
+      `https://www.wikidata.org/wiki/Special:ItemByTitle/${dbName}/${pageTitle.replace(/ /g, "_")}`
+    );
+  }
+
+  const structuredData = {
+    "@context": "https://schema.org",
+    "@type": "DefinedTerm",
+    "@id": `${WEBSITE_URL}#${nodeIdToInt(nodeId)}`,
+    additionalType: "https://www.wikidata.org/wiki/Q188451",
+    inDefinedTermSet: WEBSITE_URL,
+    name: node.label,
+    sameAs,
+    ...(node.aliases?.length ? { alternateName: node.aliases } : {}),
+  };
+
+  return (
+    <script
+      type="application/ld+json"
+      dangerouslySetInnerHTML={{
+        // Escape `<` so that a label can't close the script element
+        __html: JSON.stringify(structuredData).replace(/</g, "\\u003c"),
+      }}
+    />
+  );
+}
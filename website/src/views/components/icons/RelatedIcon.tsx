@@ -0,0 +1,35 @@
+import React from "react";
+import { StrokeIconProps } from "./IconProps";
+
+/**
+ * Related icon - chain link
+ *
+ * Used to represent looser "see also" relationships between genres.
+ */
+export const RelatedIcon: React.FC<StrokeIconProps> = ({
+  width = 18,
+  height = 18,
+  className = "",
+  stroke = "currentColor",
+  style = {},
+}) => {
+  return (
+    <svg
+      xmlns="http://www.w3.org/2000/svg"
+      width={width}
+      height={height}
+      fill="none"
+      viewBox="0 0 24 24"
+      stroke={stroke}
+      className={className}
+      style={style}
+    >
+      <path
+        strokeLinecap="round"
+        strokeLinejoin="round"
+        strokeWidth={2}
+        d="M13.828 10.172a4 4 0 00-5.656 0l-4 4a4 4 0 105.656 5.656l1.102-1.101m-.758-4.899a4 4 0 005.656 0l4-4a4 4 0 00-5.656-5.656l-1.1 1.1"
+      />
+    </svg>
+  );
+};
@@ -15,6 +15,7 @@ export * from "./EyeIcon";
 export * from "./DerivativeIcon";
 export * from "./SubgenreIcon";
 export * from "./FusionGenreIcon";
+export * from "./RelatedIcon";
 export * from "./ResizeHandleIcon";
 export * from "./SwapIcon";
 export * from "./NoteIcon";
@@ -0,0 +1,49 @@
+import { useEffect, useState } from "react";
+import { colourStyles } from "../../colours";
+import { textStyles } from "../../typography";
+
+/**
+ * Renders a description that `datagen` rendered to sanitized HTML ahead of time
+ * (`description_html`), one paragraph at a time like `WikitextTruncateAtNewline`,
+ * without parsing its wikitext.
+ */
+export function DescriptionHtml(props: {
+  paragraphs: string[];
+  expandable: boolean;
+  className?: string;
+}) {
+  const [expanded, setExpanded] = useState(false);
+
+  useEffect(() => {
+    setExpanded(false);
+  }, [props.paragraphs]);
+
+  const shown =
+    props.expandable && expanded
+      ? props.paragraphs
+      : props.paragraphs.slice(0, 1);
+  const content = (
+    <div className={`flex flex-col gap-2 ${colourStyles.text.nestedLinks}`}>
+      {shown.map((paragraph, i) => (
+        // The HTML only has formatting, links and lists, with its text escaped
+        <div key={i} dangerouslySetInnerHTML={{ __html: paragraph }} />
+      ))}
+    </div>
+  );
+
+  return props.expandable ? (
+    <div className={`flex flex-col gap-2 ${props.className || ""}`}>
+      {content}
+      {props.paragraphs.length > 1 && (
+        <button
+          onClick={() => setExpanded(!expanded)}
+          className={`w-full p-2 ${textStyles.body} ${colourStyles.text.toggle} ${colourStyles.bg.interactive} ${colourStyles.hover.medium} mx-auto block transition-colors`}
+        >
+          {expanded ? "Show less" : "Show more"}
+        </button>
+      )}
+    </div>
+  ) : (
+    content
+  );
+}
@@ -1,6 +1,6 @@
 import { WikipediaLink } from "./WikipediaLink";
 import { GenreLink } from "../../links/GenreLink";
-import { useDataContext } from "../../../../data";
+import { linkKey, useDataContext } from "../../../../data";
 import { useLinksToPageIds } from "../../../../services/dataCache";
 
 /**
@@ -19,7 +19,7 @@ export function WikipediaMaybeGenreLink({
   const linksToPageIds = useLinksToPageIds();
 
   const nodeId = linksToPageIds
-    ? linksToPageIds[pageTitle.toLowerCase()]
+    ? linksToPageIds[linkKey(pageTitle)]
     : null;
   const node = nodeId ? nodes[nodeId] : null;
 
@@ -13,6 +13,7 @@ import {
 import {
   derivativeColour,
   fusionGenreColour,
+  relatedColour,
   subgenreColour,
   type VisibleTypes,
 } from "../../settings";
@@ -353,7 +354,9 @@ export function computeEdgeColors(
             ? derivativeColour(saturation, alpha)
             : edge.ty === EdgeType.Subgenre
               ? subgenreColour(saturation, alpha)
-              : fusionGenreColour(saturation, alpha)
+              : edge.ty === EdgeType.FusionGenre
+                ? fusionGenreColour(saturation, alpha)
+                : relatedColour(saturation, alpha)
         );
 
       const isHoveredEdge =
@@ -650,7 +653,9 @@ export function computeArrowGeometry(
         ? derivativeColour(EDGE_SELECTED_SATURATION, hoverAlpha)
         : edge.ty === EdgeType.Subgenre
           ? subgenreColour(EDGE_SELECTED_SATURATION, hoverAlpha)
-          : fusionGenreColour(EDGE_SELECTED_SATURATION, hoverAlpha)
+          : edge.ty === EdgeType.FusionGenre
+            ? fusionGenreColour(EDGE_SELECTED_SATURATION, hoverAlpha)
+            : relatedColour(EDGE_SELECTED_SATURATION, hoverAlpha)
     );
     hoverColors[hi * 4] = color[0];
     hoverColors[hi * 4 + 1] = color[1];
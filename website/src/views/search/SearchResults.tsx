@@ -129,7 +129,7 @@ function ResultRow({
         <small
           className={`block italic ${textStyles.small} ${colourStyles.text.secondary}`}
         >
-          also known as{" "}
+          {result.kind === "artist" ? "top artist:" : "also known as"}{" "}
           <HighlightedText text={result.matchedText} spans={result.spans} />
         </small>
       )}
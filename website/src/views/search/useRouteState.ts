@@ -3,7 +3,11 @@ import { Dispatch, useMemo, useReducer } from "react";
 import { Data, NodeData, nodeIdToInt } from "../../data";
 import { VisibleTypes } from "../../settings";
 import { computePath } from "../../util/pathfinding";
-import { searchGenres, SearchResult } from "../../services/search";
+import {
+  searchGenres,
+  SearchResult,
+  useSearchIndex,
+} from "../../services/search";
 import type { SearchMode } from "../graph/GraphViewLabels";
 
 /** One endpoint of the route: a resolved node (if any) and the input text. */
@@ -183,13 +187,15 @@ export function useRouteSearch(
     [sourceId, destinationId, path]
   );
 
+  // Results are recomputed once the prebuilt index has loaded, as it matches more names
+  const searchIndexLoaded = useSearchIndex();
   const results = useMemo(() => {
     const slot = route.activeSlot ? route[route.activeSlot] : null;
     if (!slot) return [];
     // Don't re-offer the node the slot is already resolved to
     if (slot.id && slot.query === nodes[nodeIdToInt(slot.id)].label) return [];
     return searchGenres(nodes, slot.query);
-  }, [route, nodes]);
+  }, [route, nodes, searchIndexLoaded]);
 
   const searchMode: SearchMode = destinationId
     ? "path"
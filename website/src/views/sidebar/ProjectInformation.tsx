@@ -217,7 +217,9 @@ function CommitFooter() {
         <EL href="https://creativecommons.org/licenses/by-sa/4.0/">
           CC BY-SA 4.0
         </EL>
-        . Descriptions may be truncated from the original articles.
+        . Descriptions may be truncated from the original articles; see the{" "}
+        <EL href="/attribution.html">attribution</EL> for the revision of each
+        article they were taken from.
       </p>
       <p>
         Commit{" "}
@@ -8,23 +8,30 @@ import {
   useNodeColourLightness,
   useDataContext,
   GenreFileData,
+  GenreImage,
+  GenreMix,
+  MixEmbed,
   nodePageTitle,
   EdgeType,
 } from "../../data";
 import {
   derivativeColour,
   fusionGenreColour,
+  relatedColour,
   subgenreColour,
 } from "../../settings";
 import { stripGenreNamePrefixFromDescription } from "../../util/stripGenreNamePrefixFromDescription";
 
 import { YouTubeEmbed } from "../components/YouTubeEmbed";
+import { ExternalMixEmbed } from "../components/ExternalMixEmbed";
 import { Notice } from "../components/Notice";
 
 import { GenreLink } from "../components/links/GenreLink";
 import { DisableTooltips } from "../components/Tooltip";
+import { GenreStructuredData } from "../components/StructuredData";
 
 import { WikipediaLink } from "../components/wikipedia/links/WikipediaLink";
+import { DescriptionHtml } from "../components/wikipedia/DescriptionHtml";
 import { Wikitext } from "../components/wikipedia/wikitexts/Wikitext";
 import { WikitextTruncateAtNewline } from "../components/wikipedia/wikitexts/WikitextTruncateAtNewline";
 import { Collapsible } from "../components/Collapsible";
@@ -35,7 +42,9 @@ import {
   DerivativeIcon,
   SubgenreIcon,
   FusionGenreIcon,
+  RelatedIcon,
   NoteIcon,
+  PlayIcon,
 } from "../components/icons";
 import yt_icon_red_digital from "../components/icons/yt_icon_red_digital.png";
 
@@ -70,6 +79,7 @@ export function SelectedNodeInfo({
 
   return (
     <div className="flex flex-col gap-2">
+      {selectedId && <GenreStructuredData node={node} nodeId={selectedId} />}
       <div className="flex flex-col">
         <GenreHeader node={node} maxDegree={maxDegree} />
         {genreData && shouldShowMixes && (
@@ -78,6 +88,7 @@ export function SelectedNodeInfo({
             shouldAutoplayMixes={shouldAutoplayMixes}
           />
         )}
+        {genreData?.image && <GenreImageFigure image={genreData.image} />}
         {genreData && <GenreDescription node={node} genreData={genreData} />}
       </div>
 
@@ -170,6 +181,19 @@ function GenreDescription({
     );
   }
 
+  if (genreData.description_html) {
+    return (
+      <div
+        className={`px-4 py-3 ${textStyles.body} leading-[1.7] ${colourStyles.bg.card}`}
+      >
+        <DescriptionHtml
+          paragraphs={genreData.description_html}
+          expandable={true}
+        />
+      </div>
+    );
+  }
+
   const stripped = stripGenreNamePrefixFromDescription(node.label, description);
   const prefixDetected = stripped !== description;
 
@@ -197,6 +221,28 @@ function GenreDescription({
   );
 }
 
+/** The genre's infobox image, linking to its page for its author and license */
+function GenreImageFigure({ image }: { image: GenreImage }) {
+  return (
+    <a
+      href={image.description_url}
+      target="_blank"
+      rel="noopener noreferrer"
+      title="Image details and license"
+      className={`flex justify-center ${colourStyles.bg.card}`}
+    >
+      <img
+        src={`/${image.path}`}
+        width={image.width}
+        height={image.height}
+        alt=""
+        loading="lazy"
+        className="max-w-full h-auto"
+      />
+    </a>
+  );
+}
+
 /** Featured mix section */
 function FeaturedMix({
   genreData,
@@ -218,6 +264,13 @@ function FeaturedMix({
           <MixItem
             key={JSON.stringify(mix)}
             mix={mix}
+            embed={
+              "provider" in mix
+                ? undefined
+                : genreData.mix_embeds?.[
+                    "video" in mix ? mix.video : mix.playlist
+                  ]
+            }
             autoplay={shouldAutoplayMixes}
           />
         ))}
@@ -234,21 +287,38 @@ function FeaturedMix({
   );
 }
 
-/** Individual mix item with video or playlist */
+/** Individual mix item with video, playlist, or mix on another provider */
 function MixItem({
   mix,
+  embed,
   autoplay,
 }: {
-  mix: { playlist: string; note?: string } | { video: string; note?: string };
+  mix: GenreMix;
+  embed: MixEmbed | undefined;
   autoplay: boolean;
 }) {
+  // A mix with known details is shown as a card until it's played, so that YouTube
+  // isn't loaded for genres that are only being browsed
+  const [playing, setPlaying] = useState(autoplay || !embed);
+  const autoplayEmbed = autoplay || embed !== undefined;
+
   return (
     <div className={`${colourStyles.bg.card} overflow-hidden`}>
-      {"video" in mix ? (
-        <YouTubeEmbed videoId={mix.video} autoplay={autoplay} />
+      {!playing && embed ? (
+        <MixCard embed={embed} onPlay={() => setPlaying(true)} />
+      ) : "provider" in mix ? (
+        <ExternalMixEmbed
+          provider={mix.provider}
+          url={mix.url}
+          title={mix.title}
+          autoplay={autoplay}
+        />
+      ) : "video" in mix ? (
+        <YouTubeEmbed videoId={mix.video} autoplay={autoplayEmbed} />
       ) : (
-        <YouTubeEmbed playlistId={mix.playlist} autoplay={autoplay} />
+        <YouTubeEmbed playlistId={mix.playlist} autoplay={autoplayEmbed} />
       )}
+      <MixDetailsLine mix={mix} showTitle={!embed} />
       {mix.note && (
         <Notice colour="blue">
           <Wikitext wikitext={mix.note} />
@@ -258,6 +328,98 @@ function MixItem({
   );
 }
 
+/** What the mixes file says about a mix: its title (if there's no card to show it), length, and
+ * when and by whom it was added */
+function MixDetailsLine({
+  mix,
+  showTitle,
+}: {
+  mix: GenreMix;
+  showTitle: boolean;
+}) {
+  const facts = [
+    mix.duration !== undefined && formatMixDuration(mix.duration),
+    mix.added &&
+      `added ${mix.added}${mix.submitter ? ` by ${mix.submitter}` : ""}`,
+  ].filter(Boolean);
+  const title = showTitle ? mix.title : undefined;
+  if (!title && facts.length === 0) {
+    return null;
+  }
+
+  return (
+    <div className="flex flex-col px-2 pb-2 first:pt-2">
+      {title && (
+        <span className={`${textStyles.body} ${colourStyles.text.primary}`}>
+          {title}
+        </span>
+      )}
+      {facts.length > 0 && (
+        <span className={`${textStyles.small} ${colourStyles.text.meta}`}>
+          {facts.join(" · ")}
+        </span>
+      )}
+    </div>
+  );
+}
+
+/** A length in seconds as a clock time (`1:02:03`) */
+function formatMixDuration(seconds: number): string {
+  const hours = Math.floor(seconds / 3600);
+  const minutes = Math.floor(seconds / 60) % 60;
+  const secs = String(seconds % 60).padStart(2, "0");
+  return hours > 0
+    ? `${hours}:${String(minutes).padStart(2, "0")}:${secs}`
+    : `${minutes}:${secs}`;
+}
+
+/** A mix's thumbnail, title and channel, which plays the mix when clicked */
+function MixCard({
+  embed,
+  onPlay,
+}: {
+  embed: MixEmbed;
+  onPlay: () => void;
+}) {
+  return (
+    <div className="flex flex-col">
+      <button
+        onClick={onPlay}
+        title={`Play ${embed.title}`}
+        className={`group relative w-full aspect-video ${colourStyles.youtube.background}`}
+      >
+        <img
+          src={embed.thumbnail_url}
+          alt=""
+          loading="lazy"
+          referrerPolicy="no-referrer"
+          className="absolute inset-0 w-full h-full object-cover"
+        />
+        <div className="absolute inset-0 flex items-center justify-center">
+          <div
+            className={`p-3 rounded-full transition-colors ${colourStyles.youtube.playButton}`}
+          >
+            <PlayIcon width={28} height={28} />
+          </div>
+        </div>
+      </button>
+      <div className="flex flex-col p-2">
+        <span className={`${textStyles.body} ${colourStyles.text.primary}`}>
+          {embed.title}
+        </span>
+        <a
+          href={embed.channel_url}
+          target="_blank"
+          rel="noopener noreferrer"
+          className={`${textStyles.small} ${colourStyles.text.linkHover}`}
+        >
+          {embed.channel}
+        </a>
+      </div>
+    </div>
+  );
+}
+
 function HelpNeededForMix({ reason }: { reason: string | null }) {
   return (
     <Notice colour="blue">
@@ -376,6 +538,19 @@ function Connections({
           { type: "text", content: ":" },
         ],
       },
+      {
+        type: EdgeType.Related,
+        inbound: [
+          { type: "text", content: "Listed as " },
+          { type: "emphasis", content: "related" },
+          { type: "text", content: " by:" },
+        ],
+        outbound: [
+          { type: "text", content: "Is " },
+          { type: "emphasis", content: "related" },
+          { type: "text", content: " to:" },
+        ],
+      },
     ],
     []
   );
@@ -548,6 +723,14 @@ function ConnectionHeading({
             style={{ color: fusionGenreColour() }}
           />
         );
+      case EdgeType.Related:
+        return (
+          <RelatedIcon
+            width={16}
+            height={16}
+            style={{ color: relatedColour() }}
+          />
+        );
     }
   };
 
@@ -566,7 +749,9 @@ function ConnectionHeading({
                     ? derivativeColour()
                     : type === EdgeType.Subgenre
                       ? subgenreColour()
-                      : fusionGenreColour(),
+                      : type === EdgeType.FusionGenre
+                        ? fusionGenreColour()
+                        : relatedColour(),
               }}
             >
               {part.content}
@@ -664,7 +849,14 @@ function Artist({
           <div
             className={`${textStyles.body} leading-normal ${colourStyles.text.secondary}`}
           >
-            {artistData?.description ? (
+            {artistData?.description_html ? (
+              <div className="line-clamp-3">
+                <DescriptionHtml
+                  paragraphs={artistData.description_html}
+                  expandable={false}
+                />
+              </div>
+            ) : artistData?.description ? (
               <WikitextTruncateAtLength
                 wikitext={artistData.description}
                 length={200}
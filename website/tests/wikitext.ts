@@ -16,7 +16,7 @@ import { initWasm } from "../src/views/components/wikipedia";
 import { wikiPageUrl, wikiUrl } from "../src/views/components/wikipedia/urls";
 import { Wikitext } from "../src/views/components/wikipedia/wikitexts/Wikitext";
 import { MissingTemplateError } from "../src/views/components/wikipedia/templates/WikitextTemplate";
-import { page_name_to_filename } from "frontend_wasm";
+import { page_name_to_data_file_path } from "frontend_wasm";
 import { DataCache, DataCacheContext } from "../src/services/dataCache";
 
 // Get the directory path of the current file
@@ -97,17 +97,26 @@ function renderWikitext(
 
 try {
   // Test genre descriptions
-  const genresDir = join(__dirname, "../public/genres");
+  const publicDir = join(__dirname, "../public");
+  // Present if datagen added content hashes to the file names
+  const paths: Record<string, string> = await readFile(
+    join(publicDir, "paths.json"),
+    "utf-8"
+  )
+    .then((paths) => JSON.parse(paths))
+    .catch(() => ({}));
   for (const genre of Object.values(data.nodes)) {
     const pageTitle = nodePageTitle(genre);
     if (filterPageTitle && pageTitle !== filterPageTitle) {
       continue;
     }
 
-    const genrePath = join(
-      genresDir,
-      page_name_to_filename(pageTitle) + ".json"
+    const genreFile = page_name_to_data_file_path(
+      "genres",
+      pageTitle,
+      (data as Data).sharded_files ?? false
     );
+    const genrePath = join(publicDir, paths[genreFile] ?? genreFile);
     const genreData: GenreFileData = JSON.parse(
       await readFile(genrePath, "utf-8")
     );
@@ -119,7 +128,8 @@ try {
   // Test artist descriptions
   const artistsDir = join(__dirname, "../public/artists");
   try {
-    const artistFiles = await readdir(artistsDir);
+    // Sharded artist files are in subdirectories
+    const artistFiles = await readdir(artistsDir, { recursive: true });
 
     for (const artistFile of artistFiles) {
       if (!artistFile.endsWith(".json")) continue;